@@ -0,0 +1,287 @@
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Args, Subcommand};
+use reqwest::{Method, RequestBuilder};
+use rpassword::prompt_password;
+use shared::models::{
+    AdminPasswordResetRequest, AdminPasswordResetResponse, CreateUserAccountRequest, UserAccount,
+    UserAccountPage, UserAccountQuery, UserRole,
+};
+use uuid::Uuid;
+
+use super::{chat::client_with_session, session};
+
+#[derive(Args, Debug)]
+#[command(about = "Administer the server (requires the admin role)")]
+pub struct AdminArgs {
+    #[command(subcommand)]
+    pub command: AdminCommand,
+
+    /// `RustyGPT` server base URL (default: <http://localhost:8080>)
+    #[arg(long, global = true, default_value = "http://localhost:8080")]
+    pub server: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AdminCommand {
+    /// List, create and manage user accounts
+    Users {
+        #[command(subcommand)]
+        command: UsersCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum UsersCommand {
+    /// List accounts, newest first
+    List {
+        /// Substring of the username, email or display name
+        #[arg(long)]
+        search: Option<String>,
+        /// Only holders of this role (admin, member or `read_only`)
+        #[arg(long, value_parser = parse_role)]
+        role: Option<UserRole>,
+        /// Only disabled accounts
+        #[arg(long, conflicts_with = "active")]
+        disabled: bool,
+        /// Only enabled accounts
+        #[arg(long)]
+        active: bool,
+        #[arg(long, default_value_t = 50)]
+        limit: u32,
+        #[arg(long, default_value_t = 0)]
+        offset: u32,
+    },
+    /// Show one account
+    Show { id: Uuid },
+    /// Create an account; without --password the user is emailed a link to choose one
+    Create {
+        #[arg(long)]
+        username: String,
+        #[arg(long)]
+        email: String,
+        #[arg(long)]
+        display_name: Option<String>,
+        /// Prompt for an initial password
+        #[arg(long)]
+        password: bool,
+        /// Global role to grant; repeat for several (default: member)
+        #[arg(long = "role", value_parser = parse_role)]
+        roles: Vec<UserRole>,
+    },
+    /// Disable an account and sign it out everywhere
+    Disable { id: Uuid },
+    /// Re-enable a disabled account
+    Enable { id: Uuid },
+    /// Grant a global role
+    Grant {
+        id: Uuid,
+        #[arg(value_parser = parse_role)]
+        role: UserRole,
+    },
+    /// Revoke a global role
+    Revoke {
+        id: Uuid,
+        #[arg(value_parser = parse_role)]
+        role: UserRole,
+    },
+    /// Email a password reset link, or set a new password with --set
+    ResetPassword {
+        id: Uuid,
+        /// Prompt for the new password instead of emailing a link
+        #[arg(long)]
+        set: bool,
+    },
+    /// Lift a failed-login lockout
+    Unlock { id: Uuid },
+}
+
+fn parse_role(value: &str) -> Result<UserRole, String> {
+    value.parse().map_err(|err: &str| err.to_string())
+}
+
+fn prompt_new_password() -> Result<String> {
+    let password = prompt_password("New password: ")?;
+    if password.is_empty() {
+        bail!("password must not be empty");
+    }
+    if prompt_password("Repeat password: ")? != password {
+        bail!("passwords do not match");
+    }
+    Ok(password)
+}
+
+fn print_user(user: &UserAccount) {
+    let roles: Vec<&str> = user.roles.iter().copied().map(UserRole::as_str).collect();
+    let mut flags = Vec::new();
+    if user.disabled_at.is_some() {
+        flags.push("disabled");
+    }
+    if user.locked_until.is_some() {
+        flags.push("locked");
+    }
+    if user.mfa_enabled {
+        flags.push("2fa");
+    }
+    if user.email_verified_at.is_none() {
+        flags.push("unverified");
+    }
+    println!(
+        "{}  {}  {}  [{}]  {}",
+        user.id,
+        user.username,
+        user.email,
+        roles.join(","),
+        flags.join(" ")
+    );
+}
+
+pub async fn handle_admin(args: AdminArgs) -> Result<()> {
+    match args.command {
+        AdminCommand::Users { command } => handle_users(&args.server, command).await,
+    }
+}
+
+#[allow(clippy::too_many_lines)] // Tracking: cli-admin-users-refactor
+async fn handle_users(server: &str, command: UsersCommand) -> Result<()> {
+    let (client, jar, server_url) = client_with_session(server)?;
+    let api_base = server_url
+        .join("api/admin/")
+        .context("invalid API base for admin")?;
+    let with_csrf = |request: RequestBuilder| match session::csrf_token_from_jar(&jar, &server_url)
+    {
+        Some(csrf) => request.header("X-CSRF-Token", csrf),
+        None => request,
+    };
+    let user_url = |id: Uuid, suffix: &str| api_base.join(&format!("users/{id}{suffix}"));
+
+    match command {
+        UsersCommand::List {
+            search,
+            role,
+            disabled,
+            active,
+            limit,
+            offset,
+        } => {
+            let query = UserAccountQuery {
+                search,
+                role,
+                disabled: (disabled || active).then_some(disabled),
+                limit: Some(limit),
+                offset: Some(offset),
+            };
+            let response = client
+                .get(api_base.join("users")?)
+                .query(&query)
+                .send()
+                .await
+                .context("request failed")?;
+            let page: UserAccountPage = ensure_success(response, "list").await?.json().await?;
+            for user in &page.users {
+                print_user(user);
+            }
+            eprintln!("{} of {} account(s)", page.users.len(), page.total);
+        }
+        UsersCommand::Show { id } => {
+            let response = client
+                .get(user_url(id, "")?)
+                .send()
+                .await
+                .context("request failed")?;
+            let user: UserAccount = ensure_success(response, "show").await?.json().await?;
+            println!("{}", serde_json::to_string_pretty(&user)?);
+        }
+        UsersCommand::Create {
+            username,
+            email,
+            display_name,
+            password,
+            roles,
+        } => {
+            let payload = CreateUserAccountRequest {
+                username,
+                email,
+                display_name,
+                password: if password {
+                    Some(prompt_new_password()?)
+                } else {
+                    None
+                },
+                roles,
+            };
+            let request = client
+                .request(Method::POST, api_base.join("users")?)
+                .json(&payload);
+            let response = with_csrf(request).send().await.context("request failed")?;
+            let user: UserAccount = ensure_success(response, "create").await?.json().await?;
+            print_user(&user);
+            if payload.password.is_none() {
+                println!("Sent a password setup link to {}", user.email);
+            }
+        }
+        UsersCommand::Disable { id } | UsersCommand::Enable { id } => {
+            let action = if matches!(command, UsersCommand::Disable { .. }) {
+                "disable"
+            } else {
+                "enable"
+            };
+            let request = client.request(Method::POST, user_url(id, &format!("/{action}"))?);
+            let response = with_csrf(request).send().await.context("request failed")?;
+            let user: UserAccount = ensure_success(response, action).await?.json().await?;
+            print_user(&user);
+        }
+        UsersCommand::Grant { id, role } | UsersCommand::Revoke { id, role } => {
+            let (method, action) = if matches!(command, UsersCommand::Grant { .. }) {
+                (Method::PUT, "grant")
+            } else {
+                (Method::DELETE, "revoke")
+            };
+            let request = client.request(method, user_url(id, &format!("/roles/{role}"))?);
+            let response = with_csrf(request).send().await.context("request failed")?;
+            let user: UserAccount = ensure_success(response, action).await?.json().await?;
+            print_user(&user);
+        }
+        UsersCommand::ResetPassword { id, set } => {
+            let payload = AdminPasswordResetRequest {
+                password: if set {
+                    Some(prompt_new_password()?)
+                } else {
+                    None
+                },
+            };
+            let request = client
+                .request(Method::POST, user_url(id, "/password")?)
+                .json(&payload);
+            let response = with_csrf(request).send().await.context("request failed")?;
+            let result: AdminPasswordResetResponse = ensure_success(response, "password reset")
+                .await?
+                .json()
+                .await?;
+            if result.email_sent {
+                println!("Sent a password reset link");
+            } else {
+                println!(
+                    "Password updated; signed out {} session(s)",
+                    result.revoked_sessions
+                );
+            }
+        }
+        UsersCommand::Unlock { id } => {
+            let request = client.request(Method::POST, user_url(id, "/unlock")?);
+            let response = with_csrf(request).send().await.context("request failed")?;
+            ensure_success(response, "unlock").await?;
+            println!("Unlocked account {id}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn ensure_success(response: reqwest::Response, action: &str) -> Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Err(anyhow!("user {action} failed with {status}: {body}"))
+}
@@ -0,0 +1,129 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result, anyhow};
+use clap::Args;
+use shared::models::{AuditEvent, AuditEventPage};
+use uuid::Uuid;
+
+use super::chat::client_with_session;
+
+/// Events fetched per request while paging through the log.
+const PAGE_SIZE: u32 = 500;
+
+#[derive(Args, Debug)]
+#[command(about = "Export the audit log as JSON lines or CSV (requires the admin role)")]
+pub struct AuditArgs {
+    /// Only events performed by this user
+    #[arg(long)]
+    pub actor: Option<Uuid>,
+
+    /// Exact action or dotted prefix, e.g. `membership` or `auth.login_failed`
+    #[arg(long)]
+    pub action: Option<String>,
+
+    /// Only events about this kind of target, e.g. `conversation` or `session`
+    #[arg(long)]
+    pub target_type: Option<String>,
+
+    /// Only events about this target id
+    #[arg(long)]
+    pub target_id: Option<String>,
+
+    /// Earliest event time to include (RFC 3339, e.g. 2026-03-01T00:00:00Z)
+    #[arg(long)]
+    pub from: Option<String>,
+
+    /// Only events before this time (RFC 3339)
+    #[arg(long)]
+    pub to: Option<String>,
+
+    /// Write CSV instead of JSON lines
+    #[arg(long)]
+    pub csv: bool,
+
+    /// Write to this file instead of stdout
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// `RustyGPT` server base URL (default: <http://localhost:8080>)
+    #[arg(long, default_value = "http://localhost:8080")]
+    pub server: String,
+}
+
+pub async fn handle_audit(args: AuditArgs) -> Result<()> {
+    let (client, _jar, server_url) = client_with_session(&args.server)?;
+    let base = server_url
+        .join("api/admin/audit")
+        .context("invalid API base for audit")?;
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("failed to create {}", path.display())
+            })?))
+        }
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    if args.csv {
+        writeln!(out, "{}", AuditEvent::CSV_HEADER)?;
+    }
+
+    let mut before: Option<i64> = None;
+    let mut exported = 0usize;
+    loop {
+        let mut url = base.clone();
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("limit", &PAGE_SIZE.to_string());
+            if let Some(actor) = args.actor {
+                query.append_pair("actor_id", &actor.to_string());
+            }
+            for (key, value) in [
+                ("action", &args.action),
+                ("target_type", &args.target_type),
+                ("target_id", &args.target_id),
+                ("from", &args.from),
+                ("to", &args.to),
+            ] {
+                if let Some(value) = value.as_deref() {
+                    query.append_pair(key, value);
+                }
+            }
+            if let Some(cursor) = before {
+                query.append_pair("before", &cursor.to_string());
+            }
+        }
+
+        let response = client.get(url).send().await.context("request failed")?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("audit export failed with {status}: {body}"));
+        }
+        let page: AuditEventPage = response.json().await.context("invalid audit page")?;
+
+        for event in &page.events {
+            if args.csv {
+                writeln!(out, "{}", event.to_csv_row())?;
+            } else {
+                writeln!(out, "{}", serde_json::to_string(event)?)?;
+            }
+        }
+        exported += page.events.len();
+
+        match page.next_before {
+            Some(cursor) => before = Some(cursor),
+            None => break,
+        }
+    }
+    out.flush()?;
+
+    if let Some(path) = &args.output {
+        eprintln!("Exported {exported} audit event(s) to {}", path.display());
+    }
+    Ok(())
+}
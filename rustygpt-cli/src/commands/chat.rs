@@ -6,8 +6,8 @@ use futures_util::StreamExt;
 use reqwest::{Client, cookie::Jar};
 use serde_json::from_str;
 use shared::models::{
-    ConversationStreamEvent, MembershipChangeAction, MessageRole, ReplyMessageRequest,
-    ThreadListResponse, ThreadTreeResponse, UnreadSummaryResponse,
+    ConversationLifecycleAction, ConversationStreamEvent, MembershipChangeAction, MessageRole,
+    ReplyMessageRequest, ThreadListResponse, ThreadTreeResponse, UnreadSummaryResponse,
 };
 use tokio::time::{Duration, sleep};
 use url::Url;
@@ -15,8 +15,20 @@ use uuid::Uuid;
 
 use super::session;
 
-fn client_with_session(server: &str) -> Result<(Client, Arc<Jar>, Url)> {
+/// Environment variable holding a personal access token; takes precedence over the cookie jar.
+pub(crate) const API_TOKEN_ENV: &str = "RUSTYGPT_API_TOKEN";
+
+pub(crate) fn client_with_session(server: &str) -> Result<(Client, Arc<Jar>, Url)> {
     let server_url = Url::parse(server).context("invalid server URL")?;
+    if let Some(token) = std::env::var(API_TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+    {
+        let jar = Arc::new(Jar::default());
+        let client = session::build_token_client(jar.clone(), token.trim())?;
+        return Ok((client, jar, server_url));
+    }
+
     let jar_path = session::session_path();
     let jar = session::load_cookie_jar(&server_url, &jar_path).with_context(|| {
         format!(
@@ -246,6 +258,7 @@ pub async fn handle_follow(args: FollowArgs) -> Result<()> {
     }
 }
 
+#[allow(clippy::too_many_lines)] // Tracking: cli-stream-event-refactor
 fn handle_stream_event(
     event_name: &str,
     data: &str,
@@ -301,6 +314,25 @@ fn handle_stream_event(
                     status = payload.status
                 );
             }
+            ConversationStreamEvent::MessageEdited { payload } => {
+                if payload.root_id == root_filter {
+                    println!(
+                        "[message {id} edited by {user}]",
+                        id = payload.message_id,
+                        user = payload.edited_by
+                    );
+                }
+            }
+            ConversationStreamEvent::MessageMention { payload } => {
+                if payload.root_id == root_filter {
+                    println!(
+                        "[message {id} by {author} mentions {user}]",
+                        id = payload.message_id,
+                        author = payload.author_id,
+                        user = payload.user_id
+                    );
+                }
+            }
             ConversationStreamEvent::UnreadUpdate { payload } => {
                 if payload.root_id == root_filter {
                     let unread = payload.unread;
@@ -323,6 +355,20 @@ fn handle_stream_event(
                     );
                 }
             }
+            ConversationStreamEvent::ConversationLifecycle { payload } => {
+                if payload.conversation_id == conversation_filter {
+                    let action = match payload.action {
+                        ConversationLifecycleAction::Archived => "archived",
+                        ConversationLifecycleAction::Unarchived => "unarchived",
+                        ConversationLifecycleAction::Deleted => "deleted",
+                        ConversationLifecycleAction::Purged => "purged",
+                    };
+                    println!(
+                        "[conversation {action}] {count} message(s) affected",
+                        count = payload.affected_messages
+                    );
+                }
+            }
             ConversationStreamEvent::Error { payload } => {
                 eprintln!(
                     "[stream error {code}] {message}",
@@ -0,0 +1,72 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use clap::Args;
+use shared::models::ExportFormat;
+use uuid::Uuid;
+
+use super::chat::client_with_session;
+
+#[derive(Args, Debug)]
+#[command(about = "Export a conversation or thread to Markdown, JSON or HTML")]
+pub struct ExportArgs {
+    /// Conversation identifier to export
+    #[arg(long, alias = "conv")]
+    pub conversation: Uuid,
+
+    /// Limit the export to a single thread root
+    #[arg(long)]
+    pub root: Option<Uuid>,
+
+    /// Output format: md, json or html
+    #[arg(long, short, default_value = "md", value_parser = parse_format)]
+    pub format: ExportFormat,
+
+    /// Write to this file instead of stdout
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// `RustyGPT` server base URL (default: <http://localhost:8080>)
+    #[arg(long, default_value = "http://localhost:8080")]
+    pub server: String,
+}
+
+fn parse_format(value: &str) -> Result<ExportFormat, String> {
+    value.parse()
+}
+
+pub async fn handle_export(args: ExportArgs) -> Result<()> {
+    let (client, _jar, server_url) = client_with_session(&args.server)?;
+    let api_base = server_url
+        .join("api/")
+        .context("invalid API base for export")?;
+
+    let mut url = api_base.join(&format!(
+        "conversations/{conversation}/export",
+        conversation = args.conversation
+    ))?;
+    url.query_pairs_mut()
+        .append_pair("format", args.format.as_str());
+    if let Some(root) = args.root {
+        url.query_pairs_mut()
+            .append_pair("root_id", &root.to_string());
+    }
+
+    let response = client.get(url).send().await.context("request failed")?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("export failed with {status}: {body}"));
+    }
+    let body = response.text().await?;
+
+    if let Some(path) = args.output {
+        fs::write(&path, body)
+            .with_context(|| format!("failed to write export to {}", path.display()))?;
+        eprintln!("Exported {} to {}", args.conversation, path.display());
+    } else {
+        print!("{body}");
+    }
+
+    Ok(())
+}
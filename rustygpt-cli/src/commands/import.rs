@@ -0,0 +1,95 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use clap::Args;
+use shared::models::{ImportReport, ImportSource};
+
+use super::{chat::client_with_session, session};
+
+#[derive(Args, Debug)]
+#[command(about = "Import RustyGPT exports or ChatGPT / OpenAI-format conversation histories")]
+pub struct ImportArgs {
+    /// A `rustygpt export --format json` file, `conversations.json` from a `ChatGPT` export, or an
+    /// `OpenAI` messages JSONL file
+    #[arg(long, short)]
+    pub file: PathBuf,
+
+    /// Input format: rustygpt, chatgpt or jsonl (detected from the file when omitted)
+    #[arg(long, value_parser = parse_source)]
+    pub format: Option<ImportSource>,
+
+    /// Parse and report without creating any conversations
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// `RustyGPT` server base URL (default: <http://localhost:8080>)
+    #[arg(long, default_value = "http://localhost:8080")]
+    pub server: String,
+}
+
+fn parse_source(value: &str) -> Result<ImportSource, String> {
+    value.parse()
+}
+
+pub async fn handle_import(args: ImportArgs) -> Result<()> {
+    let body =
+        fs::read(&args.file).with_context(|| format!("failed to read {}", args.file.display()))?;
+
+    let (client, jar, server_url) = client_with_session(&args.server)?;
+    let mut url = server_url
+        .join("api/conversations/import")
+        .context("invalid API base for import")?;
+    if let Some(format) = args.format {
+        url.query_pairs_mut().append_pair("format", format.as_str());
+    }
+    if args.dry_run {
+        url.query_pairs_mut().append_pair("dry_run", "true");
+    }
+
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+        .body(body);
+    if let Some(csrf) = session::csrf_token_from_jar(&jar, &server_url) {
+        request = request.header("X-CSRF-Token", csrf);
+    }
+    let response = request.send().await.context("request failed")?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("import failed with {status}: {body}"));
+    }
+    let report: ImportReport = response.json().await.context("invalid import report")?;
+
+    print_report(&report);
+    Ok(())
+}
+
+fn print_report(report: &ImportReport) {
+    let verb = if report.dry_run {
+        "Would import"
+    } else {
+        "Imported"
+    };
+    println!(
+        "{verb} {} conversation(s), {} message(s) from {}",
+        report.total_conversations, report.total_messages, report.source
+    );
+    for conversation in &report.conversations {
+        let id = conversation
+            .conversation_id
+            .map(|id| format!(" -> {id}"))
+            .unwrap_or_default();
+        println!(
+            "  {}: {} message(s), {} thread(s), {} branch point(s), {} skipped{id}",
+            conversation.title,
+            conversation.message_count,
+            conversation.thread_count,
+            conversation.branch_count,
+            conversation.skipped_messages,
+        );
+    }
+    for warning in &report.warnings {
+        eprintln!("warning: {warning}");
+    }
+}
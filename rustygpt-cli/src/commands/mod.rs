@@ -1,5 +1,13 @@
+pub mod admin;
+pub mod audit;
 pub mod chat;
 pub mod completion;
 pub mod config;
+pub mod export;
+pub mod import;
+pub mod preferences;
 pub mod session;
+pub mod sessions;
 pub mod spec;
+pub mod tokens;
+pub mod usage;
@@ -0,0 +1,197 @@
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Args, Subcommand, ValueEnum};
+use serde_json::{Map, Value, json};
+use shared::models::{ThemePreference, UserPreferences};
+
+use super::{chat::client_with_session, session};
+
+#[derive(Args, Debug)]
+#[command(about = "Show and change your account preferences")]
+pub struct PreferencesArgs {
+    #[command(subcommand)]
+    pub command: PreferencesCommand,
+
+    /// `RustyGPT` server base URL (default: <http://localhost:8080>)
+    #[arg(long, global = true, default_value = "http://localhost:8080")]
+    pub server: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PreferencesCommand {
+    /// Print the saved preferences as JSON
+    Show,
+    /// Change some preferences; anything not mentioned keeps its value
+    Set(SetPreferencesArgs),
+}
+
+#[derive(Args, Debug, Default)]
+pub struct SetPreferencesArgs {
+    /// Model used for new replies
+    #[arg(long)]
+    pub model: Option<String>,
+    /// Sampling temperature, 0.0 to 2.0
+    #[arg(long)]
+    pub temperature: Option<f32>,
+    /// Text sent ahead of every conversation
+    #[arg(long, conflicts_with = "system_prompt_file")]
+    pub system_prompt: Option<String>,
+    /// Read the system prompt from a file
+    #[arg(long)]
+    pub system_prompt_file: Option<std::path::PathBuf>,
+    /// Interface language code, such as `en` or `de`
+    #[arg(long)]
+    pub language: Option<String>,
+    #[arg(long, value_parser = parse_theme)]
+    pub theme: Option<ThemePreference>,
+    /// Expand the assistant's reasoning in clients
+    #[arg(long)]
+    pub show_reasoning: Option<bool>,
+    #[arg(long)]
+    pub email_notifications: Option<bool>,
+    #[arg(long)]
+    pub browser_notifications: Option<bool>,
+    #[arg(long)]
+    pub sound_notifications: Option<bool>,
+    /// Reset a preference to its default; repeat for several
+    #[arg(long = "clear", value_enum)]
+    pub clear: Vec<PreferenceField>,
+}
+
+/// Preferences that can be reset with `--clear`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum PreferenceField {
+    Model,
+    Temperature,
+    SystemPrompt,
+    Language,
+    Theme,
+    ShowReasoning,
+    Notifications,
+}
+
+impl PreferenceField {
+    const fn key(self) -> &'static str {
+        match self {
+            Self::Model => "default_model",
+            Self::Temperature => "temperature",
+            Self::SystemPrompt => "system_prompt",
+            Self::Language => "language",
+            Self::Theme => "theme",
+            Self::ShowReasoning => "show_reasoning",
+            Self::Notifications => "notifications",
+        }
+    }
+}
+
+fn parse_theme(value: &str) -> Result<ThemePreference, String> {
+    value.parse().map_err(|err: &str| err.to_string())
+}
+
+impl SetPreferencesArgs {
+    /// Builds the JSON merge patch sent to `PATCH /api/me/preferences`.
+    fn into_patch(self) -> Result<Value> {
+        let system_prompt = match self.system_prompt_file {
+            Some(path) => Some(
+                std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?,
+            ),
+            None => self.system_prompt,
+        };
+
+        let mut patch = Map::new();
+        for field in &self.clear {
+            patch.insert(field.key().to_string(), Value::Null);
+        }
+        let mut set = |key: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                patch.insert(key.to_string(), value);
+            }
+        };
+        set("default_model", self.model.map(Value::from));
+        set("temperature", self.temperature.map(Value::from));
+        set("system_prompt", system_prompt.map(Value::from));
+        set("language", self.language.map(Value::from));
+        set("theme", self.theme.map(|theme| Value::from(theme.as_str())));
+        set("show_reasoning", self.show_reasoning.map(Value::from));
+
+        let mut notifications = Map::new();
+        for (key, value) in [
+            ("email", self.email_notifications),
+            ("browser", self.browser_notifications),
+            ("sound", self.sound_notifications),
+        ] {
+            if let Some(value) = value {
+                notifications.insert(key.to_string(), json!(value));
+            }
+        }
+        if !notifications.is_empty() {
+            patch.insert("notifications".to_string(), Value::Object(notifications));
+        }
+
+        if patch.is_empty() {
+            bail!("nothing to change; pass at least one option or --clear");
+        }
+        Ok(Value::Object(patch))
+    }
+}
+
+pub async fn handle_preferences(args: PreferencesArgs) -> Result<()> {
+    let (client, jar, server_url) = client_with_session(&args.server)?;
+    let url = server_url
+        .join("api/me/preferences")
+        .context("invalid API base for preferences")?;
+
+    let response = match args.command {
+        PreferencesCommand::Show => client.get(url).send().await,
+        PreferencesCommand::Set(set) => {
+            let mut request = client.patch(url).json(&set.into_patch()?);
+            if let Some(csrf) = session::csrf_token_from_jar(&jar, &server_url) {
+                request = request.header("X-CSRF-Token", csrf);
+            }
+            request.send().await
+        }
+    }
+    .context("request failed")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("preferences request failed with {status}: {body}"));
+    }
+    let preferences: UserPreferences = response.json().await?;
+    println!("{}", serde_json::to_string_pretty(&preferences)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patch_contains_only_mentioned_fields() {
+        let patch = SetPreferencesArgs {
+            temperature: Some(0.5),
+            theme: Some(ThemePreference::Dark),
+            sound_notifications: Some(true),
+            clear: vec![PreferenceField::SystemPrompt],
+            ..SetPreferencesArgs::default()
+        }
+        .into_patch()
+        .unwrap();
+
+        assert_eq!(
+            patch,
+            json!({
+                "temperature": 0.5,
+                "theme": "dark",
+                "system_prompt": null,
+                "notifications": {"sound": true}
+            })
+        );
+    }
+
+    #[test]
+    fn empty_patch_is_rejected() {
+        assert!(SetPreferencesArgs::default().into_patch().is_err());
+    }
+}
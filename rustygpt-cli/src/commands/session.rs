@@ -15,7 +15,10 @@ use reqwest::{
 use rpassword::prompt_password;
 use shared::{
     config::server::Config,
-    models::{AuthenticatedUser, LoginRequest, LoginResponse, MeResponse, SessionSummary},
+    models::{
+        AuthenticatedUser, LoginRequest, LoginResponse, MeResponse, MfaChallengeResponse,
+        MfaVerifyRequest, SessionSummary, TotpEnrollment,
+    },
 };
 use url::Url;
 
@@ -67,12 +70,69 @@ pub async fn login(args: LoginArgs) -> Result<()> {
         bail!("login failed with {status}: {body}");
     }
 
-    let login: LoginResponse = response.json().await?;
+    let login: LoginResponse = if response.status() == StatusCode::ACCEPTED {
+        let challenge: MfaChallengeResponse = response.json().await?;
+        complete_mfa(&client, &origin, challenge).await?
+    } else {
+        response.json().await?
+    };
     persist_cookie_jar(&jar, &origin, &jar_path)?;
     print_session_summary(&login.user, &login.session, &jar_path);
+    print_recovery_codes(&login.recovery_codes);
     Ok(())
 }
 
+async fn complete_mfa(
+    client: &Client,
+    origin: &Url,
+    challenge: MfaChallengeResponse,
+) -> Result<LoginResponse> {
+    if let Some(enrollment) = &challenge.enrollment {
+        print_enrollment(enrollment);
+    }
+    let code = prompt("Authentication code: ")?;
+    if code.is_empty() {
+        bail!("authentication code must not be empty");
+    }
+
+    let mfa_url = origin
+        .join("api/auth/login/mfa")
+        .context("invalid two-factor endpoint")?;
+    let response = client
+        .post(mfa_url)
+        .json(&MfaVerifyRequest {
+            challenge_token: challenge.challenge_token,
+            code,
+        })
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        bail!("two-factor verification failed with {status}: {body}");
+    }
+
+    Ok(response.json().await?)
+}
+
+fn print_enrollment(enrollment: &TotpEnrollment) {
+    println!("Your account requires two-factor authentication.");
+    println!("Add this key to an authenticator app:");
+    println!("  Secret: {}", enrollment.secret);
+    println!("  URI:    {}", enrollment.otpauth_uri);
+}
+
+fn print_recovery_codes(codes: &[String]) {
+    if codes.is_empty() {
+        return;
+    }
+    println!("Recovery codes (each works once; store them somewhere safe):");
+    for code in codes {
+        println!("  {code}");
+    }
+}
+
 pub async fn me(args: MeArgs) -> Result<()> {
     let config = Config::load_config(args.config.clone(), None)?;
     let origin = config.server.public_base_url.clone();
@@ -172,6 +232,22 @@ pub fn build_client(jar: Arc<Jar>) -> Result<Client> {
         .context("failed to build HTTP client")
 }
 
+/// Build a client that authenticates every request with a bearer token.
+pub fn build_token_client(jar: Arc<Jar>, token: &str) -> Result<Client> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    let mut value = reqwest::header::HeaderValue::from_str(&format!("Bearer {token}"))
+        .context("API token contains invalid characters")?;
+    value.set_sensitive(true);
+    headers.insert(reqwest::header::AUTHORIZATION, value);
+
+    Client::builder()
+        .cookie_provider(jar)
+        .default_headers(headers)
+        .user_agent("rustygpt-cli")
+        .build()
+        .context("failed to build HTTP client")
+}
+
 pub fn load_cookie_jar(origin: &Url, path: &Path) -> Result<Arc<Jar>> {
     if !path.exists() {
         bail!("session cookie jar not found at {}", path.display());
@@ -0,0 +1,119 @@
+use anyhow::{Context, Result, anyhow};
+use clap::{Args, Subcommand};
+use reqwest::{Method, RequestBuilder};
+use shared::models::{ActiveSessionListResponse, RevokeSessionsResponse};
+use uuid::Uuid;
+
+use super::{chat::client_with_session, session};
+
+#[derive(Args, Debug)]
+#[command(about = "List and sign out signed-in devices")]
+pub struct SessionsArgs {
+    #[command(subcommand)]
+    pub command: SessionsCommand,
+
+    /// Manage another user's sessions (admin only)
+    #[arg(long, global = true)]
+    pub user: Option<Uuid>,
+
+    /// `RustyGPT` server base URL (default: <http://localhost:8080>)
+    #[arg(long, global = true, default_value = "http://localhost:8080")]
+    pub server: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SessionsCommand {
+    /// List active sessions
+    List,
+    /// Sign out one session by id
+    Revoke {
+        /// Session identifier from `sessions list`
+        id: Uuid,
+    },
+    /// Sign out every session except this one (with --user: every session of that user)
+    RevokeOthers,
+    /// Lift a failed-login lockout (admin only; requires --user)
+    Unlock,
+}
+
+/// Path of the session collection, relative to `/api/`.
+fn sessions_path(user: Option<Uuid>) -> String {
+    user.map_or_else(
+        || "auth/sessions".to_string(),
+        |user| format!("admin/users/{user}/sessions"),
+    )
+}
+
+pub async fn handle_sessions(args: SessionsArgs) -> Result<()> {
+    let (client, jar, server_url) = client_with_session(&args.server)?;
+    let api_base = server_url
+        .join("api/")
+        .context("invalid API base for sessions")?;
+    let with_csrf = |request: RequestBuilder| match session::csrf_token_from_jar(&jar, &server_url)
+    {
+        Some(csrf) => request.header("X-CSRF-Token", csrf),
+        None => request,
+    };
+    let collection = sessions_path(args.user);
+
+    match args.command {
+        SessionsCommand::List => {
+            let response = client
+                .get(api_base.join(&collection)?)
+                .send()
+                .await
+                .context("request failed")?;
+            let listing: ActiveSessionListResponse =
+                ensure_success(response, "list").await?.json().await?;
+            for entry in listing.sessions {
+                let marker = if entry.current { "*" } else { " " };
+                println!(
+                    "{marker} {}  {}  {}  last active {}",
+                    entry.id,
+                    entry.device,
+                    entry.ip.as_deref().unwrap_or("-"),
+                    entry.last_seen_at.0
+                );
+            }
+        }
+        SessionsCommand::Revoke { id } => {
+            let request = client.request(
+                Method::DELETE,
+                api_base.join(&format!("{collection}/{id}"))?,
+            );
+            let response = with_csrf(request).send().await.context("request failed")?;
+            ensure_success(response, "revoke").await?;
+            println!("Signed out session {id}");
+        }
+        SessionsCommand::RevokeOthers => {
+            let request = client.request(Method::DELETE, api_base.join(&collection)?);
+            let response = with_csrf(request).send().await.context("request failed")?;
+            let result: RevokeSessionsResponse =
+                ensure_success(response, "revoke").await?.json().await?;
+            println!("Signed out {} session(s)", result.revoked);
+        }
+        SessionsCommand::Unlock => {
+            let user = args
+                .user
+                .ok_or_else(|| anyhow!("--user is required to unlock an account"))?;
+            let request = client.request(
+                Method::POST,
+                api_base.join(&format!("admin/users/{user}/unlock"))?,
+            );
+            let response = with_csrf(request).send().await.context("request failed")?;
+            ensure_success(response, "unlock").await?;
+            println!("Unlocked account {user}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn ensure_success(response: reqwest::Response, action: &str) -> Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Err(anyhow!("session {action} failed with {status}: {body}"))
+}
@@ -0,0 +1,130 @@
+use anyhow::{Context, Result, anyhow};
+use clap::{Args, Subcommand};
+use reqwest::{Method, RequestBuilder};
+use shared::models::{
+    ApiTokenListResponse, ApiTokenScope, CreateApiTokenRequest, CreateApiTokenResponse,
+};
+use uuid::Uuid;
+
+use super::{chat::client_with_session, session};
+
+#[derive(Args, Debug)]
+#[command(about = "Manage personal access tokens for scripts and editor plugins")]
+pub struct TokensArgs {
+    #[command(subcommand)]
+    pub command: TokensCommand,
+
+    /// `RustyGPT` server base URL (default: <http://localhost:8080>)
+    #[arg(long, global = true, default_value = "http://localhost:8080")]
+    pub server: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TokensCommand {
+    /// Create a token; the secret is printed once
+    Create {
+        /// Label shown when listing tokens
+        #[arg(long)]
+        name: String,
+
+        /// Scope to grant (repeatable): v1:chat, threads:write or admin:*
+        #[arg(long = "scope", required = true, value_parser = parse_scope)]
+        scopes: Vec<ApiTokenScope>,
+
+        /// Expire the token after this many days
+        #[arg(long)]
+        expires_in_days: Option<u32>,
+    },
+    /// List your tokens
+    List,
+    /// Revoke a token by id
+    Revoke {
+        /// Token identifier from `tokens list`
+        id: Uuid,
+    },
+}
+
+fn parse_scope(value: &str) -> Result<ApiTokenScope, String> {
+    value.parse()
+}
+
+pub async fn handle_tokens(args: TokensArgs) -> Result<()> {
+    let (client, jar, server_url) = client_with_session(&args.server)?;
+    let api_base = server_url
+        .join("api/")
+        .context("invalid API base for tokens")?;
+    let with_csrf = |request: RequestBuilder| match session::csrf_token_from_jar(&jar, &server_url)
+    {
+        Some(csrf) => request.header("X-CSRF-Token", csrf),
+        None => request,
+    };
+
+    match args.command {
+        TokensCommand::Create {
+            name,
+            scopes,
+            expires_in_days,
+        } => {
+            let payload = CreateApiTokenRequest {
+                name,
+                scopes,
+                expires_in_days,
+            };
+            let response = with_csrf(client.post(api_base.join("tokens")?).json(&payload))
+                .send()
+                .await
+                .context("request failed")?;
+            let created: CreateApiTokenResponse =
+                ensure_success(response, "create").await?.json().await?;
+            println!("{}", created.token);
+            eprintln!(
+                "Created token {} ({}). Store it now; it will not be shown again.",
+                created.summary.id, created.summary.prefix
+            );
+        }
+        TokensCommand::List => {
+            let response = client
+                .get(api_base.join("tokens")?)
+                .send()
+                .await
+                .context("request failed")?;
+            let listing: ApiTokenListResponse =
+                ensure_success(response, "list").await?.json().await?;
+            for token in listing.tokens {
+                let scopes: Vec<&str> = token.scopes.iter().map(|scope| scope.as_str()).collect();
+                let state = if token.revoked_at.is_some() {
+                    "revoked".to_string()
+                } else {
+                    token.last_used_at.map_or_else(
+                        || "never used".to_string(),
+                        |ts| format!("last used {}", ts.0),
+                    )
+                };
+                println!(
+                    "{}  {}  {}  [{}]  {state}",
+                    token.id,
+                    token.prefix,
+                    token.name,
+                    scopes.join(", ")
+                );
+            }
+        }
+        TokensCommand::Revoke { id } => {
+            let request = client.request(Method::DELETE, api_base.join(&format!("tokens/{id}"))?);
+            let response = with_csrf(request).send().await.context("request failed")?;
+            ensure_success(response, "revoke").await?;
+            println!("Revoked token {id}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn ensure_success(response: reqwest::Response, action: &str) -> Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Err(anyhow!("token {action} failed with {status}: {body}"))
+}
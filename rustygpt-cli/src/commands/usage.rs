@@ -0,0 +1,114 @@
+use anyhow::{Context, Result, anyhow};
+use clap::Args;
+use shared::models::{UsageGroupBy, UsageReport};
+use uuid::Uuid;
+
+use super::chat::client_with_session;
+
+#[derive(Args, Debug)]
+#[command(about = "Show token usage from the usage ledger")]
+pub struct UsageArgs {
+    /// First UTC day to include (YYYY-MM-DD; default: 30 days ago)
+    #[arg(long)]
+    pub from: Option<String>,
+
+    /// Last UTC day to include (YYYY-MM-DD; default: today)
+    #[arg(long)]
+    pub to: Option<String>,
+
+    /// Aggregate by day, model or user
+    #[arg(long, default_value = "day", value_parser = parse_group_by)]
+    pub group_by: UsageGroupBy,
+
+    /// Report every user (requires the admin role)
+    #[arg(long)]
+    pub all: bool,
+
+    /// With --all, restrict the report to one user
+    #[arg(long, requires = "all")]
+    pub user: Option<Uuid>,
+
+    /// Print CSV instead of a table
+    #[arg(long)]
+    pub csv: bool,
+
+    /// `RustyGPT` server base URL (default: <http://localhost:8080>)
+    #[arg(long, default_value = "http://localhost:8080")]
+    pub server: String,
+}
+
+fn parse_group_by(value: &str) -> Result<UsageGroupBy, String> {
+    value.parse()
+}
+
+pub async fn handle_usage(args: UsageArgs) -> Result<()> {
+    let (client, _jar, server_url) = client_with_session(&args.server)?;
+    let path = if args.all {
+        "api/admin/usage"
+    } else {
+        "api/usage"
+    };
+    let mut url = server_url
+        .join(path)
+        .context("invalid API base for usage")?;
+    {
+        let mut query = url.query_pairs_mut();
+        query.append_pair("group_by", args.group_by.as_str());
+        if let Some(from) = args.from.as_deref() {
+            query.append_pair("from", from);
+        }
+        if let Some(to) = args.to.as_deref() {
+            query.append_pair("to", to);
+        }
+        if let Some(user) = args.user {
+            query.append_pair("user_id", &user.to_string());
+        }
+        if args.csv {
+            query.append_pair("format", "csv");
+        }
+    }
+
+    let response = client.get(url).send().await.context("request failed")?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("usage report failed with {status}: {body}"));
+    }
+
+    if args.csv {
+        print!("{}", response.text().await.context("invalid usage CSV")?);
+        return Ok(());
+    }
+
+    let report: UsageReport = response.json().await.context("invalid usage report")?;
+    print_report(&report);
+    Ok(())
+}
+
+fn print_report(report: &UsageReport) {
+    println!(
+        "{:<38} {:>8} {:>12} {:>12} {:>12} {:>10}",
+        report.group_by, "requests", "prompt", "completion", "total", "avg ms"
+    );
+    for row in &report.rows {
+        let bucket = row.username.as_ref().map_or_else(
+            || row.bucket.clone(),
+            |name| format!("{name} ({})", row.bucket),
+        );
+        println!(
+            "{bucket:<38} {:>8} {:>12} {:>12} {:>12} {:>10}",
+            row.requests,
+            row.prompt_tokens,
+            row.completion_tokens,
+            row.total_tokens,
+            row.avg_latency_ms
+        );
+    }
+    let total: i64 = report.rows.iter().map(|row| row.total_tokens).sum();
+    println!(
+        "{} row(s), {total} token(s) between {} and {}",
+        report.rows.len(),
+        report.from.0.date_naive(),
+        report.to.0.date_naive()
+    );
+}
@@ -49,6 +49,10 @@ enum Commands {
     Reply(commands::chat::ReplyArgs),
     /// Follow SSE updates for a thread
     Follow(commands::chat::FollowArgs),
+    /// Export a conversation or thread to Markdown, JSON or HTML
+    Export(commands::export::ExportArgs),
+    /// Import `ChatGPT` or `OpenAI`-format conversation histories
+    Import(commands::import::ImportArgs),
     /// Generate the `OpenAPI` specification
     Spec {
         /// Output path for the `OpenAPI` spec (YAML or JSON based on extension, or "json"/"yaml" for streaming)
@@ -82,6 +86,18 @@ enum Commands {
     Me(commands::session::MeArgs),
     /// Logout and remove stored session cookies
     Logout(commands::session::LogoutArgs),
+    /// Show and change your account preferences
+    Preferences(commands::preferences::PreferencesArgs),
+    /// List signed-in devices and sign them out
+    Sessions(commands::sessions::SessionsArgs),
+    /// Create, list and revoke personal access tokens
+    Tokens(commands::tokens::TokensArgs),
+    /// Report token usage by day, model or user
+    Usage(commands::usage::UsageArgs),
+    /// Export the audit log (admin only)
+    Audit(commands::audit::AuditArgs),
+    /// Manage user accounts and roles (admin only)
+    Admin(commands::admin::AdminArgs),
 }
 
 #[tokio::main]
@@ -104,6 +120,12 @@ async fn main() -> Result<(), Box<dyn Error>> {
         Commands::Follow(args) => {
             commands::chat::handle_follow(args).await?;
         }
+        Commands::Export(args) => {
+            commands::export::handle_export(args).await?;
+        }
+        Commands::Import(args) => {
+            commands::import::handle_import(args).await?;
+        }
         Commands::Spec { output_path } => {
             commands::spec::generate_spec(output_path.as_deref())?;
         }
@@ -126,6 +148,24 @@ async fn main() -> Result<(), Box<dyn Error>> {
         Commands::Logout(args) => {
             commands::session::logout(args).await?;
         }
+        Commands::Preferences(args) => {
+            commands::preferences::handle_preferences(args).await?;
+        }
+        Commands::Sessions(args) => {
+            commands::sessions::handle_sessions(args).await?;
+        }
+        Commands::Tokens(args) => {
+            commands::tokens::handle_tokens(args).await?;
+        }
+        Commands::Usage(args) => {
+            commands::usage::handle_usage(args).await?;
+        }
+        Commands::Audit(args) => {
+            commands::audit::handle_audit(args).await?;
+        }
+        Commands::Admin(args) => {
+            commands::admin::handle_admin(args).await?;
+        }
     }
 
     Ok(())
@@ -174,6 +214,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_export_command() {
+        let conversation = uuid::Uuid::new_v4();
+        let cli = Cli::try_parse_from([
+            "cli",
+            "export",
+            "--conversation",
+            &conversation.to_string(),
+            "--format",
+            "html",
+        ]);
+        if let Err(e) = &cli {
+            panic!("CLI parse error: {e}");
+        }
+
+        match cli.unwrap().command {
+            Commands::Export(args) => {
+                assert_eq!(args.conversation, conversation);
+                assert_eq!(args.format, shared::models::ExportFormat::Html);
+                assert!(args.root.is_none());
+            }
+            _ => panic!("Expected Export command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_import_command() {
+        let cli = Cli::try_parse_from([
+            "cli",
+            "import",
+            "--file",
+            "conversations.json",
+            "--format",
+            "chatgpt",
+            "--dry-run",
+        ]);
+        if let Err(e) = &cli {
+            panic!("CLI parse error: {e}");
+        }
+
+        match cli.unwrap().command {
+            Commands::Import(args) => {
+                assert_eq!(args.file, PathBuf::from("conversations.json"));
+                assert_eq!(args.format, Some(shared::models::ImportSource::ChatGpt));
+                assert!(args.dry_run);
+            }
+            _ => panic!("Expected Import command"),
+        }
+    }
+
     #[test]
     fn test_cli_login_command() {
         let cli = Cli::try_parse_from(["cli", "login"]);
@@ -207,6 +297,204 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_tokens_create_command() {
+        let cli = Cli::try_parse_from([
+            "cli",
+            "tokens",
+            "create",
+            "--name",
+            "editor",
+            "--scope",
+            "v1:chat",
+            "--scope",
+            "threads:write",
+            "--expires-in-days",
+            "30",
+        ]);
+        if let Err(e) = &cli {
+            panic!("CLI parse error: {e}");
+        }
+
+        match cli.unwrap().command {
+            Commands::Tokens(args) => match args.command {
+                commands::tokens::TokensCommand::Create {
+                    name,
+                    scopes,
+                    expires_in_days,
+                } => {
+                    assert_eq!(name, "editor");
+                    assert_eq!(
+                        scopes,
+                        vec![
+                            shared::models::ApiTokenScope::V1Chat,
+                            shared::models::ApiTokenScope::ThreadsWrite
+                        ]
+                    );
+                    assert_eq!(expires_in_days, Some(30));
+                }
+                other => panic!("Expected tokens create, got {other:?}"),
+            },
+            _ => panic!("Expected Tokens command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_sessions_command() {
+        let user = uuid::Uuid::new_v4();
+        let cli = Cli::try_parse_from([
+            "cli",
+            "sessions",
+            "revoke-others",
+            "--user",
+            &user.to_string(),
+        ]);
+        if let Err(e) = &cli {
+            panic!("CLI parse error: {e}");
+        }
+
+        match cli.unwrap().command {
+            Commands::Sessions(args) => {
+                assert!(matches!(
+                    args.command,
+                    commands::sessions::SessionsCommand::RevokeOthers
+                ));
+                assert_eq!(args.user, Some(user));
+            }
+            _ => panic!("Expected Sessions command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_preferences_command() {
+        let cli = Cli::try_parse_from([
+            "cli",
+            "preferences",
+            "set",
+            "--theme",
+            "dark",
+            "--show-reasoning",
+            "true",
+            "--clear",
+            "system-prompt",
+        ]);
+        if let Err(e) = &cli {
+            panic!("CLI parse error: {e}");
+        }
+
+        match cli.unwrap().command {
+            Commands::Preferences(args) => match args.command {
+                commands::preferences::PreferencesCommand::Set(set) => {
+                    assert_eq!(set.theme, Some(shared::models::ThemePreference::Dark));
+                    assert_eq!(set.show_reasoning, Some(true));
+                    assert_eq!(
+                        set.clear,
+                        vec![commands::preferences::PreferenceField::SystemPrompt]
+                    );
+                }
+                other @ commands::preferences::PreferencesCommand::Show => {
+                    panic!("Expected preferences set, got {other:?}")
+                }
+            },
+            _ => panic!("Expected Preferences command"),
+        }
+
+        assert!(Cli::try_parse_from(["cli", "preferences", "set", "--theme", "sepia"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_admin_users_command() {
+        let user = uuid::Uuid::new_v4();
+        let cli = Cli::try_parse_from([
+            "cli",
+            "admin",
+            "users",
+            "grant",
+            &user.to_string(),
+            "read_only",
+        ]);
+        if let Err(e) = &cli {
+            panic!("CLI parse error: {e}");
+        }
+
+        match cli.unwrap().command {
+            Commands::Admin(args) => match args.command {
+                commands::admin::AdminCommand::Users {
+                    command: commands::admin::UsersCommand::Grant { id, role },
+                } => {
+                    assert_eq!(id, user);
+                    assert_eq!(role, shared::models::UserRole::ReadOnly);
+                }
+                other @ commands::admin::AdminCommand::Users { .. } => {
+                    panic!("Expected users grant, got {other:?}")
+                }
+            },
+            _ => panic!("Expected Admin command"),
+        }
+
+        assert!(Cli::try_parse_from(["cli", "admin", "users", "list", "--role", "owner"]).is_err());
+        assert!(
+            Cli::try_parse_from(["cli", "admin", "users", "list", "--active", "--disabled"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_usage_command() {
+        let cli = Cli::try_parse_from([
+            "cli",
+            "usage",
+            "--all",
+            "--group-by",
+            "model",
+            "--from",
+            "2026-01-01",
+            "--csv",
+        ]);
+        if let Err(e) = &cli {
+            panic!("CLI parse error: {e}");
+        }
+
+        match cli.unwrap().command {
+            Commands::Usage(args) => {
+                assert!(args.all && args.csv);
+                assert_eq!(args.group_by, shared::models::UsageGroupBy::Model);
+                assert_eq!(args.from.as_deref(), Some("2026-01-01"));
+                assert!(args.to.is_none());
+            }
+            _ => panic!("Expected Usage command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_audit_command() {
+        let cli = Cli::try_parse_from([
+            "cli",
+            "audit",
+            "--action",
+            "membership",
+            "--from",
+            "2026-03-01T00:00:00Z",
+            "--csv",
+            "-o",
+            "audit.csv",
+        ]);
+        if let Err(e) = &cli {
+            panic!("CLI parse error: {e}");
+        }
+
+        match cli.unwrap().command {
+            Commands::Audit(args) => {
+                assert!(args.csv);
+                assert_eq!(args.action.as_deref(), Some("membership"));
+                assert_eq!(args.from.as_deref(), Some("2026-03-01T00:00:00Z"));
+                assert_eq!(args.output, Some(std::path::PathBuf::from("audit.csv")));
+                assert!(args.actor.is_none());
+            }
+            _ => panic!("Expected Audit command"),
+        }
+    }
+
     #[test]
     fn test_cli_config_command() {
         let cli = Cli::try_parse_from(["cli", "config", "--format", "json"]);
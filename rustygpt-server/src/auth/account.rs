@@ -0,0 +1,332 @@
+//! Self-service account recovery: password reset, password change and email verification.
+//!
+//! Reset and verification links carry a random token; only its SHA-256 hash is stored.
+
+use std::sync::Arc;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use oauth2::url::form_urlencoded;
+use sha2::{Digest, Sha256};
+use shared::config::server::Config;
+use sqlx::{PgConnection, PgPool};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    auth::session::{hash_password, verify_password},
+    services::{
+        audit_service::AuditContext,
+        chat_service::{ChatServiceError, ChatServiceResult},
+        mail_templates::{MailTemplate, describe_ttl},
+        mailer,
+    },
+};
+
+/// Shortest password accepted by reset and change.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+pub(crate) fn new_token() -> (String, Vec<u8>) {
+    let mut raw = [0u8; 32];
+    OsRng.fill_bytes(&mut raw);
+    let token = URL_SAFE_NO_PAD.encode(raw);
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+pub(crate) fn hash_token(token: &str) -> Vec<u8> {
+    Sha256::digest(token.trim().as_bytes()).to_vec()
+}
+
+fn validate_new_password(password: &str) -> ChatServiceResult<()> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(ChatServiceError::Validation(format!(
+            "password must be at least {MIN_PASSWORD_LENGTH} characters"
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn hash_new_password(password: &str) -> ChatServiceResult<String> {
+    validate_new_password(password)?;
+    hash_password(password).map_err(|err| ChatServiceError::Validation(err.to_string()))
+}
+
+/// Builds `<public_base_url><path>?token=<token>`, the page in the web app that consumes it.
+pub(crate) fn token_link(base: &str, path: &str, token: &str) -> String {
+    let token: String = form_urlencoded::byte_serialize(token.as_bytes()).collect();
+    format!("{}{path}?token={token}", base.trim_end_matches('/'))
+}
+
+fn ttl_seconds(value: u64) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
+#[derive(sqlx::FromRow)]
+struct RecipientRow {
+    email: String,
+    display_name: String,
+}
+
+#[derive(Clone)]
+pub struct AccountService {
+    pool: PgPool,
+    config: Arc<Config>,
+}
+
+impl AccountService {
+    pub const fn new(pool: PgPool, config: Arc<Config>) -> Self {
+        Self { pool, config }
+    }
+
+    /// Emails a reset link when `email` belongs to an active account. Unknown addresses succeed
+    /// silently so the endpoint cannot be used to discover accounts.
+    #[instrument(name = "account.password_reset_request", skip(self, audit, email), err)]
+    pub async fn request_password_reset(
+        &self,
+        audit: &AuditContext,
+        email: &str,
+    ) -> ChatServiceResult<()> {
+        let (token, hash) = new_token();
+        let ttl = self.config.mail.password_reset_ttl_seconds;
+
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let recipient = sqlx::query_as::<_, RecipientRow>(
+            "SELECT email, display_name FROM rustygpt.sp_password_reset_request($1, $2, $3)",
+        )
+        .bind(email.trim())
+        .bind(&hash)
+        .bind(ttl_seconds(ttl))
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        if let Some(recipient) = recipient {
+            let link = token_link(
+                self.config.server.public_base_url.as_str(),
+                "/reset-password",
+                &token,
+            );
+            let mail = MailTemplate::PasswordReset.render(
+                &recipient.email,
+                &[
+                    ("name", &recipient.display_name),
+                    ("link", &link),
+                    ("expires_in", &describe_ttl(ttl)),
+                ],
+            );
+            mailer::enqueue_on(&mut tx, &mail)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+        }
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(())
+    }
+
+    /// Sets a new password from a reset link, revokes every session of the account and clears
+    /// any failed-login lockout.
+    #[instrument(name = "account.password_reset", skip_all, err)]
+    pub async fn reset_password(
+        &self,
+        audit: &AuditContext,
+        token: &str,
+        new_password: &str,
+    ) -> ChatServiceResult<Uuid> {
+        let password_hash = hash_new_password(new_password)?;
+
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let user_id =
+            sqlx::query_scalar::<_, Uuid>("SELECT rustygpt.sp_password_reset_complete($1, $2)")
+                .bind(hash_token(token))
+                .bind(&password_hash)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+        // Proving control of the mailbox also lifts a failed-login lockout.
+        sqlx::query("SELECT rustygpt.sp_auth_login_success($1)")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(user_id)
+    }
+
+    /// Changes the password after checking the current one. Sessions other than `keep_session`
+    /// are revoked.
+    #[instrument(
+        name = "account.password_change",
+        skip(self, audit, current, new_password),
+        err
+    )]
+    pub async fn change_password(
+        &self,
+        audit: &AuditContext,
+        user_id: Uuid,
+        keep_session: Option<Uuid>,
+        current: &str,
+        new_password: &str,
+    ) -> ChatServiceResult<()> {
+        let stored = sqlx::query_scalar::<_, String>(
+            "SELECT password_hash FROM rustygpt.users WHERE id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?
+        .ok_or_else(|| ChatServiceError::NotFound("user not found".to_string()))?;
+        verify_password(&stored, current).map_err(|_| {
+            ChatServiceError::Validation("current password is incorrect".to_string())
+        })?;
+        let password_hash = hash_new_password(new_password)?;
+
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        sqlx::query("SELECT rustygpt.sp_password_change($1, $2, $3)")
+            .bind(user_id)
+            .bind(&password_hash)
+            .bind(keep_session)
+            .execute(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(())
+    }
+
+    /// Emails a verification link for the account's current address. Returns `false` when the
+    /// address is already verified.
+    #[instrument(name = "account.send_verification", skip(self, audit), err)]
+    pub async fn send_verification(
+        &self,
+        audit: &AuditContext,
+        user_id: Uuid,
+    ) -> ChatServiceResult<bool> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let queued = self.queue_verification(&mut tx, user_id).await?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(queued)
+    }
+
+    async fn queue_verification(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+    ) -> ChatServiceResult<bool> {
+        let (token, hash) = new_token();
+        let ttl = self.config.mail.verification_ttl_seconds;
+
+        let Some(recipient) = sqlx::query_as::<_, RecipientRow>(
+            "SELECT email, display_name FROM rustygpt.sp_email_verification_create($1, $2, $3)",
+        )
+        .bind(user_id)
+        .bind(&hash)
+        .bind(ttl_seconds(ttl))
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(ChatServiceError::from_db_error)?
+        else {
+            return Ok(false);
+        };
+
+        let link = token_link(
+            self.config.server.public_base_url.as_str(),
+            "/verify-email",
+            &token,
+        );
+        let mail = MailTemplate::EmailVerification.render(
+            &recipient.email,
+            &[
+                ("name", &recipient.display_name),
+                ("email", &recipient.email),
+                ("link", &link),
+                ("expires_in", &describe_ttl(ttl)),
+            ],
+        );
+        mailer::enqueue_on(conn, &mail)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(true)
+    }
+
+    /// Sends a verification link to the account registered under `email`, if it exists.
+    #[instrument(name = "account.send_verification_to", skip(self, audit), err)]
+    pub async fn send_verification_to(
+        &self,
+        audit: &AuditContext,
+        email: &str,
+    ) -> ChatServiceResult<bool> {
+        let user_id =
+            sqlx::query_scalar::<_, Uuid>("SELECT id FROM rustygpt.users WHERE email = $1::citext")
+                .bind(email.trim())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+
+        match user_id {
+            Some(user_id) => self.send_verification(audit, user_id).await,
+            None => Ok(false),
+        }
+    }
+
+    /// Marks the address in a verification link as confirmed.
+    #[instrument(name = "account.verify_email", skip_all, err)]
+    pub async fn verify_email(&self, audit: &AuditContext, token: &str) -> ChatServiceResult<Uuid> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let user_id =
+            sqlx::query_scalar::<_, Uuid>("SELECT rustygpt.sp_email_verification_confirm($1)")
+                .bind(hash_token(token))
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_hash_ignores_surrounding_whitespace() {
+        let (token, hash) = new_token();
+        assert_eq!(hash_token(&format!(" {token}\n")), hash);
+        assert_ne!(hash_token("other"), hash);
+    }
+
+    #[test]
+    fn token_link_points_at_the_web_page() {
+        assert_eq!(
+            token_link("https://chat.example.com/", "/reset-password", "abc_123"),
+            "https://chat.example.com/reset-password?token=abc_123"
+        );
+        assert_eq!(
+            token_link("https://chat.example.com", "/invite", "a+b/c="),
+            "https://chat.example.com/invite?token=a%2Bb%2Fc%3D"
+        );
+    }
+
+    #[test]
+    fn short_passwords_are_rejected() {
+        assert!(matches!(
+            validate_new_password("short"),
+            Err(ChatServiceError::Validation(_))
+        ));
+        assert!(validate_new_password("long enough").is_ok());
+    }
+}
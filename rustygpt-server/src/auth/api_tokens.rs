@@ -0,0 +1,404 @@
+//! Personal access tokens accepted as `Authorization: Bearer` credentials.
+
+use std::{fmt::Write as _, str::FromStr};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::http::{HeaderMap, Method, header};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use shared::models::{
+    API_TOKEN_MARKER, ApiTokenScope, ApiTokenSummary, CreateApiTokenRequest,
+    CreateApiTokenResponse, Timestamp, UserRole,
+};
+
+use crate::{
+    auth::session::SessionUser,
+    services::chat_service::{ChatServiceError, ChatServiceResult, begin_as},
+};
+
+const PREFIX_BYTES: usize = 4;
+const SECRET_BYTES: usize = 32;
+
+/// Identity resolved from a bearer token; inserted as a request extension by the auth middleware.
+#[derive(Debug, Clone)]
+pub struct ApiTokenPrincipal {
+    pub token_id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub roles: Vec<UserRole>,
+    pub scopes: Vec<ApiTokenScope>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ApiTokenPrincipal {
+    pub fn allows(&self, scope: ApiTokenScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Present the token owner the same way a cookie session would be.
+    pub fn session_user(&self) -> SessionUser {
+        // Non-expiring tokens report a rolling day so clients never see an unbounded date.
+        let expires_at = self
+            .expires_at
+            .unwrap_or_else(|| Utc::now() + Duration::days(1));
+        SessionUser {
+            id: self.user_id,
+            email: self.email.clone(),
+            username: self.username.clone(),
+            display_name: self.display_name.clone(),
+            roles: self.roles.clone(),
+            session_id: self.token_id,
+            issued_at: self.created_at,
+            expires_at,
+            absolute_expires_at: expires_at,
+        }
+    }
+}
+
+/// Return the raw bearer credential if the request carries one.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// Scope a bearer token needs for a route; `None` means any valid token may call it.
+pub fn required_scope(method: &Method, path: &str) -> Option<ApiTokenScope> {
+    let mut path = path;
+    while let Some(rest) = path
+        .strip_prefix("/api")
+        .filter(|rest| rest.starts_with('/'))
+    {
+        path = rest;
+    }
+
+    if path.starts_with("/v1/") {
+        Some(ApiTokenScope::V1Chat)
+    } else if path == "/admin" || path.starts_with("/admin/") {
+        Some(ApiTokenScope::Admin)
+    } else if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        None
+    } else {
+        Some(ApiTokenScope::ThreadsWrite)
+    }
+}
+
+/// Split `rgp_<prefix>_<secret>` and return the lookup prefix.
+fn token_prefix(token: &str) -> Option<&str> {
+    let mut parts = token.splitn(3, '_');
+    let marker = parts.next()?;
+    let prefix = parts.next()?;
+    let secret = parts.next()?;
+    (marker == API_TOKEN_MARKER && !prefix.is_empty() && !secret.is_empty()).then_some(prefix)
+}
+
+fn hash_token(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+/// Generate a new token and its lookup prefix. The prefix is hex so it never contains `_`.
+fn new_token() -> (String, String) {
+    let mut prefix_raw = [0u8; PREFIX_BYTES];
+    OsRng.fill_bytes(&mut prefix_raw);
+    let prefix =
+        prefix_raw
+            .iter()
+            .fold(String::with_capacity(PREFIX_BYTES * 2), |mut acc, byte| {
+                let _ = write!(acc, "{byte:02x}");
+                acc
+            });
+
+    let mut secret = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut secret);
+    let token = format!(
+        "{API_TOKEN_MARKER}_{prefix}_{}",
+        URL_SAFE_NO_PAD.encode(secret)
+    );
+    (token, prefix)
+}
+
+#[derive(sqlx::FromRow)]
+struct ApiTokenRow {
+    id: Uuid,
+    name: String,
+    prefix: String,
+    scopes: Vec<String>,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiTokenRow> for ApiTokenSummary {
+    fn from(row: ApiTokenRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            prefix: format!("{API_TOKEN_MARKER}_{}", row.prefix),
+            scopes: parse_scopes(&row.scopes),
+            created_at: Timestamp(row.created_at),
+            expires_at: row.expires_at.map(Timestamp),
+            last_used_at: row.last_used_at.map(Timestamp),
+            revoked_at: row.revoked_at.map(Timestamp),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AuthenticatedTokenRow {
+    token_id: Uuid,
+    user_id: Uuid,
+    email: String,
+    username: String,
+    display_name: Option<String>,
+    roles: Vec<String>,
+    scopes: Vec<String>,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+fn parse_scopes(values: &[String]) -> Vec<ApiTokenScope> {
+    values
+        .iter()
+        .filter_map(|value| match ApiTokenScope::from_str(value) {
+            Ok(scope) => Some(scope),
+            Err(err) => {
+                warn!(error = %err, "ignoring unknown token scope");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Database-backed management and lookup of personal access tokens.
+#[derive(Clone)]
+pub struct ApiTokenService {
+    pool: PgPool,
+}
+
+impl ApiTokenService {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(name = "api_tokens.create", skip(self, request), err)]
+    pub async fn create(
+        &self,
+        actor: Uuid,
+        request: CreateApiTokenRequest,
+    ) -> ChatServiceResult<CreateApiTokenResponse> {
+        let CreateApiTokenRequest {
+            name,
+            mut scopes,
+            expires_in_days,
+        } = request;
+        scopes.sort_by_key(|scope| scope.as_str());
+        scopes.dedup();
+        let scope_names: Vec<&str> = scopes.iter().map(|scope| scope.as_str()).collect();
+        let expires_at = expires_in_days
+            .filter(|days| *days > 0)
+            .map(|days| Utc::now() + Duration::days(i64::from(days)));
+        let (token, prefix) = new_token();
+
+        let mut tx = begin_as(&self.pool, actor).await?;
+        let row = sqlx::query_as::<_, ApiTokenRow>(
+            "SELECT id, name, prefix, scopes, created_at, expires_at, last_used_at, revoked_at
+             FROM rustygpt.sp_create_api_token($1, $2, $3, $4, $5)",
+        )
+        .bind(name)
+        .bind(&prefix)
+        .bind(hash_token(&token))
+        .bind(&scope_names)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        Ok(CreateApiTokenResponse {
+            token,
+            summary: row.into(),
+        })
+    }
+
+    #[instrument(name = "api_tokens.list", skip(self), err)]
+    pub async fn list(&self, actor: Uuid) -> ChatServiceResult<Vec<ApiTokenSummary>> {
+        let mut tx = begin_as(&self.pool, actor).await?;
+        let rows = sqlx::query_as::<_, ApiTokenRow>(
+            "SELECT id, name, prefix, scopes, created_at, expires_at, last_used_at, revoked_at
+             FROM rustygpt.sp_list_api_tokens()",
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        Ok(rows.into_iter().map(ApiTokenSummary::from).collect())
+    }
+
+    #[instrument(name = "api_tokens.revoke", skip(self), err)]
+    pub async fn revoke(&self, actor: Uuid, token_id: Uuid) -> ChatServiceResult<bool> {
+        let mut tx = begin_as(&self.pool, actor).await?;
+        let revoked: bool = sqlx::query_scalar("SELECT rustygpt.sp_revoke_api_token($1)")
+            .bind(token_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(revoked)
+    }
+
+    /// Resolve a bearer token, recording its use. Returns `None` for unknown,
+    /// revoked or expired tokens and for disabled owners.
+    #[instrument(name = "api_tokens.authenticate", skip(self, token))]
+    pub async fn authenticate(
+        &self,
+        token: &str,
+        ip: Option<&str>,
+    ) -> Result<Option<ApiTokenPrincipal>, sqlx::Error> {
+        let Some(prefix) = token_prefix(token) else {
+            return Ok(None);
+        };
+
+        let row = sqlx::query_as::<_, AuthenticatedTokenRow>(
+            "SELECT token_id, user_id, email, username, display_name, roles, scopes,
+                    created_at, expires_at
+             FROM rustygpt.sp_authenticate_api_token($1, $2, $3)",
+        )
+        .bind(prefix)
+        .bind(hash_token(token))
+        .bind(ip)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| {
+            let mut roles: Vec<UserRole> = row
+                .roles
+                .iter()
+                .filter_map(|role| UserRole::from_str(role).ok())
+                .collect();
+            if roles.is_empty() {
+                roles.push(UserRole::Member);
+            }
+            ApiTokenPrincipal {
+                token_id: row.token_id,
+                user_id: row.user_id,
+                email: row.email,
+                username: row.username,
+                display_name: row.display_name,
+                roles,
+                scopes: parse_scopes(&row.scopes),
+                created_at: row.created_at,
+                expires_at: row.expires_at,
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::TestDatabase;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn generated_tokens_round_trip_their_prefix() {
+        let (token, prefix) = new_token();
+        assert!(token.starts_with("rgp_"));
+        assert_eq!(prefix.len(), PREFIX_BYTES * 2);
+        assert_eq!(token_prefix(&token), Some(prefix.as_str()));
+        assert_eq!(token_prefix("rgp_abcd"), None);
+        assert_eq!(token_prefix("ghp_abcd_secret"), None);
+    }
+
+    #[test]
+    fn bearer_token_requires_bearer_scheme() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer rgp_1234_secret"),
+        );
+        assert_eq!(bearer_token(&headers), Some("rgp_1234_secret"));
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic abc"));
+        assert_eq!(bearer_token(&headers), None);
+    }
+
+    #[test]
+    fn required_scope_follows_route_family() {
+        assert_eq!(
+            required_scope(&Method::POST, "/api/v1/chat/completions"),
+            Some(ApiTokenScope::V1Chat)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/api/admin/retention"),
+            Some(ApiTokenScope::Admin)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/api/messages/abc/reply"),
+            Some(ApiTokenScope::ThreadsWrite)
+        );
+        assert_eq!(required_scope(&Method::GET, "/api/threads/abc/tree"), None);
+    }
+
+    #[tokio::test]
+    async fn tokens_can_be_created_listed_and_revoked() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("token_owner").await;
+        let other = db.create_user("token_other").await;
+        let service = ApiTokenService::new(db.pool.clone());
+
+        let created = service
+            .create(
+                owner,
+                CreateApiTokenRequest {
+                    name: "ci".to_string(),
+                    scopes: vec![ApiTokenScope::ThreadsWrite, ApiTokenScope::ThreadsWrite],
+                    expires_in_days: Some(7),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.summary.scopes, vec![ApiTokenScope::ThreadsWrite]);
+        assert!(created.summary.expires_at.is_some());
+
+        let principal = service
+            .authenticate(&created.token, None)
+            .await
+            .unwrap()
+            .expect("new token authenticates");
+        assert_eq!(principal.user_id, owner);
+
+        let listed = service.list(owner).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, created.summary.id);
+        assert!(service.list(other).await.unwrap().is_empty());
+
+        assert!(matches!(
+            service.revoke(other, created.summary.id).await,
+            Err(ChatServiceError::NotFound(_))
+        ));
+        assert!(service.revoke(owner, created.summary.id).await.unwrap());
+        assert!(
+            service
+                .authenticate(&created.token, None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(service.list(owner).await.unwrap()[0].revoked_at.is_some());
+
+        db.destroy().await;
+    }
+}
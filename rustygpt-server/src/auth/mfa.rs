@@ -0,0 +1,281 @@
+//! TOTP enrollment, second-factor verification and the per-role 2FA policy.
+
+use std::str::FromStr;
+
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use shared::models::{MfaPolicy, MfaStatus, TotpEnrollment, UserRole};
+
+use crate::{
+    auth::totp,
+    services::{
+        audit_service::AuditContext,
+        chat_service::{ChatServiceError, ChatServiceResult},
+    },
+};
+
+/// Issuer shown by authenticator apps next to the account name.
+pub const TOTP_ISSUER: &str = "RustyGPT";
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub(crate) struct MfaState {
+    pub enabled: bool,
+    pub required: bool,
+    /// The active secret, or the pending one while enrollment is unconfirmed.
+    pub secret: Option<Vec<u8>>,
+    pub recovery_codes_remaining: i64,
+}
+
+pub(crate) async fn load_state(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<MfaState, sqlx::Error> {
+    sqlx::query_as::<_, MfaState>(
+        "SELECT enabled, required, secret, recovery_codes_remaining
+         FROM rustygpt.sp_mfa_state($1)",
+    )
+    .bind(user_id)
+    .fetch_one(conn)
+    .await
+}
+
+/// Generates and stores a pending secret for `account`.
+pub(crate) async fn stage_enrollment(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    account: &str,
+) -> Result<TotpEnrollment, sqlx::Error> {
+    let secret = totp::generate_secret();
+    sqlx::query("SELECT rustygpt.sp_mfa_totp_stage($1, $2)")
+        .bind(user_id)
+        .bind(&secret)
+        .execute(conn)
+        .await?;
+
+    Ok(TotpEnrollment {
+        secret: totp::encode_base32(&secret),
+        otpauth_uri: totp::provisioning_uri(TOTP_ISSUER, account, &secret),
+    })
+}
+
+/// Confirms a pending enrollment with its first code and returns fresh recovery codes, or
+/// `None` when the code does not match.
+pub(crate) async fn confirm_enrollment(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    secret: &[u8],
+    code: &str,
+) -> Result<Option<Vec<String>>, sqlx::Error> {
+    let Some(step) = totp::verify(secret, code, Utc::now().timestamp()) else {
+        return Ok(None);
+    };
+
+    let codes = totp::generate_recovery_codes();
+    let hashes: Vec<Vec<u8>> = codes
+        .iter()
+        .map(|code| totp::hash_recovery_code(code))
+        .collect();
+    let enabled = sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_mfa_totp_enable($1, $2, $3)")
+        .bind(user_id)
+        .bind(step)
+        .bind(&hashes)
+        .fetch_one(conn)
+        .await?;
+
+    Ok(enabled.then_some(codes))
+}
+
+/// Accepts a current TOTP code (at most once per time step) or an unused recovery code.
+pub(crate) async fn verify_second_factor(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    state: &MfaState,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    if let Some(secret) = state.secret.as_deref().filter(|_| state.enabled)
+        && let Some(step) = totp::verify(secret, code, Utc::now().timestamp())
+    {
+        return sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_mfa_totp_accept_step($1, $2)")
+            .bind(user_id)
+            .bind(step)
+            .fetch_one(&mut *conn)
+            .await;
+    }
+
+    sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_mfa_recovery_consume($1, $2)")
+        .bind(user_id)
+        .bind(totp::hash_recovery_code(code))
+        .fetch_one(conn)
+        .await
+}
+
+fn invalid_code() -> ChatServiceError {
+    ChatServiceError::Validation("invalid two-factor code".to_string())
+}
+
+/// Self-service 2FA management for signed-in users, plus the admin role policy.
+#[derive(Clone)]
+pub struct MfaService {
+    pool: PgPool,
+}
+
+impl MfaService {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(name = "mfa.status", skip(self), err)]
+    pub async fn status(&self, user_id: Uuid) -> ChatServiceResult<MfaStatus> {
+        let mut conn = self.pool.acquire().await.map_err(ChatServiceError::from)?;
+        let state = load_state(&mut conn, user_id)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(MfaStatus {
+            enabled: state.enabled,
+            required: state.required,
+            recovery_codes_remaining: state.recovery_codes_remaining,
+        })
+    }
+
+    /// Starts (or restarts) enrollment; 2FA stays off until [`Self::confirm`] succeeds.
+    #[instrument(name = "mfa.enroll", skip(self), err)]
+    pub async fn enroll(&self, user_id: Uuid, account: &str) -> ChatServiceResult<TotpEnrollment> {
+        let mut conn = self.pool.acquire().await.map_err(ChatServiceError::from)?;
+        stage_enrollment(&mut conn, user_id, account)
+            .await
+            .map_err(ChatServiceError::from_db_error)
+    }
+
+    #[instrument(name = "mfa.confirm", skip(self, code), err)]
+    pub async fn confirm(&self, user_id: Uuid, code: &str) -> ChatServiceResult<Vec<String>> {
+        let mut conn = self.pool.acquire().await.map_err(ChatServiceError::from)?;
+        let state = load_state(&mut conn, user_id)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        if state.enabled {
+            return Err(ChatServiceError::Validation(
+                "two-factor authentication is already enabled".to_string(),
+            ));
+        }
+        let secret = state.secret.ok_or_else(|| {
+            ChatServiceError::NotFound("no two-factor enrollment in progress".to_string())
+        })?;
+
+        confirm_enrollment(&mut conn, user_id, &secret, code)
+            .await
+            .map_err(ChatServiceError::from_db_error)?
+            .ok_or_else(invalid_code)
+    }
+
+    /// Turns 2FA off after checking a current code; refused when a role requires it.
+    #[instrument(name = "mfa.disable", skip(self, code), err)]
+    pub async fn disable(&self, user_id: Uuid, code: &str) -> ChatServiceResult<()> {
+        let mut conn = self.pool.acquire().await.map_err(ChatServiceError::from)?;
+        Self::require_code(&mut conn, user_id, code).await?;
+
+        sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_mfa_disable($1)")
+            .bind(user_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(())
+    }
+
+    /// Replaces every recovery code after checking a current code.
+    #[instrument(name = "mfa.recovery_codes", skip(self, code), err)]
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: Uuid,
+        code: &str,
+    ) -> ChatServiceResult<Vec<String>> {
+        let mut conn = self.pool.acquire().await.map_err(ChatServiceError::from)?;
+        Self::require_code(&mut conn, user_id, code).await?;
+
+        let codes = totp::generate_recovery_codes();
+        let hashes: Vec<Vec<u8>> = codes
+            .iter()
+            .map(|code| totp::hash_recovery_code(code))
+            .collect();
+        sqlx::query("SELECT rustygpt.sp_mfa_recovery_replace($1, $2)")
+            .bind(user_id)
+            .bind(&hashes)
+            .execute(&mut *conn)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(codes)
+    }
+
+    async fn require_code(
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        code: &str,
+    ) -> ChatServiceResult<()> {
+        let state = load_state(conn, user_id)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        if !state.enabled {
+            return Err(ChatServiceError::Validation(
+                "two-factor authentication is not enabled".to_string(),
+            ));
+        }
+        if verify_second_factor(conn, user_id, &state, code)
+            .await
+            .map_err(ChatServiceError::from_db_error)?
+        {
+            Ok(())
+        } else {
+            Err(invalid_code())
+        }
+    }
+
+    #[instrument(name = "mfa.policy", skip(self), err)]
+    pub async fn policy(&self) -> ChatServiceResult<MfaPolicy> {
+        let roles =
+            sqlx::query_scalar::<_, String>("SELECT role FROM rustygpt.sp_mfa_policy_list()")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+
+        Ok(MfaPolicy {
+            required_roles: roles
+                .iter()
+                .filter_map(|role| match UserRole::from_str(role) {
+                    Ok(role) => Some(role),
+                    Err(err) => {
+                        warn!(error = err, role = %role, "ignoring unknown role in 2FA policy");
+                        None
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    #[instrument(name = "mfa.set_policy", skip(self, audit), err)]
+    pub async fn set_policy(
+        &self,
+        audit: &AuditContext,
+        policy: &MfaPolicy,
+    ) -> ChatServiceResult<MfaPolicy> {
+        let roles: Vec<&str> = policy
+            .required_roles
+            .iter()
+            .map(|role| role.as_str())
+            .collect();
+
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        sqlx::query("SELECT rustygpt.sp_mfa_policy_set($1::TEXT[])")
+            .bind(&roles)
+            .execute(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        self.policy().await
+    }
+}
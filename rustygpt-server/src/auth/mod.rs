@@ -1 +1,7 @@
+pub mod account;
+pub mod api_tokens;
+pub mod mfa;
+pub mod oidc;
 pub mod session;
+pub mod totp;
+pub mod user_admin;
@@ -0,0 +1,846 @@
+//! Generic `OpenID` Connect relying party.
+//!
+//! Providers come from `[oauth.providers.<name>]`. Endpoints are discovered from the issuer,
+//! logins use the authorization code flow with PKCE, and ID tokens are checked against the
+//! provider's JWKS, the configured client id and the nonce stored with the login request.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header, jwk::JwkSet};
+use oauth2::{
+    PkceCodeChallenge,
+    url::{Url, form_urlencoded},
+};
+use reqwest::header::ACCEPT;
+use serde::{Deserialize, Deserializer};
+use shared::config::server::{Config, OidcProviderConfig, OidcTokenAuthMethod};
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::account::{hash_token, new_token},
+    services::{
+        audit_service::AuditContext,
+        chat_service::{ChatServiceError, ChatServiceResult},
+    },
+};
+
+/// How long a user has to finish signing in at the provider.
+pub const LOGIN_REQUEST_TTL_SECONDS: i32 = 600;
+const DISCOVERY_TTL: Duration = Duration::from_secs(3600);
+/// Unknown key ids trigger a JWKS refetch, at most this often.
+const JWKS_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+const CLOCK_SKEW_SECONDS: u64 = 60;
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_ERROR_BODY: usize = 200;
+
+#[derive(Debug, Error)]
+pub enum OidcError {
+    #[error("unknown identity provider '{0}'")]
+    UnknownProvider(String),
+    #[error("identity provider request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("invalid discovery document: {0}")]
+    Discovery(String),
+    #[error("token request failed: {0}")]
+    TokenExchange(String),
+    #[error("invalid ID token: {0}")]
+    InvalidIdToken(String),
+}
+
+/// The subset of the discovery document this client uses.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: Url,
+    pub token_endpoint: Url,
+    pub jwks_uri: Url,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<Url>,
+}
+
+struct Discovered {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+    fetched_at: Instant,
+    jwks_fetched_at: Instant,
+}
+
+/// Where to send the browser, plus the secrets to keep until the callback.
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+    pub url: Url,
+    pub state: String,
+    pub nonce: String,
+    pub pkce_verifier: String,
+}
+
+/// Claims of a verified ID token, topped up from the userinfo endpoint when needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedIdentity {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub username: Option<String>,
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    #[serde(default)]
+    nonce: Option<String>,
+    #[serde(default)]
+    azp: Option<String>,
+    #[serde(flatten)]
+    profile: ProfileClaims,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ProfileClaims {
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default, deserialize_with = "lenient_bool")]
+    email_verified: Option<bool>,
+    #[serde(default)]
+    preferred_username: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    sub: String,
+    #[serde(flatten)]
+    profile: ProfileClaims,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    id_token: Option<String>,
+}
+
+/// Some providers (Cognito, older ADFS) send `email_verified` as a string.
+fn lenient_bool<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Text(String),
+    }
+
+    Ok(match Option::<Flag>::deserialize(deserializer)? {
+        Some(Flag::Bool(value)) => Some(value),
+        Some(Flag::Text(value)) => Some(value.eq_ignore_ascii_case("true")),
+        None => None,
+    })
+}
+
+fn form_encode(value: &str) -> String {
+    form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+fn truncate(mut body: String) -> String {
+    if body.len() > MAX_ERROR_BODY {
+        let mut end = MAX_ERROR_BODY;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+    }
+    body
+}
+
+/// One configured provider with its discovery document and keys cached.
+pub struct OidcProvider {
+    name: String,
+    config: OidcProviderConfig,
+    http: reqwest::Client,
+    cache: RwLock<Option<Discovered>>,
+}
+
+impl OidcProvider {
+    #[must_use]
+    pub fn new(name: &str, config: OidcProviderConfig) -> Self {
+        let http = reqwest::Client::builder()
+            // Following redirects opens the client up to SSRF vulnerabilities.
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .expect("Client should build");
+        Self {
+            name: name.to_string(),
+            config,
+            http,
+            cache: RwLock::new(None),
+        }
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &OidcProviderConfig {
+        &self.config
+    }
+
+    fn discovery_url(&self) -> String {
+        format!(
+            "{}/.well-known/openid-configuration",
+            self.config.issuer.as_str().trim_end_matches('/')
+        )
+    }
+
+    async fn fetch_jwks(&self, uri: &Url) -> Result<JwkSet, OidcError> {
+        Ok(self
+            .http
+            .get(uri.clone())
+            .header(ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    async fn discover(&self) -> Result<Discovered, OidcError> {
+        let metadata: ProviderMetadata = self
+            .http
+            .get(self.discovery_url())
+            .header(ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        // The document must describe the issuer we were configured with, not another one.
+        if metadata.issuer.trim_end_matches('/')
+            != self.config.issuer.as_str().trim_end_matches('/')
+        {
+            return Err(OidcError::Discovery(format!(
+                "issuer '{}' does not match configured issuer '{}'",
+                metadata.issuer, self.config.issuer
+            )));
+        }
+        let jwks = self.fetch_jwks(&metadata.jwks_uri).await?;
+        let now = Instant::now();
+        Ok(Discovered {
+            metadata,
+            jwks,
+            fetched_at: now,
+            jwks_fetched_at: now,
+        })
+    }
+
+    /// Returns the discovery document, fetching it on first use and after [`DISCOVERY_TTL`].
+    ///
+    /// # Errors
+    /// Returns an error if the document cannot be fetched or names a different issuer.
+    pub async fn metadata(&self) -> Result<ProviderMetadata, OidcError> {
+        if let Some(cached) = self.cache.read().await.as_ref()
+            && cached.fetched_at.elapsed() < DISCOVERY_TTL
+        {
+            return Ok(cached.metadata.clone());
+        }
+        let discovered = self.discover().await?;
+        let metadata = discovered.metadata.clone();
+        *self.cache.write().await = Some(discovered);
+        Ok(metadata)
+    }
+
+    fn scopes(&self) -> String {
+        let mut scopes = vec!["openid"];
+        scopes.extend(
+            self.config
+                .scopes
+                .iter()
+                .map(String::as_str)
+                .filter(|scope| *scope != "openid"),
+        );
+        scopes.join(" ")
+    }
+
+    /// Builds the authorization URL with a fresh state, nonce and PKCE challenge.
+    ///
+    /// # Errors
+    /// Returns an error if discovery fails.
+    pub async fn authorization_request(
+        &self,
+        redirect_uri: &str,
+    ) -> Result<AuthorizationRequest, OidcError> {
+        let metadata = self.metadata().await?;
+        let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+        let (state, _) = new_token();
+        let (nonce, _) = new_token();
+
+        let mut url = metadata.authorization_endpoint;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("scope", &self.scopes())
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", challenge.as_str())
+            .append_pair("code_challenge_method", challenge.method().as_str());
+
+        Ok(AuthorizationRequest {
+            url,
+            state,
+            nonce,
+            pkce_verifier: verifier.secret().clone(),
+        })
+    }
+
+    /// Redeems an authorization code and returns the verified identity.
+    ///
+    /// # Errors
+    /// Returns an error if the token request fails or the ID token does not verify.
+    #[instrument(skip_all, fields(provider = %self.name))]
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: &str,
+        redirect_uri: &str,
+        nonce: &str,
+    ) -> Result<VerifiedIdentity, OidcError> {
+        let metadata = self.metadata().await?;
+        let client_id = self.config.client_id.as_str();
+        let secret = self.config.client_secret.expose();
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+            ("code_verifier", pkce_verifier),
+        ];
+        let mut request = self
+            .http
+            .post(metadata.token_endpoint.clone())
+            .header(ACCEPT, "application/json");
+        if secret.is_empty() {
+            form.push(("client_id", client_id));
+        } else {
+            match self.config.token_auth_method {
+                OidcTokenAuthMethod::ClientSecretBasic => {
+                    request = request.basic_auth(form_encode(client_id), Some(form_encode(secret)));
+                }
+                OidcTokenAuthMethod::ClientSecretPost => {
+                    form.push(("client_id", client_id));
+                    form.push(("client_secret", secret));
+                }
+            }
+        }
+
+        let response = request.form(&form).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(OidcError::TokenExchange(format!(
+                "{status}: {}",
+                truncate(body)
+            )));
+        }
+        let tokens: TokenResponse = response.json().await?;
+        let id_token = tokens.id_token.ok_or_else(|| {
+            OidcError::TokenExchange("response did not include an id_token".into())
+        })?;
+
+        let claims = self.verify_id_token(&metadata, &id_token, nonce).await?;
+        let mut profile = claims.profile;
+        if profile.email.is_none()
+            && let Some(endpoint) = &metadata.userinfo_endpoint
+        {
+            let info: UserInfo = self
+                .http
+                .get(endpoint.clone())
+                .bearer_auth(&tokens.access_token)
+                .header(ACCEPT, "application/json")
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            if info.sub != claims.sub {
+                return Err(OidcError::InvalidIdToken(
+                    "userinfo subject does not match the ID token".into(),
+                ));
+            }
+            profile = ProfileClaims {
+                email: info.profile.email,
+                email_verified: info.profile.email_verified,
+                preferred_username: profile
+                    .preferred_username
+                    .or(info.profile.preferred_username),
+                name: profile.name.or(info.profile.name),
+            };
+        }
+
+        Ok(VerifiedIdentity {
+            subject: claims.sub,
+            email: profile.email,
+            email_verified: profile.email_verified.unwrap_or(false),
+            username: profile.preferred_username,
+            display_name: profile.name,
+        })
+    }
+
+    async fn decoding_key(&self, kid: Option<&str>) -> Result<DecodingKey, OidcError> {
+        let find = |jwks: &JwkSet| match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+
+        let (jwk, refetch) = {
+            let cache = self.cache.read().await;
+            let cached = cache
+                .as_ref()
+                .ok_or_else(|| OidcError::Discovery("provider has not been discovered".into()))?;
+            (
+                find(&cached.jwks),
+                cached.jwks_fetched_at.elapsed() >= JWKS_REFETCH_INTERVAL,
+            )
+        };
+
+        let jwk = match jwk {
+            Some(jwk) => jwk,
+            // Providers rotate keys; a new kid usually means the cached set is stale.
+            None if refetch => {
+                debug!(provider = %self.name, ?kid, "unknown signing key; refetching JWKS");
+                let mut cache = self.cache.write().await;
+                let cached = cache.as_mut().ok_or_else(|| {
+                    OidcError::Discovery("provider has not been discovered".into())
+                })?;
+                cached.jwks = self.fetch_jwks(&cached.metadata.jwks_uri).await?;
+                cached.jwks_fetched_at = Instant::now();
+                find(&cached.jwks).ok_or_else(|| {
+                    OidcError::InvalidIdToken(format!("no signing key matches kid {kid:?}"))
+                })?
+            }
+            None => {
+                return Err(OidcError::InvalidIdToken(format!(
+                    "no signing key matches kid {kid:?}"
+                )));
+            }
+        };
+
+        DecodingKey::from_jwk(&jwk).map_err(|err| OidcError::InvalidIdToken(err.to_string()))
+    }
+
+    async fn verify_id_token(
+        &self,
+        metadata: &ProviderMetadata,
+        token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let header =
+            decode_header(token).map_err(|err| OidcError::InvalidIdToken(err.to_string()))?;
+        // Symmetric algorithms would let anyone holding the client secret mint tokens.
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(OidcError::InvalidIdToken(format!(
+                "unsupported signing algorithm {:?}",
+                header.alg
+            )));
+        }
+        let key = self.decoding_key(header.kid.as_deref()).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[metadata.issuer.as_str()]);
+        validation.set_audience(&[self.config.client_id.as_str()]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = CLOCK_SKEW_SECONDS;
+
+        let claims = decode::<IdTokenClaims>(token, &key, &validation)
+            .map_err(|err| OidcError::InvalidIdToken(err.to_string()))?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidIdToken("nonce mismatch".into()));
+        }
+        if let Some(azp) = &claims.azp
+            && azp != &self.config.client_id
+        {
+            return Err(OidcError::InvalidIdToken(format!(
+                "token was issued to '{azp}'"
+            )));
+        }
+        Ok(claims)
+    }
+}
+
+/// Every configured provider, keyed by name.
+#[derive(Default)]
+pub struct OidcRegistry {
+    providers: BTreeMap<String, Arc<OidcProvider>>,
+}
+
+pub type SharedOidcRegistry = Arc<OidcRegistry>;
+
+impl OidcRegistry {
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            providers: config
+                .oauth
+                .providers
+                .iter()
+                .map(|(name, provider)| {
+                    (
+                        name.clone(),
+                        Arc::new(OidcProvider::new(name, provider.clone())),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// # Errors
+    /// Returns [`OidcError::UnknownProvider`] if no provider has that name.
+    pub fn get(&self, name: &str) -> Result<Arc<OidcProvider>, OidcError> {
+        self.providers
+            .get(name)
+            .cloned()
+            .ok_or_else(|| OidcError::UnknownProvider(name.to_string()))
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &Arc<OidcProvider>> {
+        self.providers.values()
+    }
+}
+
+/// Login request restored at the callback.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PendingLogin {
+    pub nonce: String,
+    pub pkce_verifier: String,
+    pub return_to: String,
+}
+
+/// Local account an identity resolved to, and whether it was `existing`, `linked` or `created`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ResolvedUser {
+    pub user_id: Uuid,
+    pub outcome: String,
+}
+
+/// Persists state, nonce and PKCE verifier until the provider redirects back.
+///
+/// # Errors
+/// Returns an error if the database write fails.
+pub async fn store_login_request(
+    pool: &PgPool,
+    provider: &str,
+    request: &AuthorizationRequest,
+    return_to: &str,
+) -> ChatServiceResult<()> {
+    sqlx::query("SELECT rustygpt.sp_oidc_request_create($1, $2, $3, $4, $5, $6)")
+        .bind(hash_token(&request.state))
+        .bind(provider)
+        .bind(&request.nonce)
+        .bind(&request.pkce_verifier)
+        .bind(return_to)
+        .bind(LOGIN_REQUEST_TTL_SECONDS)
+        .execute(pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+    Ok(())
+}
+
+/// Redeems a login request; each state works once and only for the provider that issued it.
+///
+/// # Errors
+/// Returns an error if the database read fails.
+pub async fn consume_login_request(
+    pool: &PgPool,
+    provider: &str,
+    state: &str,
+) -> ChatServiceResult<Option<PendingLogin>> {
+    sqlx::query_as::<_, PendingLogin>(
+        "SELECT nonce, pkce_verifier, return_to FROM rustygpt.sp_oidc_request_consume($1, $2)",
+    )
+    .bind(hash_token(state))
+    .bind(provider)
+    .fetch_optional(pool)
+    .await
+    .map_err(ChatServiceError::from_db_error)
+}
+
+/// Finds, links or creates the local account for a verified identity.
+///
+/// # Errors
+/// Returns `Forbidden` when the email belongs to an account that may not be linked or sign-up
+/// is disabled, and `Validation` when the provider sent no email for a new identity.
+#[instrument(name = "oidc.resolve_user", skip(pool, audit, config, identity), err)]
+pub async fn resolve_user(
+    pool: &PgPool,
+    audit: &AuditContext,
+    provider: &str,
+    config: &OidcProviderConfig,
+    identity: &VerifiedIdentity,
+) -> ChatServiceResult<ResolvedUser> {
+    let mut tx = audit.begin(pool).await.map_err(ChatServiceError::from)?;
+    let resolved = sqlx::query_as::<_, ResolvedUser>(
+        "SELECT user_id, outcome
+         FROM rustygpt.sp_oidc_resolve_user($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+    .bind(provider)
+    .bind(&identity.subject)
+    .bind(identity.email.as_deref())
+    .bind(identity.email_verified)
+    .bind(identity.username.as_deref())
+    .bind(identity.display_name.as_deref())
+    .bind(config.link_existing_users)
+    .bind(config.allow_signup)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ChatServiceError::from_db_error)?;
+    tx.commit().await.map_err(ChatServiceError::from)?;
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Form, Json, Router, extract::State, http::StatusCode, routing::get};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use serde_json::{Value, json};
+    use shared::config::server::SecretString;
+    use std::{
+        collections::HashMap,
+        sync::Mutex,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    const CLIENT_ID: &str = "rustygpt";
+    const SIGNING_KEY: &[u8] = include_bytes!("../../tests/fixtures/oidc_test_key.pem");
+    const JWKS: &str = include_str!("../../tests/fixtures/oidc_test_jwks.json");
+
+    /// A minimal identity provider: discovery, JWKS and a token endpoint that signs whatever
+    /// claims the test asks for.
+    #[derive(Clone)]
+    struct MockIdp {
+        issuer: String,
+        claims: Arc<Mutex<Value>>,
+        token_requests: Arc<Mutex<Vec<HashMap<String, String>>>>,
+    }
+
+    impl MockIdp {
+        async fn start() -> Self {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let issuer = format!("http://{}/realms/test", listener.local_addr().unwrap());
+            let idp = Self {
+                issuer,
+                claims: Arc::new(Mutex::new(json!({}))),
+                token_requests: Arc::new(Mutex::new(Vec::new())),
+            };
+            let app = Router::new()
+                .route(
+                    "/realms/test/.well-known/openid-configuration",
+                    get(discovery),
+                )
+                .route("/realms/test/certs", get(|| async { JWKS }))
+                .route("/realms/test/token", axum::routing::post(token))
+                .with_state(idp.clone());
+            tokio::spawn(async move { axum::serve(listener, app).await });
+            idp
+        }
+
+        fn set_claims(&self, claims: Value) {
+            *self.claims.lock().unwrap() = claims;
+        }
+
+        fn provider(&self) -> OidcProvider {
+            OidcProvider::new("test", provider_config(&self.issuer))
+        }
+    }
+
+    async fn discovery(State(idp): State<MockIdp>) -> Json<Value> {
+        Json(json!({
+            "issuer": idp.issuer,
+            "authorization_endpoint": format!("{}/auth", idp.issuer),
+            "token_endpoint": format!("{}/token", idp.issuer),
+            "jwks_uri": format!("{}/certs", idp.issuer),
+        }))
+    }
+
+    async fn token(
+        State(idp): State<MockIdp>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        if form.get("code_verifier").is_none_or(String::is_empty) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        idp.token_requests.lock().unwrap().push(form);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut claims = json!({
+            "iss": idp.issuer,
+            "aud": CLIENT_ID,
+            "sub": "user-1",
+            "iat": now,
+            "exp": now + 300,
+        });
+        for (key, value) in idp.claims.lock().unwrap().as_object().unwrap() {
+            claims[key] = value.clone();
+        }
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some("test-key".into());
+        let id_token = encode(
+            &header,
+            &claims,
+            &EncodingKey::from_rsa_pem(SIGNING_KEY).unwrap(),
+        )
+        .unwrap();
+
+        Ok(Json(json!({
+            "access_token": "access",
+            "token_type": "Bearer",
+            "id_token": id_token,
+        })))
+    }
+
+    fn provider_config(issuer: &str) -> OidcProviderConfig {
+        OidcProviderConfig {
+            display_name: "Test".into(),
+            issuer: Url::parse(issuer).unwrap(),
+            client_id: CLIENT_ID.into(),
+            client_secret: SecretString::new("secret".into()),
+            token_auth_method: OidcTokenAuthMethod::ClientSecretPost,
+            scopes: vec!["email".into()],
+            link_existing_users: true,
+            allow_signup: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn authorization_url_carries_state_nonce_and_pkce() {
+        let idp = MockIdp::start().await;
+        let provider = idp.provider();
+
+        let request = provider
+            .authorization_request("http://app.test/callback")
+            .await
+            .expect("authorization request");
+
+        let query: HashMap<_, _> = request.url.query_pairs().into_owned().collect();
+        assert!(
+            request
+                .url
+                .as_str()
+                .starts_with(&format!("{}/auth?", idp.issuer))
+        );
+        assert_eq!(query["response_type"], "code");
+        assert_eq!(query["client_id"], CLIENT_ID);
+        assert_eq!(query["scope"], "openid email");
+        assert_eq!(query["state"], request.state);
+        assert_eq!(query["nonce"], request.nonce);
+        assert_eq!(query["code_challenge_method"], "S256");
+        assert_ne!(query["code_challenge"], request.pkce_verifier);
+    }
+
+    #[tokio::test]
+    async fn exchange_code_returns_verified_identity() {
+        let idp = MockIdp::start().await;
+        idp.set_claims(json!({
+            "nonce": "n-1",
+            "email": "ada@example.com",
+            "email_verified": "true",
+            "preferred_username": "ada",
+            "name": "Ada Lovelace",
+        }));
+        let provider = idp.provider();
+
+        let identity = provider
+            .exchange_code("code", "verifier", "http://app.test/callback", "n-1")
+            .await
+            .expect("identity");
+
+        assert_eq!(
+            identity,
+            VerifiedIdentity {
+                subject: "user-1".into(),
+                email: Some("ada@example.com".into()),
+                email_verified: true,
+                username: Some("ada".into()),
+                display_name: Some("Ada Lovelace".into()),
+            }
+        );
+        let requests = idp.token_requests.lock().unwrap();
+        assert_eq!(requests[0]["code_verifier"], "verifier");
+        assert_eq!(requests[0]["client_secret"], "secret");
+    }
+
+    #[tokio::test]
+    async fn id_token_with_another_nonce_is_rejected() {
+        let idp = MockIdp::start().await;
+        idp.set_claims(json!({ "nonce": "replayed" }));
+
+        let err = idp
+            .provider()
+            .exchange_code("code", "verifier", "http://app.test/callback", "n-1")
+            .await
+            .expect_err("nonce mismatch");
+        assert!(matches!(err, OidcError::InvalidIdToken(message) if message.contains("nonce")));
+    }
+
+    #[tokio::test]
+    async fn id_token_for_another_client_is_rejected() {
+        let idp = MockIdp::start().await;
+        idp.set_claims(json!({ "nonce": "n-1", "aud": "someone-else" }));
+
+        let err = idp
+            .provider()
+            .exchange_code("code", "verifier", "http://app.test/callback", "n-1")
+            .await
+            .expect_err("audience mismatch");
+        assert!(matches!(err, OidcError::InvalidIdToken(_)));
+    }
+
+    #[tokio::test]
+    async fn expired_id_token_is_rejected() {
+        let idp = MockIdp::start().await;
+        idp.set_claims(json!({ "nonce": "n-1", "exp": 1_000_000 }));
+
+        let err = idp
+            .provider()
+            .exchange_code("code", "verifier", "http://app.test/callback", "n-1")
+            .await
+            .expect_err("expired");
+        assert!(matches!(err, OidcError::InvalidIdToken(_)));
+    }
+
+    #[tokio::test]
+    async fn missing_discovery_document_is_an_error() {
+        let idp = MockIdp::start().await;
+        let provider = OidcProvider::new(
+            "test",
+            OidcProviderConfig {
+                issuer: Url::parse(&format!("{}/../other", idp.issuer)).unwrap(),
+                ..provider_config(&idp.issuer)
+            },
+        );
+
+        assert!(provider.metadata().await.is_err());
+    }
+}
@@ -1,4 +1,6 @@
-use std::{collections::HashMap, fmt, net::IpAddr, str::FromStr, sync::Arc};
+use std::{
+    collections::HashMap, fmt, net::IpAddr, str::FromStr, sync::Arc, time::Duration as StdDuration,
+};
 
 use argon2::password_hash::rand_core::{OsRng, RngCore};
 use argon2::{
@@ -20,13 +22,28 @@ use uuid::Uuid;
 
 use shared::{
     config::server::{Config, CookieSameSite},
-    models::UserRole,
+    models::{TotpEnrollment, UserRole},
+};
+
+use crate::{
+    auth::mfa,
+    services::{
+        audit_service::{self, AuditContext, NewAuditEvent},
+        mail_templates::{MailTemplate, describe_ttl},
+        mailer,
+    },
 };
 
 const LOGIN_METRIC_NAME: &str = "rustygpt_auth_logins_total";
 const ROTATION_METRIC_NAME: &str = "rustygpt_auth_session_rotations_total";
 const ACTIVE_SESSIONS_METRIC_NAME: &str = "rustygpt_auth_active_sessions";
+const LOCKOUT_METRIC_NAME: &str = "rustygpt_auth_lockouts_total";
+/// Upper bound for the delay added to rejected logins, however many failures preceded them.
+const MAX_FAILURE_DELAY_MS: u64 = 5_000;
 const ALL_ROLES: &[UserRole] = &[UserRole::Admin, UserRole::Member, UserRole::ReadOnly];
+/// How long a password-verified login may wait for its second factor.
+const MFA_CHALLENGE_TTL_SECONDS: i32 = 300;
+const MFA_CHALLENGE_MAX_ATTEMPTS: i32 = 5;
 
 /// Errors produced by the session subsystem.
 #[derive(Debug, Error)]
@@ -37,6 +54,8 @@ pub enum SessionError {
     InvalidCredentials,
     #[error("user account disabled")]
     DisabledUser,
+    #[error("account temporarily locked")]
+    AccountLocked { until: DateTime<Utc> },
     #[error("session expired")]
     SessionExpired,
     #[error("session absolute lifetime exceeded")]
@@ -45,6 +64,14 @@ pub enum SessionError {
     RotationRequired,
     #[error("suspicious session activity")]
     SuspiciousActivity,
+    #[error("invalid two-factor code")]
+    InvalidMfaCode,
+    #[error("two-factor challenge expired or invalid")]
+    MfaChallengeInvalid,
+    #[error("session not found")]
+    SessionNotFound,
+    #[error("user not found")]
+    UserNotFound,
     #[error("database error: {0}")]
     Database(#[from] sqlx::Error),
     #[error("time conversion error: {0}")]
@@ -57,6 +84,8 @@ pub struct SessionMetadata {
     pub user_agent: Option<String>,
     pub ip: Option<String>,
     pub fingerprint: Option<String>,
+    /// Correlates audit events with the request; never persisted on the session.
+    pub request_id: Option<String>,
 }
 
 impl SessionMetadata {
@@ -84,6 +113,12 @@ impl SessionMetadata {
         self
     }
 
+    #[must_use]
+    pub fn with_request_id<T: Into<Option<String>>>(mut self, value: T) -> Self {
+        self.request_id = value.into().filter(|id| !id.is_empty());
+        self
+    }
+
     #[must_use]
     pub fn as_json(&self) -> serde_json::Value {
         json!({
@@ -172,6 +207,42 @@ pub struct SessionValidation {
     pub rotated: bool,
 }
 
+/// A freshly issued session, plus recovery codes when the login completed 2FA enrollment.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedLogin {
+    pub user: SessionUser,
+    pub bundle: SessionBundle,
+    pub recovery_codes: Vec<String>,
+}
+
+/// Second step required before a password-verified login gets a session.
+#[derive(Debug, Clone)]
+pub struct MfaChallenge {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    /// Set when a role policy requires 2FA and the user has not enrolled yet.
+    pub enrollment: Option<TotpEnrollment>,
+}
+
+/// An active session as listed for its owner or an admin.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SessionRecord {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub absolute_expires_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+/// Result of the password step of a login.
+#[derive(Debug, Clone)]
+pub enum LoginOutcome {
+    Authenticated(Box<AuthenticatedLogin>),
+    MfaRequired(MfaChallenge),
+}
+
 #[async_trait]
 pub trait SessionManager: Send + Sync {
     async fn authenticate(
@@ -179,7 +250,21 @@ pub trait SessionManager: Send + Sync {
         identifier: &str,
         password: &str,
         metadata: &SessionMetadata,
-    ) -> Result<(SessionUser, SessionBundle), SessionError>;
+    ) -> Result<LoginOutcome, SessionError>;
+
+    async fn complete_mfa_login(
+        &self,
+        challenge_token: &str,
+        code: &str,
+        metadata: &SessionMetadata,
+    ) -> Result<AuthenticatedLogin, SessionError>;
+
+    async fn authenticate_federated(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        metadata: &SessionMetadata,
+    ) -> Result<LoginOutcome, SessionError>;
 
     async fn validate_session(
         &self,
@@ -197,6 +282,7 @@ pub trait SessionManager: Send + Sync {
         &self,
         session_id: Uuid,
         reason: Option<&str>,
+        metadata: &SessionMetadata,
     ) -> Result<(), SessionError>;
 
     async fn mark_user_for_rotation(
@@ -204,6 +290,31 @@ pub trait SessionManager: Send + Sync {
         user_id: Uuid,
         reason: &str,
     ) -> Result<i64, SessionError>;
+
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<SessionRecord>, SessionError>;
+
+    async fn revoke_user_session(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        actor: Uuid,
+        metadata: &SessionMetadata,
+    ) -> Result<(), SessionError>;
+
+    async fn revoke_user_sessions(
+        &self,
+        user_id: Uuid,
+        keep: Option<Uuid>,
+        actor: Uuid,
+        metadata: &SessionMetadata,
+    ) -> Result<u64, SessionError>;
+
+    async fn unlock_user(
+        &self,
+        user_id: Uuid,
+        actor: Uuid,
+        metadata: &SessionMetadata,
+    ) -> Result<Option<DateTime<Utc>>, SessionError>;
 }
 
 /// Database-backed session manager.
@@ -320,13 +431,111 @@ impl SessionService {
             .map(|value| i32::try_from(value).unwrap_or(i32::MAX))
     }
 
+    /// Delay before answering a rejected login; grows with consecutive failures.
+    fn failure_delay(&self, failures: i32) -> StdDuration {
+        let failures = u64::try_from(failures.max(1)).unwrap_or(1);
+        StdDuration::from_millis(
+            self.config
+                .auth
+                .failure_delay_ms
+                .saturating_mul(failures)
+                .min(MAX_FAILURE_DELAY_MS),
+        )
+    }
+
+    /// Counts a wrong password or second-factor code and, once `auth.lockout_threshold` is
+    /// reached, locks the account and tells its owner. Failures to record are logged: the login
+    /// is rejected either way.
+    async fn record_login_failure(
+        &self,
+        conn: &mut PoolConnection<Postgres>,
+        row: &CredentialRow,
+        metadata: &SessionMetadata,
+    ) -> Option<LoginFailureRow> {
+        let auth = &self.config.auth;
+        let failure = sqlx::query_as::<_, LoginFailureRow>(
+            "SELECT failed_count, locked_until FROM rustygpt.sp_auth_login_failure($1, $2, $3, $4)",
+        )
+        .bind(row.id)
+        .bind(i32::try_from(auth.lockout_threshold).unwrap_or(i32::MAX))
+        .bind(i32::try_from(auth.lockout_base_seconds).unwrap_or(i32::MAX))
+        .bind(i32::try_from(auth.lockout_max_seconds).unwrap_or(i32::MAX))
+        .fetch_optional(conn.as_mut())
+        .await
+        .inspect_err(|err| warn!(error = %err, "failed to record failed login"))
+        .ok()
+        .flatten()?;
+
+        if let Some(until) = failure.locked_until {
+            metrics::counter!(LOCKOUT_METRIC_NAME).increment(1);
+            warn!(user_id = %row.id, %until, failures = failure.failed_count, "account locked");
+            let event = NewAuditEvent::new("auth.account_locked", "user", Some(row.id.to_string()))
+                .with_metadata(json!({
+                    "failed_attempts": failure.failed_count,
+                    "locked_until": until,
+                }));
+            record_audit(
+                conn,
+                &AuditContext::from_session_metadata(None, metadata),
+                &event,
+            )
+            .await;
+            self.notify_lockout(conn, row, until).await;
+        }
+        Some(failure)
+    }
+
+    /// Waits out the failure delay and picks the error to answer with: a lockout triggered by
+    /// this failure wins over `err`. Callers release their connection first so the wait holds
+    /// none from the pool.
+    async fn login_failure_response(
+        &self,
+        failure: Option<LoginFailureRow>,
+        err: SessionError,
+    ) -> SessionError {
+        let failures = failure.as_ref().map_or(1, |failure| failure.failed_count);
+        tokio::time::sleep(self.failure_delay(failures)).await;
+        match failure.and_then(|failure| failure.locked_until) {
+            Some(until) => SessionError::AccountLocked { until },
+            None => err,
+        }
+    }
+
+    async fn notify_lockout(
+        &self,
+        conn: &mut PoolConnection<Postgres>,
+        row: &CredentialRow,
+        until: DateTime<Utc>,
+    ) {
+        let remaining = u64::try_from((until - Utc::now()).num_seconds().max(1)).unwrap_or(1);
+        let link = format!(
+            "{}/forgot-password",
+            self.config
+                .server
+                .public_base_url
+                .as_str()
+                .trim_end_matches('/')
+        );
+        let mail = MailTemplate::AccountLocked.render(
+            &row.email,
+            &[
+                ("name", row.display_name.as_deref().unwrap_or(&row.username)),
+                ("link", &link),
+                ("expires_in", &describe_ttl(remaining)),
+            ],
+        );
+        if let Err(err) = mailer::enqueue_on(conn.as_mut(), &mail).await {
+            warn!(error = %err, user_id = %row.id, "failed to queue lockout notification");
+        }
+    }
+
     #[instrument(skip(self, password), fields(identifier = %identifier))]
     pub async fn authenticate(
         &self,
         identifier: &str,
         password: &str,
         metadata: &SessionMetadata,
-    ) -> Result<(SessionUser, SessionBundle), SessionError> {
+    ) -> Result<LoginOutcome, SessionError> {
         let mut conn = match self.acquire_connection().await {
             Ok(conn) => conn,
             Err(err) => {
@@ -341,7 +550,8 @@ impl SessionService {
                     username::TEXT AS username,
                     display_name,
                     password_hash,
-                    disabled_at
+                    disabled_at,
+                    locked_until
              FROM rustygpt.users
              WHERE email = $1::citext OR username = $1::citext",
         )
@@ -352,20 +562,240 @@ impl SessionService {
 
         let Some(row) = record else {
             record_login_metric("invalid_credentials");
+            record_failed_login(&mut conn, None, identifier, "invalid_credentials", metadata).await;
+            drop(conn);
+            tokio::time::sleep(self.failure_delay(1)).await;
             return Err(SessionError::InvalidCredentials);
         };
 
         if row.disabled_at.is_some() {
             record_login_metric("disabled");
+            record_failed_login(&mut conn, Some(row.id), identifier, "disabled", metadata).await;
             return Err(SessionError::DisabledUser);
         }
 
+        // A locked account is refused before the password is checked, so guesses made during
+        // the lockout cannot succeed and do not extend it.
+        if let Some(until) = row.locked_until.filter(|until| *until > Utc::now()) {
+            record_login_metric("locked");
+            record_failed_login(&mut conn, Some(row.id), identifier, "locked", metadata).await;
+            return Err(SessionError::AccountLocked { until });
+        }
+
         if let Err(err) = verify_password(&row.password_hash, password) {
             record_login_metric("invalid_credentials");
-            return Err(err);
+            record_failed_login(
+                &mut conn,
+                Some(row.id),
+                identifier,
+                "invalid_credentials",
+                metadata,
+            )
+            .await;
+            let failure = self.record_login_failure(&mut conn, &row, metadata).await;
+            drop(conn);
+            return Err(self.login_failure_response(failure, err).await);
+        }
+
+        let state = match mfa::load_state(conn.as_mut(), row.id).await {
+            Ok(state) => state,
+            Err(err) => {
+                record_login_metric("error");
+                return Err(err.into());
+            }
+        };
+        if state.enabled || state.required {
+            let challenge = match Self::create_mfa_challenge(&mut conn, &row, &state).await {
+                Ok(challenge) => challenge,
+                Err(err) => {
+                    record_login_metric("error");
+                    return Err(err);
+                }
+            };
+            record_login_metric("mfa_required");
+            return Ok(LoginOutcome::MfaRequired(challenge));
         }
 
-        let roles = match self.load_roles(&mut conn, row.id).await {
+        clear_login_failures(&mut conn, row.id).await;
+        let login = self
+            .start_session(&mut conn, row, metadata, json!({}))
+            .await?;
+        Ok(LoginOutcome::Authenticated(Box::new(login)))
+    }
+
+    /// Finishes a login paused by [`LoginOutcome::MfaRequired`]. For users who still had to
+    /// enroll, the code confirms the pending secret and the response carries recovery codes.
+    #[instrument(skip(self, challenge_token, code, metadata))]
+    pub async fn complete_mfa_login(
+        &self,
+        challenge_token: &str,
+        code: &str,
+        metadata: &SessionMetadata,
+    ) -> Result<AuthenticatedLogin, SessionError> {
+        let mut conn = self.acquire_connection().await?;
+        let hash = Self::hash_for_token(challenge_token.trim());
+
+        let user_id = sqlx::query_scalar::<_, Option<Uuid>>(
+            "SELECT rustygpt.sp_mfa_challenge_attempt($1, $2)",
+        )
+        .bind(&hash)
+        .bind(MFA_CHALLENGE_MAX_ATTEMPTS)
+        .fetch_one(conn.as_mut())
+        .await?;
+        let Some(user_id) = user_id else {
+            record_login_metric("mfa_challenge_invalid");
+            return Err(SessionError::MfaChallengeInvalid);
+        };
+
+        let row = sqlx::query_as::<_, CredentialRow>(
+            "SELECT id,
+                    email::TEXT AS email,
+                    username::TEXT AS username,
+                    display_name,
+                    password_hash,
+                    disabled_at,
+                    locked_until
+             FROM rustygpt.users
+             WHERE id = $1",
+        )
+        .bind(user_id)
+        .fetch_one(conn.as_mut())
+        .await?;
+
+        if row.disabled_at.is_some() {
+            record_login_metric("disabled");
+            record_failed_login(&mut conn, Some(row.id), &row.email, "disabled", metadata).await;
+            return Err(SessionError::DisabledUser);
+        }
+
+        // Wrong codes count towards the lockout, so a lock taken meanwhile ends the challenge.
+        if let Some(until) = row.locked_until.filter(|until| *until > Utc::now()) {
+            record_login_metric("locked");
+            record_failed_login(&mut conn, Some(row.id), &row.email, "locked", metadata).await;
+            return Err(SessionError::AccountLocked { until });
+        }
+
+        let state = mfa::load_state(conn.as_mut(), user_id).await?;
+        let recovery_codes = if state.enabled {
+            mfa::verify_second_factor(conn.as_mut(), user_id, &state, code)
+                .await?
+                .then(Vec::new)
+        } else if let Some(secret) = state.secret.as_deref() {
+            mfa::confirm_enrollment(conn.as_mut(), user_id, secret, code).await?
+        } else {
+            None
+        };
+        let Some(recovery_codes) = recovery_codes else {
+            record_login_metric("invalid_mfa_code");
+            record_failed_login(
+                &mut conn,
+                Some(row.id),
+                &row.email,
+                "invalid_mfa_code",
+                metadata,
+            )
+            .await;
+            let failure = self.record_login_failure(&mut conn, &row, metadata).await;
+            drop(conn);
+            return Err(self
+                .login_failure_response(failure, SessionError::InvalidMfaCode)
+                .await);
+        };
+
+        sqlx::query("SELECT rustygpt.sp_mfa_challenge_consume($1)")
+            .bind(&hash)
+            .execute(conn.as_mut())
+            .await?;
+        clear_login_failures(&mut conn, row.id).await;
+
+        let mut login = self
+            .start_session(&mut conn, row, metadata, json!({ "mfa": true }))
+            .await?;
+        login.recovery_codes = recovery_codes;
+        Ok(login)
+    }
+
+    /// Signs in a user whose identity an external provider vouched for. Two-factor
+    /// authentication still applies, exactly as after a correct password.
+    #[instrument(skip(self, metadata))]
+    pub async fn authenticate_federated(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        metadata: &SessionMetadata,
+    ) -> Result<LoginOutcome, SessionError> {
+        let mut conn = self.acquire_connection().await?;
+        let row = sqlx::query_as::<_, CredentialRow>(
+            "SELECT id,
+                    email::TEXT AS email,
+                    username::TEXT AS username,
+                    display_name,
+                    password_hash,
+                    disabled_at,
+                    locked_until
+             FROM rustygpt.users
+             WHERE id = $1",
+        )
+        .bind(user_id)
+        .fetch_one(conn.as_mut())
+        .await?;
+
+        if row.disabled_at.is_some() {
+            record_login_metric("disabled");
+            record_failed_login(&mut conn, Some(row.id), &row.email, "disabled", metadata).await;
+            return Err(SessionError::DisabledUser);
+        }
+
+        let state = mfa::load_state(conn.as_mut(), row.id).await?;
+        if state.enabled || state.required {
+            let challenge = Self::create_mfa_challenge(&mut conn, &row, &state).await?;
+            record_login_metric("mfa_required");
+            return Ok(LoginOutcome::MfaRequired(challenge));
+        }
+
+        let login = self
+            .start_session(&mut conn, row, metadata, json!({ "provider": provider }))
+            .await?;
+        Ok(LoginOutcome::Authenticated(Box::new(login)))
+    }
+
+    async fn create_mfa_challenge(
+        conn: &mut PoolConnection<Postgres>,
+        row: &CredentialRow,
+        state: &mfa::MfaState,
+    ) -> Result<MfaChallenge, SessionError> {
+        let enrollment = if state.enabled {
+            None
+        } else {
+            Some(mfa::stage_enrollment(conn.as_mut(), row.id, &row.email).await?)
+        };
+
+        let (token, hash) = Self::new_token();
+        let expires_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+            "SELECT rustygpt.sp_mfa_challenge_create($1, $2, $3)",
+        )
+        .bind(row.id)
+        .bind(&hash)
+        .bind(MFA_CHALLENGE_TTL_SECONDS)
+        .fetch_one(conn.as_mut())
+        .await?;
+
+        Ok(MfaChallenge {
+            token,
+            expires_at,
+            enrollment,
+        })
+    }
+
+    /// Issues the session once every required factor has been checked.
+    async fn start_session(
+        &self,
+        conn: &mut PoolConnection<Postgres>,
+        row: CredentialRow,
+        metadata: &SessionMetadata,
+        audit_metadata: JsonValue,
+    ) -> Result<AuthenticatedLogin, SessionError> {
+        let roles = match self.load_roles(conn, row.id).await {
             Ok(roles) => roles,
             Err(err) => {
                 record_login_metric("error");
@@ -373,10 +803,7 @@ impl SessionService {
             }
         };
 
-        let bundle = match self
-            .issue_session(&mut conn, row.id, &roles, metadata)
-            .await
-        {
+        let bundle = match self.issue_session(conn, row.id, &roles, metadata).await {
             Ok(bundle) => bundle,
             Err(err) => {
                 record_login_metric("error");
@@ -385,7 +812,16 @@ impl SessionService {
         };
 
         record_login_metric("success");
-        if let Err(err) = self.refresh_active_session_metrics(&mut conn).await {
+        let event =
+            NewAuditEvent::new("auth.login", "session", Some(bundle.session_id.to_string()))
+                .with_metadata(audit_metadata);
+        record_audit(
+            conn,
+            &AuditContext::from_session_metadata(Some(row.id), metadata),
+            &event,
+        )
+        .await;
+        if let Err(err) = self.refresh_active_session_metrics(conn).await {
             warn!(error = %err, "failed to refresh active session metrics after login");
         }
 
@@ -393,7 +829,7 @@ impl SessionService {
             id: row.id,
             email: row.email,
             username: row.username,
-            display_name: row.display_name.clone(),
+            display_name: row.display_name,
             roles,
             session_id: bundle.session_id,
             issued_at: bundle.issued_at,
@@ -401,7 +837,11 @@ impl SessionService {
             absolute_expires_at: bundle.absolute_expires_at,
         };
 
-        Ok((user, bundle))
+        Ok(AuthenticatedLogin {
+            user,
+            bundle,
+            recovery_codes: Vec::new(),
+        })
     }
 
     #[instrument(skip(self, token, metadata))]
@@ -584,9 +1024,26 @@ impl SessionService {
         &self,
         session_id: Uuid,
         reason: Option<&str>,
+        metadata: &SessionMetadata,
     ) -> Result<(), SessionError> {
         let mut conn = self.acquire_connection().await?;
-        self.logout_session(&mut conn, session_id, reason).await
+        self.logout_session(&mut conn, session_id, reason).await?;
+
+        let owner = sqlx::query_scalar::<_, Uuid>(
+            "SELECT user_id FROM rustygpt.user_sessions WHERE id = $1",
+        )
+        .bind(session_id)
+        .fetch_optional(conn.as_mut())
+        .await?;
+        let event = NewAuditEvent::new("session.revoke", "session", Some(session_id.to_string()))
+            .with_metadata(json!({ "reason": reason.unwrap_or("logout") }));
+        record_audit(
+            &mut conn,
+            &AuditContext::from_session_metadata(owner, metadata),
+            &event,
+        )
+        .await;
+        Ok(())
     }
 
     #[instrument(skip(self, reason), fields(user_id = %user_id))]
@@ -605,6 +1062,122 @@ impl SessionService {
         Ok(updated)
     }
 
+    /// Lists the unexpired, unrevoked sessions of `user_id`, most recently used first.
+    pub async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<SessionRecord>, SessionError> {
+        let mut conn = self.acquire_connection().await?;
+        let sessions = sqlx::query_as::<_, SessionRecord>(
+            "SELECT id, created_at, last_seen_at, expires_at, absolute_expires_at, user_agent, ip
+             FROM rustygpt.sp_auth_list_sessions($1)",
+        )
+        .bind(user_id)
+        .fetch_all(conn.as_mut())
+        .await?;
+        Ok(sessions)
+    }
+
+    /// Revokes one session of `user_id` on behalf of `actor`, who is the owner or an admin.
+    #[instrument(skip(self, metadata), fields(user_id = %user_id, session_id = %session_id))]
+    pub async fn revoke_user_session(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        actor: Uuid,
+        metadata: &SessionMetadata,
+    ) -> Result<(), SessionError> {
+        let reason = revocation_reason(user_id, actor);
+        let mut conn = self.acquire_connection().await?;
+        let revoked = sqlx::query_scalar::<_, bool>(
+            "SELECT rustygpt.sp_auth_revoke_user_session($1, $2, $3, $4)",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(reason)
+        .bind(actor)
+        .fetch_one(conn.as_mut())
+        .await?;
+        if !revoked {
+            return Err(SessionError::SessionNotFound);
+        }
+        if let Err(err) = self.refresh_active_session_metrics(&mut conn).await {
+            warn!(error = %err, "failed to refresh active session metrics after revocation");
+        }
+
+        let event = NewAuditEvent::new("session.revoke", "session", Some(session_id.to_string()))
+            .with_metadata(json!({ "reason": reason, "user_id": user_id }));
+        record_audit(
+            &mut conn,
+            &AuditContext::from_session_metadata(Some(actor), metadata),
+            &event,
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Revokes every session of `user_id` except `keep` and returns how many were signed out.
+    #[instrument(skip(self, metadata), fields(user_id = %user_id))]
+    pub async fn revoke_user_sessions(
+        &self,
+        user_id: Uuid,
+        keep: Option<Uuid>,
+        actor: Uuid,
+        metadata: &SessionMetadata,
+    ) -> Result<u64, SessionError> {
+        let reason = revocation_reason(user_id, actor);
+        let mut conn = self.acquire_connection().await?;
+        let revoked = sqlx::query_scalar::<_, i32>(
+            "SELECT rustygpt.sp_auth_revoke_user_sessions($1, $2, $3, $4)",
+        )
+        .bind(user_id)
+        .bind(keep)
+        .bind(reason)
+        .bind(actor)
+        .fetch_one(conn.as_mut())
+        .await?;
+        let revoked = u64::try_from(revoked).unwrap_or_default();
+        if let Err(err) = self.refresh_active_session_metrics(&mut conn).await {
+            warn!(error = %err, "failed to refresh active session metrics after revocation");
+        }
+
+        let event = NewAuditEvent::new("session.revoke_all", "user", Some(user_id.to_string()))
+            .with_metadata(json!({ "reason": reason, "revoked": revoked, "kept": keep }));
+        record_audit(
+            &mut conn,
+            &AuditContext::from_session_metadata(Some(actor), metadata),
+            &event,
+        )
+        .await;
+        Ok(revoked)
+    }
+
+    /// Lifts a lockout and clears the failed login count. Returns the lock that was lifted, if
+    /// the account was locked.
+    #[instrument(skip(self, metadata), fields(user_id = %user_id))]
+    pub async fn unlock_user(
+        &self,
+        user_id: Uuid,
+        actor: Uuid,
+        metadata: &SessionMetadata,
+    ) -> Result<Option<DateTime<Utc>>, SessionError> {
+        let mut conn = self.acquire_connection().await?;
+        let previous = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+            "SELECT previous_locked_until FROM rustygpt.sp_auth_unlock_user($1)",
+        )
+        .bind(user_id)
+        .fetch_optional(conn.as_mut())
+        .await?
+        .ok_or(SessionError::UserNotFound)?;
+
+        let event = NewAuditEvent::new("auth.account_unlocked", "user", Some(user_id.to_string()))
+            .with_metadata(json!({ "was_locked": previous.is_some(), "locked_until": previous }));
+        record_audit(
+            &mut conn,
+            &AuditContext::from_session_metadata(Some(actor), metadata),
+            &event,
+        )
+        .await;
+        Ok(previous)
+    }
+
     #[instrument(skip(self, roles, metadata))]
     async fn issue_session(
         &self,
@@ -893,10 +1466,28 @@ impl SessionManager for SessionService {
         identifier: &str,
         password: &str,
         metadata: &SessionMetadata,
-    ) -> Result<(SessionUser, SessionBundle), SessionError> {
+    ) -> Result<LoginOutcome, SessionError> {
         Self::authenticate(self, identifier, password, metadata).await
     }
 
+    async fn complete_mfa_login(
+        &self,
+        challenge_token: &str,
+        code: &str,
+        metadata: &SessionMetadata,
+    ) -> Result<AuthenticatedLogin, SessionError> {
+        Self::complete_mfa_login(self, challenge_token, code, metadata).await
+    }
+
+    async fn authenticate_federated(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        metadata: &SessionMetadata,
+    ) -> Result<LoginOutcome, SessionError> {
+        Self::authenticate_federated(self, user_id, provider, metadata).await
+    }
+
     async fn validate_session(
         &self,
         token: &str,
@@ -917,8 +1508,9 @@ impl SessionManager for SessionService {
         &self,
         session_id: Uuid,
         reason: Option<&str>,
+        metadata: &SessionMetadata,
     ) -> Result<(), SessionError> {
-        Self::revoke_session_by_id(self, session_id, reason).await
+        Self::revoke_session_by_id(self, session_id, reason, metadata).await
     }
 
     async fn mark_user_for_rotation(
@@ -928,6 +1520,48 @@ impl SessionManager for SessionService {
     ) -> Result<i64, SessionError> {
         Self::mark_user_for_rotation(self, user_id, reason).await
     }
+
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<SessionRecord>, SessionError> {
+        Self::list_sessions(self, user_id).await
+    }
+
+    async fn revoke_user_session(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        actor: Uuid,
+        metadata: &SessionMetadata,
+    ) -> Result<(), SessionError> {
+        Self::revoke_user_session(self, user_id, session_id, actor, metadata).await
+    }
+
+    async fn revoke_user_sessions(
+        &self,
+        user_id: Uuid,
+        keep: Option<Uuid>,
+        actor: Uuid,
+        metadata: &SessionMetadata,
+    ) -> Result<u64, SessionError> {
+        Self::revoke_user_sessions(self, user_id, keep, actor, metadata).await
+    }
+
+    async fn unlock_user(
+        &self,
+        user_id: Uuid,
+        actor: Uuid,
+        metadata: &SessionMetadata,
+    ) -> Result<Option<DateTime<Utc>>, SessionError> {
+        Self::unlock_user(self, user_id, actor, metadata).await
+    }
+}
+
+/// Recorded as the rotation reason so users can tell their own sign-outs from an admin's.
+fn revocation_reason(user_id: Uuid, actor: Uuid) -> &'static str {
+    if user_id == actor {
+        "user_revoked"
+    } else {
+        "admin_revoked"
+    }
 }
 
 fn roles_snapshot_changed(roles: &[UserRole], snapshot: Option<&Vec<String>>) -> bool {
@@ -937,6 +1571,51 @@ fn roles_snapshot_changed(roles: &[UserRole], snapshot: Option<&Vec<String>>) ->
     })
 }
 
+/// Audit failures are logged rather than surfaced: they must not block signing in or out.
+async fn record_audit(
+    conn: &mut PoolConnection<Postgres>,
+    context: &AuditContext,
+    event: &NewAuditEvent<'_>,
+) {
+    if let Err(err) = audit_service::record_on(conn, context, event).await {
+        warn!(error = %err, action = event.action, "failed to record audit event");
+    }
+}
+
+async fn record_failed_login(
+    conn: &mut PoolConnection<Postgres>,
+    user_id: Option<Uuid>,
+    identifier: &str,
+    reason: &str,
+    metadata: &SessionMetadata,
+) {
+    // The caller is unauthenticated, so there is no actor. Unknown identifiers have no user id
+    // either; keep what was typed so brute forcing stays visible.
+    let event = NewAuditEvent::new(
+        "auth.login_failed",
+        "user",
+        user_id.map(|id| id.to_string()),
+    )
+    .with_metadata(json!({ "identifier": identifier, "reason": reason }));
+    record_audit(
+        conn,
+        &AuditContext::from_session_metadata(None, metadata),
+        &event,
+    )
+    .await;
+}
+
+/// A completed login resets the failure count; like auditing, this must not block the login.
+async fn clear_login_failures(conn: &mut PoolConnection<Postgres>, user_id: Uuid) {
+    if let Err(err) = sqlx::query("SELECT rustygpt.sp_auth_login_success($1)")
+        .bind(user_id)
+        .execute(conn.as_mut())
+        .await
+    {
+        warn!(error = %err, %user_id, "failed to clear failed login count");
+    }
+}
+
 fn record_login_metric(result: &'static str) {
     metrics::counter!(
         LOGIN_METRIC_NAME,
@@ -990,8 +1669,14 @@ pub fn hash_password(password: &str) -> Result<String, SessionError> {
         .map_err(|err| SessionError::PasswordHash(err.to_string()))
 }
 
+/// Stored for accounts created through single sign-on; no password matches it.
+pub const UNUSABLE_PASSWORD_HASH: &str = "!";
+
 /// Verify a password against an encoded Argon2id hash.
 pub fn verify_password(hash: &str, candidate: &str) -> Result<(), SessionError> {
+    if hash == UNUSABLE_PASSWORD_HASH {
+        return Err(SessionError::InvalidCredentials);
+    }
     let parsed =
         PasswordHash::new(hash).map_err(|err| SessionError::PasswordHash(err.to_string()))?;
     let argon2 = Argon2::default();
@@ -1008,6 +1693,13 @@ struct CredentialRow {
     display_name: Option<String>,
     password_hash: String,
     disabled_at: Option<DateTime<Utc>>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct LoginFailureRow {
+    failed_count: i32,
+    locked_until: Option<DateTime<Utc>>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -1128,4 +1820,104 @@ mod tests {
 
         assert!(stored.suspicious_mismatch(&current));
     }
+
+    #[test]
+    fn revocation_reason_distinguishes_admins() {
+        let user = Uuid::new_v4();
+        assert_eq!(revocation_reason(user, user), "user_revoked");
+        assert_eq!(revocation_reason(user, Uuid::new_v4()), "admin_revoked");
+    }
+
+    #[tokio::test]
+    async fn failure_delay_grows_with_failures_and_is_capped() {
+        let mut config = Config::default_for_profile(Profile::Test);
+        config.auth.failure_delay_ms = 400;
+        let service = service_with_config(config);
+
+        assert_eq!(service.failure_delay(0), StdDuration::from_millis(400));
+        assert_eq!(service.failure_delay(3), StdDuration::from_millis(1_200));
+        assert_eq!(
+            service.failure_delay(1_000),
+            StdDuration::from_millis(MAX_FAILURE_DELAY_MS)
+        );
+    }
+
+    #[test]
+    fn unusable_password_hash_never_verifies() {
+        assert!(matches!(
+            verify_password(UNUSABLE_PASSWORD_HASH, ""),
+            Err(SessionError::InvalidCredentials)
+        ));
+        assert!(matches!(
+            verify_password(UNUSABLE_PASSWORD_HASH, "!"),
+            Err(SessionError::InvalidCredentials)
+        ));
+    }
+    async fn failed_logins(pool: &PgPool, user_id: Uuid) -> i32 {
+        sqlx::query_scalar("SELECT failed_login_count FROM rustygpt.users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn wrong_second_factor_codes_count_towards_the_lockout() {
+        let Some(db) = crate::db::test_support::TestDatabase::create().await else {
+            return;
+        };
+        let user = db.create_user("ada").await;
+        sqlx::query(
+            "UPDATE rustygpt.users
+             SET password_hash = $2, failed_login_count = 1, last_failed_login_at = now()
+             WHERE id = $1",
+        )
+        .bind(user)
+        .bind(hash_password("correct horse").unwrap())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO rustygpt.user_totp (user_id, secret, enabled_at) VALUES ($1, $2, now())",
+        )
+        .bind(user)
+        .bind(vec![7_u8; 20])
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let mut config = Config::default_for_profile(Profile::Test);
+        config.auth.lockout_threshold = 2;
+        config.auth.failure_delay_ms = 0;
+        let service = SessionService::new(db.pool.clone(), Arc::new(config));
+        let metadata = SessionMetadata::default();
+
+        let outcome = service
+            .authenticate("ada", "correct horse", &metadata)
+            .await
+            .unwrap();
+        let LoginOutcome::MfaRequired(challenge) = outcome else {
+            panic!("expected a second-factor challenge");
+        };
+        assert_eq!(
+            failed_logins(&db.pool, user).await,
+            1,
+            "the password alone does not finish the login"
+        );
+
+        let err = service
+            .complete_mfa_login(&challenge.token, "not a code", &metadata)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SessionError::AccountLocked { .. }), "{err:?}");
+        assert_eq!(failed_logins(&db.pool, user).await, 2);
+
+        let err = service
+            .complete_mfa_login(&challenge.token, "not a code", &metadata)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SessionError::AccountLocked { .. }), "{err:?}");
+
+        db.destroy().await;
+    }
 }
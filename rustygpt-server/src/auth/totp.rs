@@ -0,0 +1,176 @@
+//! RFC 6238 time-based one-time passwords and single-use recovery codes.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// Authenticator apps assume SHA-1, 6 digits and 30 second steps unless told otherwise.
+const DIGITS: u32 = 6;
+const STEP_SECONDS: i64 = 30;
+/// Accept codes from one step either side of now to absorb clock drift.
+const ALLOWED_SKEW: i64 = 1;
+const SECRET_BYTES: usize = 20;
+const RECOVERY_CODE_COUNT: usize = 10;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[must_use]
+pub(crate) fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut secret);
+    secret
+}
+
+/// RFC 4648 base32 without padding, as expected in `otpauth://` URIs.
+#[must_use]
+pub(crate) fn encode_base32(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(char::from(
+                BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize],
+            ));
+        }
+    }
+    if bits > 0 {
+        out.push(char::from(
+            BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize],
+        ));
+    }
+    out
+}
+
+/// The numeric code for a given time step (RFC 4226 dynamic truncation).
+#[must_use]
+pub(crate) fn code_at(secret: &[u8], step: i64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+#[must_use]
+pub(crate) const fn step_for(unix_seconds: i64) -> i64 {
+    unix_seconds.div_euclid(STEP_SECONDS)
+}
+
+/// Returns the matching time step so callers can reject replays of an already used code.
+#[must_use]
+pub(crate) fn verify(secret: &[u8], code: &str, unix_seconds: i64) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let expected: u32 = code.parse().ok()?;
+    let now = step_for(unix_seconds);
+    (now - ALLOWED_SKEW..=now + ALLOWED_SKEW).find(|&step| code_at(secret, step) == expected)
+}
+
+/// `otpauth://totp/<issuer>:<account>?secret=...` for authenticator QR codes.
+#[must_use]
+pub(crate) fn provisioning_uri(issuer: &str, account: &str, secret: &[u8]) -> String {
+    let mut uri = Url::parse("otpauth://totp/").expect("static otpauth URI parses");
+    uri.set_path(&format!("/{issuer}:{account}"));
+    uri.query_pairs_mut()
+        .append_pair("secret", &encode_base32(secret))
+        .append_pair("issuer", issuer)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &DIGITS.to_string())
+        .append_pair("period", &STEP_SECONDS.to_string());
+    uri.to_string()
+}
+
+/// Ten `xxxxx-xxxxx` codes from the base32 alphabet, lower-cased for readability.
+#[must_use]
+pub(crate) fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut raw = [0u8; 7];
+            OsRng.fill_bytes(&mut raw);
+            let encoded = encode_base32(&raw).to_ascii_lowercase();
+            format!("{}-{}", &encoded[..5], &encoded[5..10])
+        })
+        .collect()
+}
+
+/// Recovery codes are compared by hash; separators and case are ignored.
+#[must_use]
+pub(crate) fn hash_recovery_code(code: &str) -> Vec<u8> {
+    let normalized: String = code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect();
+    Sha256::digest(normalized.as_bytes()).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn matches_rfc_6238_sha1_vectors() {
+        // RFC 6238 appendix B lists 8 digit codes; 6 digit codes are their last six digits.
+        for (time, expected) in [
+            (59, 287_082),
+            (1_111_111_109, 81_804),
+            (1_234_567_890, 5_924),
+            (2_000_000_000, 279_037),
+        ] {
+            assert_eq!(code_at(RFC_SECRET, step_for(time)), expected, "t={time}");
+        }
+    }
+
+    #[test]
+    fn verify_allows_one_step_of_drift() {
+        assert_eq!(verify(RFC_SECRET, "287082", 59), Some(1));
+        assert_eq!(verify(RFC_SECRET, "287082", 59 + 30), Some(1));
+        assert_eq!(verify(RFC_SECRET, "287082", 59 + 60), None);
+        assert_eq!(verify(RFC_SECRET, "28708", 59), None);
+        assert_eq!(verify(RFC_SECRET, "abcdef", 59), None);
+    }
+
+    #[test]
+    fn base32_matches_rfc_4648() {
+        assert_eq!(encode_base32(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(
+            encode_base32(RFC_SECRET),
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"
+        );
+    }
+
+    #[test]
+    fn provisioning_uri_carries_secret_and_issuer() {
+        let uri = provisioning_uri("RustyGPT", "alice@example.com", b"foobar");
+        assert!(uri.starts_with("otpauth://totp/RustyGPT:alice@example.com?"));
+        assert!(uri.contains("secret=MZXW6YTBOI"));
+        assert!(uri.contains("issuer=RustyGPT"));
+    }
+
+    #[test]
+    fn recovery_codes_hash_ignores_formatting() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert!(codes.iter().all(|code| code.len() == 11));
+        assert_eq!(
+            hash_recovery_code("abcde-fghij"),
+            hash_recovery_code(" ABCDE FGHIJ ")
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! Administrator management of user accounts: listing, creation, disable/enable, global roles
+//! and password resets. Every change is audited by the stored procedure that performs it.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use shared::{
+    config::server::Config,
+    models::{
+        AdminPasswordResetResponse, CreateUserAccountRequest, RoleSummary, Timestamp, UserAccount,
+        UserAccountPage, UserAccountQuery, UserRole,
+    },
+};
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    auth::{
+        account::{AccountService, hash_new_password},
+        session::UNUSABLE_PASSWORD_HASH,
+    },
+    services::{
+        audit_service::AuditContext,
+        chat_service::{ChatServiceError, ChatServiceResult},
+    },
+};
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+
+fn page_size(requested: Option<u32>) -> u32 {
+    requested
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Unknown role names are skipped rather than failing the whole listing.
+fn parse_roles(roles: &[String]) -> Vec<UserRole> {
+    roles.iter().filter_map(|role| role.parse().ok()).collect()
+}
+
+fn role_names(roles: &[UserRole]) -> Vec<&'static str> {
+    if roles.is_empty() {
+        return vec![UserRole::Member.as_str()];
+    }
+    roles.iter().copied().map(UserRole::as_str).collect()
+}
+
+#[derive(sqlx::FromRow)]
+struct UserAccountRow {
+    id: Uuid,
+    username: String,
+    email: String,
+    display_name: Option<String>,
+    roles: Vec<String>,
+    created_at: DateTime<Utc>,
+    disabled_at: Option<DateTime<Utc>>,
+    locked_until: Option<DateTime<Utc>>,
+    email_verified_at: Option<DateTime<Utc>>,
+    mfa_enabled: bool,
+    last_seen_at: Option<DateTime<Utc>>,
+    total_count: i64,
+}
+
+impl From<UserAccountRow> for UserAccount {
+    fn from(row: UserAccountRow) -> Self {
+        Self {
+            id: row.id,
+            username: row.username,
+            email: row.email,
+            display_name: row.display_name,
+            roles: parse_roles(&row.roles),
+            created_at: Timestamp(row.created_at),
+            disabled_at: row.disabled_at.map(Timestamp),
+            locked_until: row.locked_until.map(Timestamp),
+            email_verified_at: row.email_verified_at.map(Timestamp),
+            mfa_enabled: row.mfa_enabled,
+            last_seen_at: row.last_seen_at.map(Timestamp),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RoleSummaryRow {
+    role: String,
+    user_count: i64,
+    mfa_required: bool,
+}
+
+const LIST_COLUMNS: &str = "SELECT id, username, email, display_name, roles, created_at, \
+     disabled_at, locked_until, email_verified_at, mfa_enabled, last_seen_at, total_count \
+     FROM rustygpt.sp_admin_user_list($1, $2, $3, $4, $5, $6)";
+
+#[derive(Clone)]
+pub struct UserAdminService {
+    pool: PgPool,
+    config: Arc<Config>,
+}
+
+impl UserAdminService {
+    pub const fn new(pool: PgPool, config: Arc<Config>) -> Self {
+        Self { pool, config }
+    }
+
+    /// Newest-first page of accounts matching `query`.
+    #[instrument(name = "user_admin.list", skip(self, query), err)]
+    pub async fn list(&self, query: &UserAccountQuery) -> ChatServiceResult<UserAccountPage> {
+        let limit = page_size(query.limit);
+        let rows = sqlx::query_as::<_, UserAccountRow>(LIST_COLUMNS)
+            .bind(None::<Uuid>)
+            .bind(query.search.as_deref())
+            .bind(query.role.map(UserRole::as_str))
+            .bind(query.disabled)
+            .bind(i32::try_from(limit).unwrap_or(i32::MAX))
+            .bind(i32::try_from(query.offset.unwrap_or(0)).unwrap_or(i32::MAX))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+
+        let total = rows
+            .first()
+            .map_or(0, |row| u64::try_from(row.total_count).unwrap_or(0));
+        Ok(UserAccountPage {
+            users: rows.into_iter().map(UserAccount::from).collect(),
+            total,
+        })
+    }
+
+    #[instrument(name = "user_admin.get", skip(self), err)]
+    pub async fn get(&self, user_id: Uuid) -> ChatServiceResult<UserAccount> {
+        sqlx::query_as::<_, UserAccountRow>(LIST_COLUMNS)
+            .bind(Some(user_id))
+            .bind(None::<&str>)
+            .bind(None::<&str>)
+            .bind(None::<bool>)
+            .bind(1_i32)
+            .bind(0_i32)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?
+            .map(UserAccount::from)
+            .ok_or_else(|| ChatServiceError::NotFound("user not found".to_string()))
+    }
+
+    /// Creates an account. Without an initial password the user is emailed a reset link.
+    #[instrument(name = "user_admin.create", skip(self, audit, request), err)]
+    pub async fn create(
+        &self,
+        audit: &AuditContext,
+        request: &CreateUserAccountRequest,
+    ) -> ChatServiceResult<UserAccount> {
+        let password_hash = match request.password.as_deref() {
+            Some(password) => hash_new_password(password)?,
+            None => UNUSABLE_PASSWORD_HASH.to_string(),
+        };
+
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let user_id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT rustygpt.sp_admin_user_create($1, $2, $3, $4, $5)",
+        )
+        .bind(&request.username)
+        .bind(&request.email)
+        .bind(request.display_name.as_deref())
+        .bind(&password_hash)
+        .bind(role_names(&request.roles))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        if request.password.is_none() {
+            self.accounts()
+                .request_password_reset(audit, &request.email)
+                .await?;
+        }
+        self.get(user_id).await
+    }
+
+    /// Disables or re-enables an account. Disabling signs the user out everywhere. Returns
+    /// whether the flag changed.
+    #[instrument(name = "user_admin.set_disabled", skip(self, audit), err)]
+    pub async fn set_disabled(
+        &self,
+        audit: &AuditContext,
+        user_id: Uuid,
+        disabled: bool,
+    ) -> ChatServiceResult<bool> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let changed =
+            sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_admin_user_set_disabled($1, $2)")
+                .bind(user_id)
+                .bind(disabled)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(changed)
+    }
+
+    /// Grants (`granted = true`) or revokes a global role. Returns whether membership changed;
+    /// the caller is responsible for rotating the user's sessions when it did.
+    #[instrument(name = "user_admin.set_role", skip(self, audit), err)]
+    pub async fn set_role(
+        &self,
+        audit: &AuditContext,
+        user_id: Uuid,
+        role: UserRole,
+        granted: bool,
+    ) -> ChatServiceResult<bool> {
+        let sql = if granted {
+            "SELECT rustygpt.sp_admin_user_grant_role($1, $2)"
+        } else {
+            "SELECT rustygpt.sp_admin_user_revoke_role($1, $2)"
+        };
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let changed = sqlx::query_scalar::<_, bool>(sql)
+            .bind(user_id)
+            .bind(role.as_str())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(changed)
+    }
+
+    /// Replaces the password and revokes every session, or emails a reset link when no
+    /// password is given.
+    #[instrument(name = "user_admin.reset_password", skip(self, audit, password), err)]
+    pub async fn reset_password(
+        &self,
+        audit: &AuditContext,
+        user_id: Uuid,
+        password: Option<&str>,
+    ) -> ChatServiceResult<AdminPasswordResetResponse> {
+        let Some(password) = password else {
+            let user = self.get(user_id).await?;
+            if user.disabled_at.is_some() {
+                return Err(ChatServiceError::Validation(
+                    "cannot email a reset link to a disabled account".to_string(),
+                ));
+            }
+            self.accounts()
+                .request_password_reset(audit, &user.email)
+                .await?;
+            return Ok(AdminPasswordResetResponse {
+                revoked_sessions: 0,
+                email_sent: true,
+            });
+        };
+
+        let password_hash = hash_new_password(password)?;
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let revoked =
+            sqlx::query_scalar::<_, i32>("SELECT rustygpt.sp_admin_user_set_password($1, $2)")
+                .bind(user_id)
+                .bind(&password_hash)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(AdminPasswordResetResponse {
+            revoked_sessions: u64::try_from(revoked).unwrap_or(0),
+            email_sent: false,
+        })
+    }
+
+    #[instrument(name = "user_admin.roles", skip(self), err)]
+    pub async fn role_summaries(&self) -> ChatServiceResult<Vec<RoleSummary>> {
+        let rows = sqlx::query_as::<_, RoleSummaryRow>(
+            "SELECT role, user_count, mfa_required FROM rustygpt.sp_admin_role_summary()",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(RoleSummary {
+                    role: row.role.parse().ok()?,
+                    user_count: u64::try_from(row.user_count).unwrap_or(0),
+                    mfa_required: row.mfa_required,
+                })
+            })
+            .collect())
+    }
+
+    fn accounts(&self) -> AccountService {
+        AccountService::new(self.pool.clone(), self.config.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_size_is_defaulted_and_clamped() {
+        assert_eq!(page_size(None), DEFAULT_PAGE_SIZE);
+        assert_eq!(page_size(Some(0)), 1);
+        assert_eq!(page_size(Some(10_000)), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn new_accounts_default_to_member() {
+        assert_eq!(role_names(&[]), vec!["member"]);
+        assert_eq!(
+            role_names(&[UserRole::Admin, UserRole::Member]),
+            vec!["admin", "member"]
+        );
+    }
+
+    #[test]
+    fn unknown_roles_are_ignored() {
+        let roles = vec!["admin".to_string(), "auditor".to_string()];
+        assert_eq!(parse_roles(&roles), vec![UserRole::Admin]);
+    }
+}
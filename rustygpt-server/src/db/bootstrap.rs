@@ -13,6 +13,8 @@ use shared::config::server::DatabaseConfig;
 
 #[derive(Debug, Clone)]
 struct BootstrapStage {
+    /// Ledger key. When the scripts of a stage that has shipped change, bump its `@N` suffix so
+    /// databases that already applied it run it again; such a stage must be safe to repeat.
     label: &'static str,
     kind: ScriptStage,
     files: &'static [&'static str],
@@ -43,7 +45,7 @@ const BOOTSTRAP_STAGES: &[BootstrapStage] = &[
         ],
     },
     BootstrapStage {
-        label: "procs/020_threads.sql",
+        label: "procs/020_threads.sql@2",
         kind: ScriptStage::Procedures,
         files: &[
             "procs/018_conversations.sql",
@@ -75,10 +77,230 @@ const BOOTSTRAP_STAGES: &[BootstrapStage] = &[
         ],
     },
     BootstrapStage {
-        label: "procs/034_limits.sql",
+        label: "procs/034_limits.sql@2",
         kind: ScriptStage::Procedures,
         files: &["procs/034_limits.sql"],
     },
+    BootstrapStage {
+        label: "procs/035_message_variants.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/035_message_variants.sql"],
+    },
+    BootstrapStage {
+        label: "schema/060_message_revisions.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/060_message_revisions.sql"],
+    },
+    BootstrapStage {
+        label: "procs/036_message_revisions.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/036_message_revisions.sql"],
+    },
+    BootstrapStage {
+        label: "schema/070_message_forks.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/070_message_forks.sql"],
+    },
+    BootstrapStage {
+        label: "procs/037_message_forks.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/037_message_forks.sql"],
+    },
+    BootstrapStage {
+        label: "schema/080_conversation_retention.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/080_conversation_retention.sql"],
+    },
+    BootstrapStage {
+        label: "procs/038_conversation_retention.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/038_conversation_retention.sql"],
+    },
+    BootstrapStage {
+        label: "schema/085_message_attachments.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/085_message_attachments.sql"],
+    },
+    BootstrapStage {
+        label: "procs/039_conversation_export.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/039_conversation_export.sql"],
+    },
+    BootstrapStage {
+        label: "procs/040_conversation_import.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/040_conversation_import.sql"],
+    },
+    BootstrapStage {
+        label: "schema/090_api_tokens.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/090_api_tokens.sql"],
+    },
+    BootstrapStage {
+        label: "procs/041_api_tokens.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/041_api_tokens.sql"],
+    },
+    BootstrapStage {
+        label: "schema/100_usage_ledger.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/100_usage_ledger.sql"],
+    },
+    BootstrapStage {
+        label: "procs/042_usage_ledger.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/042_usage_ledger.sql"],
+    },
+    BootstrapStage {
+        label: "schema/110_token_quotas.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/110_token_quotas.sql"],
+    },
+    BootstrapStage {
+        label: "procs/043_token_quotas.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/043_token_quotas.sql"],
+    },
+    BootstrapStage {
+        label: "schema/120_audit_events.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/120_audit_events.sql"],
+    },
+    BootstrapStage {
+        label: "procs/044_audit_events.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/044_audit_events.sql"],
+    },
+    BootstrapStage {
+        label: "schema/130_mfa.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/130_mfa.sql"],
+    },
+    BootstrapStage {
+        label: "procs/045_mfa.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/045_mfa.sql"],
+    },
+    BootstrapStage {
+        label: "schema/140_mail.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/140_mail.sql"],
+    },
+    BootstrapStage {
+        label: "procs/046_mail.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/046_mail.sql"],
+    },
+    BootstrapStage {
+        label: "schema/150_oidc.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/150_oidc.sql"],
+    },
+    BootstrapStage {
+        label: "procs/047_oidc.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/047_oidc.sql"],
+    },
+    BootstrapStage {
+        label: "procs/048_sessions.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/048_sessions.sql"],
+    },
+    BootstrapStage {
+        label: "schema/160_login_lockout.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/160_login_lockout.sql"],
+    },
+    BootstrapStage {
+        label: "procs/049_login_lockout.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/049_login_lockout.sql"],
+    },
+    BootstrapStage {
+        label: "procs/050_admin_users.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/050_admin_users.sql"],
+    },
+    BootstrapStage {
+        label: "schema/170_user_preferences.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/170_user_preferences.sql"],
+    },
+    BootstrapStage {
+        label: "procs/051_user_preferences.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/051_user_preferences.sql"],
+    },
+    BootstrapStage {
+        label: "schema/180_organizations.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/180_organizations.sql"],
+    },
+    BootstrapStage {
+        label: "procs/052_organizations.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/052_organizations.sql"],
+    },
+    BootstrapStage {
+        label: "schema/190_user_groups.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/190_user_groups.sql"],
+    },
+    BootstrapStage {
+        label: "procs/053_user_groups.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/053_user_groups.sql"],
+    },
+    BootstrapStage {
+        label: "schema/200_webhooks.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/200_webhooks.sql"],
+    },
+    BootstrapStage {
+        label: "procs/054_webhooks.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/054_webhooks.sql"],
+    },
+    BootstrapStage {
+        label: "schema/210_stream_relay.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/210_stream_relay.sql"],
+    },
+    BootstrapStage {
+        label: "procs/055_stream_relay.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/055_stream_relay.sql"],
+    },
+    BootstrapStage {
+        label: "schema/220_mentions.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/220_mentions.sql"],
+    },
+    BootstrapStage {
+        label: "procs/056_mentions.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/056_mentions.sql"],
+    },
+    BootstrapStage {
+        label: "schema/230_message_interruptions.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/230_message_interruptions.sql"],
+    },
+    BootstrapStage {
+        label: "procs/057_message_interruptions.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/057_message_interruptions.sql"],
+    },
+    BootstrapStage {
+        label: "schema/240_generation_status.sql",
+        kind: ScriptStage::Schema,
+        files: &["schema/240_generation_status.sql"],
+    },
+    BootstrapStage {
+        label: "procs/058_generation_status.sql",
+        kind: ScriptStage::Procedures,
+        files: &["procs/058_generation_status.sql"],
+    },
 ];
 
 #[cfg(test)]
@@ -389,7 +611,7 @@ async fn apply_script(pool: &PgPool, path: &Path) -> Result<(), BootstrapError>
     })?;
 
     info!(script = %path.display(), "executing bootstrap script");
-    if let Err(source) = sqlx::query(trimmed).execute(&mut *transaction).await {
+    if let Err(source) = sqlx::raw_sql(trimmed).execute(&mut *transaction).await {
         return Err(BootstrapError::Sql {
             path: path.to_path_buf(),
             source,
@@ -421,10 +643,54 @@ mod tests {
                 "schema/010_auth.sql",
                 "procs/010_auth.sql",
                 "schema/020_conversations_threads.sql",
-                "procs/020_threads.sql",
+                "procs/020_threads.sql@2",
                 "schema/040_rate_limits.sql",
                 "seed/002_rate_limits.sql",
-                "procs/034_limits.sql"
+                "procs/034_limits.sql@2",
+                "procs/035_message_variants.sql",
+                "schema/060_message_revisions.sql",
+                "procs/036_message_revisions.sql",
+                "schema/070_message_forks.sql",
+                "procs/037_message_forks.sql",
+                "schema/080_conversation_retention.sql",
+                "procs/038_conversation_retention.sql",
+                "schema/085_message_attachments.sql",
+                "procs/039_conversation_export.sql",
+                "procs/040_conversation_import.sql",
+                "schema/090_api_tokens.sql",
+                "procs/041_api_tokens.sql",
+                "schema/100_usage_ledger.sql",
+                "procs/042_usage_ledger.sql",
+                "schema/110_token_quotas.sql",
+                "procs/043_token_quotas.sql",
+                "schema/120_audit_events.sql",
+                "procs/044_audit_events.sql",
+                "schema/130_mfa.sql",
+                "procs/045_mfa.sql",
+                "schema/140_mail.sql",
+                "procs/046_mail.sql",
+                "schema/150_oidc.sql",
+                "procs/047_oidc.sql",
+                "procs/048_sessions.sql",
+                "schema/160_login_lockout.sql",
+                "procs/049_login_lockout.sql",
+                "procs/050_admin_users.sql",
+                "schema/170_user_preferences.sql",
+                "procs/051_user_preferences.sql",
+                "schema/180_organizations.sql",
+                "procs/052_organizations.sql",
+                "schema/190_user_groups.sql",
+                "procs/053_user_groups.sql",
+                "schema/200_webhooks.sql",
+                "procs/054_webhooks.sql",
+                "schema/210_stream_relay.sql",
+                "procs/055_stream_relay.sql",
+                "schema/220_mentions.sql",
+                "procs/056_mentions.sql",
+                "schema/230_message_interruptions.sql",
+                "procs/057_message_interruptions.sql",
+                "schema/240_generation_status.sql",
+                "procs/058_generation_status.sql"
             ]
         );
     }
@@ -489,4 +755,31 @@ mod tests {
 
         super::set_readiness_override(None);
     }
+
+    #[tokio::test]
+    async fn stages_recorded_under_an_older_version_run_again() {
+        let Some(db) = crate::db::test_support::TestDatabase::create().await else {
+            return;
+        };
+        // Record the versioned stages the way a release before the bump did.
+        sqlx::query(
+            "UPDATE rustygpt.bootstrap_applied SET stage = split_part(stage, '@', 1)
+             WHERE stage LIKE '%@%'",
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let config = DatabaseConfig {
+            bootstrap_path: Path::new(env!("CARGO_MANIFEST_DIR")).join("../scripts/pg"),
+            ..DatabaseConfig::default()
+        };
+        run(&db.pool, &config).await.unwrap();
+
+        let applied = fetch_applied_stage_names(&db.pool).await.unwrap();
+        for stage in BOOTSTRAP_STAGES {
+            assert!(applied.contains(stage.label), "{} not applied", stage.label);
+        }
+        db.destroy().await;
+    }
 }
@@ -1 +1,3 @@
 pub mod bootstrap;
+#[cfg(test)]
+pub(crate) mod test_support;
@@ -0,0 +1,112 @@
+//! Throwaway databases for tests that exercise the stored procedures.
+//!
+//! Set `RUSTYGPT_TEST_DATABASE_URL` to a server where the user may create databases. Each
+//! [`TestDatabase::create`] bootstraps a fresh database there; without the variable it returns
+//! `None` and the calling test passes without running.
+
+use std::path::Path;
+
+use sqlx::{
+    Connection, PgConnection, PgPool, Postgres, Transaction,
+    postgres::{PgConnectOptions, PgPoolOptions},
+};
+use uuid::Uuid;
+
+use shared::config::server::DatabaseConfig;
+
+use super::bootstrap;
+
+const URL_VAR: &str = "RUSTYGPT_TEST_DATABASE_URL";
+
+pub(crate) struct TestDatabase {
+    pub pool: PgPool,
+    server: PgConnectOptions,
+    name: String,
+}
+
+impl TestDatabase {
+    pub(crate) async fn create() -> Option<Self> {
+        let Ok(url) = std::env::var(URL_VAR) else {
+            eprintln!("{URL_VAR} is not set; skipping database test");
+            return None;
+        };
+        let server: PgConnectOptions = url.parse().expect("invalid test database url");
+        let name = format!("rustygpt_test_{}", Uuid::new_v4().simple());
+
+        let mut admin = PgConnection::connect_with(&server)
+            .await
+            .expect("connect to test database server");
+        sqlx::query(&format!("CREATE DATABASE {name}"))
+            .execute(&mut admin)
+            .await
+            .expect("create test database");
+        admin.close().await.ok();
+
+        let pool = PgPoolOptions::new()
+            .max_connections(4)
+            .connect_with(server.clone().database(&name))
+            .await
+            .expect("connect to test database");
+        let config = DatabaseConfig {
+            bootstrap_path: Path::new(env!("CARGO_MANIFEST_DIR")).join("../scripts/pg"),
+            ..DatabaseConfig::default()
+        };
+        bootstrap::run(&pool, &config)
+            .await
+            .expect("bootstrap test database");
+
+        Some(Self { pool, server, name })
+    }
+
+    /// Closes the pool and drops the database.
+    pub(crate) async fn destroy(self) {
+        self.pool.close().await;
+        let mut admin = PgConnection::connect_with(&self.server)
+            .await
+            .expect("connect to test database server");
+        sqlx::query(&format!(
+            "DROP DATABASE IF EXISTS {} WITH (FORCE)",
+            self.name
+        ))
+        .execute(&mut admin)
+        .await
+        .expect("drop test database");
+        admin.close().await.ok();
+    }
+
+    pub(crate) async fn create_user(&self, username: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO rustygpt.users (email, username, password_hash)
+             VALUES ($1 || '@example.com', $1, 'unused')
+             RETURNING id",
+        )
+        .bind(username)
+        .fetch_one(&self.pool)
+        .await
+        .expect("insert test user")
+    }
+
+    /// Starts a transaction in which `user` is the session user the procedures check.
+    pub(crate) async fn begin_as(&self, user: Uuid) -> Transaction<'static, Postgres> {
+        let mut tx = self.pool.begin().await.expect("begin transaction");
+        sqlx::query("SELECT set_config('app.current_user_id', $1, true)")
+            .bind(user.to_string())
+            .execute(&mut *tx)
+            .await
+            .expect("set session user");
+        tx
+    }
+
+    /// Creates a conversation owned by `owner`.
+    pub(crate) async fn create_conversation(&self, owner: Uuid, title: &str) -> Uuid {
+        let mut tx = self.begin_as(owner).await;
+        let id = sqlx::query_scalar("SELECT rustygpt.sp_create_conversation($1, FALSE, $2)")
+            .bind(title)
+            .bind(owner)
+            .fetch_one(&mut *tx)
+            .await
+            .expect("create conversation");
+        tx.commit().await.expect("commit conversation");
+        id
+    }
+}
@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router, extract::Extension, http::StatusCode, response::IntoResponse, routing::post,
+};
+use tracing::instrument;
+
+use crate::{
+    app_state::AppState,
+    auth::{account::AccountService, api_tokens::ApiTokenPrincipal},
+    handlers::admin_limits::require_pool,
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::audit_service::AuditContext,
+};
+use shared::{
+    config::server::Config,
+    models::{
+        ChangePasswordRequest, ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest,
+    },
+};
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/me/password", post(change_password))
+        .route("/api/me/verify-email", post(resend_verification))
+}
+
+fn audit_context(context: Option<&Extension<RequestContext>>) -> AuditContext {
+    context
+        .map(|Extension(context)| AuditContext::from(context))
+        .unwrap_or_default()
+}
+
+/// Always answers 202 so the response does not reveal whether the address has an account.
+#[instrument(skip(state, config, context, payload))]
+pub async fn forgot_password(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    context: Option<Extension<RequestContext>>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> AppResult<impl IntoResponse> {
+    if payload.email.trim().is_empty() {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_request",
+            "email is required",
+        ));
+    }
+    let pool = require_pool(&state)?;
+
+    AccountService::new(pool, config)
+        .request_password_reset(&audit_context(context.as_ref()), &payload.email)
+        .await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[instrument(skip(state, config, context, payload))]
+pub async fn reset_password(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    context: Option<Extension<RequestContext>>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> AppResult<impl IntoResponse> {
+    let pool = require_pool(&state)?;
+
+    AccountService::new(pool, config)
+        .reset_password(
+            &audit_context(context.as_ref()),
+            &payload.token,
+            &payload.new_password,
+        )
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[instrument(skip(state, config, context, payload))]
+pub async fn verify_email(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    context: Option<Extension<RequestContext>>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> AppResult<impl IntoResponse> {
+    let pool = require_pool(&state)?;
+
+    AccountService::new(pool, config)
+        .verify_email(&audit_context(context.as_ref()), &payload.token)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Changes the password and signs out every other session of the account.
+#[instrument(skip(state, config, context, principal, payload))]
+async fn change_password(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> AppResult<impl IntoResponse> {
+    if principal.is_some() {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "RGP.AUTH.SESSION_REQUIRED",
+            "API tokens cannot change the account password",
+        ));
+    }
+    let session = context
+        .session
+        .as_ref()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))?;
+    let pool = require_pool(&state)?;
+
+    AccountService::new(pool, config)
+        .change_password(
+            &AuditContext::from(&context),
+            session.id,
+            Some(session.session_id),
+            &payload.current_password,
+            &payload.new_password,
+        )
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[instrument(skip(state, config, context))]
+async fn resend_verification(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<impl IntoResponse> {
+    let user_id = context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))?;
+    let pool = require_pool(&state)?;
+
+    let queued = AccountService::new(pool, config)
+        .send_verification(&AuditContext::from(&context), user_id)
+        .await?;
+    if queued {
+        Ok(StatusCode::ACCEPTED)
+    } else {
+        Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "RGP.EMAIL_ALREADY_VERIFIED",
+            "email address is already verified",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn forgot_password_requires_an_email() {
+        let status = match forgot_password(
+            Extension(Arc::new(AppState::default())),
+            Extension(Arc::new(Config::default_for_profile(
+                shared::config::server::Profile::Test,
+            ))),
+            None,
+            Json(ForgotPasswordRequest { email: "  ".into() }),
+        )
+        .await
+        {
+            Ok(_) => panic!("blank email must be rejected"),
+            Err(err) => err.into_response().status(),
+        };
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Extension, Query},
+};
+use tracing::instrument;
+
+use crate::{
+    app_state::AppState,
+    handlers::admin_limits::{require_admin_context, require_pool},
+    http::error::AppResult,
+    middleware::request_context::RequestContext,
+    services::audit_service::AuditService,
+};
+use shared::models::{AuditEventPage, AuditQuery};
+
+/// Newest-first page of the audit log; pass `next_before` back as `before` for older events.
+#[instrument(skip(state, context))]
+pub async fn list_audit_events(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Query(query): Query<AuditQuery>,
+) -> AppResult<Json<AuditEventPage>> {
+    let admin = require_admin_context(&context)?;
+    let pool = require_pool(&state)?;
+
+    let page = AuditService::new(pool).list(admin.id, &query).await?;
+    Ok(Json(page))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse};
+    use shared::models::UserRole;
+
+    #[tokio::test]
+    async fn audit_log_requires_admin_role() {
+        let context = RequestContext::test_with_roles(vec![UserRole::Member]);
+
+        let status = match list_audit_events(
+            Extension(Arc::new(AppState::default())),
+            Extension(context),
+            Query(AuditQuery::default()),
+        )
+        .await
+        {
+            Ok(_) => panic!("expected forbidden"),
+            Err(err) => err.into_response().status(),
+        };
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
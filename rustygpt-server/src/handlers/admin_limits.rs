@@ -16,6 +16,7 @@ use crate::{
     auth::session::SessionUser,
     http::error::{ApiError, AppResult},
     middleware::request_context::RequestContext,
+    services::audit_service::AuditContext,
 };
 use shared::models::Timestamp;
 use shared::models::{
@@ -23,7 +24,7 @@ use shared::models::{
     UpdateRateLimitProfileRequest, UserRole,
 };
 
-fn require_pool(state: &Arc<AppState>) -> AppResult<PgPool> {
+pub(crate) fn require_pool(state: &Arc<AppState>) -> AppResult<PgPool> {
     state.pool.clone().ok_or_else(|| {
         ApiError::new(
             StatusCode::SERVICE_UNAVAILABLE,
@@ -33,7 +34,7 @@ fn require_pool(state: &Arc<AppState>) -> AppResult<PgPool> {
     })
 }
 
-fn require_admin_context(context: &RequestContext) -> AppResult<&SessionUser> {
+pub(crate) fn require_admin_context(context: &RequestContext) -> AppResult<&SessionUser> {
     let session = context.session.as_ref().ok_or_else(|| {
         ApiError::new(
             StatusCode::UNAUTHORIZED,
@@ -106,30 +107,11 @@ mod tests {
     use http::StatusCode;
     use serde_json::json;
     use shared::models::CreateRateLimitProfileRequest;
-    use uuid::Uuid;
-
-    fn make_context_with_roles(roles: Vec<UserRole>) -> RequestContext {
-        let now = chrono::Utc::now();
-        RequestContext {
-            request_id: "test".into(),
-            session: Some(SessionUser {
-                id: Uuid::new_v4(),
-                email: "user@example.com".into(),
-                username: "user".into(),
-                display_name: None,
-                roles,
-                session_id: Uuid::new_v4(),
-                issued_at: now,
-                expires_at: now,
-                absolute_expires_at: now,
-            }),
-        }
-    }
 
     #[tokio::test]
     async fn list_profiles_requires_admin_role() {
         let state = Arc::new(AppState::default());
-        let context = make_context_with_roles(vec![UserRole::Member]);
+        let context = RequestContext::test_with_roles(vec![UserRole::Member]);
 
         let status = match list_profiles(Extension(state), Extension(context)).await {
             Ok(_) => panic!("expected forbidden"),
@@ -145,6 +127,7 @@ mod tests {
         let context = RequestContext {
             request_id: "test".into(),
             session: None,
+            ..RequestContext::default()
         };
 
         let status = match list_profiles(Extension(state), Extension(context)).await {
@@ -158,7 +141,7 @@ mod tests {
     #[tokio::test]
     async fn create_profile_without_pool_returns_service_unavailable() {
         let state = Arc::new(AppState::default());
-        let context = make_context_with_roles(vec![UserRole::Admin]);
+        let context = RequestContext::test_with_roles(vec![UserRole::Admin]);
         let payload = CreateRateLimitProfileRequest {
             name: "burst".into(),
             algorithm: "gcra".into(),
@@ -241,6 +224,7 @@ pub async fn create_profile(
     }
 
     let pool = require_pool(&state)?;
+    let mut tx = AuditContext::from(&context).begin(&pool).await?;
 
     let row = sqlx::query_as::<_, DbProfileRow>(
         "SELECT * FROM rustygpt.sp_limits_create_profile($1, $2, $3, $4)",
@@ -249,8 +233,9 @@ pub async fn create_profile(
     .bind(payload.algorithm.trim())
     .bind(payload.params)
     .bind(payload.description.as_deref())
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     reload_limits(&state).await;
 
@@ -266,6 +251,7 @@ pub async fn update_profile(
 ) -> AppResult<Json<RateLimitProfile>> {
     require_admin_context(&context)?;
     let pool = require_pool(&state)?;
+    let mut tx = AuditContext::from(&context).begin(&pool).await?;
 
     let row = sqlx::query_as::<_, DbProfileRow>(
         "SELECT * FROM rustygpt.sp_limits_update_profile($1, $2, $3)",
@@ -273,8 +259,9 @@ pub async fn update_profile(
     .bind(profile_id)
     .bind(payload.params)
     .bind(payload.description.as_deref())
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     reload_limits(&state).await;
 
@@ -288,11 +275,13 @@ pub async fn delete_profile(
 ) -> AppResult<Response> {
     require_admin_context(&context)?;
     let pool = require_pool(&state)?;
+    let mut tx = AuditContext::from(&context).begin(&pool).await?;
 
     let deleted = sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_limits_delete_profile($1)")
         .bind(profile_id)
-        .fetch_one(&pool)
+        .fetch_one(&mut *tx)
         .await?;
+    tx.commit().await?;
 
     if !deleted {
         return Err(ApiError::not_found("profile not found"));
@@ -338,14 +327,16 @@ pub async fn assign_route(
     let method = normalize_method(&payload.method);
     let path = normalize_path(&payload.path);
 
+    let mut tx = AuditContext::from(&context).begin(&pool).await?;
     let row = sqlx::query_as::<_, DbAssignmentRow>(
         "SELECT * FROM rustygpt.sp_limits_assign_route($1, $2, $3)",
     )
     .bind(payload.profile_id)
     .bind(method)
     .bind(path)
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     reload_limits(&state).await;
 
@@ -359,11 +350,13 @@ pub async fn delete_assignment(
 ) -> AppResult<Response> {
     require_admin_context(&context)?;
     let pool = require_pool(&state)?;
+    let mut tx = AuditContext::from(&context).begin(&pool).await?;
 
     let deleted = sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_limits_delete_assignment($1)")
         .bind(assignment_id)
-        .fetch_one(&pool)
+        .fetch_one(&mut *tx)
         .await?;
+    tx.commit().await?;
 
     if !deleted {
         return Err(ApiError::not_found("assignment not found"));
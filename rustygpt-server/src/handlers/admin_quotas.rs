@@ -0,0 +1,210 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    handlers::admin_limits::{require_admin_context, require_pool},
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::chat_service::ChatServiceError,
+};
+use shared::models::{Timestamp, TokenQuota, TokenQuotaTarget, UpsertTokenQuotaRequest};
+
+#[derive(sqlx::FromRow)]
+struct DbQuotaRow {
+    quota_id: Uuid,
+    target_kind: String,
+    user_id: Option<Uuid>,
+    role: Option<String>,
+    profile_id: Option<Uuid>,
+    org_id: Option<Uuid>,
+    target_label: Option<String>,
+    daily_tokens: Option<i64>,
+    monthly_tokens: Option<i64>,
+    description: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn apply_quota(row: DbQuotaRow) -> AppResult<TokenQuota> {
+    let target = match (
+        row.target_kind.as_str(),
+        row.user_id,
+        row.role,
+        row.profile_id,
+        row.org_id,
+    ) {
+        ("user", Some(user_id), ..) => TokenQuotaTarget::User { user_id },
+        ("role", _, Some(role), ..) => TokenQuotaTarget::Role {
+            role: role
+                .parse()
+                .map_err(|_| ApiError::internal_server_error(format!("unknown role {role}")))?,
+        },
+        ("profile", _, _, Some(profile_id), _) => TokenQuotaTarget::Profile { profile_id },
+        ("org", _, _, _, Some(org_id)) => TokenQuotaTarget::Org { org_id },
+        (kind, ..) => {
+            return Err(ApiError::internal_server_error(format!(
+                "malformed {kind} quota {}",
+                row.quota_id
+            )));
+        }
+    };
+
+    Ok(TokenQuota {
+        id: row.quota_id,
+        target,
+        target_label: row.target_label,
+        daily_tokens: row.daily_tokens,
+        monthly_tokens: row.monthly_tokens,
+        description: row.description,
+        created_at: Timestamp(row.created_at),
+        updated_at: Timestamp(row.updated_at),
+    })
+}
+
+async fn fetch_quotas(pool: &PgPool) -> AppResult<Vec<TokenQuota>> {
+    let rows = sqlx::query_as::<_, DbQuotaRow>(
+        "SELECT quota_id, target_kind, user_id, role, profile_id, org_id, target_label, daily_tokens, monthly_tokens, description, created_at, updated_at FROM rustygpt.sp_quota_list()",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(apply_quota).collect()
+}
+
+pub async fn list_quotas(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<Vec<TokenQuota>>> {
+    require_admin_context(&context)?;
+    let pool = require_pool(&state)?;
+
+    Ok(Json(fetch_quotas(&pool).await?))
+}
+
+/// Sets the budgets for a target, replacing any quota it already has.
+pub async fn upsert_quota(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Json(payload): Json<UpsertTokenQuotaRequest>,
+) -> AppResult<Json<TokenQuota>> {
+    require_admin_context(&context)?;
+    let pool = require_pool(&state)?;
+
+    let (kind, user_id, role, profile_id, org_id) = match payload.target {
+        TokenQuotaTarget::User { user_id } => ("user", Some(user_id), None, None, None),
+        TokenQuotaTarget::Role { role } => ("role", None, Some(role.as_str()), None, None),
+        TokenQuotaTarget::Profile { profile_id } => ("profile", None, None, Some(profile_id), None),
+        TokenQuotaTarget::Org { org_id } => ("org", None, None, None, Some(org_id)),
+    };
+
+    let quota_id = sqlx::query_scalar::<_, Uuid>(
+        "SELECT rustygpt.sp_quota_upsert($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+    .bind(kind)
+    .bind(user_id)
+    .bind(role)
+    .bind(profile_id)
+    .bind(org_id)
+    .bind(payload.daily_tokens)
+    .bind(payload.monthly_tokens)
+    .bind(payload.description.as_deref())
+    .fetch_one(&pool)
+    .await
+    .map_err(ChatServiceError::from_db_error)?;
+
+    fetch_quotas(&pool)
+        .await?
+        .into_iter()
+        .find(|quota| quota.id == quota_id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("quota not found"))
+}
+
+pub async fn delete_quota(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(quota_id): Path<Uuid>,
+) -> AppResult<Response> {
+    require_admin_context(&context)?;
+    let pool = require_pool(&state)?;
+
+    let deleted = sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_quota_delete($1)")
+        .bind(quota_id)
+        .fetch_one(&pool)
+        .await?;
+
+    if !deleted {
+        return Err(ApiError::not_found("quota not found"));
+    }
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::UserRole;
+
+    #[tokio::test]
+    async fn upsert_quota_requires_admin_role() {
+        let state = Arc::new(AppState::default());
+        let payload = UpsertTokenQuotaRequest {
+            target: TokenQuotaTarget::Role {
+                role: UserRole::Member,
+            },
+            daily_tokens: Some(10_000),
+            monthly_tokens: None,
+            description: None,
+        };
+
+        let status = match upsert_quota(
+            Extension(state),
+            Extension(RequestContext::test_with_roles(vec![UserRole::Member])),
+            Json(payload),
+        )
+        .await
+        {
+            Ok(_) => panic!("expected forbidden"),
+            Err(err) => err.into_response().status(),
+        };
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn quota_rows_map_to_their_target() {
+        let now = chrono::Utc::now();
+        let row = DbQuotaRow {
+            quota_id: Uuid::new_v4(),
+            target_kind: "role".into(),
+            user_id: None,
+            role: Some("admin".into()),
+            profile_id: None,
+            org_id: None,
+            target_label: Some("admin".into()),
+            daily_tokens: None,
+            monthly_tokens: Some(1_000_000),
+            description: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let quota = apply_quota(row).expect("role quota");
+        assert_eq!(
+            quota.target,
+            TokenQuotaTarget::Role {
+                role: UserRole::Admin
+            }
+        );
+        assert_eq!(quota.monthly_tokens, Some(1_000_000));
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::Extension};
+
+use crate::{
+    app_state::AppState,
+    handlers::admin_limits::{require_admin_context, require_pool},
+    http::error::AppResult,
+    middleware::request_context::RequestContext,
+    services::chat_service::ChatService,
+};
+use shared::models::{RetentionPolicy, UpdateRetentionPolicyRequest};
+
+pub async fn get_global_policy(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<RetentionPolicy>> {
+    let admin = require_admin_context(&context)?;
+    let pool = require_pool(&state)?;
+
+    let policy = ChatService::new(pool)
+        .get_retention_policy(admin.id, None)
+        .await?;
+    Ok(Json(policy))
+}
+
+pub async fn update_global_policy(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Json(payload): Json<UpdateRetentionPolicyRequest>,
+) -> AppResult<Json<RetentionPolicy>> {
+    let admin = require_admin_context(&context)?;
+    let pool = require_pool(&state)?;
+
+    let policy = ChatService::new(pool)
+        .set_retention_policy(admin.id, None, payload)
+        .await?;
+    Ok(Json(policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse};
+    use shared::models::UserRole;
+
+    #[tokio::test]
+    async fn update_global_policy_requires_admin_role() {
+        let state = Arc::new(AppState::default());
+        let payload = UpdateRetentionPolicyRequest {
+            archive_after_days: Some(30),
+            purge_deleted_after_days: None,
+        };
+
+        let status = match update_global_policy(
+            Extension(state),
+            Extension(RequestContext::test_with_roles(vec![UserRole::Member])),
+            Json(payload),
+        )
+        .await
+        {
+            Ok(_) => panic!("expected forbidden"),
+            Err(err) => err.into_response().status(),
+        };
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,242 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Extension, Path, Query},
+    http::{HeaderMap, StatusCode},
+};
+use shared::{
+    config::server::Config,
+    models::{
+        AdminPasswordResetRequest, AdminPasswordResetResponse, CreateUserAccountRequest,
+        RoleSummary, UserAccount, UserAccountPage, UserAccountQuery, UserRole,
+    },
+};
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    auth::user_admin::UserAdminService,
+    handlers::{
+        admin_limits::{require_admin_context, require_pool},
+        auth::{map_session_error, metadata_from_headers, session_service},
+    },
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::audit_service::AuditContext,
+};
+
+fn user_admin(state: &Arc<AppState>, config: Arc<Config>) -> AppResult<UserAdminService> {
+    Ok(UserAdminService::new(require_pool(state)?, config))
+}
+
+#[instrument(skip(state, config, context))]
+pub async fn list_users(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+    Query(query): Query<UserAccountQuery>,
+) -> AppResult<Json<UserAccountPage>> {
+    require_admin_context(&context)?;
+    let page = user_admin(&state, config)?.list(&query).await?;
+    Ok(Json(page))
+}
+
+#[instrument(skip(state, config, context))]
+pub async fn get_user(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<Json<UserAccount>> {
+    require_admin_context(&context)?;
+    let user = user_admin(&state, config)?.get(user_id).await?;
+    Ok(Json(user))
+}
+
+/// Creates an account; without a password the new user is emailed a link to choose one.
+#[instrument(skip(state, config, context, payload))]
+pub async fn create_user(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+    Json(payload): Json<CreateUserAccountRequest>,
+) -> AppResult<(StatusCode, Json<UserAccount>)> {
+    require_admin_context(&context)?;
+    let user = user_admin(&state, config)?
+        .create(&AuditContext::from(&context), &payload)
+        .await?;
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+/// Disables an account and revokes all of its sessions.
+#[instrument(skip(state, config, context))]
+pub async fn disable_user(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<Json<UserAccount>> {
+    set_disabled(&state, config, &context, user_id, true).await
+}
+
+#[instrument(skip(state, config, context))]
+pub async fn enable_user(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<Json<UserAccount>> {
+    set_disabled(&state, config, &context, user_id, false).await
+}
+
+async fn set_disabled(
+    state: &Arc<AppState>,
+    config: Arc<Config>,
+    context: &RequestContext,
+    user_id: Uuid,
+    disabled: bool,
+) -> AppResult<Json<UserAccount>> {
+    require_admin_context(context)?;
+    let service = user_admin(state, config)?;
+    service
+        .set_disabled(&AuditContext::from(context), user_id, disabled)
+        .await?;
+    Ok(Json(service.get(user_id).await?))
+}
+
+#[instrument(skip(state, config, context))]
+pub async fn grant_role(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+    Path((user_id, role)): Path<(Uuid, UserRole)>,
+) -> AppResult<Json<UserAccount>> {
+    set_role(&state, config, &context, user_id, role, true).await
+}
+
+#[instrument(skip(state, config, context))]
+pub async fn revoke_role(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+    Path((user_id, role)): Path<(Uuid, UserRole)>,
+) -> AppResult<Json<UserAccount>> {
+    set_role(&state, config, &context, user_id, role, false).await
+}
+
+/// Applies a global role change and rotates the user's sessions so it takes effect on their
+/// next request.
+async fn set_role(
+    state: &Arc<AppState>,
+    config: Arc<Config>,
+    context: &RequestContext,
+    user_id: Uuid,
+    role: UserRole,
+    granted: bool,
+) -> AppResult<Json<UserAccount>> {
+    require_admin_context(context)?;
+    let service = user_admin(state, config)?;
+    let changed = service
+        .set_role(&AuditContext::from(context), user_id, role, granted)
+        .await?;
+
+    if let Some(sessions) = state.sessions.as_ref().filter(|_| changed) {
+        sessions
+            .mark_user_for_rotation(user_id, "role_change")
+            .await
+            .map_err(|err| {
+                warn!(
+                    error = %err,
+                    user_id = %user_id,
+                    "failed to mark session rotation after role change"
+                );
+                ApiError::internal_server_error("failed to flag session rotation for user")
+            })?;
+    }
+    Ok(Json(service.get(user_id).await?))
+}
+
+/// Sets a new password (signing the user out everywhere) or emails a reset link.
+#[instrument(skip(state, config, context, payload))]
+pub async fn reset_password(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<AdminPasswordResetRequest>,
+) -> AppResult<Json<AdminPasswordResetResponse>> {
+    require_admin_context(&context)?;
+    let response = user_admin(&state, config)?
+        .reset_password(
+            &AuditContext::from(&context),
+            user_id,
+            payload.password.as_deref(),
+        )
+        .await?;
+    Ok(Json(response))
+}
+
+#[instrument(skip(state, config, context))]
+pub async fn list_roles(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<Vec<RoleSummary>>> {
+    require_admin_context(&context)?;
+    let roles = user_admin(&state, config)?.role_summaries().await?;
+    Ok(Json(roles))
+}
+
+/// Lifts a failed-login lockout before it expires and resets the failure count.
+#[instrument(skip(state, context, headers))]
+pub async fn unlock_user(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    let admin = require_admin_context(&context)?.id;
+    let metadata = metadata_from_headers(&headers).with_request_id(context.request_id.clone());
+    session_service(&state)?
+        .unlock_user(user_id, admin, &metadata)
+        .await
+        .map_err(map_session_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use shared::config::server::Profile;
+
+    #[tokio::test]
+    async fn user_management_requires_admin_role() {
+        let state = Arc::new(AppState::default());
+        let config = Arc::new(Config::default_for_profile(Profile::Test));
+
+        let listed = list_users(
+            Extension(state.clone()),
+            Extension(config.clone()),
+            Extension(RequestContext::test_with_roles(vec![UserRole::Member])),
+            Query(UserAccountQuery::default()),
+        )
+        .await;
+        let granted = grant_role(
+            Extension(state),
+            Extension(config),
+            Extension(RequestContext::test_with_roles(vec![UserRole::Member])),
+            Path((Uuid::new_v4(), UserRole::Admin)),
+        )
+        .await;
+
+        for status in [
+            listed.map(|_| ()).unwrap_err().into_response().status(),
+            granted.map(|_| ()).unwrap_err().into_response().status(),
+        ] {
+            assert_eq!(status, StatusCode::FORBIDDEN);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    routing::{get, post},
+};
+use shared::models::{
+    CreateWebhookRequest, CreateWebhookResponse, UpdateWebhookRequest, WebhookDelivery,
+    WebhookDeliveryDetail, WebhookDeliveryQuery, WebhookSubscription,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    handlers::admin_limits::{require_admin_context, require_pool},
+    http::error::AppResult,
+    middleware::request_context::RequestContext,
+    services::{audit_service::AuditContext, webhook_service::WebhookService},
+};
+
+pub fn admin_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/admin/webhooks", get(list_webhooks).post(create_webhook))
+        .route(
+            "/admin/webhooks/{id}",
+            get(get_webhook).put(update_webhook).delete(delete_webhook),
+        )
+        .route("/admin/webhooks/{id}/deliveries", get(list_deliveries))
+        .route(
+            "/admin/webhooks/deliveries/{delivery_id}",
+            get(get_delivery),
+        )
+        .route(
+            "/admin/webhooks/deliveries/{delivery_id}/replay",
+            post(replay_delivery),
+        )
+}
+
+fn webhooks(state: &Arc<AppState>, context: &RequestContext) -> AppResult<WebhookService> {
+    require_admin_context(context)?;
+    Ok(WebhookService::new(require_pool(state)?))
+}
+
+#[instrument(skip(state, context))]
+pub async fn list_webhooks(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<Vec<WebhookSubscription>>> {
+    Ok(Json(webhooks(&state, &context)?.list().await?))
+}
+
+#[instrument(skip(state, context))]
+pub async fn get_webhook(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<WebhookSubscription>> {
+    Ok(Json(webhooks(&state, &context)?.get(id).await?))
+}
+
+/// Creates a subscription; the response carries the signing secret, which is not shown again.
+#[instrument(skip(state, context, payload))]
+pub async fn create_webhook(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> AppResult<(StatusCode, Json<CreateWebhookResponse>)> {
+    let created = webhooks(&state, &context)?
+        .create(&AuditContext::from(&context), &payload)
+        .await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[instrument(skip(state, context, payload))]
+pub async fn update_webhook(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateWebhookRequest>,
+) -> AppResult<Json<WebhookSubscription>> {
+    let updated = webhooks(&state, &context)?
+        .update(&AuditContext::from(&context), id, &payload)
+        .await?;
+    Ok(Json(updated))
+}
+
+#[instrument(skip(state, context))]
+pub async fn delete_webhook(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    webhooks(&state, &context)?
+        .delete(&AuditContext::from(&context), id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[instrument(skip(state, context))]
+pub async fn list_deliveries(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<WebhookDeliveryQuery>,
+) -> AppResult<Json<Vec<WebhookDelivery>>> {
+    Ok(Json(
+        webhooks(&state, &context)?.deliveries(id, &query).await?,
+    ))
+}
+
+#[instrument(skip(state, context))]
+pub async fn get_delivery(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(delivery_id): Path<Uuid>,
+) -> AppResult<Json<WebhookDeliveryDetail>> {
+    Ok(Json(
+        webhooks(&state, &context)?.delivery(delivery_id).await?,
+    ))
+}
+
+/// Queues the delivery again with the same event id and body, whatever its state.
+#[instrument(skip(state, context))]
+pub async fn replay_delivery(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(delivery_id): Path<Uuid>,
+) -> AppResult<(StatusCode, Json<WebhookDeliveryDetail>)> {
+    let replay = webhooks(&state, &context)?
+        .replay(&AuditContext::from(&context), delivery_id)
+        .await?;
+    Ok((StatusCode::ACCEPTED, Json(replay)))
+}
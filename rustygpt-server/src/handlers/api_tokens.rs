@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get},
+};
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    auth::api_tokens::{ApiTokenPrincipal, ApiTokenService},
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+};
+use shared::models::{ApiTokenListResponse, CreateApiTokenRequest};
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/tokens", get(list_tokens).post(create_token))
+        .route("/api/tokens/{token_id}", delete(revoke_token))
+}
+
+#[instrument(skip(app_state, context, principal, payload))]
+async fn create_token(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+    Json(payload): Json<CreateApiTokenRequest>,
+) -> AppResult<impl IntoResponse> {
+    let actor = require_session_user(&context, principal.as_ref())?;
+    let pool = require_pool(&app_state)?;
+
+    let created = ApiTokenService::new(pool).create(actor, payload).await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[instrument(skip(app_state, context, principal))]
+async fn list_tokens(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+) -> AppResult<Json<ApiTokenListResponse>> {
+    let actor = require_session_user(&context, principal.as_ref())?;
+    let pool = require_pool(&app_state)?;
+
+    let tokens = ApiTokenService::new(pool).list(actor).await?;
+    Ok(Json(ApiTokenListResponse { tokens }))
+}
+
+#[instrument(skip(app_state, context, principal))]
+async fn revoke_token(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+    Path(token_id): Path<Uuid>,
+) -> AppResult<impl IntoResponse> {
+    let actor = require_session_user(&context, principal.as_ref())?;
+    let pool = require_pool(&app_state)?;
+
+    ApiTokenService::new(pool).revoke(actor, token_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Token management needs an interactive session so a leaked token cannot mint more.
+pub(crate) fn require_session_user(
+    context: &RequestContext,
+    principal: Option<&Extension<ApiTokenPrincipal>>,
+) -> AppResult<Uuid> {
+    if principal.is_some() {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "RGP.AUTH.SESSION_REQUIRED",
+            "API tokens cannot manage API tokens",
+        ));
+    }
+
+    context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))
+}
+
+fn require_pool(state: &AppState) -> AppResult<PgPool> {
+    state.pool.clone().ok_or_else(|| {
+        ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "database_unavailable",
+            "database pool not configured",
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use shared::models::UserRole;
+
+    #[test]
+    fn token_authenticated_requests_cannot_manage_tokens() {
+        let user_id = Uuid::new_v4();
+        let principal = ApiTokenPrincipal {
+            token_id: Uuid::new_v4(),
+            user_id,
+            email: "user@example.com".into(),
+            username: "user".into(),
+            display_name: None,
+            roles: vec![UserRole::Member],
+            scopes: vec![shared::models::ApiTokenScope::ThreadsWrite],
+            created_at: Utc::now(),
+            expires_at: None,
+        };
+        let context = RequestContext {
+            request_id: "test".into(),
+            session: Some(principal.session_user()),
+            ..RequestContext::default()
+        };
+
+        let err = require_session_user(&context, Some(&Extension(principal)))
+            .expect_err("token callers are rejected");
+        assert_eq!(err.into_response().status(), StatusCode::FORBIDDEN);
+        assert_eq!(require_session_user(&context, None).ok(), Some(user_id));
+    }
+}
@@ -10,7 +10,9 @@ use tracing::instrument;
 
 use crate::{
     app_state::AppState,
-    auth::session::{SessionError, SessionManager, SessionMetadata},
+    auth::session::{
+        AuthenticatedLogin, LoginOutcome, SessionError, SessionManager, SessionMetadata,
+    },
     http::error::{ApiError, AppResult},
     middleware::request_context::RequestContext,
 };
@@ -18,7 +20,8 @@ use serde_json::json;
 use shared::{
     config::server::{Config, CookieSameSite},
     models::{
-        AuthenticatedUser, LoginRequest, LoginResponse, MeResponse, SessionSummary, Timestamp,
+        AuthenticatedUser, LoginRequest, LoginResponse, MeResponse, MfaChallengeResponse,
+        MfaVerifyRequest, SessionSummary, Timestamp,
     },
 };
 use time::{Duration as TimeDuration, OffsetDateTime};
@@ -40,6 +43,16 @@ pub fn map_session_error(error: SessionError) -> ApiError {
             "RGP.AUTH.DISABLED",
             "user account disabled",
         ),
+        SessionError::AccountLocked { until } => {
+            let retry_after = (until - chrono::Utc::now()).num_seconds().max(1);
+            ApiError::new(
+                StatusCode::LOCKED,
+                "RGP.AUTH.LOCKED",
+                "too many failed sign-in attempts; try again later",
+            )
+            .with_details(json!({ "locked_until": until.to_rfc3339() }))
+            .with_header(header::RETRY_AFTER, HeaderValue::from(retry_after))
+        }
         SessionError::RotationRequired => ApiError::new(
             StatusCode::CONFLICT,
             "RGP.AUTH.ROTATION_REQUIRED",
@@ -54,6 +67,18 @@ pub fn map_session_error(error: SessionError) -> ApiError {
             header::WWW_AUTHENTICATE,
             HeaderValue::from_static("refresh"),
         ),
+        SessionError::InvalidMfaCode => ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "RGP.AUTH.MFA_INVALID",
+            "invalid two-factor code",
+        ),
+        SessionError::MfaChallengeInvalid => ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "RGP.AUTH.MFA_CHALLENGE",
+            "two-factor challenge expired; sign in again",
+        ),
+        SessionError::SessionNotFound => ApiError::not_found("session not found"),
+        SessionError::UserNotFound => ApiError::not_found("user not found"),
         other => ApiError::internal_server_error(other.to_string()),
     }
 }
@@ -131,7 +156,7 @@ const fn build_session_summary(bundle: &crate::auth::session::SessionBundle) ->
     }
 }
 
-fn apply_cookies(response: &mut Response, cookies: &[cookie::Cookie<'static>]) {
+pub(crate) fn apply_cookies(response: &mut Response, cookies: &[cookie::Cookie<'static>]) {
     for cookie in cookies {
         if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
             response.headers_mut().append(header::SET_COOKIE, value);
@@ -139,7 +164,7 @@ fn apply_cookies(response: &mut Response, cookies: &[cookie::Cookie<'static>]) {
     }
 }
 
-fn session_service(state: &Arc<AppState>) -> Result<Arc<dyn SessionManager>, ApiError> {
+pub(crate) fn session_service(state: &Arc<AppState>) -> Result<Arc<dyn SessionManager>, ApiError> {
     state
         .sessions
         .clone()
@@ -167,14 +192,16 @@ fn clear_cookie(
     builder.build()
 }
 
-#[instrument(skip(state, headers, payload))]
+#[instrument(skip(state, context, headers, payload))]
 pub async fn login(
     Extension(state): Extension<Arc<AppState>>,
+    context: Option<Extension<RequestContext>>,
     headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
 ) -> AppResult<Response> {
     let service = session_service(&state)?;
-    let metadata = metadata_from_headers(&headers);
+    let metadata = metadata_from_headers(&headers)
+        .with_request_id(context.map(|Extension(context)| context.request_id));
 
     if payload.email.trim().is_empty() || payload.password.trim().is_empty() {
         return Err(ApiError::new(
@@ -184,24 +211,68 @@ pub async fn login(
         ));
     }
 
-    let (user, bundle) = service
+    let outcome = service
         .authenticate(payload.email.trim(), &payload.password, &metadata)
         .await
         .map_err(map_session_error)?;
 
+    match outcome {
+        LoginOutcome::Authenticated(login) => Ok(login_response(*login)),
+        LoginOutcome::MfaRequired(challenge) => {
+            let body = MfaChallengeResponse {
+                challenge_token: challenge.token,
+                expires_at: Timestamp(challenge.expires_at),
+                enrollment: challenge.enrollment,
+            };
+            Ok((StatusCode::ACCEPTED, Json(body)).into_response())
+        }
+    }
+}
+
+/// Second login step: exchanges a challenge token and a TOTP or recovery code for a session.
+#[instrument(skip(state, context, headers, payload))]
+pub async fn login_mfa(
+    Extension(state): Extension<Arc<AppState>>,
+    context: Option<Extension<RequestContext>>,
+    headers: HeaderMap,
+    Json(payload): Json<MfaVerifyRequest>,
+) -> AppResult<Response> {
+    let service = session_service(&state)?;
+    let metadata = metadata_from_headers(&headers)
+        .with_request_id(context.map(|Extension(context)| context.request_id));
+
+    if payload.challenge_token.trim().is_empty() || payload.code.trim().is_empty() {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_request",
+            "challenge_token and code are required",
+        ));
+    }
+
+    let login = service
+        .complete_mfa_login(&payload.challenge_token, &payload.code, &metadata)
+        .await
+        .map_err(map_session_error)?;
+
+    Ok(login_response(login))
+}
+
+fn login_response(login: AuthenticatedLogin) -> Response {
+    let AuthenticatedLogin {
+        user,
+        bundle,
+        recovery_codes,
+    } = login;
     let response_body = LoginResponse {
         user: build_authenticated_user(&user),
         session: build_session_summary(&bundle),
         csrf_token: bundle.csrf_token.clone(),
+        recovery_codes,
     };
 
     let mut response = Json(response_body).into_response();
-    apply_cookies(
-        &mut response,
-        &[bundle.session_cookie.clone(), bundle.csrf_cookie.clone()],
-    );
-
-    Ok(response)
+    apply_cookies(&mut response, &[bundle.session_cookie, bundle.csrf_cookie]);
+    response
 }
 
 #[instrument(skip(state, headers))]
@@ -238,6 +309,7 @@ pub async fn refresh(
         user: build_authenticated_user(&user),
         session: build_session_summary(&bundle),
         csrf_token: bundle.csrf_token.clone(),
+        recovery_codes: Vec::new(),
     };
 
     let mut response = Json(response_body).into_response();
@@ -277,14 +349,16 @@ pub async fn me(Extension(context): Extension<RequestContext>) -> AppResult<Json
     }))
 }
 
-#[instrument(skip(state, headers))]
+#[instrument(skip(state, context, headers))]
 pub async fn logout(
     Extension(state): Extension<Arc<AppState>>,
     Extension(config): Extension<Arc<Config>>,
+    context: Option<Extension<RequestContext>>,
     headers: HeaderMap,
 ) -> AppResult<Response> {
     let service = session_service(&state)?;
-    let metadata = metadata_from_headers(&headers);
+    let metadata = metadata_from_headers(&headers)
+        .with_request_id(context.map(|Extension(context)| context.request_id));
     let session_cookie_name = config.session.session_cookie_name.clone();
 
     let token = extract_session_cookie(&headers, &session_cookie_name).ok_or_else(|| {
@@ -308,7 +382,7 @@ pub async fn logout(
         })?;
 
     service
-        .revoke_session_by_id(validation.user.session_id, Some("logout"))
+        .revoke_session_by_id(validation.user.session_id, Some("logout"), &metadata)
         .await
         .map_err(map_session_error)?;
 
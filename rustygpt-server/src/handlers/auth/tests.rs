@@ -1,8 +1,11 @@
 use super::*;
 use crate::{
     auth::session::{
-        SessionBundle, SessionManager, SessionMetadata, SessionUser, SessionValidation,
+        AuthenticatedLogin, LoginOutcome, MfaChallenge, SessionBundle, SessionManager,
+        SessionMetadata, SessionRecord, SessionUser, SessionValidation,
     },
+    handlers::{admin_users, sessions},
+    middleware::request_context::RequestContext,
     middleware::{auth::auth_middleware, csrf},
     server,
     services::chat_service::ChatServiceError,
@@ -16,7 +19,7 @@ use axum::{
     routing::post,
 };
 use axum_test::TestServer;
-use chrono::{Duration as ChronoDuration, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use cookie::{Cookie, SameSite};
 use serde_json::json;
 use shared::config::server::{Config, Profile};
@@ -65,6 +68,18 @@ fn map_session_error_maps_status_codes() {
         .status();
     assert_eq!(locked, StatusCode::LOCKED);
 
+    let lockout = map_session_error(SessionError::AccountLocked {
+        until: Utc::now() + ChronoDuration::seconds(90),
+    })
+    .into_response();
+    assert_eq!(lockout.status(), StatusCode::LOCKED);
+    let retry_after: i64 = lockout.headers()[header::RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=90).contains(&retry_after));
+
     let conflict = map_session_error(SessionError::RotationRequired)
         .into_response()
         .status();
@@ -150,6 +165,7 @@ fn refresh_rotates_cookie_and_sets_x_session_rotated() {
         user: build_authenticated_user(&sample_session_user()),
         session: build_session_summary(&bundle),
         csrf_token: bundle.csrf_token.clone(),
+        recovery_codes: Vec::new(),
     };
 
     let mut response = Json(response_body).into_response();
@@ -237,14 +253,38 @@ type RefreshResult = Result<Option<(SessionUser, SessionBundle)>, SessionError>;
 
 #[derive(Default)]
 struct StubSessionManager {
-    authenticate: Mutex<VecDeque<Result<(SessionUser, SessionBundle), SessionError>>>,
+    authenticate: Mutex<VecDeque<Result<LoginOutcome, SessionError>>>,
+    complete_mfa: Mutex<VecDeque<Result<AuthenticatedLogin, SessionError>>>,
     validate: Mutex<VecDeque<Result<Option<SessionValidation>, SessionError>>>,
     refresh: Mutex<VecDeque<RefreshResult>>,
+    sessions: Mutex<Vec<SessionRecord>>,
+    /// `(user_id, kept session, actor)` for every bulk revocation.
+    bulk_revocations: Mutex<Vec<(Uuid, Option<Uuid>, Uuid)>>,
+    /// `(user_id, actor)` for every unlock.
+    unlocks: Mutex<Vec<(Uuid, Uuid)>>,
 }
 
 impl StubSessionManager {
     fn enqueue_auth(&self, response: Result<(SessionUser, SessionBundle), SessionError>) {
-        self.authenticate.lock().unwrap().push_back(response);
+        let outcome = response.map(|(user, bundle)| {
+            LoginOutcome::Authenticated(Box::new(AuthenticatedLogin {
+                user,
+                bundle,
+                recovery_codes: Vec::new(),
+            }))
+        });
+        self.authenticate.lock().unwrap().push_back(outcome);
+    }
+
+    fn enqueue_mfa_challenge(&self, challenge: MfaChallenge) {
+        self.authenticate
+            .lock()
+            .unwrap()
+            .push_back(Ok(LoginOutcome::MfaRequired(challenge)));
+    }
+
+    fn enqueue_complete_mfa(&self, response: Result<AuthenticatedLogin, SessionError>) {
+        self.complete_mfa.lock().unwrap().push_back(response);
     }
 
     fn enqueue_validate(&self, response: Result<Option<SessionValidation>, SessionError>) {
@@ -263,7 +303,33 @@ impl SessionManager for StubSessionManager {
         _identifier: &str,
         _password: &str,
         _metadata: &SessionMetadata,
-    ) -> Result<(SessionUser, SessionBundle), SessionError> {
+    ) -> Result<LoginOutcome, SessionError> {
+        self.authenticate
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("missing authenticate response")
+    }
+
+    async fn complete_mfa_login(
+        &self,
+        _challenge_token: &str,
+        _code: &str,
+        _metadata: &SessionMetadata,
+    ) -> Result<AuthenticatedLogin, SessionError> {
+        self.complete_mfa
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("missing complete_mfa_login response")
+    }
+
+    async fn authenticate_federated(
+        &self,
+        _user_id: Uuid,
+        _provider: &str,
+        _metadata: &SessionMetadata,
+    ) -> Result<LoginOutcome, SessionError> {
         self.authenticate
             .lock()
             .unwrap()
@@ -291,6 +357,7 @@ impl SessionManager for StubSessionManager {
         &self,
         _session_id: Uuid,
         _reason: Option<&str>,
+        _metadata: &SessionMetadata,
     ) -> Result<(), SessionError> {
         Ok(())
     }
@@ -302,6 +369,53 @@ impl SessionManager for StubSessionManager {
     ) -> Result<i64, SessionError> {
         Ok(0)
     }
+
+    async fn list_sessions(&self, _user_id: Uuid) -> Result<Vec<SessionRecord>, SessionError> {
+        Ok(self.sessions.lock().unwrap().clone())
+    }
+
+    async fn revoke_user_session(
+        &self,
+        _user_id: Uuid,
+        session_id: Uuid,
+        _actor: Uuid,
+        _metadata: &SessionMetadata,
+    ) -> Result<(), SessionError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|record| record.id != session_id);
+        if sessions.len() == before {
+            return Err(SessionError::SessionNotFound);
+        }
+        Ok(())
+    }
+
+    async fn revoke_user_sessions(
+        &self,
+        user_id: Uuid,
+        keep: Option<Uuid>,
+        actor: Uuid,
+        _metadata: &SessionMetadata,
+    ) -> Result<u64, SessionError> {
+        self.bulk_revocations
+            .lock()
+            .unwrap()
+            .push((user_id, keep, actor));
+        let mut sessions = self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|record| Some(record.id) == keep);
+        Ok(u64::try_from(before - sessions.len()).unwrap())
+    }
+
+    async fn unlock_user(
+        &self,
+        user_id: Uuid,
+        actor: Uuid,
+        _metadata: &SessionMetadata,
+    ) -> Result<Option<DateTime<Utc>>, SessionError> {
+        self.unlocks.lock().unwrap().push((user_id, actor));
+        Ok(None)
+    }
 }
 
 fn test_config() -> Arc<Config> {
@@ -397,6 +511,108 @@ async fn login_handler_sets_cookies_and_returns_payload() {
     assert_eq!(csrf.value(), "csrf-token");
 }
 
+#[tokio::test]
+async fn login_returns_challenge_without_cookies_when_mfa_required() {
+    let stub = Arc::new(StubSessionManager::default());
+    stub.enqueue_mfa_challenge(MfaChallenge {
+        token: "challenge-token".into(),
+        expires_at: Utc::now() + ChronoDuration::minutes(5),
+        enrollment: None,
+    });
+
+    let session_manager: Arc<dyn SessionManager> = stub.clone();
+    let state = server::create_app_state(None, None, None, Some(session_manager), None, None);
+
+    let app = Router::new()
+        .route("/api/auth/login", post(login))
+        .layer(Extension(state));
+
+    let server = TestServer::new(app).expect("test server");
+    let response = server
+        .post("/api/auth/login")
+        .json(&LoginRequest {
+            email: "integration@example.com".into(),
+            password: "secret".into(),
+        })
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::ACCEPTED);
+    assert!(response.cookies().iter().next().is_none());
+    let body: MfaChallengeResponse = response.json();
+    assert_eq!(body.challenge_token, "challenge-token");
+    assert!(body.enrollment.is_none());
+}
+
+#[tokio::test]
+async fn login_reports_lockout_with_retry_after() {
+    let stub = Arc::new(StubSessionManager::default());
+    stub.enqueue_auth(Err(SessionError::AccountLocked {
+        until: Utc::now() + ChronoDuration::minutes(2),
+    }));
+
+    let session_manager: Arc<dyn SessionManager> = stub.clone();
+    let state = server::create_app_state(None, None, None, Some(session_manager), None, None);
+
+    let app = Router::new()
+        .route("/api/auth/login", post(login))
+        .layer(Extension(state));
+
+    let server = TestServer::new(app).expect("test server");
+    let response = server
+        .post("/api/auth/login")
+        .json(&LoginRequest {
+            email: "integration@example.com".into(),
+            password: "wrong".into(),
+        })
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::LOCKED);
+    assert!(response.headers().contains_key(header::RETRY_AFTER));
+    assert!(response.cookies().iter().next().is_none());
+    let payload: serde_json::Value = response.json();
+    assert_eq!(payload["code"], "RGP.AUTH.LOCKED");
+}
+
+#[tokio::test]
+async fn login_mfa_issues_session_and_returns_recovery_codes() {
+    let stub = Arc::new(StubSessionManager::default());
+    let (user, bundle) = build_session_artifacts();
+    stub.enqueue_complete_mfa(Err(SessionError::InvalidMfaCode));
+    stub.enqueue_complete_mfa(Ok(AuthenticatedLogin {
+        user: user.clone(),
+        bundle: bundle.clone(),
+        recovery_codes: vec!["abcde-fghij".into()],
+    }));
+
+    let session_manager: Arc<dyn SessionManager> = stub.clone();
+    let state = server::create_app_state(None, None, None, Some(session_manager), None, None);
+
+    let app = Router::new()
+        .route("/api/auth/login/mfa", post(login_mfa))
+        .layer(Extension(state));
+
+    let server = TestServer::new(app).expect("test server");
+    let request = MfaVerifyRequest {
+        challenge_token: "challenge-token".into(),
+        code: "123456".into(),
+    };
+
+    let rejected = server.post("/api/auth/login/mfa").json(&request).await;
+    assert_eq!(rejected.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server.post("/api/auth/login/mfa").json(&request).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(
+        response
+            .cookies()
+            .iter()
+            .any(|cookie| cookie.name() == "SESSION_ID")
+    );
+    let body: LoginResponse = response.json();
+    assert_eq!(body.user.id, user.id);
+    assert_eq!(body.recovery_codes, vec!["abcde-fghij".to_string()]);
+}
+
 #[tokio::test]
 async fn refresh_returns_rotated_session_and_header() {
     let config = test_config();
@@ -577,3 +793,156 @@ async fn mutation_without_csrf_is_rejected() {
     let payload: serde_json::Value = response.json();
     assert_eq!(payload["code"], "RGP.AUTH.CSRF");
 }
+
+fn session_record(id: Uuid, user_agent: &str) -> SessionRecord {
+    let now = Utc::now();
+    SessionRecord {
+        id,
+        created_at: now,
+        last_seen_at: now,
+        expires_at: now + ChronoDuration::hours(1),
+        absolute_expires_at: now + ChronoDuration::hours(4),
+        user_agent: Some(user_agent.into()),
+        ip: Some("203.0.113.7".into()),
+    }
+}
+
+fn session_server(stub: &Arc<StubSessionManager>, user: SessionUser) -> TestServer {
+    let session_manager: Arc<dyn SessionManager> = stub.clone();
+    let state = server::create_app_state(None, None, None, Some(session_manager), None, None);
+    let context = RequestContext {
+        request_id: "test".into(),
+        session: Some(user),
+        ..RequestContext::default()
+    };
+    let app = sessions::routes()
+        .merge(sessions::admin_routes())
+        .layer(Extension(context))
+        .layer(Extension(state.clone()))
+        .with_state(state);
+    TestServer::new(app).expect("test server")
+}
+
+#[tokio::test]
+async fn session_list_marks_the_current_session() {
+    let stub = Arc::new(StubSessionManager::default());
+    let user = sample_session_user();
+    let other = Uuid::new_v4();
+    *stub.sessions.lock().unwrap() = vec![
+        session_record(user.session_id, "rustygpt-cli/0.1.0"),
+        session_record(other, "Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0"),
+    ];
+
+    let response = session_server(&stub, user.clone())
+        .get("/api/auth/sessions")
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let payload: shared::models::ActiveSessionListResponse = response.json();
+    assert_eq!(payload.sessions.len(), 2);
+    assert!(payload.sessions[0].current);
+    assert_eq!(payload.sessions[0].device, "RustyGPT CLI");
+    assert!(!payload.sessions[1].current);
+    assert_eq!(payload.sessions[1].device, "Firefox on Linux");
+}
+
+#[tokio::test]
+async fn revoking_an_unknown_session_is_not_found() {
+    let stub = Arc::new(StubSessionManager::default());
+    let response = session_server(&stub, sample_session_user())
+        .delete(&format!("/api/auth/sessions/{}", Uuid::new_v4()))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn sign_out_everywhere_else_keeps_the_current_session() {
+    let stub = Arc::new(StubSessionManager::default());
+    let user = sample_session_user();
+    *stub.sessions.lock().unwrap() = vec![
+        session_record(user.session_id, "current"),
+        session_record(Uuid::new_v4(), "laptop"),
+        session_record(Uuid::new_v4(), "phone"),
+    ];
+
+    let response = session_server(&stub, user.clone())
+        .delete("/api/auth/sessions")
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let payload: shared::models::RevokeSessionsResponse = response.json();
+    assert_eq!(payload.revoked, 2);
+    assert_eq!(
+        stub.bulk_revocations.lock().unwrap()[0],
+        (user.id, Some(user.session_id), user.id)
+    );
+}
+
+#[tokio::test]
+async fn admin_session_routes_require_admin_role() {
+    let stub = Arc::new(StubSessionManager::default());
+    let server = session_server(&stub, sample_session_user());
+
+    let response = server
+        .get(&format!("/admin/users/{}/sessions", Uuid::new_v4()))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn admin_can_sign_out_another_user_everywhere() {
+    let stub = Arc::new(StubSessionManager::default());
+    let mut admin = sample_session_user();
+    admin.roles = vec![shared::models::UserRole::Admin];
+    let target = Uuid::new_v4();
+    *stub.sessions.lock().unwrap() = vec![session_record(Uuid::new_v4(), "browser")];
+
+    let response = session_server(&stub, admin.clone())
+        .delete(&format!("/admin/users/{target}/sessions"))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(
+        stub.bulk_revocations.lock().unwrap()[0],
+        (target, None, admin.id)
+    );
+}
+
+fn unlock_server(stub: &Arc<StubSessionManager>, user: SessionUser) -> TestServer {
+    let session_manager: Arc<dyn SessionManager> = stub.clone();
+    let state = server::create_app_state(None, None, None, Some(session_manager), None, None);
+    let context = RequestContext {
+        request_id: "test".into(),
+        session: Some(user),
+        ..RequestContext::default()
+    };
+    let app = Router::new()
+        .route(
+            "/admin/users/{user_id}/unlock",
+            post(admin_users::unlock_user),
+        )
+        .layer(Extension(context))
+        .layer(Extension(state));
+    TestServer::new(app).expect("test server")
+}
+
+#[tokio::test]
+async fn unlock_requires_admin_and_records_the_actor() {
+    let stub = Arc::new(StubSessionManager::default());
+    let target = Uuid::new_v4();
+
+    let response = unlock_server(&stub, sample_session_user())
+        .post(&format!("/admin/users/{target}/unlock"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    assert!(stub.unlocks.lock().unwrap().is_empty());
+
+    let mut admin = sample_session_user();
+    admin.roles = vec![shared::models::UserRole::Admin];
+    let response = unlock_server(&stub, admin.clone())
+        .post(&format!("/admin/users/{target}/unlock"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+    assert_eq!(stub.unlocks.lock().unwrap()[0], (target, admin.id));
+}
@@ -2,8 +2,9 @@ use std::sync::Arc;
 
 use axum::{
     Json, Router,
-    extract::{Extension, Path, Query},
-    http::StatusCode,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Extension, Path, Query},
+    http::{StatusCode, header},
     response::IntoResponse,
     routing::{get, post},
 };
@@ -19,17 +20,50 @@ use crate::{
     handlers::streaming::SharedStreamHub,
     http::error::{ApiError, AppResult},
     middleware::request_context::RequestContext,
-    services::chat_service::{AcceptInviteResult, ChatService},
+    services::{
+        audit_service::AuditContext,
+        chat_service::{AcceptInviteResult, ChatService},
+        conversation_export,
+        organization_service::OrganizationService,
+    },
 };
+use shared::config::server::Config;
 use shared::models::{
-    AcceptInviteRequest, AddParticipantRequest, ConversationCreateRequest, ConversationStreamEvent,
-    CreateInviteRequest, CreateInviteResponse, MembershipChangeAction, MembershipChangedEvent,
-    PresenceStatus, PresenceUpdate, ThreadListResponse, Timestamp, UnreadSummaryResponse,
+    AcceptInviteRequest, AddParticipantRequest, ConversationArchiveResponse,
+    ConversationCreateRequest, ConversationLifecycleAction, ConversationLifecycleEvent,
+    ConversationStreamEvent, CreateInviteRequest, CreateInviteResponse, ExportFormat, ImportReport,
+    ImportSource, MembershipChangeAction, MembershipChangedEvent, PresenceStatus, PresenceUpdate,
+    RetentionPolicy, ThreadListResponse, Timestamp, UnreadSummaryResponse,
+    UpdateRetentionPolicyRequest, parse_import,
 };
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/api/conversations", post(create_conversation))
+        .route(
+            "/api/conversations/import",
+            post(import_conversations).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT_BYTES)),
+        )
+        .route(
+            "/api/conversations/{conversation_id}",
+            axum::routing::delete(delete_conversation),
+        )
+        .route(
+            "/api/conversations/{conversation_id}/archive",
+            post(archive_conversation),
+        )
+        .route(
+            "/api/conversations/{conversation_id}/unarchive",
+            post(unarchive_conversation),
+        )
+        .route(
+            "/api/conversations/{conversation_id}/retention",
+            get(get_retention_policy).put(update_retention_policy),
+        )
+        .route(
+            "/api/conversations/{conversation_id}/export",
+            get(export_conversation),
+        )
         .route(
             "/api/conversations/{conversation_id}/participants",
             post(add_participant),
@@ -54,12 +88,28 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/api/invites/{token}/revoke", post(revoke_invite))
 }
 
+/// History exports are much larger than ordinary API payloads.
+const IMPORT_BODY_LIMIT_BYTES: usize = 64 * 1024 * 1024;
+
 #[derive(Deserialize, Default)]
 struct ThreadListQuery {
     after: Option<DateTime<Utc>>,
     limit: Option<i32>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+struct ImportQuery {
+    format: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Deserialize, Default)]
+struct ExportQuery {
+    format: Option<String>,
+    root_id: Option<Uuid>,
+}
+
 #[instrument(skip(app_state, context, payload))]
 async fn create_conversation(
     Extension(app_state): Extension<Arc<AppState>>,
@@ -74,6 +124,220 @@ async fn create_conversation(
     Ok((StatusCode::CREATED, Json(created)))
 }
 
+#[instrument(skip(app_state, context, hub))]
+async fn archive_conversation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Path(conversation_id): Path<Uuid>,
+) -> AppResult<Json<ConversationArchiveResponse>> {
+    set_archived(&app_state, &context, &hub, conversation_id, true).await
+}
+
+#[instrument(skip(app_state, context, hub))]
+async fn unarchive_conversation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Path(conversation_id): Path<Uuid>,
+) -> AppResult<Json<ConversationArchiveResponse>> {
+    set_archived(&app_state, &context, &hub, conversation_id, false).await
+}
+
+async fn set_archived(
+    app_state: &AppState,
+    context: &RequestContext,
+    hub: &SharedStreamHub,
+    conversation_id: Uuid,
+    archived: bool,
+) -> AppResult<Json<ConversationArchiveResponse>> {
+    let actor = require_user(context)?;
+    let pool = require_pool(app_state)?;
+    let service = ChatService::new(pool);
+
+    let (response, changed) = service
+        .set_conversation_archived(actor, conversation_id, archived)
+        .await?;
+
+    if changed {
+        let action = if archived {
+            ConversationLifecycleAction::Archived
+        } else {
+            ConversationLifecycleAction::Unarchived
+        };
+        publish_lifecycle(hub, conversation_id, actor, action, 0).await;
+    }
+
+    Ok(Json(response))
+}
+
+#[instrument(skip(app_state, context, hub))]
+async fn delete_conversation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Path(conversation_id): Path<Uuid>,
+) -> AppResult<impl IntoResponse> {
+    let actor = require_user(&context)?;
+    let pool = require_pool(&app_state)?;
+    let service = ChatService::new(pool);
+
+    let removed = service.delete_conversation(actor, conversation_id).await?;
+    publish_lifecycle(
+        &hub,
+        conversation_id,
+        actor,
+        ConversationLifecycleAction::Deleted,
+        removed,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[instrument(skip(app_state, context))]
+async fn get_retention_policy(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(conversation_id): Path<Uuid>,
+) -> AppResult<Json<RetentionPolicy>> {
+    let actor = require_user(&context)?;
+    let pool = require_pool(&app_state)?;
+    let service = ChatService::new(pool);
+
+    let policy = service
+        .get_retention_policy(actor, Some(conversation_id))
+        .await?;
+    Ok(Json(policy))
+}
+
+#[instrument(skip(app_state, context, payload))]
+async fn update_retention_policy(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(conversation_id): Path<Uuid>,
+    Json(payload): Json<UpdateRetentionPolicyRequest>,
+) -> AppResult<Json<RetentionPolicy>> {
+    let actor = require_user(&context)?;
+    let pool = require_pool(&app_state)?;
+    let service = ChatService::new(pool);
+
+    let policy = service
+        .set_retention_policy(actor, Some(conversation_id), payload)
+        .await?;
+    Ok(Json(policy))
+}
+
+#[instrument(skip(app_state, context, query))]
+async fn export_conversation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(conversation_id): Path<Uuid>,
+    Query(query): Query<ExportQuery>,
+) -> AppResult<impl IntoResponse> {
+    let actor = require_user(&context)?;
+    let format = query
+        .format
+        .as_deref()
+        .map_or(Ok(ExportFormat::default()), str::parse::<ExportFormat>)
+        .map_err(|message| {
+            ApiError::new(StatusCode::BAD_REQUEST, "RGP.V1.EXPORT_FORMAT", message)
+        })?;
+    let pool = require_pool(&app_state)?;
+    let service = ChatService::new(pool.clone());
+
+    let export = service
+        .export_conversation(actor, conversation_id, query.root_id)
+        .await?;
+    let policy = OrganizationService::new(pool)
+        .conversation_policy(conversation_id)
+        .await?;
+    if policy.is_some_and(|policy| !policy.features.conversation_export) {
+        return Err(ApiError::forbidden(
+            "conversation export is disabled for this organization",
+        ));
+    }
+    let body = conversation_export::render(&export, format).map_err(|err| {
+        warn!(error = %err, "failed to serialize conversation export");
+        ApiError::internal_server_error("failed to render export")
+    })?;
+
+    let filename = query.root_id.map_or_else(
+        || format!("conversation-{conversation_id}.{format}"),
+        |root_id| format!("thread-{root_id}.{format}"),
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        body,
+    ))
+}
+
+#[instrument(skip(app_state, context, body))]
+async fn import_conversations(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> AppResult<(StatusCode, Json<ImportReport>)> {
+    let actor = require_user(&context)?;
+    let raw = std::str::from_utf8(&body).map_err(|_| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "RGP.V1.IMPORT_INVALID",
+            "import file must be UTF-8 encoded",
+        )
+    })?;
+    let source = query
+        .format
+        .as_deref()
+        .map_or_else(|| Ok(ImportSource::detect(raw)), str::parse::<ImportSource>)
+        .map_err(|message| {
+            ApiError::new(StatusCode::BAD_REQUEST, "RGP.V1.IMPORT_FORMAT", message)
+        })?;
+    let parsed = parse_import(source, raw).map_err(|err| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "RGP.V1.IMPORT_INVALID",
+            err.to_string(),
+        )
+    })?;
+
+    if query.dry_run {
+        return Ok((StatusCode::OK, Json(parsed.report(true, &[]))));
+    }
+
+    let pool = require_pool(&app_state)?;
+    let ids = ChatService::new(pool)
+        .import_conversations(actor, &parsed.conversations)
+        .await?;
+    Ok((StatusCode::CREATED, Json(parsed.report(false, &ids))))
+}
+
+async fn publish_lifecycle(
+    hub: &SharedStreamHub,
+    conversation_id: Uuid,
+    actor: Uuid,
+    action: ConversationLifecycleAction,
+    affected_messages: i32,
+) {
+    let event = ConversationStreamEvent::ConversationLifecycle {
+        payload: ConversationLifecycleEvent {
+            conversation_id,
+            action,
+            actor_user_id: Some(actor),
+            affected_messages,
+            occurred_at: Timestamp(Utc::now()),
+        },
+    };
+    hub.publish(conversation_id, event).await;
+}
+
 #[instrument(skip(app_state, context, payload))]
 async fn add_participant(
     Extension(app_state): Extension<Arc<AppState>>,
@@ -84,7 +348,7 @@ async fn add_participant(
 ) -> AppResult<impl IntoResponse> {
     let user_id = require_user(&context)?;
     let pool = require_pool(&app_state)?;
-    let service = ChatService::new(pool);
+    let service = ChatService::new(pool).with_audit(AuditContext::from(&context));
 
     let assigned_role = service
         .add_participant(user_id, conversation_id, payload.clone())
@@ -123,7 +387,7 @@ async fn remove_participant(
 ) -> AppResult<impl IntoResponse> {
     let actor = require_user(&context)?;
     let pool = require_pool(&app_state)?;
-    let service = ChatService::new(pool);
+    let service = ChatService::new(pool).with_audit(AuditContext::from(&context));
 
     let prior_role = service
         .remove_participant(actor, conversation_id, user_id)
@@ -153,9 +417,10 @@ async fn remove_participant(
     Ok(StatusCode::NO_CONTENT)
 }
 
-#[instrument(skip(app_state, context, payload))]
+#[instrument(skip(app_state, config, context, payload))]
 async fn create_invite(
     Extension(app_state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
     Extension(context): Extension<RequestContext>,
     Path(conversation_id): Path<Uuid>,
     Json(payload): Json<CreateInviteRequest>,
@@ -172,6 +437,7 @@ async fn create_invite(
             &payload.email,
             payload.role,
             Some(ttl),
+            Some(config.server.public_base_url.as_str()),
         )
         .await?;
 
@@ -187,7 +453,7 @@ async fn accept_invite(
 ) -> AppResult<impl IntoResponse> {
     let actor = require_user(&context)?;
     let pool = require_pool(&app_state)?;
-    let service = ChatService::new(pool);
+    let service = ChatService::new(pool).with_audit(AuditContext::from(&context));
 
     let AcceptInviteResult {
         conversation_id,
@@ -228,7 +494,7 @@ async fn revoke_invite(
 ) -> AppResult<impl IntoResponse> {
     let actor = require_user(&context)?;
     let pool = require_pool(&app_state)?;
-    let service = ChatService::new(pool);
+    let service = ChatService::new(pool).with_audit(AuditContext::from(&context));
 
     service.revoke_invite(actor, &token).await?;
     Ok(StatusCode::NO_CONTENT)
@@ -292,7 +558,11 @@ fn require_sessions(state: &AppState) -> AppResult<Arc<dyn SessionManager>> {
     })
 }
 
-async fn mark_membership_rotation(state: &AppState, user_id: Uuid, reason: &str) -> AppResult<()> {
+pub(crate) async fn mark_membership_rotation(
+    state: &AppState,
+    user_id: Uuid,
+    reason: &str,
+) -> AppResult<()> {
     let sessions = require_sessions(state)?;
     sessions
         .mark_user_for_rotation(user_id, reason)
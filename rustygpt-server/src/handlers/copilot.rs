@@ -23,21 +23,28 @@ use uuid::Uuid;
 
 use crate::{
     app_state::AppState,
-    auth::session::{SessionUser, SessionValidation},
+    auth::{
+        api_tokens::{ApiTokenService, bearer_token},
+        session::{SessionUser, SessionValidation},
+    },
     handlers::{
         auth::{extract_session_cookie, map_session_error, metadata_from_headers},
         streaming::SharedStreamHub,
         threads::{
-            ensure_reply_response, infer_usage_from_text, persist_chunk_if_needed,
-            publish_delta_event, token_usage_to_breakdown,
+            ensure_reply_response, generation_status, persist_chunk_if_needed, publish_delta_event,
+            record_generation_end,
         },
     },
     http::error::{ApiError, AppResult},
-    middleware::request_context::RequestContext,
+    middleware::{rate_limit::AppliedRateLimitProfile, request_context::RequestContext},
     services::{
-        assistant_service::{AssistantError, AssistantStreamingSession, finish_reason_to_string},
+        assistant_service::{
+            AssistantError, AssistantStreamingSession, UsageMeter, finish_reason_to_string,
+        },
         chat_service::{ChatService, ChatServiceError, ThreadSummaryWithConversation},
+        quota_service::QuotaService,
         stream_supervisor::{SharedStreamSupervisor, StreamSession, StreamStopReason},
+        usage_service::{UsageEntry, UsageService},
     },
 };
 use chrono::Utc;
@@ -46,11 +53,11 @@ use shared::{
     llms::ThreadContextBuilder,
     llms::types::{LLMRequest, StreamingResponse, TokenUsage},
     models::{
-        ChatCompletionChoice, ChatCompletionChunk, ChatCompletionChunkChoice,
+        ApiTokenScope, ChatCompletionChoice, ChatCompletionChunk, ChatCompletionChunkChoice,
         ChatCompletionChunkDelta, ChatCompletionMessage, ChatCompletionRequest,
         ChatCompletionResponse, ConversationStreamEvent, MessageDoneEvent, MessageRole,
         MessageView, Model, ModelsResponse, ReplyMessageRequest, ReplyMessageResponse,
-        StreamErrorEvent, ThreadActivityEvent, UsageBreakdown,
+        StreamErrorEvent, ThreadActivityEvent, UsageBreakdown, UsageSource,
     },
 };
 
@@ -82,6 +89,39 @@ struct RustyMetadata {
     parent_message_id: Uuid,
 }
 
+/// Where a completion's token usage is recorded once it finishes.
+#[derive(Clone)]
+struct UsageLedger {
+    service: UsageService,
+    user_id: Option<Uuid>,
+}
+
+impl UsageLedger {
+    async fn record(
+        &self,
+        meter: &UsageMeter,
+        usage: &UsageBreakdown,
+        message: Option<(Uuid, Uuid)>,
+    ) {
+        let mut entry = UsageEntry::from_meter(UsageSource::V1, meter, usage, self.user_id);
+        if let Some((conversation_id, message_id)) = message {
+            entry = entry.for_message(conversation_id, message_id);
+        }
+        self.service.record_or_warn(&entry).await;
+    }
+}
+
+async fn record_usage(
+    ledger: Option<&UsageLedger>,
+    meter: &UsageMeter,
+    usage: &UsageBreakdown,
+    message: Option<(Uuid, Uuid)>,
+) {
+    if let Some(ledger) = ledger {
+        ledger.record(meter, usage, message).await;
+    }
+}
+
 struct StatefulContext {
     actor: SessionUser,
     service: ChatService,
@@ -205,7 +245,7 @@ impl StatefulStreamController {
     #[allow(clippy::cognitive_complexity, clippy::too_many_lines)] // Tracking: copilot-stream-refactor
     async fn finalize(
         mut self,
-        prompt_tokens: i64,
+        meter: &UsageMeter,
         finish_reason: Option<String>,
         usage: Option<TokenUsage>,
         mut stream_error: Option<String>,
@@ -219,6 +259,9 @@ impl StatefulStreamController {
                 Some(StreamStopReason::TimedOut) => {
                     "Assistant response timed out before completion.".to_string()
                 }
+                Some(StreamStopReason::Interrupted) => {
+                    "Assistant response interrupted by a server shutdown.".to_string()
+                }
                 _ => "I'm sorry, I couldn't generate a response right now.".to_string(),
             };
             let created = self
@@ -241,6 +284,12 @@ impl StatefulStreamController {
             stream_error = None;
         }
 
+        if stop_reason == Some(StreamStopReason::Interrupted) {
+            warning_message =
+                Some("assistant generation was interrupted by a server shutdown.".to_string());
+            stream_error = None;
+        }
+
         if warning_message.is_none()
             && let Some(error) = stream_error.clone()
         {
@@ -266,16 +315,14 @@ impl StatefulStreamController {
             warn!(error = %err, "failed to persist assistant final content");
         }
 
-        let usage_breakdown = usage.as_ref().map_or_else(
-            || infer_usage_from_text(prompt_tokens, &self.accumulated),
-            |usage| token_usage_to_breakdown(usage, prompt_tokens, &self.accumulated),
-        );
+        let usage_breakdown = meter.breakdown(usage.as_ref(), &self.accumulated).await;
 
         let default_finish = finish_reason.unwrap_or_else(|| "stop".to_string());
 
         let finish_reason_value = match stop_reason {
             Some(StreamStopReason::Cancelled) => "cancelled".to_string(),
             Some(StreamStopReason::TimedOut) => "timeout".to_string(),
+            Some(StreamStopReason::Interrupted) => "interrupted".to_string(),
             _ => {
                 if warning_message.is_some() {
                     "error".to_string()
@@ -284,6 +331,14 @@ impl StatefulStreamController {
                 }
             }
         };
+        record_generation_end(
+            &self.service,
+            self.actor_id,
+            reply_response.message_id,
+            generation_status(stop_reason, warning_message.is_some()),
+            &finish_reason_value,
+        )
+        .await;
 
         let conversation = self
             .resolved_conversation
@@ -306,10 +361,10 @@ impl StatefulStreamController {
             .await;
 
         if let Some(message) = warning_message.as_ref() {
-            let code = if matches!(stop_reason, Some(StreamStopReason::TimedOut)) {
-                "assistant_timeout"
-            } else {
-                "assistant_stream_error"
+            let code = match stop_reason {
+                Some(StreamStopReason::TimedOut) => "assistant_timeout",
+                Some(StreamStopReason::Interrupted) => "server_shutdown",
+                _ => "assistant_stream_error",
             };
             self.hub
                 .publish(
@@ -351,6 +406,8 @@ impl StatefulStreamController {
         }
 
         Ok(StatefulFinalization {
+            conversation_id: reply_response.conversation_id,
+            message_id: reply_response.message_id,
             accumulated: self.accumulated,
             usage: usage_breakdown,
             finish_reason: finish_reason_value,
@@ -360,6 +417,8 @@ impl StatefulStreamController {
 }
 
 struct StatefulFinalization {
+    conversation_id: Uuid,
+    message_id: Uuid,
     accumulated: String,
     usage: UsageBreakdown,
     finish_reason: String,
@@ -485,6 +544,10 @@ async fn authenticate_request(
     config: &Config,
     headers: &HeaderMap,
 ) -> AppResult<Option<SessionValidation>> {
+    if let Some(token) = bearer_token(headers) {
+        return authenticate_bearer(state, token).await.map(Some);
+    }
+
     let Some(manager) = state.sessions.clone() else {
         return Ok(None);
     };
@@ -505,6 +568,42 @@ async fn authenticate_request(
     }
 }
 
+async fn authenticate_bearer(state: &AppState, token: &str) -> AppResult<SessionValidation> {
+    let pool = state.pool.clone().ok_or_else(|| {
+        ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "RGP.DB.UNAVAILABLE",
+            "database pool not configured",
+        )
+    })?;
+
+    let principal = ApiTokenService::new(pool)
+        .authenticate(token, None)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "RGP.AUTH.INVALID_TOKEN",
+                "invalid, expired or revoked API token",
+            )
+        })?;
+
+    if !principal.allows(ApiTokenScope::V1Chat) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "RGP.AUTH.INSUFFICIENT_SCOPE",
+            "API token lacks the v1:chat scope",
+        ));
+    }
+
+    Ok(SessionValidation {
+        user: principal.session_user(),
+        bundle: None,
+        rotated: false,
+    })
+}
+
 async fn prepare_stateful_context(
     state: &Arc<AppState>,
     hub: &SharedStreamHub,
@@ -824,6 +923,7 @@ fn header_value(value: &str) -> Result<header::HeaderValue, ApiError> {
         .map_err(|_| ApiError::internal_server_error("failed to encode cookie header".to_string()))
 }
 
+#[allow(clippy::too_many_arguments)] // Tracking: copilot-stream-refactor
 async fn complete_non_streaming(
     session: AssistantStreamingSession,
     completion_id: String,
@@ -832,6 +932,7 @@ async fn complete_non_streaming(
     warnings: Vec<String>,
     stateful: Option<StatefulContext>,
     persist_chunks: bool,
+    ledger: Option<UsageLedger>,
 ) -> AppResult<Response> {
     if let Some(context) = stateful {
         complete_stateful_non_streaming(
@@ -842,14 +943,23 @@ async fn complete_non_streaming(
             warnings,
             context,
             persist_chunks,
+            ledger,
         )
         .await
     } else {
-        complete_stateless_non_streaming(session, completion_id, created, model_name, warnings)
-            .await
+        complete_stateless_non_streaming(
+            session,
+            completion_id,
+            created,
+            model_name,
+            warnings,
+            ledger,
+        )
+        .await
     }
 }
 
+#[allow(clippy::too_many_arguments)] // Tracking: copilot-stream-refactor
 fn stream_completion(
     session: AssistantStreamingSession,
     completion_id: String,
@@ -858,6 +968,7 @@ fn stream_completion(
     warnings: Vec<String>,
     stateful: Option<StatefulContext>,
     persist_chunks: bool,
+    ledger: Option<UsageLedger>,
 ) -> Response {
     let (tx, rx) = mpsc::channel::<Event>(32);
 
@@ -870,6 +981,7 @@ fn stream_completion(
             warnings,
             stateful,
             persist_chunks,
+            ledger,
             tx,
         )
         .await
@@ -898,6 +1010,7 @@ async fn run_streaming_session(
     mut warnings: Vec<String>,
     stateful: Option<StatefulContext>,
     persist_chunks: bool,
+    ledger: Option<UsageLedger>,
     tx: mpsc::Sender<Event>,
 ) -> Result<(), ApiError> {
     let mut stream = session.stream;
@@ -1018,11 +1131,12 @@ async fn run_streaming_session(
         session_handle.mark_completed();
     }
 
-    let (final_finish, final_usage) = if let Some((controller, _)) = stateful_state.take() {
+    let (final_finish, final_usage, message) = if let Some((controller, _)) = stateful_state.take()
+    {
         let stateful_error = stream_error.take();
         match controller
             .finalize(
-                session.prompt_tokens,
+                &session.usage,
                 finish_reason,
                 usage,
                 stateful_error,
@@ -1034,13 +1148,18 @@ async fn run_streaming_session(
                 if let Some(warning) = result.warning.as_ref() {
                     warnings.push(warning.clone());
                 }
-                (result.finish_reason, result.usage)
+                (
+                    result.finish_reason,
+                    result.usage,
+                    Some((result.conversation_id, result.message_id)),
+                )
             }
             Err(err) => {
                 warnings.push(format!("assistant finalization error: {err}"));
                 (
                     "error".to_string(),
-                    infer_usage_from_text(session.prompt_tokens, ""),
+                    session.usage.breakdown(None, "").await,
+                    None,
                 )
             }
         }
@@ -1049,18 +1168,20 @@ async fn run_streaming_session(
         if let Some(error) = error_opt.clone() {
             warnings.push(format!("assistant stream error: {error}"));
         }
-        let usage_breakdown = usage.as_ref().map_or_else(
-            || infer_usage_from_text(session.prompt_tokens, &stateless_accumulated),
-            |usage| token_usage_to_breakdown(usage, session.prompt_tokens, &stateless_accumulated),
-        );
+        let usage_breakdown = session
+            .usage
+            .breakdown(usage.as_ref(), &stateless_accumulated)
+            .await;
         let finish = if error_opt.is_some() {
             "error".to_string()
         } else {
             finish_reason.unwrap_or_else(|| "stop".to_string())
         };
-        (finish, usage_breakdown)
+        (finish, usage_breakdown, None)
     };
 
+    record_usage(ledger.as_ref(), &session.usage, &final_usage, message).await;
+
     let final_chunk = ChatCompletionChunk {
         id: completion_id,
         object: OBJECT_CHUNK.to_string(),
@@ -1106,6 +1227,7 @@ async fn complete_stateless_non_streaming(
     created: i64,
     model_name: String,
     mut warnings: Vec<String>,
+    ledger: Option<UsageLedger>,
 ) -> AppResult<Response> {
     let mut stream = session.stream;
     let mut accumulated = String::new();
@@ -1136,10 +1258,8 @@ async fn complete_stateless_non_streaming(
         }
     }
 
-    let usage_breakdown = usage.as_ref().map_or_else(
-        || infer_usage_from_text(session.prompt_tokens, &accumulated),
-        |usage| token_usage_to_breakdown(usage, session.prompt_tokens, &accumulated),
-    );
+    let usage_breakdown = session.usage.breakdown(usage.as_ref(), &accumulated).await;
+    record_usage(ledger.as_ref(), &session.usage, &usage_breakdown, None).await;
 
     let finish_reason_value = finish_reason.unwrap_or_else(|| "stop".to_string());
 
@@ -1166,6 +1286,7 @@ async fn complete_stateless_non_streaming(
     Ok(Json(response).into_response())
 }
 
+#[allow(clippy::too_many_arguments)] // Tracking: copilot-stream-refactor
 async fn complete_stateful_non_streaming(
     session: AssistantStreamingSession,
     completion_id: String,
@@ -1174,6 +1295,7 @@ async fn complete_stateful_non_streaming(
     mut warnings: Vec<String>,
     context: StatefulContext,
     persist_chunks: bool,
+    ledger: Option<UsageLedger>,
 ) -> AppResult<Response> {
     let mut stream = session.stream;
     let stream_session = context.streams.as_ref().map(|sup| sup.create_session());
@@ -1223,7 +1345,7 @@ async fn complete_stateful_non_streaming(
 
     let finalization = controller
         .finalize(
-            session.prompt_tokens,
+            &session.usage,
             finish_reason,
             usage,
             stream_error,
@@ -1232,6 +1354,14 @@ async fn complete_stateful_non_streaming(
         .await
         .map_err(ApiError::from)?;
 
+    record_usage(
+        ledger.as_ref(),
+        &session.usage,
+        &finalization.usage,
+        Some((finalization.conversation_id, finalization.message_id)),
+    )
+    .await;
+
     if let Some(warning) = finalization.warning.as_ref() {
         warnings.push(warning.clone());
     }
@@ -1265,12 +1395,16 @@ pub async fn post_chat_completions(
     Extension(config): Extension<Arc<Config>>,
     Extension(_context): Extension<RequestContext>,
     Extension(hub): Extension<SharedStreamHub>,
+    rate_profile: Option<Extension<AppliedRateLimitProfile>>,
     headers: HeaderMap,
     Json(payload): Json<ChatCompletionRequest>,
 ) -> AppResult<Response> {
     let assistant = state.assistant.clone().ok_or_else(|| {
         ApiError::internal_server_error("assistant streaming service not configured")
     })?;
+    if let Some(streams) = state.streams.as_ref() {
+        streams.admit()?;
+    }
 
     let stream = payload.stream.unwrap_or(false);
     let stop_sequences = parse_stop_sequences(payload.stop.as_ref())?;
@@ -1291,6 +1425,17 @@ pub async fn post_chat_completions(
         ));
     }
 
+    // Anonymous calls have no budget to charge; they are bounded by rate limits alone.
+    if let (Some(validation), Some(pool)) = (auth_session.as_ref(), state.pool.clone()) {
+        let profile = rate_profile.as_deref().map(|profile| profile.0.as_str());
+        if let Some(exceeded) = QuotaService::new(pool)
+            .check(validation.user.id, profile)
+            .await?
+        {
+            return Err(exceeded.into());
+        }
+    }
+
     let default_config = assistant
         .default_chat_config()
         .map_err(|err| ApiError::internal_server_error(err.to_string()))?;
@@ -1322,6 +1467,10 @@ pub async fn post_chat_completions(
         .map_err(map_assistant_error)?;
 
     let persist_chunks = assistant.persist_stream_chunks();
+    let ledger = state.pool.clone().map(|pool| UsageLedger {
+        service: UsageService::new(pool),
+        user_id: auth_session.as_ref().map(|validation| validation.user.id),
+    });
 
     let mut response = if stream {
         stream_completion(
@@ -1332,6 +1481,7 @@ pub async fn post_chat_completions(
             warnings,
             stateful_context,
             persist_chunks,
+            ledger,
         )
     } else {
         complete_non_streaming(
@@ -1342,6 +1492,7 @@ pub async fn post_chat_completions(
             warnings,
             stateful_context,
             persist_chunks,
+            ledger,
         )
         .await?
     };
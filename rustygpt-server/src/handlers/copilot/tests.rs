@@ -103,6 +103,7 @@ fn test_app(assistant: Arc<dyn AssistantRuntime>) -> TestServer {
     let context = RequestContext {
         request_id: "req".into(),
         session: None,
+        ..RequestContext::default()
     };
 
     let app_state = Arc::new(AppState {
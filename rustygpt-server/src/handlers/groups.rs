@@ -0,0 +1,299 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use axum::{
+    Json, Router,
+    extract::{Extension, Path},
+    http::StatusCode,
+    routing::{delete, get, put},
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    handlers::{
+        admin_limits::require_pool, conversations::mark_membership_rotation,
+        streaming::SharedStreamHub,
+    },
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::{
+        audit_service::AuditContext,
+        group_service::{AccessChange, GroupService},
+    },
+};
+use shared::models::{
+    ConversationGroupGrant, ConversationStreamEvent, CreateUserGroupRequest, GrantGroupRequest,
+    SetGroupMemberRequest, UpdateUserGroupRequest, UserGroup, UserGroupMember, UserRole,
+};
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/groups", get(list_groups).post(create_group))
+        .route(
+            "/api/groups/{group_id}",
+            get(get_group).put(update_group).delete(delete_group),
+        )
+        .route("/api/groups/{group_id}/members", get(list_members))
+        .route(
+            "/api/groups/{group_id}/members/{user_id}",
+            put(put_member).delete(remove_member),
+        )
+        .route(
+            "/api/conversations/{conversation_id}/groups",
+            get(list_grants).post(grant_group),
+        )
+        .route(
+            "/api/conversations/{conversation_id}/groups/{group_id}",
+            delete(revoke_group),
+        )
+}
+
+fn groups(state: &Arc<AppState>) -> AppResult<GroupService> {
+    Ok(GroupService::new(require_pool(state)?))
+}
+
+fn require_user(context: &RequestContext) -> AppResult<Uuid> {
+    context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))
+}
+
+fn is_global_admin(context: &RequestContext) -> bool {
+    context
+        .session
+        .as_ref()
+        .is_some_and(|session| session.roles.contains(&UserRole::Admin))
+}
+
+/// Loads the group as the caller sees it; administrators see every group and everyone else
+/// gets a 404 for groups they are not in.
+async fn visible_group(
+    service: &GroupService,
+    context: &RequestContext,
+    group_id: Uuid,
+) -> AppResult<UserGroup> {
+    let user_id = require_user(context)?;
+    let mut group = service.get(group_id, Some(user_id)).await?;
+    if group.is_none() && is_global_admin(context) {
+        group = service.get(group_id, None).await?;
+    }
+    group.ok_or_else(|| ApiError::not_found("group not found"))
+}
+
+/// Group managers and global administrators edit a group; members may only leave it.
+async fn authorize_group_change(
+    service: &GroupService,
+    context: &RequestContext,
+    group_id: Uuid,
+    leaving: Option<Uuid>,
+) -> AppResult<()> {
+    let group = visible_group(service, context, group_id).await?;
+    let actor = require_user(context)?;
+    if group.is_manager == Some(true) || is_global_admin(context) || leaving == Some(actor) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden(
+            "only group managers can change this group",
+        ))
+    }
+}
+
+/// Publishes `membership.changed` for every user whose effective role moved and flags their
+/// sessions for rotation, as direct participant changes do.
+async fn announce(
+    state: &AppState,
+    hub: &SharedStreamHub,
+    changes: &[AccessChange],
+) -> AppResult<()> {
+    let mut rotated = BTreeSet::new();
+    for change in changes {
+        let Some(payload) = change.membership_event() else {
+            continue;
+        };
+        hub.publish(
+            change.conversation_id,
+            ConversationStreamEvent::MembershipChanged { payload },
+        )
+        .await;
+        rotated.insert(change.user_id);
+    }
+    for user_id in rotated {
+        mark_membership_rotation(state, user_id, "membership_change").await?;
+    }
+    Ok(())
+}
+
+#[instrument(skip(state, context))]
+async fn list_groups(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<Vec<UserGroup>>> {
+    let user_id = require_user(&context)?;
+    Ok(Json(groups(&state)?.list(Some(user_id)).await?))
+}
+
+/// Creates a group with the caller as its first manager.
+#[instrument(skip(state, context, payload))]
+async fn create_group(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Json(payload): Json<CreateUserGroupRequest>,
+) -> AppResult<(StatusCode, Json<UserGroup>)> {
+    let user_id = require_user(&context)?;
+    let group = groups(&state)?
+        .create(&AuditContext::from(&context), user_id, &payload)
+        .await?;
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+#[instrument(skip(state, context))]
+async fn get_group(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(group_id): Path<Uuid>,
+) -> AppResult<Json<UserGroup>> {
+    let service = groups(&state)?;
+    Ok(Json(visible_group(&service, &context, group_id).await?))
+}
+
+#[instrument(skip(state, context, payload))]
+async fn update_group(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(group_id): Path<Uuid>,
+    Json(payload): Json<UpdateUserGroupRequest>,
+) -> AppResult<Json<UserGroup>> {
+    let service = groups(&state)?;
+    authorize_group_change(&service, &context, group_id, None).await?;
+    service
+        .update(&AuditContext::from(&context), group_id, &payload)
+        .await?;
+    Ok(Json(visible_group(&service, &context, group_id).await?))
+}
+
+/// Deletes the group; its members lose the access its grants gave them.
+#[instrument(skip(state, context, hub))]
+async fn delete_group(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Path(group_id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    let service = groups(&state)?;
+    authorize_group_change(&service, &context, group_id, None).await?;
+    let changes = service
+        .delete(&AuditContext::from(&context), group_id)
+        .await?;
+    announce(&state, &hub, &changes).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[instrument(skip(state, context))]
+async fn list_members(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(group_id): Path<Uuid>,
+) -> AppResult<Json<Vec<UserGroupMember>>> {
+    let service = groups(&state)?;
+    let group = visible_group(&service, &context, group_id).await?;
+    Ok(Json(service.members(group.id).await?))
+}
+
+/// Adds a member or changes whether they manage the group. A new member joins every
+/// conversation the group is granted on.
+#[instrument(skip(state, context, hub))]
+async fn put_member(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Path((group_id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<SetGroupMemberRequest>,
+) -> AppResult<Json<UserGroupMember>> {
+    let service = groups(&state)?;
+    authorize_group_change(&service, &context, group_id, None).await?;
+    let changes = service
+        .put_member(
+            &AuditContext::from(&context),
+            group_id,
+            user_id,
+            payload.is_manager,
+        )
+        .await?;
+    announce(&state, &hub, &changes).await?;
+    service
+        .members(group_id)
+        .await?
+        .into_iter()
+        .find(|member| member.user_id == user_id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("member not found"))
+}
+
+/// Removes a member, who loses the access the group's grants gave them. Members may remove
+/// themselves.
+#[instrument(skip(state, context, hub))]
+async fn remove_member(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Path((group_id, user_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    let service = groups(&state)?;
+    authorize_group_change(&service, &context, group_id, Some(user_id)).await?;
+    let changes = service
+        .remove_member(&AuditContext::from(&context), group_id, user_id)
+        .await?;
+    announce(&state, &hub, &changes).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[instrument(skip(state, context))]
+async fn list_grants(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(conversation_id): Path<Uuid>,
+) -> AppResult<Json<Vec<ConversationGroupGrant>>> {
+    require_user(&context)?;
+    let grants = groups(&state)?
+        .conversation_grants(&AuditContext::from(&context), conversation_id)
+        .await?;
+    Ok(Json(grants))
+}
+
+/// Grants a group a role on the conversation. Conversation owners and admins may grant groups
+/// they belong to; granting a group again changes its role.
+#[instrument(skip(state, context, hub))]
+async fn grant_group(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Path(conversation_id): Path<Uuid>,
+    Json(payload): Json<GrantGroupRequest>,
+) -> AppResult<Json<Vec<ConversationGroupGrant>>> {
+    require_user(&context)?;
+    let service = groups(&state)?;
+    let audit = AuditContext::from(&context);
+    let changes = service
+        .grant(&audit, conversation_id, payload.group_id, payload.role)
+        .await?;
+    announce(&state, &hub, &changes).await?;
+    Ok(Json(
+        service.conversation_grants(&audit, conversation_id).await?,
+    ))
+}
+
+#[instrument(skip(state, context, hub))]
+async fn revoke_group(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Path((conversation_id, group_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    require_user(&context)?;
+    let changes = groups(&state)?
+        .revoke(&AuditContext::from(&context), conversation_id, group_id)
+        .await?;
+    announce(&state, &hub, &changes).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
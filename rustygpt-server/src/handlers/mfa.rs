@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::Extension,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    auth::{api_tokens::ApiTokenPrincipal, mfa::MfaService},
+    handlers::admin_limits::{require_admin_context, require_pool},
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::audit_service::AuditContext,
+};
+use shared::models::{MfaCodeRequest, MfaPolicy, MfaStatus, RecoveryCodesResponse, TotpEnrollment};
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/me/mfa", get(get_status))
+        .route("/api/me/mfa/totp", post(enroll_totp).delete(disable_totp))
+        .route("/api/me/mfa/totp/confirm", post(confirm_totp))
+        .route(
+            "/api/me/mfa/recovery-codes",
+            post(regenerate_recovery_codes),
+        )
+}
+
+#[instrument(skip(state, context))]
+async fn get_status(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<MfaStatus>> {
+    let user_id = context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))?;
+    let pool = require_pool(&state)?;
+
+    let status = MfaService::new(pool).status(user_id).await?;
+    Ok(Json(status))
+}
+
+/// Starts enrollment; the returned secret only takes effect once confirmed with a code.
+#[instrument(skip(state, context, principal))]
+async fn enroll_totp(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+) -> AppResult<Json<TotpEnrollment>> {
+    let user_id = require_session_user(&context, principal.as_ref())?;
+    let account = context
+        .session
+        .as_ref()
+        .map(|session| session.email.clone())
+        .unwrap_or_default();
+    let pool = require_pool(&state)?;
+
+    let enrollment = MfaService::new(pool).enroll(user_id, &account).await?;
+    Ok(Json(enrollment))
+}
+
+#[instrument(skip(state, context, principal, payload))]
+async fn confirm_totp(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+    Json(payload): Json<MfaCodeRequest>,
+) -> AppResult<Json<RecoveryCodesResponse>> {
+    let user_id = require_session_user(&context, principal.as_ref())?;
+    let pool = require_pool(&state)?;
+
+    let codes = MfaService::new(pool)
+        .confirm(user_id, &payload.code)
+        .await?;
+    Ok(Json(RecoveryCodesResponse { codes }))
+}
+
+#[instrument(skip(state, context, principal, payload))]
+async fn disable_totp(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+    Json(payload): Json<MfaCodeRequest>,
+) -> AppResult<impl IntoResponse> {
+    let user_id = require_session_user(&context, principal.as_ref())?;
+    let pool = require_pool(&state)?;
+
+    MfaService::new(pool)
+        .disable(user_id, &payload.code)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[instrument(skip(state, context, principal, payload))]
+async fn regenerate_recovery_codes(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+    Json(payload): Json<MfaCodeRequest>,
+) -> AppResult<Json<RecoveryCodesResponse>> {
+    let user_id = require_session_user(&context, principal.as_ref())?;
+    let pool = require_pool(&state)?;
+
+    let codes = MfaService::new(pool)
+        .regenerate_recovery_codes(user_id, &payload.code)
+        .await?;
+    Ok(Json(RecoveryCodesResponse { codes }))
+}
+
+#[instrument(skip(state, context))]
+pub async fn get_policy(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<MfaPolicy>> {
+    require_admin_context(&context)?;
+    let pool = require_pool(&state)?;
+
+    let policy = MfaService::new(pool).policy().await?;
+    Ok(Json(policy))
+}
+
+/// Replaces the roles that must sign in with a second factor. Members of those roles who have
+/// not enrolled are walked through enrollment at their next login.
+#[instrument(skip(state, context, payload))]
+pub async fn update_policy(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Json(payload): Json<MfaPolicy>,
+) -> AppResult<Json<MfaPolicy>> {
+    require_admin_context(&context)?;
+    let pool = require_pool(&state)?;
+
+    let policy = MfaService::new(pool)
+        .set_policy(&AuditContext::from(&context), &payload)
+        .await?;
+    Ok(Json(policy))
+}
+
+/// A stolen API token must not be able to switch the second factor off or read new codes.
+fn require_session_user(
+    context: &RequestContext,
+    principal: Option<&Extension<ApiTokenPrincipal>>,
+) -> AppResult<Uuid> {
+    if principal.is_some() {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "RGP.AUTH.SESSION_REQUIRED",
+            "API tokens cannot manage two-factor authentication",
+        ));
+    }
+
+    context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::UserRole;
+
+    #[tokio::test]
+    async fn mfa_policy_requires_admin_role() {
+        let context = RequestContext::test_with_roles(vec![UserRole::Member]);
+
+        let status = match update_policy(
+            Extension(Arc::new(AppState::default())),
+            Extension(context),
+            Json(MfaPolicy {
+                required_roles: vec![UserRole::Admin],
+            }),
+        )
+        .await
+        {
+            Ok(_) => panic!("members must not change the 2FA policy"),
+            Err(err) => err.into_response().status(),
+        };
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
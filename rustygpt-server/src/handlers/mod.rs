@@ -1,13 +1,28 @@
+pub mod account;
+pub mod admin_audit;
 pub mod admin_limits;
+pub mod admin_quotas;
+pub mod admin_retention;
+pub mod admin_users;
+pub mod admin_webhooks;
+pub mod api_tokens;
 pub mod apple_auth;
 pub mod auth;
 pub mod conversations;
 pub mod copilot;
 pub mod github_auth;
+pub mod groups;
+pub mod mfa;
 pub mod oauth_testable;
+pub mod oidc;
+pub mod organizations;
+pub mod preferences;
+pub mod sessions;
 pub mod setup;
+pub mod stream_socket;
 pub mod streaming;
 pub mod threads;
+pub mod usage;
 
 #[cfg(test)]
 mod apple_auth_test;
@@ -0,0 +1,356 @@
+//! Browser endpoints for `OpenID` Connect sign-in.
+//!
+//! The callback never answers with JSON: the browser arrived by redirect, so every outcome is a
+//! redirect back into the web app. Failures and pending two-factor challenges travel in the URL
+//! fragment, which is not sent to servers or logged by proxies.
+
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Extension, Path, Query},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+};
+use oauth2::url::form_urlencoded;
+use serde::Deserialize;
+use time::{Duration as TimeDuration, OffsetDateTime};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    app_state::AppState,
+    auth::{
+        oidc::{self, LOGIN_REQUEST_TTL_SECONDS, OidcError, SharedOidcRegistry},
+        session::{LoginOutcome, SessionError},
+    },
+    handlers::{
+        admin_limits::require_pool,
+        auth::{apply_cookies, extract_session_cookie, metadata_from_headers, session_service},
+    },
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::{audit_service::AuditContext, chat_service::ChatServiceError},
+};
+use shared::{
+    config::server::Config,
+    models::{OidcProviderSummary, OidcProvidersResponse},
+};
+
+/// Binds a login request to the browser that started it.
+pub const STATE_COOKIE_NAME: &str = "OIDC-STATE";
+const STATE_COOKIE_PATH: &str = "/api/auth/oidc";
+
+#[derive(Debug, Deserialize)]
+pub struct OidcLoginQuery {
+    pub return_to: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+}
+
+fn login_url(provider: &str) -> String {
+    format!("/api/auth/oidc/{provider}/login")
+}
+
+fn redirect_uri(config: &Config, provider: &str) -> String {
+    format!(
+        "{}/api/auth/oidc/{provider}/callback",
+        config.server.public_base_url.as_str().trim_end_matches('/')
+    )
+}
+
+/// Only same-origin paths are accepted so the login cannot be used as an open redirect.
+fn safe_return_to(value: Option<&str>) -> String {
+    match value {
+        Some(path) if path.starts_with('/') && !path.starts_with("//") && !path.contains('\\') => {
+            path.to_string()
+        }
+        _ => "/".to_string(),
+    }
+}
+
+fn state_cookie(config: &Config, value: String, max_age: TimeDuration) -> cookie::Cookie<'static> {
+    let mut builder = cookie::Cookie::build((STATE_COOKIE_NAME, value))
+        .path(STATE_COOKIE_PATH)
+        .http_only(true)
+        .secure(config.security.cookie.secure)
+        // Lax so the cookie comes back on the top-level redirect from the provider.
+        .same_site(cookie::SameSite::Lax)
+        .max_age(max_age);
+    if max_age.is_zero() {
+        builder = builder.expires(OffsetDateTime::UNIX_EPOCH);
+    }
+    builder.build()
+}
+
+fn fragment_redirect(pairs: &[(&str, &str)]) -> Response {
+    let fragment = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs)
+        .finish();
+    Redirect::to(&format!("/#{fragment}")).into_response()
+}
+
+fn error_redirect(code: &str) -> Response {
+    fragment_redirect(&[("oidc_error", code)])
+}
+
+const fn oidc_error_code(error: &ChatServiceError) -> &'static str {
+    match error {
+        ChatServiceError::Validation(_) => "email_required",
+        ChatServiceError::Forbidden(_) => "account_not_linked",
+        _ => "server_error",
+    }
+}
+
+const fn session_error_code(error: &SessionError) -> &'static str {
+    match error {
+        SessionError::DisabledUser => "account_disabled",
+        _ => "server_error",
+    }
+}
+
+/// Lists the configured providers for the sign-in page.
+#[instrument(skip(registry))]
+pub async fn list_providers(
+    Extension(registry): Extension<SharedOidcRegistry>,
+) -> Json<OidcProvidersResponse> {
+    Json(OidcProvidersResponse {
+        providers: registry
+            .providers()
+            .map(|provider| OidcProviderSummary {
+                name: provider.name().to_string(),
+                display_name: provider.config().display_name.clone(),
+                login_url: login_url(provider.name()),
+            })
+            .collect(),
+    })
+}
+
+/// Starts a login: stores state, nonce and PKCE verifier, then redirects to the provider.
+#[instrument(skip(state, config, registry, query))]
+pub async fn login(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(registry): Extension<SharedOidcRegistry>,
+    Path(provider_name): Path<String>,
+    Query(query): Query<OidcLoginQuery>,
+) -> AppResult<Response> {
+    let provider = registry
+        .get(&provider_name)
+        .map_err(|err| ApiError::not_found(err.to_string()))?;
+    let pool = require_pool(&state)?;
+
+    let request = provider
+        .authorization_request(&redirect_uri(&config, &provider_name))
+        .await
+        .map_err(|err| {
+            warn!(provider = %provider_name, error = %err, "OIDC discovery failed");
+            ApiError::new(
+                StatusCode::BAD_GATEWAY,
+                "RGP.AUTH.OIDC_UNAVAILABLE",
+                "identity provider is unavailable",
+            )
+        })?;
+    oidc::store_login_request(
+        &pool,
+        &provider_name,
+        &request,
+        &safe_return_to(query.return_to.as_deref()),
+    )
+    .await?;
+
+    let mut response = Redirect::to(request.url.as_str()).into_response();
+    apply_cookies(
+        &mut response,
+        &[state_cookie(
+            &config,
+            request.state,
+            TimeDuration::seconds(i64::from(LOGIN_REQUEST_TTL_SECONDS)),
+        )],
+    );
+    Ok(response)
+}
+
+/// Finishes a login at the redirect URI registered with the provider.
+#[instrument(skip(state, config, registry, context, headers, query))]
+pub async fn callback(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
+    Extension(registry): Extension<SharedOidcRegistry>,
+    context: Option<Extension<RequestContext>>,
+    Path(provider_name): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Response {
+    let cookie_state = extract_session_cookie(&headers, STATE_COOKIE_NAME);
+    let mut response = complete_login(
+        &state,
+        &config,
+        &registry,
+        context.as_ref().map(|Extension(context)| context),
+        &provider_name,
+        &headers,
+        cookie_state,
+        query,
+    )
+    .await;
+    apply_cookies(
+        &mut response,
+        &[state_cookie(&config, String::new(), TimeDuration::ZERO)],
+    );
+    response
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn complete_login(
+    state: &Arc<AppState>,
+    config: &Config,
+    registry: &SharedOidcRegistry,
+    context: Option<&RequestContext>,
+    provider_name: &str,
+    headers: &HeaderMap,
+    cookie_state: Option<String>,
+    query: OidcCallbackQuery,
+) -> Response {
+    if let Some(error) = query.error {
+        warn!(provider = %provider_name, %error, "identity provider returned an error");
+        return error_redirect("provider_error");
+    }
+    let (Some(code), Some(returned_state)) = (query.code, query.state) else {
+        return error_redirect("invalid_request");
+    };
+    // The state must come back to the same browser that started the login.
+    if cookie_state.as_deref() != Some(returned_state.as_str()) {
+        return error_redirect("invalid_state");
+    }
+    let Ok(provider) = registry.get(provider_name) else {
+        return error_redirect("unknown_provider");
+    };
+    let (Ok(pool), Ok(sessions)) = (require_pool(state), session_service(state)) else {
+        return error_redirect("server_error");
+    };
+
+    let pending = match oidc::consume_login_request(&pool, provider_name, &returned_state).await {
+        Ok(Some(pending)) => pending,
+        Ok(None) => return error_redirect("invalid_state"),
+        Err(err) => {
+            warn!(provider = %provider_name, error = %err, "failed to load OIDC login request");
+            return error_redirect("server_error");
+        }
+    };
+
+    let identity = match provider
+        .exchange_code(
+            &code,
+            &pending.pkce_verifier,
+            &redirect_uri(config, provider_name),
+            &pending.nonce,
+        )
+        .await
+    {
+        Ok(identity) => identity,
+        Err(err) => {
+            warn!(provider = %provider_name, error = %err, "OIDC code exchange failed");
+            return error_redirect(match err {
+                OidcError::InvalidIdToken(_) => "invalid_token",
+                _ => "provider_unavailable",
+            });
+        }
+    };
+
+    let audit = context.map(AuditContext::from).unwrap_or_default();
+    let resolved = match oidc::resolve_user(
+        &pool,
+        &audit,
+        provider_name,
+        provider.config(),
+        &identity,
+    )
+    .await
+    {
+        Ok(resolved) => resolved,
+        Err(err) => {
+            warn!(provider = %provider_name, error = %err, "OIDC identity rejected");
+            return error_redirect(oidc_error_code(&err));
+        }
+    };
+    info!(
+        provider = %provider_name,
+        user_id = %resolved.user_id,
+        outcome = %resolved.outcome,
+        "OIDC identity resolved"
+    );
+
+    let metadata = metadata_from_headers(headers)
+        .with_request_id(context.map(|context| context.request_id.clone()));
+    match sessions
+        .authenticate_federated(resolved.user_id, provider_name, &metadata)
+        .await
+    {
+        Ok(LoginOutcome::Authenticated(login)) => {
+            let mut response = Redirect::to(&pending.return_to).into_response();
+            apply_cookies(
+                &mut response,
+                &[login.bundle.session_cookie, login.bundle.csrf_cookie],
+            );
+            response
+        }
+        Ok(LoginOutcome::MfaRequired(challenge)) => {
+            let expires_at = challenge.expires_at.to_rfc3339();
+            let mut pairs = vec![
+                ("mfa_challenge", challenge.token.as_str()),
+                ("expires_at", expires_at.as_str()),
+            ];
+            if let Some(enrollment) = &challenge.enrollment {
+                pairs.push(("mfa_secret", enrollment.secret.as_str()));
+                pairs.push(("mfa_uri", enrollment.otpauth_uri.as_str()));
+            }
+            fragment_redirect(&pairs)
+        }
+        Err(err) => {
+            warn!(provider = %provider_name, error = %err, "federated login failed");
+            error_redirect(session_error_code(&err))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header;
+    use shared::config::server::Profile;
+
+    #[test]
+    fn return_to_only_accepts_local_paths() {
+        assert_eq!(safe_return_to(Some("/chat/42?x=1")), "/chat/42?x=1");
+        assert_eq!(safe_return_to(Some("//evil.example")), "/");
+        assert_eq!(safe_return_to(Some("/\\evil.example")), "/");
+        assert_eq!(safe_return_to(Some("https://evil.example")), "/");
+        assert_eq!(safe_return_to(None), "/");
+    }
+
+    #[test]
+    fn errors_are_reported_in_the_fragment() {
+        let response = fragment_redirect(&[("mfa_challenge", "a b"), ("expires_at", "x")]);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "/#mfa_challenge=a+b&expires_at=x"
+        );
+    }
+
+    #[test]
+    fn state_cookie_is_scoped_to_the_oidc_routes() {
+        let cookie = state_cookie(
+            &Config::default_for_profile(Profile::Test),
+            "abc".into(),
+            TimeDuration::seconds(600),
+        );
+        assert_eq!(cookie.path(), Some(STATE_COOKIE_PATH));
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.same_site(), Some(cookie::SameSite::Lax));
+    }
+}
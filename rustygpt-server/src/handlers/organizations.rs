@@ -0,0 +1,301 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Extension, Path},
+    http::StatusCode,
+    routing::{get, put},
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    handlers::admin_limits::{require_admin_context, require_pool},
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::{audit_service::AuditContext, organization_service::OrganizationService},
+};
+use shared::models::{
+    CreateOrganizationRequest, OrgRole, Organization, OrganizationMember,
+    SetOrganizationMemberRequest, UpdateOrganizationRequest, UserRole,
+};
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/orgs", get(list_my_orgs))
+        .route("/api/orgs/{org_id}", get(get_org))
+        .route("/api/orgs/{org_id}/members", get(list_members))
+        .route(
+            "/api/orgs/{org_id}/members/{user_id}",
+            put(put_member).delete(remove_member),
+        )
+}
+
+/// Admin counterparts, mounted by `routes::admin`.
+pub fn admin_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/admin/orgs", get(list_all_orgs).post(create_org))
+        .route(
+            "/admin/orgs/{org_id}",
+            get(admin_get_org).put(update_org).delete(delete_org),
+        )
+}
+
+fn organizations(state: &Arc<AppState>) -> AppResult<OrganizationService> {
+    Ok(OrganizationService::new(require_pool(state)?))
+}
+
+fn require_user(context: &RequestContext) -> AppResult<Uuid> {
+    context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))
+}
+
+fn is_global_admin(context: &RequestContext) -> bool {
+    context
+        .session
+        .as_ref()
+        .is_some_and(|session| session.roles.contains(&UserRole::Admin))
+}
+
+/// Whether someone holding `actor` in the organization may move a member from `current` to
+/// `next` (`None` meaning not a member). Admins manage members and admins; only owners touch
+/// owners. Anyone may leave on their own.
+const fn may_change_member(
+    actor: Option<OrgRole>,
+    current: Option<OrgRole>,
+    next: Option<OrgRole>,
+    is_self: bool,
+) -> bool {
+    match actor {
+        Some(OrgRole::Owner) => true,
+        Some(OrgRole::Admin) => {
+            !matches!(current, Some(OrgRole::Owner)) && !matches!(next, Some(OrgRole::Owner))
+        }
+        _ => is_self && next.is_none(),
+    }
+}
+
+/// Loads the organization as the caller sees it; administrators see every organization and
+/// everyone else gets a 404 for organizations they are not in.
+async fn visible_org(
+    service: &OrganizationService,
+    context: &RequestContext,
+    org_id: Uuid,
+) -> AppResult<Organization> {
+    let user_id = require_user(context)?;
+    let mut org = service.get(org_id, Some(user_id)).await?;
+    if org.is_none() && is_global_admin(context) {
+        org = service.get(org_id, None).await?;
+    }
+    org.ok_or_else(|| ApiError::not_found("organization not found"))
+}
+
+async fn authorize_member_change(
+    service: &OrganizationService,
+    context: &RequestContext,
+    org_id: Uuid,
+    user_id: Uuid,
+    next: Option<OrgRole>,
+) -> AppResult<()> {
+    let org = visible_org(service, context, org_id).await?;
+    if is_global_admin(context) {
+        return Ok(());
+    }
+    let actor = require_user(context)?;
+    let current = service.member_role(org.id, user_id).await?;
+    if may_change_member(org.role, current, next, actor == user_id) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden(
+            "insufficient organization role to change this member",
+        ))
+    }
+}
+
+#[instrument(skip(state, context))]
+async fn list_my_orgs(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<Vec<Organization>>> {
+    let user_id = require_user(&context)?;
+    let orgs = organizations(&state)?.list(Some(user_id)).await?;
+    Ok(Json(orgs))
+}
+
+#[instrument(skip(state, context))]
+async fn get_org(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(org_id): Path<Uuid>,
+) -> AppResult<Json<Organization>> {
+    let service = organizations(&state)?;
+    Ok(Json(visible_org(&service, &context, org_id).await?))
+}
+
+#[instrument(skip(state, context))]
+async fn list_members(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(org_id): Path<Uuid>,
+) -> AppResult<Json<Vec<OrganizationMember>>> {
+    let service = organizations(&state)?;
+    let org = visible_org(&service, &context, org_id).await?;
+    Ok(Json(service.members(org.id).await?))
+}
+
+/// Adds a member or changes their role. Organization owners and admins, and global
+/// administrators, may do this.
+#[instrument(skip(state, context))]
+async fn put_member(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path((org_id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<SetOrganizationMemberRequest>,
+) -> AppResult<Json<OrganizationMember>> {
+    let service = organizations(&state)?;
+    authorize_member_change(&service, &context, org_id, user_id, Some(payload.role)).await?;
+    service
+        .put_member(&AuditContext::from(&context), org_id, user_id, payload.role)
+        .await?;
+    service
+        .members(org_id)
+        .await?
+        .into_iter()
+        .find(|member| member.user_id == user_id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("member not found"))
+}
+
+/// Removes a member, who also leaves every conversation the organization owns. Members may
+/// remove themselves.
+#[instrument(skip(state, context))]
+async fn remove_member(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path((org_id, user_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    let service = organizations(&state)?;
+    authorize_member_change(&service, &context, org_id, user_id, None).await?;
+    if service
+        .remove_member(&AuditContext::from(&context), org_id, user_id)
+        .await?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::not_found("member not found"))
+    }
+}
+
+#[instrument(skip(state, context))]
+async fn list_all_orgs(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<Vec<Organization>>> {
+    require_admin_context(&context)?;
+    Ok(Json(organizations(&state)?.list(None).await?))
+}
+
+#[instrument(skip(state, context))]
+async fn admin_get_org(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(org_id): Path<Uuid>,
+) -> AppResult<Json<Organization>> {
+    require_admin_context(&context)?;
+    organizations(&state)?
+        .get(org_id, None)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("organization not found"))
+}
+
+#[instrument(skip(state, context, payload))]
+async fn create_org(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> AppResult<(StatusCode, Json<Organization>)> {
+    require_admin_context(&context)?;
+    let org = organizations(&state)?
+        .create(&AuditContext::from(&context), &payload)
+        .await?;
+    Ok((StatusCode::CREATED, Json(org)))
+}
+
+/// Replaces the organization's name, rate-limit profile, allowed models and features.
+#[instrument(skip(state, context, payload))]
+async fn update_org(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(org_id): Path<Uuid>,
+    Json(payload): Json<UpdateOrganizationRequest>,
+) -> AppResult<Json<Organization>> {
+    require_admin_context(&context)?;
+    let org = organizations(&state)?
+        .update(&AuditContext::from(&context), org_id, &payload)
+        .await?;
+    Ok(Json(org))
+}
+
+#[instrument(skip(state, context))]
+async fn delete_org(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(org_id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    require_admin_context(&context)?;
+    if organizations(&state)?
+        .delete(&AuditContext::from(&context), org_id)
+        .await?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::not_found("organization not found"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn org_admins_cannot_touch_owners() {
+        let admin = Some(OrgRole::Admin);
+        assert!(may_change_member(admin, None, Some(OrgRole::Member), false));
+        assert!(may_change_member(
+            admin,
+            Some(OrgRole::Member),
+            Some(OrgRole::Admin),
+            false
+        ));
+        assert!(!may_change_member(
+            admin,
+            Some(OrgRole::Member),
+            Some(OrgRole::Owner),
+            false
+        ));
+        assert!(!may_change_member(admin, Some(OrgRole::Owner), None, false));
+        assert!(may_change_member(
+            Some(OrgRole::Owner),
+            Some(OrgRole::Owner),
+            Some(OrgRole::Member),
+            false
+        ));
+    }
+
+    #[test]
+    fn members_may_only_leave() {
+        let member = Some(OrgRole::Member);
+        assert!(may_change_member(member, member, None, true));
+        assert!(!may_change_member(
+            member,
+            member,
+            Some(OrgRole::Admin),
+            true
+        ));
+        assert!(!may_change_member(member, None, None, false));
+        assert!(!may_change_member(None, None, Some(OrgRole::Member), false));
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use axum::{Json, Router, extract::Extension, routing::get};
+use serde_json::Value;
+use tracing::instrument;
+
+use crate::{
+    app_state::AppState,
+    handlers::admin_limits::require_pool,
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::preferences_service::PreferencesService,
+};
+use shared::models::UserPreferences;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route(
+        "/api/me/preferences",
+        get(get_preferences).patch(update_preferences),
+    )
+}
+
+#[instrument(skip(state, context))]
+async fn get_preferences(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+) -> AppResult<Json<UserPreferences>> {
+    let user_id = context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))?;
+    let pool = require_pool(&state)?;
+
+    let preferences = PreferencesService::new(pool).get(user_id).await?;
+    Ok(Json(preferences))
+}
+
+/// Applies a JSON merge patch: fields present in the body replace the stored values and `null`
+/// resets a field to its default.
+#[instrument(skip(state, context, patch))]
+async fn update_preferences(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Json(patch): Json<Value>,
+) -> AppResult<Json<UserPreferences>> {
+    let user_id = context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))?;
+    let pool = require_pool(&state)?;
+
+    let preferences = PreferencesService::new(pool)
+        .update(user_id, &patch)
+        .await?;
+    Ok(Json(preferences))
+}
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Extension, Path},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get},
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    auth::{
+        api_tokens::ApiTokenPrincipal,
+        session::{SessionMetadata, SessionRecord},
+    },
+    handlers::{
+        admin_limits::require_admin_context,
+        api_tokens::require_session_user,
+        auth::{map_session_error, metadata_from_headers, session_service},
+    },
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+};
+use shared::models::{
+    ActiveSession, ActiveSessionListResponse, RevokeSessionsResponse, Timestamp, device_label,
+};
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/api/auth/sessions",
+            get(list_my_sessions).delete(revoke_my_other_sessions),
+        )
+        .route("/api/auth/sessions/{session_id}", delete(revoke_my_session))
+}
+
+/// Admin counterparts, mounted by `routes::admin`.
+pub fn admin_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/admin/users/{user_id}/sessions",
+            get(list_user_sessions).delete(revoke_user_sessions),
+        )
+        .route(
+            "/admin/users/{user_id}/sessions/{session_id}",
+            delete(revoke_user_session),
+        )
+}
+
+fn active_session(record: SessionRecord, current: Option<Uuid>) -> ActiveSession {
+    ActiveSession {
+        id: record.id,
+        created_at: Timestamp(record.created_at),
+        last_seen_at: Timestamp(record.last_seen_at),
+        expires_at: Timestamp(record.expires_at),
+        absolute_expires_at: Timestamp(record.absolute_expires_at),
+        device: device_label(record.user_agent.as_deref()),
+        user_agent: record.user_agent,
+        ip: record.ip,
+        current: current == Some(record.id),
+    }
+}
+
+fn request_metadata(headers: &HeaderMap, context: &RequestContext) -> SessionMetadata {
+    metadata_from_headers(headers).with_request_id(context.request_id.clone())
+}
+
+/// The cookie session making the request; API tokens cannot manage sessions.
+fn current_session(
+    context: &RequestContext,
+    principal: Option<&Extension<ApiTokenPrincipal>>,
+) -> AppResult<(Uuid, Uuid)> {
+    let user_id = require_session_user(context, principal)?;
+    let session_id = context
+        .session
+        .as_ref()
+        .map(|session| session.session_id)
+        .ok_or_else(|| ApiError::forbidden("authentication required"))?;
+    Ok((user_id, session_id))
+}
+
+async fn list_sessions(
+    state: &Arc<AppState>,
+    user_id: Uuid,
+    current: Option<Uuid>,
+) -> AppResult<Json<ActiveSessionListResponse>> {
+    let sessions = session_service(state)?
+        .list_sessions(user_id)
+        .await
+        .map_err(map_session_error)?;
+    Ok(Json(ActiveSessionListResponse {
+        sessions: sessions
+            .into_iter()
+            .map(|record| active_session(record, current))
+            .collect(),
+    }))
+}
+
+#[instrument(skip(state, context, principal))]
+async fn list_my_sessions(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+) -> AppResult<Json<ActiveSessionListResponse>> {
+    let (user_id, session_id) = current_session(&context, principal.as_ref())?;
+    list_sessions(&state, user_id, Some(session_id)).await
+}
+
+#[instrument(skip(state, context, principal, headers))]
+async fn revoke_my_session(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+) -> AppResult<impl IntoResponse> {
+    let (user_id, _) = current_session(&context, principal.as_ref())?;
+    session_service(&state)?
+        .revoke_user_session(
+            user_id,
+            session_id,
+            user_id,
+            &request_metadata(&headers, &context),
+        )
+        .await
+        .map_err(map_session_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// "Sign out everywhere else": revokes every session except the one making the request.
+#[instrument(skip(state, context, principal, headers))]
+async fn revoke_my_other_sessions(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+    headers: HeaderMap,
+) -> AppResult<Json<RevokeSessionsResponse>> {
+    let (user_id, session_id) = current_session(&context, principal.as_ref())?;
+    let revoked = session_service(&state)?
+        .revoke_user_sessions(
+            user_id,
+            Some(session_id),
+            user_id,
+            &request_metadata(&headers, &context),
+        )
+        .await
+        .map_err(map_session_error)?;
+    Ok(Json(RevokeSessionsResponse { revoked }))
+}
+
+#[instrument(skip(state, context))]
+async fn list_user_sessions(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<Json<ActiveSessionListResponse>> {
+    let admin = require_admin_context(&context)?;
+    let current = Some(admin.session_id);
+    list_sessions(&state, user_id, current).await
+}
+
+#[instrument(skip(state, context, headers))]
+async fn revoke_user_session(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    headers: HeaderMap,
+    Path((user_id, session_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<impl IntoResponse> {
+    let admin = require_admin_context(&context)?.id;
+    session_service(&state)?
+        .revoke_user_session(
+            user_id,
+            session_id,
+            admin,
+            &request_metadata(&headers, &context),
+        )
+        .await
+        .map_err(map_session_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Signs a user out everywhere. An admin targeting their own account keeps the current session.
+#[instrument(skip(state, context, headers))]
+async fn revoke_user_sessions(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<Json<RevokeSessionsResponse>> {
+    let admin = require_admin_context(&context)?;
+    let keep = (admin.id == user_id).then_some(admin.session_id);
+    let admin_id = admin.id;
+    let revoked = session_service(&state)?
+        .revoke_user_sessions(
+            user_id,
+            keep,
+            admin_id,
+            &request_metadata(&headers, &context),
+        )
+        .await
+        .map_err(map_session_error)?;
+    Ok(Json(RevokeSessionsResponse { revoked }))
+}
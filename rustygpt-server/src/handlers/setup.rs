@@ -2,17 +2,24 @@ use std::sync::Arc;
 
 use crate::{
     app_state::AppState,
-    services::setup::{init_setup, is_setup},
+    auth::account::AccountService,
+    services::{
+        audit_service::AuditContext,
+        setup::{init_setup, is_setup},
+    },
 };
 use axum::{
-    Router,
+    Extension, Router,
     extract::{Json, State},
     response::{IntoResponse, Response},
     routing::get,
 };
 use http::StatusCode;
-use shared::models::{ErrorResponse, SetupRequest, SetupResponse};
-use tracing::{info, instrument};
+use shared::{
+    config::server::Config,
+    models::{ErrorResponse, SetupRequest, SetupResponse},
+};
+use tracing::{info, instrument, warn};
 
 // Handler for checking if the system is setup
 #[utoipa::path(
@@ -58,15 +65,23 @@ pub async fn get_setup(State(state): State<Arc<AppState>>) -> Response {
     ),
     tag = "Setup"
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, config))]
 pub async fn post_setup(
     State(state): State<Arc<AppState>>,
+    Extension(config): Extension<Arc<Config>>,
     Json(payload): Json<SetupRequest>,
 ) -> Response {
     info!("Received setup request: {:?}", payload);
     match init_setup(state.pool.as_ref(), &payload).await {
         Ok(true) => {
             info!("Setup completed successfully");
+            if let Some(pool) = state.pool.clone()
+                && let Err(err) = AccountService::new(pool, config)
+                    .send_verification_to(&AuditContext::default(), &payload.email)
+                    .await
+            {
+                warn!(error = %err, "failed to queue the verification email for the first account");
+            }
             (StatusCode::OK).into_response()
         }
         Ok(false) => {
@@ -0,0 +1,466 @@
+//! WebSocket transport for conversation streams. One socket carries the feeds of several
+//! conversations, fanned out from the same [`StreamHub`] as SSE, and accepts the typing,
+//! presence, mark-read and cancel commands that otherwise need their own POSTs. Like those
+//! POSTs, the commands need the `threads:write` scope when the socket was opened with a token.
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::{
+        Extension,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code},
+    },
+    http::{HeaderMap, StatusCode, header},
+    response::Response,
+};
+use futures::stream::BoxStream;
+use shared::{
+    config::server::Config,
+    models::{
+        ApiTokenScope, ConversationStreamEvent, StreamClientMessage, StreamCommand,
+        StreamServerMessage, TypingRequest,
+    },
+};
+use tokio::time::{self, MissedTickBehavior};
+use tokio_stream::{StreamExt, StreamMap};
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    auth::api_tokens::ApiTokenPrincipal,
+    handlers::{
+        streaming::{SharedStreamHub, StreamHub, follow_conversation},
+        threads::{apply_heartbeat, apply_mark_read, apply_typing, cancel_generation},
+    },
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::chat_service::ChatService,
+};
+
+/// Conversations one socket may follow at once.
+const MAX_SUBSCRIPTIONS: usize = 64;
+const PING_INTERVAL: Duration = Duration::from_secs(20);
+
+type Subscriptions = StreamMap<Uuid, BoxStream<'static, (String, ConversationStreamEvent)>>;
+
+#[instrument(skip(app_state, context, principal, hub, config, headers, upgrade))]
+pub async fn stream_socket(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    principal: Option<Extension<ApiTokenPrincipal>>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Extension(config): Extension<Arc<Config>>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> AppResult<Response> {
+    let user_id = context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))?;
+    check_origin(&headers, &config)?;
+    let pool = app_state.pool.clone().ok_or_else(|| {
+        ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "database_unavailable",
+            "database pool not configured",
+        )
+    })?;
+
+    let session = SocketSession {
+        app_state,
+        hub,
+        service: ChatService::new(pool),
+        user_id,
+        principal: principal.map(|Extension(principal)| principal),
+    };
+    Ok(upgrade.on_upgrade(move |socket| session.run(socket)))
+}
+
+/// Browsers attach session cookies to cross-site WebSocket handshakes, so a browser `Origin`
+/// must be the server's own or one allowed by CORS. Clients without an `Origin` header are not
+/// browsers and pass.
+fn check_origin(headers: &HeaderMap, config: &Config) -> AppResult<()> {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return Ok(());
+    };
+    let origin = origin.to_str().unwrap_or_default().trim_end_matches('/');
+    let own = config.server.public_base_url.origin().ascii_serialization();
+    let allowed = origin == own
+        || config
+            .server
+            .cors
+            .allowed_origins
+            .iter()
+            .any(|allowed| allowed.trim_end_matches('/') == origin);
+    if allowed {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("origin not allowed"))
+    }
+}
+
+struct SocketSession {
+    app_state: Arc<AppState>,
+    hub: SharedStreamHub,
+    service: ChatService,
+    user_id: Uuid,
+    /// The bearer token the socket was opened with; `None` for cookie sessions.
+    principal: Option<ApiTokenPrincipal>,
+}
+
+impl SocketSession {
+    async fn run(self, mut socket: WebSocket) {
+        let mut subscriptions = Subscriptions::new();
+        let mut ping = time::interval(PING_INTERVAL);
+        ping.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ping.tick().await;
+        let closed = self.hub.closed();
+        tokio::pin!(closed);
+
+        loop {
+            let reply = tokio::select! {
+                incoming = socket.recv() => match incoming {
+                    Some(Ok(Message::Text(text))) => {
+                        self.handle_text(text.as_str(), &mut subscriptions).await
+                    }
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                },
+                Some((conversation_id, (id, event))) = subscriptions.next(),
+                    if !subscriptions.is_empty() =>
+                {
+                    StreamServerMessage::Event {
+                        conversation_id,
+                        id,
+                        event,
+                    }
+                }
+                _ = ping.tick() => {
+                    if socket.send(Message::Ping(Vec::new().into())).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                () = &mut closed => {
+                    // 1012 asks the client to reconnect, which lands it on a running instance.
+                    let frame = CloseFrame {
+                        code: close_code::RESTART,
+                        reason: "server shutting down".into(),
+                    };
+                    let _ = socket.send(Message::Close(Some(frame))).await;
+                    break;
+                }
+            };
+
+            let Ok(text) = serde_json::to_string(&reply) else {
+                continue;
+            };
+            if socket.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+        debug!(user_id = %self.user_id, "stream socket closed");
+    }
+
+    async fn handle_text(
+        &self,
+        text: &str,
+        subscriptions: &mut Subscriptions,
+    ) -> StreamServerMessage {
+        let message = match serde_json::from_str::<StreamClientMessage>(text) {
+            Ok(message) => message,
+            Err(err) => {
+                return StreamServerMessage::Error {
+                    request_id: None,
+                    code: "RGP.WS.INVALID_MESSAGE".to_string(),
+                    message: err.to_string(),
+                };
+            }
+        };
+
+        match self.apply(message.command, subscriptions).await {
+            Ok(status) => StreamServerMessage::Ack {
+                request_id: message.request_id,
+                status: status.map(str::to_string),
+            },
+            Err(err) => {
+                if err.code() == "RGP.INTERNAL" {
+                    warn!(
+                        code = err.code(),
+                        error = err.message(),
+                        "stream socket command failed"
+                    );
+                }
+                StreamServerMessage::Error {
+                    request_id: message.request_id,
+                    code: err.code().to_string(),
+                    message: err.message().to_string(),
+                }
+            }
+        }
+    }
+
+    async fn apply(
+        &self,
+        command: StreamCommand,
+        subscriptions: &mut Subscriptions,
+    ) -> AppResult<Option<&'static str>> {
+        if !matches!(
+            command,
+            StreamCommand::Subscribe { .. } | StreamCommand::Unsubscribe { .. }
+        ) {
+            self.require_write_scope()?;
+        }
+
+        let hub: &StreamHub = &self.hub;
+        match command {
+            StreamCommand::Subscribe {
+                conversation_id,
+                last_event_id,
+                since,
+            } => {
+                if !subscriptions.contains_key(&conversation_id)
+                    && subscriptions.len() >= MAX_SUBSCRIPTIONS
+                {
+                    return Err(ApiError::new(
+                        StatusCode::BAD_REQUEST,
+                        "RGP.WS.TOO_MANY_SUBSCRIPTIONS",
+                        format!("a socket may follow at most {MAX_SUBSCRIPTIONS} conversations"),
+                    ));
+                }
+                let events = follow_conversation(
+                    hub,
+                    &self.service,
+                    self.user_id,
+                    conversation_id,
+                    last_event_id.as_deref(),
+                    since,
+                )
+                .await?;
+                subscriptions.insert(conversation_id, events);
+                Ok(None)
+            }
+            StreamCommand::Unsubscribe { conversation_id } => {
+                subscriptions.remove(&conversation_id);
+                Ok(None)
+            }
+            StreamCommand::Typing {
+                conversation_id,
+                root_id,
+                seconds,
+            } => {
+                let request = TypingRequest {
+                    conversation_id,
+                    root_id,
+                    seconds,
+                };
+                apply_typing(&self.service, hub, self.user_id, &request).await?;
+                Ok(None)
+            }
+            StreamCommand::Heartbeat { status } => {
+                apply_heartbeat(&self.service, hub, self.user_id, status).await?;
+                Ok(None)
+            }
+            StreamCommand::MarkRead { root_id, path } => {
+                apply_mark_read(&self.service, hub, self.user_id, root_id, path.as_deref()).await?;
+                Ok(None)
+            }
+            StreamCommand::Cancel { message_id } => {
+                let status =
+                    cancel_generation(&self.app_state, &self.service, self.user_id, message_id)
+                        .await?;
+                Ok(Some(status))
+            }
+        }
+    }
+
+    /// The upgrade is a GET, which any token may make; the commands change state like the
+    /// POSTs they mirror and need the scope those POSTs need.
+    fn require_write_scope(&self) -> AppResult<()> {
+        match &self.principal {
+            Some(principal) if !principal.allows(ApiTokenScope::ThreadsWrite) => {
+                Err(ApiError::new(
+                    StatusCode::FORBIDDEN,
+                    "RGP.AUTH.INSUFFICIENT_SCOPE",
+                    "API token lacks the threads:write scope",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::TestDatabase;
+    use axum::{Router, http::HeaderValue, routing::get};
+    use axum_test::{TestServer, TestWebSocket};
+    use chrono::Utc;
+    use shared::{
+        config::server::Profile,
+        models::{MembershipChangeAction, MembershipChangedEvent, UserRole},
+    };
+
+    fn headers(origin: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ORIGIN, HeaderValue::from_str(origin).unwrap());
+        headers
+    }
+
+    #[test]
+    fn browser_origins_must_be_known() {
+        let mut config = Config::default_for_profile(Profile::Prod);
+        config.server.public_base_url = "https://chat.example.com/app/".parse().unwrap();
+        config.server.cors.allowed_origins = vec!["https://ops.example.com".to_string()];
+
+        assert!(check_origin(&HeaderMap::new(), &config).is_ok());
+        assert!(check_origin(&headers("https://chat.example.com"), &config).is_ok());
+        assert!(check_origin(&headers("https://ops.example.com"), &config).is_ok());
+        assert!(check_origin(&headers("https://evil.example.com"), &config).is_err());
+    }
+
+    fn principal(user_id: Uuid, scopes: Vec<ApiTokenScope>) -> ApiTokenPrincipal {
+        ApiTokenPrincipal {
+            token_id: Uuid::new_v4(),
+            user_id,
+            email: "socket@example.com".to_string(),
+            username: "socket".to_string(),
+            display_name: None,
+            roles: vec![UserRole::Member],
+            scopes,
+            created_at: Utc::now(),
+            expires_at: None,
+        }
+    }
+
+    async fn open_socket(
+        db: &TestDatabase,
+        hub: &SharedStreamHub,
+        principal: ApiTokenPrincipal,
+    ) -> TestWebSocket {
+        let context = RequestContext {
+            session: Some(principal.session_user()),
+            ..RequestContext::default()
+        };
+        let app = Router::new()
+            .route("/api/stream/ws", get(stream_socket))
+            .layer(Extension(crate::server::create_app_state(
+                Some(db.pool.clone()),
+                None,
+                None,
+                None,
+                None,
+                None,
+            )))
+            .layer(Extension(context))
+            .layer(Extension(principal))
+            .layer(Extension(hub.clone()))
+            .layer(Extension(Arc::new(Config::default_for_profile(
+                Profile::Dev,
+            ))));
+        let server = TestServer::builder()
+            .http_transport()
+            .build(app)
+            .expect("test server");
+        server
+            .get_websocket("/api/stream/ws")
+            .await
+            .into_websocket()
+            .await
+    }
+
+    async fn send(socket: &mut TestWebSocket, request_id: &str, command: StreamCommand) {
+        socket
+            .send_json(&StreamClientMessage {
+                request_id: Some(request_id.to_string()),
+                command,
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn tokens_without_write_scope_can_only_follow_conversations() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let user = db.create_user("socket_reader").await;
+        let conversation = db.create_conversation(user, "Socket").await;
+        let hub: SharedStreamHub = Arc::new(StreamHub::new(64, None, None));
+
+        let mut reader = open_socket(&db, &hub, principal(user, vec![ApiTokenScope::V1Chat])).await;
+        let subscribe = StreamCommand::Subscribe {
+            conversation_id: conversation,
+            last_event_id: None,
+            since: None,
+        };
+        send(&mut reader, "subscribe", subscribe).await;
+        assert_eq!(
+            reader.receive_json::<StreamServerMessage>().await,
+            StreamServerMessage::Ack {
+                request_id: Some("subscribe".to_string()),
+                status: None,
+            }
+        );
+
+        let joined = ConversationStreamEvent::MembershipChanged {
+            payload: MembershipChangedEvent {
+                conversation_id: conversation,
+                user_id: Uuid::new_v4(),
+                role: None,
+                action: MembershipChangeAction::Added,
+            },
+        };
+        hub.publish(conversation, joined.clone()).await;
+        assert!(matches!(
+            reader.receive_json::<StreamServerMessage>().await,
+            StreamServerMessage::Event { conversation_id, event, .. }
+                if conversation_id == conversation && event == joined
+        ));
+
+        for command in [
+            StreamCommand::Heartbeat { status: None },
+            StreamCommand::MarkRead {
+                root_id: Uuid::new_v4(),
+                path: None,
+            },
+            StreamCommand::Cancel {
+                message_id: Uuid::new_v4(),
+            },
+        ] {
+            send(&mut reader, "write", command).await;
+            let reply = reader.receive_json::<StreamServerMessage>().await;
+            assert!(
+                matches!(
+                    &reply,
+                    StreamServerMessage::Error { request_id, code, .. }
+                        if request_id.as_deref() == Some("write")
+                            && code == "RGP.AUTH.INSUFFICIENT_SCOPE"
+                ),
+                "unexpected reply {reply:?}"
+            );
+        }
+
+        let mut writer = open_socket(
+            &db,
+            &hub,
+            principal(user, vec![ApiTokenScope::ThreadsWrite]),
+        )
+        .await;
+        send(
+            &mut writer,
+            "heartbeat",
+            StreamCommand::Heartbeat { status: None },
+        )
+        .await;
+        assert_eq!(
+            writer.receive_json::<StreamServerMessage>().await,
+            StreamServerMessage::Ack {
+                request_id: Some("heartbeat".to_string()),
+                status: None,
+            }
+        );
+
+        db.destroy().await;
+    }
+}
@@ -12,11 +12,12 @@ use axum::{
     response::sse::{Event, KeepAlive, Sse},
 };
 use chrono::{DateTime, Utc};
-use futures::{StreamExt, stream};
+use futures::{StreamExt, stream, stream::BoxStream};
 use serde::Deserialize;
 use serde_json::json;
 use tokio::sync::{Mutex, broadcast};
 use tokio_stream::wrappers::BroadcastStream;
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
 use tracing::{instrument, warn};
 use uuid::Uuid;
 
@@ -28,11 +29,14 @@ use crate::{
     services::{
         chat_service::ChatService,
         sse_persistence::{PersistedStreamEvent, SsePersistence, StreamEventRecord},
+        stream_relay::{RelayedEvent, StreamRelay},
+        stream_supervisor::SHUTDOWN_RETRY_AFTER,
+        webhook_outbox::WebhookQueue,
     },
 };
 use shared::{
     config::server::SsePersistenceConfig,
-    models::{ConversationStreamEvent, ReplyMessageResponse},
+    models::{ConversationStreamEvent, ReplyMessageResponse, UserStreamEvent},
 };
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -116,13 +120,25 @@ pub struct StreamHub {
     history_capacity: usize,
     persistence: Option<Arc<dyn SsePersistence>>,
     persistence_config: Option<SsePersistenceConfig>,
+    webhooks: Option<WebhookQueue>,
+    relay: Option<Arc<dyn StreamRelay>>,
+    closing: CancellationToken,
+    /// Events held back until a conversation's next subscriber arrives.
+    deferred: Arc<Mutex<DeferredEvents>>,
+    /// Users whose conversations changed, with the time of the change, so their open user
+    /// streams can re-read which conversations to follow.
+    memberships: broadcast::Sender<(Uuid, i64)>,
 }
 
+type DeferredEvents = HashMap<Uuid, Vec<(ConversationStreamEvent, EventMetadata)>>;
+
 impl fmt::Debug for StreamHub {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("StreamHub")
             .field("history_capacity", &self.history_capacity)
             .field("has_persistence", &self.persistence.is_some())
+            .field("has_webhooks", &self.webhooks.is_some())
+            .field("has_relay", &self.relay.is_some())
             .finish_non_exhaustive()
     }
 }
@@ -151,9 +167,39 @@ impl StreamHub {
             history_capacity: history_capacity.max(64),
             persistence,
             persistence_config,
+            webhooks: None,
+            relay: None,
+            closing: CancellationToken::new(),
+            deferred: Arc::new(Mutex::new(HashMap::new())),
+            memberships: broadcast::channel(256).0,
         }
     }
 
+    /// Queues published events for webhook delivery.
+    #[must_use]
+    pub fn with_webhooks(mut self, webhooks: WebhookQueue) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    /// Sends published events to other server instances through `relay`.
+    #[must_use]
+    pub fn with_relay(mut self, relay: Arc<dyn StreamRelay>) -> Self {
+        self.relay = Some(relay);
+        self
+    }
+
+    /// Ends every open SSE and WebSocket stream so the server can exit; clients are told to
+    /// reconnect after [`SHUTDOWN_RETRY_AFTER`].
+    pub fn close_streams(&self) {
+        self.closing.cancel();
+    }
+
+    /// Resolves once [`Self::close_streams`] has been called.
+    pub fn closed(&self) -> WaitForCancellationFutureOwned {
+        self.closing.clone().cancelled_owned()
+    }
+
     pub fn replay_limit(&self) -> usize {
         self.persistence_config
             .as_ref()
@@ -219,8 +265,88 @@ impl StreamHub {
         drop(state);
 
         let _ = channel.sender.send(envelope.clone());
+        self.note_membership_change(&envelope);
 
         persist_event(self, conversation_id, &envelope).await;
+        relay_event(self, conversation_id, &envelope).await;
+
+        if let Some(webhooks) = &self.webhooks {
+            let name = event_name(&envelope.event);
+            if let Err(err) = webhooks
+                .enqueue(conversation_id, name, &envelope.event)
+                .await
+            {
+                warn!(%conversation_id, event = name, error = %err, "failed to queue webhooks");
+            }
+        }
+    }
+
+    /// Re-broadcasts an event another instance published. It reaches local subscribers and the
+    /// in-memory history only; the origin already persisted it and queued its webhooks.
+    pub async fn publish_relayed(&self, relayed: RelayedEvent) {
+        let Some(event) = relayed.payload.and_then(deserialize_persisted_event) else {
+            return;
+        };
+
+        let channel = self.get_channel(relayed.conversation_id).await;
+        let mut state = channel.state.lock().await;
+        if state
+            .history
+            .iter()
+            .any(|envelope| envelope.event_id() == relayed.event_id)
+        {
+            return;
+        }
+
+        // Keep this instance's own sequences and timestamps ahead of what the others used.
+        let sequence = state.next_sequence;
+        let remote_next = u64::try_from(relayed.sequence)
+            .unwrap_or(0)
+            .saturating_add(1);
+        state.next_sequence = sequence.saturating_add(1).max(remote_next);
+        state.last_timestamp_ms = state.last_timestamp_ms.max(relayed.timestamp_ms);
+
+        let envelope = EventEnvelope {
+            sequence,
+            timestamp_ms: relayed.timestamp_ms,
+            metadata: EventMetadata {
+                chunk_index: relayed.chunk_index,
+            },
+            event,
+        };
+        state.history.push_back(envelope.clone());
+        if state.history.len() > self.history_capacity {
+            state.history.pop_front();
+        }
+        drop(state);
+
+        self.note_membership_change(&envelope);
+        let _ = channel.sender.send(envelope);
+    }
+
+    fn note_membership_change(&self, envelope: &EventEnvelope) {
+        if let ConversationStreamEvent::MembershipChanged { payload } = &envelope.event {
+            let _ = self
+                .memberships
+                .send((payload.user_id, envelope.timestamp_ms));
+        }
+    }
+
+    /// Re-broadcasts events from other instances until the relay stream ends. Returns at once
+    /// when the hub has no relay.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the relay subscription cannot be opened.
+    pub async fn run_relay(&self) -> anyhow::Result<()> {
+        let Some(relay) = &self.relay else {
+            return Ok(());
+        };
+        let mut events = relay.subscribe().await?;
+        while let Some(relayed) = events.next().await {
+            self.publish_relayed(relayed).await;
+        }
+        Ok(())
     }
 
     pub async fn publish_chunk_event(
@@ -237,6 +363,51 @@ impl StreamHub {
         .await;
     }
 
+    /// Holds `event` until someone next subscribes to `conversation_id`, then publishes it
+    /// as a chunk event, so corrections nobody was listening for still reach a client live.
+    /// With subscribers already listening it is published at once.
+    pub async fn publish_on_subscribe(
+        &self,
+        conversation_id: Uuid,
+        event: ConversationStreamEvent,
+        chunk_index: Option<i32>,
+    ) {
+        let metadata = EventMetadata { chunk_index };
+        let listening = self
+            .inner
+            .lock()
+            .await
+            .get(&conversation_id)
+            .is_some_and(|channel| channel.sender.receiver_count() > 0);
+        if listening {
+            self.publish_with_metadata(conversation_id, event, metadata)
+                .await;
+            return;
+        }
+        self.deferred
+            .lock()
+            .await
+            .entry(conversation_id)
+            .or_default()
+            .push((event, metadata));
+    }
+
+    async fn publish_deferred(&self, conversation_id: Uuid) {
+        let Some(events) = self.deferred.lock().await.remove(&conversation_id) else {
+            return;
+        };
+        for (event, metadata) in events {
+            self.publish_with_metadata(conversation_id, event, metadata)
+                .await;
+        }
+    }
+
+    async fn live_receiver(&self, conversation_id: Uuid) -> broadcast::Receiver<EventEnvelope> {
+        let receiver = self.get_channel(conversation_id).await.sender.subscribe();
+        self.publish_deferred(conversation_id).await;
+        receiver
+    }
+
     async fn subscribe(
         &self,
         conversation_id: Uuid,
@@ -259,7 +430,9 @@ impl StreamHub {
             ordered.insert(envelope.sequence, envelope);
         }
 
-        (channel.sender.subscribe(), ordered.into_values().collect())
+        let receiver = channel.sender.subscribe();
+        self.publish_deferred(conversation_id).await;
+        (receiver, ordered.into_values().collect())
     }
 
     async fn load_persisted(
@@ -297,21 +470,23 @@ impl StreamHub {
     }
 }
 
+fn stream_event_record(envelope: &EventEnvelope) -> Result<StreamEventRecord, serde_json::Error> {
+    Ok(StreamEventRecord {
+        sequence: i64::try_from(envelope.sequence).unwrap_or(i64::MAX),
+        event_id: envelope.event_id(),
+        event_type: event_name(&envelope.event).to_string(),
+        payload: serde_json::to_value(&envelope.event)?,
+        root_message_id: envelope.root_id(),
+    })
+}
+
 async fn persist_event(hub: &StreamHub, conversation_id: Uuid, envelope: &EventEnvelope) {
     let Some(store) = &hub.persistence else {
         return;
     };
 
-    match serde_json::to_value(&envelope.event) {
-        Ok(json_payload) => {
-            let record = StreamEventRecord {
-                sequence: i64::try_from(envelope.sequence).unwrap_or(i64::MAX),
-                event_id: envelope.event_id(),
-                event_type: event_name(&envelope.event).to_string(),
-                payload: json_payload,
-                root_message_id: envelope.root_id(),
-            };
-
+    match stream_event_record(envelope) {
+        Ok(record) => {
             if let Err(err) = store.record_event(conversation_id, &record).await {
                 warn!(error = %err, "failed to persist SSE event");
             }
@@ -324,6 +499,29 @@ async fn persist_event(hub: &StreamHub, conversation_id: Uuid, envelope: &EventE
     }
 }
 
+async fn relay_event(hub: &StreamHub, conversation_id: Uuid, envelope: &EventEnvelope) {
+    let Some(relay) = &hub.relay else {
+        return;
+    };
+
+    let result = match stream_event_record(envelope) {
+        Ok(record) => {
+            relay
+                .publish(
+                    conversation_id,
+                    &record,
+                    envelope.timestamp_ms,
+                    envelope.chunk_index(),
+                )
+                .await
+        }
+        Err(err) => Err(err.into()),
+    };
+    if let Err(err) = result {
+        warn!(%conversation_id, error = %err, "failed to relay stream event");
+    }
+}
+
 async fn prune_history(hub: &StreamHub, store: &Arc<dyn SsePersistence>, conversation_id: Uuid) {
     let Some(cfg) = &hub.persistence_config else {
         return;
@@ -513,7 +711,6 @@ pub async fn conversation_stream(
     let user_id = require_user(&context)?;
     let pool = require_pool(&app_state)?;
     let service = ChatService::new(pool);
-    service.ensure_membership(user_id, conversation_id).await?;
 
     let header_cursor = headers
         .get("last-event-id")
@@ -523,10 +720,102 @@ pub async fn conversation_stream(
     let since_override = params.since.map(|value| value.max(0));
     let replay_cursor = build_replay_cursor(header_cursor, since_override);
 
+    let combined = replay_then_follow(&hub, &service, user_id, conversation_id, replay_cursor)
+        .await?
+        .filter_map(|envelope| async move { convert_event(&envelope) });
+
+    Ok(Sse::new(until_closed(&hub, combined).map(Ok)).keep_alive(stream_keep_alive()))
+}
+
+/// `GET /api/stream/me`: the badge-relevant events of every conversation the user can read,
+/// over one connection. Event ids combine the conversation and its event id, so
+/// `Last-Event-ID` resumes across all of them; without a cursor the stream starts live.
+#[instrument(skip(app_state, context, hub, headers))]
+pub async fn user_stream(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Query(params): Query<StreamQuery>,
+    headers: HeaderMap,
+) -> AppResult<Sse<impl futures::Stream<Item = Result<Event, Infallible>>>> {
+    let user_id = require_user(&context)?;
+    let pool = require_pool(&app_state)?;
+    let service = ChatService::new(pool);
+
+    let header_cursor = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_user_event_id);
+    let cursor = build_user_cursor(header_cursor, params.since.map(|value| value.max(0)));
+
+    let conversations = service.active_conversations(user_id).await?;
+    let events = follow_user(&hub, service, user_id, &conversations, cursor)
+        .await
+        .filter_map(|(conversation_id, envelope)| async move {
+            user_sse_event(conversation_id, &envelope)
+        });
+
+    Ok(Sse::new(until_closed(&hub, events).map(Ok)).keep_alive(stream_keep_alive()))
+}
+
+/// Ends `events` when the hub closes for shutdown, with a last frame that tells the client
+/// how long to wait before reconnecting.
+fn until_closed<S>(hub: &StreamHub, events: S) -> impl futures::Stream<Item = Event> + use<S>
+where
+    S: futures::Stream<Item = Event>,
+{
+    let closing = hub.closing.clone();
+    let farewell = stream::once(async move {
+        closing.is_cancelled().then(|| {
+            Event::default()
+                .retry(SHUTDOWN_RETRY_AFTER)
+                .comment("server shutting down")
+        })
+    })
+    .filter_map(futures::future::ready);
+    events.take_until(hub.closed()).chain(farewell)
+}
+
+fn stream_keep_alive() -> KeepAlive {
+    KeepAlive::new()
+        .interval(Duration::from_secs(20))
+        .text(json!({"type": "ping"}).to_string())
+}
+
+/// A conversation's events as `(event id, event)` pairs: the backlog after the cursor, then
+/// the live feed. `last_event_id` and `since` behave like the SSE `Last-Event-ID` header and
+/// `since` query parameter.
+pub(crate) async fn follow_conversation(
+    hub: &StreamHub,
+    service: &ChatService,
+    user_id: Uuid,
+    conversation_id: Uuid,
+    last_event_id: Option<&str>,
+    since: Option<i64>,
+) -> AppResult<BoxStream<'static, (String, ConversationStreamEvent)>> {
+    let cursor = build_replay_cursor(
+        last_event_id.and_then(parse_last_event_id),
+        since.map(|value| value.max(0)),
+    );
+    let events = replay_then_follow(hub, service, user_id, conversation_id, cursor).await?;
+    Ok(events
+        .map(|envelope| (envelope.event_id(), envelope.event))
+        .boxed())
+}
+
+async fn replay_then_follow(
+    hub: &StreamHub,
+    service: &ChatService,
+    user_id: Uuid,
+    conversation_id: Uuid,
+    replay_cursor: Option<ReplayCursor>,
+) -> AppResult<impl futures::Stream<Item = EventEnvelope> + Send + use<>> {
+    service.ensure_membership(user_id, conversation_id).await?;
+
     let (receiver, mut replay) = hub.subscribe(conversation_id, replay_cursor).await;
     let chunk_limit = hub.replay_limit();
     let chunk_events = chunk_replay_events(
-        &service,
+        service,
         user_id,
         replay_cursor.as_ref(),
         chunk_limit,
@@ -535,11 +824,9 @@ pub async fn conversation_stream(
     .await?;
     replay = merge_replay_events(replay, chunk_events);
 
-    let initial =
-        stream::iter(replay).filter_map(|envelope| async move { convert_event(&envelope) });
-    let broadcast = BroadcastStream::new(receiver).filter_map(|result| async move {
+    let live = BroadcastStream::new(receiver).filter_map(|result| async move {
         match result {
-            Ok(envelope) => convert_event(&envelope),
+            Ok(envelope) => Some(envelope),
             Err(err) => {
                 tracing::warn!(error = %err, "stream subscriber lagged");
                 None
@@ -547,13 +834,256 @@ pub async fn conversation_stream(
         }
     });
 
-    let combined = initial.chain(broadcast).map(Ok);
+    Ok(stream::iter(replay).chain(live))
+}
+
+/// Position in the per-user stream: an event of one conversation. Events of other
+/// conversations count as seen when they are older, or equally old and sort before it.
+#[derive(Clone, Copy, Debug)]
+struct UserStreamCursor {
+    conversation_id: Uuid,
+    inner: ReplayCursor,
+}
+
+async fn follow_user(
+    hub: &StreamHub,
+    service: ChatService,
+    user_id: Uuid,
+    conversations: &[Uuid],
+    cursor: Option<UserStreamCursor>,
+) -> BoxStream<'static, (Uuid, EventEnvelope)> {
+    let memberships = BroadcastStream::new(hub.memberships.subscribe());
+    let mut replay = Vec::new();
+    let mut following = UserFollow {
+        hub: hub.clone(),
+        service,
+        user_id,
+        feeds: HashMap::new(),
+        live: stream::SelectAll::new(),
+        memberships,
+    };
+
+    for &conversation_id in conversations {
+        let receiver = if let Some(cursor) = cursor {
+            let (receiver, events) = hub
+                .subscribe(
+                    conversation_id,
+                    Some(cursor.replay_cursor_for(conversation_id)),
+                )
+                .await;
+            replay.extend(
+                events
+                    .into_iter()
+                    .filter(|envelope| {
+                        is_user_stream_event(&envelope.event, user_id)
+                            && cursor.admits(conversation_id, envelope)
+                    })
+                    .map(|envelope| (conversation_id, envelope)),
+            );
+            receiver
+        } else {
+            hub.live_receiver(conversation_id).await
+        };
+        following.add_feed(conversation_id, Vec::new(), receiver);
+    }
+
+    replay.sort_by(|(a_conversation, a), (b_conversation, b)| {
+        a.timestamp_ms
+            .cmp(&b.timestamp_ms)
+            .then(a_conversation.cmp(b_conversation))
+            .then(a.sequence.cmp(&b.sequence))
+    });
+
+    stream::iter(replay)
+        .chain(stream::unfold(following, UserFollow::next))
+        .boxed()
+}
+
+/// The live half of a user stream. Joining or leaving a conversation changes which feeds it
+/// reads, so a membership change for the user re-reads their conversations.
+struct UserFollow {
+    hub: StreamHub,
+    service: ChatService,
+    user_id: Uuid,
+    feeds: HashMap<Uuid, CancellationToken>,
+    live: stream::SelectAll<BoxStream<'static, (Uuid, EventEnvelope)>>,
+    memberships: BroadcastStream<(Uuid, i64)>,
+}
+
+impl UserFollow {
+    async fn next(mut self) -> Option<((Uuid, EventEnvelope), Self)> {
+        loop {
+            // Events already queued on a feed go out before a removal stops it.
+            tokio::select! {
+                biased;
+                Some(item) = self.live.next() => return Some((item, self)),
+                Some(notice) = self.memberships.next() => match notice {
+                    Ok((user_id, _)) if user_id != self.user_id => {}
+                    Ok((_, changed_at)) => self.refresh(changed_at).await,
+                    Err(err) => {
+                        tracing::warn!(error = %err, "user stream membership notices lagged");
+                        self.refresh(Utc::now().timestamp_millis()).await;
+                    }
+                },
+                else => return None,
+            }
+        }
+    }
+
+    /// Starts following conversations the user joined, from `changed_at` on so events sent
+    /// right after the change are not missed, and stops following the ones they left.
+    async fn refresh(&mut self, changed_at: i64) {
+        let conversations = match self.service.active_conversations(self.user_id).await {
+            Ok(conversations) => conversations,
+            Err(err) => {
+                warn!(user_id = %self.user_id, error = %err, "failed to refresh user stream conversations");
+                return;
+            }
+        };
+
+        self.feeds.retain(|conversation_id, stop| {
+            let keep = conversations.contains(conversation_id);
+            if !keep {
+                stop.cancel();
+            }
+            keep
+        });
+
+        for conversation_id in conversations {
+            if self.feeds.contains_key(&conversation_id) {
+                continue;
+            }
+            let since = ReplayCursor {
+                _root_id: None,
+                message_id: None,
+                chunk_index: None,
+                timestamp_ms: changed_at.saturating_sub(1),
+            };
+            let (receiver, backlog) = self.hub.subscribe(conversation_id, Some(since)).await;
+            let user_id = self.user_id;
+            let backlog = backlog
+                .into_iter()
+                .filter(|envelope| is_user_stream_event(&envelope.event, user_id))
+                .map(|envelope| (conversation_id, envelope))
+                .collect();
+            self.add_feed(conversation_id, backlog, receiver);
+        }
+    }
+
+    fn add_feed(
+        &mut self,
+        conversation_id: Uuid,
+        backlog: Vec<(Uuid, EventEnvelope)>,
+        receiver: broadcast::Receiver<EventEnvelope>,
+    ) {
+        let user_id = self.user_id;
+        let stop = CancellationToken::new();
+        let live = BroadcastStream::new(receiver).filter_map(move |result| async move {
+            match result {
+                Ok(envelope) if is_user_stream_event(&envelope.event, user_id) => {
+                    Some((conversation_id, envelope))
+                }
+                Ok(_) => None,
+                Err(err) => {
+                    tracing::warn!(error = %err, "user stream subscriber lagged");
+                    None
+                }
+            }
+        });
+        self.live.push(
+            stream::iter(backlog)
+                .chain(live)
+                .take_until(stop.clone().cancelled_owned())
+                .boxed(),
+        );
+        self.feeds.insert(conversation_id, stop);
+    }
+}
+
+impl UserStreamCursor {
+    /// The cursor handed to the hub: exact for the cursor's conversation, and for the others
+    /// loose enough to keep events from the cursor's millisecond for [`Self::admits`].
+    fn replay_cursor_for(&self, conversation_id: Uuid) -> ReplayCursor {
+        if conversation_id == self.conversation_id {
+            return self.inner;
+        }
+        ReplayCursor {
+            _root_id: None,
+            message_id: None,
+            chunk_index: None,
+            timestamp_ms: self.inner.timestamp_ms.saturating_sub(1),
+        }
+    }
+
+    /// Whether `envelope` of `conversation_id` comes after the cursor.
+    fn admits(&self, conversation_id: Uuid, envelope: &EventEnvelope) -> bool {
+        if conversation_id == self.conversation_id {
+            return should_include_event(envelope, Some(&self.inner));
+        }
+        (envelope.timestamp_ms, conversation_id) > (self.inner.timestamp_ms, self.conversation_id)
+    }
+}
 
-    Ok(Sse::new(combined).keep_alive(
-        KeepAlive::new()
-            .interval(Duration::from_secs(20))
-            .text(json!({"type": "ping"}).to_string()),
-    ))
+/// Events worth a badge outside the open conversation; mentions and unread counts only reach
+/// the user they belong to.
+fn is_user_stream_event(event: &ConversationStreamEvent, user_id: Uuid) -> bool {
+    match event {
+        ConversationStreamEvent::ThreadNew { .. }
+        | ConversationStreamEvent::MembershipChanged { .. } => true,
+        ConversationStreamEvent::MessageMention { payload } => payload.user_id == user_id,
+        ConversationStreamEvent::UnreadUpdate { payload } => payload.user_id == user_id,
+        _ => false,
+    }
+}
+
+fn format_user_event_id(conversation_id: Uuid, envelope: &EventEnvelope) -> String {
+    format!("{conversation_id}/{}", envelope.event_id())
+}
+
+fn parse_user_event_id(raw: &str) -> Option<UserStreamCursor> {
+    let (conversation_raw, event_raw) = raw.split_once('/')?;
+    Some(UserStreamCursor {
+        conversation_id: Uuid::parse_str(conversation_raw).ok()?,
+        inner: parse_last_event_id(event_raw)?,
+    })
+}
+
+const fn build_user_cursor(
+    header_cursor: Option<UserStreamCursor>,
+    since_override: Option<i64>,
+) -> Option<UserStreamCursor> {
+    match (header_cursor, since_override) {
+        (None, None) => None,
+        (Some(mut cursor), Some(since)) => {
+            cursor.inner.timestamp_ms = since;
+            Some(cursor)
+        }
+        (Some(cursor), None) => Some(cursor),
+        (None, Some(since)) => Some(UserStreamCursor {
+            conversation_id: Uuid::nil(),
+            inner: ReplayCursor {
+                _root_id: None,
+                message_id: None,
+                chunk_index: None,
+                timestamp_ms: since,
+            },
+        }),
+    }
+}
+
+fn user_sse_event(conversation_id: Uuid, envelope: &EventEnvelope) -> Option<Event> {
+    let name = event_name(&envelope.event);
+    let data = serde_json::to_string(&UserStreamEvent {
+        conversation_id,
+        event: envelope.event.clone(),
+    })
+    .ok()?;
+    Some(
+        Event::default()
+            .event(name)
+            .data(data)
+            .id(format_user_event_id(conversation_id, envelope)),
+    )
 }
 
 fn convert_event(envelope: &EventEnvelope) -> Option<Event> {
@@ -566,10 +1096,13 @@ const fn event_name(event: &ConversationStreamEvent) -> &'static str {
         ConversationStreamEvent::ThreadActivity { .. } => "thread.activity",
         ConversationStreamEvent::MessageDelta { .. } => "message.delta",
         ConversationStreamEvent::MessageDone { .. } => "message.done",
+        ConversationStreamEvent::MessageEdited { .. } => "message.edited",
+        ConversationStreamEvent::MessageMention { .. } => "message.mention",
         ConversationStreamEvent::PresenceUpdate { .. } => "presence.update",
         ConversationStreamEvent::TypingUpdate { .. } => "typing.update",
         ConversationStreamEvent::UnreadUpdate { .. } => "unread.update",
         ConversationStreamEvent::MembershipChanged { .. } => "membership.changed",
+        ConversationStreamEvent::ConversationLifecycle { .. } => "conversation.lifecycle",
         ConversationStreamEvent::Error { .. } => "error",
     }
 }
@@ -618,6 +1151,8 @@ const fn event_root_id(event: &ConversationStreamEvent) -> Option<Uuid> {
         ConversationStreamEvent::ThreadActivity { payload } => Some(payload.root_id),
         ConversationStreamEvent::MessageDelta { payload } => Some(payload.root_id),
         ConversationStreamEvent::MessageDone { payload } => Some(payload.root_id),
+        ConversationStreamEvent::MessageEdited { payload } => Some(payload.root_id),
+        ConversationStreamEvent::MessageMention { payload } => Some(payload.root_id),
         ConversationStreamEvent::TypingUpdate { payload } => Some(payload.root_id),
         ConversationStreamEvent::UnreadUpdate { payload } => Some(payload.root_id),
         _ => None,
@@ -727,13 +1262,16 @@ fn require_pool(state: &AppState) -> AppResult<sqlx::PgPool> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::test_support::TestDatabase;
     use anyhow::Result;
     use async_trait::async_trait;
     use chrono::{DateTime, Utc};
     use shared::models::{
-        ChatDelta, ChatDeltaChoice, ChatDeltaChunk, MessageDoneEvent, MessageRole,
-        ThreadActivityEvent, ThreadNewEvent, ThreadSummary, Timestamp, UsageBreakdown,
+        ChatDelta, ChatDeltaChoice, ChatDeltaChunk, MembershipChangeAction, MembershipChangedEvent,
+        MessageDoneEvent, MessageMentionEvent, MessageRole, ThreadActivityEvent, ThreadNewEvent,
+        ThreadSummary, Timestamp, UnreadUpdateEvent, UsageBreakdown,
     };
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
     use std::{collections::HashMap, sync::Mutex, time::Duration};
 
     #[derive(Clone, Default)]
@@ -875,6 +1413,355 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
+    struct RecordingRelay {
+        published: Mutex<Vec<RelayedEvent>>,
+    }
+
+    #[async_trait]
+    impl StreamRelay for RecordingRelay {
+        async fn publish(
+            &self,
+            conversation_id: Uuid,
+            record: &StreamEventRecord,
+            timestamp_ms: i64,
+            chunk_index: Option<i32>,
+        ) -> Result<()> {
+            self.published.lock().unwrap().push(RelayedEvent {
+                origin: Uuid::nil(),
+                conversation_id,
+                sequence: record.sequence,
+                event_id: record.event_id.clone(),
+                timestamp_ms,
+                chunk_index,
+                payload: Some(record.payload.clone()),
+            });
+            Ok(())
+        }
+
+        async fn subscribe(&self) -> Result<BoxStream<'static, RelayedEvent>> {
+            Ok(stream::empty().boxed())
+        }
+    }
+
+    #[tokio::test]
+    async fn relayed_events_reach_other_instances_once() {
+        let origin_relay = Arc::new(RecordingRelay::default());
+        let peer_relay = Arc::new(RecordingRelay::default());
+        let origin = StreamHub::new(64, None, None).with_relay(origin_relay.clone());
+        let peer = StreamHub::new(64, None, None).with_relay(peer_relay.clone());
+        let conversation = Uuid::new_v4();
+        let root_id = Uuid::new_v4();
+        let message_id = Uuid::new_v4();
+
+        origin
+            .publish_chunk_event(
+                conversation,
+                sample_delta(message_id, root_id, conversation),
+                4,
+            )
+            .await;
+
+        let relayed = origin_relay.published.lock().unwrap().clone();
+        assert_eq!(relayed.len(), 1);
+        peer.publish_relayed(relayed[0].clone()).await;
+        peer.publish_relayed(relayed[0].clone()).await;
+
+        let (_, sent) = origin.subscribe(conversation, None).await;
+        let (_, received) = peer.subscribe(conversation, None).await;
+        assert_eq!(received.len(), 1, "duplicate notifications are dropped");
+        assert_eq!(received[0].event_id(), sent[0].event_id());
+        assert_eq!(received[0].chunk_index(), Some(4));
+        assert!(
+            peer_relay.published.lock().unwrap().is_empty(),
+            "relayed events are not sent back out"
+        );
+
+        peer.publish(conversation, sample_done(message_id, root_id, conversation))
+            .await;
+        let (_, received) = peer.subscribe(conversation, None).await;
+        assert!(received[1].timestamp_ms > received[0].timestamp_ms);
+    }
+
+    fn unread(user_id: Uuid, root_id: Uuid, unread: i64) -> ConversationStreamEvent {
+        ConversationStreamEvent::UnreadUpdate {
+            payload: UnreadUpdateEvent {
+                user_id,
+                root_id,
+                unread,
+            },
+        }
+    }
+
+    fn membership(
+        conversation_id: Uuid,
+        user_id: Uuid,
+        action: MembershipChangeAction,
+    ) -> ConversationStreamEvent {
+        ConversationStreamEvent::MembershipChanged {
+            payload: MembershipChangedEvent {
+                conversation_id,
+                user_id,
+                role: None,
+                action,
+            },
+        }
+    }
+
+    fn unconnected_service() -> ChatService {
+        ChatService::new(PgPoolOptions::new().connect_lazy_with(PgConnectOptions::new()))
+    }
+
+    async fn next_user_event(
+        events: &mut BoxStream<'static, (Uuid, EventEnvelope)>,
+    ) -> Result<Option<(Uuid, EventEnvelope)>, tokio::time::error::Elapsed> {
+        tokio::time::timeout(Duration::from_secs(2), events.next()).await
+    }
+
+    fn mention(conversation_id: Uuid, user_id: Uuid) -> ConversationStreamEvent {
+        ConversationStreamEvent::MessageMention {
+            payload: MessageMentionEvent {
+                conversation_id,
+                root_id: Uuid::new_v4(),
+                message_id: Uuid::new_v4(),
+                author_id: Uuid::new_v4(),
+                user_id,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn deferred_events_reach_the_next_subscriber_live() {
+        let hub = StreamHub::new(64, None, None);
+        let conversation = Uuid::new_v4();
+        let (message_id, root_id) = (Uuid::new_v4(), Uuid::new_v4());
+        hub.publish_on_subscribe(
+            conversation,
+            sample_done(message_id, root_id, conversation),
+            Some(4),
+        )
+        .await;
+
+        let (mut receiver, replay) = hub.subscribe(conversation, None).await;
+        assert!(replay.is_empty());
+        let envelope = receiver.try_recv().expect("deferred event published");
+        assert_eq!(envelope.message_id(), Some(message_id));
+        assert_eq!(envelope.chunk_index(), Some(4));
+
+        let (_, replay) = hub.subscribe(conversation, None).await;
+        assert_eq!(replay.len(), 1, "published once, then kept in history");
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn deferred_events_go_out_at_once_while_someone_listens() {
+        let hub = StreamHub::new(64, None, None);
+        let conversation = Uuid::new_v4();
+        let (mut receiver, _) = hub.subscribe(conversation, None).await;
+
+        let (message_id, root_id) = (Uuid::new_v4(), Uuid::new_v4());
+        hub.publish_on_subscribe(
+            conversation,
+            sample_done(message_id, root_id, conversation),
+            Some(2),
+        )
+        .await;
+
+        let envelope = receiver
+            .try_recv()
+            .expect("published without a new subscriber");
+        assert_eq!(envelope.message_id(), Some(message_id));
+        assert_eq!(envelope.chunk_index(), Some(2));
+        assert!(hub.deferred.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn closing_the_hub_ends_streams_with_a_reconnect_hint() {
+        let hub = StreamHub::new(64, None, None);
+
+        let finished = until_closed(&hub, stream::iter([Event::default()]))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            finished.len(),
+            1,
+            "no farewell when the stream ends by itself"
+        );
+
+        let open = tokio::spawn(until_closed(&hub, stream::pending::<Event>()).count());
+        hub.close_streams();
+        let frames = tokio::time::timeout(Duration::from_secs(1), open)
+            .await
+            .expect("stream closed")
+            .unwrap();
+        assert_eq!(frames, 1, "only the reconnect hint follows the close");
+    }
+
+    #[tokio::test]
+    async fn user_stream_merges_conversations_and_resumes_across_them() {
+        let hub = StreamHub::new(64, None, None);
+        let user = Uuid::new_v4();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let root_id = Uuid::new_v4();
+
+        // Distinct milliseconds keep the cross-conversation order independent of the ids.
+        let tick = || tokio::time::sleep(Duration::from_millis(3));
+        hub.publish(first, unread(user, root_id, 1)).await;
+        tick().await;
+        hub.publish(second, mention(second, Uuid::new_v4())).await;
+        hub.publish(second, unread(Uuid::new_v4(), root_id, 7))
+            .await;
+        hub.publish(second, mention(second, user)).await;
+        tick().await;
+        hub.publish(
+            first,
+            ConversationStreamEvent::ThreadActivity {
+                payload: ThreadActivityEvent {
+                    root_id,
+                    last_activity_at: Timestamp(Utc::now()),
+                },
+            },
+        )
+        .await;
+        hub.publish(first, unread(user, root_id, 2)).await;
+
+        let since_start = build_user_cursor(None, Some(0));
+        let replay: Vec<_> = follow_user(
+            &hub,
+            unconnected_service(),
+            user,
+            &[first, second],
+            since_start,
+        )
+        .await
+        .take(3)
+        .collect()
+        .await;
+        let names: Vec<_> = replay
+            .iter()
+            .map(|(conversation, envelope)| (*conversation, event_name(&envelope.event)))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (first, "unread.update"),
+                (second, "message.mention"),
+                (first, "unread.update"),
+            ],
+            "other users' mentions and unread counts and thread activity stay out of the user stream"
+        );
+
+        let resume_id = format_user_event_id(replay[1].0, &replay[1].1);
+        let cursor = parse_user_event_id(&resume_id).expect("user event id parses");
+        let mut resumed = follow_user(
+            &hub,
+            unconnected_service(),
+            user,
+            &[first, second],
+            Some(cursor),
+        )
+        .await;
+        let (conversation, envelope) = resumed.next().await.expect("event after cursor");
+        assert_eq!(conversation, first);
+        assert!(matches!(
+            envelope.event,
+            ConversationStreamEvent::UnreadUpdate { payload } if payload.unread == 2
+        ));
+
+        hub.publish(second, unread(user, root_id, 5)).await;
+        let (conversation, _) = resumed.next().await.expect("live event");
+        assert_eq!(conversation, second);
+    }
+
+    #[tokio::test]
+    async fn user_stream_follows_conversations_joined_and_left_after_connecting() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("stream_owner").await;
+        let user = db.create_user("stream_member").await;
+        let conversation = db.create_conversation(owner, "Joined later").await;
+        let root_id = Uuid::new_v4();
+
+        let hub = StreamHub::new(64, None, None);
+        let service = ChatService::new(db.pool.clone());
+        let conversations = service.active_conversations(user).await.unwrap();
+        assert!(conversations.is_empty());
+        let mut events = follow_user(&hub, service, user, &conversations, None).await;
+
+        let mut tx = db.begin_as(owner).await;
+        sqlx::query("SELECT rustygpt.sp_add_participant($1, $2, 'member')")
+            .bind(conversation)
+            .bind(user)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        hub.publish(
+            conversation,
+            membership(conversation, user, MembershipChangeAction::Added),
+        )
+        .await;
+        hub.publish(conversation, unread(user, root_id, 1)).await;
+
+        let (joined, added) = next_user_event(&mut events)
+            .await
+            .unwrap()
+            .expect("membership event");
+        assert_eq!(joined, conversation);
+        assert_eq!(event_name(&added.event), "membership.changed");
+        let (_, update) = next_user_event(&mut events)
+            .await
+            .unwrap()
+            .expect("unread update");
+        assert!(matches!(
+            update.event,
+            ConversationStreamEvent::UnreadUpdate { payload } if payload.unread == 1
+        ));
+
+        let mut tx = db.begin_as(owner).await;
+        sqlx::query("SELECT rustygpt.sp_remove_participant($1, $2)")
+            .bind(conversation)
+            .bind(user)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        hub.publish(
+            conversation,
+            membership(conversation, user, MembershipChangeAction::Removed),
+        )
+        .await;
+        let (_, removed) = next_user_event(&mut events)
+            .await
+            .unwrap()
+            .expect("removal event");
+        assert_eq!(event_name(&removed.event), "membership.changed");
+
+        // Let the stream re-read the user's conversations before anything else is sent.
+        assert!(next_user_event(&mut events).await.is_err());
+        hub.publish(conversation, unread(user, root_id, 2)).await;
+        assert!(
+            next_user_event(&mut events).await.is_err(),
+            "a conversation the user left is no longer followed"
+        );
+
+        drop(events);
+        db.destroy().await;
+    }
+
+    #[test]
+    fn user_event_ids_need_a_conversation_prefix() {
+        assert!(parse_user_event_id("not-a-uuid/x").is_none());
+        assert!(parse_user_event_id(&format_event_id(None, None, None, 5)).is_none());
+
+        let conversation = Uuid::new_v4();
+        let raw = format!("{conversation}/{}", format_event_id(None, None, None, 5));
+        let cursor = parse_user_event_id(&raw).expect("valid id");
+        assert_eq!(cursor.conversation_id, conversation);
+        assert_eq!(cursor.inner.timestamp_ms, 5);
+    }
+
     fn sample_delta(
         message_id: Uuid,
         root_id: Uuid,
@@ -983,10 +1870,13 @@ mod tests {
                 ConversationStreamEvent::ThreadActivity { .. } => "thread.activity",
                 ConversationStreamEvent::MessageDelta { .. } => "message.delta",
                 ConversationStreamEvent::MessageDone { .. } => "message.done",
+                ConversationStreamEvent::MessageEdited { .. } => "message.edited",
+                ConversationStreamEvent::MessageMention { .. } => "message.mention",
                 ConversationStreamEvent::PresenceUpdate { .. } => "presence.update",
                 ConversationStreamEvent::TypingUpdate { .. } => "typing.update",
                 ConversationStreamEvent::UnreadUpdate { .. } => "unread.update",
                 ConversationStreamEvent::MembershipChanged { .. } => "membership.changed",
+                ConversationStreamEvent::ConversationLifecycle { .. } => "conversation.lifecycle",
                 ConversationStreamEvent::Error { .. } => "error",
             })
             .collect();
@@ -1060,10 +1950,13 @@ mod tests {
                 ConversationStreamEvent::ThreadActivity { .. } => "thread.activity",
                 ConversationStreamEvent::MessageDelta { .. } => "message.delta",
                 ConversationStreamEvent::MessageDone { .. } => "message.done",
+                ConversationStreamEvent::MessageEdited { .. } => "message.edited",
+                ConversationStreamEvent::MessageMention { .. } => "message.mention",
                 ConversationStreamEvent::PresenceUpdate { .. } => "presence.update",
                 ConversationStreamEvent::TypingUpdate { .. } => "typing.update",
                 ConversationStreamEvent::UnreadUpdate { .. } => "unread.update",
                 ConversationStreamEvent::MembershipChanged { .. } => "membership.changed",
+                ConversationStreamEvent::ConversationLifecycle { .. } => "conversation.lifecycle",
                 ConversationStreamEvent::Error { .. } => "error",
             })
             .collect();
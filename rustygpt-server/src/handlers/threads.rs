@@ -15,13 +15,19 @@ use uuid::Uuid;
 
 use crate::{
     app_state::AppState,
-    handlers::streaming::SharedStreamHub,
+    handlers::streaming::{SharedStreamHub, StreamHub},
     http::error::{ApiError, AppResult},
-    middleware::request_context::RequestContext,
+    middleware::{rate_limit::AppliedRateLimitProfile, request_context::RequestContext},
     services::{
         assistant_service::{AssistantRuntime, finish_reason_to_string},
-        chat_service::{ChatService, ChatServiceError, ThreadSummaryWithConversation},
+        chat_service::{
+            ChatService, ChatServiceError, GenerationStatus, ThreadSummaryWithConversation,
+        },
+        organization_service::OrganizationService,
+        preferences_service::PreferencesService,
+        quota_service::QuotaService,
         stream_supervisor::{SharedStreamSupervisor, StreamSession, StreamStopReason},
+        usage_service::{UsageEntry, UsageService},
     },
 };
 use futures::StreamExt;
@@ -33,12 +39,14 @@ use shared::{
         types::{LLMConfig, LLMRequest, TokenUsage},
     },
     models::{
-        ChatDelta, ChatDeltaChoice, ChatDeltaChunk, ConversationStreamEvent, MarkThreadReadRequest,
-        MessageChunk, MessageDeleteRequest, MessageDoneEvent, MessageEditRequest, MessageRole,
-        MessageView, PostRootMessageRequest, PresenceHeartbeatRequest, PresenceStatus,
-        PresenceUpdate, ReplyMessageRequest, ReplyMessageResponse, StreamErrorEvent,
-        ThreadActivityEvent, ThreadNewEvent, ThreadTreeResponse, Timestamp, TypingRequest,
-        TypingUpdate, UnreadUpdateEvent, UsageBreakdown,
+        ChatDelta, ChatDeltaChoice, ChatDeltaChunk, ConversationStreamEvent, ForkMessageRequest,
+        MarkThreadReadRequest, MessageChunk, MessageDeleteRequest, MessageDoneEvent,
+        MessageEditRequest, MessageEditedEvent, MessageMentionEvent, MessageRevisionsResponse,
+        MessageRole, MessageView, PostRootMessageRequest, PresenceHeartbeatRequest, PresenceStatus,
+        PresenceUpdate, RegenerateMessageRequest, ReplyMessageRequest, ReplyMessageResponse,
+        StreamErrorEvent, ThreadActivityEvent, ThreadNewEvent, ThreadTreeResponse, Timestamp,
+        TypingRequest, TypingUpdate, UnreadUpdateEvent, UsageSource, UserPreferences,
+        mentioned_usernames,
     },
 };
 
@@ -52,7 +60,16 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/api/messages/{message_id}/delete", post(delete_message))
         .route("/api/messages/{message_id}/restore", post(restore_message))
         .route("/api/messages/{message_id}/edit", post(edit_message))
+        .route(
+            "/api/messages/{message_id}/revisions",
+            get(message_revisions),
+        )
         .route("/api/messages/{message_id}/cancel", post(cancel_message))
+        .route("/api/messages/{message_id}/fork", post(fork_message))
+        .route(
+            "/api/messages/{message_id}/regenerate",
+            post(regenerate_message),
+        )
         .route("/api/typing", post(set_typing))
         .route("/api/presence/heartbeat", post(presence_heartbeat))
 }
@@ -75,6 +92,51 @@ struct CancelResponse {
     status: &'static str,
 }
 
+#[derive(Debug, Clone, Default)]
+struct GenerationOverrides {
+    model: Option<String>,
+    temperature: Option<f32>,
+    system_prompt: Option<String>,
+}
+
+impl GenerationOverrides {
+    /// Fills whatever the request left unset from the user's saved preferences.
+    fn with_preferences(self, preferences: UserPreferences) -> Self {
+        Self {
+            model: self.model.or(preferences.default_model),
+            temperature: self.temperature.or(preferences.temperature),
+            system_prompt: self.system_prompt.or(preferences.system_prompt),
+        }
+    }
+}
+
+impl TryFrom<RegenerateMessageRequest> for GenerationOverrides {
+    type Error = ApiError;
+
+    fn try_from(request: RegenerateMessageRequest) -> Result<Self, Self::Error> {
+        let model = request
+            .model
+            .map(|model| model.trim().to_string())
+            .filter(|model| !model.is_empty());
+
+        if let Some(temperature) = request.temperature
+            && !(0.0..=2.0).contains(&temperature)
+        {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "RGP.V1.REGENERATE_TEMPERATURE",
+                "temperature must be between 0.0 and 2.0",
+            ));
+        }
+
+        Ok(Self {
+            model,
+            temperature: request.temperature,
+            system_prompt: None,
+        })
+    }
+}
+
 #[instrument(skip(app_state, context, query))]
 async fn thread_tree(
     Extension(app_state): Extension<Arc<AppState>>,
@@ -98,6 +160,7 @@ async fn post_root(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(context): Extension<RequestContext>,
     Extension(hub): Extension<SharedStreamHub>,
+    rate_profile: Option<Extension<AppliedRateLimitProfile>>,
     Path(conversation_id): Path<Uuid>,
     Json(payload): Json<PostRootMessageRequest>,
 ) -> AppResult<impl IntoResponse> {
@@ -107,6 +170,17 @@ async fn post_root(
     let assistant = require_assistant(&app_state)?;
 
     let PostRootMessageRequest { content, role } = payload;
+    if should_spawn_assistant(role) {
+        ensure_generation_allowed(
+            app_state.streams.as_ref(),
+            &pool,
+            user_id,
+            conversation_id,
+            rate_profile.as_deref(),
+            None,
+        )
+        .await?;
+    }
     let request = PostRootMessageRequest {
         content: content.clone(),
         role,
@@ -169,12 +243,24 @@ async fn post_root(
 
     let unread_event = ConversationStreamEvent::UnreadUpdate {
         payload: UnreadUpdateEvent {
+            user_id,
             root_id: response.root_id,
             unread,
         },
     };
     hub.publish(conversation_id, unread_event).await;
 
+    publish_mentions(
+        &service,
+        &hub,
+        conversation_id,
+        response.root_id,
+        response.message_id,
+        user_id,
+        &content,
+    )
+    .await;
+
     if should_spawn_assistant(role) {
         spawn_assistant_reply(
             pool,
@@ -184,6 +270,7 @@ async fn post_root(
             user_id,
             response.message_id,
             content,
+            GenerationOverrides::default(),
         );
     }
 
@@ -195,6 +282,7 @@ async fn reply_message(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(context): Extension<RequestContext>,
     Extension(hub): Extension<SharedStreamHub>,
+    rate_profile: Option<Extension<AppliedRateLimitProfile>>,
     Path(parent_id): Path<Uuid>,
     Json(payload): Json<ReplyMessageRequest>,
 ) -> AppResult<impl IntoResponse> {
@@ -204,6 +292,18 @@ async fn reply_message(
     let assistant = require_assistant(&app_state)?;
 
     let ReplyMessageRequest { content, role } = payload;
+    if should_spawn_assistant(role) {
+        let parent = service.get_message(user_id, parent_id).await?;
+        ensure_generation_allowed(
+            app_state.streams.as_ref(),
+            &pool,
+            user_id,
+            parent.conversation_id,
+            rate_profile.as_deref(),
+            None,
+        )
+        .await?;
+    }
     let request = ReplyMessageRequest {
         content: content.clone(),
         role,
@@ -261,12 +361,24 @@ async fn reply_message(
 
     let unread_event = ConversationStreamEvent::UnreadUpdate {
         payload: UnreadUpdateEvent {
+            user_id,
             root_id: response.root_id,
             unread,
         },
     };
     hub.publish(conversation_id, unread_event).await;
 
+    publish_mentions(
+        &service,
+        &hub,
+        conversation_id,
+        response.root_id,
+        response.message_id,
+        user_id,
+        &content,
+    )
+    .await;
+
     if should_spawn_assistant(role) {
         spawn_assistant_reply(
             pool,
@@ -276,6 +388,7 @@ async fn reply_message(
             user_id,
             response.message_id,
             content,
+            GenerationOverrides::default(),
         );
     }
 
@@ -310,16 +423,58 @@ async fn mark_thread_read(
 ) -> AppResult<impl IntoResponse> {
     let actor = require_user(&context)?;
     let pool = require_pool(&app_state)?;
-    let service = ChatService::new(pool.clone());
+    let service = ChatService::new(pool);
+
+    apply_mark_read(&service, &hub, actor, root_id, payload.path.as_deref()).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
 
+/// Publishes a `message.mention` for every participant the message names with `@username`.
+/// The message is already stored, so failures are logged rather than returned.
+async fn publish_mentions(
+    service: &ChatService,
+    hub: &StreamHub,
+    conversation_id: Uuid,
+    root_id: Uuid,
+    message_id: Uuid,
+    author_id: Uuid,
+    content: &str,
+) {
+    let usernames = mentioned_usernames(content);
+    let mentioned = match service.mentioned_users(conversation_id, &usernames).await {
+        Ok(users) => users,
+        Err(err) => {
+            warn!(%message_id, error = %err, "failed to resolve mentions");
+            return;
+        }
+    };
+
+    for user_id in mentioned.into_iter().filter(|user| *user != author_id) {
+        let event = ConversationStreamEvent::MessageMention {
+            payload: MessageMentionEvent {
+                conversation_id,
+                root_id,
+                message_id,
+                author_id,
+                user_id,
+            },
+        };
+        hub.publish(conversation_id, event).await;
+    }
+}
+
+/// Marks a thread read and publishes the new unread count. Shared with the stream socket.
+pub(crate) async fn apply_mark_read(
+    service: &ChatService,
+    hub: &StreamHub,
+    actor: Uuid,
+    root_id: Uuid,
+    path: Option<&str>,
+) -> AppResult<()> {
     let root_message = service.get_message(actor, root_id).await?;
     service
-        .mark_thread_read(
-            actor,
-            root_message.conversation_id,
-            root_id,
-            payload.path.as_deref(),
-        )
+        .mark_thread_read(actor, root_message.conversation_id, root_id, path)
         .await?;
 
     let summaries = service
@@ -331,11 +486,14 @@ async fn mark_thread_read(
         .map_or(0, |item| item.unread);
 
     let event = ConversationStreamEvent::UnreadUpdate {
-        payload: UnreadUpdateEvent { root_id, unread },
+        payload: UnreadUpdateEvent {
+            user_id: actor,
+            root_id,
+            unread,
+        },
     };
     hub.publish(root_message.conversation_id, event).await;
-
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
 }
 
 #[instrument(skip(app_state, context, payload))]
@@ -366,6 +524,22 @@ async fn cancel_message(
     let pool = require_pool(&app_state)?;
     let service = ChatService::new(pool);
 
+    let status = cancel_generation(&app_state, &service, actor, message_id).await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(CancelResponse { message_id, status }),
+    ))
+}
+
+/// Stops an in-flight assistant reply, returning how its stream ended. Shared with the stream
+/// socket.
+pub(crate) async fn cancel_generation(
+    app_state: &AppState,
+    service: &ChatService,
+    actor: Uuid,
+    message_id: Uuid,
+) -> AppResult<&'static str> {
     let message = service.get_message(actor, message_id).await?;
     if message.role != MessageRole::Assistant {
         return Err(ApiError::new(
@@ -380,16 +554,55 @@ async fn cancel_message(
             StreamStopReason::Cancelled => "cancelled",
             StreamStopReason::TimedOut => "timed_out",
             StreamStopReason::Completed => "completed",
+            StreamStopReason::Interrupted => "interrupted",
             StreamStopReason::None => "not_tracked",
         }
     } else {
         "not_tracked"
     };
+    Ok(status)
+}
 
-    Ok((
-        StatusCode::ACCEPTED,
-        Json(CancelResponse { message_id, status }),
-    ))
+#[instrument(skip(app_state, context, hub, payload))]
+async fn regenerate_message(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    rate_profile: Option<Extension<AppliedRateLimitProfile>>,
+    Path(message_id): Path<Uuid>,
+    Json(payload): Json<RegenerateMessageRequest>,
+) -> AppResult<impl IntoResponse> {
+    let actor = require_user(&context)?;
+    let pool = require_pool(&app_state)?;
+    let service = ChatService::new(pool.clone());
+    let assistant = require_assistant(&app_state)?;
+
+    let overrides = GenerationOverrides::try_from(payload)?;
+    let message = service.get_message(actor, message_id).await?;
+    ensure_generation_allowed(
+        app_state.streams.as_ref(),
+        &pool,
+        actor,
+        message.conversation_id,
+        rate_profile.as_deref(),
+        overrides.model.as_deref(),
+    )
+    .await?;
+    let response = service.prepare_regenerate(actor, message_id).await?;
+    let parent = service.get_message(actor, response.parent_id).await?;
+
+    spawn_assistant_reply(
+        pool,
+        hub.clone(),
+        assistant,
+        app_state.streams.clone(),
+        actor,
+        response.parent_id,
+        parent.content,
+        overrides,
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(response)))
 }
 
 #[instrument(skip(app_state, context))]
@@ -406,16 +619,31 @@ async fn restore_message(
     Ok(StatusCode::NO_CONTENT)
 }
 
-#[instrument(skip(app_state, context, payload))]
+#[instrument(skip(app_state, context, hub, payload))]
 async fn edit_message(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    rate_profile: Option<Extension<AppliedRateLimitProfile>>,
     Path(message_id): Path<Uuid>,
     Json(payload): Json<MessageEditRequest>,
 ) -> AppResult<impl IntoResponse> {
     let actor = require_user(&context)?;
     let pool = require_pool(&app_state)?;
-    let service = ChatService::new(pool);
+    let service = ChatService::new(pool.clone());
+
+    if payload.regenerate_reply {
+        let message = service.get_message(actor, message_id).await?;
+        ensure_generation_allowed(
+            app_state.streams.as_ref(),
+            &pool,
+            actor,
+            message.conversation_id,
+            rate_profile.as_deref(),
+            None,
+        )
+        .await?;
+    }
 
     service
         .edit_message(
@@ -426,9 +654,88 @@ async fn edit_message(
         )
         .await?;
 
+    let message = service.get_message(actor, message_id).await?;
+
+    let edited = ConversationStreamEvent::MessageEdited {
+        payload: MessageEditedEvent {
+            message_id,
+            root_id: message.root_id,
+            conversation_id: message.conversation_id,
+            content: message.content.clone(),
+            edited_by: actor,
+            edited_at: Timestamp(Utc::now()),
+            reason: payload.reason,
+        },
+    };
+    hub.publish(message.conversation_id, edited).await;
+
+    if payload.regenerate_reply && message.role == MessageRole::User {
+        let assistant = require_assistant(&app_state)?;
+        spawn_assistant_reply(
+            pool,
+            hub.clone(),
+            assistant,
+            app_state.streams.clone(),
+            actor,
+            message_id,
+            message.content,
+            GenerationOverrides::default(),
+        );
+    }
+
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[instrument(skip(app_state, context, hub, payload))]
+async fn fork_message(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Extension(hub): Extension<SharedStreamHub>,
+    Path(message_id): Path<Uuid>,
+    Json(payload): Json<ForkMessageRequest>,
+) -> AppResult<impl IntoResponse> {
+    let actor = require_user(&context)?;
+    let pool = require_pool(&app_state)?;
+    let service = ChatService::new(pool);
+
+    let response = service.fork_messages(actor, message_id, payload).await?;
+
+    let summary = service.get_thread_summary(actor, response.root_id).await?;
+
+    let thread_new = ConversationStreamEvent::ThreadNew {
+        payload: ThreadNewEvent {
+            conversation_id: response.conversation_id,
+            root_id: response.root_id,
+            summary: summary.summary.clone(),
+        },
+    };
+    hub.publish(response.conversation_id, thread_new).await;
+
+    let activity = ConversationStreamEvent::ThreadActivity {
+        payload: ThreadActivityEvent {
+            root_id: response.root_id,
+            last_activity_at: summary.summary.last_activity_at.clone(),
+        },
+    };
+    hub.publish(response.conversation_id, activity).await;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+#[instrument(skip(app_state, context))]
+async fn message_revisions(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(message_id): Path<Uuid>,
+) -> AppResult<Json<MessageRevisionsResponse>> {
+    let actor = require_user(&context)?;
+    let pool = require_pool(&app_state)?;
+    let service = ChatService::new(pool);
+
+    let revisions = service.list_message_revisions(actor, message_id).await?;
+    Ok(Json(revisions))
+}
+
 #[instrument(skip(app_state, context, hub, payload))]
 async fn set_typing(
     Extension(app_state): Extension<Arc<AppState>>,
@@ -440,6 +747,18 @@ async fn set_typing(
     let pool = require_pool(&app_state)?;
     let service = ChatService::new(pool);
 
+    apply_typing(&service, &hub, actor, &payload).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Records the typing state and announces it while it lasts. Shared with the stream socket.
+pub(crate) async fn apply_typing(
+    service: &ChatService,
+    hub: &StreamHub,
+    actor: Uuid,
+    payload: &TypingRequest,
+) -> AppResult<()> {
     service
         .set_typing(
             actor,
@@ -461,8 +780,7 @@ async fn set_typing(
         };
         hub.publish(payload.conversation_id, event).await;
     }
-
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
 }
 
 #[instrument(skip(app_state, context, hub, payload))]
@@ -476,9 +794,20 @@ async fn presence_heartbeat(
     let pool = require_pool(&app_state)?;
     let service = ChatService::new(pool);
 
-    let status = payload
-        .status
-        .unwrap_or(shared::models::PresenceStatus::Online);
+    apply_heartbeat(&service, &hub, actor, payload.status).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Refreshes presence and announces it in every conversation the user can see. Shared with the
+/// stream socket.
+pub(crate) async fn apply_heartbeat(
+    service: &ChatService,
+    hub: &StreamHub,
+    actor: Uuid,
+    status: Option<PresenceStatus>,
+) -> AppResult<()> {
+    let status = status.unwrap_or(PresenceStatus::Online);
     service.heartbeat(actor, Some(status)).await?;
 
     let last_seen = Timestamp(Utc::now());
@@ -494,8 +823,7 @@ async fn presence_heartbeat(
     for conversation_id in conversations {
         hub.publish(conversation_id, event.clone()).await;
     }
-
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
 }
 
 const fn should_spawn_assistant(role: Option<MessageRole>) -> bool {
@@ -661,7 +989,7 @@ pub async fn ensure_reply_response(
         }
 
         let created = service
-            .reply_as_assistant(actor, parent_message_id, accumulated.to_string())
+            .start_assistant_reply(actor, parent_message_id, accumulated.to_string())
             .await?;
 
         *resolved_conversation = Some(created.conversation_id);
@@ -681,6 +1009,34 @@ pub async fn ensure_reply_response(
     Ok(false)
 }
 
+/// Lifecycle status for a reply whose stream stopped for `stop_reason`; `failed` is set when
+/// it ended on a timeout or stream error.
+pub const fn generation_status(
+    stop_reason: Option<StreamStopReason>,
+    failed: bool,
+) -> GenerationStatus {
+    match stop_reason {
+        Some(StreamStopReason::Interrupted) => GenerationStatus::Interrupted,
+        _ if failed => GenerationStatus::Failed,
+        _ => GenerationStatus::Complete,
+    }
+}
+
+pub async fn record_generation_end(
+    service: &ChatService,
+    actor: Uuid,
+    message_id: Uuid,
+    status: GenerationStatus,
+    finish_reason: &str,
+) {
+    if let Err(err) = service
+        .finish_generation(actor, message_id, status, finish_reason)
+        .await
+    {
+        warn!(error = %err, "failed to record assistant generation status");
+    }
+}
+
 pub async fn persist_chunk_if_needed(
     service: &ChatService,
     actor: Uuid,
@@ -751,6 +1107,7 @@ pub async fn publish_delta_event(
         .await;
 }
 
+#[allow(clippy::too_many_arguments)] // Tracking: threads-assistant-reply-refactor
 fn spawn_assistant_reply(
     pool: PgPool,
     hub: SharedStreamHub,
@@ -759,6 +1116,7 @@ fn spawn_assistant_reply(
     actor: Uuid,
     parent_message_id: Uuid,
     user_message: String,
+    overrides: GenerationOverrides,
 ) {
     tokio::spawn(async move {
         if let Err(err) = run_assistant_reply(
@@ -769,6 +1127,7 @@ fn spawn_assistant_reply(
             actor,
             parent_message_id,
             user_message,
+            overrides,
         )
         .await
         {
@@ -778,7 +1137,7 @@ fn spawn_assistant_reply(
 }
 
 #[allow(clippy::cognitive_complexity)]
-#[allow(clippy::too_many_lines)] // Tracking: threads-assistant-reply-refactor
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)] // Tracking: threads-assistant-reply-refactor
 async fn run_assistant_reply(
     pool: PgPool,
     hub: SharedStreamHub,
@@ -787,14 +1146,43 @@ async fn run_assistant_reply(
     actor: Uuid,
     parent_message_id: Uuid,
     user_message: String,
+    overrides: GenerationOverrides,
 ) -> Result<(), ChatServiceError> {
-    let service = ChatService::new(pool);
+    // Created first so shutdown waits for (or interrupts) the whole generation, not only
+    // the part after the model starts streaming.
+    let stream_session = supervisor.as_ref().map(|sup| sup.create_session());
+    let usage_ledger = UsageService::new(pool.clone());
+    let preferences = PreferencesService::new(pool.clone())
+        .get(actor)
+        .await
+        .unwrap_or_else(|err| {
+            warn!(error = %err, "failed to load user preferences; using defaults");
+            UserPreferences::default()
+        });
+    let overrides = overrides.with_preferences(preferences);
+    let service = ChatService::new(pool.clone());
 
     let default_config = assistant
         .default_chat_config()
         .map_err(|err| ChatServiceError::Validation(err.to_string()))?;
 
     let parent_message = service.get_message(actor, parent_message_id).await?;
+    let mut overrides = overrides;
+    if let Some(policy) = OrganizationService::new(pool.clone())
+        .conversation_policy(parent_message.conversation_id)
+        .await?
+    {
+        // Explicitly requested models were checked before the reply was accepted; a saved
+        // or server default the organization does not allow falls back to one it does.
+        let preferred = overrides
+            .model
+            .as_deref()
+            .unwrap_or_else(|| assistant.default_model_name());
+        let model = policy.fallback_model(preferred).to_string();
+        if model != preferred {
+            overrides.model = Some(model);
+        }
+    }
     let context_chain = service
         .get_ancestor_chain(actor, parent_message.root_id, &parent_message.path)
         .await?;
@@ -806,6 +1194,7 @@ async fn run_assistant_reply(
         &default_config,
         assistant.default_model_name(),
         &user_message,
+        &overrides,
     );
 
     let assistant_session = assistant
@@ -814,8 +1203,8 @@ async fn run_assistant_reply(
         .map_err(|err| ChatServiceError::Validation(err.to_string()))?;
 
     let mut stream = assistant_session.stream;
+    let usage_meter = assistant_session.usage;
     let persist_chunks = assistant.persist_stream_chunks();
-    let stream_session = supervisor.as_ref().map(|sup| sup.create_session());
 
     let stream_context = AssistantStreamContext {
         service: &service,
@@ -856,6 +1245,9 @@ async fn run_assistant_reply(
             Some(StreamStopReason::TimedOut) => {
                 "Assistant response timed out before completion.".to_string()
             }
+            Some(StreamStopReason::Interrupted) => {
+                "Assistant response interrupted by a server shutdown.".to_string()
+            }
             _ => "I'm sorry, I couldn't generate a response right now.".to_string(),
         };
         let created = service
@@ -891,6 +1283,19 @@ async fn run_assistant_reply(
         stream_error = None;
     }
 
+    if stop_reason == Some(StreamStopReason::Interrupted) {
+        let message = "Assistant generation was interrupted by a server shutdown.".to_string();
+        if !accumulated.is_empty() {
+            accumulated.push_str("\n\n");
+        }
+        let _ = write!(accumulated, "⚠️ {message}");
+        error_event = Some(StreamErrorEvent {
+            code: "server_shutdown".to_string(),
+            message,
+        });
+        stream_error = None;
+    }
+
     if error_event.is_none()
         && let Some(error) = stream_error.take()
     {
@@ -912,14 +1317,23 @@ async fn run_assistant_reply(
         warn!(error = %err, "failed to persist final assistant message content");
     }
 
-    let usage_breakdown = usage.map_or_else(
-        || infer_usage_from_text(assistant_session.prompt_tokens, &accumulated),
-        |usage| token_usage_to_breakdown(&usage, assistant_session.prompt_tokens, &accumulated),
-    );
+    let usage_breakdown = usage_meter.breakdown(usage.as_ref(), &accumulated).await;
+    usage_ledger
+        .record_or_warn(
+            &UsageEntry::from_meter(
+                UsageSource::Thread,
+                &usage_meter,
+                &usage_breakdown,
+                Some(actor),
+            )
+            .for_message(reply_response.conversation_id, reply_response.message_id),
+        )
+        .await;
 
     let finish_reason_value = match stop_reason {
         Some(StreamStopReason::Cancelled) => "cancelled".to_string(),
         Some(StreamStopReason::TimedOut) => "timeout".to_string(),
+        Some(StreamStopReason::Interrupted) => "interrupted".to_string(),
         _ => {
             if error_event.is_some() {
                 "error".to_string()
@@ -928,6 +1342,14 @@ async fn run_assistant_reply(
             }
         }
     };
+    record_generation_end(
+        &service,
+        actor,
+        reply_response.message_id,
+        generation_status(stop_reason, error_event.is_some()),
+        &finish_reason_value,
+    )
+    .await;
 
     let done = ConversationStreamEvent::MessageDone {
         payload: MessageDoneEvent {
@@ -985,58 +1407,55 @@ fn require_pool(state: &AppState) -> AppResult<PgPool> {
     })
 }
 
-fn require_assistant(state: &AppState) -> AppResult<Arc<dyn AssistantRuntime>> {
-    state.assistant.clone().ok_or_else(|| {
-        ApiError::internal_server_error("assistant streaming service not configured")
-    })
-}
-
-pub fn token_usage_to_breakdown(
-    usage: &TokenUsage,
-    prompt_fallback: i64,
-    content: &str,
-) -> UsageBreakdown {
-    let prompt_tokens = if usage.prompt_tokens > 0 {
-        i64::from(usage.prompt_tokens)
-    } else {
-        prompt_fallback
-    };
-
-    let completion_tokens = if usage.completion_tokens > 0 {
-        i64::from(usage.completion_tokens)
-    } else {
-        approximate_text_tokens(content)
-    };
+/// Rejects a request that would start a generation in `conversation_id` while the server
+/// drains for shutdown, once the caller's token budget is spent, or when the conversation's
+/// organization has used up its shared budget, turned the assistant off or does not allow
+/// the requested model.
+async fn ensure_generation_allowed(
+    streams: Option<&SharedStreamSupervisor>,
+    pool: &PgPool,
+    user_id: Uuid,
+    conversation_id: Uuid,
+    rate_profile: Option<&AppliedRateLimitProfile>,
+    requested_model: Option<&str>,
+) -> AppResult<()> {
+    if let Some(streams) = streams {
+        streams.admit()?;
+    }
+    let quotas = QuotaService::new(pool.clone());
+    let profile = rate_profile.map(|profile| profile.0.as_str());
+    if let Some(exceeded) = quotas.check(user_id, profile).await? {
+        return Err(exceeded.into());
+    }
 
-    let total_tokens = if usage.total_tokens > 0 {
-        i64::from(usage.total_tokens)
-    } else {
-        prompt_tokens + completion_tokens
+    let Some(policy) = OrganizationService::new(pool.clone())
+        .conversation_policy(conversation_id)
+        .await?
+    else {
+        return Ok(());
     };
-
-    UsageBreakdown {
-        prompt_tokens,
-        completion_tokens,
-        total_tokens,
+    if !policy.features.assistant {
+        return Err(ApiError::forbidden(
+            "assistant replies are disabled for this organization",
+        ));
     }
-}
-
-pub fn infer_usage_from_text(prompt_tokens: i64, content: &str) -> UsageBreakdown {
-    let completion_tokens = approximate_text_tokens(content);
-    UsageBreakdown {
-        prompt_tokens,
-        completion_tokens,
-        total_tokens: prompt_tokens + completion_tokens,
+    if let Some(model) = requested_model
+        && !policy.allows_model(model)
+    {
+        return Err(ApiError::forbidden(format!(
+            "model {model} is not allowed in this organization"
+        )));
+    }
+    match quotas.check_org(policy.org_id).await? {
+        Some(exceeded) => Err(exceeded.into()),
+        None => Ok(()),
     }
 }
 
-fn approximate_text_tokens(text: &str) -> i64 {
-    let trimmed = text.trim();
-    if trimmed.is_empty() {
-        0
-    } else {
-        i64::try_from(trimmed.split_whitespace().count()).unwrap_or(i64::MAX)
-    }
+fn require_assistant(state: &AppState) -> AppResult<Arc<dyn AssistantRuntime>> {
+    state.assistant.clone().ok_or_else(|| {
+        ApiError::internal_server_error("assistant streaming service not configured")
+    })
 }
 
 fn build_stream_request(
@@ -1044,8 +1463,9 @@ fn build_stream_request(
     default_config: &LLMConfig,
     model_name: &str,
     fallback_user_message: &str,
+    overrides: &GenerationOverrides,
 ) -> LLMRequest {
-    let mut system_segments = Vec::new();
+    let mut system_segments: Vec<String> = overrides.system_prompt.iter().cloned().collect();
     let mut lines = Vec::new();
 
     for message in messages {
@@ -1089,7 +1509,7 @@ fn build_stream_request(
     if let Some(max_tokens) = default_config.max_tokens {
         request = request.with_max_tokens(max_tokens);
     }
-    if let Some(temperature) = default_config.temperature {
+    if let Some(temperature) = overrides.temperature.or(default_config.temperature) {
         request = request.with_temperature(temperature);
     }
     if let Some(top_p) = default_config.top_p {
@@ -1111,6 +1531,7 @@ fn build_stream_request(
         }
     }
 
+    let model_name = overrides.model.as_deref().unwrap_or(model_name);
     request = request.with_metadata("model", json!(model_name));
     request
 }
@@ -1127,4 +1548,98 @@ mod tests {
         assert!(!should_spawn_assistant(Some(MessageRole::System)));
         assert!(!should_spawn_assistant(Some(MessageRole::Tool)));
     }
+
+    #[test]
+    fn generation_status_follows_how_the_stream_stopped() {
+        assert_eq!(
+            generation_status(Some(StreamStopReason::Completed), false),
+            GenerationStatus::Complete
+        );
+        assert_eq!(
+            generation_status(Some(StreamStopReason::Cancelled), false),
+            GenerationStatus::Complete
+        );
+        assert_eq!(
+            generation_status(Some(StreamStopReason::TimedOut), true),
+            GenerationStatus::Failed
+        );
+        assert_eq!(
+            generation_status(Some(StreamStopReason::Interrupted), true),
+            GenerationStatus::Interrupted
+        );
+        assert_eq!(generation_status(None, true), GenerationStatus::Failed);
+    }
+
+    #[test]
+    fn regenerate_overrides_replace_model_and_temperature() {
+        let overrides = GenerationOverrides::try_from(RegenerateMessageRequest {
+            model: Some(" alt-model ".to_string()),
+            temperature: Some(1.2),
+        })
+        .expect("valid overrides");
+        let config = LLMConfig {
+            temperature: Some(0.7),
+            ..LLMConfig::default()
+        };
+
+        let request = build_stream_request(&[], &config, "default-model", "hello", &overrides);
+
+        assert_eq!(request.temperature, Some(1.2));
+        assert_eq!(request.metadata.get("model"), Some(&json!("alt-model")));
+    }
+
+    #[test]
+    fn preferences_fill_unset_overrides_and_lead_the_system_prompt() {
+        let preferences = UserPreferences {
+            default_model: Some("preferred-model".to_string()),
+            temperature: Some(0.2),
+            system_prompt: Some("Answer in French.".to_string()),
+            ..UserPreferences::default()
+        };
+        let overrides = GenerationOverrides {
+            temperature: Some(1.0),
+            ..GenerationOverrides::default()
+        }
+        .with_preferences(preferences);
+        let messages = vec![MessageView {
+            id: Uuid::new_v4(),
+            root_id: Uuid::new_v4(),
+            parent_id: None,
+            conversation_id: Uuid::new_v4(),
+            author_user_id: None,
+            role: MessageRole::System,
+            content: "Conversation rules.".to_string(),
+            path: "1".to_string(),
+            depth: 0,
+            created_at: Timestamp(Utc::now()),
+            alternates: Vec::new(),
+        }];
+
+        let request = build_stream_request(
+            &messages,
+            &LLMConfig::default(),
+            "default-model",
+            "hello",
+            &overrides,
+        );
+
+        assert_eq!(request.temperature, Some(1.0));
+        assert_eq!(
+            request.metadata.get("model"),
+            Some(&json!("preferred-model"))
+        );
+        assert_eq!(
+            request.system_message.as_deref(),
+            Some("Answer in French.\nConversation rules.")
+        );
+    }
+
+    #[test]
+    fn regenerate_rejects_out_of_range_temperature() {
+        let result = GenerationOverrides::try_from(RegenerateMessageRequest {
+            model: None,
+            temperature: Some(3.5),
+        });
+        assert!(result.is_err());
+    }
 }
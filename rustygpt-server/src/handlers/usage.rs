@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Extension, Query},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    handlers::admin_limits::require_admin_context,
+    http::error::{ApiError, AppResult},
+    middleware::request_context::RequestContext,
+    services::usage_service::UsageService,
+};
+use shared::models::{UsageGroupBy, UsageReport};
+
+/// Reports without an explicit range cover the last 30 days, today included.
+const DEFAULT_RANGE_DAYS: u64 = 30;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/api/usage", get(get_my_usage))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UsageQuery {
+    /// First UTC day included in the report (`YYYY-MM-DD`).
+    from: Option<NaiveDate>,
+    /// Last UTC day included in the report (`YYYY-MM-DD`).
+    to: Option<NaiveDate>,
+    group_by: Option<String>,
+    /// `json` (default) or `csv`.
+    format: Option<String>,
+    /// Admin report only: restrict to a single user.
+    user_id: Option<Uuid>,
+}
+
+struct ReportParams {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    group_by: UsageGroupBy,
+    csv: bool,
+}
+
+impl UsageQuery {
+    fn params(&self, today: NaiveDate) -> AppResult<ReportParams> {
+        let group_by = self
+            .group_by
+            .as_deref()
+            .map_or(Ok(UsageGroupBy::default()), str::parse::<UsageGroupBy>)
+            .map_err(|message| {
+                ApiError::new(StatusCode::BAD_REQUEST, "RGP.V1.USAGE_GROUP", message)
+            })?;
+
+        let csv = match self
+            .format
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            None | Some("json") => false,
+            Some("csv") => true,
+            Some(other) => {
+                return Err(ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    "RGP.V1.USAGE_FORMAT",
+                    format!("unsupported usage format: {other}"),
+                ));
+            }
+        };
+
+        let last_day = self.to.unwrap_or(today);
+        let first_day = self.from.unwrap_or_else(|| {
+            last_day
+                .checked_sub_days(Days::new(DEFAULT_RANGE_DAYS - 1))
+                .unwrap_or(last_day)
+        });
+        if first_day > last_day {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "RGP.V1.USAGE_RANGE",
+                "from must not be after to",
+            ));
+        }
+        let end_exclusive = last_day.checked_add_days(Days::new(1)).ok_or_else(|| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "RGP.V1.USAGE_RANGE",
+                "to is out of range",
+            )
+        })?;
+
+        Ok(ReportParams {
+            from: first_day.and_time(NaiveTime::MIN).and_utc(),
+            to: end_exclusive.and_time(NaiveTime::MIN).and_utc(),
+            group_by,
+            csv,
+        })
+    }
+}
+
+#[instrument(skip(app_state, context))]
+async fn get_my_usage(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Query(query): Query<UsageQuery>,
+) -> AppResult<Response> {
+    let actor = context
+        .user_id()
+        .ok_or_else(|| ApiError::forbidden("authentication required"))?;
+    let params = query.params(Utc::now().date_naive())?;
+    let pool = require_pool(&app_state)?;
+
+    let report = UsageService::new(pool)
+        .report(actor, params.from, params.to, params.group_by, Some(actor))
+        .await?;
+    Ok(render(&report, params.csv))
+}
+
+/// Usage across every user; `user_id` narrows it to one account.
+#[instrument(skip(app_state, context))]
+pub async fn get_all_usage(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Query(query): Query<UsageQuery>,
+) -> AppResult<Response> {
+    let admin = require_admin_context(&context)?;
+    let params = query.params(Utc::now().date_naive())?;
+    let pool = require_pool(&app_state)?;
+
+    let report = UsageService::new(pool)
+        .report(
+            admin.id,
+            params.from,
+            params.to,
+            params.group_by,
+            query.user_id,
+        )
+        .await?;
+    Ok(render(&report, params.csv))
+}
+
+fn render(report: &UsageReport, csv: bool) -> Response {
+    if !csv {
+        return Json(report).into_response();
+    }
+
+    let filename = format!(
+        "usage-{}-{}.csv",
+        report.from.0.format("%Y%m%d"),
+        report.to.0.format("%Y%m%d")
+    );
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        report.to_csv(),
+    )
+        .into_response()
+}
+
+fn require_pool(state: &AppState) -> AppResult<PgPool> {
+    state.pool.clone().ok_or_else(|| {
+        ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "database_unavailable",
+            "database pool not configured",
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(value: &str) -> NaiveDate {
+        value.parse().expect("valid date")
+    }
+
+    #[test]
+    fn params_cover_whole_days_and_default_to_thirty() {
+        let today = day("2026-03-31");
+
+        let params = UsageQuery::default().params(today).expect("defaults");
+        assert_eq!(params.group_by, UsageGroupBy::Day);
+        assert!(!params.csv);
+        assert_eq!(params.from.to_rfc3339(), "2026-03-02T00:00:00+00:00");
+        assert_eq!(params.to.to_rfc3339(), "2026-04-01T00:00:00+00:00");
+
+        let explicit = UsageQuery {
+            from: Some(day("2026-03-10")),
+            to: Some(day("2026-03-10")),
+            group_by: Some("model".into()),
+            format: Some("CSV".into()),
+            user_id: None,
+        }
+        .params(today)
+        .expect("explicit range");
+        assert_eq!(explicit.group_by, UsageGroupBy::Model);
+        assert!(explicit.csv);
+        assert_eq!(explicit.to - explicit.from, chrono::Duration::days(1));
+    }
+
+    #[test]
+    fn params_reject_inverted_ranges_and_unknown_options() {
+        let today = day("2026-03-31");
+        let inverted = UsageQuery {
+            from: Some(day("2026-03-11")),
+            to: Some(day("2026-03-10")),
+            ..UsageQuery::default()
+        };
+        assert!(inverted.params(today).is_err());
+
+        let bad_group = UsageQuery {
+            group_by: Some("week".into()),
+            ..UsageQuery::default()
+        };
+        assert!(bad_group.params(today).is_err());
+
+        let bad_format = UsageQuery {
+            format: Some("xml".into()),
+            ..UsageQuery::default()
+        };
+        assert!(bad_format.params(today).is_err());
+    }
+}
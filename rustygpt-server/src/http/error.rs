@@ -4,7 +4,11 @@ use serde_json::json;
 use thiserror::Error;
 
 use super::problem::ProblemDetails;
-use crate::services::chat_service::ChatServiceError;
+use crate::services::{
+    chat_service::ChatServiceError,
+    quota_service::QuotaExceeded,
+    stream_supervisor::{SHUTDOWN_RETRY_AFTER, ShuttingDown},
+};
 
 pub type AppResult<T> = Result<T, ApiError>;
 
@@ -54,6 +58,14 @@ impl ApiError {
         self.headers.push((name, value));
         self
     }
+
+    pub const fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
 }
 
 impl IntoResponse for ApiError {
@@ -125,6 +137,47 @@ impl From<ChatServiceError> for ApiError {
     }
 }
 
+impl From<QuotaExceeded> for ApiError {
+    fn from(quota: QuotaExceeded) -> Self {
+        let retry_after = (quota.resets_at - chrono::Utc::now()).num_seconds().max(1);
+        let resets_at = quota.resets_at.to_rfc3339();
+        let mut error = Self::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "RGP.QUOTA.EXCEEDED",
+            format!("{} token quota exceeded", quota.period),
+        )
+        .with_details(json!({
+            "period": quota.period,
+            "source": quota.source,
+            "limit": quota.limit,
+            "used": quota.used,
+            "resets_at": resets_at,
+        }))
+        .with_header(http::header::RETRY_AFTER, HeaderValue::from(retry_after));
+        if let Ok(value) = HeaderValue::from_str(&resets_at) {
+            error = error.with_header(HeaderName::from_static("x-quota-reset"), value);
+        }
+        if let Ok(value) = HeaderValue::from_str(&quota.period) {
+            error = error.with_header(HeaderName::from_static("x-quota-period"), value);
+        }
+        error
+    }
+}
+
+impl From<ShuttingDown> for ApiError {
+    fn from(err: ShuttingDown) -> Self {
+        Self::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "RGP.SHUTTING_DOWN",
+            format!("{err}; retry shortly"),
+        )
+        .with_header(
+            http::header::RETRY_AFTER,
+            HeaderValue::from(SHUTDOWN_RETRY_AFTER.as_secs()),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,4 +253,39 @@ mod tests {
         let db = ApiError::from(ChatServiceError::Database(sqlx::Error::PoolTimedOut));
         assert_eq!(db.status, StatusCode::INTERNAL_SERVER_ERROR);
     }
+
+    #[test]
+    fn exceeded_quota_maps_to_429_with_reset_headers() {
+        let resets_at = chrono::Utc::now() + chrono::Duration::hours(2);
+        let error = ApiError::from(QuotaExceeded {
+            period: "day".into(),
+            source: "user".into(),
+            limit: 1000,
+            used: 1200,
+            resets_at,
+        });
+        assert_eq!(error.status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(error.code, "RGP.QUOTA.EXCEEDED");
+
+        let response = error.into_response();
+        let headers = response.headers();
+        let retry_after: i64 = headers[http::header::RETRY_AFTER]
+            .to_str()
+            .expect("ascii header")
+            .parse()
+            .expect("seconds");
+        assert!((7000..=7200).contains(&retry_after));
+        assert_eq!(headers["x-quota-reset"], resets_at.to_rfc3339().as_str());
+        assert_eq!(headers["x-quota-period"], "day");
+    }
+
+    #[test]
+    fn shutting_down_maps_to_503_with_retry_after() {
+        let error = ApiError::from(ShuttingDown);
+        assert_eq!(error.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(error.code, "RGP.SHUTTING_DOWN");
+
+        let response = error.into_response();
+        assert_eq!(response.headers()[http::header::RETRY_AFTER], "5");
+    }
 }
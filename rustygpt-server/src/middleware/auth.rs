@@ -13,8 +13,11 @@ use tracing::{instrument, warn};
 
 use crate::{
     app_state::AppState,
-    auth::session::{SessionError, SessionMetadata, SessionValidation},
-    middleware::request_context::RequestContext,
+    auth::{
+        api_tokens::{ApiTokenService, bearer_token, required_scope},
+        session::{SessionError, SessionMetadata, SessionUser, SessionValidation},
+    },
+    middleware::request_context::{RequestContext, client_ip},
 };
 
 // Middleware to check if a user is authenticated
@@ -34,6 +37,11 @@ pub async fn auth_middleware(mut req: Request<Body>, next: Next) -> Result<Respo
         .get::<Arc<AppState>>()
         .cloned()
         .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if let Some(token) = bearer_token(req.headers()).map(ToString::to_string) {
+        return authenticate_bearer(&state, &config, req, next, &token).await;
+    }
+
     let session_service = state
         .sessions
         .clone()
@@ -50,29 +58,13 @@ pub async fn auth_middleware(mut req: Request<Body>, next: Next) -> Result<Respo
         .and_then(|value| value.to_str().ok())
         .map(ToString::to_string);
 
-    let forwarded_ip = req
-        .headers()
-        .get("x-forwarded-for")
-        .and_then(|value| value.to_str().ok())
-        .and_then(|raw| raw.split(',').next())
-        .map(str::trim)
-        .filter(|segment| !segment.is_empty())
-        .map(ToString::to_string);
-
-    let real_ip = req
-        .headers()
-        .get("x-real-ip")
-        .and_then(|value| value.to_str().ok())
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty());
-
     let fingerprint = req
         .headers()
         .get("x-client-fingerprint")
         .and_then(|value| value.to_str().ok())
         .map(ToString::to_string);
 
-    let ip_source = forwarded_ip.or(real_ip);
+    let ip_source = client_ip(req.headers());
     let mut metadata = SessionMetadata::default()
         .with_user_agent(user_agent)
         .with_fingerprint(fingerprint);
@@ -103,28 +95,7 @@ pub async fn auth_middleware(mut req: Request<Body>, next: Next) -> Result<Respo
         rotated,
     } = validation;
 
-    let request_id = req
-        .extensions()
-        .get::<RequestContext>()
-        .map(|ctx| ctx.request_id.clone())
-        .or_else(|| {
-            let header = HeaderName::from_str(&config.server.request_id_header)
-                .unwrap_or_else(|_| HeaderName::from_static("x-request-id"));
-            req.headers()
-                .get(&header)
-                .and_then(|value| value.to_str().ok())
-                .map(ToString::to_string)
-        })
-        .unwrap_or_default();
-
-    if let Some(context) = req.extensions_mut().get_mut::<RequestContext>() {
-        context.session = Some(user.clone());
-    } else {
-        req.extensions_mut().insert(RequestContext {
-            request_id,
-            session: Some(user.clone()),
-        });
-    }
+    attach_session_user(&mut req, &config, user);
 
     let mut response = next.run(req).await;
     let rotation_header_set = bundle.as_ref().is_some_and(|bundle| {
@@ -151,6 +122,77 @@ pub async fn auth_middleware(mut req: Request<Body>, next: Next) -> Result<Respo
     Ok(response)
 }
 
+async fn authenticate_bearer(
+    state: &AppState,
+    config: &Config,
+    mut req: Request<Body>,
+    next: Next,
+    token: &str,
+) -> Result<Response, StatusCode> {
+    let pool = state
+        .pool
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let ip = client_ip(req.headers());
+
+    let principal = match ApiTokenService::new(pool)
+        .authenticate(token, ip.as_deref())
+        .await
+    {
+        Ok(Some(principal)) => principal,
+        Ok(None) => return Ok(unauthorized_response_with("Bearer")),
+        Err(err) => {
+            warn!(error = %err, "api token validation failed");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    if let Some(scope) = required_scope(req.method(), req.uri().path())
+        && !principal.allows(scope)
+    {
+        return Ok(insufficient_scope_response());
+    }
+
+    attach_session_user(&mut req, config, principal.session_user());
+    req.extensions_mut().insert(principal);
+    Ok(next.run(req).await)
+}
+
+fn attach_session_user(req: &mut Request<Body>, config: &Config, user: SessionUser) {
+    let request_id = req
+        .extensions()
+        .get::<RequestContext>()
+        .map(|ctx| ctx.request_id.clone())
+        .or_else(|| {
+            let header = HeaderName::from_str(&config.server.request_id_header)
+                .unwrap_or_else(|_| HeaderName::from_static("x-request-id"));
+            req.headers()
+                .get(&header)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+        })
+        .unwrap_or_default();
+
+    if let Some(context) = req.extensions_mut().get_mut::<RequestContext>() {
+        context.session = Some(user);
+    } else {
+        let mut context = RequestContext::from_headers(request_id, req.headers());
+        context.session = Some(user);
+        req.extensions_mut().insert(context);
+    }
+}
+
+fn insufficient_scope_response() -> Response {
+    http::Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .header(
+            header::WWW_AUTHENTICATE,
+            "Bearer error=\"insufficient_scope\"",
+        )
+        .body(Body::empty())
+        .unwrap()
+}
+
 fn extract_session_cookie(headers: &http::HeaderMap, name: &str) -> Option<String> {
     let value = headers.get(header::COOKIE)?.to_str().ok()?;
     Cookie::split_parse(value)
@@ -217,6 +259,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn insufficient_scope_is_forbidden_with_bearer_challenge() {
+        let response = insufficient_scope_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::WWW_AUTHENTICATE)
+                .map(|value| value.to_str().unwrap()),
+            Some("Bearer error=\"insufficient_scope\"")
+        );
+    }
+
     #[test]
     fn map_session_error_handles_disabled_user() {
         let response = map_session_error(SessionError::DisabledUser);
@@ -7,7 +7,10 @@ use axum::{
 };
 use cookie::Cookie;
 
-use crate::http::error::{ApiError, AppResult};
+use crate::{
+    auth::api_tokens::bearer_token,
+    http::error::{ApiError, AppResult},
+};
 use shared::config::server::Config;
 
 fn csrf_error(message: &'static str) -> ApiError {
@@ -55,6 +58,11 @@ pub async fn enforce_csrf(
         return Ok(next.run(request).await);
     }
 
+    // Browsers never attach bearer tokens on their own, so these requests cannot be forged.
+    if bearer_token(request.headers()).is_some() {
+        return Ok(next.run(request).await);
+    }
+
     let header_token = request
         .headers()
         .get(&state.header_name)
@@ -79,8 +87,9 @@ fn is_stream_endpoint(path: &str) -> bool {
     path.starts_with("/api/stream")
 }
 
+/// Sign-in endpoints run before the client holds a CSRF token; session management does not.
 fn is_auth_endpoint(path: &str) -> bool {
-    path.starts_with("/api/auth/")
+    path.starts_with("/api/auth/") && !path.starts_with("/api/auth/sessions")
 }
 
 fn extract_cookie(request: &Request<Body>, cookie_name: &str) -> Option<String> {
@@ -117,6 +126,7 @@ mod tests {
             .route("/api/messages", get(ok_handler).post(ok_handler))
             .route("/api/conversations", get(ok_handler))
             .route("/api/auth/login", post(ok_handler))
+            .route("/api/auth/sessions", axum::routing::delete(ok_handler))
             .layer(axum::middleware::from_fn_with_state(
                 state.clone(),
                 enforce_csrf,
@@ -184,6 +194,19 @@ mod tests {
         assert_eq!(response.status(), http::StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn bypasses_bearer_token_requests() {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/api/messages")
+            .header(header::AUTHORIZATION, "Bearer rgp_0123abcd_secret")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = call(csrf_state(true), request).await;
+        assert_eq!(response.status(), http::StatusCode::OK);
+    }
+
     #[tokio::test]
     async fn bypasses_auth_endpoints() {
         let request = Request::builder()
@@ -195,4 +218,16 @@ mod tests {
         let response = call(csrf_state(true), request).await;
         assert_eq!(response.status(), http::StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn session_management_is_not_exempt() {
+        let request = Request::builder()
+            .method(Method::DELETE)
+            .uri("/api/auth/sessions")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = call(csrf_state(true), request).await;
+        assert_eq!(response.status(), http::StatusCode::FORBIDDEN);
+    }
 }
@@ -93,6 +93,23 @@ impl RateLimitState {
                 bucket_key: "auth_login".to_string(),
                 profile: "auth_login".to_string(),
             }
+        } else if path.starts_with("/api/auth/password")
+            || path.starts_with("/api/auth/verify-email")
+        {
+            // Token endpoints share the login budget but not its bucket, so a flood of reset
+            // requests cannot lock users out of signing in.
+            AppliedStrategy {
+                strategy: self.auth_strategy,
+                bucket_key: "auth_account".to_string(),
+                profile: "auth_account".to_string(),
+            }
+        } else if path.starts_with("/api/auth/oidc/") && path != "/api/auth/oidc/providers" {
+            // Each login and callback costs a provider round trip.
+            AppliedStrategy {
+                strategy: self.auth_strategy,
+                bucket_key: "auth_oidc".to_string(),
+                profile: "auth_oidc".to_string(),
+            }
         } else {
             AppliedStrategy {
                 strategy: self.default_strategy,
@@ -312,9 +329,14 @@ fn saturating_f64_to_u64(value: f64) -> u64 {
 #[allow(clippy::cast_precision_loss)]
 const U64_MAX_AS_F64: f64 = u64::MAX as f64;
 
+/// Name of the rate-limit profile that admitted a request, for handlers that
+/// apply per-profile policies such as token quotas.
+#[derive(Debug, Clone)]
+pub struct AppliedRateLimitProfile(pub String);
+
 pub async fn enforce_rate_limits(
     State(state): State<RateLimitState>,
-    request: Request<Body>,
+    mut request: Request<Body>,
     next: Next,
 ) -> AppResult<Response> {
     if request.method() == Method::OPTIONS {
@@ -354,6 +376,9 @@ pub async fn enforce_rate_limits(
                 "profile" => applied.profile.clone()
             )
             .set(reset_seconds);
+            request
+                .extensions_mut()
+                .insert(AppliedRateLimitProfile(applied.profile.clone()));
             let mut response = next.run(request).await;
             attach_rate_limit_headers(
                 &mut response,
@@ -524,6 +549,34 @@ mod tests {
         assert_eq!(applied.bucket_key, "auth_login");
     }
 
+    #[tokio::test]
+    async fn select_strategy_limits_password_reset_like_login() {
+        let config = test_config();
+        let state = RateLimitState::new(&config, None);
+        let applied = state
+            .select_strategy(&Method::POST, "/api/auth/password/forgot")
+            .await;
+
+        assert_eq!(applied.profile, "auth_account");
+        assert_eq!(applied.bucket_key, "auth_account");
+        assert!((applied.strategy.capacity - state.auth_strategy.capacity).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn select_strategy_limits_oidc_logins() {
+        let config = test_config();
+        let state = RateLimitState::new(&config, None);
+        let applied = state
+            .select_strategy(&Method::GET, "/api/auth/oidc/keycloak/callback")
+            .await;
+        assert_eq!(applied.bucket_key, "auth_oidc");
+
+        let listing = state
+            .select_strategy(&Method::GET, "/api/auth/oidc/providers")
+            .await;
+        assert_eq!(listing.profile, "default");
+    }
+
     #[tokio::test]
     async fn select_strategy_matches_configured_assignment() {
         let config = test_config();
@@ -3,7 +3,7 @@ use std::str::FromStr;
 use axum::{
     body::Body,
     extract::State,
-    http::{HeaderMap, HeaderName, HeaderValue, Request},
+    http::{HeaderMap, HeaderName, HeaderValue, Request, header},
     middleware::Next,
     response::Response,
 };
@@ -19,12 +19,51 @@ use shared::config::server::Config;
 pub struct RequestContext {
     pub request_id: String,
     pub session: Option<SessionUser>,
+    /// Caller address from `X-Forwarded-For` / `X-Real-IP`, recorded in audit events.
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
 }
 
 impl RequestContext {
     pub fn user_id(&self) -> Option<Uuid> {
         self.session.as_ref().map(|session| session.id)
     }
+
+    /// Builds an anonymous context for `request_id`, capturing the caller's address and agent.
+    pub fn from_headers(request_id: String, headers: &HeaderMap) -> Self {
+        Self {
+            request_id,
+            session: None,
+            client_ip: client_ip(headers),
+            user_agent: headers
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string),
+        }
+    }
+}
+
+#[cfg(test)]
+impl RequestContext {
+    /// Signed-in context holding `roles`, shared by handler tests.
+    pub(crate) fn test_with_roles(roles: Vec<shared::models::UserRole>) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            request_id: "test".into(),
+            session: Some(SessionUser {
+                id: Uuid::new_v4(),
+                email: "user@example.com".into(),
+                username: "user".into(),
+                display_name: None,
+                roles,
+                session_id: Uuid::new_v4(),
+                issued_at: now,
+                expires_at: now,
+                absolute_expires_at: now,
+            }),
+            ..Self::default()
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -50,10 +89,8 @@ pub async fn assign_request_id(
 
     let request_id = current.unwrap_or_else(|| Uuid::new_v4().to_string());
 
-    request.extensions_mut().insert(RequestContext {
-        request_id: request_id.clone(),
-        session: None,
-    });
+    let context = RequestContext::from_headers(request_id.clone(), request.headers());
+    request.extensions_mut().insert(context);
 
     request.headers_mut().insert(
         header_name.clone(),
@@ -71,6 +108,24 @@ pub async fn assign_request_id(
     Ok(response)
 }
 
+pub(crate) fn client_ip(headers: &HeaderMap) -> Option<String> {
+    let forwarded_ip = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|raw| raw.split(',').next())
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(ToString::to_string);
+
+    forwarded_ip.or_else(|| {
+        headers
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    })
+}
+
 fn extract_request_id(headers: &HeaderMap, header: &HeaderName) -> Option<String> {
     headers
         .get(header)
@@ -88,26 +143,13 @@ mod tests {
 
     #[test]
     fn user_id_proxies_session_identifier() {
-        let context = RequestContext {
-            request_id: "req".into(),
-            session: Some(SessionUser {
-                id: Uuid::new_v4(),
-                email: "user@example.com".into(),
-                username: "user".into(),
-                display_name: None,
-                roles: vec![],
-                session_id: Uuid::new_v4(),
-                issued_at: chrono::Utc::now(),
-                expires_at: chrono::Utc::now(),
-                absolute_expires_at: chrono::Utc::now(),
-            }),
-        };
+        let context = RequestContext::test_with_roles(vec![]);
 
         assert!(context.user_id().is_some());
         assert!(
             RequestContext {
                 request_id: "noop".into(),
-                session: None
+                ..RequestContext::default()
             }
             .user_id()
             .is_none()
@@ -2,10 +2,17 @@ use std::sync::Arc;
 
 use axum::{
     Router, middleware,
-    routing::{delete, get, put},
+    routing::{delete, get, post, put},
 };
 
-use crate::{app_state::AppState, handlers::admin_limits, middleware::auth::auth_middleware};
+use crate::{
+    app_state::AppState,
+    handlers::{
+        admin_audit, admin_limits, admin_quotas, admin_retention, admin_users, admin_webhooks, mfa,
+        organizations, sessions, usage,
+    },
+    middleware::auth::auth_middleware,
+};
 
 pub fn create_router_admin() -> Router<Arc<AppState>> {
     Router::new()
@@ -25,6 +32,50 @@ pub fn create_router_admin() -> Router<Arc<AppState>> {
             "/admin/limits/assignments/{id}",
             delete(admin_limits::delete_assignment),
         )
+        .route(
+            "/admin/limits/quotas",
+            get(admin_quotas::list_quotas).post(admin_quotas::upsert_quota),
+        )
+        .route(
+            "/admin/limits/quotas/{id}",
+            delete(admin_quotas::delete_quota),
+        )
+        .route(
+            "/admin/retention",
+            get(admin_retention::get_global_policy).put(admin_retention::update_global_policy),
+        )
+        .route("/admin/usage", get(usage::get_all_usage))
+        .route("/admin/audit", get(admin_audit::list_audit_events))
+        .route("/admin/mfa", get(mfa::get_policy).put(mfa::update_policy))
+        .route(
+            "/admin/users",
+            get(admin_users::list_users).post(admin_users::create_user),
+        )
+        .route("/admin/users/{user_id}", get(admin_users::get_user))
+        .route(
+            "/admin/users/{user_id}/disable",
+            post(admin_users::disable_user),
+        )
+        .route(
+            "/admin/users/{user_id}/enable",
+            post(admin_users::enable_user),
+        )
+        .route(
+            "/admin/users/{user_id}/roles/{role}",
+            put(admin_users::grant_role).delete(admin_users::revoke_role),
+        )
+        .route(
+            "/admin/users/{user_id}/password",
+            post(admin_users::reset_password),
+        )
+        .route("/admin/roles", get(admin_users::list_roles))
+        .route(
+            "/admin/users/{user_id}/unlock",
+            post(admin_users::unlock_user),
+        )
+        .merge(sessions::admin_routes())
+        .merge(organizations::admin_routes())
+        .merge(admin_webhooks::admin_routes())
         .route_layer(middleware::from_fn(auth_middleware))
 }
 
@@ -3,9 +3,11 @@ use std::sync::Arc;
 use crate::{
     app_state::AppState,
     handlers::{
+        account::{forgot_password, reset_password, verify_email},
         apple_auth::apple_auth_routes,
-        auth::{login, logout, me, refresh},
+        auth::{login, login_mfa, logout, me, refresh},
         github_auth::github_auth_routes,
+        oidc,
     },
     middleware::auth::auth_middleware,
 };
@@ -20,8 +22,15 @@ pub fn create_router_auth() -> Router<Arc<AppState>> {
     info!("Creating auth router");
     Router::new()
         .route("/auth/login", post(login))
+        .route("/auth/login/mfa", post(login_mfa))
         .route("/auth/logout", post(logout))
         .route("/auth/refresh", post(refresh))
+        .route("/auth/password/forgot", post(forgot_password))
+        .route("/auth/password/reset", post(reset_password))
+        .route("/auth/verify-email", post(verify_email))
+        .route("/auth/oidc/providers", get(oidc::list_providers))
+        .route("/auth/oidc/{provider}/login", get(oidc::login))
+        .route("/auth/oidc/{provider}/callback", get(oidc::callback))
         .route(
             "/auth/me",
             get(me).route_layer(middleware::from_fn(auth_middleware)),
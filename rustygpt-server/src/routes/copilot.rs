@@ -126,6 +126,7 @@ mod tests {
         let context = RequestContext {
             request_id: "req".into(),
             session: None,
+            ..RequestContext::default()
         };
 
         let app_state = Arc::new(AppState {
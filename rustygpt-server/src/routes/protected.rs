@@ -2,7 +2,10 @@ use std::sync::Arc;
 
 use crate::{
     app_state::AppState,
-    handlers::{conversations, threads},
+    handlers::{
+        account, api_tokens, conversations, groups, mfa, organizations, preferences, sessions,
+        threads, usage,
+    },
 };
 use axum::Router;
 use tracing::info;
@@ -11,8 +14,16 @@ use tracing::info;
 pub fn create_router_protected() -> Router<Arc<AppState>> {
     info!("Creating protected router");
     Router::new()
+        .merge(account::routes())
+        .merge(api_tokens::routes())
         .merge(conversations::routes())
+        .merge(groups::routes())
+        .merge(mfa::routes())
+        .merge(organizations::routes())
+        .merge(preferences::routes())
+        .merge(sessions::routes())
         .merge(threads::routes())
+        .merge(usage::routes())
     // Note: SSE endpoint moved to unprotected routes for connection stability
 }
 
@@ -1,7 +1,10 @@
 use crate::handlers::streaming::{SharedStreamHub, StreamHub};
 use crate::{
     app_state,
-    auth::session::{SessionManager, SessionService},
+    auth::{
+        oidc::OidcRegistry,
+        session::{SessionManager, SessionService},
+    },
     db::bootstrap,
     middleware::{
         auth::auth_middleware,
@@ -13,18 +16,25 @@ use crate::{
     routes,
     services::{
         assistant_service::{AssistantRuntime, AssistantService},
+        chat_service::{ChatService, ChatServiceError},
+        mailer::{self, MailOutbox},
         sse_persistence::{SsePersistence, SsePersistenceStore},
+        stream_relay::PgStreamRelay,
         stream_supervisor::{SharedStreamSupervisor, StreamSupervisor},
+        webhook_outbox::{HttpWebhookTransport, WebhookOutbox, WebhookQueue},
     },
     tracer,
 };
 use app_state::AppState;
 use axum::http::{HeaderValue, StatusCode, header};
 use axum::{Extension, Router, middleware, response::IntoResponse, routing::get, serve};
-use chrono::{Duration as ChronoDuration, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use routes::openapi::openapi_routes;
-use shared::config::server::{Config, DatabaseConfig, LogFormat, SsePersistenceConfig};
+use shared::config::server::{
+    Config, DatabaseConfig, LogFormat, SseHubBackend, SsePersistenceConfig,
+};
+use shared::models::{ConversationStreamEvent, MessageDoneEvent, StreamErrorEvent};
 use sqlx::{PgPool, postgres::PgPoolOptions};
 use std::{
     fmt,
@@ -48,6 +58,16 @@ static PROMETHEUS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
 const SSE_RETENTION_SCAN_LIMIT: i64 = 128;
 const SSE_RETENTION_INTERVAL_SECS: u64 = 300;
 const RATE_LIMIT_REFRESH_INTERVAL_SECS: u64 = 60;
+const CONVERSATION_RETENTION_INTERVAL_SECS: u64 = 3600;
+const CONVERSATION_RETENTION_BATCH: i32 = 100;
+const STREAM_RELAY_RESTART_DELAY_SECS: u64 = 5;
+/// How long interrupted generations get to record their final state before streams close.
+const SHUTDOWN_FINALIZE_TIMEOUT_SECS: u64 = 10;
+/// With the Postgres hub backend, a `streaming` reply idle for less than this may still belong
+/// to another running instance.
+const ORPHANED_GENERATION_STALE_SECS: i64 = 120;
+/// How often instances sharing the Postgres hub look for replies another instance left behind.
+const ORPHANED_GENERATION_SWEEP_SECS: u64 = 60;
 
 /// Returns the shared Prometheus metrics handle.
 ///
@@ -140,6 +160,144 @@ fn spawn_sse_retention_task(pool: sqlx::PgPool, config: &SsePersistenceConfig) {
     });
 }
 
+async fn apply_retention_once(
+    pool: &PgPool,
+    hub: &SharedStreamHub,
+) -> Result<(), ChatServiceError> {
+    let events = ChatService::new(pool.clone())
+        .apply_retention(CONVERSATION_RETENTION_BATCH)
+        .await?;
+
+    for payload in events {
+        info!(
+            conversation_id = %payload.conversation_id,
+            action = ?payload.action,
+            affected_messages = payload.affected_messages,
+            "retention policy applied"
+        );
+        hub.publish(
+            payload.conversation_id,
+            ConversationStreamEvent::ConversationLifecycle { payload },
+        )
+        .await;
+    }
+
+    Ok(())
+}
+
+/// Marks replies whose generation died with a server process as interrupted and sends their
+/// closing events to each conversation's listeners, or to the next client that subscribes.
+async fn recover_orphaned_generations(
+    pool: &PgPool,
+    hub: &SharedStreamHub,
+    stale_before: DateTime<Utc>,
+) -> Result<(), ChatServiceError> {
+    let recovered = ChatService::new(pool.clone())
+        .recover_orphaned_generations(stale_before)
+        .await?;
+
+    for reply in recovered {
+        info!(
+            conversation_id = %reply.conversation_id,
+            message_id = %reply.message_id,
+            "recovered interrupted assistant reply"
+        );
+        let done = ConversationStreamEvent::MessageDone {
+            payload: MessageDoneEvent {
+                message_id: reply.message_id,
+                root_id: reply.root_id,
+                conversation_id: reply.conversation_id,
+                finish_reason: Some("interrupted".to_string()),
+                usage: None,
+            },
+        };
+        hub.publish_on_subscribe(reply.conversation_id, done, Some(reply.next_chunk_idx))
+            .await;
+        let error = ConversationStreamEvent::Error {
+            payload: StreamErrorEvent {
+                code: "generation_interrupted".to_string(),
+                message: "Assistant generation stopped when the server went down.".to_string(),
+            },
+        };
+        hub.publish_on_subscribe(reply.conversation_id, error, None)
+            .await;
+    }
+
+    Ok(())
+}
+
+fn spawn_conversation_retention_task(pool: PgPool, hub: SharedStreamHub) {
+    tokio::spawn(async move {
+        let mut ticker = time::interval(Duration::from_secs(CONVERSATION_RETENTION_INTERVAL_SECS));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            if let Err(err) = apply_retention_once(&pool, &hub).await {
+                warn!(error = %err, "conversation retention sweep failed");
+            }
+        }
+    });
+}
+
+/// Recovers replies orphaned by another instance going down while this one keeps running. The
+/// startup recovery covers this instance's own previous run, so the first sweep waits a period.
+fn spawn_orphaned_generation_task(pool: PgPool, hub: SharedStreamHub) {
+    tokio::spawn(async move {
+        let period = Duration::from_secs(ORPHANED_GENERATION_SWEEP_SECS);
+        let mut ticker = time::interval_at(time::Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            let stale_before = Utc::now() - ChronoDuration::seconds(ORPHANED_GENERATION_STALE_SECS);
+            if let Err(err) = recover_orphaned_generations(&pool, &hub, stale_before).await {
+                warn!(error = %err, "orphaned generation sweep failed");
+            }
+        }
+    });
+}
+
+fn spawn_stream_relay_task(hub: SharedStreamHub) {
+    tokio::spawn(async move {
+        loop {
+            match hub.run_relay().await {
+                Ok(()) => warn!("stream relay listener stopped; restarting"),
+                Err(err) => warn!(error = %err, "stream relay listener failed; restarting"),
+            }
+            time::sleep(Duration::from_secs(STREAM_RELAY_RESTART_DELAY_SECS)).await;
+        }
+    });
+}
+
+fn spawn_mail_outbox_task(outbox: MailOutbox, poll_interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = time::interval(poll_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            if let Err(err) = outbox.deliver_due().await {
+                warn!(error = %err, "mail outbox delivery failed");
+            }
+        }
+    });
+}
+
+fn spawn_webhook_outbox_task(outbox: WebhookOutbox, poll_interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = time::interval(poll_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            if let Err(err) = outbox.deliver_due().await {
+                warn!(error = %err, "webhook outbox delivery failed");
+            }
+        }
+    });
+}
+
 /// Initializes the tracing subscriber for logging using the provided configuration.
 #[must_use]
 pub fn initialize_tracing(config: &Config) -> String {
@@ -298,6 +456,16 @@ pub fn create_api_router(config: &Config) -> Router<Arc<AppState>> {
             axum::routing::get(crate::handlers::streaming::conversation_stream)
                 .route_layer(middleware::from_fn(auth_middleware)),
         );
+        router = router.route(
+            "/stream/me",
+            axum::routing::get(crate::handlers::streaming::user_stream)
+                .route_layer(middleware::from_fn(auth_middleware)),
+        );
+        router = router.route(
+            "/stream/ws",
+            axum::routing::get(crate::handlers::stream_socket::stream_socket)
+                .route_layer(middleware::from_fn(auth_middleware)),
+        );
     }
 
     router
@@ -329,29 +497,48 @@ where
     )
 }
 
+/// Creates the stream hub shared by request handlers and background jobs.
+#[must_use]
+pub fn create_stream_hub(state: &AppState, config: &Config) -> SharedStreamHub {
+    let persistence_config = if config.sse.persistence.enabled {
+        Some(config.sse.persistence.clone())
+    } else {
+        None
+    };
+    let mut hub = StreamHub::new(
+        config.sse.channel_capacity,
+        state.sse_store.clone(),
+        persistence_config,
+    );
+    if let Some(pool) = &state.pool {
+        if config.webhooks.enabled {
+            hub = hub.with_webhooks(WebhookQueue::new(pool.clone()));
+        }
+        if config.sse.backend == SseHubBackend::Postgres {
+            hub = hub.with_relay(Arc::new(PgStreamRelay::new(pool.clone())));
+        }
+    }
+    Arc::new(hub)
+}
+
 /// Creates the main application router with all middleware and routes.
 ///
 /// # Arguments
 /// * `state` - Application state to share across handlers.
-/// * `cors` - CORS layer for the application.
 /// * `config` - Shared application configuration.
+/// * `stream_hub` - Stream hub shared with the background jobs.
 ///
 /// # Returns
 /// Returns the fully configured application [`Router`].
-pub fn create_app_router(state: Arc<AppState>, config: Arc<Config>) -> Router {
-    let persistence_config = if config.sse.persistence.enabled {
-        Some(config.sse.persistence.clone())
-    } else {
-        None
-    };
-    let api_router = {
-        let stream_hub: SharedStreamHub = Arc::new(StreamHub::new(
-            config.sse.channel_capacity,
-            state.sse_store.clone(),
-            persistence_config,
-        ));
-        create_api_router(config.as_ref()).layer(Extension(stream_hub))
-    };
+pub fn create_app_router(
+    state: Arc<AppState>,
+    config: Arc<Config>,
+    stream_hub: SharedStreamHub,
+) -> Router {
+    let oidc_registry = Arc::new(OidcRegistry::from_config(&config));
+    let api_router = create_api_router(config.as_ref())
+        .layer(Extension(stream_hub))
+        .layer(Extension(oidc_registry));
     let static_files_service =
         create_static_service(config.web.static_dir.clone(), config.web.spa_index.clone());
 
@@ -397,17 +584,73 @@ pub fn create_app_router(state: Arc<AppState>, config: Arc<Config>) -> Router {
 /// Creates the graceful shutdown signal handler.
 ///
 /// # Returns
-/// Returns a future that resolves when a shutdown signal is received.
+/// Returns a future that resolves when `SIGTERM` or `CTRL+C` is received.
 ///
 /// # Panics
-/// Panics if installing the `CTRL+C` signal handler fails.
+/// Panics if installing a signal handler fails.
 pub async fn create_shutdown_signal() {
-    tokio::signal::ctrl_c()
-        .await
-        .expect("Failed to install CTRL+C signal handler");
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install CTRL+C signal handler");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM signal handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
     info!("Shutting down...");
 }
 
+/// Waits for `signal`, then drains the server before it stops: new generations are refused,
+/// in-flight ones get `grace` to finish, the rest are interrupted, and open streams are
+/// closed with a reconnect hint.
+pub async fn drain_on_shutdown(
+    signal: impl Future<Output = ()>,
+    supervisor: SharedStreamSupervisor,
+    hub: SharedStreamHub,
+    grace: Duration,
+) {
+    signal.await;
+    supervisor.begin_drain();
+
+    let running = supervisor.active_sessions();
+    if running > 0 {
+        info!(
+            running,
+            grace_seconds = grace.as_secs(),
+            "waiting for in-flight generations"
+        );
+    }
+    if !supervisor.wait_idle(grace).await {
+        let interrupted = supervisor.interrupt_all();
+        warn!(
+            interrupted,
+            "interrupting generations still running after the grace period"
+        );
+        // Interrupted generations still persist their partial reply and publish their
+        // terminal events, which subscribers should see before their streams close.
+        let finalize = Duration::from_secs(SHUTDOWN_FINALIZE_TIMEOUT_SECS);
+        if !supervisor.wait_idle(finalize).await {
+            warn!(
+                remaining = supervisor.active_sessions(),
+                "interrupted generations did not finish before shutdown"
+            );
+        }
+    }
+
+    hub.close_streams();
+}
+
 type AnyError = Box<dyn std::error::Error>;
 
 #[derive(Debug)]
@@ -476,16 +719,55 @@ pub async fn run(config: Config) -> Result<(), AnyError> {
         Some(stream_supervisor.clone()),
     );
 
+    let stream_hub = create_stream_hub(&state, &config);
+    let orphaned_before = match config.sse.backend {
+        SseHubBackend::Memory => Utc::now(),
+        SseHubBackend::Postgres => {
+            Utc::now() - ChronoDuration::seconds(ORPHANED_GENERATION_STALE_SECS)
+        }
+    };
+    if let Err(err) = recover_orphaned_generations(&pool, &stream_hub, orphaned_before).await {
+        warn!(error = %err, "failed to recover interrupted assistant replies");
+    }
+    spawn_conversation_retention_task(pool.clone(), stream_hub.clone());
+    if config.sse.backend == SseHubBackend::Postgres {
+        // Oversized relayed events land in the SSE log even when persistence is off.
+        if !config.sse.persistence.enabled {
+            spawn_sse_retention_task(pool.clone(), &config.sse.persistence);
+        }
+        spawn_stream_relay_task(stream_hub.clone());
+        spawn_orphaned_generation_task(pool.clone(), stream_hub.clone());
+    }
+    let mail_transport = mailer::transport_from_config(&config.mail)?;
+    spawn_mail_outbox_task(
+        MailOutbox::new(pool.clone(), mail_transport, &config.mail),
+        Duration::from_secs(config.mail.poll_interval_seconds),
+    );
+    if config.webhooks.enabled {
+        let webhook_transport = Arc::new(HttpWebhookTransport::new(Duration::from_secs(
+            config.webhooks.timeout_seconds,
+        ))?);
+        spawn_webhook_outbox_task(
+            WebhookOutbox::new(pool.clone(), webhook_transport, &config.webhooks),
+            Duration::from_secs(config.webhooks.poll_interval_seconds),
+        );
+    }
+
+    let shutdown_signal = drain_on_shutdown(
+        create_shutdown_signal(),
+        stream_supervisor,
+        stream_hub.clone(),
+        Duration::from_secs(config.server.shutdown_grace_seconds),
+    );
+
     // Create the application router
-    let app = create_app_router(state, config.clone());
+    let app = create_app_router(state, config.clone(), stream_hub);
 
     // Start the server
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server.port));
     let listener = TcpListener::bind(addr).await?;
     info!("Listening on {}", addr);
 
-    let shutdown_signal = create_shutdown_signal();
-
     serve(listener, app)
         .with_graceful_shutdown(shutdown_signal)
         .await?;
@@ -549,6 +831,7 @@ async fn initialize_rate_limiting(config: &Arc<Config>, pool: &PgPool) -> Arc<Ra
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::stream_supervisor::StreamStopReason;
     use serde_json::Value;
     use shared::config::server::{Config, LogFormat, Profile};
     use std::{
@@ -668,6 +951,33 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn drain_interrupts_generations_that_outlive_the_grace_period() {
+        let supervisor: SharedStreamSupervisor = Arc::new(StreamSupervisor::new(None));
+        let hub: SharedStreamHub = Arc::new(StreamHub::new(64, None, None));
+        let session = supervisor.create_session();
+        let generation = tokio::spawn(async move {
+            session.cancellation_token().cancelled().await;
+            session.mark_completed();
+            session.stop_reason()
+        });
+
+        drain_on_shutdown(
+            async {},
+            Arc::clone(&supervisor),
+            Arc::clone(&hub),
+            Duration::from_millis(20),
+        )
+        .await;
+
+        assert!(supervisor.admit().is_err());
+        assert_eq!(supervisor.active_sessions(), 0);
+        assert_eq!(generation.await.unwrap(), StreamStopReason::Interrupted);
+        tokio::time::timeout(Duration::from_secs(1), hub.closed())
+            .await
+            .expect("streams closed");
+    }
+
     #[tokio::test]
     async fn metrics_endpoint_returns_prometheus_payload() {
         use axum::{
@@ -679,7 +989,8 @@ mod tests {
         let _ = super::metrics_handle();
         let config = Arc::new(Config::default_for_profile(Profile::Test));
         let app_state = Arc::new(AppState::default());
-        let app = super::create_app_router(app_state, config);
+        let stream_hub = super::create_stream_hub(&app_state, &config);
+        let app = super::create_app_router(app_state, config, stream_hub);
 
         let response = app
             .oneshot(
@@ -10,11 +10,13 @@ use shared::{
     llms::{
         llama_cpp::{LlamaCppModel, LlamaCppProvider},
         traits::{LLMModel, LLMProvider, StreamingResponseStream},
-        types::{LLMConfig, LLMRequest},
+        types::{LLMConfig, LLMRequest, TokenUsage},
     },
+    models::UsageBreakdown,
 };
 use thiserror::Error;
 use tokio::sync::RwLock;
+use tracing::warn;
 
 #[derive(Debug, Error)]
 pub enum AssistantError {
@@ -28,8 +30,7 @@ pub enum AssistantError {
 
 pub struct AssistantStreamingSession {
     pub stream: StreamingResponseStream,
-    pub prompt_tokens: i64,
-    _model_guard: Option<Arc<LlamaCppModel>>,
+    pub usage: UsageMeter,
     _metrics_guard: Option<SessionMetricsGuard>,
 }
 
@@ -38,27 +39,87 @@ impl AssistantStreamingSession {
     pub(crate) fn from_stream(stream: StreamingResponseStream, prompt_tokens: i64) -> Self {
         Self {
             stream,
-            prompt_tokens,
-            _model_guard: None,
+            usage: UsageMeter {
+                model: "test-model".to_string(),
+                prompt_tokens,
+                tokenizer: None,
+                started_at: Instant::now(),
+            },
             _metrics_guard: None,
         }
     }
 
     fn with_guards(
         stream: StreamingResponseStream,
-        prompt_tokens: i64,
-        model_guard: Arc<LlamaCppModel>,
+        usage: UsageMeter,
         metrics_guard: SessionMetricsGuard,
     ) -> Self {
         Self {
             stream,
-            prompt_tokens,
-            _model_guard: Some(model_guard),
+            usage,
             _metrics_guard: Some(metrics_guard),
         }
     }
 }
 
+/// Measures one generation for the usage ledger and keeps its model loaded until dropped.
+pub struct UsageMeter {
+    /// Model name the request resolved to.
+    pub model: String,
+    /// Prompt length counted with the model tokenizer.
+    pub prompt_tokens: i64,
+    tokenizer: Option<Arc<LlamaCppModel>>,
+    started_at: Instant,
+}
+
+impl UsageMeter {
+    /// Final usage for a generation, preferring the counts reported by the stream and
+    /// otherwise tokenizing `completion` with the same model.
+    pub async fn breakdown(
+        &self,
+        reported: Option<&TokenUsage>,
+        completion: &str,
+    ) -> UsageBreakdown {
+        let prompt_tokens = match reported {
+            Some(usage) if usage.prompt_tokens > 0 => i64::from(usage.prompt_tokens),
+            _ => self.prompt_tokens,
+        };
+
+        let completion_tokens = match reported {
+            Some(usage) if usage.completion_tokens > 0 => i64::from(usage.completion_tokens),
+            _ => self.count_tokens(completion).await,
+        };
+
+        UsageBreakdown {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    /// Milliseconds since the request was handed to the model.
+    #[must_use]
+    pub fn latency_ms(&self) -> i64 {
+        i64::try_from(self.started_at.elapsed().as_millis()).unwrap_or(i64::MAX)
+    }
+
+    async fn count_tokens(&self, text: &str) -> i64 {
+        if text.trim().is_empty() {
+            return 0;
+        }
+
+        if let Some(model) = self.tokenizer.as_ref() {
+            match model.count_tokens(text).await {
+                Ok(count) => return i64::from(count),
+                Err(err) => warn!(error = %err, "failed to tokenize completion for usage"),
+            }
+        }
+
+        // Only reached without a loaded model (stub runtimes) or when tokenization fails.
+        i64::try_from(text.split_whitespace().count()).unwrap_or(i64::MAX)
+    }
+}
+
 #[async_trait]
 pub trait AssistantRuntime: Send + Sync {
     async fn stream_reply(
@@ -93,6 +154,7 @@ impl AssistantService {
         &self,
         request: LLMRequest,
     ) -> Result<AssistantStreamingSession, AssistantError> {
+        let started_at = Instant::now();
         let (model_name, provider_type, llm_config) = self.resolve_model_choice(&request)?;
         let cache_key = format!("{provider_type}::{model_name}");
         let model = self
@@ -112,11 +174,16 @@ impl AssistantService {
 
         let metrics_guard =
             SessionMetricsGuard::new(self.metrics.clone(), provider_type, model_name.clone());
+        let usage = UsageMeter {
+            model: model_name,
+            prompt_tokens,
+            tokenizer: Some(model),
+            started_at,
+        };
 
         Ok(AssistantStreamingSession::with_guards(
             stream,
-            prompt_tokens,
-            model,
+            usage,
             metrics_guard,
         ))
     }
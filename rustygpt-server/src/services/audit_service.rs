@@ -0,0 +1,260 @@
+//! Append-only audit trail of security-relevant changes.
+//!
+//! Most events are written by the stored procedures that perform the change; they read the
+//! request metadata from transaction-local settings applied by [`AuditContext::apply`].
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use tracing::instrument;
+use uuid::Uuid;
+
+use shared::models::{AuditEvent, AuditEventPage, AuditQuery, Timestamp};
+
+use crate::{
+    auth::session::SessionMetadata,
+    middleware::request_context::RequestContext,
+    services::chat_service::{ChatServiceError, ChatServiceResult},
+};
+
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Who made a change and from where.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor: Option<Uuid>,
+    pub request_id: Option<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl From<&RequestContext> for AuditContext {
+    fn from(context: &RequestContext) -> Self {
+        Self {
+            actor: context.user_id(),
+            request_id: Some(context.request_id.clone()).filter(|id| !id.is_empty()),
+            client_ip: context.client_ip.clone(),
+            user_agent: context.user_agent.clone(),
+        }
+    }
+}
+
+impl AuditContext {
+    #[must_use]
+    pub fn from_session_metadata(actor: Option<Uuid>, metadata: &SessionMetadata) -> Self {
+        Self {
+            actor,
+            request_id: metadata.request_id.clone(),
+            client_ip: metadata.ip.clone(),
+            user_agent: metadata.user_agent.clone(),
+        }
+    }
+
+    /// Exposes the request metadata to `sp_audit_record` for the rest of the transaction.
+    pub(crate) async fn apply(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "SELECT set_config('app.request_id', $1, true),
+                    set_config('app.client_ip', $2, true),
+                    set_config('app.user_agent', $3, true)",
+        )
+        .bind(self.request_id.as_deref().unwrap_or_default())
+        .bind(self.client_ip.as_deref().unwrap_or_default())
+        .bind(self.user_agent.as_deref().unwrap_or_default())
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Starts a transaction acting as `actor` with the request metadata applied.
+    pub async fn begin<'p>(
+        &self,
+        pool: &'p PgPool,
+    ) -> Result<Transaction<'p, Postgres>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        if let Some(actor) = self.actor {
+            sqlx::query("SELECT set_config('app.current_user_id', $1, true)")
+                .bind(actor.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        self.apply(&mut tx).await?;
+        Ok(tx)
+    }
+}
+
+/// An event recorded from Rust rather than from a stored procedure.
+#[derive(Debug, Clone)]
+pub struct NewAuditEvent<'a> {
+    /// Dotted action name, e.g. `auth.login`.
+    pub action: &'a str,
+    pub target_type: &'a str,
+    pub target_id: Option<String>,
+    pub metadata: Value,
+}
+
+impl<'a> NewAuditEvent<'a> {
+    #[must_use]
+    pub fn new(action: &'a str, target_type: &'a str, target_id: Option<String>) -> Self {
+        Self {
+            action,
+            target_type,
+            target_id,
+            metadata: Value::Object(serde_json::Map::new()),
+        }
+    }
+
+    #[must_use]
+    pub fn with_metadata(mut self, metadata: Value) -> Self {
+        self.metadata = metadata;
+        self
+    }
+}
+
+/// Appends `event` on `conn`, so it commits or rolls back with the surrounding change.
+pub(crate) async fn record_on(
+    conn: &mut PgConnection,
+    context: &AuditContext,
+    event: &NewAuditEvent<'_>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>("SELECT rustygpt.sp_audit_record($1, $2, $3, $4, $5, $6, $7, $8)")
+        .bind(event.action)
+        .bind(event.target_type)
+        .bind(event.target_id.as_deref())
+        .bind(&event.metadata)
+        .bind(context.actor)
+        .bind(context.client_ip.as_deref())
+        .bind(context.user_agent.as_deref())
+        .bind(context.request_id.as_deref())
+        .fetch_one(conn)
+        .await
+}
+
+#[derive(sqlx::FromRow)]
+struct AuditEventRow {
+    id: i64,
+    occurred_at: DateTime<Utc>,
+    actor_id: Option<Uuid>,
+    actor_username: Option<String>,
+    action: String,
+    target_type: String,
+    target_id: Option<String>,
+    ip: Option<String>,
+    user_agent: Option<String>,
+    request_id: Option<String>,
+    metadata: Value,
+}
+
+impl From<AuditEventRow> for AuditEvent {
+    fn from(row: AuditEventRow) -> Self {
+        Self {
+            id: row.id,
+            occurred_at: Timestamp(row.occurred_at),
+            actor_id: row.actor_id,
+            actor_username: row.actor_username,
+            action: row.action,
+            target_type: row.target_type,
+            target_id: row.target_id,
+            ip: row.ip,
+            user_agent: row.user_agent,
+            request_id: row.request_id,
+            metadata: row.metadata,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AuditService {
+    pool: PgPool,
+}
+
+impl AuditService {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Newest-first page of events matching `query` (admin only).
+    #[instrument(name = "audit.list", skip(self), err)]
+    pub async fn list(&self, actor: Uuid, query: &AuditQuery) -> ChatServiceResult<AuditEventPage> {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        let context = AuditContext {
+            actor: Some(actor),
+            ..AuditContext::default()
+        };
+
+        let mut tx = context
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let rows = sqlx::query_as::<_, AuditEventRow>(
+            "SELECT id, occurred_at, actor_id, actor_username, action, target_type, target_id,
+                    ip, user_agent, request_id, metadata
+             FROM rustygpt.sp_audit_list($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(query.actor_id)
+        .bind(query.action.as_deref())
+        .bind(query.target_type.as_deref())
+        .bind(query.target_id.as_deref())
+        .bind(query.from)
+        .bind(query.to)
+        .bind(query.before)
+        .bind(i32::try_from(limit).unwrap_or(i32::MAX))
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        let events: Vec<AuditEvent> = rows.into_iter().map(AuditEvent::from).collect();
+        Ok(AuditEventPage {
+            next_before: next_cursor(&events, limit),
+            events,
+        })
+    }
+}
+
+/// A full page may have more behind it; a short one is the last.
+fn next_cursor(events: &[AuditEvent], limit: u32) -> Option<i64> {
+    if events.len() < usize::try_from(limit).unwrap_or(usize::MAX) {
+        return None;
+    }
+    events.last().map(|event| event.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: i64) -> AuditEvent {
+        AuditEvent {
+            id,
+            occurred_at: Timestamp(Utc::now()),
+            actor_id: None,
+            actor_username: None,
+            action: "auth.login".into(),
+            target_type: "session".into(),
+            target_id: None,
+            ip: None,
+            user_agent: None,
+            request_id: None,
+            metadata: Value::Null,
+        }
+    }
+
+    #[test]
+    fn only_full_pages_carry_a_cursor() {
+        assert_eq!(next_cursor(&[event(9), event(8)], 2), Some(8));
+        assert_eq!(next_cursor(&[event(9)], 2), None);
+        assert_eq!(next_cursor(&[], 2), None);
+    }
+
+    #[test]
+    fn context_drops_empty_request_id() {
+        let mut context = RequestContext::test_with_roles(Vec::new());
+        context.request_id = String::new();
+        context.client_ip = Some("10.0.0.1".into());
+
+        let audit = AuditContext::from(&context);
+        assert_eq!(audit.actor, context.user_id());
+        assert_eq!(audit.request_id, None);
+        assert_eq!(audit.client_ip.as_deref(), Some("10.0.0.1"));
+    }
+}
@@ -1,18 +1,58 @@
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 use chrono::{DateTime, Utc};
 use shared::models::timestamp::Timestamp;
 use shared::models::{
-    AddParticipantRequest, ConversationCreateRequest, ConversationCreateResponse, ConversationRole,
-    CreateInviteResponse, MessageChunk, MessageRole, MessageView, PostRootMessageRequest,
-    PostRootMessageResponse, PresenceStatus, ReplyMessageRequest, ReplyMessageResponse,
-    ThreadListResponse, ThreadSummary, ThreadTreeResponse, UnreadThreadSummary,
+    AddParticipantRequest, CONVERSATION_EXPORT_VERSION, ConversationArchiveResponse,
+    ConversationCreateRequest, ConversationCreateResponse, ConversationExport,
+    ConversationLifecycleAction, ConversationLifecycleEvent, ConversationRole,
+    CreateInviteResponse, ExportedAttachment, ExportedConversation, ExportedMessage,
+    ForkMessageRequest, ForkMessageResponse, ImportedConversation, MessageChunk, MessageRevision,
+    MessageRevisionsResponse, MessageRole, MessageView, PostRootMessageRequest,
+    PostRootMessageResponse, PresenceStatus, RegenerateMessageResponse, ReplyMessageRequest,
+    ReplyMessageResponse, RetentionPolicy, ThreadListResponse, ThreadSummary, ThreadTreeResponse,
+    UnreadThreadSummary, UpdateRetentionPolicyRequest, word_diff,
 };
 use sqlx::{PgPool, Postgres, Transaction};
 use thiserror::Error;
 use tracing::instrument;
 use uuid::Uuid;
 
+use super::{
+    audit_service::AuditContext, conversation_export, mail_templates::MailTemplate, mailer,
+};
+use crate::auth::account::token_link;
+
+/// Where an assistant reply's generation stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerationStatus {
+    Streaming,
+    Complete,
+    Interrupted,
+    Failed,
+}
+
+impl GenerationStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Streaming => "streaming",
+            Self::Complete => "complete",
+            Self::Interrupted => "interrupted",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// A reply left `streaming` by a server that went away, now marked interrupted.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RecoveredGeneration {
+    pub message_id: Uuid,
+    pub conversation_id: Uuid,
+    pub root_id: Uuid,
+    /// Chunk index the reply's closing events continue from.
+    pub next_chunk_idx: i32,
+}
+
 #[derive(sqlx::FromRow)]
 struct PostRootResponseRow {
     message_id: Uuid,
@@ -30,6 +70,29 @@ struct ReplyResponseRow {
     depth: i32,
 }
 
+#[derive(sqlx::FromRow)]
+struct RetentionPolicyRow {
+    conversation_id: Option<Uuid>,
+    archive_after_days: Option<i32>,
+    purge_deleted_after_days: Option<i32>,
+    effective_archive_after_days: Option<i32>,
+    effective_purge_deleted_after_days: Option<i32>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<RetentionPolicyRow> for RetentionPolicy {
+    fn from(row: RetentionPolicyRow) -> Self {
+        Self {
+            conversation_id: row.conversation_id,
+            archive_after_days: row.archive_after_days,
+            purge_deleted_after_days: row.purge_deleted_after_days,
+            effective_archive_after_days: row.effective_archive_after_days,
+            effective_purge_deleted_after_days: row.effective_purge_deleted_after_days,
+            updated_at: row.updated_at.map(Timestamp),
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ChatServiceError {
     #[error("database error: {0}")]
@@ -45,7 +108,7 @@ pub enum ChatServiceError {
 }
 
 impl ChatServiceError {
-    fn from_db_error(err: sqlx::Error) -> Self {
+    pub(crate) fn from_db_error(err: sqlx::Error) -> Self {
         if let sqlx::Error::Database(db) = &err {
             let message = db.message();
             if message.contains("RGP.401") {
@@ -73,6 +136,7 @@ pub type ChatServiceResult<T> = Result<T, ChatServiceError>;
 #[derive(Clone)]
 pub struct ChatService {
     pool: PgPool,
+    audit: Option<AuditContext>,
 }
 
 #[derive(Debug, Clone)]
@@ -93,18 +157,38 @@ pub struct AcceptInviteResult {
     pub role: ConversationRole,
 }
 
+/// Starts a transaction in which `user_id` is the session user the stored procedures check.
+pub(crate) async fn begin_as(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> ChatServiceResult<Transaction<'_, Postgres>> {
+    let mut tx = pool.begin().await.map_err(ChatServiceError::from)?;
+    // `SET LOCAL` takes no bind parameters; `set_config(.., true)` is its transaction-local form.
+    sqlx::query("SELECT set_config('app.current_user_id', $1, true)")
+        .bind(user_id.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from)?;
+    Ok(tx)
+}
+
 impl ChatService {
     pub const fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self { pool, audit: None }
+    }
+
+    /// Attributes audit events written by the stored procedures to this request.
+    #[must_use]
+    pub fn with_audit(mut self, context: AuditContext) -> Self {
+        self.audit = Some(context);
+        self
     }
 
     async fn begin_for(&self, user_id: Uuid) -> ChatServiceResult<Transaction<'_, Postgres>> {
-        let mut tx = self.pool.begin().await.map_err(ChatServiceError::from)?;
-        sqlx::query("SET LOCAL app.current_user_id = $1")
-            .bind(user_id)
-            .execute(&mut *tx)
-            .await
-            .map_err(ChatServiceError::from)?;
+        let mut tx = begin_as(&self.pool, user_id).await?;
+        if let Some(audit) = &self.audit {
+            audit.apply(&mut tx).await.map_err(ChatServiceError::from)?;
+        }
         Ok(tx)
     }
 
@@ -116,16 +200,32 @@ impl ChatService {
     ) -> ChatServiceResult<ConversationCreateResponse> {
         let mut tx = self.begin_for(actor).await?;
 
-        let ConversationCreateRequest { title, is_group } = request;
-
-        let conversation_id: Uuid =
-            sqlx::query_scalar("SELECT rustygpt.sp_create_conversation($1, $2, $3)")
-                .bind(&title)
-                .bind(is_group)
-                .bind(actor)
-                .fetch_one(&mut *tx)
-                .await
-                .map_err(ChatServiceError::from_db_error)?;
+        let ConversationCreateRequest {
+            title,
+            is_group,
+            org_id,
+        } = request;
+
+        let conversation_id: Uuid = match org_id {
+            Some(org_id) => {
+                sqlx::query_scalar("SELECT rustygpt.sp_create_org_conversation($1, $2, $3, $4)")
+                    .bind(&title)
+                    .bind(is_group)
+                    .bind(actor)
+                    .bind(org_id)
+                    .fetch_one(&mut *tx)
+                    .await
+            }
+            None => {
+                sqlx::query_scalar("SELECT rustygpt.sp_create_conversation($1, $2, $3)")
+                    .bind(&title)
+                    .bind(is_group)
+                    .bind(actor)
+                    .fetch_one(&mut *tx)
+                    .await
+            }
+        }
+        .map_err(ChatServiceError::from_db_error)?;
 
         tx.commit().await.map_err(ChatServiceError::from)?;
 
@@ -175,6 +275,8 @@ impl ChatService {
         Ok(role)
     }
 
+    /// Creates an invite token. With `accept_base_url` set, the invitee is also emailed a link
+    /// to `<accept_base_url>/invite` in the same transaction.
     #[instrument(name = "chat.create_invite", skip(self), err)]
     pub async fn create_invite(
         &self,
@@ -183,6 +285,7 @@ impl ChatService {
         email: &str,
         role: ConversationRole,
         ttl_seconds: Option<i32>,
+        accept_base_url: Option<&str>,
     ) -> ChatServiceResult<CreateInviteResponse> {
         let mut tx = self.begin_for(actor).await?;
 
@@ -203,6 +306,34 @@ impl ChatService {
         .await
         .map_err(ChatServiceError::from_db_error)?;
 
+        if let Some(base) = accept_base_url {
+            let (inviter, conversation) = sqlx::query_as::<_, (String, String)>(
+                "SELECT COALESCE(u.display_name, u.username::TEXT), c.title
+                 FROM rustygpt.users u, rustygpt.conversations c
+                 WHERE u.id = $1 AND c.id = $2",
+            )
+            .bind(actor)
+            .bind(conversation_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+            let link = token_link(base, "/invite", &row.token);
+            let expires_at = row.expires_at.format("%Y-%m-%d %H:%M UTC").to_string();
+            let mail = MailTemplate::ConversationInvite.render(
+                email,
+                &[
+                    ("inviter", &inviter),
+                    ("conversation", &conversation),
+                    ("role", role.as_str()),
+                    ("link", &link),
+                    ("expires_at", &expires_at),
+                ],
+            );
+            mailer::enqueue_on(&mut tx, &mail)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+        }
+
         tx.commit().await.map_err(ChatServiceError::from)?;
 
         Ok(CreateInviteResponse {
@@ -396,12 +527,30 @@ impl ChatService {
         .await
         .map_err(ChatServiceError::from_db_error)?;
 
+        let variants: HashMap<Uuid, Vec<Uuid>> = sqlx::query_as::<_, (Uuid, Vec<Uuid>)>(
+            "SELECT parent_id, variant_ids FROM rustygpt.sp_get_thread_variants($1)",
+        )
+        .bind(root_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?
+        .into_iter()
+        .collect();
+
         tx.commit().await.map_err(ChatServiceError::from)?;
 
         let messages: Vec<MessageView> = rows
             .into_iter()
             .map(|row| {
                 let role = MessageRole::try_from(row.role.as_str()).unwrap_or(MessageRole::User);
+                let alternates = match (role, row.parent_id) {
+                    (MessageRole::Assistant, Some(parent_id)) => variants
+                        .get(&parent_id)
+                        .filter(|ids| ids.contains(&row.id))
+                        .cloned()
+                        .unwrap_or_default(),
+                    _ => Vec::new(),
+                };
                 MessageView {
                     id: row.id,
                     root_id: row.root_id,
@@ -413,6 +562,7 @@ impl ChatService {
                     path: row.path,
                     depth: row.depth,
                     created_at: Timestamp(row.created_at),
+                    alternates,
                 }
             })
             .collect();
@@ -484,6 +634,7 @@ impl ChatService {
             path: row.path,
             depth: row.depth,
             created_at: Timestamp(row.created_at),
+            alternates: Vec::new(),
         })
     }
 
@@ -549,6 +700,7 @@ impl ChatService {
                     path: row.path,
                     depth: row.depth,
                     created_at: Timestamp(row.created_at),
+                    alternates: Vec::new(),
                 }
             })
             .collect();
@@ -600,6 +752,7 @@ impl ChatService {
             Some(actor),
             request.role.unwrap_or(MessageRole::User),
             request.content,
+            false,
         )
         .await
     }
@@ -611,8 +764,71 @@ impl ChatService {
         parent_message: Uuid,
         content: String,
     ) -> ChatServiceResult<ReplyMessageResponse> {
-        self.reply_with_author(actor, parent_message, None, MessageRole::Assistant, content)
-            .await
+        self.reply_with_author(
+            actor,
+            parent_message,
+            None,
+            MessageRole::Assistant,
+            content,
+            false,
+        )
+        .await
+    }
+
+    /// Creates the assistant reply a generation streams into. It stays `streaming` until
+    /// [`Self::finish_generation`] records how the generation ended.
+    #[instrument(name = "chat.reply.assistant_stream", skip(self, content), err)]
+    pub async fn start_assistant_reply(
+        &self,
+        actor: Uuid,
+        parent_message: Uuid,
+        content: String,
+    ) -> ChatServiceResult<ReplyMessageResponse> {
+        self.reply_with_author(
+            actor,
+            parent_message,
+            None,
+            MessageRole::Assistant,
+            content,
+            true,
+        )
+        .await
+    }
+
+    #[instrument(name = "chat.regenerate.prepare", skip(self), err)]
+    pub async fn prepare_regenerate(
+        &self,
+        actor: Uuid,
+        message_id: Uuid,
+    ) -> ChatServiceResult<RegenerateMessageResponse> {
+        let mut tx = self.begin_for(actor).await?;
+
+        #[derive(sqlx::FromRow)]
+        #[allow(clippy::struct_field_names)] // Column names from sp_prepare_regenerate.
+        struct RegenerateRow {
+            source_message_id: Uuid,
+            parent_id: Uuid,
+            root_id: Uuid,
+            conversation_id: Uuid,
+        }
+
+        let row = sqlx::query_as::<_, RegenerateRow>(
+            "SELECT source_message_id, parent_id, root_id, conversation_id
+             FROM rustygpt.sp_prepare_regenerate($1)",
+        )
+        .bind(message_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        Ok(RegenerateMessageResponse {
+            source_message_id: row.source_message_id,
+            parent_id: row.parent_id,
+            root_id: row.root_id,
+            conversation_id: row.conversation_id,
+        })
     }
 
     async fn reply_with_author(
@@ -622,6 +838,7 @@ impl ChatService {
         author: Option<Uuid>,
         role: MessageRole,
         content: String,
+        streaming: bool,
     ) -> ChatServiceResult<ReplyMessageResponse> {
         let mut tx = self.begin_for(actor).await?;
         let row = sqlx::query_as::<_, ReplyResponseRow>(
@@ -635,6 +852,17 @@ impl ChatService {
         .await
         .map_err(ChatServiceError::from_db_error)?;
 
+        if streaming {
+            sqlx::query(
+                "SELECT rustygpt.sp_set_message_generation($1, $2::rustygpt.generation_status, NULL)",
+            )
+            .bind(row.message_id)
+            .bind(GenerationStatus::Streaming.as_str())
+            .execute(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        }
+
         tx.commit().await.map_err(ChatServiceError::from)?;
 
         Ok(ReplyMessageResponse {
@@ -726,6 +954,30 @@ impl ChatService {
         Ok(())
     }
 
+    /// Records how an assistant reply's generation ended.
+    #[instrument(name = "chat.finish_generation", skip(self), err)]
+    pub async fn finish_generation(
+        &self,
+        actor: Uuid,
+        message_id: Uuid,
+        status: GenerationStatus,
+        finish_reason: &str,
+    ) -> ChatServiceResult<()> {
+        let mut tx = self.begin_for(actor).await?;
+        sqlx::query(
+            "SELECT rustygpt.sp_set_message_generation($1, $2::rustygpt.generation_status, $3)",
+        )
+        .bind(message_id)
+        .bind(status.as_str())
+        .bind(finish_reason)
+        .execute(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(())
+    }
+
     #[instrument(name = "chat.mark_thread_read", skip(self), err)]
     pub async fn mark_thread_read(
         &self,
@@ -878,18 +1130,429 @@ impl ChatService {
         Ok(())
     }
 
-    pub async fn active_conversations(&self, actor: Uuid) -> ChatServiceResult<Vec<Uuid>> {
-        let conversations = sqlx::query_scalar::<_, Uuid>(
-            "SELECT conversation_id FROM rustygpt.conversation_participants WHERE user_id = $1 AND left_at IS NULL",
+    #[instrument(name = "chat.fork", skip(self, request), err)]
+    pub async fn fork_messages(
+        &self,
+        actor: Uuid,
+        source_message_id: Uuid,
+        request: ForkMessageRequest,
+    ) -> ChatServiceResult<ForkMessageResponse> {
+        let mut tx = self.begin_for(actor).await?;
+
+        #[derive(sqlx::FromRow)]
+        struct ForkRow {
+            conversation_id: Uuid,
+            root_id: Uuid,
+            message_count: i32,
+            created_conversation: bool,
+        }
+
+        let ForkMessageRequest {
+            mode,
+            target_conversation_id,
+            new_conversation_title,
+        } = request;
+
+        let row = sqlx::query_as::<_, ForkRow>(
+            "SELECT conversation_id, root_id, message_count, created_conversation
+             FROM rustygpt.sp_fork_messages($1, $2, $3, $4)",
         )
-        .bind(actor)
+        .bind(source_message_id)
+        .bind(mode.as_str())
+        .bind(target_conversation_id)
+        .bind(new_conversation_title)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        Ok(ForkMessageResponse {
+            conversation_id: row.conversation_id,
+            root_id: row.root_id,
+            message_count: row.message_count,
+            created_conversation: row.created_conversation,
+        })
+    }
+
+    #[instrument(name = "chat.set_archived", skip(self), err)]
+    pub async fn set_conversation_archived(
+        &self,
+        actor: Uuid,
+        conversation_id: Uuid,
+        archived: bool,
+    ) -> ChatServiceResult<(ConversationArchiveResponse, bool)> {
+        let mut tx = self.begin_for(actor).await?;
+
+        #[derive(sqlx::FromRow)]
+        struct ArchiveRow {
+            archived_at: Option<DateTime<Utc>>,
+            changed: bool,
+        }
+
+        let row = sqlx::query_as::<_, ArchiveRow>(
+            "SELECT archived_at, changed FROM rustygpt.sp_set_conversation_archived($1, $2)",
+        )
+        .bind(conversation_id)
+        .bind(archived)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        Ok((
+            ConversationArchiveResponse {
+                conversation_id,
+                archived_at: row.archived_at.map(Timestamp),
+            },
+            row.changed,
+        ))
+    }
+
+    /// Hard-delete a conversation, returning how many messages were removed with it.
+    #[instrument(name = "chat.delete_conversation", skip(self), err)]
+    pub async fn delete_conversation(
+        &self,
+        actor: Uuid,
+        conversation_id: Uuid,
+    ) -> ChatServiceResult<i32> {
+        let mut tx = self.begin_for(actor).await?;
+
+        let removed = sqlx::query_scalar::<_, i32>("SELECT rustygpt.sp_delete_conversation($1)")
+            .bind(conversation_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(removed)
+    }
+
+    /// Fetch the retention policy for a conversation, or the global default when `None`.
+    #[instrument(name = "chat.retention_policy", skip(self), err)]
+    pub async fn get_retention_policy(
+        &self,
+        actor: Uuid,
+        conversation_id: Option<Uuid>,
+    ) -> ChatServiceResult<RetentionPolicy> {
+        let mut tx = self.begin_for(actor).await?;
+
+        let row = sqlx::query_as::<_, RetentionPolicyRow>(
+            "SELECT conversation_id, archive_after_days, purge_deleted_after_days,
+                    effective_archive_after_days, effective_purge_deleted_after_days, updated_at
+             FROM rustygpt.sp_get_retention_policy($1)",
+        )
+        .bind(conversation_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(row.into())
+    }
+
+    #[instrument(name = "chat.set_retention_policy", skip(self, request), err)]
+    pub async fn set_retention_policy(
+        &self,
+        actor: Uuid,
+        conversation_id: Option<Uuid>,
+        request: UpdateRetentionPolicyRequest,
+    ) -> ChatServiceResult<RetentionPolicy> {
+        let mut tx = self.begin_for(actor).await?;
+
+        let row = sqlx::query_as::<_, RetentionPolicyRow>(
+            "SELECT conversation_id, archive_after_days, purge_deleted_after_days,
+                    effective_archive_after_days, effective_purge_deleted_after_days, updated_at
+             FROM rustygpt.sp_set_retention_policy($1, $2, $3)",
+        )
+        .bind(conversation_id)
+        .bind(request.archive_after_days)
+        .bind(request.purge_deleted_after_days)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(row.into())
+    }
+
+    /// Run one retention sweep across all conversations. Used by the background job, so it
+    /// runs without a session user.
+    #[instrument(name = "chat.apply_retention", skip(self), err)]
+    pub async fn apply_retention(
+        &self,
+        batch: i32,
+    ) -> ChatServiceResult<Vec<ConversationLifecycleEvent>> {
+        #[derive(sqlx::FromRow)]
+        struct RetentionRow {
+            conversation_id: Uuid,
+            action: String,
+            affected_messages: i32,
+            occurred_at: DateTime<Utc>,
+        }
+
+        let rows = sqlx::query_as::<_, RetentionRow>(
+            "SELECT conversation_id, action, affected_messages, occurred_at
+             FROM rustygpt.sp_apply_retention($1)",
+        )
+        .bind(batch)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                ConversationLifecycleAction::from_db(&row.action).map(|action| {
+                    ConversationLifecycleEvent {
+                        conversation_id: row.conversation_id,
+                        action,
+                        actor_user_id: None,
+                        affected_messages: row.affected_messages,
+                        occurred_at: Timestamp(row.occurred_at),
+                    }
+                })
+            })
+            .collect())
+    }
+
+    /// Marks replies left `streaming` with no activity since `stale_before` as interrupted,
+    /// restoring their content from persisted chunks where that got further.
+    #[instrument(name = "chat.recover_generations", skip(self), err)]
+    pub async fn recover_orphaned_generations(
+        &self,
+        stale_before: DateTime<Utc>,
+    ) -> ChatServiceResult<Vec<RecoveredGeneration>> {
+        sqlx::query_as::<_, RecoveredGeneration>(
+            "SELECT message_id, conversation_id, root_id, next_chunk_idx
+             FROM rustygpt.sp_recover_orphaned_generations($1)",
+        )
+        .bind(stale_before)
         .fetch_all(&self.pool)
         .await
+        .map_err(ChatServiceError::from_db_error)
+    }
+
+    /// Load a conversation (or one thread of it) in export order.
+    #[instrument(name = "chat.export", skip(self), err)]
+    pub async fn export_conversation(
+        &self,
+        actor: Uuid,
+        conversation_id: Uuid,
+        root_id: Option<Uuid>,
+    ) -> ChatServiceResult<ConversationExport> {
+        let mut tx = self.begin_for(actor).await?;
+
+        #[derive(sqlx::FromRow)]
+        struct HeaderRow {
+            id: Uuid,
+            title: String,
+            is_group: bool,
+            created_by: Uuid,
+            created_at: DateTime<Utc>,
+            archived_at: Option<DateTime<Utc>>,
+        }
+
+        #[derive(sqlx::FromRow)]
+        struct ExportRow {
+            id: Uuid,
+            root_id: Uuid,
+            parent_id: Option<Uuid>,
+            author_user_id: Option<Uuid>,
+            author_name: Option<String>,
+            role: String,
+            content: String,
+            path: String,
+            depth: i32,
+            created_at: DateTime<Utc>,
+            edited_at: Option<DateTime<Utc>>,
+            edit_reason: Option<String>,
+            deleted_at: Option<DateTime<Utc>>,
+            forked_from_message_id: Option<Uuid>,
+            revisions: sqlx::types::Json<Vec<MessageRevision>>,
+            attachments: sqlx::types::Json<Vec<ExportedAttachment>>,
+        }
+
+        let header = sqlx::query_as::<_, HeaderRow>(
+            "SELECT id, title, is_group, created_by, created_at, archived_at
+             FROM rustygpt.sp_get_conversation_export_header($1)",
+        )
+        .bind(conversation_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        let rows = sqlx::query_as::<_, ExportRow>(
+            "SELECT id, root_id, parent_id, author_user_id, author_name, role::TEXT AS role,
+                    content, path, depth, created_at, edited_at, edit_reason, deleted_at,
+                    forked_from_message_id, revisions, attachments
+             FROM rustygpt.sp_export_conversation_messages($1, $2)",
+        )
+        .bind(conversation_id)
+        .bind(root_id)
+        .fetch_all(&mut *tx)
+        .await
         .map_err(ChatServiceError::from_db_error)?;
 
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        let messages = rows
+            .into_iter()
+            .map(|row| ExportedMessage {
+                id: row.id,
+                root_id: row.root_id,
+                parent_id: row.parent_id,
+                role: MessageRole::try_from(row.role.as_str()).unwrap_or(MessageRole::User),
+                author_user_id: row.author_user_id,
+                author_name: row.author_name,
+                content: row.content,
+                path: row.path,
+                depth: row.depth,
+                created_at: Timestamp(row.created_at),
+                edited_at: row.edited_at.map(Timestamp),
+                edit_reason: row.edit_reason,
+                deleted_at: row.deleted_at.map(Timestamp),
+                forked_from_message_id: row.forked_from_message_id,
+                revisions: row.revisions.0,
+                attachments: row.attachments.0,
+            })
+            .collect();
+
+        Ok(ConversationExport {
+            version: CONVERSATION_EXPORT_VERSION,
+            exported_at: Timestamp(Utc::now()),
+            conversation: ExportedConversation {
+                id: header.id,
+                title: header.title,
+                is_group: header.is_group,
+                created_by: header.created_by,
+                created_at: Timestamp(header.created_at),
+                archived_at: header.archived_at.map(Timestamp),
+            },
+            root_id,
+            messages: conversation_export::order_messages(messages),
+        })
+    }
+
+    /// Create one conversation per parsed entry in a single transaction.
+    ///
+    /// Returns the new conversation ids in input order; nothing is kept on error.
+    #[instrument(name = "chat.import_conversations", skip(self, conversations), err)]
+    pub async fn import_conversations(
+        &self,
+        actor: Uuid,
+        conversations: &[ImportedConversation],
+    ) -> ChatServiceResult<Vec<Uuid>> {
+        let mut tx = self.begin_for(actor).await?;
+
+        #[derive(sqlx::FromRow)]
+        struct ImportRow {
+            conversation_id: Uuid,
+        }
+
+        let mut ids = Vec::with_capacity(conversations.len());
+        for conversation in conversations {
+            let row = sqlx::query_as::<_, ImportRow>(
+                "SELECT conversation_id
+                 FROM rustygpt.sp_import_conversation($1, $2, $3)",
+            )
+            .bind(&conversation.title)
+            .bind(conversation.created_at.as_ref().map(|ts| ts.0))
+            .bind(sqlx::types::Json(&conversation.messages))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+            ids.push(row.conversation_id);
+        }
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(ids)
+    }
+
+    #[instrument(name = "chat.message_revisions", skip(self), err)]
+    pub async fn list_message_revisions(
+        &self,
+        actor: Uuid,
+        message_id: Uuid,
+    ) -> ChatServiceResult<MessageRevisionsResponse> {
+        let mut tx = self.begin_for(actor).await?;
+
+        #[derive(sqlx::FromRow)]
+        struct RevisionRow {
+            revision: i32,
+            content: String,
+            edited_by: Option<Uuid>,
+            edit_reason: Option<String>,
+            created_at: DateTime<Utc>,
+        }
+
+        let rows = sqlx::query_as::<_, RevisionRow>(
+            "SELECT revision, content, edited_by, edit_reason, created_at
+             FROM rustygpt.sp_list_message_revisions($1)",
+        )
+        .bind(message_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        let mut previous: Option<String> = None;
+        let revisions = rows
+            .into_iter()
+            .map(|row| {
+                let diff = previous
+                    .as_deref()
+                    .map(|before| word_diff(before, &row.content))
+                    .unwrap_or_default();
+                previous = Some(row.content.clone());
+                MessageRevision {
+                    revision: row.revision,
+                    content: row.content,
+                    edited_by: row.edited_by,
+                    reason: row.edit_reason,
+                    created_at: Timestamp(row.created_at),
+                    diff,
+                }
+            })
+            .collect();
+
+        Ok(MessageRevisionsResponse {
+            message_id,
+            revisions,
+        })
+    }
+
+    pub async fn active_conversations(&self, actor: Uuid) -> ChatServiceResult<Vec<Uuid>> {
+        let conversations =
+            sqlx::query_scalar::<_, Uuid>("SELECT rustygpt.sp_user_conversations($1)")
+                .bind(actor)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+
         Ok(conversations)
     }
 
+    /// Which of `usernames` can read the conversation; used to resolve `@mentions`.
+    pub async fn mentioned_users(
+        &self,
+        conversation_id: Uuid,
+        usernames: &[String],
+    ) -> ChatServiceResult<Vec<Uuid>> {
+        if usernames.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        sqlx::query_scalar::<_, Uuid>("SELECT rustygpt.sp_conversation_mentions($1, $2)")
+            .bind(conversation_id)
+            .bind(usernames)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)
+    }
+
     pub async fn ensure_membership(
         &self,
         actor: Uuid,
@@ -914,3 +1577,333 @@ impl ChatService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_support::TestDatabase;
+    use shared::models::{ExportedAttachment, ImportedMessage, MessageRevision, MessageRole};
+    use sqlx::PgConnection;
+    use uuid::Uuid;
+
+    async fn post(conn: &mut PgConnection, parent: Option<Uuid>, conversation: Uuid) -> Uuid {
+        let sql = if parent.is_some() {
+            "SELECT message_id FROM rustygpt.sp_reply_message($1, NULL, 'user', 'hi')"
+        } else {
+            "SELECT message_id FROM rustygpt.sp_post_root_message($2, NULL, 'user', 'hi')"
+        };
+        sqlx::query_scalar(sql)
+            .bind(parent)
+            .bind(conversation)
+            .fetch_one(conn)
+            .await
+            .unwrap()
+    }
+
+    /// Forks `source` in place and returns the source ids of the copies, root first.
+    async fn fork(conn: &mut PgConnection, source: Uuid, mode: &str) -> Vec<Uuid> {
+        let root: Uuid =
+            sqlx::query_scalar("SELECT root_id FROM rustygpt.sp_fork_messages($1, $2)")
+                .bind(source)
+                .bind(mode)
+                .fetch_one(&mut *conn)
+                .await
+                .unwrap();
+        sqlx::query_scalar(
+            "SELECT forked_from_message_id FROM rustygpt.messages
+             WHERE root_message_id = $1
+             ORDER BY nlevel(path), forked_from_message_id",
+        )
+        .bind(root)
+        .fetch_all(conn)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn fork_modes_copy_the_subtree_or_the_ancestor_chain() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("owner").await;
+        let conversation = db.create_conversation(owner, "forks").await;
+
+        // root -> a -> (b, c)
+        let mut tx = db.begin_as(owner).await;
+        let root = post(&mut tx, None, conversation).await;
+        let a = post(&mut tx, Some(root), conversation).await;
+        let b = post(&mut tx, Some(a), conversation).await;
+        let c = post(&mut tx, Some(a), conversation).await;
+        let (first, second) = if b < c { (b, c) } else { (c, b) };
+        tx.commit().await.unwrap();
+
+        let mut tx = db.begin_as(owner).await;
+        assert_eq!(fork(&mut tx, a, "subtree").await, vec![a, first, second]);
+        assert_eq!(fork(&mut tx, b, "ancestors").await, vec![root, a, b]);
+
+        let kept_timestamps: bool = sqlx::query_scalar(
+            "SELECT bool_and(copy.created_at = source.created_at)
+             FROM rustygpt.messages copy
+             JOIN rustygpt.messages source ON source.id = copy.forked_from_message_id",
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        assert!(kept_timestamps, "copies keep the source timestamps");
+        drop(tx);
+
+        db.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn viewers_cannot_fork_into_a_conversation() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("owner").await;
+        let viewer = db.create_user("viewer").await;
+        let conversation = db.create_conversation(owner, "forks").await;
+
+        let mut tx = db.begin_as(owner).await;
+        let root = post(&mut tx, None, conversation).await;
+        sqlx::query("SELECT rustygpt.sp_add_participant($1, $2, 'viewer')")
+            .bind(conversation)
+            .bind(viewer)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let mut tx = db.begin_as(viewer).await;
+        let err = sqlx::query("SELECT * FROM rustygpt.sp_fork_messages($1)")
+            .bind(root)
+            .execute(&mut *tx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("RGP.403"), "{err}");
+        drop(tx);
+
+        db.destroy().await;
+    }
+
+    async fn log_event(
+        db: &TestDatabase,
+        conversation: Uuid,
+        event_type: &str,
+        key: &str,
+        id: Uuid,
+    ) {
+        sqlx::query(
+            "INSERT INTO rustygpt.sse_event_log (conversation_id, sequence, event_id, event_type, payload)
+             SELECT $1, COALESCE(MAX(sequence), 0) + 1, gen_random_uuid()::TEXT, $2,
+                    jsonb_build_object('type', $2, 'payload', jsonb_build_object($3::TEXT, $4))
+             FROM rustygpt.sse_event_log WHERE conversation_id = $1",
+        )
+        .bind(conversation)
+        .bind(event_type)
+        .bind(key)
+        .bind(id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    }
+
+    async fn logged_events(db: &TestDatabase, conversation: Uuid) -> Vec<String> {
+        sqlx::query_scalar(
+            "SELECT event_type FROM rustygpt.sse_event_log
+             WHERE conversation_id = $1 ORDER BY sequence",
+        )
+        .bind(conversation)
+        .fetch_all(&db.pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn purging_and_deleting_drop_the_streamed_events() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("owner").await;
+        let conversation = db.create_conversation(owner, "retention").await;
+
+        let mut tx = db.begin_as(owner).await;
+        let root = post(&mut tx, None, conversation).await;
+        let reply = post(&mut tx, Some(root), conversation).await;
+        tx.commit().await.unwrap();
+
+        log_event(&db, conversation, "thread.new", "root_id", root).await;
+        log_event(&db, conversation, "message.done", "message_id", root).await;
+        log_event(&db, conversation, "thread.new", "root_id", reply).await;
+        log_event(&db, conversation, "message.delta", "message_id", reply).await;
+        log_event(&db, conversation, "message.edited", "message_id", reply).await;
+        log_event(&db, conversation, "thread.activity", "root_id", root).await;
+
+        sqlx::query(
+            "UPDATE rustygpt.messages SET deleted_at = now() - interval '2 days' WHERE id = $1",
+        )
+        .bind(reply)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO rustygpt.retention_policies (conversation_id, purge_deleted_after_days)
+             VALUES ($1, 1)",
+        )
+        .bind(conversation)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        sqlx::query("SELECT * FROM rustygpt.sp_apply_retention(10)")
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            logged_events(&db, conversation).await,
+            vec!["thread.new", "message.done", "thread.activity"]
+        );
+
+        let mut tx = db.begin_as(owner).await;
+        sqlx::query("SELECT rustygpt.sp_delete_conversation($1)")
+            .bind(conversation)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        assert!(logged_events(&db, conversation).await.is_empty());
+
+        db.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn accepting_an_invite_twice_returns_the_same_membership() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("owner").await;
+        let guest = db.create_user("guest").await;
+        let conversation = db.create_conversation(owner, "invites").await;
+
+        let mut tx = db.begin_as(owner).await;
+        let token: String = sqlx::query_scalar(
+            "SELECT token FROM rustygpt.sp_create_invite($1, 'guest@example.com', 'member', 60)",
+        )
+        .bind(conversation)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        tx.commit().await.unwrap();
+
+        for _ in 0..2 {
+            let mut tx = db.begin_as(guest).await;
+            let rows = sqlx::query_as::<_, (Uuid, String)>(
+                "SELECT conversation_id, role::TEXT FROM rustygpt.sp_accept_invite($1, $2)",
+            )
+            .bind(&token)
+            .bind(guest)
+            .fetch_all(&mut *tx)
+            .await
+            .unwrap();
+            assert_eq!(rows, vec![(conversation, "member".to_string())]);
+            tx.commit().await.unwrap();
+        }
+
+        let memberships: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM rustygpt.conversation_participants
+             WHERE conversation_id = $1 AND user_id = $2 AND left_at IS NULL",
+        )
+        .bind(conversation)
+        .bind(guest)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(memberships, 1);
+
+        db.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn exported_history_and_attachments_survive_an_import() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("owner").await;
+        let conversation = db.create_conversation(owner, "round trip").await;
+
+        let mut tx = db.begin_as(owner).await;
+        let root = post(&mut tx, None, conversation).await;
+        sqlx::query("SELECT rustygpt.sp_edit_message($1, $2, 'hello', 'typo')")
+            .bind(root)
+            .bind(owner)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO rustygpt.message_attachments (message_id, file_name, content_type, size_bytes)
+             VALUES ($1, 'plan.pdf', 'application/pdf', 2048)",
+        )
+        .bind(root)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        let (revisions, attachments): (
+            sqlx::types::Json<Vec<MessageRevision>>,
+            sqlx::types::Json<Vec<ExportedAttachment>>,
+        ) = sqlx::query_as(
+            "SELECT revisions, attachments FROM rustygpt.sp_export_conversation_messages($1)",
+        )
+        .bind(conversation)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(attachments[0].file_name, "plan.pdf");
+
+        let messages = vec![ImportedMessage {
+            key: root.to_string(),
+            parent_key: None,
+            role: MessageRole::User,
+            content: "hello".into(),
+            created_at: None,
+            edited_at: Some(revisions[1].created_at.clone()),
+            edit_reason: Some("typo".into()),
+            revisions: revisions.0.clone(),
+            attachments: attachments.0.clone(),
+        }];
+        let imported: Uuid = sqlx::query_scalar(
+            "SELECT conversation_id FROM rustygpt.sp_import_conversation($1, NULL, $2)",
+        )
+        .bind("round trip")
+        .bind(sqlx::types::Json(&messages))
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+
+        let (again, copied): (
+            sqlx::types::Json<Vec<MessageRevision>>,
+            sqlx::types::Json<Vec<ExportedAttachment>>,
+        ) = sqlx::query_as(
+            "SELECT revisions, attachments FROM rustygpt.sp_export_conversation_messages($1)",
+        )
+        .bind(imported)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        let contents = |revisions: &[MessageRevision]| {
+            revisions
+                .iter()
+                .map(|revision| (revision.revision, revision.content.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(contents(&again), contents(&revisions));
+        assert_eq!(copied.len(), 1);
+        assert_ne!(copied[0].id, attachments[0].id);
+        assert_eq!(
+            (copied[0].file_name.as_str(), copied[0].size_bytes),
+            ("plan.pdf", 2048)
+        );
+        drop(tx);
+
+        db.destroy().await;
+    }
+}
@@ -0,0 +1,442 @@
+//! Ordering and rendering for conversation exports.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write as _,
+};
+
+use shared::{
+    llms::ThreadContextBuilder,
+    models::{
+        ConversationExport, ExportFormat, ExportedAttachment, ExportedMessage, MessageRole,
+        MessageView, Timestamp,
+    },
+};
+use uuid::Uuid;
+
+/// Order messages thread by thread (oldest root first), walking each thread depth-first with
+/// siblings in the order `ThreadContextBuilder` assigns them.
+#[must_use]
+pub fn order_messages(messages: Vec<ExportedMessage>) -> Vec<ExportedMessage> {
+    let mut by_root: HashMap<Uuid, Vec<ExportedMessage>> = HashMap::new();
+    let mut roots: Vec<(Timestamp, Uuid)> = Vec::new();
+    for message in messages {
+        if message.id == message.root_id {
+            roots.push((message.created_at.clone(), message.id));
+        }
+        by_root.entry(message.root_id).or_default().push(message);
+    }
+    roots.sort_by(|a, b| a.0.0.cmp(&b.0.0).then(a.1.cmp(&b.1)));
+
+    let mut ordered = Vec::new();
+    for (_, root_id) in roots {
+        let Some(thread) = by_root.remove(&root_id) else {
+            continue;
+        };
+        ordered.extend(order_thread(thread));
+    }
+    // Orphans whose root is outside the export still get written, after everything else.
+    let mut leftovers: Vec<Vec<ExportedMessage>> = by_root.into_values().collect();
+    leftovers.sort_by(|a, b| a[0].created_at.0.cmp(&b[0].created_at.0));
+    for thread in leftovers {
+        ordered.extend(order_thread(thread));
+    }
+    ordered
+}
+
+fn order_thread(thread: Vec<ExportedMessage>) -> Vec<ExportedMessage> {
+    let views = thread.iter().map(as_view).collect();
+    let builder = ThreadContextBuilder::new(views);
+    let mut lookup: HashMap<Uuid, ExportedMessage> = thread
+        .into_iter()
+        .map(|message| (message.id, message))
+        .collect();
+
+    let mut visited = HashSet::new();
+    let mut ordered_ids = Vec::with_capacity(lookup.len());
+    let mut stack: Vec<Uuid> = builder.root().map(|root| root.id).into_iter().collect();
+    while let Some(id) = stack.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        ordered_ids.push(id);
+        stack.extend(builder.children(id).iter().rev().map(|child| child.id));
+    }
+    for message in builder.full_thread(None) {
+        if visited.insert(message.id) {
+            ordered_ids.push(message.id);
+        }
+    }
+
+    ordered_ids
+        .into_iter()
+        .filter_map(|id| lookup.remove(&id))
+        .collect()
+}
+
+fn as_view(message: &ExportedMessage) -> MessageView {
+    MessageView {
+        id: message.id,
+        root_id: message.root_id,
+        parent_id: message.parent_id,
+        conversation_id: Uuid::nil(),
+        author_user_id: message.author_user_id,
+        role: message.role,
+        content: String::new(),
+        path: message.path.clone(),
+        depth: message.depth,
+        created_at: message.created_at.clone(),
+        alternates: Vec::new(),
+    }
+}
+
+/// Render an export in the requested format.
+///
+/// # Errors
+/// Returns an error if JSON serialization fails.
+pub fn render(export: &ConversationExport, format: ExportFormat) -> serde_json::Result<String> {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(export),
+        ExportFormat::Markdown => Ok(render_markdown(export)),
+        ExportFormat::Html => Ok(render_html(export)),
+    }
+}
+
+const fn role_label(role: MessageRole) -> &'static str {
+    match role {
+        MessageRole::User => "User",
+        MessageRole::Assistant => "Assistant",
+        MessageRole::System => "System",
+        MessageRole::Tool => "Tool",
+    }
+}
+
+fn format_time(timestamp: &Timestamp) -> String {
+    timestamp.0.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+fn edit_marker(message: &ExportedMessage) -> Option<String> {
+    if message.deleted_at.is_some() {
+        return Some("deleted".to_string());
+    }
+    let edited_at = message.edited_at.as_ref()?;
+    let mut marker = format!("edited {}", format_time(edited_at));
+    if message.revisions.len() > 1 {
+        let _ = write!(marker, ", {} revisions", message.revisions.len());
+    }
+    Some(marker)
+}
+
+fn format_size(bytes: i64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    #[allow(clippy::cast_precision_loss)]
+    // One decimal place is all the listing shows.
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+fn attachment_details(attachment: &ExportedAttachment) -> String {
+    format!(
+        "{}, {}",
+        attachment.content_type,
+        format_size(attachment.size_bytes)
+    )
+}
+
+fn indent_for(depth: i32) -> usize {
+    usize::try_from(depth.saturating_sub(1)).unwrap_or(0) * 2
+}
+
+#[must_use]
+pub fn render_markdown(export: &ConversationExport) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", export.conversation.title.trim());
+    let _ = writeln!(
+        out,
+        "_Exported {} · conversation `{}`_",
+        format_time(&export.exported_at),
+        export.conversation.id
+    );
+
+    for message in &export.messages {
+        if message.id == message.root_id {
+            let _ = write!(out, "\n---\n\n");
+        }
+        let pad = " ".repeat(indent_for(message.depth));
+        let _ = write!(
+            out,
+            "{pad}- **{}**",
+            message
+                .author_name
+                .as_deref()
+                .unwrap_or_else(|| role_label(message.role))
+        );
+        if message.author_name.is_some() {
+            let _ = write!(out, " ({})", role_label(message.role).to_lowercase());
+        }
+        let _ = write!(out, " · {}", format_time(&message.created_at));
+        if let Some(marker) = edit_marker(message) {
+            let _ = write!(out, " _({marker})_");
+        }
+        out.push_str("\n\n");
+
+        if message.deleted_at.is_some() {
+            let _ = writeln!(out, "{pad}  _[message deleted]_\n");
+            continue;
+        }
+        for line in message.content.lines() {
+            if line.trim().is_empty() {
+                out.push('\n');
+            } else {
+                let _ = writeln!(out, "{pad}  {line}");
+            }
+        }
+        out.push('\n');
+        if !message.attachments.is_empty() {
+            let _ = writeln!(out, "{pad}  Attachments:\n");
+            for attachment in &message.attachments {
+                let _ = writeln!(
+                    out,
+                    "{pad}  - `{}` ({})",
+                    attachment.file_name,
+                    attachment_details(attachment)
+                );
+            }
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+#[must_use]
+pub fn render_html(export: &ConversationExport) -> String {
+    let title = escape_html(export.conversation.title.trim());
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\nbody {{ font-family: sans-serif; max-width: 48rem; margin: 2rem auto; }}\n\
+         article {{ border-left: 2px solid #ccc; padding: 0.25rem 0.75rem; margin: 0.75rem 0; }}\n\
+         article.assistant {{ border-color: #6b8afd; }}\n\
+         header {{ font-size: 0.85rem; color: #555; }}\n\
+         .content {{ white-space: pre-wrap; }}\n\
+         .marker {{ font-style: italic; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n<p class=\"meta\">Exported {} &middot; conversation <code>{}</code></p>\n",
+        format_time(&export.exported_at),
+        export.conversation.id
+    );
+
+    let mut in_thread = false;
+    for message in &export.messages {
+        if message.id == message.root_id {
+            if in_thread {
+                out.push_str("</section>\n");
+            }
+            let _ = writeln!(
+                out,
+                "<section class=\"thread\" id=\"thread-{}\">",
+                message.id
+            );
+            in_thread = true;
+        }
+        let author = message
+            .author_name
+            .as_deref()
+            .map_or_else(|| role_label(message.role).to_string(), escape_html);
+        let _ = write!(
+            out,
+            "<article class=\"{role}\" id=\"m-{id}\" style=\"margin-left: {indent}rem\">\n<header><strong>{author}</strong> &middot; <time datetime=\"{datetime}\">{time}</time>",
+            role = message.role.as_str(),
+            id = message.id,
+            indent = indent_for(message.depth),
+            datetime = message.created_at.0.to_rfc3339(),
+            time = format_time(&message.created_at),
+        );
+        if let Some(marker) = edit_marker(message) {
+            let _ = write!(
+                out,
+                " <span class=\"marker\">({})</span>",
+                escape_html(&marker)
+            );
+        }
+        out.push_str("</header>\n");
+        if message.deleted_at.is_some() {
+            out.push_str("<div class=\"content marker\">[message deleted]</div>\n");
+        } else {
+            let _ = writeln!(
+                out,
+                "<div class=\"content\">{}</div>",
+                escape_html(&message.content)
+            );
+        }
+        if !message.attachments.is_empty() {
+            out.push_str("<ul class=\"attachments\">\n");
+            for attachment in &message.attachments {
+                let _ = writeln!(
+                    out,
+                    "<li>{} <span class=\"marker\">({})</span></li>",
+                    escape_html(&attachment.file_name),
+                    escape_html(&attachment_details(attachment))
+                );
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</article>\n");
+    }
+    if in_thread {
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use shared::models::{CONVERSATION_EXPORT_VERSION, ExportedConversation};
+
+    fn message(
+        id: Uuid,
+        root_id: Uuid,
+        parent_id: Option<Uuid>,
+        path: &str,
+        minutes: i64,
+    ) -> ExportedMessage {
+        let base = Utc.with_ymd_and_hms(2025, 1, 2, 3, 4, 0).unwrap();
+        ExportedMessage {
+            id,
+            root_id,
+            parent_id,
+            role: MessageRole::User,
+            author_user_id: None,
+            author_name: None,
+            content: format!("message {path}"),
+            path: path.to_string(),
+            depth: i32::try_from(path.split('.').count()).unwrap_or(1),
+            created_at: Timestamp(base + Duration::minutes(minutes)),
+            edited_at: None,
+            edit_reason: None,
+            deleted_at: None,
+            forked_from_message_id: None,
+            revisions: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+
+    fn export(messages: Vec<ExportedMessage>) -> ConversationExport {
+        let now = Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap();
+        ConversationExport {
+            version: CONVERSATION_EXPORT_VERSION,
+            exported_at: Timestamp(now),
+            conversation: ExportedConversation {
+                id: Uuid::nil(),
+                title: "Planning <draft>".into(),
+                is_group: false,
+                created_by: Uuid::nil(),
+                created_at: Timestamp(now),
+                archived_at: None,
+            },
+            root_id: None,
+            messages,
+        }
+    }
+
+    #[test]
+    fn order_messages_walks_branches_depth_first() {
+        let root = Uuid::new_v4();
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let a_child = Uuid::new_v4();
+        let later_root = Uuid::new_v4();
+
+        let ordered = order_messages(vec![
+            message(later_root, later_root, None, "z", 10),
+            message(b, root, Some(root), "r.b", 3),
+            message(a_child, root, Some(a), "r.a.c", 4),
+            message(a, root, Some(root), "r.a", 2),
+            message(root, root, None, "r", 1),
+        ]);
+
+        let ids: Vec<Uuid> = ordered.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![root, a, a_child, b, later_root]);
+    }
+
+    #[test]
+    fn markdown_nests_replies_and_marks_edits() {
+        let root = Uuid::new_v4();
+        let reply = Uuid::new_v4();
+        let mut edited = message(reply, root, Some(root), "r.a", 1);
+        edited.role = MessageRole::Assistant;
+        edited.edited_at = Some(edited.created_at.clone());
+        let rendered = render_markdown(&export(vec![message(root, root, None, "r", 0), edited]));
+
+        assert!(rendered.starts_with("# Planning <draft>\n"));
+        assert!(rendered.contains("- **User** · 2025-01-02 03:04 UTC\n\n  message r\n"));
+        assert!(rendered.contains("  - **Assistant** · 2025-01-02 03:05 UTC _(edited"));
+        assert!(rendered.contains("\n    message r.a\n"));
+    }
+
+    #[test]
+    fn html_escapes_content_and_hides_deleted_messages() {
+        let root = Uuid::new_v4();
+        let mut first = message(root, root, None, "r", 0);
+        first.content = "<script>alert('x')</script>".into();
+        let mut deleted = message(Uuid::new_v4(), root, Some(root), "r.a", 1);
+        deleted.deleted_at = Some(deleted.created_at.clone());
+        deleted.content = String::new();
+
+        let rendered = render_html(&export(vec![first, deleted]));
+        assert!(rendered.contains("<title>Planning &lt;draft&gt;</title>"));
+        assert!(rendered.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"));
+        assert!(!rendered.contains("<script>"));
+        assert!(rendered.contains("[message deleted]"));
+    }
+
+    #[test]
+    fn attachments_are_listed_with_type_and_size() {
+        let root = Uuid::new_v4();
+        let mut first = message(root, root, None, "r", 0);
+        first.attachments.push(ExportedAttachment {
+            id: Uuid::new_v4(),
+            file_name: "notes <v2>.pdf".into(),
+            content_type: "application/pdf".into(),
+            size_bytes: 3 * 1024 * 1024 / 2,
+            created_at: first.created_at.clone(),
+        });
+        let export = export(vec![first]);
+
+        assert!(
+            render_markdown(&export)
+                .contains("  Attachments:\n\n  - `notes <v2>.pdf` (application/pdf, 1.5 MiB)\n")
+        );
+        assert!(render_html(&export).contains(
+            "<li>notes &lt;v2&gt;.pdf <span class=\"marker\">(application/pdf, 1.5 MiB)</span></li>"
+        ));
+        assert_eq!(format_size(512), "512 B");
+    }
+}
@@ -0,0 +1,612 @@
+//! User groups: named sets of users granted a role on conversations as a unit. Effective
+//! access is computed in the database; every write here reports the (conversation, user) pairs
+//! whose access changed so the caller can announce them.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use shared::models::{
+    ConversationGroupGrant, ConversationRole, CreateUserGroupRequest, MembershipChangeAction,
+    MembershipChangedEvent, Timestamp, UpdateUserGroupRequest, UserGroup, UserGroupMember,
+};
+
+use crate::services::{
+    audit_service::AuditContext,
+    chat_service::{ChatServiceError, ChatServiceResult},
+};
+
+/// A user whose access to a conversation changed through a group write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessChange {
+    pub conversation_id: Uuid,
+    pub user_id: Uuid,
+    /// The user's effective role before the write, `None` without access.
+    pub previous_role: Option<ConversationRole>,
+    /// The user's effective role afterwards, `None` once they lost access.
+    pub role: Option<ConversationRole>,
+}
+
+impl AccessChange {
+    /// The `membership.changed` event announcing this change, `None` when the user's
+    /// effective role did not move (for instance because they are also a direct participant).
+    pub fn membership_event(&self) -> Option<MembershipChangedEvent> {
+        let (role, action) = match (self.previous_role, self.role) {
+            (None, Some(role)) => (role, MembershipChangeAction::Added),
+            (Some(previous), None) => (previous, MembershipChangeAction::Removed),
+            (Some(previous), Some(role)) if previous != role => {
+                (role, MembershipChangeAction::RoleUpdated)
+            }
+            _ => return None,
+        };
+        Some(MembershipChangedEvent {
+            conversation_id: self.conversation_id,
+            user_id: self.user_id,
+            role: Some(role),
+            action,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AccessChangeRow {
+    conversation_id: Uuid,
+    user_id: Uuid,
+    previous_role: Option<String>,
+    role: Option<String>,
+}
+
+fn parse_role(role: Option<String>) -> Option<ConversationRole> {
+    role.and_then(|role| ConversationRole::try_from(role.as_str()).ok())
+}
+
+impl From<AccessChangeRow> for AccessChange {
+    fn from(row: AccessChangeRow) -> Self {
+        Self {
+            conversation_id: row.conversation_id,
+            user_id: row.user_id,
+            previous_role: parse_role(row.previous_role),
+            role: parse_role(row.role),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct GroupRow {
+    id: Uuid,
+    name: String,
+    description: Option<String>,
+    member_count: i64,
+    is_manager: Option<bool>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<GroupRow> for UserGroup {
+    fn from(row: GroupRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            member_count: u64::try_from(row.member_count).unwrap_or(0),
+            is_manager: row.is_manager,
+            created_at: Timestamp(row.created_at),
+            updated_at: Timestamp(row.updated_at),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct MemberRow {
+    user_id: Uuid,
+    username: String,
+    email: String,
+    display_name: Option<String>,
+    is_manager: bool,
+    added_at: DateTime<Utc>,
+}
+
+impl From<MemberRow> for UserGroupMember {
+    fn from(row: MemberRow) -> Self {
+        Self {
+            user_id: row.user_id,
+            username: row.username,
+            email: row.email,
+            display_name: row.display_name,
+            is_manager: row.is_manager,
+            added_at: Timestamp(row.added_at),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct GrantRow {
+    group_id: Uuid,
+    name: String,
+    role: String,
+    member_count: i64,
+    granted_at: DateTime<Utc>,
+}
+
+impl From<GrantRow> for ConversationGroupGrant {
+    fn from(row: GrantRow) -> Self {
+        Self {
+            group_id: row.group_id,
+            name: row.name,
+            role: ConversationRole::try_from(row.role.as_str()).unwrap_or(ConversationRole::Member),
+            member_count: u64::try_from(row.member_count).unwrap_or(0),
+            granted_at: Timestamp(row.granted_at),
+        }
+    }
+}
+
+const LIST_COLUMNS: &str = "SELECT id, name, description, member_count, is_manager, \
+     created_at, updated_at FROM rustygpt.sp_group_list($1, $2)";
+
+fn validate_name(name: &str) -> ChatServiceResult<()> {
+    if name.trim().is_empty() {
+        return Err(ChatServiceError::Validation(
+            "group name must not be blank".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct GroupService {
+    pool: PgPool,
+}
+
+impl GroupService {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Groups `user_id` belongs to, or every group when `None`.
+    #[instrument(name = "group.list", skip(self), err)]
+    pub async fn list(&self, user_id: Option<Uuid>) -> ChatServiceResult<Vec<UserGroup>> {
+        let rows = sqlx::query_as::<_, GroupRow>(LIST_COLUMNS)
+            .bind(user_id)
+            .bind(None::<Uuid>)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(rows.into_iter().map(UserGroup::from).collect())
+    }
+
+    /// One group as seen by `viewer`: `None` when the viewer is not a member. Pass no viewer
+    /// to read it regardless of membership.
+    #[instrument(name = "group.get", skip(self), err)]
+    pub async fn get(
+        &self,
+        group_id: Uuid,
+        viewer: Option<Uuid>,
+    ) -> ChatServiceResult<Option<UserGroup>> {
+        let row = sqlx::query_as::<_, GroupRow>(LIST_COLUMNS)
+            .bind(viewer)
+            .bind(Some(group_id))
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(row.map(UserGroup::from))
+    }
+
+    /// Creates a group managed by the audited actor.
+    #[instrument(name = "group.create", skip(self, audit, request), err)]
+    pub async fn create(
+        &self,
+        audit: &AuditContext,
+        creator: Uuid,
+        request: &CreateUserGroupRequest,
+    ) -> ChatServiceResult<UserGroup> {
+        validate_name(&request.name)?;
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let group_id = sqlx::query_scalar::<_, Uuid>("SELECT rustygpt.sp_group_create($1, $2, $3)")
+            .bind(&request.name)
+            .bind(request.description.as_deref())
+            .bind(creator)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        self.get(group_id, Some(creator))
+            .await?
+            .ok_or_else(|| ChatServiceError::NotFound("group not found".to_string()))
+    }
+
+    #[instrument(name = "group.update", skip(self, audit, request), err)]
+    pub async fn update(
+        &self,
+        audit: &AuditContext,
+        group_id: Uuid,
+        request: &UpdateUserGroupRequest,
+    ) -> ChatServiceResult<()> {
+        validate_name(&request.name)?;
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        sqlx::query("SELECT rustygpt.sp_group_update($1, $2, $3)")
+            .bind(group_id)
+            .bind(&request.name)
+            .bind(request.description.as_deref())
+            .execute(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(())
+    }
+
+    /// Deletes a group along with its conversation grants.
+    #[instrument(name = "group.delete", skip(self, audit), err)]
+    pub async fn delete(
+        &self,
+        audit: &AuditContext,
+        group_id: Uuid,
+    ) -> ChatServiceResult<Vec<AccessChange>> {
+        self.change_access(
+            audit,
+            sqlx::query_as::<_, AccessChangeRow>(
+                "SELECT conversation_id, user_id, previous_role, role
+                 FROM rustygpt.sp_group_delete($1)",
+            )
+            .bind(group_id),
+        )
+        .await
+    }
+
+    #[instrument(name = "group.members", skip(self), err)]
+    pub async fn members(&self, group_id: Uuid) -> ChatServiceResult<Vec<UserGroupMember>> {
+        let rows = sqlx::query_as::<_, MemberRow>(
+            "SELECT user_id, username, email, display_name, is_manager, added_at
+             FROM rustygpt.sp_group_member_list($1)",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        Ok(rows.into_iter().map(UserGroupMember::from).collect())
+    }
+
+    /// Adds a member or changes whether they manage the group. A new member gains access to
+    /// the conversations the group is granted on.
+    #[instrument(name = "group.member_put", skip(self, audit), err)]
+    pub async fn put_member(
+        &self,
+        audit: &AuditContext,
+        group_id: Uuid,
+        user_id: Uuid,
+        is_manager: bool,
+    ) -> ChatServiceResult<Vec<AccessChange>> {
+        self.change_access(
+            audit,
+            sqlx::query_as::<_, AccessChangeRow>(
+                "SELECT conversation_id, user_id, previous_role, role
+                 FROM rustygpt.sp_group_member_put($1, $2, $3)",
+            )
+            .bind(group_id)
+            .bind(user_id)
+            .bind(is_manager),
+        )
+        .await
+    }
+
+    #[instrument(name = "group.member_remove", skip(self, audit), err)]
+    pub async fn remove_member(
+        &self,
+        audit: &AuditContext,
+        group_id: Uuid,
+        user_id: Uuid,
+    ) -> ChatServiceResult<Vec<AccessChange>> {
+        self.change_access(
+            audit,
+            sqlx::query_as::<_, AccessChangeRow>(
+                "SELECT conversation_id, user_id, previous_role, role
+                 FROM rustygpt.sp_group_member_remove($1, $2)",
+            )
+            .bind(group_id)
+            .bind(user_id),
+        )
+        .await
+    }
+
+    /// Groups granted on a conversation; the audited actor must have access to it.
+    #[instrument(name = "group.conversation_grants", skip(self, audit), err)]
+    pub async fn conversation_grants(
+        &self,
+        audit: &AuditContext,
+        conversation_id: Uuid,
+    ) -> ChatServiceResult<Vec<ConversationGroupGrant>> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let rows = sqlx::query_as::<_, GrantRow>(
+            "SELECT group_id, name, role, member_count, granted_at
+             FROM rustygpt.sp_conversation_group_list($1)",
+        )
+        .bind(conversation_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(rows.into_iter().map(ConversationGroupGrant::from).collect())
+    }
+
+    /// Grants a group `role` on the conversation, or changes the role of an existing grant.
+    /// The audited actor must be an owner or admin there and a member of the group.
+    #[instrument(name = "group.grant", skip(self, audit), err)]
+    pub async fn grant(
+        &self,
+        audit: &AuditContext,
+        conversation_id: Uuid,
+        group_id: Uuid,
+        role: ConversationRole,
+    ) -> ChatServiceResult<Vec<AccessChange>> {
+        self.change_access(
+            audit,
+            sqlx::query_as::<_, AccessChangeRow>(
+                "SELECT conversation_id, user_id, previous_role, role
+                 FROM rustygpt.sp_conversation_group_grant($1, $2, $3)",
+            )
+            .bind(conversation_id)
+            .bind(group_id)
+            .bind(role.as_str()),
+        )
+        .await
+    }
+
+    #[instrument(name = "group.revoke", skip(self, audit), err)]
+    pub async fn revoke(
+        &self,
+        audit: &AuditContext,
+        conversation_id: Uuid,
+        group_id: Uuid,
+    ) -> ChatServiceResult<Vec<AccessChange>> {
+        self.change_access(
+            audit,
+            sqlx::query_as::<_, AccessChangeRow>(
+                "SELECT conversation_id, user_id, previous_role, role
+                 FROM rustygpt.sp_conversation_group_revoke($1, $2)",
+            )
+            .bind(conversation_id)
+            .bind(group_id),
+        )
+        .await
+    }
+
+    async fn change_access(
+        &self,
+        audit: &AuditContext,
+        query: sqlx::query::QueryAs<
+            '_,
+            sqlx::Postgres,
+            AccessChangeRow,
+            sqlx::postgres::PgArguments,
+        >,
+    ) -> ChatServiceResult<Vec<AccessChange>> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let rows = query
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(rows.into_iter().map(AccessChange::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::TestDatabase;
+
+    #[test]
+    fn lost_access_has_no_role() {
+        let change = AccessChange::from(AccessChangeRow {
+            conversation_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            previous_role: Some("member".to_string()),
+            role: None,
+        });
+        assert_eq!(change.previous_role, Some(ConversationRole::Member));
+        assert_eq!(change.role, None);
+
+        let change = AccessChange::from(AccessChangeRow {
+            conversation_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            previous_role: None,
+            role: Some("admin".to_string()),
+        });
+        assert_eq!(change.role, Some(ConversationRole::Admin));
+    }
+
+    #[test]
+    fn only_moved_roles_are_announced() {
+        let change = |previous_role, role| AccessChange {
+            conversation_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            previous_role,
+            role,
+        };
+        let action = |previous_role, role| {
+            change(previous_role, role)
+                .membership_event()
+                .map(|event| (event.action, event.role))
+        };
+
+        assert_eq!(
+            action(None, Some(ConversationRole::Viewer)),
+            Some((
+                MembershipChangeAction::Added,
+                Some(ConversationRole::Viewer)
+            ))
+        );
+        assert_eq!(
+            action(Some(ConversationRole::Member), None),
+            Some((
+                MembershipChangeAction::Removed,
+                Some(ConversationRole::Member)
+            ))
+        );
+        assert_eq!(
+            action(
+                Some(ConversationRole::Member),
+                Some(ConversationRole::Admin)
+            ),
+            Some((
+                MembershipChangeAction::RoleUpdated,
+                Some(ConversationRole::Admin)
+            ))
+        );
+        assert_eq!(
+            action(Some(ConversationRole::Owner), Some(ConversationRole::Owner)),
+            None
+        );
+        assert_eq!(action(None, None), None);
+    }
+
+    async fn role_of(db: &TestDatabase, user: Uuid, conversation: Uuid) -> Option<String> {
+        sqlx::query_scalar("SELECT rustygpt.sp_user_conversation_role($1, $2)::TEXT")
+            .bind(user)
+            .bind(conversation)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap()
+    }
+
+    async fn grant_group(db: &TestDatabase, conversation: Uuid, member: Uuid, role: &str) {
+        sqlx::query(
+            "WITH g AS (
+                 INSERT INTO rustygpt.user_groups (name) VALUES ($1) RETURNING id
+             ), m AS (
+                 INSERT INTO rustygpt.user_group_members (group_id, user_id)
+                 SELECT id, $2 FROM g
+             )
+             INSERT INTO rustygpt.conversation_group_grants (conversation_id, group_id, role)
+             SELECT $3, id, $4::rustygpt.conversation_role FROM g",
+        )
+        .bind(format!("group-{}", Uuid::new_v4().simple()))
+        .bind(member)
+        .bind(conversation)
+        .bind(role)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn conversation_role_is_the_strongest_grant() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("owner").await;
+        let alice = db.create_user("alice").await;
+        let stranger = db.create_user("stranger").await;
+        let conversation = db.create_conversation(owner, "roles").await;
+
+        let mut tx = db.begin_as(owner).await;
+        sqlx::query("SELECT rustygpt.sp_add_participant($1, $2, 'viewer')")
+            .bind(conversation)
+            .bind(alice)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(
+            role_of(&db, alice, conversation).await.as_deref(),
+            Some("viewer")
+        );
+
+        grant_group(&db, conversation, alice, "admin").await;
+        assert_eq!(
+            role_of(&db, alice, conversation).await.as_deref(),
+            Some("admin")
+        );
+        assert_eq!(
+            role_of(&db, owner, conversation).await.as_deref(),
+            Some("owner")
+        );
+        assert_eq!(role_of(&db, stranger, conversation).await, None);
+
+        db.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn group_roles_pass_membership_checks() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("owner").await;
+        let alice = db.create_user("alice").await;
+        let bob = db.create_user("bob").await;
+        let conversation = db.create_conversation(owner, "roles").await;
+        grant_group(&db, conversation, alice, "admin").await;
+
+        let mut tx = db.begin_as(alice).await;
+        let role: String =
+            sqlx::query_scalar("SELECT rustygpt.sp_add_participant($1, $2, 'member')::TEXT")
+                .bind(conversation)
+                .bind(bob)
+                .fetch_one(&mut *tx)
+                .await
+                .expect("a group admin may add participants");
+        tx.commit().await.unwrap();
+        assert_eq!(role, "member");
+
+        let mut tx = db.begin_as(bob).await;
+        let err = sqlx::query("SELECT rustygpt.sp_remove_participant($1, $2)")
+            .bind(conversation)
+            .bind(owner)
+            .execute(&mut *tx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("RGP.403"), "{err}");
+        drop(tx);
+
+        db.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn organization_conversations_require_membership_for_any_role() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let owner = db.create_user("owner").await;
+        let alice = db.create_user("alice").await;
+        let conversation = db.create_conversation(owner, "roles").await;
+        let org: Uuid = sqlx::query_scalar(
+            "INSERT INTO rustygpt.organizations (slug, name) VALUES ('acme', 'Acme') RETURNING id",
+        )
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE rustygpt.conversations SET org_id = $1 WHERE id = $2")
+            .bind(org)
+            .bind(conversation)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        grant_group(&db, conversation, alice, "member").await;
+        assert_eq!(role_of(&db, alice, conversation).await, None);
+
+        sqlx::query("INSERT INTO rustygpt.organization_members (org_id, user_id) VALUES ($1, $2)")
+            .bind(org)
+            .bind(alice)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            role_of(&db, alice, conversation).await.as_deref(),
+            Some("member")
+        );
+
+        db.destroy().await;
+    }
+}
@@ -0,0 +1,147 @@
+//! Plain-text email templates.
+//!
+//! Each template under `templates/mail/` starts with a `Subject:` line, followed by a blank line
+//! and the body. `{{name}}` placeholders are replaced when the message is rendered.
+
+/// Templates known to the outbox; the name is stored with every queued message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MailTemplate {
+    PasswordReset,
+    EmailVerification,
+    ConversationInvite,
+    AccountLocked,
+}
+
+impl MailTemplate {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::PasswordReset => "password_reset",
+            Self::EmailVerification => "email_verification",
+            Self::ConversationInvite => "conversation_invite",
+            Self::AccountLocked => "account_locked",
+        }
+    }
+
+    const fn source(self) -> &'static str {
+        match self {
+            Self::PasswordReset => include_str!("../../templates/mail/password_reset.txt"),
+            Self::EmailVerification => {
+                include_str!("../../templates/mail/email_verification.txt")
+            }
+            Self::ConversationInvite => {
+                include_str!("../../templates/mail/conversation_invite.txt")
+            }
+            Self::AccountLocked => include_str!("../../templates/mail/account_locked.txt"),
+        }
+    }
+
+    /// Renders the template for `to`, substituting each `(name, value)` pair.
+    #[must_use]
+    pub fn render(self, to: &str, vars: &[(&str, &str)]) -> RenderedMail {
+        let source = self.source();
+        let (subject, body) = source
+            .split_once('\n')
+            .and_then(|(first, rest)| {
+                first
+                    .strip_prefix("Subject:")
+                    .map(|subject| (subject.trim(), rest.trim_start_matches(['\r', '\n'])))
+            })
+            .unwrap_or(("", source));
+
+        RenderedMail {
+            template: self.name(),
+            to: to.to_string(),
+            subject: substitute(subject, vars),
+            body: substitute(body, vars),
+        }
+    }
+}
+
+/// A message ready to be queued.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedMail {
+    pub template: &'static str,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+fn substitute(text: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter().fold(text.to_string(), |acc, (name, value)| {
+        acc.replace(&format!("{{{{{name}}}}}"), value)
+    })
+}
+
+/// Formats a token lifetime for message bodies, e.g. `1 hour` or `30 minutes`.
+#[must_use]
+pub fn describe_ttl(seconds: u64) -> String {
+    let (value, unit) = if seconds >= 86_400 && seconds.is_multiple_of(86_400) {
+        (seconds / 86_400, "day")
+    } else if seconds >= 3_600 && seconds.is_multiple_of(3_600) {
+        (seconds / 3_600, "hour")
+    } else if seconds >= 60 {
+        (seconds / 60, "minute")
+    } else {
+        (seconds, "second")
+    };
+    if value == 1 {
+        format!("1 {unit}")
+    } else {
+        format!("{value} {unit}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_template_has_a_subject_and_body() {
+        for template in [
+            MailTemplate::PasswordReset,
+            MailTemplate::EmailVerification,
+            MailTemplate::ConversationInvite,
+            MailTemplate::AccountLocked,
+        ] {
+            let mail = template.render("user@example.com", &[]);
+            assert!(
+                !mail.subject.is_empty(),
+                "{} has no subject",
+                template.name()
+            );
+            assert!(!mail.body.starts_with("Subject:"));
+            assert!(!mail.body.starts_with('\n'));
+        }
+    }
+
+    #[test]
+    fn render_substitutes_placeholders() {
+        let mail = MailTemplate::PasswordReset.render(
+            "user@example.com",
+            &[
+                ("name", "Ada"),
+                ("link", "https://chat.example.com/reset-password?token=abc"),
+                ("expires_in", "1 hour"),
+            ],
+        );
+        assert_eq!(mail.template, "password_reset");
+        assert_eq!(mail.to, "user@example.com");
+        assert!(mail.body.starts_with("Hi Ada,"));
+        assert!(
+            mail.body
+                .contains("https://chat.example.com/reset-password?token=abc")
+        );
+        assert!(mail.body.contains("expires in 1 hour"));
+        assert!(!mail.body.contains("{{"));
+    }
+
+    #[test]
+    fn describe_ttl_picks_the_largest_whole_unit() {
+        assert_eq!(describe_ttl(86_400), "1 day");
+        assert_eq!(describe_ttl(3_600), "1 hour");
+        assert_eq!(describe_ttl(7_200), "2 hours");
+        assert_eq!(describe_ttl(5_400), "90 minutes");
+        assert_eq!(describe_ttl(30), "30 seconds");
+    }
+}
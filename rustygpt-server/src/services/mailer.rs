@@ -0,0 +1,374 @@
+//! Outgoing mail.
+//!
+//! Messages are rendered and written to `rustygpt.mail_outbox` inside the transaction that
+//! caused them, so an email is only sent when the change that triggered it commits. A
+//! background worker ([`MailOutbox::deliver_due`]) drains the queue through the configured
+//! [`MailTransport`], retrying failures with backoff.
+
+use std::{path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    address::AddressError,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use shared::config::server::{MailConfig, MailTransportKind};
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::services::mail_templates::RenderedMail;
+
+const MAIL_METRIC_NAME: &str = "mail_outbox_deliveries_total";
+const CLAIM_BATCH_SIZE: i32 = 20;
+/// How long a claimed message stays hidden from other workers before it is retried.
+const CLAIM_LEASE_SECONDS: i32 = 300;
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("invalid mail address '{address}': {source}")]
+    Address {
+        address: String,
+        #[source]
+        source: AddressError,
+    },
+    #[error("failed to build message: {0}")]
+    Message(#[from] lettre::error::Error),
+    #[error("SMTP delivery failed: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+    #[error("failed to write message: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A queued message handed to a transport.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutgoingMail {
+    pub id: Uuid,
+    pub template: String,
+    pub to_address: String,
+    pub subject: String,
+    pub body_text: String,
+    pub attempts: i32,
+}
+
+/// Delivery backend for queued mail.
+#[async_trait]
+pub trait MailTransport: Send + Sync {
+    async fn send(&self, mail: &OutgoingMail) -> Result<(), MailError>;
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, MailError> {
+    address.parse().map_err(|source| MailError::Address {
+        address: address.to_string(),
+        source,
+    })
+}
+
+fn build_message(from: &Mailbox, mail: &OutgoingMail) -> Result<Message, MailError> {
+    Ok(Message::builder()
+        .from(from.clone())
+        .to(parse_mailbox(&mail.to_address)?)
+        .subject(mail.subject.clone())
+        .header(ContentType::TEXT_PLAIN)
+        .body(mail.body_text.clone())?)
+}
+
+/// Relays mail through an SMTP server.
+pub struct SmtpMailTransport {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailTransport {
+    /// Connects with STARTTLS, or over implicit TLS when `starttls` is off.
+    ///
+    /// # Errors
+    /// Returns an error if the sender address or relay host is invalid.
+    pub fn new(config: &MailConfig) -> Result<Self, MailError> {
+        let smtp = &config.smtp;
+        let builder = if smtp.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)?
+        };
+        let builder = match &smtp.username {
+            Some(username) => builder.credentials(Credentials::new(
+                username.clone(),
+                smtp.password.expose().to_string(),
+            )),
+            None => builder,
+        };
+
+        Ok(Self {
+            from: parse_mailbox(&config.from)?,
+            transport: builder.port(smtp.port).build(),
+        })
+    }
+}
+
+#[async_trait]
+impl MailTransport for SmtpMailTransport {
+    async fn send(&self, mail: &OutgoingMail) -> Result<(), MailError> {
+        let message = build_message(&self.from, mail)?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+/// Writes each message to `<dir>/<id>.eml`; handy for local development and tests.
+pub struct FileMailTransport {
+    from: Mailbox,
+    dir: PathBuf,
+}
+
+impl FileMailTransport {
+    /// # Errors
+    /// Returns an error if the sender address is invalid.
+    pub fn new(config: &MailConfig) -> Result<Self, MailError> {
+        Ok(Self {
+            from: parse_mailbox(&config.from)?,
+            dir: config.file_dir.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl MailTransport for FileMailTransport {
+    async fn send(&self, mail: &OutgoingMail) -> Result<(), MailError> {
+        let message = build_message(&self.from, mail)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(
+            self.dir.join(format!("{}.eml", mail.id)),
+            message.formatted(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Logs each message instead of sending it.
+pub struct StdoutMailTransport;
+
+#[async_trait]
+impl MailTransport for StdoutMailTransport {
+    async fn send(&self, mail: &OutgoingMail) -> Result<(), MailError> {
+        info!(
+            mail_id = %mail.id,
+            template = %mail.template,
+            to = %mail.to_address,
+            subject = %mail.subject,
+            body = %mail.body_text,
+            "outgoing mail"
+        );
+        Ok(())
+    }
+}
+
+/// Builds the transport selected by `mail.transport`.
+///
+/// # Errors
+/// Returns an error if the configured sender address or SMTP relay is invalid.
+pub fn transport_from_config(config: &MailConfig) -> Result<Arc<dyn MailTransport>, MailError> {
+    Ok(match config.transport {
+        MailTransportKind::Smtp => Arc::new(SmtpMailTransport::new(config)?),
+        MailTransportKind::File => Arc::new(FileMailTransport::new(config)?),
+        MailTransportKind::Stdout => Arc::new(StdoutMailTransport),
+    })
+}
+
+/// Queues `mail` on `conn`, so it is only sent if the surrounding transaction commits.
+pub(crate) async fn enqueue_on(
+    conn: &mut PgConnection,
+    mail: &RenderedMail,
+) -> Result<Uuid, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>("SELECT rustygpt.sp_mail_enqueue($1, $2, $3, $4)")
+        .bind(mail.template)
+        .bind(&mail.to)
+        .bind(&mail.subject)
+        .bind(&mail.body)
+        .fetch_one(conn)
+        .await
+}
+
+/// Drains the outbox through a transport.
+#[derive(Clone)]
+pub struct MailOutbox {
+    pool: PgPool,
+    transport: Arc<dyn MailTransport>,
+    max_attempts: i32,
+}
+
+impl MailOutbox {
+    #[must_use]
+    pub fn new(pool: PgPool, transport: Arc<dyn MailTransport>, config: &MailConfig) -> Self {
+        Self {
+            pool,
+            transport,
+            max_attempts: i32::try_from(config.max_attempts).unwrap_or(i32::MAX),
+        }
+    }
+
+    /// Sends every message that is due, returning how many were delivered.
+    #[instrument(name = "mail.deliver_due", skip(self), err)]
+    pub async fn deliver_due(&self) -> Result<usize, sqlx::Error> {
+        let mut delivered = 0;
+        loop {
+            let batch = sqlx::query_as::<_, OutgoingMail>(
+                "SELECT id, template, to_address, subject, body_text, attempts
+                 FROM rustygpt.sp_mail_claim($1, $2)",
+            )
+            .bind(CLAIM_BATCH_SIZE)
+            .bind(CLAIM_LEASE_SECONDS)
+            .fetch_all(&self.pool)
+            .await?;
+            if batch.is_empty() {
+                return Ok(delivered);
+            }
+
+            for mail in &batch {
+                if self.deliver(mail).await? {
+                    delivered += 1;
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, mail: &OutgoingMail) -> Result<bool, sqlx::Error> {
+        match self.transport.send(mail).await {
+            Ok(()) => {
+                sqlx::query("SELECT rustygpt.sp_mail_mark_sent($1)")
+                    .bind(mail.id)
+                    .execute(&self.pool)
+                    .await?;
+                record_delivery_metric("sent");
+                Ok(true)
+            }
+            Err(err) => {
+                let retrying = sqlx::query_scalar::<_, bool>(
+                    "SELECT rustygpt.sp_mail_mark_failed($1, $2, $3)",
+                )
+                .bind(mail.id)
+                .bind(err.to_string())
+                .bind(self.max_attempts)
+                .fetch_one(&self.pool)
+                .await?;
+                warn!(
+                    mail_id = %mail.id,
+                    template = %mail.template,
+                    attempt = mail.attempts + 1,
+                    retrying,
+                    error = %err,
+                    "mail delivery failed"
+                );
+                record_delivery_metric(if retrying { "retry" } else { "failed" });
+                Ok(false)
+            }
+        }
+    }
+}
+
+fn record_delivery_metric(result: &'static str) {
+    metrics::counter!(
+        MAIL_METRIC_NAME,
+        "result" => result.to_string()
+    )
+    .increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_mail() -> OutgoingMail {
+        OutgoingMail {
+            id: Uuid::new_v4(),
+            template: "password_reset".into(),
+            to_address: "user@example.com".into(),
+            subject: "Reset your password".into(),
+            body_text: "Hi there".into(),
+            attempts: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn file_transport_writes_one_eml_per_message() {
+        let dir = std::env::temp_dir().join(format!("rustygpt-mail-{}", Uuid::new_v4()));
+        let config = MailConfig {
+            transport: MailTransportKind::File,
+            file_dir: dir.clone(),
+            ..MailConfig::default()
+        };
+        let transport = transport_from_config(&config).expect("file transport");
+        let mail = sample_mail();
+
+        transport.send(&mail).await.expect("send");
+
+        let written = std::fs::read_to_string(dir.join(format!("{}.eml", mail.id))).expect("eml");
+        assert!(written.contains("To: user@example.com"));
+        assert!(written.contains("Subject: Reset your password"));
+        assert!(written.contains("Hi there"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn invalid_recipient_is_reported() {
+        let transport = FileMailTransport::new(&MailConfig::default()).expect("file transport");
+        let mail = OutgoingMail {
+            to_address: "not an address".into(),
+            ..sample_mail()
+        };
+
+        let err = transport.send(&mail).await.expect_err("invalid address");
+        assert!(matches!(err, MailError::Address { .. }));
+    }
+
+    struct FailingTransport;
+
+    #[async_trait]
+    impl MailTransport for FailingTransport {
+        async fn send(&self, _mail: &OutgoingMail) -> Result<(), MailError> {
+            Err(MailError::Io(std::io::Error::other("relay unavailable")))
+        }
+    }
+
+    async fn queued_body(pool: &PgPool, id: Uuid) -> String {
+        sqlx::query_scalar("SELECT body_text FROM rustygpt.mail_outbox WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn bodies_are_cleared_once_mail_leaves_the_queue() {
+        let Some(db) = crate::db::test_support::TestDatabase::create().await else {
+            return;
+        };
+        let config = MailConfig {
+            max_attempts: 1,
+            ..MailConfig::default()
+        };
+        let enqueue = |template: &'static str| {
+            sqlx::query_scalar::<_, Uuid>(
+                "SELECT rustygpt.sp_mail_enqueue($1, 'user@example.com', 'Subject', 'token=secret')",
+            )
+            .bind(template)
+            .fetch_one(&db.pool)
+        };
+
+        let sent = enqueue("password_reset").await.unwrap();
+        let outbox = MailOutbox::new(db.pool.clone(), Arc::new(StdoutMailTransport), &config);
+        assert_eq!(outbox.deliver_due().await.unwrap(), 1);
+        assert_eq!(queued_body(&db.pool, sent).await, "");
+
+        let abandoned = enqueue("email_verification").await.unwrap();
+        let outbox = MailOutbox::new(db.pool.clone(), Arc::new(FailingTransport), &config);
+        assert_eq!(outbox.deliver_due().await.unwrap(), 0);
+        assert_eq!(queued_body(&db.pool, abandoned).await, "");
+
+        db.destroy().await;
+    }
+}
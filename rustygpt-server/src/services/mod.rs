@@ -1,9 +1,21 @@
 /// Database services for chat functionality
 pub mod assistant_service;
+pub mod audit_service;
 pub mod chat_service;
+pub mod conversation_export;
+pub mod group_service;
+pub mod mail_templates;
+pub mod mailer;
 pub mod oauth_service;
 pub mod oauth_service_trait;
+pub mod organization_service;
+pub mod preferences_service;
+pub mod quota_service;
 pub mod setup;
 pub mod sse_persistence;
+pub mod stream_relay;
 pub mod stream_supervisor;
+pub mod usage_service;
 pub mod user_service;
+pub mod webhook_outbox;
+pub mod webhook_service;
@@ -0,0 +1,368 @@
+//! Organizations: tenants that own conversations, their memberships and the per-organization
+//! policy (models, features) applied to those conversations. Membership is also enforced in
+//! the database, by row-level security and triggers; the checks here decide who may manage
+//! an organization and what the application offers inside it.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use shared::models::{
+    CreateOrganizationRequest, OrgRole, Organization, OrganizationFeatures, OrganizationMember,
+    Timestamp, UpdateOrganizationRequest,
+};
+
+use crate::services::{
+    audit_service::AuditContext,
+    chat_service::{ChatServiceError, ChatServiceResult},
+};
+
+/// Stored feature flags that no longer parse fall back to everything enabled rather than
+/// failing every request in the organization.
+fn parse_features(org_id: Uuid, features: Value) -> OrganizationFeatures {
+    serde_json::from_value(features).unwrap_or_else(|err| {
+        warn!(error = %err, org_id = %org_id, "ignoring unreadable organization features");
+        OrganizationFeatures::default()
+    })
+}
+
+#[derive(sqlx::FromRow)]
+struct OrganizationRow {
+    id: Uuid,
+    slug: String,
+    name: String,
+    rate_limit_profile_id: Option<Uuid>,
+    rate_limit_profile_name: Option<String>,
+    allowed_models: Option<Vec<String>>,
+    features: Value,
+    member_count: i64,
+    my_role: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<OrganizationRow> for Organization {
+    fn from(row: OrganizationRow) -> Self {
+        Self {
+            id: row.id,
+            slug: row.slug,
+            name: row.name,
+            rate_limit_profile_id: row.rate_limit_profile_id,
+            rate_limit_profile_name: row.rate_limit_profile_name,
+            allowed_models: row.allowed_models,
+            features: parse_features(row.id, row.features),
+            member_count: u64::try_from(row.member_count).unwrap_or(0),
+            role: row.my_role.and_then(|role| role.parse().ok()),
+            created_at: Timestamp(row.created_at),
+            updated_at: Timestamp(row.updated_at),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct MemberRow {
+    user_id: Uuid,
+    username: String,
+    email: String,
+    display_name: Option<String>,
+    role: String,
+    joined_at: DateTime<Utc>,
+}
+
+impl From<MemberRow> for OrganizationMember {
+    fn from(row: MemberRow) -> Self {
+        Self {
+            user_id: row.user_id,
+            username: row.username,
+            email: row.email,
+            display_name: row.display_name,
+            role: row.role.parse().unwrap_or(OrgRole::Member),
+            joined_at: Timestamp(row.joined_at),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PolicyRow {
+    org_id: Uuid,
+    allowed_models: Option<Vec<String>>,
+    features: Value,
+}
+
+/// What an organization allows in one of its conversations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversationPolicy {
+    pub org_id: Uuid,
+    pub allowed_models: Option<Vec<String>>,
+    pub features: OrganizationFeatures,
+}
+
+impl ConversationPolicy {
+    pub fn allows_model(&self, model: &str) -> bool {
+        self.allowed_models
+            .as_ref()
+            .is_none_or(|models| models.iter().any(|allowed| allowed == model))
+    }
+
+    /// The model a reply should use when `preferred` came from a default rather than the
+    /// request: kept when allowed, otherwise the organization's first allowed model.
+    pub fn fallback_model<'a>(&'a self, preferred: &'a str) -> &'a str {
+        match &self.allowed_models {
+            Some(models) if !self.allows_model(preferred) => {
+                models.first().map_or(preferred, String::as_str)
+            }
+            _ => preferred,
+        }
+    }
+}
+
+const LIST_COLUMNS: &str = "SELECT id, slug, name, rate_limit_profile_id, \
+     rate_limit_profile_name, allowed_models, features, member_count, my_role, created_at, \
+     updated_at FROM rustygpt.sp_org_list($1, $2)";
+
+#[derive(Clone)]
+pub struct OrganizationService {
+    pool: PgPool,
+}
+
+impl OrganizationService {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Organizations `user_id` belongs to, or every organization when `None`.
+    #[instrument(name = "org.list", skip(self), err)]
+    pub async fn list(&self, user_id: Option<Uuid>) -> ChatServiceResult<Vec<Organization>> {
+        let rows = sqlx::query_as::<_, OrganizationRow>(LIST_COLUMNS)
+            .bind(user_id)
+            .bind(None::<Uuid>)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(rows.into_iter().map(Organization::from).collect())
+    }
+
+    /// One organization as seen by `viewer`: `None` when the viewer is not a member. Pass no
+    /// viewer to read it regardless of membership.
+    #[instrument(name = "org.get", skip(self), err)]
+    pub async fn get(
+        &self,
+        org_id: Uuid,
+        viewer: Option<Uuid>,
+    ) -> ChatServiceResult<Option<Organization>> {
+        let row = sqlx::query_as::<_, OrganizationRow>(LIST_COLUMNS)
+            .bind(viewer)
+            .bind(Some(org_id))
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(row.map(Organization::from))
+    }
+
+    async fn require(&self, org_id: Uuid) -> ChatServiceResult<Organization> {
+        self.get(org_id, None)
+            .await?
+            .ok_or_else(|| ChatServiceError::NotFound("organization not found".to_string()))
+    }
+
+    #[instrument(name = "org.create", skip(self, audit, request), err)]
+    pub async fn create(
+        &self,
+        audit: &AuditContext,
+        request: &CreateOrganizationRequest,
+    ) -> ChatServiceResult<Organization> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let org_id = sqlx::query_scalar::<_, Uuid>("SELECT rustygpt.sp_org_create($1, $2, $3)")
+            .bind(&request.slug)
+            .bind(&request.name)
+            .bind(request.owner_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        self.require(org_id).await
+    }
+
+    /// Replaces the organization's name, rate-limit profile, allowed models and features.
+    #[instrument(name = "org.update", skip(self, audit, request), err)]
+    pub async fn update(
+        &self,
+        audit: &AuditContext,
+        org_id: Uuid,
+        request: &UpdateOrganizationRequest,
+    ) -> ChatServiceResult<Organization> {
+        if request
+            .allowed_models
+            .as_ref()
+            .is_some_and(|models| models.iter().any(|model| model.trim().is_empty()))
+        {
+            return Err(ChatServiceError::Validation(
+                "allowed models must not be blank".to_string(),
+            ));
+        }
+        let features = serde_json::to_value(request.features)
+            .map_err(|err| ChatServiceError::Validation(err.to_string()))?;
+
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        sqlx::query("SELECT rustygpt.sp_org_update($1, $2, $3, $4, $5)")
+            .bind(org_id)
+            .bind(&request.name)
+            .bind(request.rate_limit_profile_id)
+            .bind(request.allowed_models.as_deref())
+            .bind(features)
+            .execute(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        self.require(org_id).await
+    }
+
+    /// Deletes an organization that no longer owns any conversations.
+    #[instrument(name = "org.delete", skip(self, audit), err)]
+    pub async fn delete(&self, audit: &AuditContext, org_id: Uuid) -> ChatServiceResult<bool> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let deleted = sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_org_delete($1)")
+            .bind(org_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(deleted)
+    }
+
+    #[instrument(name = "org.members", skip(self), err)]
+    pub async fn members(&self, org_id: Uuid) -> ChatServiceResult<Vec<OrganizationMember>> {
+        let rows = sqlx::query_as::<_, MemberRow>(
+            "SELECT user_id, username, email, display_name, role, joined_at
+             FROM rustygpt.sp_org_member_list($1)",
+        )
+        .bind(org_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        Ok(rows.into_iter().map(OrganizationMember::from).collect())
+    }
+
+    /// `user_id`'s role in the organization, `None` when they are not a member.
+    #[instrument(name = "org.member_role", skip(self), err)]
+    pub async fn member_role(
+        &self,
+        org_id: Uuid,
+        user_id: Uuid,
+    ) -> ChatServiceResult<Option<OrgRole>> {
+        let role =
+            sqlx::query_scalar::<_, Option<String>>("SELECT rustygpt.sp_org_member_role($1, $2)")
+                .bind(org_id)
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+        Ok(role.and_then(|role| role.parse().ok()))
+    }
+
+    /// Adds a member or changes their role.
+    #[instrument(name = "org.member_put", skip(self, audit), err)]
+    pub async fn put_member(
+        &self,
+        audit: &AuditContext,
+        org_id: Uuid,
+        user_id: Uuid,
+        role: OrgRole,
+    ) -> ChatServiceResult<OrgRole> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let role = sqlx::query_scalar::<_, String>("SELECT rustygpt.sp_org_member_put($1, $2, $3)")
+            .bind(org_id)
+            .bind(user_id)
+            .bind(role.as_str())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(role.parse().unwrap_or(OrgRole::Member))
+    }
+
+    /// Removes a member, who also leaves the organization's conversations.
+    #[instrument(name = "org.member_remove", skip(self, audit), err)]
+    pub async fn remove_member(
+        &self,
+        audit: &AuditContext,
+        org_id: Uuid,
+        user_id: Uuid,
+    ) -> ChatServiceResult<bool> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let removed = sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_org_member_remove($1, $2)")
+            .bind(org_id)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(removed)
+    }
+
+    /// The policy of the organization owning `conversation_id`, `None` outside organizations.
+    #[instrument(name = "org.conversation_policy", skip(self), err)]
+    pub async fn conversation_policy(
+        &self,
+        conversation_id: Uuid,
+    ) -> ChatServiceResult<Option<ConversationPolicy>> {
+        let row = sqlx::query_as::<_, PolicyRow>(
+            "SELECT org_id, allowed_models, features
+             FROM rustygpt.sp_org_conversation_policy($1)",
+        )
+        .bind(conversation_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        Ok(row.map(|row| ConversationPolicy {
+            org_id: row.org_id,
+            allowed_models: row.allowed_models,
+            features: parse_features(row.org_id, row.features),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allowed_models: Option<Vec<&str>>) -> ConversationPolicy {
+        ConversationPolicy {
+            org_id: Uuid::nil(),
+            allowed_models: allowed_models
+                .map(|models| models.into_iter().map(str::to_string).collect()),
+            features: OrganizationFeatures::default(),
+        }
+    }
+
+    #[test]
+    fn fallback_keeps_allowed_models() {
+        assert_eq!(policy(None).fallback_model("mistral"), "mistral");
+        let restricted = policy(Some(vec!["llama-3", "qwen"]));
+        assert_eq!(restricted.fallback_model("qwen"), "qwen");
+        assert_eq!(restricted.fallback_model("mistral"), "llama-3");
+    }
+
+    #[test]
+    fn unreadable_features_enable_everything() {
+        let features = parse_features(Uuid::nil(), serde_json::json!({"invites": "sometimes"}));
+        assert_eq!(features, OrganizationFeatures::default());
+    }
+}
@@ -0,0 +1,135 @@
+//! Per-user preferences stored as a JSONB document and updated with JSON merge patches
+//! (RFC 7396): keys in the patch replace stored values, `null` resets a key to its default.
+
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use shared::models::UserPreferences;
+
+use crate::services::chat_service::{ChatServiceError, ChatServiceResult};
+
+/// Applies `patch` to `target` following RFC 7396.
+fn merge_patch(target: Value, patch: &Value) -> Value {
+    let Value::Object(patch) = patch else {
+        return patch.clone();
+    };
+    let mut target = match target {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            let current = target.remove(key).unwrap_or(Value::Null);
+            target.insert(key.clone(), merge_patch(current, value));
+        }
+    }
+    Value::Object(target)
+}
+
+/// Reads a stored document; one that no longer matches the model is replaced by defaults
+/// rather than failing every request that needs it.
+fn parse_stored(user_id: Uuid, document: Value) -> UserPreferences {
+    serde_json::from_value(document).unwrap_or_else(|err| {
+        warn!(error = %err, user_id = %user_id, "ignoring unreadable user preferences");
+        UserPreferences::default()
+    })
+}
+
+#[derive(Clone)]
+pub struct PreferencesService {
+    pool: PgPool,
+}
+
+impl PreferencesService {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(name = "preferences.get", skip(self), err)]
+    pub async fn get(&self, user_id: Uuid) -> ChatServiceResult<UserPreferences> {
+        let document =
+            sqlx::query_scalar::<_, Value>("SELECT rustygpt.sp_user_preferences_get($1)")
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+        Ok(parse_stored(user_id, document))
+    }
+
+    /// Merges `patch` into the stored preferences, validates the result and saves it.
+    #[instrument(name = "preferences.update", skip(self, patch), err)]
+    pub async fn update(&self, user_id: Uuid, patch: &Value) -> ChatServiceResult<UserPreferences> {
+        if !patch.is_object() {
+            return Err(ChatServiceError::Validation(
+                "preferences patch must be a JSON object".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(ChatServiceError::from)?;
+        let current =
+            sqlx::query_scalar::<_, Value>("SELECT rustygpt.sp_user_preferences_lock($1)")
+                .bind(user_id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+
+        let preferences = serde_json::from_value::<UserPreferences>(merge_patch(current, patch))
+            .map_err(|err| ChatServiceError::Validation(format!("invalid preferences: {err}")))?
+            .normalized()
+            .map_err(ChatServiceError::Validation)?;
+        let document = serde_json::to_value(&preferences)
+            .map_err(|err| ChatServiceError::Validation(err.to_string()))?;
+
+        sqlx::query("SELECT rustygpt.sp_user_preferences_put($1, $2)")
+            .bind(user_id)
+            .bind(&document)
+            .execute(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        Ok(preferences)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use shared::models::ThemePreference;
+
+    #[test]
+    fn merge_patch_replaces_nested_keys_and_removes_nulls() {
+        let stored = json!({
+            "default_model": "llama",
+            "temperature": 0.3,
+            "notifications": {"email": false, "sound": true}
+        });
+        let patch = json!({
+            "temperature": null,
+            "theme": "dark",
+            "notifications": {"sound": false}
+        });
+
+        assert_eq!(
+            merge_patch(stored, &patch),
+            json!({
+                "default_model": "llama",
+                "theme": "dark",
+                "notifications": {"email": false, "sound": false}
+            })
+        );
+    }
+
+    #[test]
+    fn unreadable_documents_fall_back_to_defaults() {
+        let preferences = parse_stored(Uuid::nil(), json!({"theme": "sepia"}));
+        assert_eq!(preferences, UserPreferences::default());
+
+        let preferences = parse_stored(Uuid::nil(), json!({"theme": "light"}));
+        assert_eq!(preferences.theme, ThemePreference::Light);
+    }
+}
@@ -0,0 +1,119 @@
+//! Token budgets checked before inference.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::services::chat_service::{ChatServiceError, ChatServiceResult};
+
+/// A budget period that has been used up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    /// `day` or `month`.
+    pub period: String,
+    /// Which quota applied: `user`, `profile`, `role` or `org`.
+    pub source: String,
+    pub limit: i64,
+    pub used: i64,
+    pub resets_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct QuotaPeriodRow {
+    period: String,
+    source: String,
+    token_limit: i64,
+    used: i64,
+    resets_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct QuotaService {
+    pool: PgPool,
+}
+
+impl QuotaService {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the exhausted period, if any, for `user_id` on a request admitted by `profile`.
+    #[instrument(name = "quota.check", skip(self), err)]
+    pub async fn check(
+        &self,
+        user_id: Uuid,
+        profile: Option<&str>,
+    ) -> ChatServiceResult<Option<QuotaExceeded>> {
+        let rows = sqlx::query_as::<_, QuotaPeriodRow>(
+            "SELECT period, source, token_limit, used, resets_at
+             FROM rustygpt.sp_quota_check($1, $2)",
+        )
+        .bind(user_id)
+        .bind(profile)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        Ok(exhausted_period(rows))
+    }
+
+    /// Returns the exhausted period, if any, of the budget shared by an organization.
+    #[instrument(name = "quota.check_org", skip(self), err)]
+    pub async fn check_org(&self, org_id: Uuid) -> ChatServiceResult<Option<QuotaExceeded>> {
+        let rows = sqlx::query_as::<_, QuotaPeriodRow>(
+            "SELECT period, source, token_limit, used, resets_at
+             FROM rustygpt.sp_quota_check_org($1)",
+        )
+        .bind(org_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+
+        Ok(exhausted_period(rows))
+    }
+}
+
+/// When several periods are exhausted the caller has to wait for the latest reset.
+fn exhausted_period(rows: Vec<QuotaPeriodRow>) -> Option<QuotaExceeded> {
+    rows.into_iter()
+        .filter(|row| row.used >= row.token_limit)
+        .max_by_key(|row| row.resets_at)
+        .map(|row| QuotaExceeded {
+            period: row.period,
+            source: row.source,
+            limit: row.token_limit,
+            used: row.used,
+            resets_at: row.resets_at,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn row(period: &str, limit: i64, used: i64, resets_in_days: i64) -> QuotaPeriodRow {
+        QuotaPeriodRow {
+            period: period.to_string(),
+            source: "role".to_string(),
+            token_limit: limit,
+            used,
+            resets_at: Utc::now() + Duration::days(resets_in_days),
+        }
+    }
+
+    #[test]
+    fn reports_latest_reset_among_exhausted_periods() {
+        assert_eq!(exhausted_period(vec![row("day", 100, 99, 1)]), None);
+
+        let day_only = exhausted_period(vec![row("day", 100, 100, 1), row("month", 1000, 10, 20)])
+            .expect("daily budget exhausted");
+        assert_eq!(day_only.period, "day");
+
+        let both = exhausted_period(vec![row("day", 100, 150, 1), row("month", 120, 150, 20)])
+            .expect("both budgets exhausted");
+        assert_eq!(both.period, "month");
+        assert_eq!(both.used, 150);
+    }
+}
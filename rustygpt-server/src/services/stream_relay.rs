@@ -0,0 +1,243 @@
+//! Cross-instance relay for stream events.
+//!
+//! Stream hub channels live inside one process. With the `postgres` hub backend every event an
+//! instance publishes is also sent through `NOTIFY` on [`STREAM_RELAY_CHANNEL`]; every instance
+//! `LISTEN`s on the same channel and re-broadcasts what the others published to its local
+//! subscribers. Events too large for a notification are written to `rustygpt.sse_event_log`
+//! and the notification only carries a pointer to the row.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::{StreamExt, stream, stream::BoxStream};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{PgPool, postgres::PgListener};
+use tracing::{trace, warn};
+use uuid::Uuid;
+
+use crate::services::sse_persistence::StreamEventRecord;
+
+/// Notification channel shared by every instance.
+pub const STREAM_RELAY_CHANNEL: &str = "rustygpt_stream";
+/// Postgres rejects notification payloads of 8000 bytes or more; larger events go by pointer.
+const MAX_INLINE_PAYLOAD: usize = 7000;
+/// Pause before listening again after the listener connection could not be re-established.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// One event as it travels between instances.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayedEvent {
+    /// Instance that published the event.
+    pub origin: Uuid,
+    pub conversation_id: Uuid,
+    pub sequence: i64,
+    pub event_id: String,
+    pub timestamp_ms: i64,
+    pub chunk_index: Option<i32>,
+    /// The encoded `ConversationStreamEvent`; `None` when it has to be read back from
+    /// `rustygpt.sse_event_log` by `event_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Value>,
+}
+
+/// Transport that carries published events to the other instances.
+#[async_trait]
+pub trait StreamRelay: Send + Sync {
+    /// Sends one event to every other instance.
+    async fn publish(
+        &self,
+        conversation_id: Uuid,
+        record: &StreamEventRecord,
+        timestamp_ms: i64,
+        chunk_index: Option<i32>,
+    ) -> Result<()>;
+
+    /// Events published by other instances, with pointers already resolved.
+    async fn subscribe(&self) -> Result<BoxStream<'static, RelayedEvent>>;
+}
+
+/// [`StreamRelay`] over Postgres `LISTEN`/`NOTIFY`.
+#[derive(Clone)]
+pub struct PgStreamRelay {
+    pool: PgPool,
+    origin: Uuid,
+}
+
+impl PgStreamRelay {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            origin: Uuid::new_v4(),
+        }
+    }
+}
+
+#[async_trait]
+impl StreamRelay for PgStreamRelay {
+    async fn publish(
+        &self,
+        conversation_id: Uuid,
+        record: &StreamEventRecord,
+        timestamp_ms: i64,
+        chunk_index: Option<i32>,
+    ) -> Result<()> {
+        let mut event = RelayedEvent {
+            origin: self.origin,
+            conversation_id,
+            sequence: record.sequence,
+            event_id: record.event_id.clone(),
+            timestamp_ms,
+            chunk_index,
+            payload: Some(record.payload.clone()),
+        };
+        let (notice, inline) = encode_notice(&mut event)?;
+
+        if !inline {
+            // Upserts on (conversation, sequence), so this is a no-op when persistence
+            // already stored the event.
+            sqlx::query("CALL rustygpt.sp_record_sse_event($1, $2, $3, $4, $5, $6)")
+                .bind(conversation_id)
+                .bind(record.sequence)
+                .bind(&record.event_id)
+                .bind(&record.event_type)
+                .bind(&record.payload)
+                .bind(record.root_message_id)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(STREAM_RELAY_CHANNEL)
+            .bind(&notice)
+            .execute(&self.pool)
+            .await?;
+
+        trace!(%conversation_id, event_id = %record.event_id, inline, "relayed stream event");
+        Ok(())
+    }
+
+    async fn subscribe(&self) -> Result<BoxStream<'static, RelayedEvent>> {
+        let mut listener = PgListener::connect_with(&self.pool)
+            .await
+            .context("failed to open stream relay listener")?;
+        listener
+            .listen(STREAM_RELAY_CHANNEL)
+            .await
+            .context("failed to listen for relayed stream events")?;
+
+        let relay = self.clone();
+        let events = stream::unfold(listener, move |mut listener| {
+            let relay = relay.clone();
+            async move {
+                loop {
+                    // `recv` reconnects on its own; an error means it could not.
+                    let notification = match listener.recv().await {
+                        Ok(notification) => notification,
+                        Err(err) => {
+                            warn!(error = %err, "stream relay listener failed");
+                            tokio::time::sleep(RECONNECT_DELAY).await;
+                            continue;
+                        }
+                    };
+                    if let Some(event) = relay.decode(notification.payload()).await {
+                        return Some((event, listener));
+                    }
+                }
+            }
+        });
+
+        Ok(events.boxed())
+    }
+}
+
+impl PgStreamRelay {
+    /// Parses a notification, skipping this instance's own events and resolving pointers.
+    async fn decode(&self, raw: &str) -> Option<RelayedEvent> {
+        let mut event = match serde_json::from_str::<RelayedEvent>(raw) {
+            Ok(event) => event,
+            Err(err) => {
+                warn!(error = %err, "ignoring malformed stream relay notification");
+                return None;
+            }
+        };
+        if event.origin == self.origin {
+            return None;
+        }
+        if event.payload.is_none() {
+            let stored = sqlx::query_scalar::<_, Option<Value>>(
+                "SELECT rustygpt.sp_sse_event_payload($1, $2)",
+            )
+            .bind(event.conversation_id)
+            .bind(&event.event_id)
+            .fetch_one(&self.pool)
+            .await;
+            match stored {
+                Ok(Some(payload)) => event.payload = Some(payload),
+                Ok(None) => {
+                    warn!(event_id = %event.event_id, "relayed stream event is no longer logged");
+                    return None;
+                }
+                Err(err) => {
+                    warn!(error = %err, event_id = %event.event_id, "failed to load relayed stream event");
+                    return None;
+                }
+            }
+        }
+        Some(event)
+    }
+}
+
+/// Encodes a notification, dropping the payload when it would not fit. Returns whether the
+/// payload went inline.
+fn encode_notice(event: &mut RelayedEvent) -> Result<(String, bool)> {
+    let notice = serde_json::to_string(event)?;
+    if notice.len() <= MAX_INLINE_PAYLOAD {
+        return Ok((notice, true));
+    }
+    event.payload = None;
+    Ok((serde_json::to_string(event)?, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn relayed(payload: Value) -> RelayedEvent {
+        RelayedEvent {
+            origin: Uuid::new_v4(),
+            conversation_id: Uuid::new_v4(),
+            sequence: 7,
+            event_id: "evt".into(),
+            timestamp_ms: 1_700_000_000_000,
+            chunk_index: Some(3),
+            payload: Some(payload),
+        }
+    }
+
+    #[test]
+    fn small_events_travel_inline() {
+        let mut event = relayed(json!({"type": "typing.update"}));
+        let (notice, inline) = encode_notice(&mut event).unwrap();
+
+        assert!(inline);
+        let decoded: RelayedEvent = serde_json::from_str(&notice).unwrap();
+        assert_eq!(decoded.payload, Some(json!({"type": "typing.update"})));
+        assert_eq!(decoded.chunk_index, Some(3));
+    }
+
+    #[test]
+    fn oversized_events_become_pointers() {
+        let mut event = relayed(json!({"text": "x".repeat(MAX_INLINE_PAYLOAD)}));
+        let (notice, inline) = encode_notice(&mut event).unwrap();
+
+        assert!(!inline);
+        assert!(notice.len() < MAX_INLINE_PAYLOAD);
+        let decoded: RelayedEvent = serde_json::from_str(&notice).unwrap();
+        assert!(decoded.payload.is_none());
+        assert_eq!(decoded.event_id, "evt");
+        assert_eq!(decoded.sequence, 7);
+    }
+}
@@ -2,42 +2,68 @@ use std::{
     collections::HashMap,
     sync::{
         Arc,
-        atomic::{AtomicU8, Ordering},
+        atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
     },
     time::{Duration, Instant},
 };
 
 use metrics::{counter, histogram};
-use tokio::sync::RwLock;
+use thiserror::Error;
+use tokio::sync::{Notify, RwLock};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+/// How long clients are asked to wait before retrying against a server that is shutting down.
+pub const SHUTDOWN_RETRY_AFTER: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StreamStopReason {
     None,
     Cancelled,
     TimedOut,
     Completed,
+    /// Cut off because the server shut down before the stream finished.
+    Interrupted,
 }
 
 const STATE_ACTIVE: u8 = 0;
 const STATE_CANCELLED: u8 = 1;
 const STATE_TIMEOUT: u8 = 2;
 const STATE_COMPLETED: u8 = 3;
+const STATE_INTERRUPTED: u8 = 4;
+
+/// New generations are refused while the server drains for shutdown.
+#[derive(Debug, Clone, Copy, Error)]
+#[error("server is shutting down")]
+pub struct ShuttingDown;
+
+/// Number of live sessions, so shutdown can wait for them to finish.
+#[derive(Debug, Default)]
+struct ActiveSessions {
+    count: AtomicUsize,
+    idle: Notify,
+}
 
 #[derive(Debug)]
 pub struct StreamSession {
     token: CancellationToken,
     state: AtomicU8,
     started_at: Instant,
+    active: Arc<ActiveSessions>,
 }
 
 impl StreamSession {
-    fn new(default_timeout: Option<Duration>) -> Arc<Self> {
+    fn new(
+        default_timeout: Option<Duration>,
+        shutdown: &CancellationToken,
+        active: Arc<ActiveSessions>,
+    ) -> Arc<Self> {
+        active.count.fetch_add(1, Ordering::SeqCst);
         let session = Arc::new(Self {
-            token: CancellationToken::new(),
+            token: shutdown.child_token(),
             state: AtomicU8::new(STATE_ACTIVE),
             started_at: Instant::now(),
+            active,
         });
 
         if let Some(duration) = default_timeout.filter(|d| !d.is_zero()) {
@@ -98,12 +124,16 @@ impl StreamSession {
     }
 
     pub fn mark_completed(&self) {
-        let _ = self.state.compare_exchange(
-            STATE_ACTIVE,
-            STATE_COMPLETED,
-            Ordering::SeqCst,
-            Ordering::SeqCst,
-        );
+        // Cancelling and timing out leave the active state first, so a cancelled token on an
+        // active session can only come from the supervisor's shutdown.
+        let next = if self.token.is_cancelled() {
+            STATE_INTERRUPTED
+        } else {
+            STATE_COMPLETED
+        };
+        let _ = self
+            .state
+            .compare_exchange(STATE_ACTIVE, next, Ordering::SeqCst, Ordering::SeqCst);
     }
 
     pub fn stop_reason(&self) -> StreamStopReason {
@@ -111,15 +141,28 @@ impl StreamSession {
             STATE_CANCELLED => StreamStopReason::Cancelled,
             STATE_TIMEOUT => StreamStopReason::TimedOut,
             STATE_COMPLETED => StreamStopReason::Completed,
+            STATE_INTERRUPTED => StreamStopReason::Interrupted,
+            _ if self.token.is_cancelled() => StreamStopReason::Interrupted,
             _ => StreamStopReason::None,
         }
     }
 }
 
+impl Drop for StreamSession {
+    fn drop(&mut self) {
+        if self.active.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.active.idle.notify_waiters();
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct StreamSupervisor {
     sessions: RwLock<HashMap<Uuid, Arc<StreamSession>>>,
     default_timeout: Option<Duration>,
+    draining: AtomicBool,
+    shutdown: CancellationToken,
+    active: Arc<ActiveSessions>,
 }
 
 impl StreamSupervisor {
@@ -127,11 +170,65 @@ impl StreamSupervisor {
         Self {
             sessions: RwLock::new(HashMap::new()),
             default_timeout,
+            draining: AtomicBool::new(false),
+            shutdown: CancellationToken::new(),
+            active: Arc::default(),
         }
     }
 
+    /// A session counts as in flight until every handle to it is dropped.
     pub fn create_session(&self) -> Arc<StreamSession> {
-        StreamSession::new(self.default_timeout)
+        StreamSession::new(
+            self.default_timeout,
+            &self.shutdown,
+            Arc::clone(&self.active),
+        )
+    }
+
+    /// Checks that a new generation may start.
+    ///
+    /// # Errors
+    /// Returns [`ShuttingDown`] once [`Self::begin_drain`] has been called.
+    pub fn admit(&self) -> Result<(), ShuttingDown> {
+        if self.draining.load(Ordering::SeqCst) {
+            Err(ShuttingDown)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Stops admitting new generations; sessions already running carry on.
+    pub fn begin_drain(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    pub fn active_sessions(&self) -> usize {
+        self.active.count.load(Ordering::SeqCst)
+    }
+
+    /// Waits up to `timeout` for every session to finish. Returns whether they all did.
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Created before the check so a notification in between is not missed.
+            let idle = self.active.idle.notified();
+            if self.active_sessions() == 0 {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, idle).await.is_err() {
+                return self.active_sessions() == 0;
+            }
+        }
+    }
+
+    /// Cancels every live session, including ones created afterwards, and reports them as
+    /// [`StreamStopReason::Interrupted`]. Returns how many sessions were still running.
+    pub fn interrupt_all(&self) -> usize {
+        let running = self.active_sessions();
+        self.shutdown.cancel();
+        counter!("rustygpt_stream_interrupts_total")
+            .increment(u64::try_from(running).unwrap_or(u64::MAX));
+        running
     }
 
     pub async fn register(&self, message_id: Uuid, session: Arc<StreamSession>) {
@@ -193,4 +290,44 @@ mod tests {
         let reason = supervisor.cancel(&message_id).await;
         assert_eq!(reason, StreamStopReason::TimedOut);
     }
+
+    #[tokio::test]
+    async fn drain_refuses_new_generations_and_waits_for_running_ones() {
+        let supervisor = StreamSupervisor::new(None);
+        let session = supervisor.create_session();
+        supervisor.begin_drain();
+
+        assert!(supervisor.admit().is_err());
+        assert!(!supervisor.wait_idle(Duration::from_millis(20)).await);
+
+        let finish = tokio::spawn(async move {
+            sleep(Duration::from_millis(10)).await;
+            session.mark_completed();
+            assert_eq!(session.stop_reason(), StreamStopReason::Completed);
+        });
+        assert!(supervisor.wait_idle(Duration::from_secs(1)).await);
+        finish.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn interrupt_all_cancels_running_and_later_sessions() {
+        let supervisor = StreamSupervisor::new(None);
+        let running = supervisor.create_session();
+        let cancelled = supervisor.create_session();
+        let message_id = Uuid::new_v4();
+        supervisor
+            .register(message_id, Arc::clone(&cancelled))
+            .await;
+        supervisor.cancel(&message_id).await;
+
+        assert_eq!(supervisor.interrupt_all(), 2);
+        let late = supervisor.create_session();
+
+        assert!(running.cancellation_token().is_cancelled());
+        assert!(late.cancellation_token().is_cancelled());
+        running.mark_completed();
+        assert_eq!(running.stop_reason(), StreamStopReason::Interrupted);
+        assert_eq!(late.stop_reason(), StreamStopReason::Interrupted);
+        assert_eq!(cancelled.stop_reason(), StreamStopReason::Cancelled);
+    }
 }
@@ -0,0 +1,223 @@
+//! Token usage ledger: records every generation and aggregates it for reports.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use shared::models::{
+    Timestamp, UsageBreakdown, UsageGroupBy, UsageReport, UsageReportRow, UsageSource,
+};
+
+use crate::services::{
+    assistant_service::UsageMeter,
+    chat_service::{ChatServiceError, ChatServiceResult, begin_as},
+};
+
+/// A finished generation, attributed to whoever triggered it.
+#[derive(Debug, Clone)]
+pub struct UsageEntry<'a> {
+    /// `None` for anonymous `/v1` calls.
+    pub user_id: Option<Uuid>,
+    pub conversation_id: Option<Uuid>,
+    pub message_id: Option<Uuid>,
+    pub source: UsageSource,
+    pub model: &'a str,
+    pub usage: &'a UsageBreakdown,
+    pub latency_ms: i64,
+}
+
+impl<'a> UsageEntry<'a> {
+    #[must_use]
+    pub fn from_meter(
+        source: UsageSource,
+        meter: &'a UsageMeter,
+        usage: &'a UsageBreakdown,
+        user_id: Option<Uuid>,
+    ) -> Self {
+        Self {
+            user_id,
+            conversation_id: None,
+            message_id: None,
+            source,
+            model: &meter.model,
+            usage,
+            latency_ms: meter.latency_ms(),
+        }
+    }
+
+    #[must_use]
+    pub const fn for_message(mut self, conversation_id: Uuid, message_id: Uuid) -> Self {
+        self.conversation_id = Some(conversation_id);
+        self.message_id = Some(message_id);
+        self
+    }
+}
+
+#[derive(Clone)]
+pub struct UsageService {
+    pool: PgPool,
+}
+
+#[derive(sqlx::FromRow)]
+struct UsageReportDbRow {
+    bucket: String,
+    username: Option<String>,
+    requests: i64,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+    total_tokens: i64,
+    avg_latency_ms: i64,
+}
+
+impl From<UsageReportDbRow> for UsageReportRow {
+    fn from(row: UsageReportDbRow) -> Self {
+        Self {
+            bucket: row.bucket,
+            username: row.username,
+            requests: row.requests,
+            prompt_tokens: row.prompt_tokens,
+            completion_tokens: row.completion_tokens,
+            total_tokens: row.total_tokens,
+            avg_latency_ms: row.avg_latency_ms,
+        }
+    }
+}
+
+impl UsageService {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(name = "usage.record", skip(self, entry), err)]
+    pub async fn record(&self, entry: &UsageEntry<'_>) -> ChatServiceResult<()> {
+        sqlx::query("SELECT rustygpt.sp_record_usage($1, $2, $3, $4, $5, $6, $7, $8, $9)")
+            .bind(entry.user_id)
+            .bind(entry.conversation_id)
+            .bind(entry.message_id)
+            .bind(entry.source.as_str())
+            .bind(entry.model)
+            .bind(entry.usage.prompt_tokens)
+            .bind(entry.usage.completion_tokens)
+            .bind(entry.usage.total_tokens)
+            .bind(entry.latency_ms)
+            .execute(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(())
+    }
+
+    /// Records an entry, logging instead of failing: a ledger outage must not lose the reply.
+    pub async fn record_or_warn(&self, entry: &UsageEntry<'_>) {
+        if let Err(err) = self.record(entry).await {
+            warn!(error = %err, source = entry.source.as_str(), "failed to record token usage");
+        }
+    }
+
+    /// Aggregates the ledger for `user_id`, or for everyone when `None` (admin only).
+    #[instrument(name = "usage.report", skip(self), err)]
+    pub async fn report(
+        &self,
+        actor: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        group_by: UsageGroupBy,
+        user_id: Option<Uuid>,
+    ) -> ChatServiceResult<UsageReport> {
+        let mut tx = begin_as(&self.pool, actor).await?;
+        let rows = sqlx::query_as::<_, UsageReportDbRow>(
+            "SELECT bucket, username, requests, prompt_tokens, completion_tokens, total_tokens,
+                    avg_latency_ms
+             FROM rustygpt.sp_usage_report($1, $2, $3, $4)",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(group_by.as_str())
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        Ok(UsageReport {
+            group_by,
+            from: Timestamp(from),
+            to: Timestamp(to),
+            user_id,
+            rows: rows.into_iter().map(UsageReportRow::from).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::TestDatabase;
+    use chrono::Duration;
+
+    fn entry<'a>(user_id: Uuid, model: &'a str, usage: &'a UsageBreakdown) -> UsageEntry<'a> {
+        UsageEntry {
+            user_id: Some(user_id),
+            conversation_id: None,
+            message_id: None,
+            source: UsageSource::V1,
+            model,
+            usage,
+            latency_ms: 100,
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_run_as_the_actor_and_only_admins_see_everyone() {
+        let Some(db) = TestDatabase::create().await else {
+            return;
+        };
+        let user = db.create_user("usage_user").await;
+        let admin = db.create_user("usage_admin").await;
+        sqlx::query("INSERT INTO rustygpt.user_roles (user_id, role) VALUES ($1, 'admin')")
+            .bind(admin)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let service = UsageService::new(db.pool.clone());
+        let usage = UsageBreakdown {
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            total_tokens: 15,
+        };
+        service.record(&entry(user, "small", &usage)).await.unwrap();
+        service.record(&entry(user, "small", &usage)).await.unwrap();
+        service
+            .record(&entry(admin, "large", &usage))
+            .await
+            .unwrap();
+
+        let (from, to) = (
+            Utc::now() - Duration::hours(1),
+            Utc::now() + Duration::hours(1),
+        );
+        let own = service
+            .report(user, from, to, UsageGroupBy::Model, Some(user))
+            .await
+            .unwrap();
+        assert_eq!(own.rows.len(), 1);
+        assert_eq!(own.rows[0].bucket, "small");
+        assert_eq!(own.rows[0].requests, 2);
+        assert_eq!(own.rows[0].total_tokens, 30);
+
+        assert!(matches!(
+            service
+                .report(user, from, to, UsageGroupBy::Model, None)
+                .await,
+            Err(ChatServiceError::Forbidden(_))
+        ));
+        let everyone = service
+            .report(admin, from, to, UsageGroupBy::User, None)
+            .await
+            .unwrap();
+        assert_eq!(everyone.rows.len(), 2);
+
+        db.destroy().await;
+    }
+}
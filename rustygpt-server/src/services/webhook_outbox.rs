@@ -0,0 +1,530 @@
+//! Outgoing webhooks.
+//!
+//! Events published on the stream hub are written to `rustygpt.webhook_deliveries` for every
+//! matching subscription ([`WebhookQueue`]). A background worker
+//! ([`WebhookOutbox::deliver_due`]) POSTs them through a [`WebhookTransport`], logging every
+//! attempt, retrying failures with backoff and dead-lettering deliveries that run out of
+//! attempts.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use shared::{
+    config::server::WebhookConfig,
+    models::{
+        ConversationStreamEvent, WEBHOOK_DELIVERY_HEADER, WEBHOOK_EVENT_HEADER,
+        WEBHOOK_EVENT_TYPES, WEBHOOK_SIGNATURE_HEADER,
+    },
+};
+use sqlx::PgPool;
+use thiserror::Error;
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+const WEBHOOK_METRIC_NAME: &str = "webhook_deliveries_total";
+const CLAIM_BATCH_SIZE: i32 = 20;
+/// How long a claimed delivery stays hidden from other workers before it is retried.
+const CLAIM_LEASE_SECONDS: i32 = 300;
+/// Receivers that answer with a huge error page only get this much of it logged.
+const MAX_LOGGED_BODY: usize = 500;
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("receiver answered {status}: {body}")]
+    Status { status: u16, body: String },
+    #[error("failed to encode payload: {0}")]
+    Encode(#[from] serde_json::Error),
+}
+
+impl WebhookError {
+    /// The receiver's status code, when it answered at all.
+    #[must_use]
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            Self::Status { status, .. } => Some(*status),
+            Self::Http(err) => err.status().map(|status| status.as_u16()),
+            Self::Encode(_) => None,
+        }
+    }
+}
+
+/// A claimed delivery handed to a transport.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutgoingWebhook {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub event_type: String,
+    pub payload: Value,
+    pub attempts: i32,
+}
+
+/// Delivery backend for queued webhooks. Returns the receiver's status code on success.
+#[async_trait]
+pub trait WebhookTransport: Send + Sync {
+    async fn send(&self, webhook: &OutgoingWebhook) -> Result<u16, WebhookError>;
+}
+
+fn hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+}
+
+fn mac(secret: &str, timestamp: i64, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// The `X-RustyGPT-Signature` value for `body` sent at `timestamp` (Unix seconds):
+/// `t=<timestamp>,v1=<hex HMAC-SHA256 of "<timestamp>.<body>">`.
+#[must_use]
+pub fn signature_header(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let digest = mac(secret, timestamp, body).finalize().into_bytes();
+    format!("t={timestamp},v1={}", hex(&digest))
+}
+
+/// Checks a signature header the way a receiver should, rejecting timestamps more than
+/// `tolerance` away from `now` (Unix seconds) to limit replays.
+#[cfg(test)]
+#[must_use]
+pub fn verify_signature(
+    secret: &str,
+    header: &str,
+    body: &[u8],
+    now: i64,
+    tolerance: Duration,
+) -> bool {
+    let mut timestamp = None;
+    let mut signature = None;
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signature = Some(value),
+            _ => {}
+        }
+    }
+    let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+        return false;
+    };
+    if now.abs_diff(timestamp) > tolerance.as_secs() {
+        return false;
+    }
+    let Some(expected) = decode_hex(signature) else {
+        return false;
+    };
+    mac(secret, timestamp, body).verify_slice(&expected).is_ok()
+}
+
+#[cfg(test)]
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(value.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
+
+/// POSTs the event as JSON with the signature, event and delivery headers. Any 2xx answer
+/// counts as delivered; redirects are not followed.
+pub struct HttpWebhookTransport {
+    http: reqwest::Client,
+}
+
+impl HttpWebhookTransport {
+    /// # Errors
+    /// Returns an error if the HTTP client cannot be built.
+    pub fn new(timeout: Duration) -> Result<Self, WebhookError> {
+        let http = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(timeout)
+            .user_agent(concat!("RustyGPT-Webhooks/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Self { http })
+    }
+}
+
+#[async_trait]
+impl WebhookTransport for HttpWebhookTransport {
+    async fn send(&self, webhook: &OutgoingWebhook) -> Result<u16, WebhookError> {
+        let body = serde_json::to_vec(&webhook.payload)?;
+        let signature = signature_header(&webhook.secret, Utc::now().timestamp(), &body);
+        let mut response = self
+            .http
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_SIGNATURE_HEADER, signature)
+            .header(WEBHOOK_EVENT_HEADER, &webhook.event_type)
+            .header(WEBHOOK_DELIVERY_HEADER, webhook.id.to_string())
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(status.as_u16());
+        }
+        // Stop reading once the log has enough; the rest of the body is never downloaded.
+        let mut body = Vec::new();
+        while body.len() < MAX_LOGGED_BODY {
+            match response.chunk().await {
+                Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        body.truncate(MAX_LOGGED_BODY);
+        Err(WebhookError::Status {
+            status: status.as_u16(),
+            body: utf8_prefix(&body),
+        })
+    }
+}
+
+/// Decodes `bytes` lossily, dropping a character the cut left incomplete at the end.
+fn utf8_prefix(bytes: &[u8]) -> String {
+    let valid = match std::str::from_utf8(bytes) {
+        Err(err) if err.error_len().is_none() => err.valid_up_to(),
+        _ => bytes.len(),
+    };
+    String::from_utf8_lossy(&bytes[..valid]).into_owned()
+}
+
+/// Whether webhooks deliver events named `event_type`.
+#[must_use]
+pub fn is_deliverable(event_type: &str) -> bool {
+    WEBHOOK_EVENT_TYPES.contains(&event_type)
+}
+
+/// Queues published stream events for the subscriptions that follow them.
+#[derive(Clone)]
+pub struct WebhookQueue {
+    pool: PgPool,
+}
+
+impl WebhookQueue {
+    #[must_use]
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Queues `event` for every matching subscription, returning how many deliveries were
+    /// created. Events that are never delivered by webhook are skipped without a query.
+    #[instrument(name = "webhook.enqueue", skip(self, event), err)]
+    pub async fn enqueue(
+        &self,
+        conversation_id: Uuid,
+        event_type: &str,
+        event: &ConversationStreamEvent,
+    ) -> Result<i32, sqlx::Error> {
+        if !is_deliverable(event_type) {
+            return Ok(0);
+        }
+        let data = serde_json::to_value(event).map_err(|err| sqlx::Error::Encode(err.into()))?;
+        sqlx::query_scalar::<_, i32>("SELECT rustygpt.sp_webhook_enqueue($1, $2, $3)")
+            .bind(conversation_id)
+            .bind(event_type)
+            .bind(data)
+            .fetch_one(&self.pool)
+            .await
+    }
+}
+
+/// Drains the delivery outbox through a transport.
+#[derive(Clone)]
+pub struct WebhookOutbox {
+    pool: PgPool,
+    transport: Arc<dyn WebhookTransport>,
+    max_attempts: i32,
+}
+
+impl WebhookOutbox {
+    #[must_use]
+    pub fn new(pool: PgPool, transport: Arc<dyn WebhookTransport>, config: &WebhookConfig) -> Self {
+        Self {
+            pool,
+            transport,
+            max_attempts: i32::try_from(config.max_attempts).unwrap_or(i32::MAX),
+        }
+    }
+
+    /// Attempts every delivery that is due, returning how many were delivered.
+    #[instrument(name = "webhook.deliver_due", skip(self), err)]
+    pub async fn deliver_due(&self) -> Result<usize, sqlx::Error> {
+        let mut delivered = 0;
+        loop {
+            let batch = sqlx::query_as::<_, OutgoingWebhook>(
+                "SELECT id, subscription_id, url, secret, event_type, payload, attempts
+                 FROM rustygpt.sp_webhook_claim($1, $2)",
+            )
+            .bind(CLAIM_BATCH_SIZE)
+            .bind(CLAIM_LEASE_SECONDS)
+            .fetch_all(&self.pool)
+            .await?;
+            if batch.is_empty() {
+                return Ok(delivered);
+            }
+
+            for webhook in &batch {
+                if self.deliver(webhook).await? {
+                    delivered += 1;
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, webhook: &OutgoingWebhook) -> Result<bool, sqlx::Error> {
+        let started = Instant::now();
+        let result = self.transport.send(webhook).await;
+        let duration_ms = i32::try_from(started.elapsed().as_millis()).unwrap_or(i32::MAX);
+
+        let (status_code, error) = match &result {
+            Ok(status) => (Some(*status), None),
+            Err(err) => (err.status_code(), Some(err.to_string())),
+        };
+        let outcome = sqlx::query_scalar::<_, String>(
+            "SELECT rustygpt.sp_webhook_record_attempt($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(webhook.id)
+        .bind(status_code.map(i32::from))
+        .bind(error.as_deref())
+        .bind(duration_ms)
+        .bind(result.is_ok())
+        .bind(self.max_attempts)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if let Some(error) = &error {
+            warn!(
+                delivery_id = %webhook.id,
+                subscription_id = %webhook.subscription_id,
+                event_type = %webhook.event_type,
+                attempt = webhook.attempts + 1,
+                outcome = %outcome,
+                error = %error,
+                "webhook delivery failed"
+            );
+        }
+        record_delivery_metric(&outcome);
+        Ok(result.is_ok())
+    }
+}
+
+fn record_delivery_metric(outcome: &str) {
+    metrics::counter!(
+        WEBHOOK_METRIC_NAME,
+        "result" => outcome.to_string()
+    )
+    .increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        body::Bytes,
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+    };
+    use serde_json::json;
+    use shared::models::WebhookEvent;
+    use std::sync::Mutex;
+
+    const SECRET: &str = "0123456789abcdef0123";
+
+    #[derive(Clone, Default)]
+    struct Receiver {
+        requests: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+        status: Arc<Mutex<Option<StatusCode>>>,
+    }
+
+    async fn receive(
+        State(receiver): State<Receiver>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        receiver.requests.lock().unwrap().push((headers, body));
+        receiver
+            .status
+            .lock()
+            .unwrap()
+            .unwrap_or(StatusCode::NO_CONTENT)
+    }
+
+    /// A local HTTP receiver recording every request it gets.
+    async fn start_receiver() -> (Receiver, String) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let receiver = Receiver::default();
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state(receiver.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (receiver, url)
+    }
+
+    fn outgoing(url: String) -> OutgoingWebhook {
+        OutgoingWebhook {
+            id: Uuid::new_v4(),
+            subscription_id: Uuid::new_v4(),
+            url,
+            secret: SECRET.to_string(),
+            event_type: "membership.changed".to_string(),
+            payload: json!({
+                "event_id": Uuid::nil(),
+                "type": "membership.changed",
+                "conversation_id": Uuid::nil(),
+                "occurred_at": "2024-01-01T00:00:00Z",
+                "data": {
+                    "type": "membership.changed",
+                    "payload": {
+                        "conversation_id": Uuid::nil(),
+                        "user_id": Uuid::nil(),
+                        "action": "removed"
+                    }
+                }
+            }),
+            attempts: 0,
+        }
+    }
+
+    #[test]
+    fn signatures_verify_only_with_the_same_secret_and_body() {
+        let now = 1_700_000_000;
+        let header = signature_header(SECRET, now, b"{}");
+        let tolerance = Duration::from_secs(300);
+
+        assert!(verify_signature(
+            SECRET,
+            &header,
+            b"{}",
+            now + 10,
+            tolerance
+        ));
+        assert!(!verify_signature(
+            "another-secret-value",
+            &header,
+            b"{}",
+            now,
+            tolerance
+        ));
+        assert!(!verify_signature(SECRET, &header, b"{ }", now, tolerance));
+        assert!(!verify_signature(
+            SECRET,
+            &header,
+            b"{}",
+            now + 301,
+            tolerance
+        ));
+        assert!(!verify_signature(SECRET, "v1=abc", b"{}", now, tolerance));
+    }
+
+    #[test]
+    fn only_listed_events_are_deliverable() {
+        assert!(is_deliverable("message.done"));
+        assert!(!is_deliverable("message.delta"));
+        assert!(!is_deliverable("typing.update"));
+    }
+
+    #[tokio::test]
+    async fn http_transport_posts_signed_events() {
+        let (receiver, url) = start_receiver().await;
+        let transport = HttpWebhookTransport::new(Duration::from_secs(5)).expect("client");
+        let webhook = outgoing(url);
+
+        let status = transport.send(&webhook).await.expect("delivered");
+        assert_eq!(status, 204);
+
+        let requests = receiver.requests.lock().unwrap();
+        let (headers, body) = requests.first().expect("one request");
+        let header = |name: &str| headers.get(name).unwrap().to_str().unwrap().to_string();
+        assert_eq!(header(WEBHOOK_EVENT_HEADER), "membership.changed");
+        assert_eq!(header(WEBHOOK_DELIVERY_HEADER), webhook.id.to_string());
+        assert!(verify_signature(
+            SECRET,
+            &header(WEBHOOK_SIGNATURE_HEADER),
+            body,
+            Utc::now().timestamp(),
+            Duration::from_secs(60),
+        ));
+
+        let event: WebhookEvent = serde_json::from_slice(body).expect("webhook body");
+        assert!(matches!(
+            event.data,
+            ConversationStreamEvent::MembershipChanged { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn http_transport_reports_receiver_errors() {
+        let (receiver, url) = start_receiver().await;
+        *receiver.status.lock().unwrap() = Some(StatusCode::SERVICE_UNAVAILABLE);
+        let transport = HttpWebhookTransport::new(Duration::from_secs(5)).expect("client");
+
+        let err = transport
+            .send(&outgoing(url))
+            .await
+            .expect_err("receiver failed");
+        assert_eq!(err.status_code(), Some(503));
+    }
+
+    #[tokio::test]
+    async fn http_transport_reads_only_the_start_of_an_error_body() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        // An error page that never ends: reading all of it would run into the client timeout.
+        let app = Router::new().route(
+            "/hook",
+            post(|| async {
+                let page = futures::stream::repeat_with(|| {
+                    Ok::<_, std::io::Error>(Bytes::from("é".repeat(512)))
+                });
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    axum::body::Body::from_stream(page),
+                )
+            }),
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let transport = HttpWebhookTransport::new(Duration::from_secs(5)).expect("client");
+
+        let err = transport
+            .send(&outgoing(url))
+            .await
+            .expect_err("receiver failed");
+        let WebhookError::Status { status, body } = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(status, 500);
+        assert_eq!(body, "é".repeat(MAX_LOGGED_BODY / 2));
+    }
+
+    #[test]
+    fn utf8_prefix_drops_a_split_character() {
+        assert_eq!(utf8_prefix("aé".as_bytes()), "aé");
+        assert_eq!(utf8_prefix(&"aé".as_bytes()[..2]), "a");
+        assert_eq!(utf8_prefix(b"a\xffb"), "a\u{fffd}b");
+    }
+}
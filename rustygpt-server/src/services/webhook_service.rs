@@ -0,0 +1,380 @@
+//! Admin management of webhook subscriptions and their delivery log. Delivery itself lives in
+//! [`crate::services::webhook_outbox`].
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use shared::models::{
+    CreateWebhookRequest, CreateWebhookResponse, Timestamp, UpdateWebhookRequest,
+    WEBHOOK_EVENT_TYPES, WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryDetail,
+    WebhookDeliveryQuery, WebhookDeliveryStatus, WebhookSubscription,
+};
+
+use crate::services::{
+    audit_service::AuditContext,
+    chat_service::{ChatServiceError, ChatServiceResult},
+};
+
+const MIN_SECRET_LEN: usize = 16;
+
+#[derive(sqlx::FromRow)]
+struct SubscriptionRow {
+    id: Uuid,
+    url: String,
+    description: Option<String>,
+    conversation_id: Option<Uuid>,
+    org_id: Option<Uuid>,
+    event_types: Vec<String>,
+    enabled: bool,
+    pending_count: i64,
+    dead_count: i64,
+    last_delivered_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<SubscriptionRow> for WebhookSubscription {
+    fn from(row: SubscriptionRow) -> Self {
+        Self {
+            id: row.id,
+            url: row.url,
+            description: row.description,
+            conversation_id: row.conversation_id,
+            org_id: row.org_id,
+            event_types: row.event_types,
+            enabled: row.enabled,
+            pending_count: u64::try_from(row.pending_count).unwrap_or(0),
+            dead_count: u64::try_from(row.dead_count).unwrap_or(0),
+            last_delivered_at: row.last_delivered_at.map(Timestamp),
+            created_at: Timestamp(row.created_at),
+            updated_at: Timestamp(row.updated_at),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct DeliveryRow {
+    id: Uuid,
+    subscription_id: Uuid,
+    event_id: Uuid,
+    event_type: String,
+    conversation_id: Uuid,
+    replay_of: Option<Uuid>,
+    status: String,
+    attempts: i32,
+    last_status_code: Option<i32>,
+    last_error: Option<String>,
+    next_attempt_at: DateTime<Utc>,
+    delivered_at: Option<DateTime<Utc>>,
+    dead_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<DeliveryRow> for WebhookDelivery {
+    fn from(row: DeliveryRow) -> Self {
+        Self {
+            id: row.id,
+            subscription_id: row.subscription_id,
+            event_id: row.event_id,
+            event_type: row.event_type,
+            conversation_id: row.conversation_id,
+            replay_of: row.replay_of,
+            status: row.status.parse().unwrap_or(WebhookDeliveryStatus::Pending),
+            attempts: u32::try_from(row.attempts).unwrap_or(0),
+            last_status_code: row
+                .last_status_code
+                .and_then(|code| u16::try_from(code).ok()),
+            last_error: row.last_error,
+            next_attempt_at: Timestamp(row.next_attempt_at),
+            delivered_at: row.delivered_at.map(Timestamp),
+            dead_at: row.dead_at.map(Timestamp),
+            created_at: Timestamp(row.created_at),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AttemptRow {
+    attempted_at: DateTime<Utc>,
+    status_code: Option<i32>,
+    error: Option<String>,
+    duration_ms: i32,
+}
+
+impl From<AttemptRow> for WebhookDeliveryAttempt {
+    fn from(row: AttemptRow) -> Self {
+        Self {
+            attempted_at: Timestamp(row.attempted_at),
+            status_code: row.status_code.and_then(|code| u16::try_from(code).ok()),
+            error: row.error,
+            duration_ms: u32::try_from(row.duration_ms).unwrap_or(0),
+        }
+    }
+}
+
+const SUBSCRIPTION_COLUMNS: &str = "SELECT id, url, description, conversation_id, org_id, \
+     event_types, enabled, pending_count, dead_count, last_delivered_at, created_at, updated_at \
+     FROM rustygpt.sp_webhook_subscription_list($1)";
+
+const DELIVERY_COLUMNS: &str = "SELECT id, subscription_id, event_id, event_type, \
+     conversation_id, replay_of, status, attempts, last_status_code, last_error, \
+     next_attempt_at, delivered_at, dead_at, created_at \
+     FROM rustygpt.sp_webhook_delivery_list($1, $2, $3, $4)";
+
+fn validate_url(url: &str) -> ChatServiceResult<()> {
+    match Url::parse(url.trim()) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => Ok(()),
+        _ => Err(ChatServiceError::Validation(
+            "url must be an absolute http or https URL".to_string(),
+        )),
+    }
+}
+
+fn validate_event_types(event_types: &[String]) -> ChatServiceResult<()> {
+    match event_types
+        .iter()
+        .find(|event| !WEBHOOK_EVENT_TYPES.contains(&event.as_str()))
+    {
+        Some(event) => Err(ChatServiceError::Validation(format!(
+            "unknown webhook event type '{event}'"
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn validate_secret(secret: Option<&str>) -> ChatServiceResult<()> {
+    match secret {
+        Some(secret) if secret.chars().count() < MIN_SECRET_LEN => {
+            Err(ChatServiceError::Validation(format!(
+                "secret must be at least {MIN_SECRET_LEN} characters"
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn generate_secret() -> String {
+    let mut raw = [0u8; 32];
+    OsRng.fill_bytes(&mut raw);
+    format!("whsec_{}", URL_SAFE_NO_PAD.encode(raw))
+}
+
+#[derive(Clone)]
+pub struct WebhookService {
+    pool: PgPool,
+}
+
+impl WebhookService {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(name = "webhook.list", skip(self), err)]
+    pub async fn list(&self) -> ChatServiceResult<Vec<WebhookSubscription>> {
+        let rows = sqlx::query_as::<_, SubscriptionRow>(SUBSCRIPTION_COLUMNS)
+            .bind(None::<Uuid>)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(rows.into_iter().map(WebhookSubscription::from).collect())
+    }
+
+    #[instrument(name = "webhook.get", skip(self), err)]
+    pub async fn get(&self, id: Uuid) -> ChatServiceResult<WebhookSubscription> {
+        sqlx::query_as::<_, SubscriptionRow>(SUBSCRIPTION_COLUMNS)
+            .bind(Some(id))
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?
+            .map(WebhookSubscription::from)
+            .ok_or_else(|| ChatServiceError::NotFound("webhook not found".to_string()))
+    }
+
+    /// Creates a subscription, generating a signing secret when none is supplied. The secret
+    /// is returned here and never again.
+    #[instrument(name = "webhook.create", skip(self, audit, request), err)]
+    pub async fn create(
+        &self,
+        audit: &AuditContext,
+        request: &CreateWebhookRequest,
+    ) -> ChatServiceResult<CreateWebhookResponse> {
+        validate_url(&request.url)?;
+        validate_event_types(&request.event_types)?;
+        validate_secret(request.secret.as_deref())?;
+        if request.conversation_id.is_some() == request.org_id.is_some() {
+            return Err(ChatServiceError::Validation(
+                "set exactly one of conversation_id and org_id".to_string(),
+            ));
+        }
+        let secret = request.secret.clone().unwrap_or_else(generate_secret);
+
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT rustygpt.sp_webhook_subscription_create($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(request.url.trim())
+        .bind(request.description.as_deref())
+        .bind(&secret)
+        .bind(request.conversation_id)
+        .bind(request.org_id)
+        .bind(&request.event_types)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+
+        Ok(CreateWebhookResponse {
+            subscription: self.get(id).await?,
+            secret,
+        })
+    }
+
+    #[instrument(name = "webhook.update", skip(self, audit, request), err)]
+    pub async fn update(
+        &self,
+        audit: &AuditContext,
+        id: Uuid,
+        request: &UpdateWebhookRequest,
+    ) -> ChatServiceResult<WebhookSubscription> {
+        validate_url(&request.url)?;
+        validate_event_types(&request.event_types)?;
+        validate_secret(request.secret.as_deref())?;
+
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        sqlx::query("SELECT rustygpt.sp_webhook_subscription_update($1, $2, $3, $4, $5, $6)")
+            .bind(id)
+            .bind(request.url.trim())
+            .bind(request.description.as_deref())
+            .bind(&request.event_types)
+            .bind(request.enabled)
+            .bind(request.secret.as_deref())
+            .execute(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        self.get(id).await
+    }
+
+    /// Deletes a subscription together with its delivery log.
+    #[instrument(name = "webhook.delete", skip(self, audit), err)]
+    pub async fn delete(&self, audit: &AuditContext, id: Uuid) -> ChatServiceResult<()> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let deleted =
+            sqlx::query_scalar::<_, bool>("SELECT rustygpt.sp_webhook_subscription_delete($1)")
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        if deleted {
+            Ok(())
+        } else {
+            Err(ChatServiceError::NotFound("webhook not found".to_string()))
+        }
+    }
+
+    /// A subscription's deliveries, newest first.
+    #[instrument(name = "webhook.deliveries", skip(self), err)]
+    pub async fn deliveries(
+        &self,
+        subscription_id: Uuid,
+        query: &WebhookDeliveryQuery,
+    ) -> ChatServiceResult<Vec<WebhookDelivery>> {
+        self.get(subscription_id).await?;
+        let limit = query
+            .limit
+            .map_or(100, |limit| i32::try_from(limit).unwrap_or(i32::MAX));
+        let rows = sqlx::query_as::<_, DeliveryRow>(DELIVERY_COLUMNS)
+            .bind(Some(subscription_id))
+            .bind(query.status.map(WebhookDeliveryStatus::as_str))
+            .bind(limit)
+            .bind(None::<Uuid>)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        Ok(rows.into_iter().map(WebhookDelivery::from).collect())
+    }
+
+    /// One delivery with every HTTP attempt made for it.
+    #[instrument(name = "webhook.delivery", skip(self), err)]
+    pub async fn delivery(&self, delivery_id: Uuid) -> ChatServiceResult<WebhookDeliveryDetail> {
+        let delivery = sqlx::query_as::<_, DeliveryRow>(DELIVERY_COLUMNS)
+            .bind(None::<Uuid>)
+            .bind(None::<&str>)
+            .bind(1_i32)
+            .bind(Some(delivery_id))
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(ChatServiceError::from_db_error)?
+            .map(WebhookDelivery::from)
+            .ok_or_else(|| ChatServiceError::NotFound("delivery not found".to_string()))?;
+        let attempts = sqlx::query_as::<_, AttemptRow>(
+            "SELECT attempted_at, status_code, error, duration_ms
+             FROM rustygpt.sp_webhook_delivery_attempts($1)",
+        )
+        .bind(delivery_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ChatServiceError::from_db_error)?;
+        Ok(WebhookDeliveryDetail {
+            delivery,
+            attempts: attempts
+                .into_iter()
+                .map(WebhookDeliveryAttempt::from)
+                .collect(),
+        })
+    }
+
+    /// Queues a new delivery of the same event, returned in its pending state.
+    #[instrument(name = "webhook.replay", skip(self, audit), err)]
+    pub async fn replay(
+        &self,
+        audit: &AuditContext,
+        delivery_id: Uuid,
+    ) -> ChatServiceResult<WebhookDeliveryDetail> {
+        let mut tx = audit
+            .begin(&self.pool)
+            .await
+            .map_err(ChatServiceError::from)?;
+        let replay_id = sqlx::query_scalar::<_, Uuid>("SELECT rustygpt.sp_webhook_replay($1)")
+            .bind(delivery_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ChatServiceError::from_db_error)?;
+        tx.commit().await.map_err(ChatServiceError::from)?;
+        self.delivery(replay_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_validated_before_reaching_the_database() {
+        assert!(validate_url("https://hooks.example.com/rustygpt").is_ok());
+        assert!(validate_url("ftp://hooks.example.com").is_err());
+        assert!(validate_url("not a url").is_err());
+
+        assert!(validate_event_types(&["message.done".to_string()]).is_ok());
+        assert!(validate_event_types(&["message.delta".to_string()]).is_err());
+
+        assert!(validate_secret(None).is_ok());
+        assert!(validate_secret(Some("short")).is_err());
+        assert!(generate_secret().len() >= MIN_SECRET_LEN);
+    }
+}
@@ -333,6 +333,7 @@ mod tests {
         request.extensions_mut().insert(RequestContext {
             request_id: "req-1".into(),
             session: None,
+            ..RequestContext::default()
         });
 
         let mut make_span = HttpMakeSpan;
@@ -13,6 +13,7 @@
 use directories::BaseDirs;
 use serde::{Deserialize, Serialize, ser::Serializer};
 use std::{
+    collections::BTreeMap,
     env, fmt, fs,
     path::{Path, PathBuf},
     str::FromStr,
@@ -141,6 +142,9 @@ pub struct ServerConfig {
     pub public_base_url: Url,
     pub cors: CorsConfig,
     pub request_id_header: String,
+    /// How long in-flight assistant streams may keep running after a shutdown signal
+    /// before they are cancelled and marked interrupted.
+    pub shutdown_grace_seconds: u64,
 }
 
 impl fmt::Debug for ServerConfig {
@@ -151,6 +155,7 @@ impl fmt::Debug for ServerConfig {
             .field("public_base_url", &self.public_base_url)
             .field("cors", &self.cors)
             .field("request_id_header", &self.request_id_header)
+            .field("shutdown_grace_seconds", &self.shutdown_grace_seconds)
             .finish()
     }
 }
@@ -369,6 +374,13 @@ pub struct AuthConfig {
     pub absolute_seconds: u64,
     pub csrf: bool,
     pub suspicious_check: bool,
+    /// Consecutive failed passwords before an account is locked; `0` disables lockout.
+    pub lockout_threshold: u32,
+    /// Length of the first lockout; each further failure doubles it.
+    pub lockout_base_seconds: u64,
+    pub lockout_max_seconds: u64,
+    /// Extra delay per consecutive failure before a rejected login is answered.
+    pub failure_delay_ms: u64,
 }
 
 impl fmt::Debug for AuthConfig {
@@ -378,6 +390,10 @@ impl fmt::Debug for AuthConfig {
             .field("absolute_seconds", &self.absolute_seconds)
             .field("csrf", &self.csrf)
             .field("suspicious_check", &self.suspicious_check)
+            .field("lockout_threshold", &self.lockout_threshold)
+            .field("lockout_base_seconds", &self.lockout_base_seconds)
+            .field("lockout_max_seconds", &self.lockout_max_seconds)
+            .field("failure_delay_ms", &self.failure_delay_ms)
             .finish()
     }
 }
@@ -389,6 +405,10 @@ impl Default for AuthConfig {
             absolute_seconds: 604_800,
             csrf: true,
             suspicious_check: false,
+            lockout_threshold: 5,
+            lockout_base_seconds: 60,
+            lockout_max_seconds: 3_600,
+            failure_delay_ms: 250,
         }
     }
 }
@@ -454,6 +474,8 @@ impl Default for ApiConfig {
 pub struct OAuthConfig {
     pub redirect_base: Option<Url>,
     pub github: Option<OAuthProvider>,
+    /// `OpenID` Connect providers keyed by the name used in `/api/auth/oidc/{name}`.
+    pub providers: BTreeMap<String, OidcProviderConfig>,
 }
 
 impl fmt::Debug for OAuthConfig {
@@ -464,6 +486,7 @@ impl fmt::Debug for OAuthConfig {
                 "github_configured",
                 &self.github.as_ref().map(OAuthProvider::is_configured),
             )
+            .field("providers", &self.providers)
             .finish()
     }
 }
@@ -497,6 +520,194 @@ impl fmt::Debug for OAuthProvider {
     }
 }
 
+/// A generic `OpenID` Connect provider such as Keycloak, Authentik or a corporate SSO.
+#[derive(Serialize, Clone)]
+pub struct OidcProviderConfig {
+    /// Label for the sign-in button; defaults to the provider name.
+    pub display_name: String,
+    /// Issuer URL; `<issuer>/.well-known/openid-configuration` must serve the discovery document.
+    pub issuer: Url,
+    pub client_id: String,
+    /// Empty for public clients, which rely on PKCE alone.
+    pub client_secret: SecretString,
+    pub token_auth_method: OidcTokenAuthMethod,
+    /// Requested scopes; `openid` is always added.
+    pub scopes: Vec<String>,
+    /// Sign in existing accounts whose email matches a verified email from the provider.
+    pub link_existing_users: bool,
+    /// Create an account on first sign-in when no identity or email matches.
+    pub allow_signup: bool,
+}
+
+impl OidcProviderConfig {
+    fn new(name: &str, issuer: Url, client_id: String) -> Self {
+        Self {
+            display_name: name.to_string(),
+            issuer,
+            client_id,
+            client_secret: SecretString::new(String::new()),
+            token_auth_method: OidcTokenAuthMethod::default(),
+            scopes: vec!["openid".into(), "email".into(), "profile".into()],
+            link_existing_users: true,
+            allow_signup: true,
+        }
+    }
+}
+
+impl fmt::Debug for OidcProviderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OidcProviderConfig")
+            .field("display_name", &self.display_name)
+            .field("issuer", &self.issuer.as_str())
+            .field("client_id", &self.client_id)
+            .field("client_secret_set", &!self.client_secret.is_empty())
+            .field("token_auth_method", &self.token_auth_method)
+            .field("scopes", &self.scopes)
+            .field("link_existing_users", &self.link_existing_users)
+            .field("allow_signup", &self.allow_signup)
+            .finish()
+    }
+}
+
+/// How the client secret is presented to the token endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OidcTokenAuthMethod {
+    #[default]
+    ClientSecretBasic,
+    ClientSecretPost,
+}
+
+/// Outgoing mail configuration.
+#[derive(Serialize, Clone)]
+pub struct MailConfig {
+    pub transport: MailTransportKind,
+    pub from: String,
+    /// Directory that receives `.eml` files when `transport = "file"`.
+    pub file_dir: PathBuf,
+    pub smtp: SmtpConfig,
+    pub max_attempts: u32,
+    pub poll_interval_seconds: u64,
+    pub password_reset_ttl_seconds: u64,
+    pub verification_ttl_seconds: u64,
+}
+
+impl fmt::Debug for MailConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MailConfig")
+            .field("transport", &self.transport)
+            .field("from", &self.from)
+            .field("file_dir", &self.file_dir)
+            .field("smtp", &self.smtp)
+            .field("max_attempts", &self.max_attempts)
+            .field("poll_interval_seconds", &self.poll_interval_seconds)
+            .field(
+                "password_reset_ttl_seconds",
+                &self.password_reset_ttl_seconds,
+            )
+            .field("verification_ttl_seconds", &self.verification_ttl_seconds)
+            .finish()
+    }
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            transport: MailTransportKind::default(),
+            from: "RustyGPT <no-reply@localhost>".into(),
+            file_dir: PathBuf::from("./mail"),
+            smtp: SmtpConfig::default(),
+            max_attempts: 5,
+            poll_interval_seconds: 10,
+            password_reset_ttl_seconds: 3_600,
+            verification_ttl_seconds: 86_400,
+        }
+    }
+}
+
+/// Outgoing webhook delivery.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookConfig {
+    /// Queue and deliver webhooks; subscriptions are kept but ignored when off.
+    pub enabled: bool,
+    pub max_attempts: u32,
+    pub poll_interval_seconds: u64,
+    /// Per-request timeout; a receiver that does not answer in time counts as a failure.
+    pub timeout_seconds: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_attempts: 8,
+            poll_interval_seconds: 5,
+            timeout_seconds: 10,
+        }
+    }
+}
+
+/// Delivery backend for outgoing mail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MailTransportKind {
+    Smtp,
+    File,
+    /// Logs each message; the default so local setups work without a relay.
+    #[default]
+    Stdout,
+}
+
+impl FromStr for MailTransportKind {
+    type Err = ConfigError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "smtp" => Ok(Self::Smtp),
+            "file" => Ok(Self::File),
+            "stdout" | "log" => Ok(Self::Stdout),
+            other => Err(ConfigError::InvalidValue {
+                field: "mail.transport".into(),
+                message: format!("unknown mail transport '{other}'"),
+            }),
+        }
+    }
+}
+
+/// SMTP relay settings.
+#[derive(Serialize, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: SecretString,
+    pub starttls: bool,
+}
+
+impl fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("password_set", &!self.password.is_empty())
+            .field("starttls", &self.starttls)
+            .finish()
+    }
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".into(),
+            port: 587,
+            username: None,
+            password: SecretString::new(String::new()),
+            starttls: true,
+        }
+    }
+}
+
 /// Database connectivity configuration.
 #[derive(Serialize, Clone)]
 pub struct DatabaseConfig {
@@ -536,6 +747,8 @@ pub struct SseConfig {
     pub id_prefix: String,
     pub replay_retention_seconds: u64,
     pub max_backfill_events: usize,
+    /// How events reach subscribers connected to other server instances.
+    pub backend: SseHubBackend,
     pub persistence: SsePersistenceConfig,
     pub backpressure: SseBackpressureConfig,
 }
@@ -548,6 +761,7 @@ impl fmt::Debug for SseConfig {
             .field("id_prefix", &self.id_prefix)
             .field("replay_retention_seconds", &self.replay_retention_seconds)
             .field("max_backfill_events", &self.max_backfill_events)
+            .field("backend", &self.backend)
             .field("persistence", &self.persistence)
             .field("backpressure", &self.backpressure)
             .finish()
@@ -562,12 +776,39 @@ impl Default for SseConfig {
             id_prefix: "evt_".into(),
             replay_retention_seconds: 1800,
             max_backfill_events: 512,
+            backend: SseHubBackend::default(),
             persistence: SsePersistenceConfig::default(),
             backpressure: SseBackpressureConfig::default(),
         }
     }
 }
 
+/// Fan-out backend for stream events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SseHubBackend {
+    /// Events stay within the process; enough for a single instance.
+    #[default]
+    Memory,
+    /// Events are relayed between instances through Postgres `LISTEN`/`NOTIFY`.
+    Postgres,
+}
+
+impl FromStr for SseHubBackend {
+    type Err = ConfigError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "memory" => Ok(Self::Memory),
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            other => Err(ConfigError::InvalidValue {
+                field: "sse.backend".into(),
+                message: format!("unknown hub backend '{other}'"),
+            }),
+        }
+    }
+}
+
 /// Persistence controls for SSE event history.
 #[derive(Serialize, Clone)]
 pub struct SsePersistenceConfig {
@@ -758,6 +999,8 @@ pub struct Config {
     pub session: SessionConfig,
     pub api: ApiConfig,
     pub oauth: OAuthConfig,
+    pub mail: MailConfig,
+    pub webhooks: WebhookConfig,
     pub db: DatabaseConfig,
     pub sse: SseConfig,
     pub well_known: WellKnownConfig,
@@ -780,6 +1023,8 @@ impl fmt::Debug for Config {
             .field("session", &self.session)
             .field("api", &self.api)
             .field("oauth", &self.oauth)
+            .field("mail", &self.mail)
+            .field("webhooks", &self.webhooks)
             .field("db", &self.db)
             .field("sse", &self.sse)
             .field("well_known", &self.well_known)
@@ -888,6 +1133,7 @@ impl Config {
                 public_base_url,
                 cors: CorsConfig::defaults(profile),
                 request_id_header: "x-request-id".into(),
+                shutdown_grace_seconds: 30,
             },
             security,
             auth,
@@ -896,6 +1142,8 @@ impl Config {
             session,
             api: ApiConfig::default(),
             oauth: OAuthConfig::default(),
+            mail: MailConfig::default(),
+            webhooks: WebhookConfig::default(),
             db: DatabaseConfig::default(),
             sse: SseConfig::default(),
             well_known: WellKnownConfig::default(),
@@ -948,6 +1196,14 @@ impl Config {
             self.apply_oauth_partial(oauth, &mut flags)?;
         }
 
+        if let Some(mail) = &partial.mail {
+            self.apply_mail_partial(mail);
+        }
+
+        if let Some(webhooks) = &partial.webhooks {
+            self.apply_webhooks_partial(webhooks);
+        }
+
         if let Some(database) = &partial.db {
             self.apply_database_partial(database);
         }
@@ -1009,6 +1265,9 @@ impl Config {
         if let Some(header) = &server.request_id_header {
             self.server.request_id_header.clone_from(header);
         }
+        if let Some(grace) = server.shutdown_grace_seconds {
+            self.server.shutdown_grace_seconds = grace;
+        }
 
         Ok(())
     }
@@ -1092,6 +1351,18 @@ impl Config {
         if let Some(suspicious) = auth.suspicious_check {
             self.auth.suspicious_check = suspicious;
         }
+        if let Some(threshold) = auth.lockout_threshold {
+            self.auth.lockout_threshold = threshold;
+        }
+        if let Some(base) = auth.lockout_base_seconds {
+            self.auth.lockout_base_seconds = base;
+        }
+        if let Some(max) = auth.lockout_max_seconds {
+            self.auth.lockout_max_seconds = max;
+        }
+        if let Some(delay) = auth.failure_delay_ms {
+            self.auth.failure_delay_ms = delay;
+        }
     }
 
     const fn apply_rate_limit_partial(&mut self, rate_limits: &RateLimitPartial) {
@@ -1167,10 +1438,128 @@ impl Config {
             }
             self.oauth.github = Some(provider);
         }
+        if let Some(providers) = &oauth.providers {
+            for (name, partial) in providers {
+                self.apply_oidc_provider_partial(name, partial)?;
+            }
+        }
+
+        Ok(())
+    }
 
+    fn apply_oidc_provider_partial(
+        &mut self,
+        name: &str,
+        partial: &OidcProviderPartial,
+    ) -> Result<(), ConfigError> {
+        let field = format!("oauth.providers.{name}");
+        let mut provider = if let Some(existing) = self.oauth.providers.remove(name) {
+            existing
+        } else {
+            let issuer = partial
+                .issuer
+                .as_deref()
+                .ok_or_else(|| ConfigError::InvalidValue {
+                    field: format!("{field}.issuer"),
+                    message: "issuer is required".into(),
+                })?;
+            let client_id = partial
+                .client_id
+                .clone()
+                .ok_or_else(|| ConfigError::InvalidValue {
+                    field: format!("{field}.client_id"),
+                    message: "client_id is required".into(),
+                })?;
+            OidcProviderConfig::new(
+                name,
+                parse_url(&format!("{field}.issuer"), issuer)?,
+                client_id,
+            )
+        };
+        if let Some(display_name) = &partial.display_name {
+            provider.display_name.clone_from(display_name);
+        }
+        if let Some(issuer) = &partial.issuer {
+            provider.issuer = parse_url(&format!("{field}.issuer"), issuer)?;
+        }
+        if let Some(client_id) = &partial.client_id {
+            provider.client_id.clone_from(client_id);
+        }
+        if let Some(client_secret) = &partial.client_secret {
+            provider.client_secret = SecretString::new(client_secret.clone());
+        }
+        if let Some(method) = partial.token_auth_method {
+            provider.token_auth_method = method;
+        }
+        if let Some(scopes) = &partial.scopes {
+            provider.scopes.clone_from(scopes);
+        }
+        if let Some(link) = partial.link_existing_users {
+            provider.link_existing_users = link;
+        }
+        if let Some(signup) = partial.allow_signup {
+            provider.allow_signup = signup;
+        }
+        self.oauth.providers.insert(name.to_string(), provider);
         Ok(())
     }
 
+    fn apply_mail_partial(&mut self, mail: &MailPartial) {
+        if let Some(transport) = mail.transport {
+            self.mail.transport = transport;
+        }
+        if let Some(from) = &mail.from {
+            self.mail.from.clone_from(from);
+        }
+        if let Some(file_dir) = &mail.file_dir {
+            self.mail.file_dir.clone_from(file_dir);
+        }
+        if let Some(smtp) = &mail.smtp {
+            if let Some(host) = &smtp.host {
+                self.mail.smtp.host.clone_from(host);
+            }
+            if let Some(port) = smtp.port {
+                self.mail.smtp.port = port;
+            }
+            if let Some(username) = &smtp.username {
+                self.mail.smtp.username = Some(username.clone());
+            }
+            if let Some(password) = &smtp.password {
+                self.mail.smtp.password = SecretString::new(password.clone());
+            }
+            if let Some(starttls) = smtp.starttls {
+                self.mail.smtp.starttls = starttls;
+            }
+        }
+        if let Some(max_attempts) = mail.max_attempts {
+            self.mail.max_attempts = max_attempts;
+        }
+        if let Some(interval) = mail.poll_interval_seconds {
+            self.mail.poll_interval_seconds = interval;
+        }
+        if let Some(ttl) = mail.password_reset_ttl_seconds {
+            self.mail.password_reset_ttl_seconds = ttl;
+        }
+        if let Some(ttl) = mail.verification_ttl_seconds {
+            self.mail.verification_ttl_seconds = ttl;
+        }
+    }
+
+    const fn apply_webhooks_partial(&mut self, webhooks: &WebhookPartial) {
+        if let Some(enabled) = webhooks.enabled {
+            self.webhooks.enabled = enabled;
+        }
+        if let Some(max_attempts) = webhooks.max_attempts {
+            self.webhooks.max_attempts = max_attempts;
+        }
+        if let Some(interval) = webhooks.poll_interval_seconds {
+            self.webhooks.poll_interval_seconds = interval;
+        }
+        if let Some(timeout) = webhooks.timeout_seconds {
+            self.webhooks.timeout_seconds = timeout;
+        }
+    }
+
     fn apply_database_partial(&mut self, database: &DatabasePartial) {
         if let Some(url) = &database.url {
             self.db.url.clone_from(url);
@@ -1202,6 +1591,9 @@ impl Config {
         if let Some(max_backfill) = sse.max_backfill_events {
             self.sse.max_backfill_events = clamp_usize(max_backfill);
         }
+        if let Some(backend) = sse.backend {
+            self.sse.backend = backend;
+        }
         if let Some(persistence) = &sse.persistence {
             self.apply_sse_persistence_partial(persistence);
         }
@@ -1291,6 +1683,8 @@ impl Config {
         self.apply_env_session_overrides()?;
         self.apply_env_api_overrides()?;
         self.apply_env_oauth_overrides(&mut flags)?;
+        self.apply_env_mail_overrides()?;
+        self.apply_env_webhook_overrides()?;
         self.apply_env_database_overrides()?;
         self.apply_env_sse_overrides()?;
         self.apply_env_feature_overrides()?;
@@ -1341,6 +1735,9 @@ impl Config {
         if let Some(header) = env_value(&["server", "request_id_header"]) {
             self.server.request_id_header = header;
         }
+        if let Some(grace) = env_value_u64(&["server", "shutdown_grace_seconds"])? {
+            self.server.shutdown_grace_seconds = grace;
+        }
         Ok(())
     }
 
@@ -1400,6 +1797,18 @@ impl Config {
         if let Some(suspicious) = env_value_bool(&["auth", "suspicious_check"])? {
             self.auth.suspicious_check = suspicious;
         }
+        if let Some(threshold) = env_value_u32(&["auth", "lockout_threshold"])? {
+            self.auth.lockout_threshold = threshold;
+        }
+        if let Some(base) = env_value_u64(&["auth", "lockout_base_seconds"])? {
+            self.auth.lockout_base_seconds = base;
+        }
+        if let Some(max) = env_value_u64(&["auth", "lockout_max_seconds"])? {
+            self.auth.lockout_max_seconds = max;
+        }
+        if let Some(delay) = env_value_u64(&["auth", "failure_delay_ms"])? {
+            self.auth.failure_delay_ms = delay;
+        }
         Ok(())
     }
 
@@ -1483,6 +1892,70 @@ impl Config {
             provider.client_secret = SecretString::new(client_secret);
             self.oauth.github = Some(provider);
         }
+        // Providers are declared in the file; the environment only supplies their secrets.
+        for (name, provider) in &mut self.oauth.providers {
+            if let Some(client_secret) =
+                env_value(&["oauth", "providers", name.as_str(), "client_secret"])
+            {
+                provider.client_secret = SecretString::new(client_secret);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_env_mail_overrides(&mut self) -> Result<(), ConfigError> {
+        if let Some(transport) = env_value(&["mail", "transport"]) {
+            self.mail.transport = MailTransportKind::from_str(&transport)?;
+        }
+        if let Some(from) = env_value(&["mail", "from"]) {
+            self.mail.from = from;
+        }
+        if let Some(file_dir) = env_value(&["mail", "file_dir"]) {
+            self.mail.file_dir = PathBuf::from(file_dir);
+        }
+        if let Some(host) = env_value(&["mail", "smtp", "host"]) {
+            self.mail.smtp.host = host;
+        }
+        if let Some(port) = env_value_u16(&["mail", "smtp", "port"])? {
+            self.mail.smtp.port = port;
+        }
+        if let Some(username) = env_value(&["mail", "smtp", "username"]) {
+            self.mail.smtp.username = Some(username);
+        }
+        if let Some(password) = env_value(&["mail", "smtp", "password"]) {
+            self.mail.smtp.password = SecretString::new(password);
+        }
+        if let Some(starttls) = env_value_bool(&["mail", "smtp", "starttls"])? {
+            self.mail.smtp.starttls = starttls;
+        }
+        if let Some(max_attempts) = env_value_u32(&["mail", "max_attempts"])? {
+            self.mail.max_attempts = max_attempts;
+        }
+        if let Some(interval) = env_value_u64(&["mail", "poll_interval_seconds"])? {
+            self.mail.poll_interval_seconds = interval;
+        }
+        if let Some(ttl) = env_value_u64(&["mail", "password_reset_ttl_seconds"])? {
+            self.mail.password_reset_ttl_seconds = ttl;
+        }
+        if let Some(ttl) = env_value_u64(&["mail", "verification_ttl_seconds"])? {
+            self.mail.verification_ttl_seconds = ttl;
+        }
+        Ok(())
+    }
+
+    fn apply_env_webhook_overrides(&mut self) -> Result<(), ConfigError> {
+        if let Some(enabled) = env_value_bool(&["webhooks", "enabled"])? {
+            self.webhooks.enabled = enabled;
+        }
+        if let Some(max_attempts) = env_value_u32(&["webhooks", "max_attempts"])? {
+            self.webhooks.max_attempts = max_attempts;
+        }
+        if let Some(interval) = env_value_u64(&["webhooks", "poll_interval_seconds"])? {
+            self.webhooks.poll_interval_seconds = interval;
+        }
+        if let Some(timeout) = env_value_u64(&["webhooks", "timeout_seconds"])? {
+            self.webhooks.timeout_seconds = timeout;
+        }
         Ok(())
     }
 
@@ -1518,6 +1991,9 @@ impl Config {
         if let Some(max_backfill) = env_value_usize(&["sse", "max_backfill_events"])? {
             self.sse.max_backfill_events = max_backfill;
         }
+        if let Some(backend) = env_value(&["sse", "backend"]) {
+            self.sse.backend = SseHubBackend::from_str(&backend)?;
+        }
         if let Some(enabled) = env_value_bool(&["sse", "persistence", "enabled"])? {
             self.sse.persistence.enabled = enabled;
         }
@@ -1577,6 +2053,9 @@ impl Config {
         self.validate_rate_limits(&mut errors);
         self.validate_limits(&mut errors);
         self.validate_session(&mut errors);
+        self.validate_mail(&mut errors);
+        self.validate_webhooks(&mut errors);
+        self.validate_oidc_providers(&mut errors, &mut warnings);
         self.validate_database(&mut errors, &mut warnings);
         self.validate_logging(&mut errors);
         self.validate_sse(&mut errors, &mut warnings);
@@ -1656,6 +2135,17 @@ impl Config {
                 "auth.absolute_seconds must be greater than or equal to auth.idle_seconds".into(),
             );
         }
+        if self.auth.lockout_threshold > 0 {
+            if self.auth.lockout_base_seconds == 0 {
+                errors.push("auth.lockout_base_seconds must be greater than zero".into());
+            }
+            if self.auth.lockout_max_seconds < self.auth.lockout_base_seconds {
+                errors.push(
+                    "auth.lockout_max_seconds must be greater than or equal to auth.lockout_base_seconds"
+                        .into(),
+                );
+            }
+        }
     }
 
     fn validate_rate_limits(&self, errors: &mut Vec<String>) {
@@ -1696,6 +2186,67 @@ impl Config {
         }
     }
 
+    fn validate_mail(&self, errors: &mut Vec<String>) {
+        if self.mail.from.trim().is_empty() {
+            errors.push("mail.from must not be empty".into());
+        }
+        if self.mail.transport == MailTransportKind::Smtp && self.mail.smtp.host.trim().is_empty() {
+            errors.push("mail.smtp.host must not be empty when mail.transport is smtp".into());
+        }
+        if self.mail.max_attempts == 0 {
+            errors.push("mail.max_attempts must be greater than zero".into());
+        }
+        if self.mail.poll_interval_seconds == 0 {
+            errors.push("mail.poll_interval_seconds must be greater than zero".into());
+        }
+        if self.mail.password_reset_ttl_seconds == 0 {
+            errors.push("mail.password_reset_ttl_seconds must be greater than zero".into());
+        }
+        if self.mail.verification_ttl_seconds == 0 {
+            errors.push("mail.verification_ttl_seconds must be greater than zero".into());
+        }
+    }
+
+    fn validate_webhooks(&self, errors: &mut Vec<String>) {
+        if self.webhooks.max_attempts == 0 {
+            errors.push("webhooks.max_attempts must be greater than zero".into());
+        }
+        if self.webhooks.poll_interval_seconds == 0 {
+            errors.push("webhooks.poll_interval_seconds must be greater than zero".into());
+        }
+        if self.webhooks.timeout_seconds == 0 {
+            errors.push("webhooks.timeout_seconds must be greater than zero".into());
+        }
+    }
+
+    fn validate_oidc_providers(&self, errors: &mut Vec<String>, warnings: &mut Vec<String>) {
+        for (name, provider) in &self.oauth.providers {
+            let field = format!("oauth.providers.{name}");
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+            {
+                errors.push(format!(
+                    "{field}: provider names may only contain a-z, 0-9, '-' and '_'"
+                ));
+            }
+            if provider.client_id.trim().is_empty() {
+                errors.push(format!("{field}.client_id must not be empty"));
+            }
+            match provider.issuer.scheme() {
+                "https" => {}
+                "http" if self.profile.is_prod() => {
+                    errors.push(format!("{field}.issuer must use https in production"));
+                }
+                "http" => warnings.push(format!(
+                    "{field}.issuer uses http; only do this against a local identity provider"
+                )),
+                other => errors.push(format!("{field}.issuer has unsupported scheme '{other}'")),
+            }
+        }
+    }
+
     fn validate_database(&self, errors: &mut Vec<String>, warnings: &mut Vec<String>) {
         if self.db.url.trim().is_empty() {
             errors.push("db.url must not be empty".into());
@@ -1898,6 +2449,10 @@ struct FileConfig {
     #[serde(default)]
     oauth: Option<OAuthPartial>,
     #[serde(default)]
+    mail: Option<MailPartial>,
+    #[serde(default)]
+    webhooks: Option<WebhookPartial>,
+    #[serde(default)]
     db: Option<DatabasePartial>,
     #[serde(default)]
     sse: Option<SsePartial>,
@@ -1929,6 +2484,7 @@ struct ServerPartial {
     #[serde(default)]
     pub cors: Option<CorsPartial>,
     pub request_id_header: Option<String>,
+    pub shutdown_grace_seconds: Option<u64>,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -1995,6 +2551,10 @@ struct AuthPartial {
     pub absolute_seconds: Option<u64>,
     pub csrf: Option<bool>,
     pub suspicious_check: Option<bool>,
+    pub lockout_threshold: Option<u32>,
+    pub lockout_base_seconds: Option<u64>,
+    pub lockout_max_seconds: Option<u64>,
+    pub failure_delay_ms: Option<u64>,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -2019,6 +2579,8 @@ struct OAuthPartial {
     pub redirect_base: Option<String>,
     #[serde(default)]
     pub github: Option<OAuthProviderPartial>,
+    #[serde(default)]
+    pub providers: Option<BTreeMap<String, OidcProviderPartial>>,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -2028,6 +2590,52 @@ struct OAuthProviderPartial {
     pub client_secret: Option<String>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OidcProviderPartial {
+    pub display_name: Option<String>,
+    pub issuer: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub token_auth_method: Option<OidcTokenAuthMethod>,
+    pub scopes: Option<Vec<String>>,
+    pub link_existing_users: Option<bool>,
+    pub allow_signup: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MailPartial {
+    pub transport: Option<MailTransportKind>,
+    pub from: Option<String>,
+    pub file_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub smtp: Option<SmtpPartial>,
+    pub max_attempts: Option<u32>,
+    pub poll_interval_seconds: Option<u64>,
+    pub password_reset_ttl_seconds: Option<u64>,
+    pub verification_ttl_seconds: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SmtpPartial {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub starttls: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WebhookPartial {
+    pub enabled: Option<bool>,
+    pub max_attempts: Option<u32>,
+    pub poll_interval_seconds: Option<u64>,
+    pub timeout_seconds: Option<u64>,
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct DatabasePartial {
@@ -2045,6 +2653,7 @@ struct SsePartial {
     pub id_prefix: Option<String>,
     pub replay_retention_seconds: Option<u64>,
     pub max_backfill_events: Option<u64>,
+    pub backend: Option<SseHubBackend>,
     #[serde(default)]
     pub persistence: Option<SsePersistencePartial>,
     #[serde(default)]
@@ -2482,6 +3091,7 @@ profile = "test"
 host = "0.0.0.0"
 port = 9090
 public_base_url = "http://localhost:9090"
+shutdown_grace_seconds = 5
 
 [rate_limits]
 auth_login_per_ip_per_min = 5
@@ -2492,6 +3102,9 @@ burst = 50
 auth_v1 = false
 well_known = false
 sse_v1 = true
+
+[sse]
+backend = "postgres"
 "#
         )
         .unwrap();
@@ -2500,13 +3113,77 @@ sse_v1 = true
             Config::load_config(Some(file.path().to_path_buf()), None).expect("load config");
         assert_eq!(config.profile, Profile::Test);
         assert_eq!(config.server.port, 9090);
+        assert_eq!(config.server.shutdown_grace_seconds, 5);
         assert_eq!(config.rate_limits.auth_login_per_ip_per_min, 5);
+        assert_eq!(config.sse.backend, SseHubBackend::Postgres);
         assert!(
             !config.features.sse_v1,
             "SSE should automatically disable when auth_v1 is false"
         );
     }
 
+    #[test]
+    #[serial]
+    fn load_oidc_providers_from_toml() {
+        clear_relevant_env();
+        let mut file = Builder::new().suffix(".toml").tempfile().unwrap();
+        writeln!(
+            file,
+            r#"
+[oauth.providers.keycloak]
+display_name = "Company SSO"
+issuer = "https://sso.example.com/realms/main"
+client_id = "rustygpt"
+client_secret = "secret"
+allow_signup = false
+
+[oauth.providers.authentik]
+issuer = "https://auth.example.com/application/o/rustygpt/"
+client_id = "public-client"
+token_auth_method = "client_secret_post"
+"#
+        )
+        .unwrap();
+
+        let config =
+            Config::load_config(Some(file.path().to_path_buf()), None).expect("load config");
+        let keycloak = &config.oauth.providers["keycloak"];
+        assert_eq!(keycloak.display_name, "Company SSO");
+        assert_eq!(keycloak.client_id, "rustygpt");
+        assert!(!keycloak.client_secret.is_empty());
+        assert!(keycloak.link_existing_users);
+        assert!(!keycloak.allow_signup);
+        assert_eq!(keycloak.scopes, ["openid", "email", "profile"]);
+
+        let authentik = &config.oauth.providers["authentik"];
+        assert_eq!(authentik.display_name, "authentik");
+        assert!(authentik.client_secret.is_empty());
+        assert_eq!(
+            authentik.token_auth_method,
+            OidcTokenAuthMethod::ClientSecretPost
+        );
+    }
+
+    #[test]
+    fn oidc_provider_names_are_validated() {
+        let mut config = Config::default_for_profile(Profile::Prod);
+        config.oauth.providers.insert(
+            "Corp SSO".into(),
+            OidcProviderConfig::new(
+                "Corp SSO",
+                Url::parse("http://sso.example.com").unwrap(),
+                String::new(),
+            ),
+        );
+
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        config.validate_oidc_providers(&mut errors, &mut warnings);
+        assert!(errors.iter().any(|msg| msg.contains("provider names")));
+        assert!(errors.iter().any(|msg| msg.contains("client_id")));
+        assert!(errors.iter().any(|msg| msg.contains("https in production")));
+    }
+
     #[test]
     #[serial]
     fn env_overrides_take_precedence() {
@@ -2594,6 +3271,10 @@ url = "sqlite://memory"
         config.auth.absolute_seconds = 0;
         config.limits.default_profile.clear();
         config.sse.max_backfill_events = 0;
+        config.mail.transport = MailTransportKind::Smtp;
+        config.mail.smtp.host.clear();
+        config.auth.lockout_max_seconds = config.auth.lockout_base_seconds - 1;
+        config.webhooks.timeout_seconds = 0;
 
         match config.validate() {
             Ok(_) => panic!("validation should have failed"),
@@ -2614,6 +3295,17 @@ url = "sqlite://memory"
                         .iter()
                         .any(|msg| msg.contains("sse.max_backfill_events"))
                 );
+                assert!(errors.iter().any(|msg| msg.contains("mail.smtp.host")));
+                assert!(
+                    errors
+                        .iter()
+                        .any(|msg| msg.contains("auth.lockout_max_seconds"))
+                );
+                assert!(
+                    errors
+                        .iter()
+                        .any(|msg| msg.contains("webhooks.timeout_seconds"))
+                );
             }
             Err(other) => panic!("unexpected error: {other:?}"),
         }
@@ -107,6 +107,7 @@ mod tests {
             path: path.to_string(),
             depth,
             created_at: crate::models::timestamp::Timestamp(Utc::now()),
+            alternates: Vec::new(),
         }
     }
 
@@ -0,0 +1,112 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::Timestamp;
+
+/// Every personal access token starts with this marker so it is easy to spot in logs and diffs.
+pub const API_TOKEN_MARKER: &str = "rgp";
+
+/// Capabilities a personal access token can be granted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+pub enum ApiTokenScope {
+    /// `OpenAI`-compatible `/v1` endpoints, including stateful `metadata.rustygpt` mode.
+    #[serde(rename = "v1:chat")]
+    V1Chat,
+    /// Mutating conversation, thread and message routes.
+    #[serde(rename = "threads:write")]
+    ThreadsWrite,
+    /// Admin routes; only grantable by users holding the admin role.
+    #[serde(rename = "admin:*")]
+    Admin,
+}
+
+impl ApiTokenScope {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::V1Chat => "v1:chat",
+            Self::ThreadsWrite => "threads:write",
+            Self::Admin => "admin:*",
+        }
+    }
+}
+
+impl fmt::Display for ApiTokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApiTokenScope {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "v1:chat" => Ok(Self::V1Chat),
+            "threads:write" => Ok(Self::ThreadsWrite),
+            "admin:*" => Ok(Self::Admin),
+            other => Err(format!("unknown token scope: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<ApiTokenScope>,
+    /// Token lifetime; omit for a token that never expires.
+    #[serde(default)]
+    pub expires_in_days: Option<u32>,
+}
+
+/// Token metadata; the secret itself is never returned after creation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ApiTokenSummary {
+    pub id: Uuid,
+    pub name: String,
+    /// Leading part of the token, safe to display for identification.
+    pub prefix: String,
+    pub scopes: Vec<ApiTokenScope>,
+    pub created_at: Timestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct CreateApiTokenResponse {
+    /// The full bearer token. It is shown exactly once and cannot be recovered.
+    pub token: String,
+    #[serde(flatten)]
+    pub summary: ApiTokenSummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ApiTokenListResponse {
+    pub tokens: Vec<ApiTokenSummary>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_round_trip_through_wire_names() {
+        for scope in [
+            ApiTokenScope::V1Chat,
+            ApiTokenScope::ThreadsWrite,
+            ApiTokenScope::Admin,
+        ] {
+            let json = serde_json::to_string(&scope).expect("serialize");
+            assert_eq!(json, format!("\"{}\"", scope.as_str()));
+            assert_eq!(scope.as_str().parse::<ApiTokenScope>(), Ok(scope));
+        }
+        assert!("admin".parse::<ApiTokenScope>().is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{Timestamp, usage::csv_field};
+
+/// A single entry of the append-only audit log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct AuditEvent {
+    pub id: i64,
+    pub occurred_at: Timestamp,
+    /// `None` for events without an authenticated actor, such as failed logins for unknown users.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor_username: Option<String>,
+    /// Dotted action name, e.g. `membership.add` or `auth.login_failed`.
+    pub action: String,
+    pub target_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+}
+
+impl AuditEvent {
+    pub const CSV_HEADER: &'static str = "id,occurred_at,actor_id,actor_username,action,target_type,target_id,ip,user_agent,request_id,metadata";
+
+    /// Renders the event as one CSV line (without the trailing newline).
+    #[must_use]
+    pub fn to_csv_row(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            self.id,
+            self.occurred_at.0.to_rfc3339(),
+            self.actor_id.map(|id| id.to_string()).unwrap_or_default(),
+            csv_field(self.actor_username.as_deref().unwrap_or_default()),
+            csv_field(&self.action),
+            csv_field(&self.target_type),
+            csv_field(self.target_id.as_deref().unwrap_or_default()),
+            csv_field(self.ip.as_deref().unwrap_or_default()),
+            csv_field(self.user_agent.as_deref().unwrap_or_default()),
+            csv_field(self.request_id.as_deref().unwrap_or_default()),
+            csv_field(&self.metadata.to_string()),
+        );
+        out
+    }
+}
+
+/// One page of audit events, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct AuditEventPage {
+    pub events: Vec<AuditEvent>,
+    /// Pass as `before` to fetch the next (older) page; absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_before: Option<i64>,
+}
+
+/// Filters for `GET /api/admin/audit`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct AuditQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<Uuid>,
+    /// Exact action or dotted prefix (`membership` matches `membership.add`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<String>,
+    /// Inclusive lower bound on `occurred_at`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `occurred_at`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTime<Utc>>,
+    /// Return events with an id lower than this cursor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn csv_row_quotes_metadata_and_blanks_missing_fields() {
+        let event = AuditEvent {
+            id: 7,
+            occurred_at: Timestamp(Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap()),
+            actor_id: None,
+            actor_username: None,
+            action: "auth.login_failed".into(),
+            target_type: "user".into(),
+            target_id: Some("alice@example.com".into()),
+            ip: Some("10.0.0.1".into()),
+            user_agent: None,
+            request_id: None,
+            metadata: json!({ "reason": "invalid_credentials" }),
+        };
+
+        assert_eq!(AuditEvent::CSV_HEADER.split(',').count(), 11);
+        assert_eq!(
+            event.to_csv_row(),
+            "7,2026-03-01T12:00:00+00:00,,,auth.login_failed,user,alice@example.com,10.0.0.1,,,\"{\"\"reason\"\":\"\"invalid_credentials\"\"}\""
+        );
+    }
+}
@@ -3,7 +3,12 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 
 use super::{
-    threads::{MembershipChangedEvent, PresenceUpdate, TypingUpdate, UnreadUpdateEvent},
+    retention::ConversationLifecycleEvent,
+    revisions::MessageEditedEvent,
+    threads::{
+        MembershipChangedEvent, MessageMentionEvent, PresenceUpdate, TypingUpdate,
+        UnreadUpdateEvent,
+    },
     timestamp::Timestamp,
 };
 
@@ -82,6 +87,9 @@ pub struct ConversationCreateRequest {
     pub title: String,
     #[serde(default)]
     pub is_group: bool,
+    /// Organization that owns the conversation; only its members can join or read it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
@@ -129,6 +137,9 @@ pub struct MessageView {
     pub path: String,
     pub depth: i32,
     pub created_at: Timestamp,
+    /// Sibling assistant variants (including this message) in creation order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternates: Vec<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
@@ -171,6 +182,22 @@ pub struct ReplyMessageResponse {
     pub depth: i32,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct RegenerateMessageRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct RegenerateMessageResponse {
+    pub source_message_id: Uuid,
+    pub parent_id: Uuid,
+    pub root_id: Uuid,
+    pub conversation_id: Uuid,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
 pub struct MessageChunkPayload {
     pub message_id: Uuid,
@@ -260,6 +287,10 @@ pub enum ConversationStreamEvent {
     MessageDelta { payload: ChatDeltaChunk },
     #[serde(rename = "message.done")]
     MessageDone { payload: MessageDoneEvent },
+    #[serde(rename = "message.edited")]
+    MessageEdited { payload: MessageEditedEvent },
+    #[serde(rename = "message.mention")]
+    MessageMention { payload: MessageMentionEvent },
     #[serde(rename = "presence.update")]
     PresenceUpdate { payload: PresenceUpdate },
     #[serde(rename = "typing.update")]
@@ -268,6 +299,8 @@ pub enum ConversationStreamEvent {
     UnreadUpdate { payload: UnreadUpdateEvent },
     #[serde(rename = "membership.changed")]
     MembershipChanged { payload: MembershipChangedEvent },
+    #[serde(rename = "conversation.lifecycle")]
+    ConversationLifecycle { payload: ConversationLifecycleEvent },
     #[serde(rename = "error")]
     Error { payload: StreamErrorEvent },
 }
@@ -308,4 +341,13 @@ mod tests {
         let json = serde_json::to_string(&summary).unwrap();
         assert!(json.contains("root_excerpt"));
     }
+
+    #[test]
+    fn regenerate_request_defaults_to_original_settings() {
+        let request: RegenerateMessageRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(request, RegenerateMessageRequest::default());
+
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(json, "{}");
+    }
 }
@@ -0,0 +1,143 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{Timestamp, chat::MessageRole, revisions::MessageRevision};
+
+/// Schema version written into every JSON export; bump when the layout changes.
+pub const CONVERSATION_EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    #[serde(rename = "md", alias = "markdown")]
+    Markdown,
+    Json,
+    Html,
+}
+
+impl ExportFormat {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+            Self::Html => "html",
+        }
+    }
+
+    #[must_use]
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Json => "application/json",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            "html" => Ok(Self::Html),
+            other => Err(format!("unsupported export format: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ExportedConversation {
+    pub id: Uuid,
+    pub title: String,
+    pub is_group: bool,
+    pub created_by: Uuid,
+    pub created_at: Timestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<Timestamp>,
+}
+
+/// Description of a file attached to a message; the file itself is not part of the export.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ExportedAttachment {
+    pub id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ExportedMessage {
+    pub id: Uuid,
+    pub root_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<Uuid>,
+    pub role: MessageRole,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_user_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    pub content: String,
+    pub path: String,
+    pub depth: i32,
+    pub created_at: Timestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edit_reason: Option<String>,
+    /// Deleted messages keep their place in the tree but never their content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forked_from_message_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<MessageRevision>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<ExportedAttachment>,
+}
+
+/// Versioned, self-contained export of a conversation (or a single thread of it).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ConversationExport {
+    pub version: u32,
+    pub exported_at: Timestamp,
+    pub conversation: ExportedConversation,
+    /// Present when the export was limited to a single thread.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_id: Option<Uuid>,
+    /// Messages in thread order: threads by creation time, each walked depth-first.
+    pub messages: Vec<ExportedMessage>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_format_parses_aliases() {
+        assert_eq!("md".parse::<ExportFormat>(), Ok(ExportFormat::Markdown));
+        assert_eq!(
+            "Markdown".parse::<ExportFormat>(),
+            Ok(ExportFormat::Markdown)
+        );
+        assert_eq!("html".parse::<ExportFormat>(), Ok(ExportFormat::Html));
+        assert!("pdf".parse::<ExportFormat>().is_err());
+        assert_eq!(
+            serde_json::to_string(&ExportFormat::Markdown).expect("serialize"),
+            "\"md\""
+        );
+    }
+}
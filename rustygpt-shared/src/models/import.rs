@@ -0,0 +1,772 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    Timestamp,
+    chat::MessageRole,
+    export::{CONVERSATION_EXPORT_VERSION, ConversationExport, ExportedAttachment},
+    revisions::MessageRevision,
+};
+
+const DEFAULT_IMPORT_TITLE: &str = "Imported conversation";
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Supported history formats: our own JSON export plus third-party ones.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    /// `conversations.json` from a `ChatGPT` data export (node mapping tree).
+    #[serde(rename = "chatgpt")]
+    ChatGpt,
+    /// One `{"messages": [...]}` object per line, as used for `OpenAI` fine-tuning.
+    #[serde(rename = "openai_jsonl", alias = "jsonl")]
+    OpenAiJsonl,
+    /// A `ConversationExport` written by `GET /api/conversations/{id}/export?format=json`.
+    #[serde(rename = "rustygpt")]
+    RustyGpt,
+}
+
+impl ImportSource {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ChatGpt => "chatgpt",
+            Self::OpenAiJsonl => "openai_jsonl",
+            Self::RustyGpt => "rustygpt",
+        }
+    }
+
+    /// Guess the format from the payload: a top-level JSON array is a `ChatGPT` export and a
+    /// single object carrying `version` and `conversation` is one of ours.
+    #[must_use]
+    pub fn detect(raw: &str) -> Self {
+        let trimmed = raw.trim_start();
+        if trimmed.starts_with('[') {
+            Self::ChatGpt
+        } else if trimmed.starts_with('{')
+            && serde_json::from_str::<Value>(trimmed).is_ok_and(|value| {
+                value.get("version").is_some() && value.get("conversation").is_some()
+            })
+        {
+            Self::RustyGpt
+        } else {
+            Self::OpenAiJsonl
+        }
+    }
+}
+
+impl fmt::Display for ImportSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ImportSource {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "chatgpt" => Ok(Self::ChatGpt),
+            "openai" | "jsonl" | "openai_jsonl" => Ok(Self::OpenAiJsonl),
+            "rustygpt" => Ok(Self::RustyGpt),
+            other => Err(format!("unsupported import format: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ImportError {
+    #[error("invalid JSON: {0}")]
+    Json(String),
+    #[error("line {line}: {message}")]
+    Line { line: usize, message: String },
+    #[error("no conversations found in import")]
+    Empty,
+}
+
+/// A message ready to be handed to `sp_import_conversation`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImportedMessage {
+    /// Identifier from the source file; only meaningful within one conversation.
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_key: Option<String>,
+    pub role: MessageRole,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<Timestamp>,
+    /// Only our own exports carry edit history and attachments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edit_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<MessageRevision>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<ExportedAttachment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedConversation {
+    pub title: String,
+    pub created_at: Option<Timestamp>,
+    /// Parents always precede their children.
+    pub messages: Vec<ImportedMessage>,
+    /// Source nodes dropped because they carried no text.
+    pub skipped_messages: usize,
+}
+
+impl ImportedConversation {
+    #[must_use]
+    pub fn thread_count(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|message| message.parent_key.is_none())
+            .count()
+    }
+
+    /// Number of messages with more than one child, i.e. regenerations or edits.
+    #[must_use]
+    pub fn branch_count(&self) -> usize {
+        let mut children: HashMap<&str, usize> = HashMap::new();
+        for parent in self
+            .messages
+            .iter()
+            .filter_map(|message| message.parent_key.as_deref())
+        {
+            *children.entry(parent).or_default() += 1;
+        }
+        children.values().filter(|count| **count > 1).count()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedImport {
+    pub source: ImportSource,
+    pub conversations: Vec<ImportedConversation>,
+    pub warnings: Vec<String>,
+}
+
+impl ParsedImport {
+    /// Summarise the parse; `conversation_ids` is filled in once the import ran.
+    #[must_use]
+    pub fn report(&self, dry_run: bool, conversation_ids: &[Uuid]) -> ImportReport {
+        let conversations: Vec<ImportConversationSummary> = self
+            .conversations
+            .iter()
+            .enumerate()
+            .map(|(index, conversation)| ImportConversationSummary {
+                title: conversation.title.clone(),
+                message_count: conversation.messages.len(),
+                thread_count: conversation.thread_count(),
+                branch_count: conversation.branch_count(),
+                skipped_messages: conversation.skipped_messages,
+                conversation_id: conversation_ids.get(index).copied(),
+            })
+            .collect();
+
+        ImportReport {
+            source: self.source,
+            dry_run,
+            total_conversations: conversations.len(),
+            total_messages: conversations.iter().map(|c| c.message_count).sum(),
+            conversations,
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ImportConversationSummary {
+    pub title: String,
+    pub message_count: usize,
+    pub thread_count: usize,
+    pub branch_count: usize,
+    pub skipped_messages: usize,
+    /// Set when the conversation was actually created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ImportReport {
+    pub source: ImportSource,
+    pub dry_run: bool,
+    pub total_conversations: usize,
+    pub total_messages: usize,
+    pub conversations: Vec<ImportConversationSummary>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Parse a history file into conversations without touching the database.
+///
+/// # Errors
+///
+/// Returns [`ImportError`] when the payload is malformed or contains no conversations.
+pub fn parse_import(source: ImportSource, raw: &str) -> Result<ParsedImport, ImportError> {
+    let mut warnings = Vec::new();
+    let conversations = match source {
+        ImportSource::ChatGpt => parse_chatgpt(raw, &mut warnings)?,
+        ImportSource::OpenAiJsonl => parse_openai_jsonl(raw, &mut warnings)?,
+        ImportSource::RustyGpt => parse_rustygpt(raw)?,
+    };
+
+    if conversations.is_empty() {
+        return Err(ImportError::Empty);
+    }
+
+    Ok(ParsedImport {
+        source,
+        conversations,
+        warnings,
+    })
+}
+
+fn parse_chatgpt(
+    raw: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<ImportedConversation>, ImportError> {
+    let value: Value =
+        serde_json::from_str(raw).map_err(|err| ImportError::Json(err.to_string()))?;
+    let entries = match value {
+        Value::Array(entries) => entries,
+        // A single exported conversation is accepted as well.
+        Value::Object(_) => vec![value],
+        _ => {
+            return Err(ImportError::Json(
+                "expected an array of conversations".into(),
+            ));
+        }
+    };
+
+    let mut conversations = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let title = entry
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .unwrap_or(DEFAULT_IMPORT_TITLE)
+            .to_string();
+
+        let Some(mapping) = entry.get("mapping").and_then(Value::as_object) else {
+            warnings.push(format!(
+                "conversation {index} ({title}) has no mapping; skipped"
+            ));
+            continue;
+        };
+
+        let mut kept: HashMap<&str, ImportedMessage> = HashMap::new();
+        let mut skipped = 0;
+        for (node_id, node) in mapping {
+            match chatgpt_message(node_id, node, &title, warnings) {
+                Some(message) => {
+                    kept.insert(node_id.as_str(), message);
+                }
+                None => skipped += 1,
+            }
+        }
+
+        // Re-attach messages whose parent was dropped to the nearest kept ancestor.
+        let kept_ids: HashSet<&str> = kept.keys().copied().collect();
+        for (node_id, message) in &mut kept {
+            message.parent_key = chatgpt_kept_ancestor(mapping, node_id, &kept_ids);
+        }
+
+        if kept.is_empty() {
+            warnings.push(format!(
+                "conversation {index} ({title}) has no text messages; skipped"
+            ));
+            continue;
+        }
+
+        conversations.push(ImportedConversation {
+            title,
+            created_at: entry.get("create_time").and_then(epoch_seconds),
+            messages: parents_first(kept.into_values().collect()),
+            skipped_messages: skipped,
+        });
+    }
+
+    Ok(conversations)
+}
+
+fn chatgpt_message(
+    node_id: &str,
+    node: &Value,
+    title: &str,
+    warnings: &mut Vec<String>,
+) -> Option<ImportedMessage> {
+    let message = node.get("message").filter(|message| !message.is_null())?;
+    let role_name = message
+        .pointer("/author/role")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let Ok(role) = MessageRole::try_from(role_name) else {
+        warnings.push(format!(
+            "{title}: node {node_id} has unknown role '{role_name}'; skipped"
+        ));
+        return None;
+    };
+
+    let content = message.get("content")?;
+    let mut text = Vec::new();
+    if let Some(parts) = content.get("parts").and_then(Value::as_array) {
+        for part in parts {
+            match part {
+                Value::String(part) => text.push(part.as_str()),
+                Value::Null => {}
+                _ => warnings.push(format!(
+                    "{title}: node {node_id} has a non-text part that was not imported"
+                )),
+            }
+        }
+    } else if let Some(body) = content.get("text").and_then(Value::as_str) {
+        text.push(body);
+    }
+
+    let content = text.join("\n");
+    if content.trim().is_empty() {
+        return None;
+    }
+
+    Some(ImportedMessage {
+        key: node_id.to_string(),
+        parent_key: None,
+        role,
+        content,
+        created_at: message.get("create_time").and_then(epoch_seconds),
+        edited_at: None,
+        edit_reason: None,
+        revisions: Vec::new(),
+        attachments: Vec::new(),
+    })
+}
+
+fn chatgpt_kept_ancestor(
+    mapping: &serde_json::Map<String, Value>,
+    node_id: &str,
+    kept: &HashSet<&str>,
+) -> Option<String> {
+    let mut current = node_id;
+    // Bounded by the node count so a malformed cycle cannot spin forever.
+    for _ in 0..mapping.len() {
+        let parent = mapping.get(current)?.get("parent")?.as_str()?;
+        if kept.contains(parent) {
+            return Some(parent.to_string());
+        }
+        current = parent;
+    }
+    None
+}
+
+fn parse_openai_jsonl(
+    raw: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<ImportedConversation>, ImportError> {
+    let mut conversations = Vec::new();
+    for (index, line) in raw.lines().enumerate() {
+        let line_no = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let value: Value = serde_json::from_str(line).map_err(|err| ImportError::Line {
+            line: line_no,
+            message: err.to_string(),
+        })?;
+        let Some(entries) = value.get("messages").and_then(Value::as_array) else {
+            return Err(ImportError::Line {
+                line: line_no,
+                message: "missing messages array".into(),
+            });
+        };
+
+        let mut messages: Vec<ImportedMessage> = Vec::new();
+        let mut skipped = 0;
+        for (position, entry) in entries.iter().enumerate() {
+            let role_name = entry
+                .get("role")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let role = match role_name {
+                // Older payloads use "function" for tool output.
+                "function" => MessageRole::Tool,
+                // Newer payloads use "developer" for system instructions.
+                "developer" => MessageRole::System,
+                other => {
+                    if let Ok(role) = MessageRole::try_from(other) {
+                        role
+                    } else {
+                        warnings.push(format!(
+                            "line {line_no}: message {position} has unknown role '{other}'; skipped"
+                        ));
+                        skipped += 1;
+                        continue;
+                    }
+                }
+            };
+
+            let content = openai_content(entry.get("content"));
+            if content.trim().is_empty() {
+                skipped += 1;
+                continue;
+            }
+
+            messages.push(ImportedMessage {
+                key: position.to_string(),
+                parent_key: messages.last().map(|previous| previous.key.clone()),
+                role,
+                content,
+                created_at: None,
+                edited_at: None,
+                edit_reason: None,
+                revisions: Vec::new(),
+                attachments: Vec::new(),
+            });
+        }
+
+        if messages.is_empty() {
+            warnings.push(format!("line {line_no}: no text messages; skipped"));
+            continue;
+        }
+
+        let title = messages
+            .iter()
+            .find(|message| message.role == MessageRole::User)
+            .map_or_else(
+                || DEFAULT_IMPORT_TITLE.to_string(),
+                |m| title_from(&m.content),
+            );
+
+        conversations.push(ImportedConversation {
+            title,
+            created_at: None,
+            messages,
+            skipped_messages: skipped,
+        });
+    }
+
+    Ok(conversations)
+}
+
+fn parse_rustygpt(raw: &str) -> Result<Vec<ImportedConversation>, ImportError> {
+    let value: Value =
+        serde_json::from_str(raw).map_err(|err| ImportError::Json(err.to_string()))?;
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .unwrap_or_default();
+    if version > u64::from(CONVERSATION_EXPORT_VERSION) {
+        return Err(ImportError::Json(format!(
+            "export version {version} is newer than the supported version {CONVERSATION_EXPORT_VERSION}"
+        )));
+    }
+    let export: ConversationExport =
+        serde_json::from_value(value).map_err(|err| ImportError::Json(err.to_string()))?;
+
+    // Deleted messages were exported without content; their replies move up to the nearest
+    // message that survived, as with skipped ChatGPT nodes.
+    let parents: HashMap<Uuid, Option<Uuid>> = export
+        .messages
+        .iter()
+        .map(|message| (message.id, message.parent_id))
+        .collect();
+    let kept: HashSet<Uuid> = export
+        .messages
+        .iter()
+        .filter(|message| message.deleted_at.is_none())
+        .map(|message| message.id)
+        .collect();
+    let kept_ancestor = |mut current: Option<Uuid>| {
+        for _ in 0..parents.len() {
+            let id = current?;
+            if kept.contains(&id) {
+                return Some(id.to_string());
+            }
+            current = parents.get(&id).copied().flatten();
+        }
+        None
+    };
+
+    let skipped = export.messages.len() - kept.len();
+    let messages: Vec<ImportedMessage> = export
+        .messages
+        .iter()
+        .filter(|message| kept.contains(&message.id))
+        .map(|message| ImportedMessage {
+            key: message.id.to_string(),
+            parent_key: kept_ancestor(message.parent_id),
+            role: message.role,
+            content: message.content.clone(),
+            created_at: Some(message.created_at.clone()),
+            edited_at: message.edited_at.clone(),
+            edit_reason: message.edit_reason.clone(),
+            revisions: message.revisions.clone(),
+            attachments: message.attachments.clone(),
+        })
+        .collect();
+
+    if messages.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(vec![ImportedConversation {
+        title: export.conversation.title,
+        created_at: Some(export.conversation.created_at),
+        messages: parents_first(messages),
+        skipped_messages: skipped,
+    }])
+}
+
+fn openai_content(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter(|part| part.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn title_from(content: &str) -> String {
+    let line = content.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= MAX_TITLE_CHARS {
+        return line.to_string();
+    }
+    let mut title: String = line.chars().take(MAX_TITLE_CHARS - 1).collect();
+    title.push('…');
+    title
+}
+
+fn epoch_seconds(value: &Value) -> Option<Timestamp> {
+    let seconds = value.as_f64()?;
+    #[allow(clippy::cast_possible_truncation)]
+    // Export timestamps are millisecond-precision epochs.
+    let millis = (seconds * 1000.0).round() as i64;
+    DateTime::from_timestamp_millis(millis).map(Timestamp)
+}
+
+/// Order messages so every parent precedes its children, oldest siblings first.
+fn parents_first(mut messages: Vec<ImportedMessage>) -> Vec<ImportedMessage> {
+    messages.sort_by(|a, b| {
+        a.created_at
+            .as_ref()
+            .map(|ts| ts.0)
+            .cmp(&b.created_at.as_ref().map(|ts| ts.0))
+            .then_with(|| a.key.cmp(&b.key))
+    });
+
+    let mut children: HashMap<Option<String>, Vec<usize>> = HashMap::new();
+    for (index, message) in messages.iter().enumerate() {
+        children
+            .entry(message.parent_key.clone())
+            .or_default()
+            .push(index);
+    }
+
+    let mut order = Vec::with_capacity(messages.len());
+    let mut stack: Vec<usize> = children.get(&None).cloned().unwrap_or_default();
+    stack.reverse();
+    while let Some(index) = stack.pop() {
+        order.push(index);
+        if let Some(next) = children.get(&Some(messages[index].key.clone())) {
+            stack.extend(next.iter().rev());
+        }
+    }
+
+    let mut slots: Vec<Option<ImportedMessage>> = messages.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|index| slots[index].take())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::export::{ExportedConversation, ExportedMessage};
+
+    const CHATGPT_EXPORT: &str = r#"[{
+        "title": "Branching chat",
+        "create_time": 1700000000.5,
+        "mapping": {
+            "root": {"id": "root", "message": null, "parent": null, "children": ["sys"]},
+            "sys": {"id": "sys", "parent": "root", "children": ["q"], "message": {
+                "author": {"role": "system"}, "content": {"content_type": "text", "parts": [""]}}},
+            "q": {"id": "q", "parent": "sys", "children": ["a1", "a2"], "message": {
+                "author": {"role": "user"}, "create_time": 1700000001.0,
+                "content": {"content_type": "text", "parts": ["Hello?"]}}},
+            "a1": {"id": "a1", "parent": "q", "children": [], "message": {
+                "author": {"role": "assistant"}, "create_time": 1700000002.0,
+                "content": {"content_type": "text", "parts": ["Hi!"]}}},
+            "a2": {"id": "a2", "parent": "q", "children": [], "message": {
+                "author": {"role": "assistant"}, "create_time": 1700000003.0,
+                "content": {"content_type": "text", "parts": ["Hello there.", {"asset": "img"}]}}}
+        }
+    }]"#;
+
+    #[test]
+    fn chatgpt_export_preserves_branches_and_reparents_skipped_nodes() {
+        let parsed = parse_import(ImportSource::ChatGpt, CHATGPT_EXPORT).expect("parse");
+        assert_eq!(parsed.conversations.len(), 1);
+
+        let conversation = &parsed.conversations[0];
+        assert_eq!(conversation.title, "Branching chat");
+        assert_eq!(conversation.skipped_messages, 2);
+        assert_eq!(conversation.thread_count(), 1);
+        assert_eq!(conversation.branch_count(), 1);
+
+        let keys: Vec<&str> = conversation
+            .messages
+            .iter()
+            .map(|m| m.key.as_str())
+            .collect();
+        assert_eq!(keys, ["q", "a1", "a2"]);
+        assert_eq!(conversation.messages[0].parent_key, None);
+        assert_eq!(conversation.messages[2].parent_key.as_deref(), Some("q"));
+        assert_eq!(conversation.messages[2].role, MessageRole::Assistant);
+        assert_eq!(parsed.warnings.len(), 1, "image part should be reported");
+    }
+
+    #[test]
+    fn openai_jsonl_builds_linear_threads() {
+        let raw = concat!(
+            r#"{"messages":[{"role":"system","content":"Be brief."},{"role":"user","content":"What is Rust?"},{"role":"assistant","content":[{"type":"text","text":"A language."}]}]}"#,
+            "\n\n",
+            r#"{"messages":[{"role":"user","content":"Second"}]}"#,
+        );
+
+        assert_eq!(ImportSource::detect(raw), ImportSource::OpenAiJsonl);
+        let parsed = parse_import(ImportSource::OpenAiJsonl, raw).expect("parse");
+        assert_eq!(parsed.conversations.len(), 2);
+
+        let first = &parsed.conversations[0];
+        assert_eq!(first.title, "What is Rust?");
+        assert_eq!(first.messages.len(), 3);
+        assert_eq!(first.messages[2].parent_key.as_deref(), Some("1"));
+        assert_eq!(first.messages[2].content, "A language.");
+        assert_eq!(first.branch_count(), 0);
+
+        let report = parsed.report(true, &[]);
+        assert_eq!(report.total_messages, 4);
+        assert!(report.conversations[0].conversation_id.is_none());
+    }
+
+    fn exported(id: Uuid, parent_id: Option<Uuid>, root_id: Uuid, minutes: i64) -> ExportedMessage {
+        let created_at = DateTime::from_timestamp(1_700_000_000 + minutes * 60, 0).expect("time");
+        ExportedMessage {
+            id,
+            root_id,
+            parent_id,
+            role: MessageRole::User,
+            author_user_id: None,
+            author_name: Some("ada".into()),
+            content: format!("message {minutes}"),
+            path: String::new(),
+            depth: 1,
+            created_at: Timestamp(created_at),
+            edited_at: None,
+            edit_reason: None,
+            deleted_at: None,
+            forked_from_message_id: None,
+            revisions: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rustygpt_export_round_trips_history_and_attachments() {
+        let root = Uuid::new_v4();
+        let deleted = Uuid::new_v4();
+        let reply = Uuid::new_v4();
+
+        let mut first = exported(root, None, root, 0);
+        first.edited_at = Some(first.created_at.clone());
+        first.edit_reason = Some("typo".into());
+        first.revisions = vec![MessageRevision {
+            revision: 1,
+            content: "mesage 0".into(),
+            edited_by: None,
+            reason: None,
+            created_at: first.created_at.clone(),
+            diff: Vec::new(),
+        }];
+        let mut gone = exported(deleted, Some(root), root, 1);
+        gone.deleted_at = Some(gone.created_at.clone());
+        gone.content = String::new();
+        let mut last = exported(reply, Some(deleted), root, 2);
+        last.role = MessageRole::Assistant;
+        last.attachments = vec![ExportedAttachment {
+            id: Uuid::new_v4(),
+            file_name: "plan.pdf".into(),
+            content_type: "application/pdf".into(),
+            size_bytes: 2048,
+            created_at: last.created_at.clone(),
+        }];
+
+        let export = ConversationExport {
+            version: CONVERSATION_EXPORT_VERSION,
+            exported_at: first.created_at.clone(),
+            conversation: ExportedConversation {
+                id: Uuid::new_v4(),
+                title: "Roadmap".into(),
+                is_group: false,
+                created_by: Uuid::new_v4(),
+                created_at: first.created_at.clone(),
+                archived_at: None,
+            },
+            root_id: None,
+            messages: vec![first.clone(), gone, last.clone()],
+        };
+        let raw = serde_json::to_string_pretty(&export).expect("serialize");
+
+        assert_eq!(ImportSource::detect(&raw), ImportSource::RustyGpt);
+        let parsed = parse_import(ImportSource::RustyGpt, &raw).expect("parse");
+        let conversation = &parsed.conversations[0];
+        assert_eq!(conversation.title, "Roadmap");
+        assert_eq!(conversation.skipped_messages, 1);
+
+        let [imported_root, imported_reply] = conversation.messages.as_slice() else {
+            panic!("expected two messages, got {:?}", conversation.messages);
+        };
+        assert_eq!(imported_root.revisions, first.revisions);
+        assert_eq!(imported_root.edit_reason.as_deref(), Some("typo"));
+        assert_eq!(imported_reply.parent_key, Some(root.to_string()));
+        assert_eq!(imported_reply.role, MessageRole::Assistant);
+        assert_eq!(imported_reply.attachments, last.attachments);
+        assert_eq!(imported_reply.created_at, Some(last.created_at));
+    }
+
+    #[test]
+    fn rustygpt_export_rejects_newer_versions() {
+        let raw = format!(
+            r#"{{"version": {}, "exported_at": "2025-01-01T00:00:00Z", "conversation": {{}}, "messages": []}}"#,
+            CONVERSATION_EXPORT_VERSION + 1
+        );
+        assert_eq!(ImportSource::detect(&raw), ImportSource::RustyGpt);
+        let err = parse_import(ImportSource::RustyGpt, &raw).expect_err("newer version");
+        assert!(err.to_string().contains("newer than the supported version"));
+    }
+
+    #[test]
+    fn openai_jsonl_reports_bad_lines() {
+        let err = parse_import(ImportSource::OpenAiJsonl, "{\"messages\": []}\nnot json")
+            .expect_err("invalid line");
+        assert!(matches!(err, ImportError::Line { line: 2, .. }));
+    }
+}
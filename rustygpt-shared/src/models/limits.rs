@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-use super::Timestamp;
+use super::{Timestamp, UserRole};
 
 /// A rate-limit profile describing algorithm parameters.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
@@ -62,3 +62,71 @@ pub struct AssignRateLimitRequest {
     pub method: String,
     pub path: String,
 }
+
+/// Who a token quota applies to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TokenQuotaTarget {
+    /// A single account; overrides every other quota for that user.
+    User { user_id: Uuid },
+    /// Every holder of a global role when no user or profile quota applies.
+    Role { role: UserRole },
+    /// Requests admitted by a rate-limit profile, budgeted per user.
+    Profile { profile_id: Uuid },
+    /// One budget shared by everything generated in an organization's conversations; checked
+    /// in addition to the requesting user's own quota.
+    Org { org_id: Uuid },
+}
+
+/// Daily and monthly token budgets; `None` leaves that period unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct TokenQuota {
+    pub id: Uuid,
+    pub target: TokenQuotaTarget,
+    /// Username, role, profile or organization name for display.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_tokens: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_tokens: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+/// Request payload to create a quota or replace the budgets of an existing target.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UpsertTokenQuotaRequest {
+    pub target: TokenQuotaTarget,
+    #[serde(default)]
+    pub daily_tokens: Option<i64>,
+    #[serde(default)]
+    pub monthly_tokens: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quota_targets_are_tagged_by_kind() {
+        let request: UpsertTokenQuotaRequest = serde_json::from_value(serde_json::json!({
+            "target": { "kind": "role", "role": "member" },
+            "daily_tokens": 50000
+        }))
+        .expect("deserialize");
+
+        assert_eq!(
+            request.target,
+            TokenQuotaTarget::Role {
+                role: UserRole::Member
+            }
+        );
+        assert_eq!(request.daily_tokens, Some(50_000));
+        assert_eq!(request.monthly_tokens, None);
+    }
+}
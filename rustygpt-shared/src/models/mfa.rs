@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{Timestamp, UserRole};
+
+/// A freshly generated TOTP secret (RFC 6238, SHA-1, 6 digits, 30 second steps).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct TotpEnrollment {
+    /// Base32 secret for manual entry.
+    pub secret: String,
+    /// `otpauth://` URI to render as a QR code.
+    pub otpauth_uri: String,
+}
+
+/// Returned by `/api/auth/login` with `202 Accepted` when the password was correct but a
+/// second factor is needed before a session is issued.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct MfaChallengeResponse {
+    /// Opaque token to send back with the code to `/api/auth/login/mfa`.
+    pub challenge_token: String,
+    pub expires_at: Timestamp,
+    /// Present when the account must enroll first: add the secret to an authenticator app and
+    /// answer the challenge with its first code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrollment: Option<TotpEnrollment>,
+}
+
+/// Second login step: a TOTP code or an unused recovery code.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct MfaVerifyRequest {
+    pub challenge_token: String,
+    pub code: String,
+}
+
+/// A code proving possession of the second factor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct MfaCodeRequest {
+    pub code: String,
+}
+
+/// Single-use recovery codes; shown once and stored only as hashes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct RecoveryCodesResponse {
+    pub codes: Vec<String>,
+}
+
+/// Second-factor state of the current account.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct MfaStatus {
+    pub enabled: bool,
+    /// One of the account's roles requires 2FA, so it cannot be disabled.
+    pub required: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+/// Roles whose members must sign in with a second factor.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct MfaPolicy {
+    pub required_roles: Vec<UserRole>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn challenge_omits_enrollment_when_already_enrolled() {
+        let challenge = MfaChallengeResponse {
+            challenge_token: "tok".into(),
+            expires_at: Timestamp(Utc::now()),
+            enrollment: None,
+        };
+
+        let value = serde_json::to_value(&challenge).unwrap();
+        assert!(value.get("enrollment").is_none());
+        let parsed: MfaChallengeResponse = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, challenge);
+    }
+}
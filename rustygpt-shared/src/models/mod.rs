@@ -1,40 +1,112 @@
+pub mod account;
+pub mod api_tokens;
+pub mod audit;
 pub mod chat;
 pub mod errors;
+pub mod export;
+pub mod import;
 pub mod limits;
+pub mod mfa;
 pub mod oauth;
+pub mod organization;
+pub mod preferences;
+pub mod retention;
+pub mod revisions;
+pub mod sessions;
 pub mod setup;
 pub mod streaming;
 pub mod threads;
 pub mod timestamp;
+pub mod usage;
 pub mod user;
+pub mod user_admin;
+pub mod user_groups;
+pub mod webhooks;
 
+pub use account::{
+    ChangePasswordRequest, ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest,
+};
+pub use api_tokens::{
+    API_TOKEN_MARKER, ApiTokenListResponse, ApiTokenScope, ApiTokenSummary, CreateApiTokenRequest,
+    CreateApiTokenResponse,
+};
+pub use audit::{AuditEvent, AuditEventPage, AuditQuery};
 pub use chat::{
     AddParticipantRequest, ChatDelta, ChatDeltaChoice, ChatDeltaChunk, ConversationCreateRequest,
     ConversationCreateResponse, ConversationRole, ConversationStreamEvent, MessageChunkPayload,
     MessageDoneEvent, MessageRole, MessageView, PostRootMessageRequest, PostRootMessageResponse,
-    ReplyMessageRequest, ReplyMessageResponse, StreamErrorEvent, ThreadActivityEvent,
-    ThreadListResponse, ThreadNewEvent, ThreadSummary, ThreadTreeResponse, UsageBreakdown,
+    RegenerateMessageRequest, RegenerateMessageResponse, ReplyMessageRequest, ReplyMessageResponse,
+    StreamErrorEvent, ThreadActivityEvent, ThreadListResponse, ThreadNewEvent, ThreadSummary,
+    ThreadTreeResponse, UsageBreakdown,
 };
 pub use errors::ErrorResponse;
+pub use export::{
+    CONVERSATION_EXPORT_VERSION, ConversationExport, ExportFormat, ExportedAttachment,
+    ExportedConversation, ExportedMessage,
+};
+pub use import::{
+    ImportConversationSummary, ImportError, ImportReport, ImportSource, ImportedConversation,
+    ImportedMessage, ParsedImport, parse_import,
+};
 pub use limits::{
     AssignRateLimitRequest, CreateRateLimitProfileRequest, RateLimitAssignment, RateLimitProfile,
-    UpdateRateLimitProfileRequest,
+    TokenQuota, TokenQuotaTarget, UpdateRateLimitProfileRequest, UpsertTokenQuotaRequest,
+};
+pub use mfa::{
+    MfaChallengeResponse, MfaCodeRequest, MfaPolicy, MfaStatus, MfaVerifyRequest,
+    RecoveryCodesResponse, TotpEnrollment,
+};
+pub use oauth::{OidcProviderSummary, OidcProvidersResponse};
+pub use organization::{
+    CreateOrganizationRequest, OrgRole, Organization, OrganizationFeatures, OrganizationMember,
+    SetOrganizationMemberRequest, UpdateOrganizationRequest,
+};
+pub use preferences::{
+    MAX_SYSTEM_PROMPT_CHARS, NotificationPreferences, ThemePreference, UserPreferences,
+};
+pub use retention::{
+    ConversationArchiveResponse, ConversationLifecycleAction, ConversationLifecycleEvent,
+    RetentionPolicy, UpdateRetentionPolicyRequest,
+};
+pub use revisions::{
+    DiffOp, DiffSegment, MessageEditedEvent, MessageRevision, MessageRevisionsResponse, word_diff,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+pub use sessions::{
+    ActiveSession, ActiveSessionListResponse, RevokeSessionsResponse, device_label,
+};
 pub use setup::SetupRequest;
 pub use setup::SetupResponse;
-pub use streaming::MessageChunk;
+pub use streaming::{
+    MessageChunk, StreamClientMessage, StreamCommand, StreamServerMessage, UserStreamEvent,
+};
 pub use threads::{
-    AcceptInviteRequest, CreateInviteRequest, CreateInviteResponse, MarkThreadReadRequest,
-    MembershipChangeAction, MembershipChangedEvent, MessageDeleteRequest, MessageEditRequest,
+    AcceptInviteRequest, CreateInviteRequest, CreateInviteResponse, ForkMessageRequest,
+    ForkMessageResponse, ForkMode, MarkThreadReadRequest, MembershipChangeAction,
+    MembershipChangedEvent, MessageDeleteRequest, MessageEditRequest, MessageMentionEvent,
     PresenceHeartbeatRequest, PresenceStatus, PresenceUpdate, TypingRequest, TypingUpdate,
-    UnreadSummaryResponse, UnreadThreadSummary, UnreadUpdateEvent,
+    UnreadSummaryResponse, UnreadThreadSummary, UnreadUpdateEvent, mentioned_usernames,
 };
 pub use timestamp::Timestamp;
+pub use usage::{UsageGroupBy, UsageReport, UsageReportRow, UsageSource};
 pub use user::{
     AuthenticatedUser, LoginRequest, LoginResponse, MeResponse, SessionSummary, User, UserRole,
 };
+pub use user_admin::{
+    AdminPasswordResetRequest, AdminPasswordResetResponse, CreateUserAccountRequest, RoleSummary,
+    UserAccount, UserAccountPage, UserAccountQuery,
+};
+pub use user_groups::{
+    ConversationGroupGrant, CreateUserGroupRequest, GrantGroupRequest, SetGroupMemberRequest,
+    UpdateUserGroupRequest, UserGroup, UserGroupMember,
+};
+pub use webhooks::{
+    CreateWebhookRequest, CreateWebhookResponse, UpdateWebhookRequest, WEBHOOK_DELIVERY_HEADER,
+    WEBHOOK_EVENT_HEADER, WEBHOOK_EVENT_TYPES, WEBHOOK_SIGNATURE_HEADER, WebhookDelivery,
+    WebhookDeliveryAttempt, WebhookDeliveryDetail, WebhookDeliveryQuery, WebhookDeliveryStatus,
+    WebhookEvent, WebhookSubscription,
+};
 
 fn default_model_object() -> String {
     "model".to_string()
@@ -24,6 +24,23 @@ pub struct OAuthInitResponse {
     pub auth_url: String,
 }
 
+/// An `OpenID` Connect provider offered on the sign-in page.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct OidcProviderSummary {
+    /// Key of the provider under `[oauth.providers]`.
+    pub name: String,
+    /// Label for the sign-in button.
+    pub display_name: String,
+    /// Relative URL that starts the login.
+    pub login_url: String,
+}
+
+/// Response of `GET /api/auth/oidc/providers`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct OidcProvidersResponse {
+    pub providers: Vec<OidcProviderSummary>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
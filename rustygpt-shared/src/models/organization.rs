@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::Timestamp;
+
+/// A member's role within one organization, independent of their global roles.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrgRole {
+    Owner,
+    Admin,
+    Member,
+}
+
+impl OrgRole {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Owner => "owner",
+            Self::Admin => "admin",
+            Self::Member => "member",
+        }
+    }
+
+    /// Owners and admins manage the organization's membership.
+    #[must_use]
+    pub const fn can_manage_members(self) -> bool {
+        matches!(self, Self::Owner | Self::Admin)
+    }
+}
+
+impl std::fmt::Display for OrgRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for OrgRole {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "owner" => Ok(Self::Owner),
+            "admin" => Ok(Self::Admin),
+            "member" => Ok(Self::Member),
+            _ => Err("invalid organization role"),
+        }
+    }
+}
+
+const fn enabled() -> bool {
+    true
+}
+
+/// Features that can be switched off for an organization's conversations. Everything is on
+/// unless the organization says otherwise.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct OrganizationFeatures {
+    /// Inviting members into conversations by email.
+    #[serde(default = "enabled")]
+    pub invites: bool,
+    /// `GET /api/conversations/{id}/export`.
+    #[serde(default = "enabled")]
+    pub conversation_export: bool,
+    /// Assistant replies; when off, conversations are for people only.
+    #[serde(default = "enabled")]
+    pub assistant: bool,
+}
+
+impl Default for OrganizationFeatures {
+    fn default() -> Self {
+        Self {
+            invites: true,
+            conversation_export: true,
+            assistant: true,
+        }
+    }
+}
+
+/// An organization as returned by `/api/orgs` and `/api/admin/orgs`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct Organization {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    /// Replaces the `conversation.post` rate-limit profile in the organization's conversations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_profile_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_profile_name: Option<String>,
+    /// Models replies may use; `None` allows every model the server offers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_models: Option<Vec<String>>,
+    pub features: OrganizationFeatures,
+    pub member_count: u64,
+    /// The caller's role; absent when an administrator lists organizations they are not in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<OrgRole>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+/// Body of `POST /api/admin/orgs`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct CreateOrganizationRequest {
+    pub slug: String,
+    pub name: String,
+    /// First owner; usually the person the organization is set up for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<Uuid>,
+}
+
+/// Body of `PUT /api/admin/orgs/{id}`; replaces every setting.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UpdateOrganizationRequest {
+    pub name: String,
+    #[serde(default)]
+    pub rate_limit_profile_id: Option<Uuid>,
+    #[serde(default)]
+    pub allowed_models: Option<Vec<String>>,
+    #[serde(default)]
+    pub features: OrganizationFeatures,
+}
+
+/// A member of an organization.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct OrganizationMember {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    pub role: OrgRole,
+    pub joined_at: Timestamp,
+}
+
+/// Body of `PUT /api/orgs/{id}/members/{user_id}`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct SetOrganizationMemberRequest {
+    #[serde(default = "default_member_role")]
+    pub role: OrgRole,
+}
+
+const fn default_member_role() -> OrgRole {
+    OrgRole::Member
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_features_default_to_enabled() {
+        let features: OrganizationFeatures = serde_json::from_str(r#"{"invites":false}"#).unwrap();
+        assert!(!features.invites);
+        assert!(features.conversation_export);
+        assert!(features.assistant);
+    }
+
+    #[test]
+    fn org_roles_round_trip() {
+        for role in [OrgRole::Owner, OrgRole::Admin, OrgRole::Member] {
+            assert_eq!(role.as_str().parse::<OrgRole>(), Ok(role));
+        }
+        assert!(OrgRole::Admin.can_manage_members());
+        assert!(!OrgRole::Member.can_manage_members());
+    }
+}
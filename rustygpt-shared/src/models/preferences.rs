@@ -0,0 +1,211 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Longest accepted custom system prompt, in characters.
+pub const MAX_SYSTEM_PROMPT_CHARS: usize = 4000;
+const MAX_MODEL_NAME_CHARS: usize = 200;
+const MAX_TEMPERATURE: f32 = 2.0;
+
+/// Colour scheme for the web client.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemePreference {
+    /// Follow the operating system setting.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemePreference {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+}
+
+impl fmt::Display for ThemePreference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ThemePreference {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "system" => Ok(Self::System),
+            "light" => Ok(Self::Light),
+            "dark" => Ok(Self::Dark),
+            _ => Err("unknown theme"),
+        }
+    }
+}
+
+/// Which notifications the user wants. Security emails (password resets, lockouts) are always
+/// sent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(default)]
+pub struct NotificationPreferences {
+    /// Non-security email, such as conversation invites.
+    pub email: bool,
+    /// Desktop notifications when a reply finishes in a background tab.
+    pub browser: bool,
+    /// Play a sound when a reply finishes.
+    pub sound: bool,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            email: true,
+            browser: false,
+            sound: false,
+        }
+    }
+}
+
+/// Per-user settings returned by `GET /api/me/preferences`.
+///
+/// Unset generation fields fall back to the server's model configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+#[serde(default)]
+pub struct UserPreferences {
+    /// Model used for new replies unless a request names one.
+    pub default_model: Option<String>,
+    /// Sampling temperature, 0.0–2.0.
+    pub temperature: Option<f32>,
+    /// Sent ahead of the conversation's own system messages.
+    pub system_prompt: Option<String>,
+    /// Interface language code, e.g. `en` or `de`.
+    pub language: Option<String>,
+    pub theme: ThemePreference,
+    pub notifications: NotificationPreferences,
+    /// Whether clients expand the assistant's reasoning, when a model emits it.
+    pub show_reasoning: bool,
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn is_language_code(code: &str) -> bool {
+    (2..=16).contains(&code.len())
+        && code
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+impl UserPreferences {
+    /// Trims text fields, treats empty strings as unset and checks ranges.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message naming the first invalid field.
+    pub fn normalized(self) -> Result<Self, String> {
+        let default_model = non_empty(self.default_model);
+        if default_model
+            .as_ref()
+            .is_some_and(|model| model.chars().count() > MAX_MODEL_NAME_CHARS)
+        {
+            return Err(format!(
+                "default_model must be at most {MAX_MODEL_NAME_CHARS} characters"
+            ));
+        }
+
+        if let Some(temperature) = self.temperature
+            && !(0.0..=MAX_TEMPERATURE).contains(&temperature)
+        {
+            return Err(format!(
+                "temperature must be between 0.0 and {MAX_TEMPERATURE:.1}"
+            ));
+        }
+
+        let system_prompt = non_empty(self.system_prompt);
+        if system_prompt
+            .as_ref()
+            .is_some_and(|prompt| prompt.chars().count() > MAX_SYSTEM_PROMPT_CHARS)
+        {
+            return Err(format!(
+                "system_prompt must be at most {MAX_SYSTEM_PROMPT_CHARS} characters"
+            ));
+        }
+
+        let language = non_empty(self.language).map(|code| code.to_ascii_lowercase());
+        if language
+            .as_deref()
+            .is_some_and(|code| !is_language_code(code))
+        {
+            return Err("language must be a language code such as `en` or `pt-br`".to_string());
+        }
+
+        Ok(Self {
+            default_model,
+            system_prompt,
+            language,
+            ..self
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_take_defaults() {
+        let preferences: UserPreferences =
+            serde_json::from_str(r#"{"theme":"dark","notifications":{"sound":true}}"#).unwrap();
+        assert_eq!(preferences.theme, ThemePreference::Dark);
+        assert!(preferences.notifications.email);
+        assert!(preferences.notifications.sound);
+        assert!(preferences.default_model.is_none());
+        assert!(!preferences.show_reasoning);
+    }
+
+    #[test]
+    fn normalized_trims_and_validates() {
+        let preferences = UserPreferences {
+            default_model: Some("  ".into()),
+            system_prompt: Some(" Be brief. ".into()),
+            language: Some("PT-BR".into()),
+            temperature: Some(0.4),
+            ..UserPreferences::default()
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(preferences.default_model, None);
+        assert_eq!(preferences.system_prompt.as_deref(), Some("Be brief."));
+        assert_eq!(preferences.language.as_deref(), Some("pt-br"));
+
+        for invalid in [
+            UserPreferences {
+                temperature: Some(2.5),
+                ..UserPreferences::default()
+            },
+            UserPreferences {
+                temperature: Some(f32::NAN),
+                ..UserPreferences::default()
+            },
+            UserPreferences {
+                language: Some("en_US!".into()),
+                ..UserPreferences::default()
+            },
+            UserPreferences {
+                system_prompt: Some("x".repeat(MAX_SYSTEM_PROMPT_CHARS + 1)),
+                ..UserPreferences::default()
+            },
+        ] {
+            assert!(invalid.normalized().is_err());
+        }
+    }
+}
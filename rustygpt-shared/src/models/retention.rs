@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::Timestamp;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversationLifecycleAction {
+    Archived,
+    Unarchived,
+    Deleted,
+    /// Soft-deleted message content was removed by the retention job.
+    Purged,
+}
+
+impl ConversationLifecycleAction {
+    /// Parse the action names recorded by the retention stored procedures.
+    #[must_use]
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "archived" => Some(Self::Archived),
+            "unarchived" => Some(Self::Unarchived),
+            "deleted" => Some(Self::Deleted),
+            "purged" => Some(Self::Purged),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ConversationLifecycleEvent {
+    pub conversation_id: Uuid,
+    pub action: ConversationLifecycleAction,
+    /// `None` when the change was made by the retention job.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor_user_id: Option<Uuid>,
+    #[serde(default)]
+    pub affected_messages: i32,
+    pub occurred_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ConversationArchiveResponse {
+    pub conversation_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<Timestamp>,
+}
+
+/// Retention settings; unset fields on a conversation policy fall back to the global one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct RetentionPolicy {
+    /// `None` for the global default policy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_after_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purge_deleted_after_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_archive_after_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_purge_deleted_after_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UpdateRetentionPolicyRequest {
+    /// Archive the conversation after this many days without new messages.
+    #[serde(default)]
+    pub archive_after_days: Option<i32>,
+    /// Purge soft-deleted message content this many days after deletion.
+    #[serde(default)]
+    pub purge_deleted_after_days: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifecycle_action_round_trips_db_names() {
+        for action in [
+            ConversationLifecycleAction::Archived,
+            ConversationLifecycleAction::Unarchived,
+            ConversationLifecycleAction::Deleted,
+            ConversationLifecycleAction::Purged,
+        ] {
+            let name = serde_json::to_value(action).expect("serialize");
+            let name = name.as_str().expect("string");
+            assert_eq!(ConversationLifecycleAction::from_db(name), Some(action));
+        }
+        assert_eq!(ConversationLifecycleAction::from_db("exploded"), None);
+    }
+}
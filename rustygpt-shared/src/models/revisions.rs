@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::Timestamp;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct DiffSegment {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct MessageRevision {
+    pub revision: i32,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edited_by: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub created_at: Timestamp,
+    /// Word-level diff against the previous revision; empty for the original.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diff: Vec<DiffSegment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct MessageRevisionsResponse {
+    pub message_id: Uuid,
+    pub revisions: Vec<MessageRevision>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct MessageEditedEvent {
+    pub message_id: Uuid,
+    pub root_id: Uuid,
+    pub conversation_id: Uuid,
+    pub content: String,
+    pub edited_by: Uuid,
+    pub edited_at: Timestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Split text into alternating word and whitespace tokens so diffs preserve spacing.
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_whitespace: Option<bool> = None;
+
+    for (idx, ch) in text.char_indices() {
+        let whitespace = ch.is_whitespace();
+        if in_whitespace.is_some_and(|current| current != whitespace) {
+            tokens.push(&text[start..idx]);
+            start = idx;
+        }
+        in_whitespace = Some(whitespace);
+    }
+
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+fn push_segment(segments: &mut Vec<DiffSegment>, op: DiffOp, text: &str) {
+    if let Some(last) = segments.last_mut()
+        && last.op == op
+    {
+        last.text.push_str(text);
+        return;
+    }
+    segments.push(DiffSegment {
+        op,
+        text: text.to_string(),
+    });
+}
+
+/// Largest LCS table [`word_diff`] builds; bigger rewrites fall back to a whole-text replace.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Compute a word-level diff between two revisions using a longest common subsequence.
+///
+/// The common prefix and suffix are matched directly. When what remains would need a table of
+/// more than [`MAX_DIFF_CELLS`] entries, the middle is reported as one delete and one insert.
+#[must_use]
+pub fn word_diff(before: &str, after: &str) -> Vec<DiffSegment> {
+    let old = tokenize(before);
+    let new = tokenize(after);
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut segments = Vec::new();
+    for token in &old[..prefix] {
+        push_segment(&mut segments, DiffOp::Equal, token);
+    }
+    diff_middle(
+        &mut segments,
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    for token in &old[old.len() - suffix..] {
+        push_segment(&mut segments, DiffOp::Equal, token);
+    }
+
+    segments
+}
+
+fn diff_middle(segments: &mut Vec<DiffSegment>, old: &[&str], new: &[&str]) {
+    if (old.len() + 1).saturating_mul(new.len() + 1) > MAX_DIFF_CELLS {
+        if !old.is_empty() {
+            push_segment(segments, DiffOp::Delete, &old.concat());
+        }
+        if !new.is_empty() {
+            push_segment(segments, DiffOp::Insert, &new.concat());
+        }
+        return;
+    }
+
+    let mut lcs = vec![vec![0_u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            push_segment(segments, DiffOp::Equal, old[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            push_segment(segments, DiffOp::Delete, old[i]);
+            i += 1;
+        } else {
+            push_segment(segments, DiffOp::Insert, new[j]);
+            j += 1;
+        }
+    }
+    for token in &old[i..] {
+        push_segment(segments, DiffOp::Delete, token);
+    }
+    for token in &new[j..] {
+        push_segment(segments, DiffOp::Insert, token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_keeps_whitespace_runs() {
+        assert_eq!(
+            tokenize("hello  big\nworld"),
+            vec!["hello", "  ", "big", "\n", "world"]
+        );
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn word_diff_marks_replaced_words() {
+        let diff = word_diff("the quick fox", "the slow fox");
+        assert_eq!(
+            diff,
+            vec![
+                DiffSegment {
+                    op: DiffOp::Equal,
+                    text: "the ".into()
+                },
+                DiffSegment {
+                    op: DiffOp::Delete,
+                    text: "quick".into()
+                },
+                DiffSegment {
+                    op: DiffOp::Insert,
+                    text: "slow".into()
+                },
+                DiffSegment {
+                    op: DiffOp::Equal,
+                    text: " fox".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn word_diff_handles_appended_text() {
+        let diff = word_diff("hello", "hello world");
+        assert_eq!(diff.len(), 2);
+        assert_eq!(diff[1].op, DiffOp::Insert);
+        assert_eq!(diff[1].text, " world");
+    }
+
+    #[test]
+    fn word_diff_keeps_large_rewrites_bounded() {
+        let before = (0..20_000)
+            .map(|n| format!("w{n}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let after = (0..20_000)
+            .map(|n| format!("x{n}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let diff = word_diff(&before, &after);
+        assert_eq!(
+            diff,
+            vec![
+                DiffSegment {
+                    op: DiffOp::Delete,
+                    text: before.clone()
+                },
+                DiffSegment {
+                    op: DiffOp::Insert,
+                    text: after.clone()
+                },
+            ]
+        );
+
+        let edited = before.replacen("w10000", "changed", 1);
+        let diff = word_diff(&before, &edited);
+        let ops: Vec<_> = diff.iter().map(|segment| segment.op).collect();
+        assert_eq!(
+            ops,
+            vec![DiffOp::Equal, DiffOp::Delete, DiffOp::Insert, DiffOp::Equal]
+        );
+        assert_eq!(diff[1].text, "w10000");
+        assert_eq!(diff[2].text, "changed");
+    }
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::Timestamp;
+
+/// A signed-in browser or CLI, as listed by `GET /api/auth/sessions`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ActiveSession {
+    pub id: Uuid,
+    pub created_at: Timestamp,
+    pub last_seen_at: Timestamp,
+    pub expires_at: Timestamp,
+    pub absolute_expires_at: Timestamp,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    /// Short description derived from the user agent, e.g. `Firefox on Linux`.
+    pub device: String,
+    /// True for the session that made the request.
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ActiveSessionListResponse {
+    pub sessions: Vec<ActiveSession>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct RevokeSessionsResponse {
+    /// Number of sessions that were signed out.
+    pub revoked: u64,
+}
+
+/// Names the browser (or client) and operating system in a user agent string.
+///
+/// Only the common cases are recognised; anything else is reported as `Unknown device`
+/// or by its product token.
+#[must_use]
+pub fn device_label(user_agent: Option<&str>) -> String {
+    let Some(agent) = user_agent.map(str::trim).filter(|agent| !agent.is_empty()) else {
+        return "Unknown device".to_string();
+    };
+
+    // Order matters: Edge and Opera also claim Chrome, and Chrome also claims Safari.
+    let client = [
+        ("rustygpt-cli", "RustyGPT CLI"),
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+        ("curl/", "curl"),
+    ]
+    .iter()
+    .find(|(needle, _)| agent.contains(needle))
+    .map(|(_, name)| (*name).to_string());
+
+    let os = [
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Macintosh", "macOS"),
+        ("CrOS", "ChromeOS"),
+        ("Linux", "Linux"),
+    ]
+    .iter()
+    .find(|(needle, _)| agent.contains(needle))
+    .map(|(_, name)| *name);
+
+    let client =
+        client.unwrap_or_else(|| agent.split(['/', ' ']).next().unwrap_or(agent).to_string());
+    match os {
+        Some(os) => format!("{client} on {os}"),
+        None => client,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_label_names_browser_and_os() {
+        assert_eq!(
+            device_label(Some(
+                "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0"
+            )),
+            "Firefox on Linux"
+        );
+        assert_eq!(
+            device_label(Some(
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) \
+                 Chrome/126.0.0.0 Safari/537.36 Edg/126.0.0.0"
+            )),
+            "Edge on Windows"
+        );
+        assert_eq!(
+            device_label(Some(
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 \
+                 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1"
+            )),
+            "Safari on iOS"
+        );
+    }
+
+    #[test]
+    fn device_label_falls_back_to_product_token() {
+        assert_eq!(device_label(Some("rustygpt-cli/0.1.0")), "RustyGPT CLI");
+        assert_eq!(
+            device_label(Some("python-requests/2.32")),
+            "python-requests"
+        );
+        assert_eq!(device_label(Some("  ")), "Unknown device");
+        assert_eq!(device_label(None), "Unknown device");
+    }
+}
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-use super::timestamp::Timestamp;
+use super::{chat::ConversationStreamEvent, threads::PresenceStatus, timestamp::Timestamp};
 
 /// Represents a persisted streaming chunk for SSE replay.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
@@ -17,11 +17,140 @@ pub struct MessageChunk {
     pub created_at: Timestamp,
 }
 
+/// A JSON text frame sent by the client over `GET /api/stream/ws`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StreamClientMessage {
+    /// Echoed in the `ack` or `error` reply so the client can match it to the command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(flatten)]
+    pub command: StreamCommand,
+}
+
+/// Commands accepted on the stream socket. Besides subscriptions they mirror the typing,
+/// presence, mark-read and cancel endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamCommand {
+    /// Starts (or restarts) the feed of a conversation. `last_event_id` and `since` resume it
+    /// exactly like the SSE `Last-Event-ID` header and `since` query parameter.
+    Subscribe {
+        conversation_id: Uuid,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_event_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since: Option<i64>,
+    },
+    Unsubscribe {
+        conversation_id: Uuid,
+    },
+    Typing {
+        conversation_id: Uuid,
+        root_id: Uuid,
+        seconds: i32,
+    },
+    Heartbeat {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<PresenceStatus>,
+    },
+    MarkRead {
+        root_id: Uuid,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+    Cancel {
+        message_id: Uuid,
+    },
+}
+
+/// A JSON text frame sent by the server over `GET /api/stream/ws`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamServerMessage {
+    /// An event of a subscribed conversation. `id` is the SSE event id, usable as
+    /// `last_event_id` when subscribing again.
+    Event {
+        conversation_id: Uuid,
+        id: String,
+        event: ConversationStreamEvent,
+    },
+    /// The command succeeded. `status` carries the outcome of `cancel`.
+    Ack {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+    },
+    /// The command, or the frame itself, was rejected; `code` matches the HTTP error codes.
+    Error {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+        code: String,
+        message: String,
+    },
+}
+
+/// An event on `GET /api/stream/me`, which follows every conversation of the user: the
+/// conversation event with the conversation it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UserStreamEvent {
+    pub conversation_id: Uuid,
+    #[serde(flatten)]
+    pub event: ConversationStreamEvent,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::UnreadUpdateEvent;
     use chrono::{TimeZone, Utc};
 
+    #[test]
+    fn client_messages_carry_an_optional_request_id() {
+        let message: StreamClientMessage = serde_json::from_str(
+            r#"{"type":"subscribe","request_id":"1","conversation_id":"00000000-0000-0000-0000-000000000000","since":5}"#,
+        )
+        .unwrap();
+        assert_eq!(message.request_id.as_deref(), Some("1"));
+        assert_eq!(
+            message.command,
+            StreamCommand::Subscribe {
+                conversation_id: Uuid::nil(),
+                last_event_id: None,
+                since: Some(5),
+            }
+        );
+
+        let heartbeat: StreamClientMessage =
+            serde_json::from_str(r#"{"type":"heartbeat"}"#).unwrap();
+        assert_eq!(heartbeat.request_id, None);
+        assert_eq!(heartbeat.command, StreamCommand::Heartbeat { status: None });
+    }
+
+    #[test]
+    fn user_stream_events_tag_the_conversation_event() {
+        let conversation_id = Uuid::new_v4();
+        let event = UserStreamEvent {
+            conversation_id,
+            event: ConversationStreamEvent::UnreadUpdate {
+                payload: UnreadUpdateEvent {
+                    user_id: Uuid::nil(),
+                    root_id: Uuid::nil(),
+                    unread: 3,
+                },
+            },
+        };
+
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["conversation_id"], conversation_id.to_string());
+        assert_eq!(value["type"], "unread.update");
+        assert_eq!(value["payload"]["unread"], 3);
+        assert_eq!(
+            serde_json::from_value::<UserStreamEvent>(value).unwrap(),
+            event
+        );
+    }
+
     #[test]
     fn message_chunk_round_trip() {
         let chunk = MessageChunk {
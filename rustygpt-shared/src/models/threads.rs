@@ -109,10 +109,141 @@ pub struct MessageEditRequest {
     pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// Re-run the assistant reply beneath an edited user message.
+    #[serde(default)]
+    pub regenerate_reply: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ForkMode {
+    /// Copy the message and every descendant beneath it.
+    #[default]
+    Subtree,
+    /// Copy the chain from the thread root down to the message.
+    Ancestors,
+}
+
+impl ForkMode {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Subtree => "subtree",
+            Self::Ancestors => "ancestors",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ForkMessageRequest {
+    #[serde(default)]
+    pub mode: ForkMode,
+    /// Existing conversation to receive the fork; defaults to the source conversation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_conversation_id: Option<Uuid>,
+    /// Create a new conversation with this title to receive the fork.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_conversation_title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ForkMessageResponse {
+    pub conversation_id: Uuid,
+    pub root_id: Uuid,
+    pub message_count: i32,
+    pub created_conversation: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
 pub struct UnreadUpdateEvent {
+    /// The reader this count belongs to; every participant has their own.
+    pub user_id: Uuid,
     pub root_id: Uuid,
     pub unread: i64,
 }
+
+/// A message named a participant with `@username`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct MessageMentionEvent {
+    pub conversation_id: Uuid,
+    pub root_id: Uuid,
+    pub message_id: Uuid,
+    pub author_id: Uuid,
+    /// The mentioned participant.
+    pub user_id: Uuid,
+}
+
+/// Usernames mentioned as `@name` in message content, lowercased and without duplicates.
+///
+/// A mention starts at an `@` that does not follow a letter or digit, so email addresses are
+/// skipped, and runs over letters, digits, `_`, `.` and `-`; a trailing `.` or `-` is
+/// punctuation, not part of the name.
+#[must_use]
+pub fn mentioned_usernames(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut chars = content.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        let at_boundary = previous.is_none_or(|prev| !prev.is_alphanumeric());
+        previous = Some(ch);
+        if ch != '@' || !at_boundary {
+            continue;
+        }
+
+        let mut end = start + 1;
+        while let Some(&(idx, next)) = chars.peek() {
+            if !(next.is_alphanumeric() || matches!(next, '_' | '.' | '-')) {
+                break;
+            }
+            end = idx + next.len_utf8();
+            previous = Some(next);
+            chars.next();
+        }
+
+        let name = content[start + 1..end].trim_end_matches(['.', '-']);
+        if name.is_empty() {
+            continue;
+        }
+        let name = name.to_lowercase();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_are_lowercased_and_deduplicated() {
+        assert_eq!(
+            mentioned_usernames("@Ada can you check with @grace.h and @ada?"),
+            vec!["ada".to_string(), "grace.h".to_string()]
+        );
+    }
+
+    #[test]
+    fn mentions_skip_emails_and_trailing_punctuation() {
+        assert_eq!(
+            mentioned_usernames("mail ada@example.com, then ping @bob-. Or (@carol_1)"),
+            vec!["bob".to_string(), "carol_1".to_string()]
+        );
+        assert!(mentioned_usernames("just an @ sign").is_empty());
+    }
+
+    #[test]
+    fn fork_mode_matches_the_procedure_argument() {
+        for mode in [ForkMode::Subtree, ForkMode::Ancestors] {
+            let json = serde_json::to_value(mode).unwrap();
+            assert_eq!(json, serde_json::json!(mode.as_str()));
+            assert_eq!(serde_json::from_value::<ForkMode>(json).unwrap(), mode);
+        }
+
+        let request: ForkMessageRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(request.mode, ForkMode::Subtree);
+    }
+}
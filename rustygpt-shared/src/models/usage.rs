@@ -0,0 +1,161 @@
+use std::{fmt, fmt::Write, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::Timestamp;
+
+/// Which surface produced a usage ledger entry.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageSource {
+    /// Assistant replies generated inside a conversation thread.
+    Thread,
+    /// `OpenAI`-compatible `/v1/chat/completions` calls.
+    V1,
+}
+
+impl UsageSource {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Thread => "thread",
+            Self::V1 => "v1",
+        }
+    }
+}
+
+/// Dimension a usage report is aggregated over.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroupBy {
+    #[default]
+    Day,
+    Model,
+    User,
+}
+
+impl UsageGroupBy {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Model => "model",
+            Self::User => "user",
+        }
+    }
+}
+
+impl fmt::Display for UsageGroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for UsageGroupBy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "day" => Ok(Self::Day),
+            "model" => Ok(Self::Model),
+            "user" => Ok(Self::User),
+            other => Err(format!("unsupported usage grouping: {other}")),
+        }
+    }
+}
+
+/// One aggregated bucket of the usage ledger.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UsageReportRow {
+    /// `YYYY-MM-DD` (UTC), model name, or user id depending on the grouping.
+    pub bucket: String,
+    /// Username for user-grouped rows; absent for anonymous `/v1` calls.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub total_tokens: i64,
+    pub avg_latency_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UsageReport {
+    pub group_by: UsageGroupBy,
+    pub from: Timestamp,
+    pub to: Timestamp,
+    /// Set when the report is restricted to a single user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
+    pub rows: Vec<UsageReportRow>,
+}
+
+impl UsageReport {
+    /// Renders the rows as CSV with a header line.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "bucket,username,requests,prompt_tokens,completion_tokens,total_tokens,avg_latency_ms\n",
+        );
+        for row in &self.rows {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                csv_field(&row.bucket),
+                csv_field(row.username.as_deref().unwrap_or_default()),
+                row.requests,
+                row.prompt_tokens,
+                row.completion_tokens,
+                row.total_tokens,
+                row.avg_latency_ms,
+            );
+        }
+        out
+    }
+}
+
+pub(super) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn csv_export_quotes_fields_with_separators() {
+        let report = UsageReport {
+            group_by: UsageGroupBy::Model,
+            from: Timestamp(Utc::now()),
+            to: Timestamp(Utc::now()),
+            user_id: None,
+            rows: vec![UsageReportRow {
+                bucket: "llama, \"tiny\"".to_string(),
+                username: None,
+                requests: 2,
+                prompt_tokens: 10,
+                completion_tokens: 5,
+                total_tokens: 15,
+                avg_latency_ms: 120,
+            }],
+        };
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "bucket,username,requests,prompt_tokens,completion_tokens,total_tokens,avg_latency_ms"
+            )
+        );
+        assert_eq!(lines.next(), Some("\"llama, \"\"tiny\"\"\",,2,10,5,15,120"));
+        assert_eq!(lines.next(), None);
+    }
+}
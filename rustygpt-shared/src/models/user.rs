@@ -112,6 +112,9 @@ pub struct LoginResponse {
     pub user: AuthenticatedUser,
     pub session: SessionSummary,
     pub csrf_token: String,
+    /// Recovery codes issued when the second login step also completed 2FA enrollment.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recovery_codes: Vec<String>,
 }
 
 /// Response payload for /api/auth/me exposing the current session snapshot.
@@ -237,6 +240,7 @@ mod tests {
                 absolute_expires_at: Timestamp(Utc::now()),
             },
             csrf_token: "csrf123".to_string(),
+            recovery_codes: Vec::new(),
         };
 
         let serialized = serde_json::to_string(&response).unwrap();
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{Timestamp, UserRole};
+
+/// An account as shown to administrators by `GET /api/admin/users`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UserAccount {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    pub roles: Vec<UserRole>,
+    pub created_at: Timestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled_at: Option<Timestamp>,
+    /// Set while a failed-login lockout is in force.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked_until: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_verified_at: Option<Timestamp>,
+    pub mfa_enabled: bool,
+    /// Most recent activity of any of the user's sessions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen_at: Option<Timestamp>,
+}
+
+impl UserAccount {
+    #[must_use]
+    pub fn has_role(&self, role: UserRole) -> bool {
+        self.roles.contains(&role)
+    }
+}
+
+/// One page of accounts, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UserAccountPage {
+    pub users: Vec<UserAccount>,
+    /// Number of accounts matching the filters across all pages.
+    pub total: u64,
+}
+
+/// Filters for `GET /api/admin/users`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UserAccountQuery {
+    /// Case-insensitive substring of the username, email or display name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<UserRole>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+}
+
+/// Body of `POST /api/admin/users`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct CreateUserAccountRequest {
+    pub username: String,
+    pub email: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Initial password. When omitted the account cannot sign in with a password until the
+    /// user follows the reset link emailed to them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Global roles to grant; defaults to `member`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<UserRole>,
+}
+
+/// Body of `POST /api/admin/users/{id}/password`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct AdminPasswordResetRequest {
+    /// New password. When omitted a reset link is emailed instead and the current password
+    /// keeps working.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct AdminPasswordResetResponse {
+    /// Sessions signed out because the password was replaced.
+    pub revoked_sessions: u64,
+    pub email_sent: bool,
+}
+
+/// A global role with the number of accounts holding it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct RoleSummary {
+    pub role: UserRole,
+    pub user_count: u64,
+    /// Whether holders must use two-factor authentication (see `/api/admin/mfa`).
+    pub mfa_required: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_request_defaults_to_no_password_and_roles() {
+        let request: CreateUserAccountRequest =
+            serde_json::from_str(r#"{"username":"ada","email":"ada@example.com"}"#).unwrap();
+        assert!(request.password.is_none());
+        assert!(request.roles.is_empty());
+        assert!(request.display_name.is_none());
+    }
+
+    #[test]
+    fn query_omits_unset_filters() {
+        let query = UserAccountQuery {
+            search: Some("ada".into()),
+            role: Some(UserRole::ReadOnly),
+            disabled: Some(false),
+            limit: Some(25),
+            offset: None,
+        };
+        let encoded = serde_json::to_value(&query).unwrap();
+        assert_eq!(
+            encoded,
+            serde_json::json!({"search": "ada", "role": "read_only", "disabled": false, "limit": 25})
+        );
+        let decoded: UserAccountQuery = serde_json::from_value(encoded).unwrap();
+        assert_eq!(decoded, query);
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{Timestamp, chat::ConversationRole};
+
+/// A named set of users that can be granted a role on conversations as a unit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UserGroup {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub member_count: u64,
+    /// Whether the caller manages the group; absent when they are not a member.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_manager: Option<bool>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+/// Body of `POST /api/groups`; the caller becomes the group's first manager.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct CreateUserGroupRequest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Body of `PUT /api/groups/{id}`; replaces the name and description.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UpdateUserGroupRequest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A member of a user group.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UserGroupMember {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Managers edit the group's details and membership.
+    pub is_manager: bool,
+    pub added_at: Timestamp,
+}
+
+/// Body of `PUT /api/groups/{id}/members/{user_id}`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct SetGroupMemberRequest {
+    #[serde(default)]
+    pub is_manager: bool,
+}
+
+/// A group's grant on a conversation: every member holds `role` there.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct ConversationGroupGrant {
+    pub group_id: Uuid,
+    pub name: String,
+    pub role: ConversationRole,
+    pub member_count: u64,
+    pub granted_at: Timestamp,
+}
+
+/// Body of `POST /api/conversations/{id}/groups`. Granting a group again changes its role.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct GrantGroupRequest {
+    pub group_id: Uuid,
+    #[serde(default = "default_grant_role")]
+    pub role: ConversationRole,
+}
+
+const fn default_grant_role() -> ConversationRole {
+    ConversationRole::Member
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grants_default_to_member() {
+        let request: GrantGroupRequest =
+            serde_json::from_str(r#"{"group_id":"00000000-0000-0000-0000-000000000000"}"#).unwrap();
+        assert_eq!(request.role, ConversationRole::Member);
+
+        let member: SetGroupMemberRequest = serde_json::from_str("{}").unwrap();
+        assert!(!member.is_manager);
+    }
+}
@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{Timestamp, chat::ConversationStreamEvent};
+
+/// Stream events that can be delivered by webhook. Token deltas, typing, presence and unread
+/// updates are left to SSE: they are too frequent, or too personal, to be worth an HTTP call.
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &[
+    "thread.new",
+    "thread.activity",
+    "message.done",
+    "message.edited",
+    "membership.changed",
+    "conversation.lifecycle",
+    "error",
+];
+
+/// Header carrying `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">`.
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-rustygpt-signature";
+/// Header carrying the stream event name, e.g. `message.done`.
+pub const WEBHOOK_EVENT_HEADER: &str = "x-rustygpt-event";
+/// Header carrying the delivery id; replays get a new one.
+pub const WEBHOOK_DELIVERY_HEADER: &str = "x-rustygpt-delivery";
+
+/// The JSON body sent to a webhook receiver.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct WebhookEvent {
+    /// Shared by every delivery of the same event, including replays; use it to deduplicate.
+    pub event_id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub conversation_id: Uuid,
+    pub occurred_at: Timestamp,
+    /// The event exactly as SSE subscribers receive it.
+    pub data: ConversationStreamEvent,
+}
+
+/// A webhook subscription as returned by `/api/admin/webhooks`. The secret is never returned
+/// after creation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Set when the subscription follows one conversation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<Uuid>,
+    /// Set when the subscription follows every conversation of an organization.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<Uuid>,
+    /// Event names delivered; empty for every entry of [`WEBHOOK_EVENT_TYPES`].
+    pub event_types: Vec<String>,
+    pub enabled: bool,
+    pub pending_count: u64,
+    pub dead_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_delivered_at: Option<Timestamp>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+/// Body of `POST /api/admin/webhooks`. Set exactly one of `conversation_id` and `org_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<Uuid>,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    /// Signing secret of at least 16 characters; generated when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+/// Response of `POST /api/admin/webhooks`; the only time the secret is shown.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct CreateWebhookResponse {
+    pub subscription: WebhookSubscription,
+    pub secret: String,
+}
+
+/// Body of `PUT /api/admin/webhooks/{id}`. The target cannot change; omit `secret` to keep it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+const fn enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    /// Waiting for its first attempt or a retry.
+    Pending,
+    Delivered,
+    /// Dead-lettered after the last retry failed; replay it to try again.
+    Dead,
+}
+
+impl WebhookDeliveryStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Dead => "dead",
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookDeliveryStatus {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "delivered" => Ok(Self::Delivered),
+            "dead" => Ok(Self::Dead),
+            _ => Err("invalid webhook delivery status"),
+        }
+    }
+}
+
+/// One event queued for one subscription.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub conversation_id: Uuid,
+    /// The delivery this one replays.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay_of: Option<Uuid>,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_status_code: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub next_attempt_at: Timestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivered_at: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_at: Option<Timestamp>,
+    pub created_at: Timestamp,
+}
+
+/// One HTTP attempt of a delivery.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct WebhookDeliveryAttempt {
+    pub attempted_at: Timestamp,
+    /// Absent when no response arrived.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u32,
+}
+
+/// Response of `GET /api/admin/webhooks/deliveries/{id}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct WebhookDeliveryDetail {
+    pub delivery: WebhookDelivery,
+    pub attempts: Vec<WebhookDeliveryAttempt>,
+}
+
+/// Query of `GET /api/admin/webhooks/{id}/deliveries`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct WebhookDeliveryQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<WebhookDeliveryStatus>,
+    /// 1–1000, default 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivery_statuses_round_trip() {
+        for status in [
+            WebhookDeliveryStatus::Pending,
+            WebhookDeliveryStatus::Delivered,
+            WebhookDeliveryStatus::Dead,
+        ] {
+            assert_eq!(status.as_str().parse(), Ok(status));
+        }
+    }
+
+    #[test]
+    fn webhook_body_wraps_the_stream_event() {
+        let body = serde_json::json!({
+            "event_id": Uuid::nil(),
+            "type": "membership.changed",
+            "conversation_id": Uuid::nil(),
+            "occurred_at": "2024-01-01T00:00:00Z",
+            "data": {
+                "type": "membership.changed",
+                "payload": {
+                    "conversation_id": Uuid::nil(),
+                    "user_id": Uuid::nil(),
+                    "action": "added"
+                }
+            }
+        });
+        let event: WebhookEvent = serde_json::from_value(body).expect("webhook body");
+        assert_eq!(event.event_type, "membership.changed");
+        assert!(matches!(
+            event.data,
+            ConversationStreamEvent::MembershipChanged { .. }
+        ));
+    }
+}
@@ -1,9 +1,15 @@
 use once_cell::unsync::OnceCell;
 use reqwest::{Client, Error, RequestBuilder, Response, StatusCode};
+use serde::Deserialize;
 use shared::models::{
-    LoginRequest, LoginResponse, MeResponse, PostRootMessageRequest, PostRootMessageResponse,
-    ReplyMessageRequest, ReplyMessageResponse, ThreadListResponse, ThreadTreeResponse,
-    UnreadSummaryResponse,
+    AcceptInviteRequest, ActiveSessionListResponse, AdminPasswordResetRequest,
+    AdminPasswordResetResponse, ChangePasswordRequest, CreateUserAccountRequest,
+    ForgotPasswordRequest, LoginRequest, LoginResponse, MeResponse, MfaChallengeResponse,
+    MfaVerifyRequest, OidcProvidersResponse, PostRootMessageRequest, PostRootMessageResponse,
+    RegenerateMessageRequest, RegenerateMessageResponse, ReplyMessageRequest, ReplyMessageResponse,
+    ResetPasswordRequest, RevokeSessionsResponse, RoleSummary, ThreadListResponse,
+    ThreadTreeResponse, UnreadSummaryResponse, UserAccount, UserAccountPage, UserAccountQuery,
+    UserPreferences, UserRole, VerifyEmailRequest,
 };
 use shared::models::{SetupRequest, SetupResponse};
 use std::sync::{Arc, Mutex};
@@ -19,6 +25,19 @@ thread_local! {
     static SHARED_CLIENT: OnceCell<RustyGPTClient> = const { OnceCell::new() };
 }
 
+/// Result of submitting a password.
+#[derive(Clone, Debug)]
+pub enum LoginResult {
+    Complete(LoginResponse),
+    /// The account uses two-factor authentication; finish with [`RustyGPTClient::login_mfa`].
+    MfaRequired(MfaChallengeResponse),
+}
+
+#[derive(Deserialize)]
+struct AcceptInviteResponse {
+    conversation_id: Uuid,
+}
+
 /// Lightweight API client for `RustyGPT` web interactions.
 #[derive(Clone, Debug)]
 pub struct RustyGPTClient {
@@ -123,15 +142,162 @@ impl RustyGPTClient {
     }
 
     /// Authenticate with email/password credentials.
-    pub async fn login(&self, payload: &LoginRequest) -> Result<LoginResponse, Error> {
+    pub async fn login(&self, payload: &LoginRequest) -> Result<LoginResult, Error> {
         let url = self.api_url("auth/login");
-        let response = self.client.post(url).json(payload).send().await?;
+        let response = self
+            .client
+            .post(url)
+            .json(payload)
+            .send()
+            .await?
+            .error_for_status()?;
+        if response.status() == StatusCode::ACCEPTED {
+            return Ok(LoginResult::MfaRequired(response.json().await?));
+        }
+        self.capture_rotation(&response);
+        let body: LoginResponse = response.json().await?;
+        self.set_csrf_token(Some(body.csrf_token.clone()));
+        Ok(LoginResult::Complete(body))
+    }
+
+    /// Complete a login challenge with an authenticator or recovery code.
+    pub async fn login_mfa(&self, payload: &MfaVerifyRequest) -> Result<LoginResponse, Error> {
+        let url = self.api_url("auth/login/mfa");
+        let response = self
+            .client
+            .post(url)
+            .json(payload)
+            .send()
+            .await?
+            .error_for_status()?;
         self.capture_rotation(&response);
         let body: LoginResponse = response.json().await?;
         self.set_csrf_token(Some(body.csrf_token.clone()));
         Ok(body)
     }
 
+    /// List the single sign-on providers offered on the sign-in page.
+    pub async fn oidc_providers(&self) -> Result<OidcProvidersResponse, Error> {
+        let url = self.api_url("auth/oidc/providers");
+        self.client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Ask for a password reset link; the server answers the same way for unknown addresses.
+    pub async fn forgot_password(&self, payload: &ForgotPasswordRequest) -> Result<(), Error> {
+        let url = self.api_url("auth/password/forgot");
+        self.client
+            .post(url)
+            .json(payload)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Set a new password with the token from a reset email.
+    pub async fn reset_password(&self, payload: &ResetPasswordRequest) -> Result<(), Error> {
+        let url = self.api_url("auth/password/reset");
+        self.client
+            .post(url)
+            .json(payload)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Confirm an email address with the token from a verification email.
+    pub async fn verify_email(&self, payload: &VerifyEmailRequest) -> Result<(), Error> {
+        let url = self.api_url("auth/verify-email");
+        self.client
+            .post(url)
+            .json(payload)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Change the signed-in user's password; other sessions are signed out.
+    pub async fn change_password(&self, payload: &ChangePasswordRequest) -> Result<(), Error> {
+        let url = self.api_url("me/password");
+        let response = self
+            .send_with_refresh(move || self.apply_csrf(self.client.post(url.clone())).json(payload))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        Ok(())
+    }
+
+    /// List the signed-in user's active sessions; the one making the request is marked `current`.
+    pub async fn list_sessions(&self) -> Result<ActiveSessionListResponse, Error> {
+        let url = self.api_url("auth/sessions");
+        let response = self
+            .send_with_refresh(move || self.client.get(url.clone()))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
+    /// Sign out one of the user's sessions.
+    pub async fn revoke_session(&self, session_id: Uuid) -> Result<(), Error> {
+        let url = self.api_url(&format!("auth/sessions/{session_id}"));
+        let response = self
+            .send_with_refresh(move || self.apply_csrf(self.client.delete(url.clone())))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        Ok(())
+    }
+
+    /// Sign out every session except the current one, returning how many were revoked.
+    pub async fn revoke_other_sessions(&self) -> Result<u64, Error> {
+        let url = self.api_url("auth/sessions");
+        let response = self
+            .send_with_refresh(move || self.apply_csrf(self.client.delete(url.clone())))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        let body: RevokeSessionsResponse = response.json().await?;
+        Ok(body.revoked)
+    }
+
+    /// Send a fresh verification email to the signed-in user.
+    pub async fn resend_verification(&self) -> Result<(), Error> {
+        let url = self.api_url("me/verify-email");
+        let response = self
+            .send_with_refresh(move || self.apply_csrf(self.client.post(url.clone())))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        Ok(())
+    }
+
+    /// Join a conversation with an invite token, returning its id.
+    pub async fn accept_invite(&self, token: &str) -> Result<Uuid, Error> {
+        let url = self.api_url("invites/accept");
+        let payload = AcceptInviteRequest {
+            token: token.to_string(),
+        };
+        let response = self
+            .send_with_refresh(move || {
+                self.apply_csrf(self.client.post(url.clone()))
+                    .json(&payload)
+            })
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        let body: AcceptInviteResponse = response.json().await?;
+        Ok(body.conversation_id)
+    }
+
     /// Refresh the current session cookie.
     pub async fn refresh_session(&self) -> Result<LoginResponse, Error> {
         let url = self.api_url("auth/refresh");
@@ -152,6 +318,35 @@ impl RustyGPTClient {
         response.json().await
     }
 
+    /// Fetch the signed-in user's saved preferences.
+    pub async fn get_preferences(&self) -> Result<UserPreferences, Error> {
+        let url = self.api_url("me/preferences");
+        let response = self
+            .send_with_refresh(move || self.client.get(url.clone()))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
+    /// Save preferences; the body is a JSON merge patch, so sending the full document replaces
+    /// every field.
+    pub async fn update_preferences(
+        &self,
+        preferences: &UserPreferences,
+    ) -> Result<UserPreferences, Error> {
+        let url = self.api_url("me/preferences");
+        let response = self
+            .send_with_refresh(move || {
+                self.apply_csrf(self.client.patch(url.clone()))
+                    .json(preferences)
+            })
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
     /// Terminate the current session.
     pub async fn logout(&self) -> Result<(), Error> {
         let url = self.api_url("auth/logout");
@@ -262,10 +457,137 @@ impl RustyGPTClient {
         response.json().await
     }
 
+    /// Regenerate an assistant reply as a sibling variant.
+    pub async fn regenerate_message(
+        &self,
+        message_id: &Uuid,
+        request: &RegenerateMessageRequest,
+    ) -> Result<RegenerateMessageResponse, Error> {
+        let url = self.api_url(&format!("messages/{message_id}/regenerate"));
+        let payload = request.clone();
+        let response = self
+            .send_with_refresh(move || {
+                let builder = self.apply_csrf(self.client.post(url.clone()));
+                builder.json(&payload)
+            })
+            .await?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
+    /// Page through user accounts (admin only).
+    pub async fn admin_list_users(
+        &self,
+        query: &UserAccountQuery,
+    ) -> Result<UserAccountPage, Error> {
+        let url = self.api_url("admin/users");
+        let response = self
+            .send_with_refresh(move || self.client.get(url.clone()).query(query))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
+    /// Create an account (admin only).
+    pub async fn admin_create_user(
+        &self,
+        payload: &CreateUserAccountRequest,
+    ) -> Result<UserAccount, Error> {
+        let url = self.api_url("admin/users");
+        let response = self
+            .send_with_refresh(move || self.apply_csrf(self.client.post(url.clone())).json(payload))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
+    /// Disable or re-enable an account (admin only).
+    pub async fn admin_set_user_disabled(
+        &self,
+        user_id: Uuid,
+        disabled: bool,
+    ) -> Result<UserAccount, Error> {
+        let action = if disabled { "disable" } else { "enable" };
+        let url = self.api_url(&format!("admin/users/{user_id}/{action}"));
+        let response = self
+            .send_with_refresh(move || self.apply_csrf(self.client.post(url.clone())))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
+    /// Grant or revoke a global role (admin only).
+    pub async fn admin_set_user_role(
+        &self,
+        user_id: Uuid,
+        role: UserRole,
+        granted: bool,
+    ) -> Result<UserAccount, Error> {
+        let url = self.api_url(&format!("admin/users/{user_id}/roles/{role}"));
+        let response = self
+            .send_with_refresh(move || {
+                let request = if granted {
+                    self.client.put(url.clone())
+                } else {
+                    self.client.delete(url.clone())
+                };
+                self.apply_csrf(request)
+            })
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
+    /// Set a user's password, or email them a reset link when `password` is empty (admin only).
+    pub async fn admin_reset_password(
+        &self,
+        user_id: Uuid,
+        payload: &AdminPasswordResetRequest,
+    ) -> Result<AdminPasswordResetResponse, Error> {
+        let url = self.api_url(&format!("admin/users/{user_id}/password"));
+        let response = self
+            .send_with_refresh(move || self.apply_csrf(self.client.post(url.clone())).json(payload))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
+    /// Lift a failed-login lockout (admin only).
+    pub async fn admin_unlock_user(&self, user_id: Uuid) -> Result<(), Error> {
+        let url = self.api_url(&format!("admin/users/{user_id}/unlock"));
+        let response = self
+            .send_with_refresh(move || self.apply_csrf(self.client.post(url.clone())))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        Ok(())
+    }
+
+    /// Global roles with their holder counts (admin only).
+    pub async fn admin_list_roles(&self) -> Result<Vec<RoleSummary>, Error> {
+        let url = self.api_url("admin/roles");
+        let response = self
+            .send_with_refresh(move || self.client.get(url.clone()))
+            .await?
+            .error_for_status()?;
+        self.capture_rotation(&response);
+        response.json().await
+    }
+
     /// Helper to construct the SSE conversation stream URL.
     pub fn conversation_stream_url(&self, conversation_id: &Uuid) -> String {
         self.api_url(&format!("stream/conversations/{conversation_id}"))
     }
+
+    /// Helper to construct the SSE stream URL covering all of the user's conversations.
+    pub fn user_stream_url(&self) -> String {
+        self.api_url("stream/me")
+    }
 }
 
 fn read_cookie(name: &str) -> Option<String> {
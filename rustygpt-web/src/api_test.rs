@@ -54,6 +54,7 @@ mod tests {
             path: "mroot".into(),
             depth: 1,
             created_at: Timestamp(Utc::now()),
+            alternates: Vec::new(),
         };
 
         assert_eq!(message.content, "Test message");
@@ -1,8 +1,11 @@
 use crate::api::RustyGPTClient;
 use crate::containers::setup::Setup;
 use crate::models::app_state::AppState;
-use crate::pages::login::LoginPage;
+use crate::pages::{
+    ForgotPasswordPage, LoginPage, ResetPasswordPage, VerifyEmailPage, apply_display_preferences,
+};
 use crate::routes::MainRoute;
+use i18nrs::yew::use_translation;
 use reqwest::StatusCode;
 use shared::models::{LoginResponse, MeResponse};
 use wasm_bindgen::prelude::*;
@@ -22,6 +25,7 @@ extern "C" {
 pub fn app() -> Html {
     let (_store_state, store_dispatch) = use_store::<AppState>();
     let app_state = use_state(|| None::<AppState>);
+    let (_i18n, set_language) = use_translation();
 
     {
         let app_state_handle = app_state.clone();
@@ -87,6 +91,25 @@ pub fn app() -> Html {
         });
     }
 
+    // Saved theme and language follow the account from browser to browser.
+    {
+        let user_id = (*app_state)
+            .as_ref()
+            .and_then(|state| state.user.as_ref())
+            .map(|user| user.id);
+        use_effect_with(user_id, move |user_id| {
+            if user_id.is_some() {
+                spawn_local(async move {
+                    match RustyGPTClient::shared().get_preferences().await {
+                        Ok(preferences) => apply_display_preferences(&preferences, &set_language),
+                        Err(err) => log(&format!("Unable to load preferences: {err}")),
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
     let logout_callback = {
         let state_setter = app_state.clone();
         let logout_dispatch = store_dispatch.clone();
@@ -122,6 +145,7 @@ pub fn app() -> Html {
                                     user,
                                     session,
                                     csrf_token,
+                                    ..
                                 } = login;
                                 client.set_csrf_token(Some(csrf_token.clone()));
                                 let state = AppState {
@@ -134,7 +158,15 @@ pub fn app() -> Html {
                                 store_dispatch.set(state);
                             })
                         };
-                        html! { <LoginPage {on_success} /> }
+                        // Links from emails must work without a session; other paths (such as
+                        // an invite) stay in the address bar and resolve once signed in.
+                        let pathname = web_sys::window().and_then(|window| window.location().pathname().ok());
+                        match pathname.as_deref().and_then(MainRoute::recognize) {
+                            Some(MainRoute::ForgotPassword) => html! { <ForgotPasswordPage /> },
+                            Some(MainRoute::ResetPassword) => html! { <ResetPasswordPage /> },
+                            Some(MainRoute::VerifyEmail) => html! { <VerifyEmailPage /> },
+                            _ => html! { <LoginPage {on_success} /> },
+                        }
                     }
                     Some(ref state) if state.is_setup == Some(true) => {
                         html! {
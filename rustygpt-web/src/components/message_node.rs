@@ -1,10 +1,22 @@
 use shared::models::{MessageRole, MessageView, Timestamp};
 use yew::{Callback, Html, Properties, classes, function_component, html};
 
+/// Position of a message within its group of regenerated variants.
+#[derive(Clone, PartialEq)]
+pub struct VariantPosition {
+    pub index: usize,
+    pub count: usize,
+    pub on_select: Callback<usize>,
+}
+
 #[derive(Properties, PartialEq, Clone)]
 pub struct MessageNodeProps {
     pub message: MessageView,
     pub on_reply: Callback<MessageView>,
+    #[prop_or_default]
+    pub on_regenerate: Option<Callback<MessageView>>,
+    #[prop_or_default]
+    pub variant: Option<VariantPosition>,
 }
 
 const fn role_classes(role: MessageRole) -> &'static str {
@@ -35,14 +47,74 @@ fn format_timestamp(timestamp: &Timestamp) -> String {
     timestamp.0.format("%H:%M:%S").to_string()
 }
 
+fn variant_nav(variant: &VariantPosition) -> Html {
+    let VariantPosition {
+        index,
+        count,
+        on_select,
+    } = variant.clone();
+
+    let previous = {
+        let on_select = on_select.clone();
+        Callback::from(move |_| on_select.emit(index.saturating_sub(1)))
+    };
+    let next = Callback::from(move |_| on_select.emit(index + 1));
+
+    html! {
+        <nav class="flex items-center gap-1" aria-label="Response variants">
+            <button
+                class="btn btn-ghost btn-xs"
+                type="button"
+                aria-label="Previous variant"
+                disabled={index == 0}
+                onclick={previous}
+            >
+                {"<"}
+            </button>
+            <span aria-live="polite">{ format!("{}/{}", index + 1, count) }</span>
+            <button
+                class="btn btn-ghost btn-xs"
+                type="button"
+                aria-label="Next variant"
+                disabled={index + 1 >= count}
+                onclick={next}
+            >
+                {">"}
+            </button>
+        </nav>
+    }
+}
+
 #[function_component(MessageNode)]
 pub fn message_node(props: &MessageNodeProps) -> Html {
     let message = props.message.clone();
     let on_reply = props.on_reply.clone();
 
-    let reply_callback = Callback::from(move |_| {
-        on_reply.emit(message.clone());
-    });
+    let reply_callback = {
+        let message = message.clone();
+        Callback::from(move |_| {
+            on_reply.emit(message.clone());
+        })
+    };
+
+    let regenerate_button = props
+        .on_regenerate
+        .clone()
+        .filter(|_| message.role == MessageRole::Assistant && message.parent_id.is_some())
+        .map_or_else(
+            || html! {},
+            |on_regenerate| {
+                let message = message.clone();
+                let onclick = Callback::from(move |_| on_regenerate.emit(message.clone()));
+                html! {
+                    <button class="btn btn-ghost btn-xs" type="button" onclick={onclick}>
+                        {"Regenerate"}
+                    </button>
+                }
+            },
+        );
+
+    let variant = props.variant.as_ref().map_or_else(|| html! {}, variant_nav);
 
     let classes = classes!(
         "rounded-xl",
@@ -70,6 +142,8 @@ pub fn message_node(props: &MessageNodeProps) -> Html {
                 >
                     {"Reply"}
                 </button>
+                { regenerate_button }
+                { variant }
             </div>
         </div>
     }
@@ -1,4 +1,5 @@
 use i18nrs::yew::use_translation;
+use shared::models::ThemePreference;
 use wasm_bindgen::prelude::*;
 use web_sys::window;
 use yew::{
@@ -12,6 +13,32 @@ extern "C" {
     fn log(s: &str);
 }
 
+fn system_prefers_dark() -> bool {
+    window()
+        .and_then(|window| {
+            window
+                .match_media("(prefers-color-scheme: dark)")
+                .ok()
+                .flatten()
+        })
+        .is_some_and(|media_query| media_query.matches())
+}
+
+/// Sets `data-theme` on the document from a saved preference, resolving `system` against the
+/// browser's colour scheme.
+pub fn apply_theme(theme: ThemePreference) {
+    let theme = match theme {
+        ThemePreference::System if system_prefers_dark() => "dark",
+        ThemePreference::System | ThemePreference::Light => "light",
+        ThemePreference::Dark => "dark",
+    };
+    if let Some(document) = window().and_then(|window| window.document())
+        && let Some(html_element) = document.document_element()
+    {
+        let _ = html_element.set_attribute("data-theme", theme);
+    }
+}
+
 #[derive(Properties, PartialEq, Eq)]
 pub struct ThemeSwitcherProps {
     #[prop_or_default]
@@ -28,26 +55,22 @@ pub fn theme_switcher(props: &ThemeSwitcherProps) -> Html {
     {
         let current_theme = current_theme.clone();
         use_effect_with((), move |()| {
-            if let Some(window) = window() {
-                let system_prefers_dark = window
-                    .match_media("(prefers-color-scheme: dark)")
-                    .ok()
-                    .flatten()
-                    .is_some_and(|media_query| media_query.matches());
-
-                let default_theme = if system_prefers_dark { "dark" } else { "light" };
+            let default_theme = if system_prefers_dark() {
+                "dark"
+            } else {
+                "light"
+            };
 
-                if let Some(document) = window.document()
-                    && let Some(html_element) = document.document_element()
-                {
-                    let theme = html_element
-                        .get_attribute("data-theme")
-                        .filter(|t| !t.is_empty())
-                        .unwrap_or_else(|| default_theme.to_string());
+            if let Some(document) = window().and_then(|window| window.document())
+                && let Some(html_element) = document.document_element()
+            {
+                let theme = html_element
+                    .get_attribute("data-theme")
+                    .filter(|t| !t.is_empty())
+                    .unwrap_or_else(|| default_theme.to_string());
 
-                    current_theme.set(theme.clone());
-                    let _ = html_element.set_attribute("data-theme", &theme);
-                }
+                current_theme.set(theme.clone());
+                let _ = html_element.set_attribute("data-theme", &theme);
             }
             || {}
         });
@@ -1,9 +1,11 @@
+use std::collections::HashMap;
+
 use chrono::Utc;
 use shared::models::{MessageRole, MessageView, Timestamp};
 use uuid::Uuid;
-use yew::{Callback, Html, Properties, function_component, html};
+use yew::{Callback, Html, Properties, function_component, html, use_state};
 
-use super::message_node::MessageNode;
+use super::message_node::{MessageNode, VariantPosition};
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct StreamingDisplay {
@@ -21,10 +23,40 @@ pub struct ThreadViewProps {
     #[prop_or_default]
     pub streaming: Vec<StreamingDisplay>,
     pub on_reply: Callback<MessageView>,
+    #[prop_or_default]
+    pub on_regenerate: Option<Callback<MessageView>>,
+}
+
+/// Resolve which variant is shown for a message's sibling group, defaulting to the newest.
+fn selected_variant(message: &MessageView, selections: &HashMap<Uuid, Uuid>) -> Option<Uuid> {
+    message
+        .parent_id
+        .and_then(|parent_id| selections.get(&parent_id).copied())
+        .filter(|id| message.alternates.contains(id))
+        .or_else(|| message.alternates.last().copied())
+}
+
+/// Paths of variants (and therefore their subtrees) that are not currently selected.
+fn hidden_variant_paths(messages: &[MessageView], selections: &HashMap<Uuid, Uuid>) -> Vec<String> {
+    messages
+        .iter()
+        .filter(|message| message.alternates.len() > 1)
+        .filter(|message| selected_variant(message, selections) != Some(message.id))
+        .map(|message| message.path.clone())
+        .collect()
+}
+
+fn is_hidden(path: &str, hidden: &[String]) -> bool {
+    hidden.iter().any(|prefix| {
+        path.strip_prefix(prefix.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
 }
 
 #[function_component(ThreadView)]
 pub fn thread_view(props: &ThreadViewProps) -> Html {
+    let selections = use_state(HashMap::<Uuid, Uuid>::new);
+
     if props.messages.is_empty() {
         return html! {
             <div class="p-6 text-sm text-base-content/70">
@@ -33,11 +65,38 @@ pub fn thread_view(props: &ThreadViewProps) -> Html {
         };
     }
 
+    let hidden = hidden_variant_paths(&props.messages, &selections);
+
     html! {
         <div class="flex flex-col gap-2">
-            { for props.messages.iter().cloned().map(|message| {
+            { for props.messages.iter().filter(|message| !is_hidden(&message.path, &hidden)).cloned().map(|message| {
                 let on_reply = props.on_reply.clone();
-                html! { <MessageNode message={message} on_reply={on_reply} /> }
+                let on_regenerate = props.on_regenerate.clone();
+                let variant = message.parent_id.filter(|_| message.alternates.len() > 1).and_then(|parent_id| {
+                    let index = message.alternates.iter().position(|id| *id == message.id)?;
+                    let selections = selections.clone();
+                    let alternates = message.alternates.clone();
+                    Some(VariantPosition {
+                        index,
+                        count: alternates.len(),
+                        on_select: Callback::from(move |next: usize| {
+                            if let Some(id) = alternates.get(next) {
+                                let mut updated = (*selections).clone();
+                                updated.insert(parent_id, *id);
+                                selections.set(updated);
+                            }
+                        }),
+                    })
+                });
+                html! {
+                    <MessageNode
+                        key={message.id.to_string()}
+                        message={message.clone()}
+                        on_reply={on_reply}
+                        on_regenerate={on_regenerate}
+                        variant={variant}
+                    />
+                }
             }) }
             { for props.streaming.iter().cloned().map(|entry| {
                 let on_reply = props.on_reply.clone();
@@ -52,6 +111,7 @@ pub fn thread_view(props: &ThreadViewProps) -> Html {
                     path: String::new(),
                     depth: entry.depth,
                     created_at: Timestamp(Utc::now()),
+                    alternates: Vec::new(),
                 };
                 html! {
                     <div class="animate-pulse opacity-80" key={entry.message_id.to_string()}>
@@ -62,3 +122,54 @@ pub fn thread_view(props: &ThreadViewProps) -> Html {
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(
+        id: Uuid,
+        parent_id: Option<Uuid>,
+        path: &str,
+        alternates: Vec<Uuid>,
+    ) -> MessageView {
+        MessageView {
+            id,
+            root_id: Uuid::nil(),
+            parent_id,
+            conversation_id: Uuid::nil(),
+            author_user_id: None,
+            role: MessageRole::Assistant,
+            content: String::new(),
+            path: path.to_string(),
+            depth: i32::try_from(path.split('.').count()).unwrap_or(1),
+            created_at: Timestamp(Utc::now()),
+            alternates,
+        }
+    }
+
+    #[test]
+    fn unselected_variants_hide_their_subtrees() {
+        let root = Uuid::new_v4();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let variants = vec![first, second];
+        let messages = vec![
+            message(root, None, "mroot", Vec::new()),
+            message(first, Some(root), "mroot.ma", variants.clone()),
+            message(Uuid::new_v4(), Some(first), "mroot.ma.mc", Vec::new()),
+            message(second, Some(root), "mroot.mb", variants),
+        ];
+
+        let hidden = hidden_variant_paths(&messages, &HashMap::new());
+        assert!(is_hidden("mroot.ma", &hidden));
+        assert!(is_hidden("mroot.ma.mc", &hidden));
+        assert!(!is_hidden("mroot.mb", &hidden));
+        assert!(!is_hidden("mroot.mab", &hidden));
+
+        let selections = HashMap::from([(root, first)]);
+        let hidden = hidden_variant_paths(&messages, &selections);
+        assert!(!is_hidden("mroot.ma.mc", &hidden));
+        assert!(is_hidden("mroot.mb", &hidden));
+    }
+}
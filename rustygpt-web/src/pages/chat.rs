@@ -12,8 +12,9 @@ use chrono::Utc;
 use gloo_timers::callback::Timeout;
 use serde_json::from_str;
 use shared::models::{
-    ConversationStreamEvent, MembershipChangeAction, MessageRole, MessageView,
-    PostRootMessageRequest, PresenceStatus, ReplyMessageRequest, ThreadSummary, Timestamp,
+    ConversationLifecycleAction, ConversationStreamEvent, MembershipChangeAction, MessageRole,
+    MessageView, PostRootMessageRequest, PresenceStatus, RegenerateMessageRequest,
+    ReplyMessageRequest, ThreadSummary, Timestamp,
 };
 use uuid::Uuid;
 use wasm_bindgen::{JsCast, closure::Closure};
@@ -232,6 +233,13 @@ fn register_stream_listeners(
                     };
 
                     if let Some(entry) = entry {
+                        // Regenerated replies need a tree refresh so variants are grouped.
+                        let is_variant = (*messages).iter().any(|msg| {
+                            msg.role == MessageRole::Assistant
+                                && msg.parent_id == entry.parent_id
+                                && msg.id != payload.message_id
+                        });
+
                         messages.set({
                             let mut next = (*messages).clone();
                             if let Some(existing) =
@@ -251,17 +259,20 @@ fn register_stream_listeners(
                                     path: String::new(),
                                     depth: entry.depth,
                                     created_at: Timestamp(Utc::now()),
+                                    alternates: Vec::new(),
                                 });
                             }
                             next.sort_by(|a, b| a.created_at.0.cmp(&b.created_at.0));
                             next
                         });
 
-                        pending_activity.set({
-                            let mut roots = (*pending_activity).clone();
-                            roots.insert(entry.root_id);
-                            roots
-                        });
+                        if !is_variant {
+                            pending_activity.set({
+                                let mut roots = (*pending_activity).clone();
+                                roots.insert(entry.root_id);
+                                roots
+                            });
+                        }
                     }
                 }
             }));
@@ -271,6 +282,79 @@ fn register_stream_listeners(
         listeners.borrow_mut().push(listener);
     }
 
+    // message.edited
+    {
+        let messages = messages.clone();
+        let listener =
+            Closure::<dyn FnMut(MessageEvent)>::wrap(Box::new(move |event: MessageEvent| {
+                if let Some(data) = event.data().as_string()
+                    && let Ok(ConversationStreamEvent::MessageEdited { payload }) = from_str(&data)
+                {
+                    messages.set({
+                        let mut next = (*messages).clone();
+                        if let Some(existing) =
+                            next.iter_mut().find(|msg| msg.id == payload.message_id)
+                        {
+                            existing.content = payload.content;
+                        }
+                        next
+                    });
+                }
+            }));
+        event_source
+            .add_event_listener_with_callback("message.edited", listener.as_ref().unchecked_ref())
+            .expect("message.edited listener");
+        listeners.borrow_mut().push(listener);
+    }
+
+    // conversation.lifecycle
+    {
+        let threads = threads.clone();
+        let selected_thread = selected_thread.clone();
+        let messages = messages.clone();
+        let error = error.clone();
+        let listener =
+            Closure::<dyn FnMut(MessageEvent)>::wrap(Box::new(move |event: MessageEvent| {
+                if let Some(data) = event.data().as_string()
+                    && let Ok(ConversationStreamEvent::ConversationLifecycle { payload }) =
+                        from_str(&data)
+                    && payload.conversation_id == conversation_id
+                {
+                    match payload.action {
+                        ConversationLifecycleAction::Deleted => {
+                            threads.set(Vec::new());
+                            selected_thread.set(None);
+                            messages.set(Vec::new());
+                            error.set(Some("This conversation was deleted.".to_string()));
+                        }
+                        ConversationLifecycleAction::Purged => {
+                            if let Some(root) = *selected_thread {
+                                let messages = messages.clone();
+                                let error = error.clone();
+                                spawn_local(async move {
+                                    let client = RustyGPTClient::shared();
+                                    match client.get_thread_tree(&root, None, Some(200)).await {
+                                        Ok(tree) => messages.set(tree.messages),
+                                        Err(err) => error
+                                            .set(Some(format!("Failed to refresh thread: {err}"))),
+                                    }
+                                });
+                            }
+                        }
+                        ConversationLifecycleAction::Archived
+                        | ConversationLifecycleAction::Unarchived => {}
+                    }
+                }
+            }));
+        event_source
+            .add_event_listener_with_callback(
+                "conversation.lifecycle",
+                listener.as_ref().unchecked_ref(),
+            )
+            .expect("conversation.lifecycle listener");
+        listeners.borrow_mut().push(listener);
+    }
+
     // typing.update
     {
         let selected_thread = selected_thread.clone();
@@ -705,6 +789,28 @@ pub fn chat_page(props: &ChatPageProps) -> Html {
         })
     };
 
+    let on_regenerate_message = {
+        let error = error_message.clone();
+        let typing = typing_active.clone();
+        Callback::from(move |message: MessageView| {
+            let error = error.clone();
+            let typing = typing.clone();
+            spawn_local(async move {
+                let client = RustyGPTClient::shared();
+                let request = RegenerateMessageRequest::default();
+                match client.regenerate_message(&message.id, &request).await {
+                    Ok(_) => {
+                        typing.set(true);
+                        error.set(None);
+                    }
+                    Err(err) => {
+                        error.set(Some(format!("Failed to regenerate reply: {err}")));
+                    }
+                }
+            });
+        })
+    };
+
     let on_composer_text = {
         let composer_text = composer_text.clone();
         Callback::from(move |value: String| composer_text.set(value))
@@ -942,6 +1048,7 @@ pub fn chat_page(props: &ChatPageProps) -> Html {
                         messages={(*messages).clone()}
                         streaming={streaming_for_selected.clone()}
                         on_reply={on_reply_to_message}
+                        on_regenerate={on_regenerate_message}
                     />
                     <TypingIndicator active={typing_display} />
                 </div>
@@ -0,0 +1,60 @@
+use crate::{
+    api::RustyGPTClient,
+    routes::{MainRoute, query_param},
+};
+use reqwest::StatusCode;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yew_router::hooks::use_navigator;
+
+/// Accepts the conversation invite from `?token=` and opens the conversation.
+#[function_component(InvitePage)]
+pub fn invite_page() -> Html {
+    let error = use_state(|| None::<String>);
+    let navigator = use_navigator();
+
+    {
+        let error = error.clone();
+        use_effect_with((), move |()| {
+            match query_param("token") {
+                Some(token) => spawn_local(async move {
+                    match RustyGPTClient::shared().accept_invite(&token).await {
+                        Ok(conversation_id) => {
+                            if let Some(nav) = navigator {
+                                nav.replace(&MainRoute::ChatConversation {
+                                    conversation_id: conversation_id.to_string(),
+                                });
+                            }
+                        }
+                        Err(err) => error.set(Some(match err.status() {
+                            Some(StatusCode::NOT_FOUND | StatusCode::GONE) => {
+                                "This invite is invalid, expired or has already been used"
+                                    .to_string()
+                            }
+                            Some(status) => format!("Unable to accept the invite: {status}"),
+                            None => "Unable to connect to server".to_string(),
+                        })),
+                    }
+                }),
+                None => error.set(Some("This invite link is incomplete".to_string())),
+            }
+            || ()
+        });
+    }
+
+    html! {
+        <div class="p-4 space-y-6">
+            <h1 class="text-2xl font-bold">{ "Conversation invite" }</h1>
+            {
+                (*error).as_ref().map_or_else(
+                    || html! { <p>{ "Joining the conversation..." }</p> },
+                    |message| html! {
+                        <div class="alert alert-error">
+                            <span>{message.clone()}</span>
+                        </div>
+                    },
+                )
+            }
+        </div>
+    }
+}
@@ -1,10 +1,17 @@
-use crate::{api::RustyGPTClient, routes::MainRoute};
+use crate::{
+    api::{LoginResult, RustyGPTClient},
+    routes::{MainRoute, take_fragment_params},
+};
+use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
-use shared::models::{LoginRequest, LoginResponse};
+use shared::models::{
+    LoginRequest, LoginResponse, MfaChallengeResponse, MfaVerifyRequest, OidcProviderSummary,
+    Timestamp, TotpEnrollment,
+};
 use wasm_bindgen_futures::spawn_local;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
-use yew_router::hooks::use_navigator;
+use yew_router::{hooks::use_navigator, prelude::Navigator};
 
 #[derive(yew::Properties, PartialEq)]
 pub struct LoginPageProps {
@@ -12,21 +19,111 @@ pub struct LoginPageProps {
     pub on_success: Option<Callback<LoginResponse>>,
 }
 
+fn finish_login(
+    response: LoginResponse,
+    navigator: Option<&Navigator>,
+    on_success: Option<&Callback<LoginResponse>>,
+) {
+    if let Some(nav) = navigator {
+        nav.push(&MainRoute::Home);
+    }
+    if let Some(callback) = on_success {
+        callback.emit(response);
+    }
+}
+
+fn error_message(err: &reqwest::Error, unauthorized: &str) -> String {
+    err.status().map_or_else(
+        || "Unable to connect to server".to_string(),
+        |status| match status {
+            StatusCode::UNAUTHORIZED => unauthorized.to_string(),
+            // Disabled accounts and failed-login lockouts both answer 423.
+            StatusCode::LOCKED => {
+                "This account is locked. Try again later or reset your password.".to_string()
+            }
+            _ => format!("Login failed: {status}"),
+        },
+    )
+}
+
+fn sso_error_message(code: &str) -> String {
+    match code {
+        "account_not_linked" => {
+            "An account with this email already exists and could not be linked to your sign-in"
+        }
+        "email_required" => "Your identity provider did not share an email address",
+        "account_disabled" => "Your account has been disabled",
+        "invalid_state" => "The sign-in took too long or was started in another browser",
+        _ => "Single sign-on failed; please try again",
+    }
+    .to_string()
+}
+
+/// Rebuilds a two-factor challenge handed over by the single sign-on callback.
+fn challenge_from_fragment(params: &[(String, String)]) -> Option<MfaChallengeResponse> {
+    let value = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    };
+    let enrollment = value("mfa_secret")
+        .zip(value("mfa_uri"))
+        .map(|(secret, otpauth_uri)| TotpEnrollment {
+            secret,
+            otpauth_uri,
+        });
+    let expires_at = value("expires_at")
+        .and_then(|raw| DateTime::parse_from_rfc3339(&raw).ok())
+        .map_or_else(Utc::now, |parsed| parsed.with_timezone(&Utc));
+    value("mfa_challenge").map(|challenge_token| MfaChallengeResponse {
+        challenge_token,
+        expires_at: Timestamp(expires_at),
+        enrollment,
+    })
+}
+
 #[function_component(LoginPage)]
 pub fn login_page(props: &LoginPageProps) -> Html {
     let email = use_state(String::new);
     let password = use_state(String::new);
+    let code = use_state(String::new);
+    let challenge = use_state(|| None::<MfaChallengeResponse>);
+    // Held back until the user has seen the recovery codes issued by a first enrollment.
+    let pending = use_state(|| None::<LoginResponse>);
     let error = use_state(|| None::<String>);
     let loading = use_state(|| false);
+    let providers = use_state(Vec::<OidcProviderSummary>::new);
     let navigator = use_navigator();
 
+    {
+        let challenge = challenge.clone();
+        let error = error.clone();
+        let providers = providers.clone();
+        use_effect_with((), move |()| {
+            let params = take_fragment_params();
+            if let Some(pending) = challenge_from_fragment(&params) {
+                challenge.set(Some(pending));
+            } else if let Some((_, code)) = params.iter().find(|(key, _)| key == "oidc_error") {
+                error.set(Some(sso_error_message(code)));
+            }
+            spawn_local(async move {
+                if let Ok(response) = RustyGPTClient::shared().oidc_providers().await {
+                    providers.set(response.providers);
+                }
+            });
+            || ()
+        });
+    }
+
     let onsubmit = {
         let email_handle = email.clone();
         let password_handle = password.clone();
+        let challenge_handle = challenge.clone();
         let error_handle = error.clone();
         let loading_handle = loading.clone();
         let on_success = props.on_success.clone();
-        let navigator = navigator;
+        let navigator = navigator.clone();
         Callback::from(move |event: SubmitEvent| {
             event.prevent_default();
             let email_value = (*email_handle).clone();
@@ -36,6 +133,7 @@ pub fn login_page(props: &LoginPageProps) -> Html {
             let on_success_cb = on_success.clone();
             let loading_ref = loading_handle.clone();
             let error_ref = error_handle.clone();
+            let challenge_ref = challenge_handle.clone();
             let navigator_handle = navigator.clone();
             spawn_local(async move {
                 let client = RustyGPTClient::shared();
@@ -44,23 +142,58 @@ pub fn login_page(props: &LoginPageProps) -> Html {
                     password: password_value,
                 };
                 match client.login(&request).await {
+                    Ok(LoginResult::Complete(response)) => {
+                        finish_login(response, navigator_handle.as_ref(), on_success_cb.as_ref());
+                    }
+                    Ok(LoginResult::MfaRequired(next)) => challenge_ref.set(Some(next)),
+                    Err(err) => error_ref.set(Some(error_message(&err, "Invalid credentials"))),
+                }
+                loading_ref.set(false);
+            });
+        })
+    };
+
+    let on_code_submit = {
+        let code_handle = code.clone();
+        let challenge_handle = challenge.clone();
+        let pending_handle = pending.clone();
+        let error_handle = error.clone();
+        let loading_handle = loading.clone();
+        let on_success = props.on_success.clone();
+        let navigator = navigator.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            let Some(current) = (*challenge_handle).clone() else {
+                return;
+            };
+            let code_value = (*code_handle).trim().to_string();
+            loading_handle.set(true);
+            error_handle.set(None);
+            let on_success_cb = on_success.clone();
+            let loading_ref = loading_handle.clone();
+            let error_ref = error_handle.clone();
+            let challenge_ref = challenge_handle.clone();
+            let pending_ref = pending_handle.clone();
+            let navigator_handle = navigator.clone();
+            spawn_local(async move {
+                let client = RustyGPTClient::shared();
+                let request = MfaVerifyRequest {
+                    challenge_token: current.challenge_token,
+                    code: code_value,
+                };
+                match client.login_mfa(&request).await {
+                    Ok(response) if !response.recovery_codes.is_empty() => {
+                        challenge_ref.set(None);
+                        pending_ref.set(Some(response));
+                    }
                     Ok(response) => {
-                        if let Some(ref nav) = navigator_handle {
-                            nav.push(&MainRoute::Home);
-                        }
-                        if let Some(callback) = on_success_cb {
-                            callback.emit(response);
-                        }
+                        finish_login(response, navigator_handle.as_ref(), on_success_cb.as_ref());
                     }
                     Err(err) => {
-                        let message = err.status().map_or_else(
-                            || "Unable to connect to server".to_string(),
-                            |status| match status {
-                                StatusCode::UNAUTHORIZED => "Invalid credentials".to_string(),
-                                _ => format!("Login failed: {status}"),
-                            },
-                        );
-                        error_ref.set(Some(message));
+                        error_ref.set(Some(error_message(
+                            &err,
+                            "Invalid code, or the sign-in expired",
+                        )));
                     }
                 }
                 loading_ref.set(false);
@@ -68,6 +201,17 @@ pub fn login_page(props: &LoginPageProps) -> Html {
         })
     };
 
+    let on_continue = {
+        let pending_handle = pending.clone();
+        let on_success = props.on_success.clone();
+        let navigator = navigator;
+        Callback::from(move |_: MouseEvent| {
+            if let Some(response) = (*pending_handle).clone() {
+                finish_login(response, navigator.as_ref(), on_success.as_ref());
+            }
+        })
+    };
+
     let on_email_change = {
         let email = email.clone();
         Callback::from(move |event: InputEvent| {
@@ -86,19 +230,95 @@ pub fn login_page(props: &LoginPageProps) -> Html {
         })
     };
 
+    let on_code_change = {
+        let code = code.clone();
+        Callback::from(move |event: InputEvent| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                code.set(input.value());
+            }
+        })
+    };
+
     let is_busy = *loading;
     let disable_submit = (*email).is_empty() || (*password).is_empty() || is_busy;
+    let error_alert = (*error).as_ref().map_or_else(
+        || html! {},
+        |message| {
+            html! {
+                <div class="alert alert-error">
+                    <span>{message.clone()}</span>
+                </div>
+            }
+        },
+    );
+
+    if let Some(response) = &*pending {
+        return html! {
+            <div class="flex items-center justify-center min-h-screen bg-base-200">
+                <div class="card w-full max-w-md shadow-lg bg-base-100">
+                    <div class="card-body">
+                        <h2 class="card-title text-2xl">{"Save your recovery codes"}</h2>
+                        <p>{"Each code signs you in once if you lose your authenticator. They will not be shown again."}</p>
+                        <ul class="font-mono grid grid-cols-2 gap-1">
+                            { for response.recovery_codes.iter().map(|code| html! { <li>{code.clone()}</li> }) }
+                        </ul>
+                        <div class="form-control mt-6">
+                            <button class="btn btn-primary" onclick={on_continue}>{"Continue"}</button>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        };
+    }
+
+    if let Some(current) = &*challenge {
+        let disable_verify = (*code).trim().is_empty() || is_busy;
+        return html! {
+            <div class="flex items-center justify-center min-h-screen bg-base-200">
+                <div class="card w-full max-w-md shadow-lg bg-base-100">
+                    <form class="card-body" onsubmit={on_code_submit}>
+                        <h2 class="card-title text-2xl">{"Two-factor authentication"}</h2>
+                        {error_alert}
+                        if let Some(enrollment) = &current.enrollment {
+                            <div class="alert alert-info flex-col items-start">
+                                <span>{"Your account requires two-factor authentication. Add this key to your authenticator app, then enter the code it shows."}</span>
+                                <code class="break-all">{enrollment.secret.clone()}</code>
+                                <a class="link break-all" href={enrollment.otpauth_uri.clone()}>{"Open in authenticator"}</a>
+                            </div>
+                        } else {
+                            <p>{"Enter the code from your authenticator app, or one of your recovery codes."}</p>
+                        }
+                        <div class="form-control">
+                            <label class="label" for="mfa-code">
+                                <span class="label-text">{"Code"}</span>
+                            </label>
+                            <input
+                                id="mfa-code"
+                                class="input input-bordered"
+                                type="text"
+                                autocomplete="one-time-code"
+                                required=true
+                                value={(*code).clone()}
+                                oninput={on_code_change}
+                            />
+                        </div>
+                        <div class="form-control mt-6">
+                            <button class="btn btn-primary" type="submit" disabled={disable_verify}>
+                                {if is_busy { "Verifying..." } else { "Verify" }}
+                            </button>
+                        </div>
+                    </form>
+                </div>
+            </div>
+        };
+    }
 
     html! {
         <div class="flex items-center justify-center min-h-screen bg-base-200">
             <div class="card w-full max-w-md shadow-lg bg-base-100">
                 <form class="card-body" onsubmit={onsubmit}>
                     <h2 class="card-title text-2xl">{"Sign in"}</h2>
-                    if let Some(message) = &*error {
-                        <div class="alert alert-error">
-                            <span>{message.clone()}</span>
-                        </div>
-                    }
+                    {error_alert}
                     <div class="form-control">
                         <label class="label" for="email">
                             <span class="label-text">{"Email"}</span>
@@ -130,6 +350,15 @@ pub fn login_page(props: &LoginPageProps) -> Html {
                             {if is_busy { "Signing in..." } else { "Sign in" }}
                         </button>
                     </div>
+                    <a class="link text-sm" href="/forgot-password">{"Forgot password?"}</a>
+                    if !providers.is_empty() {
+                        <div class="divider">{"or"}</div>
+                        { for providers.iter().map(|provider| html! {
+                            <a class="btn btn-outline" href={provider.login_url.clone()}>
+                                {format!("Sign in with {}", provider.display_name)}
+                            </a>
+                        }) }
+                    }
                 </form>
             </div>
         </div>
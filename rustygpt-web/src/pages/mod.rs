@@ -1,17 +1,23 @@
 mod chat;
 mod dashboard;
 mod error;
+mod invite;
 pub mod login;
+mod password_reset;
 mod profile;
 mod roles;
 mod settings;
 mod users;
+mod verify_email;
 
 pub use chat::ChatPage;
 pub use dashboard::DashboardPage;
 pub use error::ErrorPage;
+pub use invite::InvitePage;
 pub use login::LoginPage;
+pub use password_reset::{ForgotPasswordPage, ResetPasswordPage};
 pub use profile::ProfilePage;
 pub use roles::RolesPage;
-pub use settings::SettingsPage;
+pub use settings::{SettingsPage, apply_display_preferences};
 pub use users::UsersPage;
+pub use verify_email::VerifyEmailPage;
@@ -0,0 +1,229 @@
+use crate::{api::RustyGPTClient, routes::query_param};
+use reqwest::StatusCode;
+use shared::models::{ForgotPasswordRequest, ResetPasswordRequest};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+fn input_setter(handle: UseStateHandle<String>) -> Callback<InputEvent> {
+    Callback::from(move |event: InputEvent| {
+        if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+            handle.set(input.value());
+        }
+    })
+}
+
+fn card(title: &str, body: Html) -> Html {
+    html! {
+        <div class="flex items-center justify-center min-h-screen bg-base-200">
+            <div class="card w-full max-w-md shadow-lg bg-base-100">
+                <div class="card-body">
+                    <h2 class="card-title text-2xl">{title.to_string()}</h2>
+                    {body}
+                    <a class="link text-sm mt-4" href="/">{"Back to sign in"}</a>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+fn error_alert(error: Option<&String>) -> Html {
+    error.map_or_else(
+        || html! {},
+        |message| {
+            html! {
+                <div class="alert alert-error">
+                    <span>{message.clone()}</span>
+                </div>
+            }
+        },
+    )
+}
+
+/// Requests a password reset link by email.
+#[function_component(ForgotPasswordPage)]
+pub fn forgot_password_page() -> Html {
+    let email = use_state(String::new);
+    let sent = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    let loading = use_state(|| false);
+
+    let onsubmit = {
+        let email = email.clone();
+        let sent = sent.clone();
+        let error = error.clone();
+        let loading = loading.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            let request = ForgotPasswordRequest {
+                email: (*email).trim().to_string(),
+            };
+            loading.set(true);
+            error.set(None);
+            let sent = sent.clone();
+            let error = error.clone();
+            let loading = loading.clone();
+            spawn_local(async move {
+                match RustyGPTClient::shared().forgot_password(&request).await {
+                    Ok(()) => sent.set(true),
+                    Err(_) => error.set(Some("Unable to send the reset link".to_string())),
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    if *sent {
+        return card(
+            "Check your email",
+            html! {
+                <p>{"If an account exists for that address, we have sent a link to reset its password."}</p>
+            },
+        );
+    }
+
+    let is_busy = *loading;
+    card(
+        "Forgot password",
+        html! {
+            <form onsubmit={onsubmit}>
+                {error_alert((*error).as_ref())}
+                <p>{"Enter your account's email address and we will send you a reset link."}</p>
+                <div class="form-control">
+                    <label class="label" for="email">
+                        <span class="label-text">{"Email"}</span>
+                    </label>
+                    <input
+                        id="email"
+                        class="input input-bordered"
+                        type="email"
+                        required=true
+                        value={(*email).clone()}
+                        oninput={input_setter(email.clone())}
+                    />
+                </div>
+                <div class="form-control mt-6">
+                    <button class="btn btn-primary" type="submit" disabled={(*email).trim().is_empty() || is_busy}>
+                        {if is_busy { "Sending..." } else { "Send reset link" }}
+                    </button>
+                </div>
+            </form>
+        },
+    )
+}
+
+/// Sets a new password with the token from `?token=` in the reset link.
+#[function_component(ResetPasswordPage)]
+pub fn reset_password_page() -> Html {
+    let token = use_memo((), |()| query_param("token"));
+    let password = use_state(String::new);
+    let confirm = use_state(String::new);
+    let done = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    let loading = use_state(|| false);
+
+    let onsubmit = {
+        let token = token.clone();
+        let password = password.clone();
+        let confirm = confirm.clone();
+        let done = done.clone();
+        let error = error.clone();
+        let loading = loading.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            let Some(token) = (*token).clone() else {
+                return;
+            };
+            if password.chars().count() < MIN_PASSWORD_LENGTH {
+                error.set(Some(format!(
+                    "Password must be at least {MIN_PASSWORD_LENGTH} characters"
+                )));
+                return;
+            }
+            if *password != *confirm {
+                error.set(Some("Passwords do not match".to_string()));
+                return;
+            }
+            let request = ResetPasswordRequest {
+                token,
+                new_password: (*password).clone(),
+            };
+            loading.set(true);
+            error.set(None);
+            let done = done.clone();
+            let error = error.clone();
+            let loading = loading.clone();
+            spawn_local(async move {
+                match RustyGPTClient::shared().reset_password(&request).await {
+                    Ok(()) => done.set(true),
+                    Err(err) => error.set(Some(match err.status() {
+                        Some(StatusCode::BAD_REQUEST) => {
+                            "This reset link is invalid or has expired".to_string()
+                        }
+                        Some(status) => format!("Password reset failed: {status}"),
+                        None => "Unable to connect to server".to_string(),
+                    })),
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    if token.is_none() {
+        return card(
+            "Reset password",
+            html! { <p>{"This reset link is incomplete. Request a new one from the sign-in page."}</p> },
+        );
+    }
+    if *done {
+        return card(
+            "Password updated",
+            html! { <p>{"Your password has been changed and all sessions were signed out."}</p> },
+        );
+    }
+
+    let is_busy = *loading;
+    card(
+        "Choose a new password",
+        html! {
+            <form onsubmit={onsubmit}>
+                {error_alert((*error).as_ref())}
+                <div class="form-control">
+                    <label class="label" for="new-password">
+                        <span class="label-text">{"New password"}</span>
+                    </label>
+                    <input
+                        id="new-password"
+                        class="input input-bordered"
+                        type="password"
+                        autocomplete="new-password"
+                        required=true
+                        value={(*password).clone()}
+                        oninput={input_setter(password.clone())}
+                    />
+                </div>
+                <div class="form-control">
+                    <label class="label" for="confirm-password">
+                        <span class="label-text">{"Confirm password"}</span>
+                    </label>
+                    <input
+                        id="confirm-password"
+                        class="input input-bordered"
+                        type="password"
+                        autocomplete="new-password"
+                        required=true
+                        value={(*confirm).clone()}
+                        oninput={input_setter(confirm.clone())}
+                    />
+                </div>
+                <div class="form-control mt-6">
+                    <button class="btn btn-primary" type="submit" disabled={(*password).is_empty() || is_busy}>
+                        {if is_busy { "Saving..." } else { "Set password" }}
+                    </button>
+                </div>
+            </form>
+        },
+    )
+}
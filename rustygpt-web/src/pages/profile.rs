@@ -1,6 +1,10 @@
+use crate::api::RustyGPTClient;
 use i18nrs::yew::use_translation;
+use shared::models::ActiveSession;
+use uuid::Uuid;
 use wasm_bindgen::prelude::*;
-use yew::{Html, function_component, html};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
 
 #[wasm_bindgen]
 extern "C" {
@@ -8,6 +12,137 @@ extern "C" {
     fn log(s: &str);
 }
 
+fn load_sessions(
+    sessions: UseStateHandle<Option<Vec<ActiveSession>>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match RustyGPTClient::shared().list_sessions().await {
+            Ok(response) => sessions.set(Some(response.sessions)),
+            Err(err) => error.set(Some(format!("Unable to load sessions: {err}"))),
+        }
+    });
+}
+
+/// Signed-in devices, with sign-out for each one and for all but the current one.
+#[function_component(SessionsPanel)]
+fn sessions_panel() -> Html {
+    let sessions = use_state(|| None::<Vec<ActiveSession>>);
+    let error = use_state(|| None::<String>);
+    let notice = use_state(|| None::<String>);
+
+    {
+        let sessions = sessions.clone();
+        let error = error.clone();
+        use_effect_with((), move |()| {
+            load_sessions(sessions, error);
+            || ()
+        });
+    }
+
+    let on_revoke = {
+        let sessions = sessions.clone();
+        let error = error.clone();
+        Callback::from(move |session_id: Uuid| {
+            let sessions = sessions.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match RustyGPTClient::shared().revoke_session(session_id).await {
+                    Ok(()) => load_sessions(sessions, error),
+                    Err(err) => error.set(Some(format!("Unable to sign out session: {err}"))),
+                }
+            });
+        })
+    };
+
+    let on_revoke_others = {
+        let sessions = sessions.clone();
+        let error = error.clone();
+        let notice = notice.clone();
+        Callback::from(move |_: MouseEvent| {
+            let sessions = sessions.clone();
+            let error = error.clone();
+            let notice = notice.clone();
+            spawn_local(async move {
+                match RustyGPTClient::shared().revoke_other_sessions().await {
+                    Ok(revoked) => {
+                        notice.set(Some(format!("Signed out {revoked} other session(s)")));
+                        load_sessions(sessions, error);
+                    }
+                    Err(err) => error.set(Some(format!("Unable to sign out sessions: {err}"))),
+                }
+            });
+        })
+    };
+
+    let rows = (*sessions).as_ref().map_or_else(
+        || html! { <p>{ "Loading sessions..." }</p> },
+        |list| {
+            html! {
+                <table class="table">
+                    <thead>
+                        <tr>
+                            <th>{ "Device" }</th>
+                            <th>{ "IP address" }</th>
+                            <th>{ "Signed in" }</th>
+                            <th>{ "Last active" }</th>
+                            <th></th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for list.iter().map(|session| {
+                            let id = session.id;
+                            let on_revoke = on_revoke.clone();
+                            html! {
+                                <tr key={id.to_string()}>
+                                    <td title={session.user_agent.clone().unwrap_or_default()}>
+                                        { session.device.clone() }
+                                        if session.current {
+                                            <span class="badge badge-primary ml-2">{ "This device" }</span>
+                                        }
+                                    </td>
+                                    <td>{ session.ip.clone().unwrap_or_default() }</td>
+                                    <td>{ session.created_at.clone() }</td>
+                                    <td>{ session.last_seen_at.clone() }</td>
+                                    <td>
+                                        if !session.current {
+                                            <button
+                                                class="btn btn-sm btn-ghost"
+                                                onclick={Callback::from(move |_| on_revoke.emit(id))}
+                                            >
+                                                { "Sign out" }
+                                            </button>
+                                        }
+                                    </td>
+                                </tr>
+                            }
+                        }) }
+                    </tbody>
+                </table>
+            }
+        },
+    );
+    let has_others = (*sessions)
+        .as_ref()
+        .is_some_and(|list| list.iter().any(|session| !session.current));
+
+    html! {
+        <section class="space-y-4">
+            <h2 class="text-xl font-semibold">{ "Sessions" }</h2>
+            if let Some(message) = &*error {
+                <div class="alert alert-error"><span>{ message.clone() }</span></div>
+            }
+            if let Some(message) = &*notice {
+                <div class="alert alert-success"><span>{ message.clone() }</span></div>
+            }
+            { rows }
+            <button class="btn btn-outline" onclick={on_revoke_others} disabled={!has_others}>
+                { "Sign out everywhere else" }
+            </button>
+        </section>
+    }
+}
+
 /// `ProfilePage` page component
 #[function_component(ProfilePage)]
 pub fn profile_page() -> Html {
@@ -15,9 +150,8 @@ pub fn profile_page() -> Html {
 
     html! {
         <div class="p-4 space-y-6">
-            <h1 class="text-2xl font-bold">{ "Profile Page" }</h1>
-            <p>{ "This is the profile page." }</p>
-            <p>{ "You can adjust your profile here." }</p>
+            <h1 class="text-2xl font-bold">{ "Profile" }</h1>
+            <SessionsPanel />
         </div>
     }
 }
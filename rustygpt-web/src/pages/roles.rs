@@ -1,6 +1,11 @@
+use crate::api::RustyGPTClient;
 use i18nrs::yew::use_translation;
+use shared::models::{RoleSummary, UserAccount, UserAccountQuery, UserRole};
+use uuid::Uuid;
 use wasm_bindgen::prelude::*;
-use yew::{Html, function_component, html};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
 
 #[wasm_bindgen]
 extern "C" {
@@ -8,16 +13,294 @@ extern "C" {
     fn log(s: &str);
 }
 
-/// `RolesPage` page component
+const MEMBER_PAGE_SIZE: u32 = 200;
+const CANDIDATE_LIMIT: u32 = 10;
+
+fn load_roles(
+    roles: UseStateHandle<Option<Vec<RoleSummary>>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match RustyGPTClient::shared().admin_list_roles().await {
+            Ok(list) => roles.set(Some(list)),
+            Err(err) => error.set(Some(format!("Unable to load roles: {err}"))),
+        }
+    });
+}
+
+fn load_members(
+    role: UserRole,
+    members: UseStateHandle<Option<Vec<UserAccount>>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    let query = UserAccountQuery {
+        role: Some(role),
+        limit: Some(MEMBER_PAGE_SIZE),
+        ..UserAccountQuery::default()
+    };
+    spawn_local(async move {
+        match RustyGPTClient::shared().admin_list_users(&query).await {
+            Ok(page) => members.set(Some(page.users)),
+            Err(err) => error.set(Some(format!("Unable to load {role} holders: {err}"))),
+        }
+    });
+}
+
+#[derive(Properties, PartialEq)]
+struct RoleMembersProps {
+    role: UserRole,
+    on_changed: Callback<String>,
+    on_error: Callback<String>,
+}
+
+/// Holders of one role with revoke buttons, and a search for accounts to grant it to.
+#[function_component(RoleMembers)]
+fn role_members(props: &RoleMembersProps) -> Html {
+    let role = props.role;
+    let members = use_state(|| None::<Vec<UserAccount>>);
+    let search = use_state(String::new);
+    let candidates = use_state(Vec::<UserAccount>::new);
+    let error = use_state(|| None::<String>);
+    let reload = use_state(|| 0_u32);
+
+    {
+        let members = members.clone();
+        let error = error.clone();
+        use_effect_with((role, *reload), move |(role, _)| {
+            members.set(None);
+            load_members(*role, members, error);
+            || ()
+        });
+    }
+
+    {
+        let candidates = candidates.clone();
+        use_effect_with(
+            ((*search).clone(), role, *reload),
+            move |(term, role, _)| {
+                let term = term.trim().to_string();
+                if term.is_empty() {
+                    candidates.set(Vec::new());
+                } else {
+                    let role = *role;
+                    let query = UserAccountQuery {
+                        search: Some(term),
+                        limit: Some(CANDIDATE_LIMIT),
+                        ..UserAccountQuery::default()
+                    };
+                    spawn_local(async move {
+                        if let Ok(page) = RustyGPTClient::shared().admin_list_users(&query).await {
+                            candidates.set(
+                                page.users
+                                    .into_iter()
+                                    .filter(|user| !user.has_role(role))
+                                    .collect(),
+                            );
+                        }
+                    });
+                }
+                || ()
+            },
+        );
+    }
+
+    let set_role = {
+        let reload = reload.clone();
+        let on_changed = props.on_changed.clone();
+        let on_error = props.on_error.clone();
+        Callback::from(move |(user_id, username, granted): (Uuid, String, bool)| {
+            let reload = reload.clone();
+            let on_changed = on_changed.clone();
+            let on_error = on_error.clone();
+            spawn_local(async move {
+                match RustyGPTClient::shared()
+                    .admin_set_user_role(user_id, role, granted)
+                    .await
+                {
+                    Ok(_) => {
+                        reload.set(*reload + 1);
+                        on_changed.emit(if granted {
+                            format!("Granted {role} to {username}")
+                        } else {
+                            format!("Revoked {role} from {username}")
+                        });
+                    }
+                    Err(err) => on_error.emit(format!("Unable to update {username}: {err}")),
+                }
+            });
+        })
+    };
+
+    let on_search = {
+        let search = search.clone();
+        Callback::from(move |event: InputEvent| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                search.set(input.value());
+            }
+        })
+    };
+
+    let account_button = |user: &UserAccount, granted: bool| {
+        let set_role = set_role.clone();
+        let id = user.id;
+        let username = user.username.clone();
+        html! {
+            <button
+                class={classes!("btn", "btn-xs", if granted { "btn-primary" } else { "btn-ghost" })}
+                onclick={Callback::from(move |_| set_role.emit((id, username.clone(), granted)))}
+            >
+                { if granted { "Grant" } else { "Revoke" } }
+            </button>
+        }
+    };
+
+    let list = (*members).as_ref().map_or_else(
+        || html! { <p>{ "Loading holders..." }</p> },
+        |list| {
+            if list.is_empty() {
+                return html! { <p class="opacity-70">{ "No accounts hold this role." }</p> };
+            }
+            html! {
+                <ul class="divide-y">
+                    { for list.iter().map(|user| html! {
+                        <li key={user.id.to_string()} class="flex items-center justify-between py-1">
+                            <span>
+                                { user.username.clone() }
+                                <span class="text-sm opacity-70 ml-2">{ user.email.clone() }</span>
+                                if user.disabled_at.is_some() {
+                                    <span class="badge badge-error ml-2">{ "Disabled" }</span>
+                                }
+                            </span>
+                            { account_button(user, false) }
+                        </li>
+                    }) }
+                </ul>
+            }
+        },
+    );
+
+    html! {
+        <section class="space-y-3">
+            <h2 class="text-xl font-semibold">{ format!("Accounts with {role}") }</h2>
+            if let Some(message) = &*error {
+                <div class="alert alert-error"><span>{ message.clone() }</span></div>
+            }
+            { list }
+            <div class="space-y-2">
+                <input class="input input-bordered w-full max-w-md" type="search"
+                    placeholder="Find an account to grant this role"
+                    value={(*search).clone()} oninput={on_search} />
+                <ul class="divide-y max-w-md">
+                    { for candidates.iter().map(|user| html! {
+                        <li key={user.id.to_string()} class="flex items-center justify-between py-1">
+                            <span>
+                                { user.username.clone() }
+                                <span class="text-sm opacity-70 ml-2">{ user.email.clone() }</span>
+                            </span>
+                            { account_button(user, true) }
+                        </li>
+                    }) }
+                </ul>
+            </div>
+        </section>
+    }
+}
+
+/// Global roles with holder counts and two-factor requirements; selecting one lists its holders.
 #[function_component(RolesPage)]
 pub fn roles_page() -> Html {
     let (_i18n, _) = use_translation();
+    let roles = use_state(|| None::<Vec<RoleSummary>>);
+    let selected = use_state(|| None::<UserRole>);
+    let error = use_state(|| None::<String>);
+    let notice = use_state(|| None::<String>);
+
+    {
+        let roles = roles.clone();
+        let error = error.clone();
+        use_effect_with((), move |()| {
+            load_roles(roles, error);
+            || ()
+        });
+    }
+
+    let on_changed = {
+        let roles = roles.clone();
+        let error = error.clone();
+        let notice = notice.clone();
+        Callback::from(move |message: String| {
+            error.set(None);
+            notice.set(Some(message));
+            load_roles(roles.clone(), error.clone());
+        })
+    };
+    let on_error = {
+        let error = error.clone();
+        Callback::from(move |message: String| error.set(Some(message)))
+    };
+
+    let table = (*roles).as_ref().map_or_else(
+        || html! { <p>{ "Loading roles..." }</p> },
+        |list| {
+            html! {
+                <table class="table">
+                    <thead>
+                        <tr>
+                            <th>{ "Role" }</th>
+                            <th>{ "Accounts" }</th>
+                            <th>{ "Two-factor" }</th>
+                            <th></th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for list.iter().map(|summary| {
+                            let role = summary.role;
+                            let selected = selected.clone();
+                            let active = *selected == Some(role);
+                            html! {
+                                <tr key={role.as_str()} class={classes!(active.then_some("bg-base-200"))}>
+                                    <td class="font-medium">{ role.as_str() }</td>
+                                    <td>{ summary.user_count }</td>
+                                    <td>
+                                        if summary.mfa_required {
+                                            <span class="badge badge-warning">{ "Required" }</span>
+                                        } else {
+                                            <span class="badge badge-ghost">{ "Optional" }</span>
+                                        }
+                                    </td>
+                                    <td>
+                                        <button
+                                            class="btn btn-xs"
+                                            onclick={Callback::from(move |_| selected.set(Some(role)))}
+                                        >
+                                            { "Manage" }
+                                        </button>
+                                    </td>
+                                </tr>
+                            }
+                        }) }
+                    </tbody>
+                </table>
+            }
+        },
+    );
 
     html! {
         <div class="p-4 space-y-6">
-            <h1 class="text-2xl font-bold">{ "User Roles Page" }</h1>
-            <p>{ "This is the user roles page." }</p>
-            <p>{ "You can edit user roles and permissions here." }</p>
+            <h1 class="text-2xl font-bold">{ "Roles" }</h1>
+            if let Some(message) = &*error {
+                <div class="alert alert-error"><span>{ message.clone() }</span></div>
+            }
+            if let Some(message) = &*notice {
+                <div class="alert alert-success"><span>{ message.clone() }</span></div>
+            }
+            { table }
+            <p class="text-sm opacity-70">
+                { "Role changes take effect on the account's next request. Two-factor requirements are set under the MFA policy." }
+            </p>
+            if let Some(role) = *selected {
+                <RoleMembers {role} {on_changed} {on_error} />
+            }
         </div>
     }
 }
@@ -1,6 +1,12 @@
+use crate::api::RustyGPTClient;
+use crate::components::theme_switcher::apply_theme;
+use crate::language;
 use i18nrs::yew::use_translation;
+use shared::models::{MAX_SYSTEM_PROMPT_CHARS, ThemePreference, UserPreferences};
 use wasm_bindgen::prelude::*;
-use yew::{Html, function_component, html};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
 
 #[wasm_bindgen]
 extern "C" {
@@ -8,16 +14,284 @@ extern "C" {
     fn log(s: &str);
 }
 
-/// `SettingsPage` page component
+const THEMES: [ThemePreference; 3] = [
+    ThemePreference::System,
+    ThemePreference::Light,
+    ThemePreference::Dark,
+];
+
+/// Applies the display preferences the browser is responsible for: theme and, when it is one
+/// the interface ships, language.
+pub fn apply_display_preferences(preferences: &UserPreferences, set_language: &Callback<String>) {
+    apply_theme(preferences.theme);
+    if let Some(code) = preferences
+        .language
+        .as_deref()
+        .filter(|code| language::get_language_info(code).is_some())
+    {
+        set_language.emit(code.to_string());
+    }
+}
+
+fn parse_temperature(value: &str) -> Result<Option<f32>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse::<f32>()
+        .map(Some)
+        .map_err(|_| format!("Temperature must be a number, not `{value}`"))
+}
+
+/// Edits one field of the loaded preferences.
+fn editor<E: 'static>(
+    draft: &UseStateHandle<Option<UserPreferences>>,
+    read: impl Fn(&E) -> Option<String> + 'static,
+    write: impl Fn(&mut UserPreferences, String) + 'static,
+) -> Callback<E> {
+    let draft = draft.clone();
+    Callback::from(move |event: E| {
+        if let (Some(value), Some(current)) = (read(&event), (*draft).clone()) {
+            let mut next = current;
+            write(&mut next, value);
+            draft.set(Some(next));
+        }
+    })
+}
+
+fn input_value(event: &InputEvent) -> Option<String> {
+    event
+        .target_dyn_into::<HtmlInputElement>()
+        .map(|input| input.value())
+}
+
+fn checkbox_value(event: &Event) -> Option<String> {
+    event
+        .target_dyn_into::<HtmlInputElement>()
+        .map(|input| input.checked().to_string())
+}
+
+fn select_value(event: &Event) -> Option<String> {
+    event
+        .target_dyn_into::<HtmlSelectElement>()
+        .map(|select| select.value())
+}
+
+fn optional(value: String) -> Option<String> {
+    Some(value).filter(|value| !value.trim().is_empty())
+}
+
+/// Account preferences saved on the server: generation defaults applied to new replies, and
+/// display settings every signed-in browser picks up.
 #[function_component(SettingsPage)]
 pub fn settings_page() -> Html {
-    let (_i18n, _) = use_translation();
+    let (_i18n, set_language) = use_translation();
+    let draft = use_state(|| None::<UserPreferences>);
+    let temperature = use_state(String::new);
+    let error = use_state(|| None::<String>);
+    let notice = use_state(|| None::<String>);
+    let busy = use_state(|| false);
+
+    {
+        let draft = draft.clone();
+        let temperature = temperature.clone();
+        let error = error.clone();
+        use_effect_with((), move |()| {
+            spawn_local(async move {
+                match RustyGPTClient::shared().get_preferences().await {
+                    Ok(preferences) => {
+                        temperature.set(
+                            preferences
+                                .temperature
+                                .map(|value| value.to_string())
+                                .unwrap_or_default(),
+                        );
+                        draft.set(Some(preferences));
+                    }
+                    Err(err) => error.set(Some(format!("Unable to load preferences: {err}"))),
+                }
+            });
+            || ()
+        });
+    }
+
+    let onsubmit = {
+        let draft = draft.clone();
+        let temperature = temperature.clone();
+        let error = error.clone();
+        let notice = notice.clone();
+        let busy = busy.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            let Some(mut preferences) = (*draft).clone() else {
+                return;
+            };
+            match parse_temperature(&temperature) {
+                Ok(value) => preferences.temperature = value,
+                Err(message) => {
+                    error.set(Some(message));
+                    return;
+                }
+            }
+            busy.set(true);
+            error.set(None);
+            notice.set(None);
+            let draft = draft.clone();
+            let error = error.clone();
+            let notice = notice.clone();
+            let busy = busy.clone();
+            let set_language = set_language.clone();
+            spawn_local(async move {
+                match RustyGPTClient::shared()
+                    .update_preferences(&preferences)
+                    .await
+                {
+                    Ok(saved) => {
+                        apply_display_preferences(&saved, &set_language);
+                        draft.set(Some(saved));
+                        notice.set(Some("Preferences saved".to_string()));
+                    }
+                    Err(err) => error.set(Some(format!("Unable to save preferences: {err}"))),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let on_temperature = {
+        let temperature = temperature.clone();
+        Callback::from(move |event: InputEvent| {
+            if let Some(value) = input_value(&event) {
+                temperature.set(value);
+            }
+        })
+    };
+
+    let form = (*draft).as_ref().map_or_else(
+        || html! { <p>{ "Loading preferences..." }</p> },
+        |preferences| {
+            let mut languages: Vec<_> = language::supported_languages().into_values().collect();
+            languages.sort_by(|a, b| a.native_name.cmp(b.native_name));
+            let prompt_chars = preferences
+                .system_prompt
+                .as_deref()
+                .map_or(0, |prompt| prompt.chars().count());
+
+            html! {
+                <form class="space-y-6 max-w-2xl" onsubmit={onsubmit.clone()}>
+                    <section class="card bg-base-100 shadow">
+                        <div class="card-body space-y-3">
+                            <h2 class="card-title">{ "Replies" }</h2>
+                            <p class="text-sm opacity-70">
+                                { "Used for new assistant replies unless a request chooses otherwise. Leave a field empty to use the server default." }
+                            </p>
+                            <label class="form-control">
+                                <span class="label-text">{ "Default model" }</span>
+                                <input class="input input-bordered"
+                                    value={preferences.default_model.clone().unwrap_or_default()}
+                                    oninput={editor(&draft, input_value, |prefs, value| prefs.default_model = optional(value))} />
+                            </label>
+                            <label class="form-control">
+                                <span class="label-text">{ "Temperature (0.0 to 2.0)" }</span>
+                                <input class="input input-bordered w-32" type="number" min="0" max="2" step="0.1"
+                                    value={(*temperature).clone()} oninput={on_temperature.clone()} />
+                            </label>
+                            <label class="form-control">
+                                <span class="label-text">{ "System prompt" }</span>
+                                <textarea class="textarea textarea-bordered h-32"
+                                    maxlength={MAX_SYSTEM_PROMPT_CHARS.to_string()}
+                                    value={preferences.system_prompt.clone().unwrap_or_default()}
+                                    oninput={editor(&draft, |event: &InputEvent| {
+                                        event.target_dyn_into::<HtmlTextAreaElement>().map(|area| area.value())
+                                    }, |prefs, value| prefs.system_prompt = optional(value))} />
+                                <span class="label-text-alt opacity-70">
+                                    { format!("{prompt_chars} / {MAX_SYSTEM_PROMPT_CHARS}") }
+                                </span>
+                            </label>
+                            <label class="label cursor-pointer justify-start gap-3">
+                                <input class="checkbox" type="checkbox" checked={preferences.show_reasoning}
+                                    onchange={editor(&draft, checkbox_value, |prefs, value| prefs.show_reasoning = value == "true")} />
+                                <span class="label-text">{ "Show the assistant's reasoning when a model provides it" }</span>
+                            </label>
+                        </div>
+                    </section>
+
+                    <section class="card bg-base-100 shadow">
+                        <div class="card-body space-y-3">
+                            <h2 class="card-title">{ "Display" }</h2>
+                            <label class="form-control">
+                                <span class="label-text">{ "Language" }</span>
+                                <select class="select select-bordered"
+                                    onchange={editor(&draft, select_value, |prefs, value| prefs.language = optional(value))}>
+                                    <option value="" selected={preferences.language.is_none()}>{ "Browser default" }</option>
+                                    { for languages.iter().map(|info| html! {
+                                        <option value={info.code} selected={preferences.language.as_deref() == Some(info.code)}>
+                                            { format!("{} {}", info.flag, info.native_name) }
+                                        </option>
+                                    }) }
+                                </select>
+                            </label>
+                            <label class="form-control">
+                                <span class="label-text">{ "Theme" }</span>
+                                <select class="select select-bordered"
+                                    onchange={editor(&draft, select_value, |prefs, value| {
+                                        if let Ok(theme) = value.parse() {
+                                            prefs.theme = theme;
+                                        }
+                                    })}>
+                                    { for THEMES.iter().map(|theme| html! {
+                                        <option value={theme.as_str()} selected={*theme == preferences.theme}>
+                                            { theme.as_str() }
+                                        </option>
+                                    }) }
+                                </select>
+                            </label>
+                        </div>
+                    </section>
+
+                    <section class="card bg-base-100 shadow">
+                        <div class="card-body space-y-3">
+                            <h2 class="card-title">{ "Notifications" }</h2>
+                            <label class="label cursor-pointer justify-start gap-3">
+                                <input class="checkbox" type="checkbox" checked={preferences.notifications.email}
+                                    onchange={editor(&draft, checkbox_value, |prefs, value| prefs.notifications.email = value == "true")} />
+                                <span class="label-text">{ "Email, such as conversation invites" }</span>
+                            </label>
+                            <label class="label cursor-pointer justify-start gap-3">
+                                <input class="checkbox" type="checkbox" checked={preferences.notifications.browser}
+                                    onchange={editor(&draft, checkbox_value, |prefs, value| prefs.notifications.browser = value == "true")} />
+                                <span class="label-text">{ "Desktop notification when a reply finishes in the background" }</span>
+                            </label>
+                            <label class="label cursor-pointer justify-start gap-3">
+                                <input class="checkbox" type="checkbox" checked={preferences.notifications.sound}
+                                    onchange={editor(&draft, checkbox_value, |prefs, value| prefs.notifications.sound = value == "true")} />
+                                <span class="label-text">{ "Play a sound when a reply finishes" }</span>
+                            </label>
+                            <p class="text-sm opacity-70">
+                                { "Security emails, such as password resets, are always sent." }
+                            </p>
+                        </div>
+                    </section>
+
+                    <button class="btn btn-primary" type="submit" disabled={*busy}>
+                        { if *busy { "Saving..." } else { "Save preferences" } }
+                    </button>
+                </form>
+            }
+        },
+    );
 
     html! {
         <div class="p-4 space-y-6">
-            <h1 class="text-2xl font-bold">{ "Settings Page" }</h1>
-            <p>{ "This is the settings page." }</p>
-            <p>{ "You can adjust system settings here." }</p>
+            <h1 class="text-2xl font-bold">{ "Settings" }</h1>
+            if let Some(message) = &*error {
+                <div class="alert alert-error"><span>{ message.clone() }</span></div>
+            }
+            if let Some(message) = &*notice {
+                <div class="alert alert-success"><span>{ message.clone() }</span></div>
+            }
+            { form }
         </div>
     }
 }
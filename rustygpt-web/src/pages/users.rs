@@ -1,6 +1,13 @@
+use crate::api::RustyGPTClient;
 use i18nrs::yew::use_translation;
+use shared::models::{
+    AdminPasswordResetRequest, CreateUserAccountRequest, UserAccount, UserAccountPage,
+    UserAccountQuery, UserRole,
+};
 use wasm_bindgen::prelude::*;
-use yew::{Html, function_component, html};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
 
 #[wasm_bindgen]
 extern "C" {
@@ -8,16 +15,426 @@ extern "C" {
     fn log(s: &str);
 }
 
-/// `UserPage` page component
+const PAGE_SIZE: u32 = 50;
+const ROLES: [UserRole; 3] = [UserRole::Admin, UserRole::Member, UserRole::ReadOnly];
+
+/// Something an administrator can do to one account from the table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum UserAction {
+    SetDisabled(bool),
+    SetRole(UserRole, bool),
+    Unlock,
+    SendPasswordReset,
+}
+
+fn input_setter(handle: UseStateHandle<String>) -> Callback<InputEvent> {
+    Callback::from(move |event: InputEvent| {
+        if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+            handle.set(input.value());
+        }
+    })
+}
+
+fn load_users(
+    query: UserAccountQuery,
+    page: UseStateHandle<Option<UserAccountPage>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match RustyGPTClient::shared().admin_list_users(&query).await {
+            Ok(response) => page.set(Some(response)),
+            Err(err) => error.set(Some(format!("Unable to load users: {err}"))),
+        }
+    });
+}
+
+async fn apply_action(user: &UserAccount, action: UserAction) -> Result<String, reqwest::Error> {
+    let client = RustyGPTClient::shared();
+    match action {
+        UserAction::SetDisabled(disabled) => {
+            client.admin_set_user_disabled(user.id, disabled).await?;
+            Ok(format!(
+                "{} {}",
+                if disabled { "Disabled" } else { "Enabled" },
+                user.username
+            ))
+        }
+        UserAction::SetRole(role, granted) => {
+            client.admin_set_user_role(user.id, role, granted).await?;
+            Ok(if granted {
+                format!("Granted {role} to {}", user.username)
+            } else {
+                format!("Revoked {role} from {}", user.username)
+            })
+        }
+        UserAction::Unlock => {
+            client.admin_unlock_user(user.id).await?;
+            Ok(format!("Unlocked {}", user.username))
+        }
+        UserAction::SendPasswordReset => {
+            client
+                .admin_reset_password(user.id, &AdminPasswordResetRequest::default())
+                .await?;
+            Ok(format!("Sent a password reset link to {}", user.email))
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct CreateUserFormProps {
+    on_created: Callback<UserAccount>,
+}
+
+/// New account form. Leaving the password empty emails the user a link to choose one.
+#[function_component(CreateUserForm)]
+fn create_user_form(props: &CreateUserFormProps) -> Html {
+    let username = use_state(String::new);
+    let email = use_state(String::new);
+    let display_name = use_state(String::new);
+    let password = use_state(String::new);
+    let role = use_state(|| UserRole::Member);
+    let error = use_state(|| None::<String>);
+    let busy = use_state(|| false);
+
+    let on_role = {
+        let role = role.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>()
+                && let Ok(value) = select.value().parse()
+            {
+                role.set(value);
+            }
+        })
+    };
+
+    let onsubmit = {
+        let username = username.clone();
+        let email = email.clone();
+        let display_name = display_name.clone();
+        let password = password.clone();
+        let role = role.clone();
+        let error = error.clone();
+        let busy = busy.clone();
+        let on_created = props.on_created.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            let request = CreateUserAccountRequest {
+                username: username.trim().to_string(),
+                email: email.trim().to_string(),
+                display_name: Some(display_name.trim().to_string()).filter(|name| !name.is_empty()),
+                password: Some((*password).clone()).filter(|value| !value.is_empty()),
+                roles: vec![*role],
+            };
+            busy.set(true);
+            error.set(None);
+            let fields = [
+                username.clone(),
+                email.clone(),
+                display_name.clone(),
+                password.clone(),
+            ];
+            let error = error.clone();
+            let busy = busy.clone();
+            let on_created = on_created.clone();
+            spawn_local(async move {
+                match RustyGPTClient::shared().admin_create_user(&request).await {
+                    Ok(user) => {
+                        for field in fields {
+                            field.set(String::new());
+                        }
+                        on_created.emit(user);
+                    }
+                    Err(err) => error.set(Some(format!("Unable to create user: {err}"))),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let ready = !username.trim().is_empty() && !email.trim().is_empty() && !*busy;
+
+    html! {
+        <form class="card bg-base-100 shadow" onsubmit={onsubmit}>
+            <div class="card-body space-y-2">
+                <h2 class="card-title">{ "Create user" }</h2>
+                if let Some(message) = &*error {
+                    <div class="alert alert-error"><span>{ message.clone() }</span></div>
+                }
+                <div class="grid gap-2 md:grid-cols-2">
+                    <input class="input input-bordered" placeholder="Username" required=true
+                        value={(*username).clone()} oninput={input_setter(username.clone())} />
+                    <input class="input input-bordered" type="email" placeholder="Email" required=true
+                        value={(*email).clone()} oninput={input_setter(email.clone())} />
+                    <input class="input input-bordered" placeholder="Display name (optional)"
+                        value={(*display_name).clone()} oninput={input_setter(display_name.clone())} />
+                    <input class="input input-bordered" type="password"
+                        placeholder="Password (leave empty to email a link)"
+                        value={(*password).clone()} oninput={input_setter(password.clone())} />
+                    <select class="select select-bordered" onchange={on_role}>
+                        { for ROLES.iter().map(|option| html! {
+                            <option value={option.as_str()} selected={*option == *role}>
+                                { option.as_str() }
+                            </option>
+                        }) }
+                    </select>
+                </div>
+                <div class="card-actions justify-end">
+                    <button class="btn btn-primary" type="submit" disabled={!ready}>
+                        { if *busy { "Creating..." } else { "Create user" } }
+                    </button>
+                </div>
+            </div>
+        </form>
+    }
+}
+
+fn user_row(user: &UserAccount, on_action: &Callback<(UserAccount, UserAction)>) -> Html {
+    let action = |action: UserAction| {
+        let on_action = on_action.clone();
+        let user = user.clone();
+        Callback::from(move |_: MouseEvent| on_action.emit((user.clone(), action)))
+    };
+    let disabled = user.disabled_at.is_some();
+
+    html! {
+        <tr key={user.id.to_string()} class={classes!(disabled.then_some("opacity-60"))}>
+            <td>
+                <div class="font-medium">{ user.username.clone() }</div>
+                <div class="text-sm opacity-70">{ user.email.clone() }</div>
+                if let Some(name) = &user.display_name {
+                    <div class="text-sm">{ name.clone() }</div>
+                }
+            </td>
+            <td class="space-x-1">
+                { for ROLES.iter().map(|role| {
+                    let held = user.has_role(*role);
+                    html! {
+                        <button
+                            class={classes!("badge", "cursor-pointer", if held { "badge-primary" } else { "badge-ghost" })}
+                            title={if held { "Click to revoke" } else { "Click to grant" }}
+                            onclick={action(UserAction::SetRole(*role, !held))}
+                        >
+                            { role.as_str() }
+                        </button>
+                    }
+                }) }
+            </td>
+            <td class="space-x-1">
+                if disabled {
+                    <span class="badge badge-error">{ "Disabled" }</span>
+                } else {
+                    <span class="badge badge-success">{ "Active" }</span>
+                }
+                if user.locked_until.is_some() {
+                    <span class="badge badge-warning">{ "Locked" }</span>
+                }
+                if user.mfa_enabled {
+                    <span class="badge badge-outline">{ "2FA" }</span>
+                }
+                if user.email_verified_at.is_none() {
+                    <span class="badge badge-outline">{ "Unverified" }</span>
+                }
+            </td>
+            <td>{ user.created_at.clone() }</td>
+            <td>
+                if let Some(seen) = &user.last_seen_at {
+                    { seen.to_html() }
+                } else {
+                    { "Never" }
+                }
+            </td>
+            <td class="space-x-1 whitespace-nowrap">
+                <button class="btn btn-xs" onclick={action(UserAction::SetDisabled(!disabled))}>
+                    { if disabled { "Enable" } else { "Disable" } }
+                </button>
+                if user.locked_until.is_some() {
+                    <button class="btn btn-xs" onclick={action(UserAction::Unlock)}>{ "Unlock" }</button>
+                }
+                if !disabled {
+                    <button class="btn btn-xs btn-ghost" onclick={action(UserAction::SendPasswordReset)}>
+                        { "Send reset link" }
+                    </button>
+                }
+            </td>
+        </tr>
+    }
+}
+
+/// Searchable list of every account with disable/enable, role, unlock and password reset
+/// actions, plus a form for creating accounts.
 #[function_component(UsersPage)]
 pub fn users_page() -> Html {
     let (_i18n, _) = use_translation();
+    let search = use_state(String::new);
+    let role = use_state(|| None::<UserRole>);
+    let status = use_state(|| None::<bool>);
+    let offset = use_state(|| 0_u32);
+    let page = use_state(|| None::<UserAccountPage>);
+    let error = use_state(|| None::<String>);
+    let notice = use_state(|| None::<String>);
+    let reload = use_state(|| 0_u32);
+
+    let query = UserAccountQuery {
+        search: Some(search.trim().to_string()).filter(|value| !value.is_empty()),
+        role: *role,
+        disabled: *status,
+        limit: Some(PAGE_SIZE),
+        offset: Some(*offset),
+    };
+
+    {
+        let page = page.clone();
+        let error = error.clone();
+        use_effect_with((query.clone(), *reload), move |(query, _)| {
+            load_users(query.clone(), page, error);
+            || ()
+        });
+    }
+
+    let on_action = {
+        let error = error.clone();
+        let notice = notice.clone();
+        let reload = reload.clone();
+        Callback::from(move |(user, action): (UserAccount, UserAction)| {
+            let error = error.clone();
+            let notice = notice.clone();
+            let reload = reload.clone();
+            error.set(None);
+            spawn_local(async move {
+                match apply_action(&user, action).await {
+                    Ok(message) => {
+                        notice.set(Some(message));
+                        reload.set(*reload + 1);
+                    }
+                    Err(err) => {
+                        error.set(Some(format!("Unable to update {}: {err}", user.username)))
+                    }
+                }
+            });
+        })
+    };
+
+    let on_created = {
+        let notice = notice.clone();
+        let reload = reload.clone();
+        Callback::from(move |user: UserAccount| {
+            notice.set(Some(format!("Created {}", user.username)));
+            reload.set(*reload + 1);
+        })
+    };
+
+    let on_search = {
+        let offset = offset.clone();
+        let setter = input_setter(search.clone());
+        Callback::from(move |event: InputEvent| {
+            offset.set(0);
+            setter.emit(event);
+        })
+    };
+
+    let on_role_filter = {
+        let role = role.clone();
+        let offset = offset.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                role.set(select.value().parse().ok());
+                offset.set(0);
+            }
+        })
+    };
+
+    let on_status_filter = {
+        let status = status.clone();
+        let offset = offset.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                status.set(match select.value().as_str() {
+                    "active" => Some(false),
+                    "disabled" => Some(true),
+                    _ => None,
+                });
+                offset.set(0);
+            }
+        })
+    };
+
+    let total = (*page).as_ref().map_or(0, |page| page.total);
+    let first = if total == 0 {
+        0
+    } else {
+        u64::from(*offset) + 1
+    };
+    let last = (u64::from(*offset) + u64::from(PAGE_SIZE)).min(total);
+    let on_previous = {
+        let offset = offset.clone();
+        Callback::from(move |_: MouseEvent| offset.set(offset.saturating_sub(PAGE_SIZE)))
+    };
+    let on_next = {
+        let offset = offset.clone();
+        Callback::from(move |_: MouseEvent| offset.set(*offset + PAGE_SIZE))
+    };
+
+    let table = (*page).as_ref().map_or_else(
+        || html! { <p>{ "Loading users..." }</p> },
+        |page| {
+            html! {
+                <table class="table">
+                    <thead>
+                        <tr>
+                            <th>{ "User" }</th>
+                            <th>{ "Roles" }</th>
+                            <th>{ "Status" }</th>
+                            <th>{ "Created" }</th>
+                            <th>{ "Last active" }</th>
+                            <th></th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for page.users.iter().map(|user| user_row(user, &on_action)) }
+                    </tbody>
+                </table>
+            }
+        },
+    );
 
     html! {
         <div class="p-4 space-y-6">
-            <h1 class="text-2xl font-bold">{ "Users Page" }</h1>
-            <p>{ "This is the users page." }</p>
-            <p>{ "You can edit user accounts here." }</p>
+            <h1 class="text-2xl font-bold">{ "Users" }</h1>
+            if let Some(message) = &*error {
+                <div class="alert alert-error"><span>{ message.clone() }</span></div>
+            }
+            if let Some(message) = &*notice {
+                <div class="alert alert-success"><span>{ message.clone() }</span></div>
+            }
+            <div class="flex flex-wrap gap-2">
+                <input class="input input-bordered" type="search"
+                    placeholder="Search username, email or name"
+                    value={(*search).clone()} oninput={on_search} />
+                <select class="select select-bordered" onchange={on_role_filter}>
+                    <option value="" selected={role.is_none()}>{ "All roles" }</option>
+                    { for ROLES.iter().map(|option| html! {
+                        <option value={option.as_str()} selected={*role == Some(*option)}>
+                            { option.as_str() }
+                        </option>
+                    }) }
+                </select>
+                <select class="select select-bordered" onchange={on_status_filter}>
+                    <option value="" selected={status.is_none()}>{ "Any status" }</option>
+                    <option value="active" selected={*status == Some(false)}>{ "Active" }</option>
+                    <option value="disabled" selected={*status == Some(true)}>{ "Disabled" }</option>
+                </select>
+            </div>
+            { table }
+            <div class="flex items-center gap-2">
+                <button class="btn btn-sm" onclick={on_previous} disabled={*offset == 0}>
+                    { "Previous" }
+                </button>
+                <span class="text-sm">{ format!("{first}–{last} of {total}") }</span>
+                <button class="btn btn-sm" onclick={on_next} disabled={last >= total}>
+                    { "Next" }
+                </button>
+            </div>
+            <CreateUserForm {on_created} />
         </div>
     }
 }
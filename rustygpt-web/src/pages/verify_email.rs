@@ -0,0 +1,67 @@
+use crate::{api::RustyGPTClient, routes::query_param};
+use reqwest::StatusCode;
+use shared::models::VerifyEmailRequest;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq)]
+enum Status {
+    Pending,
+    Verified,
+    Failed(String),
+}
+
+/// Confirms the address from `?token=` in a verification link as soon as it is opened.
+#[function_component(VerifyEmailPage)]
+pub fn verify_email_page() -> Html {
+    let status = use_state(|| Status::Pending);
+
+    {
+        let status = status.clone();
+        use_effect_with((), move |()| {
+            match query_param("token") {
+                Some(token) => spawn_local(async move {
+                    let request = VerifyEmailRequest { token };
+                    status.set(
+                        match RustyGPTClient::shared().verify_email(&request).await {
+                            Ok(()) => Status::Verified,
+                            Err(err) => Status::Failed(match err.status() {
+                                Some(StatusCode::BAD_REQUEST) => {
+                                    "This verification link is invalid or has expired".to_string()
+                                }
+                                Some(code) => format!("Verification failed: {code}"),
+                                None => "Unable to connect to server".to_string(),
+                            }),
+                        },
+                    );
+                }),
+                None => status.set(Status::Failed(
+                    "This verification link is incomplete".to_string(),
+                )),
+            }
+            || ()
+        });
+    }
+
+    let body = match &*status {
+        Status::Pending => html! { <p>{"Verifying your email address..."}</p> },
+        Status::Verified => html! { <p>{"Your email address has been verified."}</p> },
+        Status::Failed(message) => html! {
+            <div class="alert alert-error">
+                <span>{message.clone()}</span>
+            </div>
+        },
+    };
+
+    html! {
+        <div class="flex items-center justify-center min-h-screen bg-base-200">
+            <div class="card w-full max-w-md shadow-lg bg-base-100">
+                <div class="card-body">
+                    <h2 class="card-title text-2xl">{"Email verification"}</h2>
+                    {body}
+                    <a class="link text-sm mt-4" href="/">{"Continue to RustyGPT"}</a>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -2,8 +2,8 @@ use crate::{
     containers::layout::Layout,
     models::app_state::AppState,
     pages::{
-        ChatPage, DashboardPage, ErrorPage, LoginPage, ProfilePage, RolesPage, SettingsPage,
-        UsersPage,
+        ChatPage, DashboardPage, ErrorPage, ForgotPasswordPage, InvitePage, LoginPage, ProfilePage,
+        ResetPasswordPage, RolesPage, SettingsPage, UsersPage, VerifyEmailPage,
     },
 };
 use shared::models::UserRole;
@@ -31,6 +31,14 @@ pub enum MainRoute {
     Chat,
     #[at("/chat/:conversation_id")]
     ChatConversation { conversation_id: String },
+    #[at("/forgot-password")]
+    ForgotPassword,
+    #[at("/reset-password")]
+    ResetPassword,
+    #[at("/verify-email")]
+    VerifyEmail,
+    #[at("/invite")]
+    Invite,
     #[at("/admin")]
     AdminRoot,
     #[at("/admin/*")]
@@ -81,6 +89,47 @@ impl From<MainRoute> for AppRoute {
     }
 }
 
+/// Reads a query string parameter from the current location, e.g. the token in an emailed link.
+pub fn query_param(name: &str) -> Option<String> {
+    let href = web_sys::window()?.location().href().ok()?;
+    url::Url::parse(&href)
+        .ok()?
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+        .filter(|value| !value.is_empty())
+}
+
+/// Reads and clears `#key=value` pairs that the server left in the fragment after a redirect,
+/// such as the outcome of a single sign-on callback.
+pub fn take_fragment_params() -> Vec<(String, String)> {
+    let Some(window) = web_sys::window() else {
+        return Vec::new();
+    };
+    let location = window.location();
+    let hash = location.hash().unwrap_or_default();
+    let Some(fragment) = hash
+        .strip_prefix('#')
+        .filter(|fragment| !fragment.is_empty())
+    else {
+        return Vec::new();
+    };
+    let params = url::form_urlencoded::parse(fragment.as_bytes())
+        .into_owned()
+        .collect();
+
+    // Keep challenge tokens out of the history and out of copied links.
+    let path = format!(
+        "{}{}",
+        location.pathname().unwrap_or_default(),
+        location.search().unwrap_or_default()
+    );
+    if let Ok(history) = window.history() {
+        let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&path));
+    }
+    params
+}
+
 #[derive(Properties, PartialEq)]
 pub struct MainRouteViewProps {
     pub route: MainRoute,
@@ -138,6 +187,20 @@ fn main_route_view(props: &MainRouteViewProps) -> Html {
                 </Layout>
             }
         }
+        MainRoute::ForgotPassword => html! { <ForgotPasswordPage /> },
+        MainRoute::ResetPassword => html! { <ResetPasswordPage /> },
+        MainRoute::VerifyEmail => html! { <VerifyEmailPage /> },
+        MainRoute::Invite => {
+            if !is_authenticated {
+                return html! { <LoginPage /> };
+            }
+            let logout = props.on_logout.clone();
+            html! {
+                <Layout current_route={AppRoute::Main(MainRoute::Chat)} on_logout={Some(logout)}>
+                    <InvitePage />
+                </Layout>
+            }
+        }
         MainRoute::AdminRoot | MainRoute::Admin => {
             if !is_authenticated {
                 return html! { <Redirect<MainRoute> to={MainRoute::Login} /> };
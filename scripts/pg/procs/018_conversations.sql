@@ -66,12 +66,7 @@ BEGIN
             MESSAGE = 'RGP.403: actor not a participant';
     END IF;
 
-    SELECT cp.role
-    INTO v_actor_role
-    FROM rustygpt.conversation_participants cp
-    WHERE cp.conversation_id = p_conv
-      AND cp.user_id = v_actor
-      AND cp.left_at IS NULL;
+    v_actor_role := rustygpt.sp_user_conversation_role(v_actor, p_conv);
 
     IF v_actor_role NOT IN ('owner', 'admin') THEN
         RAISE EXCEPTION USING
@@ -1,32 +1,60 @@
 -- Helper access control stored procedures
 SET search_path TO rustygpt, public;
 
-CREATE OR REPLACE FUNCTION rustygpt.sp_user_can_access(
+-- The highest role `p_user` holds on the conversation, directly or through a group; NULL
+-- without access. Organization conversations also require organization membership.
+-- PL/pgSQL so the group and organization tables, created later in the bootstrap, resolve at
+-- call time.
+CREATE OR REPLACE FUNCTION rustygpt.sp_user_conversation_role(
     p_user UUID,
     p_conv UUID
 )
-RETURNS BOOLEAN
+RETURNS rustygpt.conversation_role
 LANGUAGE plpgsql
+STABLE
 SECURITY DEFINER
 SET search_path = rustygpt, public
 AS $$
-DECLARE
-    v_exists BOOLEAN;
 BEGIN
     IF p_user IS NULL OR p_conv IS NULL THEN
-        RETURN FALSE;
+        RETURN NULL;
     END IF;
 
-    SELECT EXISTS (
-        SELECT 1
-        FROM rustygpt.conversation_participants cp
-        WHERE cp.conversation_id = p_conv
-          AND cp.user_id = p_user
-          AND cp.left_at IS NULL
-    )
-    INTO v_exists;
+    RETURN (
+        SELECT roles.role
+        FROM (
+            SELECT cp.role
+            FROM rustygpt.conversation_participants cp
+            WHERE cp.conversation_id = p_conv
+              AND cp.user_id = p_user
+              AND cp.left_at IS NULL
+            UNION ALL
+            SELECT g.role
+            FROM rustygpt.conversation_group_grants g
+            JOIN rustygpt.user_group_members m ON m.group_id = g.group_id
+            WHERE g.conversation_id = p_conv
+              AND m.user_id = p_user
+        ) roles
+        JOIN rustygpt.conversations c ON c.id = p_conv
+        WHERE rustygpt.sp_user_in_org(p_user, c.org_id)
+        -- Enum order runs from owner to viewer, so the first row is the strongest role.
+        ORDER BY roles.role
+        LIMIT 1
+    );
+END;
+$$;
 
-    RETURN COALESCE(v_exists, FALSE);
+CREATE OR REPLACE FUNCTION rustygpt.sp_user_can_access(
+    p_user UUID,
+    p_conv UUID
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    RETURN rustygpt.sp_user_conversation_role(p_user, p_conv) IS NOT NULL;
 END;
 $$;
 
@@ -4,7 +4,7 @@ SET search_path TO rustygpt, public;
 CREATE OR REPLACE FUNCTION rustygpt.sp_reply_message(
     p_parent_msg UUID,
     p_author UUID,
-    p_role rustygpt.message_role DEFAULT 'user',
+    p_role rustygpt.message_role,
     p_content TEXT
 )
 RETURNS TABLE (
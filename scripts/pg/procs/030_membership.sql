@@ -23,12 +23,7 @@ BEGIN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: user required';
     END IF;
 
-    SELECT role
-    INTO v_actor_role
-    FROM rustygpt.conversation_participants cp
-    WHERE cp.conversation_id = p_conversation
-      AND cp.user_id = v_actor
-      AND cp.left_at IS NULL;
+    v_actor_role := rustygpt.sp_user_conversation_role(v_actor, p_conversation);
 
     IF v_actor_role IS NULL OR v_actor_role NOT IN ('owner', 'admin') THEN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: insufficient role';
@@ -51,6 +46,17 @@ BEGIN
             WHERE conversation_id = p_conversation
               AND user_id = p_user
               AND left_at IS NULL;
+
+            PERFORM rustygpt.sp_audit_record(
+                'membership.role_change',
+                'conversation',
+                p_conversation::TEXT,
+                jsonb_build_object(
+                    'user_id', p_user,
+                    'role', v_role,
+                    'previous_role', v_existing.role
+                )
+            );
         END IF;
         RETURN v_role;
     END IF;
@@ -67,6 +73,13 @@ BEGIN
         now()
     );
 
+    PERFORM rustygpt.sp_audit_record(
+        'membership.add',
+        'conversation',
+        p_conversation::TEXT,
+        jsonb_build_object('user_id', p_user, 'role', v_role)
+    );
+
     RETURN v_role;
 END;
 $$;
@@ -88,12 +101,7 @@ DECLARE
 BEGIN
     v_actor := rustygpt.sp_require_session_user();
 
-    SELECT role
-    INTO v_actor_role
-    FROM rustygpt.conversation_participants cp
-    WHERE cp.conversation_id = p_conversation
-      AND cp.user_id = v_actor
-      AND cp.left_at IS NULL;
+    v_actor_role := rustygpt.sp_user_conversation_role(v_actor, p_conversation);
 
     IF v_actor_role IS NULL OR v_actor_role NOT IN ('owner', 'admin') THEN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: insufficient role';
@@ -130,6 +138,13 @@ BEGIN
       AND user_id = p_user
       AND left_at IS NULL;
 
+    PERFORM rustygpt.sp_audit_record(
+        'membership.remove',
+        'conversation',
+        p_conversation::TEXT,
+        jsonb_build_object('user_id', p_user, 'role', v_target.role)
+    );
+
     RETURN v_target.role;
 END;
 $$;
@@ -165,12 +180,7 @@ BEGIN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: ttl must be positive';
     END IF;
 
-    SELECT role
-    INTO v_actor_role
-    FROM rustygpt.conversation_participants cp
-    WHERE cp.conversation_id = p_conversation
-      AND cp.user_id = v_actor
-      AND cp.left_at IS NULL;
+    v_actor_role := rustygpt.sp_user_conversation_role(v_actor, p_conversation);
 
     IF v_actor_role IS NULL OR v_actor_role NOT IN ('owner', 'admin') THEN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: insufficient role';
@@ -250,19 +260,20 @@ BEGIN
 
     IF v_invite.accepted_at IS NOT NULL THEN
         RETURN QUERY SELECT v_invite.conversation_id, v_invite.role;
+        RETURN;
     END IF;
 
     IF v_invite.expires_at <= now() THEN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.410: invite expired';
     END IF;
 
-    UPDATE rustygpt.conversation_participants
+    UPDATE rustygpt.conversation_participants cp
     SET role = v_invite.role,
         joined_at = now(),
         left_at = NULL
-    WHERE conversation_id = v_invite.conversation_id
-      AND user_id = p_user
-      AND left_at IS NULL;
+    WHERE cp.conversation_id = v_invite.conversation_id
+      AND cp.user_id = p_user
+      AND cp.left_at IS NULL;
 
     IF NOT FOUND THEN
         INSERT INTO rustygpt.conversation_participants (
@@ -283,6 +294,17 @@ BEGIN
         accepted_at = now()
     WHERE id = v_invite.id;
 
+    PERFORM rustygpt.sp_audit_record(
+        'invite.accept',
+        'conversation',
+        v_invite.conversation_id::TEXT,
+        jsonb_build_object(
+            'invite_id', v_invite.id,
+            'role', v_invite.role,
+            'invited_by', v_invite.invited_by
+        )
+    );
+
     RETURN QUERY SELECT v_invite.conversation_id, v_invite.role;
 END;
 $$;
@@ -311,12 +333,7 @@ BEGIN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: invite not found';
     END IF;
 
-    SELECT role
-    INTO v_actor_role
-    FROM rustygpt.conversation_participants cp
-    WHERE cp.conversation_id = v_invite.conversation_id
-      AND cp.user_id = v_actor
-      AND cp.left_at IS NULL;
+    v_actor_role := rustygpt.sp_user_conversation_role(v_actor, v_invite.conversation_id);
 
     IF v_actor_role IS NULL OR v_actor_role NOT IN ('owner', 'admin') THEN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: insufficient role';
@@ -325,5 +342,16 @@ BEGIN
     UPDATE rustygpt.conversation_invites
     SET revoked_at = now()
     WHERE id = v_invite.id;
+
+    PERFORM rustygpt.sp_audit_record(
+        'invite.revoke',
+        'conversation',
+        v_invite.conversation_id::TEXT,
+        jsonb_build_object(
+            'invite_id', v_invite.id,
+            'invited_email', v_invite.invited_email::TEXT,
+            'role', v_invite.role
+        )
+    );
 END;
 $$;
@@ -34,12 +34,7 @@ BEGIN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: message not found';
     END IF;
 
-    SELECT role
-    INTO v_role
-    FROM rustygpt.conversation_participants cp
-    WHERE cp.conversation_id = v_message.conversation_id
-      AND cp.user_id = p_actor
-      AND cp.left_at IS NULL;
+    v_role := rustygpt.sp_user_conversation_role(p_actor, v_message.conversation_id);
 
     IF v_role IS NULL THEN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: not a participant';
@@ -83,7 +78,8 @@ BEGIN
     SELECT
         m.id,
         m.conversation_id,
-        m.deleted_at
+        m.deleted_at,
+        m.purged_at
     INTO v_message
     FROM rustygpt.messages m
     WHERE m.id = p_message;
@@ -96,17 +92,17 @@ BEGIN
         RETURN;
     END IF;
 
-    SELECT role
-    INTO v_role
-    FROM rustygpt.conversation_participants cp
-    WHERE cp.conversation_id = v_message.conversation_id
-      AND cp.user_id = p_actor
-      AND cp.left_at IS NULL;
+    v_role := rustygpt.sp_user_conversation_role(p_actor, v_message.conversation_id);
 
     IF v_role IS NULL OR v_role NOT IN ('owner', 'admin') THEN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: insufficient role';
     END IF;
 
+    -- Purged content is gone for good; refuse to resurrect an empty message.
+    IF v_message.purged_at IS NOT NULL THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: message content was purged';
+    END IF;
+
     UPDATE rustygpt.messages
     SET deleted_at = NULL,
         deleted_by = NULL,
@@ -130,6 +126,7 @@ DECLARE
     v_session UUID;
     v_message RECORD;
     v_role rustygpt.conversation_role;
+    v_revision INT;
 BEGIN
     v_session := rustygpt.sp_require_session_user();
     IF v_session <> p_actor THEN
@@ -144,10 +141,13 @@ BEGIN
         m.id,
         m.conversation_id,
         m.author_user_id,
+        m.content,
+        m.created_at,
         m.deleted_at
     INTO v_message
     FROM rustygpt.messages m
-    WHERE m.id = p_message;
+    WHERE m.id = p_message
+    FOR UPDATE;
 
     IF v_message.id IS NULL THEN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: message not found';
@@ -157,12 +157,7 @@ BEGIN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.409: message deleted';
     END IF;
 
-    SELECT role
-    INTO v_role
-    FROM rustygpt.conversation_participants cp
-    WHERE cp.conversation_id = v_message.conversation_id
-      AND cp.user_id = p_actor
-      AND cp.left_at IS NULL;
+    v_role := rustygpt.sp_user_conversation_role(p_actor, v_message.conversation_id);
 
     IF v_role IS NULL THEN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: not a participant';
@@ -172,6 +167,51 @@ BEGIN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: insufficient role';
     END IF;
 
+    SELECT MAX(r.revision)
+    INTO v_revision
+    FROM rustygpt.message_revisions r
+    WHERE r.message_id = p_message;
+
+    -- Capture the original content the first time a message is edited.
+    IF v_revision IS NULL THEN
+        INSERT INTO rustygpt.message_revisions (
+            message_id,
+            conversation_id,
+            revision,
+            content,
+            edited_by,
+            edit_reason,
+            created_at
+        )
+        VALUES (
+            p_message,
+            v_message.conversation_id,
+            1,
+            v_message.content,
+            v_message.author_user_id,
+            NULL,
+            v_message.created_at
+        );
+        v_revision := 1;
+    END IF;
+
+    INSERT INTO rustygpt.message_revisions (
+        message_id,
+        conversation_id,
+        revision,
+        content,
+        edited_by,
+        edit_reason
+    )
+    VALUES (
+        p_message,
+        v_message.conversation_id,
+        v_revision + 1,
+        p_content,
+        p_actor,
+        p_reason
+    );
+
     UPDATE rustygpt.messages
     SET content = p_content,
         edited_at = now(),
@@ -1,6 +1,8 @@
 -- Stored procedures for conversational rate limits
 SET search_path TO rustygpt, public;
 
+-- An organization's own rate-limit profile replaces the `conversation.post` profile in its
+-- conversations.
 CREATE OR REPLACE FUNCTION rustygpt.sp_user_can_post(
     p_user UUID,
     p_conversation UUID
@@ -14,6 +16,7 @@ DECLARE
     v_actor UUID;
     v_rps NUMERIC := 5;
     v_burst INTEGER := 10;
+    v_params JSONB;
     v_interval INTERVAL;
     v_burst_window INTERVAL;
     v_now TIMESTAMPTZ := clock_timestamp();
@@ -30,18 +33,22 @@ BEGIN
         RETURN FALSE;
     END IF;
 
-    SELECT
-        COALESCE(
-            NULLIF((params ->> 'requests_per_second')::NUMERIC, 0),
-            v_rps
-        ),
-        COALESCE(
-            NULLIF((params ->> 'burst')::INTEGER, 0),
-            v_burst
-        )
-    INTO v_rps, v_burst
-    FROM rustygpt.rate_limit_profiles
-    WHERE name = 'conversation.post';
+    SELECT p.params
+    INTO v_params
+    FROM rustygpt.conversations c
+    JOIN rustygpt.organizations o ON o.id = c.org_id
+    JOIN rustygpt.rate_limit_profiles p ON p.id = o.rate_limit_profile_id
+    WHERE c.id = p_conversation;
+
+    IF v_params IS NULL THEN
+        SELECT params
+        INTO v_params
+        FROM rustygpt.rate_limit_profiles
+        WHERE name = 'conversation.post';
+    END IF;
+
+    v_rps := COALESCE(NULLIF((v_params ->> 'requests_per_second')::NUMERIC, 0), v_rps);
+    v_burst := COALESCE(NULLIF((v_params ->> 'burst')::INTEGER, 0), v_burst);
 
     v_interval := interval '1 second' / v_rps;
     v_burst_window := v_interval * v_burst;
@@ -136,6 +143,13 @@ BEGIN
     RETURNING id, name, algorithm, params, description, created_at, updated_at
       INTO profile_id, name, algorithm, params, description, created_at, updated_at;
 
+    PERFORM rustygpt.sp_audit_record(
+        'rate_limit.profile_create',
+        'rate_limit_profile',
+        profile_id::TEXT,
+        jsonb_build_object('name', name, 'algorithm', algorithm, 'params', params)
+    );
+
     RETURN NEXT;
     RETURN;
 END;
@@ -159,7 +173,14 @@ LANGUAGE plpgsql
 SECURITY DEFINER
 SET search_path = rustygpt, public
 AS $$
+DECLARE
+    v_previous JSONB;
 BEGIN
+    SELECT p.params
+    INTO v_previous
+    FROM rustygpt.rate_limit_profiles p
+    WHERE p.id = p_profile_id;
+
     UPDATE rustygpt.rate_limit_profiles
     SET params = COALESCE(p_params, params),
         description = NULLIF(btrim(p_description), ''),
@@ -172,6 +193,13 @@ BEGIN
         RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: profile not found';
     END IF;
 
+    PERFORM rustygpt.sp_audit_record(
+        'rate_limit.profile_update',
+        'rate_limit_profile',
+        profile_id::TEXT,
+        jsonb_build_object('name', name, 'params', params, 'previous_params', v_previous)
+    );
+
     RETURN NEXT;
     RETURN;
 END;
@@ -187,6 +215,7 @@ SET search_path = rustygpt, public
 AS $$
 DECLARE
     v_assigned BOOLEAN;
+    v_name TEXT;
 BEGIN
     SELECT EXISTS (
         SELECT 1
@@ -200,9 +229,21 @@ BEGIN
     END IF;
 
     DELETE FROM rustygpt.rate_limit_profiles
-    WHERE id = p_profile_id;
+    WHERE id = p_profile_id
+    RETURNING name INTO v_name;
+
+    IF v_name IS NULL THEN
+        RETURN FALSE;
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record(
+        'rate_limit.profile_delete',
+        'rate_limit_profile',
+        p_profile_id::TEXT,
+        jsonb_build_object('name', v_name)
+    );
 
-    RETURN FOUND;
+    RETURN TRUE;
 END;
 $$;
 
@@ -277,6 +318,19 @@ BEGIN
           INTO assignment_id, profile_id, method, path_pattern, created_at, updated_at;
     END IF;
 
+    PERFORM rustygpt.sp_audit_record(
+        'rate_limit.assign',
+        'rate_limit_assignment',
+        assignment_id::TEXT,
+        jsonb_build_object(
+            'profile_id', p_profile_id,
+            'profile_name', v_profile.name,
+            'method', v_method,
+            'path_pattern', v_path,
+            'previous_profile_id', v_existing.profile_id
+        )
+    );
+
     RETURN NEXT;
     RETURN;
 END;
@@ -316,10 +370,28 @@ LANGUAGE plpgsql
 SECURITY DEFINER
 SET search_path = rustygpt, public
 AS $$
+DECLARE
+    v_deleted rustygpt.rate_limit_assignments%ROWTYPE;
 BEGIN
     DELETE FROM rustygpt.rate_limit_assignments
-    WHERE id = p_assignment_id;
+    WHERE id = p_assignment_id
+    RETURNING * INTO v_deleted;
 
-    RETURN FOUND;
+    IF v_deleted.id IS NULL THEN
+        RETURN FALSE;
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record(
+        'rate_limit.unassign',
+        'rate_limit_assignment',
+        p_assignment_id::TEXT,
+        jsonb_build_object(
+            'profile_id', v_deleted.profile_id,
+            'method', v_deleted.method,
+            'path_pattern', v_deleted.path_pattern
+        )
+    );
+
+    RETURN TRUE;
 END;
 $$;
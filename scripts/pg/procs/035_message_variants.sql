@@ -0,0 +1,108 @@
+-- Stored procedures: assistant reply variants (regeneration)
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_get_thread_variants(
+    p_root UUID
+)
+RETURNS TABLE (
+    parent_id UUID,
+    variant_ids UUID[]
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_conversation UUID;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    SELECT m.conversation_id
+    INTO v_conversation
+    FROM rustygpt.messages m
+    WHERE m.id = p_root
+      AND m.root_message_id = m.id;
+
+    IF v_conversation IS NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: thread root not found';
+    END IF;
+
+    IF NOT rustygpt.sp_user_can_access(v_actor, v_conversation) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for conversation';
+    END IF;
+
+    RETURN QUERY
+    SELECT
+        msg.parent_message_id AS parent_id,
+        ARRAY_AGG(msg.id ORDER BY msg.created_at, msg.id) AS variant_ids
+    FROM rustygpt.messages msg
+    WHERE msg.root_message_id = p_root
+      AND msg.parent_message_id IS NOT NULL
+      AND msg.role = 'assistant'
+      AND msg.deleted_at IS NULL
+    GROUP BY msg.parent_message_id
+    HAVING COUNT(*) > 1;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_prepare_regenerate(
+    p_message UUID
+)
+RETURNS TABLE (
+    source_message_id UUID,
+    parent_id UUID,
+    root_id UUID,
+    conversation_id UUID
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_message RECORD;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    SELECT
+        m.id,
+        m.parent_message_id,
+        m.root_message_id,
+        m.conversation_id,
+        m.role,
+        m.deleted_at
+    INTO v_message
+    FROM rustygpt.messages m
+    WHERE m.id = p_message;
+
+    IF v_message IS NULL OR v_message.deleted_at IS NOT NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: message not found';
+    END IF;
+
+    IF NOT rustygpt.sp_user_can_access(v_actor, v_message.conversation_id) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for conversation';
+    END IF;
+
+    IF v_message.role <> 'assistant' OR v_message.parent_message_id IS NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: only assistant replies can be regenerated';
+    END IF;
+
+    RETURN QUERY
+    SELECT
+        v_message.id,
+        v_message.parent_message_id,
+        v_message.root_message_id,
+        v_message.conversation_id;
+END;
+$$;
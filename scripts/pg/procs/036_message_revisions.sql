@@ -0,0 +1,63 @@
+-- Stored procedures: message revision history
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_list_message_revisions(
+    p_message UUID
+)
+RETURNS TABLE (
+    revision INT,
+    content TEXT,
+    edited_by UUID,
+    edit_reason TEXT,
+    created_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_message RECORD;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    SELECT
+        m.id,
+        m.conversation_id,
+        m.author_user_id,
+        m.content,
+        m.created_at
+    INTO v_message
+    FROM rustygpt.messages m
+    WHERE m.id = p_message;
+
+    IF v_message.id IS NULL THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: message not found';
+    END IF;
+
+    IF NOT rustygpt.sp_user_can_access(v_actor, v_message.conversation_id) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for conversation';
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM rustygpt.message_revisions r WHERE r.message_id = p_message
+    ) THEN
+        RETURN QUERY
+        SELECT 1, v_message.content, v_message.author_user_id, NULL::TEXT, v_message.created_at;
+        RETURN;
+    END IF;
+
+    RETURN QUERY
+    SELECT
+        r.revision,
+        r.content,
+        r.edited_by,
+        r.edit_reason,
+        r.created_at
+    FROM rustygpt.message_revisions r
+    WHERE r.message_id = p_message
+    ORDER BY r.revision;
+END;
+$$;
@@ -0,0 +1,159 @@
+-- Stored procedure: fork a message subtree or ancestor chain into a new thread root
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_fork_messages(
+    p_source UUID,
+    p_mode TEXT DEFAULT 'subtree',
+    p_target_conversation UUID DEFAULT NULL,
+    p_new_conversation_title TEXT DEFAULT NULL
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    root_id UUID,
+    message_count INT,
+    created_conversation BOOLEAN
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_source RECORD;
+    v_target UUID;
+    v_start UUID;
+    v_created BOOLEAN := FALSE;
+    v_root UUID;
+    v_count INT;
+    v_role rustygpt.conversation_role;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    IF p_mode NOT IN ('subtree', 'ancestors') THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: mode must be subtree or ancestors';
+    END IF;
+
+    IF p_target_conversation IS NOT NULL AND p_new_conversation_title IS NOT NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: choose either a target conversation or a new conversation title';
+    END IF;
+
+    SELECT
+        m.id,
+        m.conversation_id,
+        m.root_message_id,
+        m.path,
+        m.deleted_at
+    INTO v_source
+    FROM rustygpt.messages m
+    WHERE m.id = p_source;
+
+    IF v_source.id IS NULL OR v_source.deleted_at IS NOT NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: source message not found';
+    END IF;
+
+    IF NOT rustygpt.sp_user_can_access(v_actor, v_source.conversation_id) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for source conversation';
+    END IF;
+
+    IF p_new_conversation_title IS NOT NULL THEN
+        v_target := rustygpt.sp_create_conversation(p_new_conversation_title, FALSE, v_actor);
+        v_created := TRUE;
+    ELSE
+        v_target := COALESCE(p_target_conversation, v_source.conversation_id);
+    END IF;
+
+    -- Copies are new messages in the target, so the caller needs to be able to post there.
+    v_role := rustygpt.sp_user_conversation_role(v_actor, v_target);
+    IF v_role IS NULL OR v_role = 'viewer' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for target conversation';
+    END IF;
+
+    IF NOT rustygpt.sp_user_can_post(v_actor, v_target) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.429: message rate limit exceeded';
+    END IF;
+
+    v_start := CASE WHEN p_mode = 'subtree' THEN v_source.id ELSE v_source.root_message_id END;
+
+    CREATE TEMP TABLE IF NOT EXISTS tmp_fork_map (
+        old_id UUID PRIMARY KEY,
+        new_id UUID NOT NULL,
+        new_parent_id UUID,
+        new_path LTREE NOT NULL
+    ) ON COMMIT DROP;
+    TRUNCATE tmp_fork_map;
+
+    WITH RECURSIVE src AS (
+        SELECT m.id, m.parent_message_id, gen_random_uuid() AS new_id
+        FROM rustygpt.messages m
+        WHERE m.root_message_id = v_source.root_message_id
+          AND m.deleted_at IS NULL
+          AND (
+              (p_mode = 'subtree' AND m.path <@ v_source.path)
+              OR (p_mode = 'ancestors' AND m.path @> v_source.path)
+          )
+    ),
+    tree AS (
+        SELECT s.id, s.new_id, NULL::UUID AS new_parent_id, rustygpt.uuid_to_label(s.new_id) AS new_path
+        FROM src s
+        WHERE s.id = v_start
+        UNION ALL
+        SELECT c.id, c.new_id, t.new_id, t.new_path || rustygpt.uuid_to_label(c.new_id)
+        FROM src c
+        JOIN tree t ON c.parent_message_id = t.id
+    )
+    INSERT INTO tmp_fork_map (old_id, new_id, new_parent_id, new_path)
+    SELECT tree.id, tree.new_id, tree.new_parent_id, tree.new_path
+    FROM tree;
+
+    SELECT f.new_id INTO v_root FROM tmp_fork_map f WHERE f.old_id = v_start;
+
+    IF v_root IS NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: nothing to fork';
+    END IF;
+
+    INSERT INTO rustygpt.messages (
+        id,
+        conversation_id,
+        parent_message_id,
+        root_message_id,
+        author_user_id,
+        role,
+        content,
+        path,
+        forked_from_message_id,
+        created_at
+    )
+    SELECT
+        f.new_id,
+        v_target,
+        f.new_parent_id,
+        v_root,
+        m.author_user_id,
+        m.role,
+        m.content,
+        f.new_path,
+        m.id,
+        m.created_at
+    FROM tmp_fork_map f
+    JOIN rustygpt.messages m ON m.id = f.old_id
+    ORDER BY nlevel(f.new_path);
+
+    GET DIAGNOSTICS v_count = ROW_COUNT;
+
+    RETURN QUERY SELECT v_target, v_root, v_count, v_created;
+END;
+$$;
@@ -0,0 +1,366 @@
+-- Stored procedures: conversation archiving, deletion and retention policies
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_require_conversation_owner(
+    p_actor UUID,
+    p_conv UUID
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_role rustygpt.conversation_role;
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM rustygpt.conversations c WHERE c.id = p_conv) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: conversation not found';
+    END IF;
+
+    v_role := rustygpt.sp_user_conversation_role(p_actor, p_conv);
+
+    IF v_role IS NULL OR v_role <> 'owner' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: conversation owner required';
+    END IF;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_set_conversation_archived(
+    p_conv UUID,
+    p_archived BOOLEAN
+)
+RETURNS TABLE (
+    archived_at TIMESTAMPTZ,
+    changed BOOLEAN
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_current TIMESTAMPTZ;
+    v_next TIMESTAMPTZ;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+    PERFORM rustygpt.sp_require_conversation_owner(v_actor, p_conv);
+
+    SELECT c.archived_at
+    INTO v_current
+    FROM rustygpt.conversations c
+    WHERE c.id = p_conv
+    FOR UPDATE;
+
+    IF p_archived = (v_current IS NOT NULL) THEN
+        RETURN QUERY SELECT v_current, FALSE;
+        RETURN;
+    END IF;
+
+    v_next := CASE WHEN p_archived THEN now() ELSE NULL END;
+
+    UPDATE rustygpt.conversations c
+    SET archived_at = v_next
+    WHERE c.id = p_conv;
+
+    PERFORM rustygpt.sp_audit_record(
+        CASE WHEN p_archived THEN 'conversation.archive' ELSE 'conversation.unarchive' END,
+        'conversation',
+        p_conv::TEXT
+    );
+
+    RETURN QUERY SELECT v_next, TRUE;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_delete_conversation(
+    p_conv UUID
+)
+RETURNS INT
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_title TEXT;
+    v_messages INT;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+    PERFORM rustygpt.sp_require_conversation_owner(v_actor, p_conv);
+
+    SELECT c.title
+    INTO v_title
+    FROM rustygpt.conversations c
+    WHERE c.id = p_conv
+    FOR UPDATE;
+
+    SELECT COUNT(*)::INT
+    INTO v_messages
+    FROM rustygpt.messages m
+    WHERE m.conversation_id = p_conv;
+
+    PERFORM rustygpt.sp_audit_record(
+        'conversation.delete',
+        'conversation',
+        p_conv::TEXT,
+        jsonb_build_object('title', v_title, 'messages', v_messages)
+    );
+
+    -- The event log has no foreign key to the conversation.
+    DELETE FROM rustygpt.sse_event_log l WHERE l.conversation_id = p_conv;
+    DELETE FROM rustygpt.conversations c WHERE c.id = p_conv;
+
+    RETURN v_messages;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_get_retention_policy(
+    p_conv UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    archive_after_days INT,
+    purge_deleted_after_days INT,
+    effective_archive_after_days INT,
+    effective_purge_deleted_after_days INT,
+    updated_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    IF p_conv IS NOT NULL AND NOT rustygpt.sp_user_can_access(v_actor, p_conv) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for conversation';
+    END IF;
+
+    RETURN QUERY
+    SELECT
+        p_conv,
+        own.archive_after_days,
+        own.purge_deleted_after_days,
+        COALESCE(own.archive_after_days, dflt.archive_after_days),
+        COALESCE(own.purge_deleted_after_days, dflt.purge_deleted_after_days),
+        COALESCE(own.updated_at, dflt.updated_at)
+    FROM (SELECT 1) AS anchor
+    LEFT JOIN rustygpt.retention_policies dflt
+        ON dflt.conversation_id IS NULL
+    LEFT JOIN rustygpt.retention_policies own
+        ON p_conv IS NOT NULL AND own.conversation_id = p_conv;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_set_retention_policy(
+    p_conv UUID,
+    p_archive_after_days INT,
+    p_purge_deleted_after_days INT
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    archive_after_days INT,
+    purge_deleted_after_days INT,
+    effective_archive_after_days INT,
+    effective_purge_deleted_after_days INT,
+    updated_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    IF p_conv IS NULL THEN
+        IF NOT EXISTS (
+            SELECT 1
+            FROM rustygpt.user_roles ur
+            WHERE ur.user_id = v_actor
+              AND ur.role = 'admin'
+        ) THEN
+            RAISE EXCEPTION USING
+                ERRCODE = 'P0001',
+                MESSAGE = 'RGP.403: admin privileges required';
+        END IF;
+    ELSE
+        PERFORM rustygpt.sp_require_conversation_owner(v_actor, p_conv);
+    END IF;
+
+    IF COALESCE(p_archive_after_days, 1) <= 0 OR COALESCE(p_purge_deleted_after_days, 1) <= 0 THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: retention periods must be positive';
+    END IF;
+
+    UPDATE rustygpt.retention_policies rp
+    SET archive_after_days = p_archive_after_days,
+        purge_deleted_after_days = p_purge_deleted_after_days,
+        updated_by = v_actor,
+        updated_at = now()
+    WHERE rp.conversation_id IS NOT DISTINCT FROM p_conv;
+
+    IF NOT FOUND THEN
+        INSERT INTO rustygpt.retention_policies (
+            conversation_id,
+            archive_after_days,
+            purge_deleted_after_days,
+            updated_by
+        )
+        VALUES (p_conv, p_archive_after_days, p_purge_deleted_after_days, v_actor);
+    END IF;
+
+    RETURN QUERY SELECT * FROM rustygpt.sp_get_retention_policy(p_conv);
+END;
+$$;
+
+-- Background sweep: no session user, runs with the server's pool credentials.
+CREATE OR REPLACE FUNCTION rustygpt.sp_apply_retention(
+    p_batch INT DEFAULT 100
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    action TEXT,
+    affected_messages INT,
+    occurred_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_row RECORD;
+    v_count INT;
+BEGIN
+    FOR v_row IN
+        SELECT c.id, pol.archive_days
+        FROM rustygpt.conversations c
+        CROSS JOIN LATERAL (
+            SELECT COALESCE(own.archive_after_days, dflt.archive_after_days) AS archive_days
+            FROM (SELECT 1) AS anchor
+            LEFT JOIN rustygpt.retention_policies dflt
+                ON dflt.conversation_id IS NULL
+            LEFT JOIN rustygpt.retention_policies own
+                ON own.conversation_id = c.id
+        ) pol
+        WHERE c.archived_at IS NULL
+          AND pol.archive_days IS NOT NULL
+          AND COALESCE(
+                (SELECT MAX(m.created_at) FROM rustygpt.messages m WHERE m.conversation_id = c.id),
+                c.created_at
+              ) < now() - make_interval(days => pol.archive_days)
+        ORDER BY c.created_at
+        LIMIT GREATEST(COALESCE(p_batch, 100), 1)
+        FOR UPDATE OF c SKIP LOCKED
+    LOOP
+        UPDATE rustygpt.conversations c
+        SET archived_at = now()
+        WHERE c.id = v_row.id;
+
+        PERFORM rustygpt.sp_audit_record(
+            'conversation.archive',
+            'conversation',
+            v_row.id::TEXT,
+            jsonb_build_object('archive_after_days', v_row.archive_days)
+        );
+
+        conversation_id := v_row.id;
+        action := 'archived';
+        affected_messages := 0;
+        occurred_at := now();
+        RETURN NEXT;
+    END LOOP;
+
+    FOR v_row IN
+        SELECT c.id, pol.purge_days
+        FROM rustygpt.conversations c
+        CROSS JOIN LATERAL (
+            SELECT COALESCE(own.purge_deleted_after_days, dflt.purge_deleted_after_days) AS purge_days
+            FROM (SELECT 1) AS anchor
+            LEFT JOIN rustygpt.retention_policies dflt
+                ON dflt.conversation_id IS NULL
+            LEFT JOIN rustygpt.retention_policies own
+                ON own.conversation_id = c.id
+        ) pol
+        WHERE pol.purge_days IS NOT NULL
+          AND EXISTS (
+              SELECT 1
+              FROM rustygpt.messages m
+              WHERE m.conversation_id = c.id
+                AND m.deleted_at IS NOT NULL
+                AND m.purged_at IS NULL
+                AND m.deleted_at < now() - make_interval(days => pol.purge_days)
+          )
+        ORDER BY c.created_at
+        LIMIT GREATEST(COALESCE(p_batch, 100), 1)
+    LOOP
+        CREATE TEMP TABLE IF NOT EXISTS tmp_retention_purge (id UUID PRIMARY KEY) ON COMMIT DROP;
+        TRUNCATE tmp_retention_purge;
+
+        INSERT INTO tmp_retention_purge (id)
+        SELECT m.id
+        FROM rustygpt.messages m
+        WHERE m.conversation_id = v_row.id
+          AND m.deleted_at IS NOT NULL
+          AND m.purged_at IS NULL
+          AND m.deleted_at < now() - make_interval(days => v_row.purge_days);
+
+        DELETE FROM rustygpt.message_chunks mc
+        USING tmp_retention_purge t
+        WHERE mc.message_id = t.id;
+
+        DELETE FROM rustygpt.message_revisions mr
+        USING tmp_retention_purge t
+        WHERE mr.message_id = t.id;
+
+        DELETE FROM rustygpt.message_attachments ma
+        USING tmp_retention_purge t
+        WHERE ma.message_id = t.id;
+
+        -- Streamed deltas, edits and thread excerpts would otherwise replay the purged text.
+        DELETE FROM rustygpt.sse_event_log l
+        USING tmp_retention_purge t
+        WHERE l.conversation_id = v_row.id
+          AND (
+              l.payload #>> '{payload,message_id}' = t.id::TEXT
+              OR (l.event_type = 'thread.new' AND l.payload #>> '{payload,root_id}' = t.id::TEXT)
+          );
+
+        -- Rows stay so descendants keep their parent and root links.
+        UPDATE rustygpt.messages m
+        SET content = '',
+            purged_at = now()
+        FROM tmp_retention_purge t
+        WHERE m.id = t.id;
+
+        GET DIAGNOSTICS v_count = ROW_COUNT;
+
+        PERFORM rustygpt.sp_audit_record(
+            'conversation.purge',
+            'conversation',
+            v_row.id::TEXT,
+            jsonb_build_object(
+                'purge_deleted_after_days', v_row.purge_days,
+                'messages', v_count,
+                'message_ids', (SELECT jsonb_agg(t.id) FROM tmp_retention_purge t)
+            )
+        );
+
+        conversation_id := v_row.id;
+        action := 'purged';
+        affected_messages := v_count;
+        occurred_at := now();
+        RETURN NEXT;
+    END LOOP;
+END;
+$$;
@@ -0,0 +1,155 @@
+-- Stored procedures: conversation export
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_get_conversation_export_header(
+    p_conv UUID
+)
+RETURNS TABLE (
+    id UUID,
+    title TEXT,
+    is_group BOOLEAN,
+    created_by UUID,
+    created_at TIMESTAMPTZ,
+    archived_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    IF NOT EXISTS (SELECT 1 FROM rustygpt.conversations c WHERE c.id = p_conv) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: conversation not found';
+    END IF;
+
+    IF NOT rustygpt.sp_user_can_access(v_actor, p_conv) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for conversation';
+    END IF;
+
+    RETURN QUERY
+    SELECT c.id, c.title, c.is_group, c.created_by, c.created_at, c.archived_at
+    FROM rustygpt.conversations c
+    WHERE c.id = p_conv;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_export_conversation_messages(
+    p_conv UUID,
+    p_root UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    id UUID,
+    root_id UUID,
+    parent_id UUID,
+    author_user_id UUID,
+    author_name TEXT,
+    role rustygpt.message_role,
+    content TEXT,
+    path TEXT,
+    depth INT,
+    created_at TIMESTAMPTZ,
+    edited_at TIMESTAMPTZ,
+    edit_reason TEXT,
+    deleted_at TIMESTAMPTZ,
+    forked_from_message_id UUID,
+    revisions JSONB,
+    attachments JSONB
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    IF NOT rustygpt.sp_user_can_access(v_actor, p_conv) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for conversation';
+    END IF;
+
+    IF p_root IS NOT NULL AND NOT EXISTS (
+        SELECT 1
+        FROM rustygpt.messages m
+        WHERE m.id = p_root
+          AND m.root_message_id = m.id
+          AND m.conversation_id = p_conv
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: thread root not found';
+    END IF;
+
+    RETURN QUERY
+    SELECT
+        msg.id,
+        msg.root_message_id AS root_id,
+        msg.parent_message_id AS parent_id,
+        msg.author_user_id,
+        COALESCE(u.display_name, u.username::TEXT) AS author_name,
+        msg.role,
+        -- Deleted content is never exported, only the fact that it existed.
+        CASE WHEN msg.deleted_at IS NULL THEN msg.content ELSE '' END AS content,
+        msg.path::TEXT AS path,
+        msg.depth,
+        msg.created_at,
+        msg.edited_at,
+        msg.edit_reason,
+        msg.deleted_at,
+        msg.forked_from_message_id,
+        CASE
+            WHEN msg.deleted_at IS NOT NULL THEN '[]'::jsonb
+            ELSE COALESCE(
+                (
+                    SELECT jsonb_agg(
+                        jsonb_build_object(
+                            'revision', rev.revision,
+                            'content', rev.content,
+                            'edited_by', rev.edited_by,
+                            'reason', rev.edit_reason,
+                            'created_at', rev.created_at
+                        )
+                        ORDER BY rev.revision
+                    )
+                    FROM rustygpt.message_revisions rev
+                    WHERE rev.message_id = msg.id
+                ),
+                '[]'::jsonb
+            )
+        END AS revisions,
+        CASE
+            WHEN msg.deleted_at IS NOT NULL THEN '[]'::jsonb
+            ELSE COALESCE(
+                (
+                    SELECT jsonb_agg(
+                        jsonb_build_object(
+                            'id', att.id,
+                            'file_name', att.file_name,
+                            'content_type', att.content_type,
+                            'size_bytes', att.size_bytes,
+                            'created_at', att.created_at
+                        )
+                        ORDER BY att.created_at, att.id
+                    )
+                    FROM rustygpt.message_attachments att
+                    WHERE att.message_id = msg.id
+                ),
+                '[]'::jsonb
+            )
+        END AS attachments
+    FROM rustygpt.messages msg
+    LEFT JOIN rustygpt.users u ON u.id = msg.author_user_id
+    WHERE msg.conversation_id = p_conv
+      AND (p_root IS NULL OR msg.root_message_id = p_root)
+    ORDER BY msg.created_at, msg.path;
+END;
+$$;
@@ -0,0 +1,212 @@
+-- Stored procedures: conversation import
+SET search_path TO rustygpt, public;
+
+-- p_messages is a JSON array of {key, parent_key, role, content, created_at}, optionally with
+-- edited_at, edit_reason, revisions and attachments when re-importing one of our own exports.
+-- Keys are opaque strings from the source export; messages whose parent_key is
+-- NULL (or unknown) become thread roots. Branches are preserved as siblings.
+CREATE OR REPLACE FUNCTION rustygpt.sp_import_conversation(
+    p_title TEXT,
+    p_created_at TIMESTAMPTZ,
+    p_messages JSONB
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    message_count INT,
+    thread_count INT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_conv UUID;
+    v_count INT;
+    v_threads INT;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    IF p_messages IS NULL OR jsonb_typeof(p_messages) <> 'array' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: messages must be an array';
+    END IF;
+
+    IF jsonb_array_length(p_messages) = 0 THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: conversation has no messages';
+    END IF;
+
+    IF EXISTS (
+        SELECT 1
+        FROM jsonb_to_recordset(p_messages) AS x(role TEXT)
+        WHERE x.role IS NULL OR x.role NOT IN ('user', 'assistant', 'system', 'tool')
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: unsupported message role';
+    END IF;
+
+    IF EXISTS (
+        SELECT 1
+        FROM jsonb_array_elements(p_messages) e
+        CROSS JOIN LATERAL jsonb_to_recordset(COALESCE(e.value->'attachments', '[]'::jsonb))
+            AS a(file_name TEXT, size_bytes BIGINT)
+        WHERE COALESCE(btrim(a.file_name), '') = ''
+           OR a.size_bytes IS NULL
+           OR a.size_bytes < 0
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: attachments need a file name and size';
+    END IF;
+
+    v_conv := rustygpt.sp_create_conversation(p_title, FALSE, v_actor);
+
+    IF p_created_at IS NOT NULL THEN
+        UPDATE rustygpt.conversations c
+        SET created_at = p_created_at
+        WHERE c.id = v_conv;
+    END IF;
+
+    CREATE TEMP TABLE IF NOT EXISTS tmp_import_map (
+        key TEXT PRIMARY KEY,
+        new_id UUID NOT NULL,
+        new_parent_id UUID,
+        new_root_id UUID NOT NULL,
+        new_path LTREE NOT NULL,
+        role rustygpt.message_role NOT NULL,
+        content TEXT NOT NULL,
+        created_at TIMESTAMPTZ,
+        edited_at TIMESTAMPTZ,
+        edit_reason TEXT,
+        revisions JSONB,
+        attachments JSONB
+    ) ON COMMIT DROP;
+    TRUNCATE tmp_import_map;
+
+    WITH RECURSIVE src AS (
+        SELECT DISTINCT ON (x.key)
+            x.key,
+            x.parent_key,
+            x.role,
+            COALESCE(x.content, '') AS content,
+            x.created_at,
+            x.edited_at,
+            x.edit_reason,
+            x.revisions,
+            x.attachments,
+            gen_random_uuid() AS new_id
+        FROM jsonb_to_recordset(p_messages)
+            AS x(
+                key TEXT,
+                parent_key TEXT,
+                role TEXT,
+                content TEXT,
+                created_at TIMESTAMPTZ,
+                edited_at TIMESTAMPTZ,
+                edit_reason TEXT,
+                revisions JSONB,
+                attachments JSONB
+            )
+        WHERE x.key IS NOT NULL
+        ORDER BY x.key
+    ),
+    tree AS (
+        SELECT
+            s.key,
+            s.new_id,
+            NULL::UUID AS new_parent_id,
+            s.new_id AS new_root_id,
+            rustygpt.uuid_to_label(s.new_id) AS new_path
+        FROM src s
+        WHERE s.parent_key IS NULL
+           OR NOT EXISTS (SELECT 1 FROM src p WHERE p.key = s.parent_key)
+        UNION ALL
+        SELECT c.key, c.new_id, t.new_id, t.new_root_id, t.new_path || rustygpt.uuid_to_label(c.new_id)
+        FROM src c
+        JOIN tree t ON c.parent_key = t.key
+    )
+    INSERT INTO tmp_import_map (
+        key, new_id, new_parent_id, new_root_id, new_path, role, content, created_at,
+        edited_at, edit_reason, revisions, attachments
+    )
+    SELECT tree.key, tree.new_id, tree.new_parent_id, tree.new_root_id, tree.new_path,
+           s.role::rustygpt.message_role, s.content, s.created_at,
+           s.edited_at, s.edit_reason, s.revisions, s.attachments
+    FROM tree
+    JOIN src s ON s.key = tree.key;
+
+    INSERT INTO rustygpt.messages (
+        id,
+        conversation_id,
+        parent_message_id,
+        root_message_id,
+        author_user_id,
+        role,
+        content,
+        path,
+        created_at,
+        edited_at,
+        edit_reason
+    )
+    SELECT
+        f.new_id,
+        v_conv,
+        f.new_parent_id,
+        f.new_root_id,
+        CASE WHEN f.role = 'user' THEN v_actor ELSE NULL END,
+        f.role,
+        f.content,
+        f.new_path,
+        COALESCE(f.created_at, now()),
+        f.edited_at,
+        f.edit_reason
+    FROM tmp_import_map f
+    ORDER BY nlevel(f.new_path);
+
+    GET DIAGNOSTICS v_count = ROW_COUNT;
+
+    -- Editors from the source server mean nothing here, so revisions keep no `edited_by`.
+    INSERT INTO rustygpt.message_revisions (
+        message_id,
+        conversation_id,
+        revision,
+        content,
+        edit_reason,
+        created_at
+    )
+    SELECT f.new_id, v_conv, r.revision, COALESCE(r.content, ''), r.reason, COALESCE(r.created_at, now())
+    FROM tmp_import_map f
+    CROSS JOIN LATERAL jsonb_to_recordset(COALESCE(f.revisions, '[]'::jsonb))
+        AS r(revision INT, content TEXT, reason TEXT, created_at TIMESTAMPTZ)
+    WHERE r.revision IS NOT NULL
+    ON CONFLICT (message_id, revision) DO NOTHING;
+
+    -- Only the metadata travels; the files themselves are not part of an export.
+    INSERT INTO rustygpt.message_attachments (
+        message_id,
+        file_name,
+        content_type,
+        size_bytes,
+        created_at
+    )
+    SELECT
+        f.new_id,
+        a.file_name,
+        COALESCE(a.content_type, 'application/octet-stream'),
+        a.size_bytes,
+        COALESCE(a.created_at, now())
+    FROM tmp_import_map f
+    CROSS JOIN LATERAL jsonb_to_recordset(COALESCE(f.attachments, '[]'::jsonb))
+        AS a(file_name TEXT, content_type TEXT, size_bytes BIGINT, created_at TIMESTAMPTZ);
+
+    SELECT COUNT(*)::INT INTO v_threads
+    FROM tmp_import_map f
+    WHERE f.new_parent_id IS NULL;
+
+    RETURN QUERY SELECT v_conv, v_count, v_threads;
+END;
+$$;
@@ -0,0 +1,179 @@
+-- Stored procedures: personal access tokens
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_create_api_token(
+    p_name TEXT,
+    p_prefix TEXT,
+    p_token_hash BYTEA,
+    p_scopes TEXT[],
+    p_expires_at TIMESTAMPTZ DEFAULT NULL
+)
+RETURNS TABLE (
+    id UUID,
+    name TEXT,
+    prefix TEXT,
+    scopes TEXT[],
+    created_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    IF p_name IS NULL OR btrim(p_name) = '' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: token name required';
+    END IF;
+
+    IF p_scopes IS NULL OR cardinality(p_scopes) = 0 THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: at least one scope required';
+    END IF;
+
+    IF NOT p_scopes <@ ARRAY['v1:chat', 'threads:write', 'admin:*']::TEXT[] THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: unknown token scope';
+    END IF;
+
+    IF 'admin:*' = ANY (p_scopes) AND NOT EXISTS (
+        SELECT 1 FROM rustygpt.user_roles ur
+        WHERE ur.user_id = v_actor AND ur.role = 'admin'
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: admin scope requires the admin role';
+    END IF;
+
+    IF p_expires_at IS NOT NULL AND p_expires_at <= now() THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: expiry must be in the future';
+    END IF;
+
+    RETURN QUERY
+    INSERT INTO rustygpt.api_tokens AS t (user_id, name, prefix, token_hash, scopes, expires_at)
+    VALUES (v_actor, btrim(p_name), p_prefix, p_token_hash, p_scopes, p_expires_at)
+    RETURNING t.id, t.name, t.prefix, t.scopes, t.created_at, t.expires_at, t.last_used_at, t.revoked_at;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_list_api_tokens()
+RETURNS TABLE (
+    id UUID,
+    name TEXT,
+    prefix TEXT,
+    scopes TEXT[],
+    created_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    RETURN QUERY
+    SELECT t.id, t.name, t.prefix, t.scopes, t.created_at, t.expires_at, t.last_used_at, t.revoked_at
+    FROM rustygpt.api_tokens t
+    WHERE t.user_id = v_actor
+    ORDER BY t.created_at DESC;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_revoke_api_token(
+    p_token UUID
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    IF NOT EXISTS (
+        SELECT 1 FROM rustygpt.api_tokens t
+        WHERE t.id = p_token AND t.user_id = v_actor
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: token not found';
+    END IF;
+
+    UPDATE rustygpt.api_tokens t
+    SET revoked_at = now()
+    WHERE t.id = p_token
+      AND t.revoked_at IS NULL;
+
+    RETURN FOUND;
+END;
+$$;
+
+-- Called before any session exists, so it resolves the owner itself and only
+-- returns a row for a live token belonging to an enabled user.
+CREATE OR REPLACE FUNCTION rustygpt.sp_authenticate_api_token(
+    p_prefix TEXT,
+    p_token_hash BYTEA,
+    p_ip TEXT DEFAULT NULL
+)
+RETURNS TABLE (
+    token_id UUID,
+    user_id UUID,
+    email TEXT,
+    username TEXT,
+    display_name TEXT,
+    roles TEXT[],
+    scopes TEXT[],
+    created_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    RETURN QUERY
+    UPDATE rustygpt.api_tokens t
+    SET last_used_at = now(),
+        last_used_ip = COALESCE(p_ip, t.last_used_ip)
+    FROM rustygpt.users u
+    WHERE t.prefix = p_prefix
+      AND t.token_hash = p_token_hash
+      AND t.revoked_at IS NULL
+      AND (t.expires_at IS NULL OR t.expires_at > now())
+      AND u.id = t.user_id
+      AND u.disabled_at IS NULL
+    RETURNING
+        t.id,
+        t.user_id,
+        u.email::TEXT,
+        u.username::TEXT,
+        u.display_name,
+        ARRAY(
+            SELECT ur.role::TEXT
+            FROM rustygpt.user_roles ur
+            WHERE ur.user_id = t.user_id
+            ORDER BY ur.role
+        ),
+        t.scopes,
+        t.created_at,
+        t.expires_at;
+END;
+$$;
@@ -0,0 +1,149 @@
+-- Stored procedures: token usage ledger and reports
+SET search_path TO rustygpt, public;
+
+-- Written by the server after a generation finishes, which may be an anonymous
+-- /v1 call, so the owner is passed explicitly instead of read from the session.
+CREATE OR REPLACE FUNCTION rustygpt.sp_record_usage(
+    p_user_id UUID,
+    p_conversation_id UUID,
+    p_message_id UUID,
+    p_source TEXT,
+    p_model TEXT,
+    p_prompt_tokens BIGINT,
+    p_completion_tokens BIGINT,
+    p_total_tokens BIGINT,
+    p_latency_ms BIGINT
+)
+RETURNS BIGINT
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_id BIGINT;
+BEGIN
+    IF p_source IS NULL OR p_source NOT IN ('thread', 'v1') THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: unknown usage source';
+    END IF;
+
+    IF p_model IS NULL OR btrim(p_model) = '' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: model required';
+    END IF;
+
+    INSERT INTO rustygpt.usage_ledger (
+        user_id,
+        conversation_id,
+        message_id,
+        source,
+        model,
+        prompt_tokens,
+        completion_tokens,
+        total_tokens,
+        latency_ms
+    )
+    VALUES (
+        p_user_id,
+        p_conversation_id,
+        p_message_id,
+        p_source,
+        btrim(p_model),
+        GREATEST(p_prompt_tokens, 0),
+        GREATEST(p_completion_tokens, 0),
+        GREATEST(p_total_tokens, 0),
+        GREATEST(p_latency_ms, 0)
+    )
+    RETURNING id INTO v_id;
+
+    RETURN v_id;
+END;
+$$;
+
+-- Aggregates the ledger over [p_from, p_to). A NULL p_user_id reports every user
+-- and requires the admin role; otherwise callers may only report on themselves.
+CREATE OR REPLACE FUNCTION rustygpt.sp_usage_report(
+    p_from TIMESTAMPTZ,
+    p_to TIMESTAMPTZ,
+    p_group_by TEXT,
+    p_user_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    bucket TEXT,
+    username TEXT,
+    requests BIGINT,
+    prompt_tokens BIGINT,
+    completion_tokens BIGINT,
+    total_tokens BIGINT,
+    avg_latency_ms BIGINT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_is_admin BOOLEAN;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    v_is_admin := EXISTS (
+        SELECT 1 FROM rustygpt.user_roles ur
+        WHERE ur.user_id = v_actor AND ur.role = 'admin'
+    );
+
+    IF (p_user_id IS NULL OR p_user_id <> v_actor) AND NOT v_is_admin THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: usage for other users requires the admin role';
+    END IF;
+
+    IF p_group_by IS NULL OR p_group_by NOT IN ('day', 'model', 'user') THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: group_by must be day, model or user';
+    END IF;
+
+    IF p_from IS NULL OR p_to IS NULL OR p_from >= p_to THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: from must be before to';
+    END IF;
+
+    RETURN QUERY
+    WITH scoped AS (
+        SELECT
+            CASE p_group_by
+                WHEN 'day' THEN to_char(l.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD')
+                WHEN 'model' THEN l.model
+                ELSE COALESCE(l.user_id::TEXT, 'anonymous')
+            END AS bucket,
+            l.user_id,
+            l.prompt_tokens,
+            l.completion_tokens,
+            l.total_tokens,
+            l.latency_ms
+        FROM rustygpt.usage_ledger l
+        WHERE l.created_at >= p_from
+          AND l.created_at < p_to
+          AND (p_user_id IS NULL OR l.user_id = p_user_id)
+    )
+    SELECT
+        s.bucket,
+        CASE WHEN p_group_by = 'user' THEN MAX(u.username::TEXT) END,
+        COUNT(*)::BIGINT,
+        COALESCE(SUM(s.prompt_tokens), 0)::BIGINT,
+        COALESCE(SUM(s.completion_tokens), 0)::BIGINT,
+        COALESCE(SUM(s.total_tokens), 0)::BIGINT,
+        COALESCE(ROUND(AVG(s.latency_ms)), 0)::BIGINT
+    FROM scoped s
+    LEFT JOIN rustygpt.users u ON u.id = s.user_id
+    GROUP BY s.bucket
+    ORDER BY
+        CASE WHEN p_group_by = 'day' THEN s.bucket END,
+        SUM(s.total_tokens) DESC,
+        s.bucket;
+END;
+$$;
@@ -0,0 +1,258 @@
+-- Stored procedures: token quotas
+SET search_path TO rustygpt, public;
+
+-- PL/pgSQL so the organization join resolves at call time; organizations are created later
+-- in the bootstrap.
+CREATE OR REPLACE FUNCTION rustygpt.sp_quota_list()
+RETURNS TABLE (
+    quota_id UUID,
+    target_kind TEXT,
+    user_id UUID,
+    role TEXT,
+    profile_id UUID,
+    org_id UUID,
+    target_label TEXT,
+    daily_tokens BIGINT,
+    monthly_tokens BIGINT,
+    description TEXT,
+    created_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    RETURN QUERY
+    SELECT
+        q.id,
+        q.target_kind,
+        q.user_id,
+        q.role::TEXT,
+        q.profile_id,
+        q.org_id,
+        COALESCE(u.username::TEXT, q.role::TEXT, p.name, o.name),
+        q.daily_tokens,
+        q.monthly_tokens,
+        q.description,
+        q.created_at,
+        q.updated_at
+    FROM rustygpt.token_quotas q
+    LEFT JOIN rustygpt.users u ON u.id = q.user_id
+    LEFT JOIN rustygpt.rate_limit_profiles p ON p.id = q.profile_id
+    LEFT JOIN rustygpt.organizations o ON o.id = q.org_id
+    ORDER BY q.target_kind, 7;
+END;
+$$;
+
+-- Creates the quota for a target or replaces the budgets of the existing one.
+CREATE OR REPLACE FUNCTION rustygpt.sp_quota_upsert(
+    p_target_kind TEXT,
+    p_user_id UUID,
+    p_role TEXT,
+    p_profile_id UUID,
+    p_org_id UUID,
+    p_daily_tokens BIGINT,
+    p_monthly_tokens BIGINT,
+    p_description TEXT
+)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_id UUID;
+    v_role rustygpt.user_role;
+BEGIN
+    IF p_target_kind IS NULL OR p_target_kind NOT IN ('user', 'role', 'profile', 'org') THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: target kind must be user, role, profile or org';
+    END IF;
+
+    IF p_daily_tokens IS NULL AND p_monthly_tokens IS NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: set a daily or monthly budget';
+    END IF;
+
+    IF COALESCE(p_daily_tokens, 0) < 0 OR COALESCE(p_monthly_tokens, 0) < 0 THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: budgets must not be negative';
+    END IF;
+
+    IF p_target_kind = 'user' AND NOT EXISTS (
+        SELECT 1 FROM rustygpt.users u WHERE u.id = p_user_id
+    ) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: user not found';
+    END IF;
+
+    IF p_target_kind = 'role' THEN
+        BEGIN
+            v_role := p_role::rustygpt.user_role;
+        EXCEPTION WHEN invalid_text_representation THEN
+            v_role := NULL;
+        END;
+        IF v_role IS NULL THEN
+            RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: unknown role';
+        END IF;
+    END IF;
+
+    IF p_target_kind = 'profile' AND NOT EXISTS (
+        SELECT 1 FROM rustygpt.rate_limit_profiles p WHERE p.id = p_profile_id
+    ) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: profile not found';
+    END IF;
+
+    IF p_target_kind = 'org' AND NOT EXISTS (
+        SELECT 1 FROM rustygpt.organizations o WHERE o.id = p_org_id
+    ) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: organization not found';
+    END IF;
+
+    UPDATE rustygpt.token_quotas q
+    SET daily_tokens = p_daily_tokens,
+        monthly_tokens = p_monthly_tokens,
+        description = NULLIF(btrim(p_description), ''),
+        updated_at = now()
+    WHERE q.target_kind = p_target_kind
+      AND CASE p_target_kind
+            WHEN 'user' THEN q.user_id = p_user_id
+            WHEN 'role' THEN q.role = v_role
+            WHEN 'profile' THEN q.profile_id = p_profile_id
+            ELSE q.org_id = p_org_id
+          END
+    RETURNING q.id INTO v_id;
+
+    IF v_id IS NULL THEN
+        INSERT INTO rustygpt.token_quotas (
+            target_kind,
+            user_id,
+            role,
+            profile_id,
+            org_id,
+            daily_tokens,
+            monthly_tokens,
+            description
+        )
+        VALUES (
+            p_target_kind,
+            CASE WHEN p_target_kind = 'user' THEN p_user_id END,
+            v_role,
+            CASE WHEN p_target_kind = 'profile' THEN p_profile_id END,
+            CASE WHEN p_target_kind = 'org' THEN p_org_id END,
+            p_daily_tokens,
+            p_monthly_tokens,
+            NULLIF(btrim(p_description), '')
+        )
+        RETURNING id INTO v_id;
+    END IF;
+
+    RETURN v_id;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_quota_delete(
+    p_quota_id UUID
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    DELETE FROM rustygpt.token_quotas
+    WHERE id = p_quota_id;
+
+    RETURN FOUND;
+END;
+$$;
+
+-- Resolves the budget that applies to a user and reports consumption for each
+-- limited period. A user quota wins over a profile quota, which wins over role
+-- quotas; among several role quotas the most generous budget per period applies.
+-- Consumption is read from the usage ledger, which is written when a generation
+-- finishes, so the budget shrinks on every message.done.
+CREATE OR REPLACE FUNCTION rustygpt.sp_quota_check(
+    p_user_id UUID,
+    p_profile TEXT DEFAULT NULL
+)
+RETURNS TABLE (
+    period TEXT,
+    source TEXT,
+    token_limit BIGINT,
+    used BIGINT,
+    resets_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_daily BIGINT;
+    v_monthly BIGINT;
+    v_source TEXT;
+    v_day_start TIMESTAMPTZ := date_trunc('day', now() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC';
+    v_month_start TIMESTAMPTZ := date_trunc('month', now() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC';
+BEGIN
+    SELECT q.daily_tokens, q.monthly_tokens, 'user'
+    INTO v_daily, v_monthly, v_source
+    FROM rustygpt.token_quotas q
+    WHERE q.target_kind = 'user'
+      AND q.user_id = p_user_id;
+
+    IF v_source IS NULL AND p_profile IS NOT NULL THEN
+        SELECT q.daily_tokens, q.monthly_tokens, 'profile'
+        INTO v_daily, v_monthly, v_source
+        FROM rustygpt.token_quotas q
+        JOIN rustygpt.rate_limit_profiles p ON p.id = q.profile_id
+        WHERE q.target_kind = 'profile'
+          AND p.name = p_profile;
+    END IF;
+
+    IF v_source IS NULL THEN
+        SELECT
+            CASE WHEN bool_or(q.daily_tokens IS NULL) THEN NULL ELSE MAX(q.daily_tokens) END,
+            CASE WHEN bool_or(q.monthly_tokens IS NULL) THEN NULL ELSE MAX(q.monthly_tokens) END,
+            'role'
+        INTO v_daily, v_monthly, v_source
+        FROM rustygpt.token_quotas q
+        JOIN rustygpt.user_roles ur ON ur.role = q.role
+        WHERE q.target_kind = 'role'
+          AND ur.user_id = p_user_id
+        HAVING COUNT(*) > 0;
+    END IF;
+
+    IF v_source IS NULL THEN
+        RETURN;
+    END IF;
+
+    IF v_daily IS NOT NULL THEN
+        RETURN QUERY
+        SELECT
+            'day'::TEXT,
+            v_source,
+            v_daily,
+            COALESCE(SUM(l.total_tokens), 0)::BIGINT,
+            v_day_start + interval '1 day'
+        FROM rustygpt.usage_ledger l
+        WHERE l.user_id = p_user_id
+          AND l.created_at >= v_day_start;
+    END IF;
+
+    IF v_monthly IS NOT NULL THEN
+        RETURN QUERY
+        SELECT
+            'month'::TEXT,
+            v_source,
+            v_monthly,
+            COALESCE(SUM(l.total_tokens), 0)::BIGINT,
+            v_month_start + interval '1 month'
+        FROM rustygpt.usage_ledger l
+        WHERE l.user_id = p_user_id
+          AND l.created_at >= v_month_start;
+    END IF;
+END;
+$$;
@@ -0,0 +1,123 @@
+-- Stored procedures: audit events
+SET search_path TO rustygpt, public;
+
+-- Appends an audit event. Unset arguments fall back to the request metadata the
+-- server stores in transaction-local settings (see services::audit_service).
+CREATE OR REPLACE FUNCTION rustygpt.sp_audit_record(
+    p_action TEXT,
+    p_target_type TEXT,
+    p_target_id TEXT,
+    p_metadata JSONB DEFAULT '{}'::JSONB,
+    p_actor UUID DEFAULT NULL,
+    p_ip TEXT DEFAULT NULL,
+    p_user_agent TEXT DEFAULT NULL,
+    p_request_id TEXT DEFAULT NULL
+)
+RETURNS BIGINT
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_id BIGINT;
+BEGIN
+    INSERT INTO rustygpt.audit_events (
+        actor_id,
+        action,
+        target_type,
+        target_id,
+        ip,
+        user_agent,
+        request_id,
+        metadata
+    ) VALUES (
+        COALESCE(p_actor, NULLIF(current_setting('app.current_user_id', true), '')::UUID),
+        p_action,
+        p_target_type,
+        p_target_id,
+        COALESCE(p_ip, NULLIF(current_setting('app.client_ip', true), '')),
+        COALESCE(p_user_agent, NULLIF(current_setting('app.user_agent', true), '')),
+        COALESCE(p_request_id, NULLIF(current_setting('app.request_id', true), '')),
+        COALESCE(p_metadata, '{}'::JSONB)
+    )
+    RETURNING id INTO v_id;
+
+    RETURN v_id;
+END;
+$$;
+
+-- Newest-first page of audit events; admin only. `p_action` matches exactly or
+-- as a dotted prefix ('membership' matches 'membership.add').
+CREATE OR REPLACE FUNCTION rustygpt.sp_audit_list(
+    p_actor_id UUID DEFAULT NULL,
+    p_action TEXT DEFAULT NULL,
+    p_target_type TEXT DEFAULT NULL,
+    p_target_id TEXT DEFAULT NULL,
+    p_from TIMESTAMPTZ DEFAULT NULL,
+    p_to TIMESTAMPTZ DEFAULT NULL,
+    p_before BIGINT DEFAULT NULL,
+    p_limit INTEGER DEFAULT 100
+)
+RETURNS TABLE (
+    id BIGINT,
+    occurred_at TIMESTAMPTZ,
+    actor_id UUID,
+    actor_username TEXT,
+    action TEXT,
+    target_type TEXT,
+    target_id TEXT,
+    ip TEXT,
+    user_agent TEXT,
+    request_id TEXT,
+    metadata JSONB
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    IF NOT EXISTS (
+        SELECT 1 FROM rustygpt.user_roles ur
+        WHERE ur.user_id = v_actor AND ur.role = 'admin'
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: audit log requires the admin role';
+    END IF;
+
+    IF p_limit IS NULL OR p_limit < 1 OR p_limit > 1000 THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: limit must be between 1 and 1000';
+    END IF;
+
+    RETURN QUERY
+    SELECT
+        a.id,
+        a.occurred_at,
+        a.actor_id,
+        u.username::TEXT,
+        a.action,
+        a.target_type,
+        a.target_id,
+        a.ip,
+        a.user_agent,
+        a.request_id,
+        a.metadata
+    FROM rustygpt.audit_events a
+    LEFT JOIN rustygpt.users u ON u.id = a.actor_id
+    WHERE (p_actor_id IS NULL OR a.actor_id = p_actor_id)
+      AND (p_action IS NULL OR a.action = p_action OR a.action LIKE p_action || '.%')
+      AND (p_target_type IS NULL OR a.target_type = p_target_type)
+      AND (p_target_id IS NULL OR a.target_id = p_target_id)
+      AND (p_from IS NULL OR a.occurred_at >= p_from)
+      AND (p_to IS NULL OR a.occurred_at < p_to)
+      AND (p_before IS NULL OR a.id < p_before)
+    ORDER BY a.id DESC
+    LIMIT p_limit;
+END;
+$$;
@@ -0,0 +1,293 @@
+-- Stored procedures: two-factor authentication
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_state(p_user_id UUID)
+RETURNS TABLE (
+    enabled BOOLEAN,
+    required BOOLEAN,
+    secret BYTEA,
+    recovery_codes_remaining BIGINT
+)
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT
+        t.enabled_at IS NOT NULL,
+        EXISTS (
+            SELECT 1
+            FROM rustygpt.user_roles ur
+            JOIN rustygpt.mfa_role_requirements req ON req.role = ur.role
+            WHERE ur.user_id = p_user_id
+        ),
+        t.secret,
+        (
+            SELECT COUNT(*)
+            FROM rustygpt.user_recovery_codes rc
+            WHERE rc.user_id = p_user_id AND rc.used_at IS NULL
+        )
+    FROM (SELECT p_user_id AS user_id) target
+    LEFT JOIN rustygpt.user_totp t ON t.user_id = target.user_id;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_challenge_create(
+    p_user_id UUID,
+    p_token_hash BYTEA,
+    p_ttl_seconds INTEGER
+)
+RETURNS TIMESTAMPTZ
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_expires TIMESTAMPTZ := clock_timestamp() + make_interval(secs => p_ttl_seconds);
+BEGIN
+    DELETE FROM rustygpt.mfa_login_challenges c
+    WHERE c.user_id = p_user_id
+      AND (c.expires_at <= clock_timestamp() OR c.consumed_at IS NOT NULL);
+
+    INSERT INTO rustygpt.mfa_login_challenges (user_id, token_hash, expires_at)
+    VALUES (p_user_id, p_token_hash, v_expires);
+
+    RETURN v_expires;
+END;
+$$;
+
+-- Counts an attempt against a live challenge and returns its user, or NULL when the
+-- challenge is unknown, expired, used, or has run out of attempts.
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_challenge_attempt(
+    p_token_hash BYTEA,
+    p_max_attempts INTEGER
+)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_user_id UUID;
+BEGIN
+    UPDATE rustygpt.mfa_login_challenges c
+    SET attempts = c.attempts + 1
+    WHERE c.token_hash = p_token_hash
+      AND c.consumed_at IS NULL
+      AND c.expires_at > clock_timestamp()
+      AND c.attempts < p_max_attempts
+    RETURNING c.user_id INTO v_user_id;
+
+    RETURN v_user_id;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_challenge_consume(p_token_hash BYTEA)
+RETURNS VOID
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    UPDATE rustygpt.mfa_login_challenges
+    SET consumed_at = clock_timestamp()
+    WHERE token_hash = p_token_hash;
+$$;
+
+-- Stores a pending secret; it only takes effect once sp_mfa_totp_enable sees a valid code.
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_totp_stage(
+    p_user_id UUID,
+    p_secret BYTEA
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM rustygpt.user_totp t
+        WHERE t.user_id = p_user_id AND t.enabled_at IS NOT NULL
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: two-factor authentication is already enabled';
+    END IF;
+
+    INSERT INTO rustygpt.user_totp (user_id, secret)
+    VALUES (p_user_id, p_secret)
+    ON CONFLICT (user_id) DO UPDATE
+    SET secret = EXCLUDED.secret,
+        created_at = now(),
+        last_used_step = NULL;
+END;
+$$;
+
+-- Records `p_step` as used; FALSE means the code was already accepted once.
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_totp_accept_step(
+    p_user_id UUID,
+    p_step BIGINT
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    UPDATE rustygpt.user_totp t
+    SET last_used_step = p_step
+    WHERE t.user_id = p_user_id
+      AND (t.last_used_step IS NULL OR t.last_used_step < p_step);
+
+    RETURN FOUND;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_totp_enable(
+    p_user_id UUID,
+    p_step BIGINT,
+    p_code_hashes BYTEA[]
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    UPDATE rustygpt.user_totp t
+    SET enabled_at = clock_timestamp(),
+        last_used_step = p_step
+    WHERE t.user_id = p_user_id
+      AND t.enabled_at IS NULL;
+
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    DELETE FROM rustygpt.user_recovery_codes rc WHERE rc.user_id = p_user_id;
+    INSERT INTO rustygpt.user_recovery_codes (user_id, code_hash)
+    SELECT p_user_id, hash FROM unnest(p_code_hashes) AS hash;
+
+    PERFORM rustygpt.sp_audit_record('mfa.enable', 'user', p_user_id::TEXT, '{}'::JSONB, p_user_id);
+    RETURN TRUE;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_recovery_replace(
+    p_user_id UUID,
+    p_code_hashes BYTEA[]
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    DELETE FROM rustygpt.user_recovery_codes rc WHERE rc.user_id = p_user_id;
+    INSERT INTO rustygpt.user_recovery_codes (user_id, code_hash)
+    SELECT p_user_id, hash FROM unnest(p_code_hashes) AS hash;
+
+    PERFORM rustygpt.sp_audit_record(
+        'mfa.recovery_codes_regenerate', 'user', p_user_id::TEXT, '{}'::JSONB, p_user_id
+    );
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_recovery_consume(
+    p_user_id UUID,
+    p_code_hash BYTEA
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_remaining BIGINT;
+BEGIN
+    UPDATE rustygpt.user_recovery_codes rc
+    SET used_at = clock_timestamp()
+    WHERE rc.user_id = p_user_id
+      AND rc.code_hash = p_code_hash
+      AND rc.used_at IS NULL;
+
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    SELECT COUNT(*) INTO v_remaining
+    FROM rustygpt.user_recovery_codes rc
+    WHERE rc.user_id = p_user_id AND rc.used_at IS NULL;
+
+    PERFORM rustygpt.sp_audit_record(
+        'mfa.recovery_code_used',
+        'user',
+        p_user_id::TEXT,
+        jsonb_build_object('remaining', v_remaining),
+        p_user_id
+    );
+    RETURN TRUE;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_disable(p_user_id UUID)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1
+        FROM rustygpt.user_roles ur
+        JOIN rustygpt.mfa_role_requirements req ON req.role = ur.role
+        WHERE ur.user_id = p_user_id
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: two-factor authentication is required for your role';
+    END IF;
+
+    DELETE FROM rustygpt.user_totp t WHERE t.user_id = p_user_id;
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+    DELETE FROM rustygpt.user_recovery_codes rc WHERE rc.user_id = p_user_id;
+
+    PERFORM rustygpt.sp_audit_record('mfa.disable', 'user', p_user_id::TEXT, '{}'::JSONB, p_user_id);
+    RETURN TRUE;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_policy_list()
+RETURNS TABLE (role TEXT)
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT req.role::TEXT FROM rustygpt.mfa_role_requirements req ORDER BY req.role;
+$$;
+
+-- Replaces the set of roles that must use a second factor.
+CREATE OR REPLACE FUNCTION rustygpt.sp_mfa_policy_set(p_roles TEXT[])
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID := NULLIF(current_setting('app.current_user_id', true), '')::UUID;
+BEGIN
+    DELETE FROM rustygpt.mfa_role_requirements req
+    WHERE req.role::TEXT <> ALL (COALESCE(p_roles, ARRAY[]::TEXT[]));
+
+    INSERT INTO rustygpt.mfa_role_requirements (role, updated_by)
+    SELECT DISTINCT r::rustygpt.user_role, v_actor
+    FROM unnest(COALESCE(p_roles, ARRAY[]::TEXT[])) AS r
+    ON CONFLICT (role) DO NOTHING;
+
+    PERFORM rustygpt.sp_audit_record(
+        'mfa.policy_update',
+        'mfa_policy',
+        NULL,
+        jsonb_build_object('required_roles', to_jsonb(COALESCE(p_roles, ARRAY[]::TEXT[])))
+    );
+END;
+$$;
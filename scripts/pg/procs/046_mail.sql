@@ -0,0 +1,324 @@
+-- Stored procedures: mail outbox, password reset and email verification
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mail_enqueue(
+    p_template TEXT,
+    p_to_address TEXT,
+    p_subject TEXT,
+    p_body_text TEXT
+)
+RETURNS UUID
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    INSERT INTO rustygpt.mail_outbox (template, to_address, subject, body_text)
+    VALUES (p_template, p_to_address, p_subject, p_body_text)
+    RETURNING id;
+$$;
+
+-- Leases due messages to one worker: pushing next_attempt_at forward hides them from other
+-- claims until the lease runs out, so a crashed worker's batch is retried later.
+CREATE OR REPLACE FUNCTION rustygpt.sp_mail_claim(
+    p_limit INTEGER,
+    p_lease_seconds INTEGER
+)
+RETURNS TABLE (
+    id UUID,
+    template TEXT,
+    to_address TEXT,
+    subject TEXT,
+    body_text TEXT,
+    attempts INTEGER
+)
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    WITH due AS (
+        SELECT o.id
+        FROM rustygpt.mail_outbox o
+        WHERE o.sent_at IS NULL
+          AND o.failed_at IS NULL
+          AND o.next_attempt_at <= clock_timestamp()
+        ORDER BY o.next_attempt_at
+        LIMIT GREATEST(p_limit, 1)
+        FOR UPDATE SKIP LOCKED
+    )
+    UPDATE rustygpt.mail_outbox o
+    SET next_attempt_at = clock_timestamp() + make_interval(secs => p_lease_seconds)
+    FROM due
+    WHERE o.id = due.id
+    RETURNING o.id, o.template, o.to_address, o.subject, o.body_text, o.attempts;
+$$;
+
+-- Bodies carry one-time reset, verification and invite links, so they are cleared as soon as
+-- the message leaves the queue; the rest of the row stays for inspection.
+CREATE OR REPLACE FUNCTION rustygpt.sp_mail_mark_sent(p_id UUID)
+RETURNS VOID
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    UPDATE rustygpt.mail_outbox
+    SET sent_at = clock_timestamp(),
+        attempts = attempts + 1,
+        last_error = NULL,
+        body_text = ''
+    WHERE id = p_id;
+$$;
+
+-- Records a failed delivery. Retries back off exponentially from 30 seconds up to an hour;
+-- returns FALSE once `p_max_attempts` is reached and the message is given up on, which also
+-- clears its body.
+CREATE OR REPLACE FUNCTION rustygpt.sp_mail_mark_failed(
+    p_id UUID,
+    p_error TEXT,
+    p_max_attempts INTEGER
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_attempts INTEGER;
+BEGIN
+    UPDATE rustygpt.mail_outbox o
+    SET attempts = o.attempts + 1,
+        last_error = left(p_error, 2000)
+    WHERE o.id = p_id
+    RETURNING o.attempts INTO v_attempts;
+
+    IF v_attempts IS NULL THEN
+        RETURN FALSE;
+    END IF;
+
+    IF v_attempts >= p_max_attempts THEN
+        UPDATE rustygpt.mail_outbox
+        SET failed_at = clock_timestamp(),
+            body_text = ''
+        WHERE id = p_id;
+        RETURN FALSE;
+    END IF;
+
+    UPDATE rustygpt.mail_outbox
+    SET next_attempt_at = clock_timestamp()
+        + make_interval(secs => LEAST(3600, 30 * power(2, v_attempts - 1)))
+    WHERE id = p_id;
+    RETURN TRUE;
+END;
+$$;
+
+-- Issues a reset token for an active account. Unknown or disabled addresses return no row so
+-- callers can answer identically either way.
+CREATE OR REPLACE FUNCTION rustygpt.sp_password_reset_request(
+    p_email TEXT,
+    p_token_hash BYTEA,
+    p_ttl_seconds INTEGER
+)
+RETURNS TABLE (
+    user_id UUID,
+    email TEXT,
+    display_name TEXT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_user rustygpt.users%ROWTYPE;
+BEGIN
+    SELECT * INTO v_user
+    FROM rustygpt.users u
+    WHERE u.email = p_email::citext
+      AND u.disabled_at IS NULL;
+
+    IF NOT FOUND THEN
+        RETURN;
+    END IF;
+
+    -- Only the newest link works.
+    UPDATE rustygpt.password_reset_tokens t
+    SET used_at = clock_timestamp()
+    WHERE t.user_id = v_user.id AND t.used_at IS NULL;
+
+    INSERT INTO rustygpt.password_reset_tokens (user_id, token_hash, expires_at)
+    VALUES (v_user.id, p_token_hash, clock_timestamp() + make_interval(secs => p_ttl_seconds));
+
+    PERFORM rustygpt.sp_audit_record(
+        'auth.password_reset_request', 'user', v_user.id::TEXT, '{}'::JSONB, NULL
+    );
+
+    user_id := v_user.id;
+    email := v_user.email::TEXT;
+    display_name := COALESCE(v_user.display_name, v_user.username::TEXT);
+    RETURN NEXT;
+END;
+$$;
+
+-- Consumes a reset token, stores the new hash and signs the account out everywhere.
+CREATE OR REPLACE FUNCTION rustygpt.sp_password_reset_complete(
+    p_token_hash BYTEA,
+    p_password_hash TEXT
+)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_user_id UUID;
+BEGIN
+    UPDATE rustygpt.password_reset_tokens t
+    SET used_at = clock_timestamp()
+    FROM rustygpt.users u
+    WHERE t.token_hash = p_token_hash
+      AND t.used_at IS NULL
+      AND t.expires_at > clock_timestamp()
+      AND u.id = t.user_id
+      AND u.disabled_at IS NULL
+    RETURNING t.user_id INTO v_user_id;
+
+    IF v_user_id IS NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: reset link is invalid or has expired';
+    END IF;
+
+    UPDATE rustygpt.users
+    SET password_hash = p_password_hash,
+        updated_at = now()
+    WHERE id = v_user_id;
+
+    UPDATE rustygpt.user_sessions
+    SET revoked_at = clock_timestamp(),
+        rotation_reason = COALESCE(rotation_reason, 'password_reset')
+    WHERE user_id = v_user_id AND revoked_at IS NULL;
+
+    PERFORM rustygpt.sp_audit_record(
+        'auth.password_reset', 'user', v_user_id::TEXT, '{}'::JSONB, v_user_id
+    );
+    RETURN v_user_id;
+END;
+$$;
+
+-- Self-service change; the caller has already checked the current password. Every other
+-- session is revoked so a stolen cookie stops working.
+CREATE OR REPLACE FUNCTION rustygpt.sp_password_change(
+    p_user_id UUID,
+    p_password_hash TEXT,
+    p_keep_session_id UUID
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_revoked INTEGER;
+BEGIN
+    UPDATE rustygpt.users
+    SET password_hash = p_password_hash,
+        updated_at = now()
+    WHERE id = p_user_id;
+
+    UPDATE rustygpt.user_sessions
+    SET revoked_at = clock_timestamp(),
+        revoked_by = p_user_id,
+        rotation_reason = COALESCE(rotation_reason, 'password_change')
+    WHERE user_id = p_user_id
+      AND revoked_at IS NULL
+      AND id IS DISTINCT FROM p_keep_session_id;
+    GET DIAGNOSTICS v_revoked = ROW_COUNT;
+
+    PERFORM rustygpt.sp_audit_record(
+        'auth.password_change',
+        'user',
+        p_user_id::TEXT,
+        jsonb_build_object('revoked_sessions', v_revoked),
+        p_user_id
+    );
+END;
+$$;
+
+-- Issues a verification token for the account's current address; no row when it is already
+-- verified.
+CREATE OR REPLACE FUNCTION rustygpt.sp_email_verification_create(
+    p_user_id UUID,
+    p_token_hash BYTEA,
+    p_ttl_seconds INTEGER
+)
+RETURNS TABLE (
+    email TEXT,
+    display_name TEXT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_user rustygpt.users%ROWTYPE;
+BEGIN
+    SELECT * INTO v_user FROM rustygpt.users u WHERE u.id = p_user_id;
+    IF NOT FOUND THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: user not found';
+    END IF;
+    IF v_user.email_verified_at IS NOT NULL THEN
+        RETURN;
+    END IF;
+
+    UPDATE rustygpt.email_verification_tokens t
+    SET used_at = clock_timestamp()
+    WHERE t.user_id = p_user_id AND t.used_at IS NULL;
+
+    INSERT INTO rustygpt.email_verification_tokens (user_id, email, token_hash, expires_at)
+    VALUES (
+        p_user_id,
+        v_user.email,
+        p_token_hash,
+        clock_timestamp() + make_interval(secs => p_ttl_seconds)
+    );
+
+    email := v_user.email::TEXT;
+    display_name := COALESCE(v_user.display_name, v_user.username::TEXT);
+    RETURN NEXT;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_email_verification_confirm(p_token_hash BYTEA)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_user_id UUID;
+BEGIN
+    UPDATE rustygpt.email_verification_tokens t
+    SET used_at = clock_timestamp()
+    FROM rustygpt.users u
+    WHERE t.token_hash = p_token_hash
+      AND t.used_at IS NULL
+      AND t.expires_at > clock_timestamp()
+      AND u.id = t.user_id
+      AND u.email = t.email
+    RETURNING t.user_id INTO v_user_id;
+
+    IF v_user_id IS NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: verification link is invalid or has expired';
+    END IF;
+
+    UPDATE rustygpt.users
+    SET email_verified_at = COALESCE(email_verified_at, clock_timestamp()),
+        updated_at = now()
+    WHERE id = v_user_id;
+
+    PERFORM rustygpt.sp_audit_record(
+        'user.email_verify', 'user', v_user_id::TEXT, '{}'::JSONB, v_user_id
+    );
+    RETURN v_user_id;
+END;
+$$;
@@ -0,0 +1,174 @@
+-- Stored procedures: OpenID Connect login
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_oidc_request_create(
+    p_state_hash BYTEA,
+    p_provider TEXT,
+    p_nonce TEXT,
+    p_pkce_verifier TEXT,
+    p_return_to TEXT,
+    p_ttl_seconds INTEGER
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    DELETE FROM rustygpt.oidc_login_requests r
+    WHERE r.expires_at <= clock_timestamp();
+
+    INSERT INTO rustygpt.oidc_login_requests (
+        state_hash, provider, nonce, pkce_verifier, return_to, expires_at
+    )
+    VALUES (
+        p_state_hash,
+        p_provider,
+        p_nonce,
+        p_pkce_verifier,
+        COALESCE(p_return_to, '/'),
+        clock_timestamp() + make_interval(secs => p_ttl_seconds)
+    );
+END;
+$$;
+
+-- Removes and returns a live request; a state can only be redeemed once.
+CREATE OR REPLACE FUNCTION rustygpt.sp_oidc_request_consume(
+    p_state_hash BYTEA,
+    p_provider TEXT
+)
+RETURNS TABLE (
+    nonce TEXT,
+    pkce_verifier TEXT,
+    return_to TEXT
+)
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    DELETE FROM rustygpt.oidc_login_requests r
+    WHERE r.state_hash = p_state_hash
+      AND r.provider = p_provider
+      AND r.expires_at > clock_timestamp()
+    RETURNING r.nonce, r.pkce_verifier, r.return_to;
+$$;
+
+-- Maps a verified ID token to a local account: an existing identity first, then an account
+-- with the same verified email (when linking is allowed), then a new account (when sign-up
+-- is allowed). Returns the user and whether the identity was 'existing', 'linked' or 'created'.
+CREATE OR REPLACE FUNCTION rustygpt.sp_oidc_resolve_user(
+    p_provider TEXT,
+    p_subject TEXT,
+    p_email TEXT,
+    p_email_verified BOOLEAN,
+    p_username TEXT,
+    p_display_name TEXT,
+    p_link_by_email BOOLEAN,
+    p_allow_signup BOOLEAN
+)
+RETURNS TABLE (
+    user_id UUID,
+    outcome TEXT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_user_id UUID;
+    v_username TEXT;
+BEGIN
+    UPDATE rustygpt.user_identities i
+    SET last_login_at = clock_timestamp(),
+        email = COALESCE(p_email::citext, i.email)
+    WHERE i.provider = p_provider AND i.subject = p_subject
+    RETURNING i.user_id INTO v_user_id;
+
+    IF v_user_id IS NOT NULL THEN
+        user_id := v_user_id;
+        outcome := 'existing';
+        RETURN NEXT;
+        RETURN;
+    END IF;
+
+    IF p_email IS NULL OR btrim(p_email) = '' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: identity provider did not return an email address';
+    END IF;
+
+    SELECT u.id INTO v_user_id
+    FROM rustygpt.users u
+    WHERE u.email = p_email::citext;
+
+    IF v_user_id IS NOT NULL THEN
+        -- An unverified email could belong to anyone, so it never unlocks an existing account.
+        IF NOT (p_email_verified AND p_link_by_email) THEN
+            RAISE EXCEPTION USING
+                ERRCODE = 'P0001',
+                MESSAGE = 'RGP.403: an account with this email already exists';
+        END IF;
+
+        INSERT INTO rustygpt.user_identities (provider, subject, user_id, email, last_login_at)
+        VALUES (p_provider, p_subject, v_user_id, p_email, clock_timestamp());
+
+        UPDATE rustygpt.users u
+        SET email_verified_at = COALESCE(u.email_verified_at, clock_timestamp())
+        WHERE u.id = v_user_id;
+
+        PERFORM rustygpt.sp_audit_record(
+            'user.identity_link',
+            'user',
+            v_user_id::TEXT,
+            jsonb_build_object('provider', p_provider, 'subject', p_subject, 'outcome', 'linked'),
+            v_user_id
+        );
+
+        user_id := v_user_id;
+        outcome := 'linked';
+        RETURN NEXT;
+        RETURN;
+    END IF;
+
+    IF NOT p_allow_signup THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: sign-up through this provider is disabled';
+    END IF;
+
+    v_username := COALESCE(NULLIF(btrim(p_username), ''), split_part(p_email, '@', 1));
+    IF EXISTS (SELECT 1 FROM rustygpt.users u WHERE u.username = v_username::citext) THEN
+        v_username := v_username || '-' || substr(md5(p_provider || ':' || p_subject), 1, 6);
+    END IF;
+
+    -- '!' is not a valid Argon2 hash, so the account cannot sign in with a password until
+    -- one is set through a reset.
+    INSERT INTO rustygpt.users (username, email, display_name, password_hash, email_verified_at)
+    VALUES (
+        v_username,
+        p_email,
+        NULLIF(btrim(p_display_name), ''),
+        '!',
+        CASE WHEN p_email_verified THEN clock_timestamp() END
+    )
+    RETURNING id INTO v_user_id;
+
+    INSERT INTO rustygpt.user_roles (user_id, role)
+    VALUES (v_user_id, 'member');
+
+    INSERT INTO rustygpt.user_identities (provider, subject, user_id, email, last_login_at)
+    VALUES (p_provider, p_subject, v_user_id, p_email, clock_timestamp());
+
+    PERFORM rustygpt.sp_audit_record(
+        'user.identity_link',
+        'user',
+        v_user_id::TEXT,
+        jsonb_build_object('provider', p_provider, 'subject', p_subject, 'outcome', 'created'),
+        v_user_id
+    );
+
+    user_id := v_user_id;
+    outcome := 'created';
+    RETURN NEXT;
+END;
+$$;
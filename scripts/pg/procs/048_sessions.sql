@@ -0,0 +1,86 @@
+-- Stored procedures: self-service and admin session management
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_auth_list_sessions(
+    p_user_id UUID
+)
+RETURNS TABLE (
+    id UUID,
+    created_at TIMESTAMPTZ,
+    last_seen_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    absolute_expires_at TIMESTAMPTZ,
+    user_agent TEXT,
+    ip TEXT
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT s.id,
+           s.created_at,
+           s.last_seen_at,
+           s.expires_at,
+           s.absolute_expires_at,
+           s.user_agent,
+           host(s.ip)
+    FROM rustygpt.user_sessions s
+    WHERE s.user_id = p_user_id
+      AND s.revoked_at IS NULL
+      AND s.expires_at > now()
+      AND s.absolute_expires_at > now()
+    ORDER BY s.last_seen_at DESC;
+$$;
+
+-- Revokes one session of p_user_id. Returns FALSE when the session does not
+-- belong to the user or is no longer active, so callers cannot probe other ids.
+CREATE OR REPLACE FUNCTION rustygpt.sp_auth_revoke_user_session(
+    p_user_id UUID,
+    p_session_id UUID,
+    p_reason TEXT,
+    p_revoker UUID
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    UPDATE rustygpt.user_sessions
+    SET revoked_at = clock_timestamp(),
+        revoked_by = p_revoker,
+        rotation_reason = COALESCE(p_reason, rotation_reason)
+    WHERE id = p_session_id
+      AND user_id = p_user_id
+      AND revoked_at IS NULL;
+    RETURN FOUND;
+END;
+$$;
+
+-- Revokes every active session of p_user_id except p_keep_session_id (if any).
+CREATE OR REPLACE FUNCTION rustygpt.sp_auth_revoke_user_sessions(
+    p_user_id UUID,
+    p_keep_session_id UUID,
+    p_reason TEXT,
+    p_revoker UUID
+)
+RETURNS INTEGER
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_revoked INTEGER;
+BEGIN
+    UPDATE rustygpt.user_sessions
+    SET revoked_at = clock_timestamp(),
+        revoked_by = p_revoker,
+        rotation_reason = COALESCE(p_reason, rotation_reason)
+    WHERE user_id = p_user_id
+      AND revoked_at IS NULL
+      AND id IS DISTINCT FROM p_keep_session_id;
+    GET DIAGNOSTICS v_revoked = ROW_COUNT;
+    RETURN v_revoked;
+END;
+$$;
@@ -0,0 +1,85 @@
+-- Stored procedures: failed login tracking, lockout and admin unlock
+SET search_path TO rustygpt, public;
+
+-- Counts a wrong password. Once the count reaches p_threshold the account is locked for
+-- p_base_seconds, doubling with every further failure up to p_max_seconds. Failures older than
+-- p_max_seconds are forgotten. A threshold of zero only counts.
+CREATE OR REPLACE FUNCTION rustygpt.sp_auth_login_failure(
+    p_user_id UUID,
+    p_threshold INTEGER,
+    p_base_seconds INTEGER,
+    p_max_seconds INTEGER
+)
+RETURNS TABLE (
+    failed_count INTEGER,
+    locked_until TIMESTAMPTZ
+)
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    WITH counted AS (
+        SELECT u.id,
+               CASE
+                   WHEN u.last_failed_login_at IS NULL
+                        OR u.last_failed_login_at < now() - make_interval(secs => p_max_seconds)
+                       THEN 1
+                   ELSE u.failed_login_count + 1
+               END AS failures
+        FROM rustygpt.users u
+        WHERE u.id = p_user_id
+        FOR UPDATE
+    )
+    UPDATE rustygpt.users u
+    SET failed_login_count = counted.failures,
+        last_failed_login_at = now(),
+        locked_until = CASE
+            WHEN p_threshold > 0 AND counted.failures >= p_threshold THEN
+                now() + make_interval(secs => LEAST(
+                    p_base_seconds::DOUBLE PRECISION
+                        * power(2, LEAST(counted.failures - p_threshold, 30)),
+                    p_max_seconds::DOUBLE PRECISION
+                ))
+            ELSE NULL
+        END
+    FROM counted
+    WHERE u.id = counted.id
+    RETURNING u.failed_login_count, u.locked_until;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_auth_login_success(p_user_id UUID)
+RETURNS VOID
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    UPDATE rustygpt.users
+    SET failed_login_count = 0,
+        last_failed_login_at = NULL,
+        locked_until = NULL
+    WHERE id = p_user_id
+      AND (failed_login_count <> 0 OR locked_until IS NOT NULL);
+$$;
+
+-- Clears the lockout and failure count. Returns one row holding the lock that was lifted (NULL
+-- when the account was not locked), or no row when the user does not exist.
+CREATE OR REPLACE FUNCTION rustygpt.sp_auth_unlock_user(p_user_id UUID)
+RETURNS TABLE (previous_locked_until TIMESTAMPTZ)
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    WITH previous AS (
+        SELECT u.id, u.locked_until
+        FROM rustygpt.users u
+        WHERE u.id = p_user_id
+        FOR UPDATE
+    )
+    UPDATE rustygpt.users u
+    SET failed_login_count = 0,
+        last_failed_login_at = NULL,
+        locked_until = NULL
+    FROM previous
+    WHERE u.id = previous.id
+    RETURNING CASE WHEN previous.locked_until > now() THEN previous.locked_until END;
+$$;
@@ -0,0 +1,340 @@
+-- Stored procedures: admin user management (listing, creation, disable/enable, global roles,
+-- password resets). Callers open the transaction with an audit context, so the acting admin is
+-- read from app.current_user_id.
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_admin_user_list(
+    p_user_id UUID,
+    p_search TEXT,
+    p_role TEXT,
+    p_disabled BOOLEAN,
+    p_limit INTEGER,
+    p_offset INTEGER
+)
+RETURNS TABLE (
+    id UUID,
+    username TEXT,
+    email TEXT,
+    display_name TEXT,
+    roles TEXT[],
+    created_at TIMESTAMPTZ,
+    disabled_at TIMESTAMPTZ,
+    locked_until TIMESTAMPTZ,
+    email_verified_at TIMESTAMPTZ,
+    mfa_enabled BOOLEAN,
+    last_seen_at TIMESTAMPTZ,
+    total_count BIGINT
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT
+        u.id,
+        u.username::TEXT,
+        u.email::TEXT,
+        u.display_name,
+        ARRAY(
+            SELECT r.role::TEXT
+            FROM rustygpt.user_roles r
+            WHERE r.user_id = u.id
+            ORDER BY r.role
+        ),
+        u.created_at,
+        u.disabled_at,
+        CASE WHEN u.locked_until > now() THEN u.locked_until END,
+        u.email_verified_at,
+        EXISTS (
+            SELECT 1
+            FROM rustygpt.user_totp t
+            WHERE t.user_id = u.id AND t.enabled_at IS NOT NULL
+        ),
+        (SELECT max(s.last_seen_at) FROM rustygpt.user_sessions s WHERE s.user_id = u.id),
+        count(*) OVER ()
+    FROM rustygpt.users u
+    WHERE (p_user_id IS NULL OR u.id = p_user_id)
+      AND (
+          NULLIF(btrim(p_search), '') IS NULL
+          OR strpos(lower(u.username::TEXT), lower(btrim(p_search))) > 0
+          OR strpos(lower(u.email::TEXT), lower(btrim(p_search))) > 0
+          OR strpos(lower(COALESCE(u.display_name, '')), lower(btrim(p_search))) > 0
+      )
+      AND (
+          p_role IS NULL
+          OR EXISTS (
+              SELECT 1
+              FROM rustygpt.user_roles r
+              WHERE r.user_id = u.id AND r.role = p_role::rustygpt.user_role
+          )
+      )
+      AND (p_disabled IS NULL OR (u.disabled_at IS NOT NULL) = p_disabled)
+    ORDER BY u.created_at DESC, u.id
+    LIMIT GREATEST(p_limit, 1)
+    OFFSET GREATEST(p_offset, 0);
+$$;
+
+-- Global roles with their holder counts, for the roles page.
+CREATE OR REPLACE FUNCTION rustygpt.sp_admin_role_summary()
+RETURNS TABLE (
+    role TEXT,
+    user_count BIGINT,
+    mfa_required BOOLEAN
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT
+        e.role::TEXT,
+        count(ur.user_id),
+        EXISTS (SELECT 1 FROM rustygpt.mfa_role_requirements m WHERE m.role = e.role)
+    FROM unnest(enum_range(NULL::rustygpt.user_role)) AS e(role)
+    LEFT JOIN rustygpt.user_roles ur ON ur.role = e.role
+    GROUP BY e.role
+    ORDER BY e.role;
+$$;
+
+-- Refuses changes that would leave no enabled admin once p_user_id loses admin rights.
+CREATE OR REPLACE FUNCTION rustygpt.sp_admin_assert_other_admin(p_user_id UUID)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM rustygpt.user_roles r WHERE r.user_id = p_user_id AND r.role = 'admin'
+    ) AND NOT EXISTS (
+        SELECT 1
+        FROM rustygpt.user_roles r
+        JOIN rustygpt.users u ON u.id = r.user_id
+        WHERE r.role = 'admin'
+          AND u.disabled_at IS NULL
+          AND u.id <> p_user_id
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: at least one active admin is required';
+    END IF;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_admin_user_create(
+    p_username TEXT,
+    p_email TEXT,
+    p_display_name TEXT,
+    p_password_hash TEXT,
+    p_roles TEXT[]
+)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID := NULLIF(current_setting('app.current_user_id', true), '')::UUID;
+    v_username TEXT := btrim(p_username);
+    v_email TEXT := btrim(p_email);
+    v_user_id UUID;
+BEGIN
+    IF v_username IS NULL OR v_username = '' THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: username required';
+    END IF;
+
+    IF v_email IS NULL OR position('@' IN v_email) = 0 THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: valid email required';
+    END IF;
+
+    IF EXISTS (SELECT 1 FROM rustygpt.users WHERE email = v_email::CITEXT) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: email already in use';
+    END IF;
+
+    IF EXISTS (SELECT 1 FROM rustygpt.users WHERE username = v_username::CITEXT) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: username already taken';
+    END IF;
+
+    INSERT INTO rustygpt.users (username, email, display_name, password_hash)
+    VALUES (v_username, v_email, NULLIF(btrim(p_display_name), ''), p_password_hash)
+    RETURNING id INTO v_user_id;
+
+    INSERT INTO rustygpt.user_roles (user_id, role, granted_by)
+    SELECT DISTINCT v_user_id, r::rustygpt.user_role, v_actor
+    FROM unnest(COALESCE(p_roles, ARRAY[]::TEXT[])) AS r;
+
+    PERFORM rustygpt.sp_audit_record(
+        'user.create',
+        'user',
+        v_user_id::TEXT,
+        jsonb_build_object('username', v_username, 'email', v_email, 'roles', p_roles)
+    );
+    RETURN v_user_id;
+END;
+$$;
+
+-- Disabling revokes every session; enabling only clears the flag. Returns whether anything
+-- changed.
+CREATE OR REPLACE FUNCTION rustygpt.sp_admin_user_set_disabled(
+    p_user_id UUID,
+    p_disabled BOOLEAN
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID := NULLIF(current_setting('app.current_user_id', true), '')::UUID;
+    v_disabled_at TIMESTAMPTZ;
+    v_revoked INTEGER;
+BEGIN
+    SELECT u.disabled_at INTO v_disabled_at
+    FROM rustygpt.users u
+    WHERE u.id = p_user_id
+    FOR UPDATE;
+
+    IF NOT FOUND THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: user not found';
+    END IF;
+
+    IF p_disabled THEN
+        IF v_disabled_at IS NOT NULL THEN
+            RETURN FALSE;
+        END IF;
+        IF p_user_id = v_actor THEN
+            RAISE EXCEPTION USING
+                ERRCODE = 'P0001',
+                MESSAGE = 'RGP.VALIDATION: you cannot disable your own account';
+        END IF;
+        PERFORM rustygpt.sp_admin_assert_other_admin(p_user_id);
+
+        UPDATE rustygpt.users
+        SET disabled_at = now(),
+            updated_at = now()
+        WHERE id = p_user_id;
+
+        v_revoked := rustygpt.sp_auth_revoke_user_sessions(
+            p_user_id, NULL, 'user_disabled', v_actor
+        );
+        PERFORM rustygpt.sp_audit_record(
+            'user.disable', 'user', p_user_id::TEXT,
+            jsonb_build_object('revoked_sessions', v_revoked)
+        );
+        RETURN TRUE;
+    END IF;
+
+    IF v_disabled_at IS NULL THEN
+        RETURN FALSE;
+    END IF;
+
+    UPDATE rustygpt.users
+    SET disabled_at = NULL,
+        updated_at = now()
+    WHERE id = p_user_id;
+
+    PERFORM rustygpt.sp_audit_record('user.enable', 'user', p_user_id::TEXT);
+    RETURN TRUE;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_admin_user_grant_role(
+    p_user_id UUID,
+    p_role TEXT
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID := NULLIF(current_setting('app.current_user_id', true), '')::UUID;
+    v_granted INTEGER;
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM rustygpt.users WHERE id = p_user_id) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: user not found';
+    END IF;
+
+    INSERT INTO rustygpt.user_roles (user_id, role, granted_by)
+    VALUES (p_user_id, p_role::rustygpt.user_role, v_actor)
+    ON CONFLICT (user_id, role) DO NOTHING;
+    GET DIAGNOSTICS v_granted = ROW_COUNT;
+
+    IF v_granted > 0 THEN
+        PERFORM rustygpt.sp_audit_record(
+            'user.role_grant', 'user', p_user_id::TEXT, jsonb_build_object('role', p_role)
+        );
+    END IF;
+    RETURN v_granted > 0;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_admin_user_revoke_role(
+    p_user_id UUID,
+    p_role TEXT
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_revoked INTEGER;
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM rustygpt.users WHERE id = p_user_id) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: user not found';
+    END IF;
+
+    IF p_role = 'admin' THEN
+        PERFORM rustygpt.sp_admin_assert_other_admin(p_user_id);
+    END IF;
+
+    DELETE FROM rustygpt.user_roles
+    WHERE user_id = p_user_id AND role = p_role::rustygpt.user_role;
+    GET DIAGNOSTICS v_revoked = ROW_COUNT;
+
+    IF v_revoked > 0 THEN
+        PERFORM rustygpt.sp_audit_record(
+            'user.role_revoke', 'user', p_user_id::TEXT, jsonb_build_object('role', p_role)
+        );
+    END IF;
+    RETURN v_revoked > 0;
+END;
+$$;
+
+-- Replaces the password, clears any lockout and signs the user out everywhere.
+CREATE OR REPLACE FUNCTION rustygpt.sp_admin_user_set_password(
+    p_user_id UUID,
+    p_password_hash TEXT
+)
+RETURNS INTEGER
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID := NULLIF(current_setting('app.current_user_id', true), '')::UUID;
+    v_revoked INTEGER;
+BEGIN
+    UPDATE rustygpt.users
+    SET password_hash = p_password_hash,
+        failed_login_count = 0,
+        last_failed_login_at = NULL,
+        locked_until = NULL,
+        updated_at = now()
+    WHERE id = p_user_id;
+
+    IF NOT FOUND THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: user not found';
+    END IF;
+
+    v_revoked := rustygpt.sp_auth_revoke_user_sessions(
+        p_user_id, NULL, 'admin_password_reset', v_actor
+    );
+    PERFORM rustygpt.sp_audit_record(
+        'user.password_set', 'user', p_user_id::TEXT,
+        jsonb_build_object('revoked_sessions', v_revoked)
+    );
+    RETURN v_revoked;
+END;
+$$;
@@ -0,0 +1,64 @@
+-- Stored procedures: user preferences. The document is merged and validated by the server;
+-- these only read, lock and replace it.
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_user_preferences_get(p_user_id UUID)
+RETURNS JSONB
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT COALESCE(
+        (SELECT p.preferences FROM rustygpt.user_preferences p WHERE p.user_id = p_user_id),
+        '{}'::JSONB
+    );
+$$;
+
+-- Returns the current document with the row locked for the rest of the transaction, so
+-- concurrent updates apply one after the other.
+CREATE OR REPLACE FUNCTION rustygpt.sp_user_preferences_lock(p_user_id UUID)
+RETURNS JSONB
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_preferences JSONB;
+BEGIN
+    INSERT INTO rustygpt.user_preferences (user_id)
+    VALUES (p_user_id)
+    ON CONFLICT (user_id) DO NOTHING;
+
+    SELECT p.preferences INTO v_preferences
+    FROM rustygpt.user_preferences p
+    WHERE p.user_id = p_user_id
+    FOR UPDATE;
+
+    RETURN v_preferences;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_user_preferences_put(
+    p_user_id UUID,
+    p_preferences JSONB
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF jsonb_typeof(p_preferences) IS DISTINCT FROM 'object' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: preferences must be a JSON object';
+    END IF;
+
+    INSERT INTO rustygpt.user_preferences (user_id, preferences, updated_at)
+    VALUES (p_user_id, p_preferences, now())
+    ON CONFLICT (user_id) DO UPDATE
+    SET preferences = EXCLUDED.preferences,
+        updated_at = EXCLUDED.updated_at;
+END;
+$$;
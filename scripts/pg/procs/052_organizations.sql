@@ -0,0 +1,572 @@
+-- Stored procedures: organizations, their memberships and per-organization policy.
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_user_in_org(
+    p_user UUID,
+    p_org UUID
+)
+RETURNS BOOLEAN
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT p_org IS NULL OR EXISTS (
+        SELECT 1
+        FROM rustygpt.organization_members m
+        WHERE m.org_id = p_org
+          AND m.user_id = p_user
+    );
+$$;
+
+-- Membership guards ---------------------------------------------------------
+-- Enforced as triggers so every path that adds participants or invites (direct adds, invite
+-- acceptance, imports, forks) is covered.
+
+CREATE OR REPLACE FUNCTION rustygpt.trg_participant_org_member()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_org UUID;
+BEGIN
+    IF NEW.left_at IS NOT NULL THEN
+        RETURN NEW;
+    END IF;
+
+    SELECT c.org_id INTO v_org FROM rustygpt.conversations c WHERE c.id = NEW.conversation_id;
+
+    IF NOT rustygpt.sp_user_in_org(NEW.user_id, v_org) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: user is not a member of the conversation''s organization';
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.trg_invite_org_member()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_org rustygpt.organizations%ROWTYPE;
+BEGIN
+    SELECT o.*
+    INTO v_org
+    FROM rustygpt.conversations c
+    JOIN rustygpt.organizations o ON o.id = c.org_id
+    WHERE c.id = NEW.conversation_id;
+
+    IF v_org.id IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    IF NOT COALESCE((v_org.features ->> 'invites')::BOOLEAN, TRUE) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: invites are disabled for this organization';
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1
+        FROM rustygpt.users u
+        JOIN rustygpt.organization_members m ON m.user_id = u.id
+        WHERE u.email = NEW.invited_email
+          AND m.org_id = v_org.id
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: invites are limited to members of the organization';
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS participant_org_member ON rustygpt.conversation_participants;
+CREATE TRIGGER participant_org_member
+    BEFORE INSERT OR UPDATE OF user_id, left_at ON rustygpt.conversation_participants
+    FOR EACH ROW
+    EXECUTE FUNCTION rustygpt.trg_participant_org_member();
+
+DROP TRIGGER IF EXISTS invite_org_member ON rustygpt.conversation_invites;
+CREATE TRIGGER invite_org_member
+    BEFORE INSERT ON rustygpt.conversation_invites
+    FOR EACH ROW
+    EXECUTE FUNCTION rustygpt.trg_invite_org_member();
+
+-- Conversations ---------------------------------------------------------------
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_create_org_conversation(
+    p_title TEXT,
+    p_is_group BOOLEAN,
+    p_creator UUID,
+    p_org_id UUID
+)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_conversation_id UUID;
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM rustygpt.organizations WHERE id = p_org_id) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: organization not found';
+    END IF;
+
+    IF NOT rustygpt.sp_user_in_org(p_creator, p_org_id) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: not a member of the organization';
+    END IF;
+
+    v_conversation_id := rustygpt.sp_create_conversation(p_title, p_is_group, p_creator);
+
+    UPDATE rustygpt.conversations
+    SET org_id = p_org_id
+    WHERE id = v_conversation_id;
+
+    RETURN v_conversation_id;
+END;
+$$;
+
+-- What the application enforces for a conversation's organization. No row for conversations
+-- outside any organization.
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_conversation_policy(
+    p_conversation_id UUID
+)
+RETURNS TABLE (
+    org_id UUID,
+    allowed_models TEXT[],
+    features JSONB
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT o.id, o.allowed_models, o.features
+    FROM rustygpt.conversations c
+    JOIN rustygpt.organizations o ON o.id = c.org_id
+    WHERE c.id = p_conversation_id;
+$$;
+
+-- Organizations ---------------------------------------------------------------
+
+-- Organizations visible to p_user_id with the caller's role in each; NULL lists all of them.
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_list(
+    p_user_id UUID,
+    p_org_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    id UUID,
+    slug TEXT,
+    name TEXT,
+    rate_limit_profile_id UUID,
+    rate_limit_profile_name TEXT,
+    allowed_models TEXT[],
+    features JSONB,
+    member_count BIGINT,
+    my_role TEXT,
+    created_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT
+        o.id,
+        o.slug::TEXT,
+        o.name,
+        o.rate_limit_profile_id,
+        p.name,
+        o.allowed_models,
+        o.features,
+        (SELECT count(*) FROM rustygpt.organization_members m WHERE m.org_id = o.id),
+        mine.role::TEXT,
+        o.created_at,
+        o.updated_at
+    FROM rustygpt.organizations o
+    LEFT JOIN rustygpt.rate_limit_profiles p ON p.id = o.rate_limit_profile_id
+    LEFT JOIN rustygpt.organization_members mine
+        ON mine.org_id = o.id AND mine.user_id = p_user_id
+    WHERE (p_user_id IS NULL OR mine.user_id IS NOT NULL)
+      AND (p_org_id IS NULL OR o.id = p_org_id)
+    ORDER BY o.name, o.id;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_create(
+    p_slug TEXT,
+    p_name TEXT,
+    p_owner UUID
+)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID := NULLIF(current_setting('app.current_user_id', true), '')::UUID;
+    v_slug TEXT := lower(btrim(p_slug));
+    v_org_id UUID;
+BEGIN
+    IF v_slug IS NULL OR v_slug !~ '^[a-z0-9][a-z0-9-]{0,62}$' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: slug must be lowercase letters, digits and dashes';
+    END IF;
+
+    IF p_name IS NULL OR btrim(p_name) = '' THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: name required';
+    END IF;
+
+    IF EXISTS (SELECT 1 FROM rustygpt.organizations WHERE slug = v_slug::CITEXT) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: slug already taken';
+    END IF;
+
+    IF p_owner IS NOT NULL AND NOT EXISTS (SELECT 1 FROM rustygpt.users WHERE id = p_owner) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: user not found';
+    END IF;
+
+    INSERT INTO rustygpt.organizations (slug, name)
+    VALUES (v_slug, btrim(p_name))
+    RETURNING id INTO v_org_id;
+
+    IF p_owner IS NOT NULL THEN
+        INSERT INTO rustygpt.organization_members (org_id, user_id, role, added_by)
+        VALUES (v_org_id, p_owner, 'owner', v_actor);
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record(
+        'org.create',
+        'organization',
+        v_org_id::TEXT,
+        jsonb_build_object('slug', v_slug, 'name', btrim(p_name), 'owner', p_owner)
+    );
+    RETURN v_org_id;
+END;
+$$;
+
+-- Replaces the organization's settings; callers send the complete new values.
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_update(
+    p_org_id UUID,
+    p_name TEXT,
+    p_rate_limit_profile_id UUID,
+    p_allowed_models TEXT[],
+    p_features JSONB
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF p_name IS NULL OR btrim(p_name) = '' THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: name required';
+    END IF;
+
+    IF p_features IS NULL OR jsonb_typeof(p_features) <> 'object' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: features must be a JSON object';
+    END IF;
+
+    IF p_rate_limit_profile_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM rustygpt.rate_limit_profiles WHERE id = p_rate_limit_profile_id
+    ) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: profile not found';
+    END IF;
+
+    UPDATE rustygpt.organizations
+    SET name = btrim(p_name),
+        rate_limit_profile_id = p_rate_limit_profile_id,
+        allowed_models = p_allowed_models,
+        features = p_features,
+        updated_at = now()
+    WHERE id = p_org_id;
+
+    IF NOT FOUND THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: organization not found';
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record(
+        'org.update',
+        'organization',
+        p_org_id::TEXT,
+        jsonb_build_object(
+            'name', btrim(p_name),
+            'rate_limit_profile_id', p_rate_limit_profile_id,
+            'allowed_models', to_jsonb(p_allowed_models),
+            'features', p_features
+        )
+    );
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_delete(
+    p_org_id UUID
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM rustygpt.conversations WHERE org_id = p_org_id) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: delete or move the organization''s conversations first';
+    END IF;
+
+    DELETE FROM rustygpt.organizations WHERE id = p_org_id;
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record('org.delete', 'organization', p_org_id::TEXT);
+    RETURN TRUE;
+END;
+$$;
+
+-- Members -----------------------------------------------------------------------
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_member_list(
+    p_org_id UUID
+)
+RETURNS TABLE (
+    user_id UUID,
+    username TEXT,
+    email TEXT,
+    display_name TEXT,
+    role TEXT,
+    joined_at TIMESTAMPTZ
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT u.id, u.username::TEXT, u.email::TEXT, u.display_name, m.role::TEXT, m.joined_at
+    FROM rustygpt.organization_members m
+    JOIN rustygpt.users u ON u.id = m.user_id
+    WHERE m.org_id = p_org_id
+    ORDER BY m.role, u.username;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_member_role(
+    p_org_id UUID,
+    p_user_id UUID
+)
+RETURNS TEXT
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT m.role::TEXT
+    FROM rustygpt.organization_members m
+    WHERE m.org_id = p_org_id
+      AND m.user_id = p_user_id;
+$$;
+
+-- Refuses changes that would leave the organization without an owner once p_user_id stops
+-- being one.
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_assert_other_owner(
+    p_org_id UUID,
+    p_user_id UUID
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1
+        FROM rustygpt.organization_members m
+        WHERE m.org_id = p_org_id AND m.user_id = p_user_id AND m.role = 'owner'
+    ) AND NOT EXISTS (
+        SELECT 1
+        FROM rustygpt.organization_members m
+        WHERE m.org_id = p_org_id AND m.user_id <> p_user_id AND m.role = 'owner'
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: an organization needs at least one owner';
+    END IF;
+END;
+$$;
+
+-- Adds a member or changes their role. Returns the role now held.
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_member_put(
+    p_org_id UUID,
+    p_user_id UUID,
+    p_role TEXT
+)
+RETURNS TEXT
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID := NULLIF(current_setting('app.current_user_id', true), '')::UUID;
+    v_role rustygpt.org_role := COALESCE(p_role, 'member')::rustygpt.org_role;
+    v_previous rustygpt.org_role;
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM rustygpt.organizations WHERE id = p_org_id) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: organization not found';
+    END IF;
+
+    IF NOT EXISTS (SELECT 1 FROM rustygpt.users WHERE id = p_user_id) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: user not found';
+    END IF;
+
+    SELECT m.role
+    INTO v_previous
+    FROM rustygpt.organization_members m
+    WHERE m.org_id = p_org_id AND m.user_id = p_user_id
+    FOR UPDATE;
+
+    IF v_previous IS NULL THEN
+        INSERT INTO rustygpt.organization_members (org_id, user_id, role, added_by)
+        VALUES (p_org_id, p_user_id, v_role, v_actor);
+
+        PERFORM rustygpt.sp_audit_record(
+            'org.member_add',
+            'organization',
+            p_org_id::TEXT,
+            jsonb_build_object('user_id', p_user_id, 'role', v_role)
+        );
+    ELSIF v_previous <> v_role THEN
+        IF v_role <> 'owner' THEN
+            PERFORM rustygpt.sp_org_assert_other_owner(p_org_id, p_user_id);
+        END IF;
+
+        UPDATE rustygpt.organization_members
+        SET role = v_role
+        WHERE org_id = p_org_id AND user_id = p_user_id;
+
+        PERFORM rustygpt.sp_audit_record(
+            'org.member_role_change',
+            'organization',
+            p_org_id::TEXT,
+            jsonb_build_object('user_id', p_user_id, 'role', v_role, 'previous_role', v_previous)
+        );
+    END IF;
+
+    RETURN v_role::TEXT;
+END;
+$$;
+
+-- Removes a member, who also leaves every conversation the organization owns.
+CREATE OR REPLACE FUNCTION rustygpt.sp_org_member_remove(
+    p_org_id UUID,
+    p_user_id UUID
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_left INTEGER;
+BEGIN
+    PERFORM rustygpt.sp_org_assert_other_owner(p_org_id, p_user_id);
+
+    DELETE FROM rustygpt.organization_members
+    WHERE org_id = p_org_id AND user_id = p_user_id;
+
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    UPDATE rustygpt.conversation_participants cp
+    SET left_at = now()
+    FROM rustygpt.conversations c
+    WHERE c.id = cp.conversation_id
+      AND c.org_id = p_org_id
+      AND cp.user_id = p_user_id
+      AND cp.left_at IS NULL;
+    GET DIAGNOSTICS v_left = ROW_COUNT;
+
+    PERFORM rustygpt.sp_audit_record(
+        'org.member_remove',
+        'organization',
+        p_org_id::TEXT,
+        jsonb_build_object('user_id', p_user_id, 'conversations_left', v_left)
+    );
+    RETURN TRUE;
+END;
+$$;
+
+-- Token quotas ------------------------------------------------------------------
+
+-- Consumption against an organization's shared budget: every generation in the
+-- organization's conversations counts, whoever asked for it. Applies on top of the
+-- per-user budget from sp_quota_check.
+CREATE OR REPLACE FUNCTION rustygpt.sp_quota_check_org(
+    p_org_id UUID
+)
+RETURNS TABLE (
+    period TEXT,
+    source TEXT,
+    token_limit BIGINT,
+    used BIGINT,
+    resets_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_daily BIGINT;
+    v_monthly BIGINT;
+    v_day_start TIMESTAMPTZ := date_trunc('day', now() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC';
+    v_month_start TIMESTAMPTZ := date_trunc('month', now() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC';
+BEGIN
+    SELECT q.daily_tokens, q.monthly_tokens
+    INTO v_daily, v_monthly
+    FROM rustygpt.token_quotas q
+    WHERE q.target_kind = 'org'
+      AND q.org_id = p_org_id;
+
+    IF NOT FOUND THEN
+        RETURN;
+    END IF;
+
+    IF v_daily IS NOT NULL THEN
+        RETURN QUERY
+        SELECT
+            'day'::TEXT,
+            'org'::TEXT,
+            v_daily,
+            COALESCE(SUM(l.total_tokens), 0)::BIGINT,
+            v_day_start + interval '1 day'
+        FROM rustygpt.usage_ledger l
+        JOIN rustygpt.conversations c ON c.id = l.conversation_id
+        WHERE c.org_id = p_org_id
+          AND l.created_at >= v_day_start;
+    END IF;
+
+    IF v_monthly IS NOT NULL THEN
+        RETURN QUERY
+        SELECT
+            'month'::TEXT,
+            'org'::TEXT,
+            v_monthly,
+            COALESCE(SUM(l.total_tokens), 0)::BIGINT,
+            v_month_start + interval '1 month'
+        FROM rustygpt.usage_ledger l
+        JOIN rustygpt.conversations c ON c.id = l.conversation_id
+        WHERE c.org_id = p_org_id
+          AND l.created_at >= v_month_start;
+    END IF;
+END;
+$$;
@@ -0,0 +1,573 @@
+-- Stored procedures: user groups and their grants on conversations.
+--
+-- Procedures that change who can reach a conversation return the affected
+-- (conversation_id, user_id) pairs with the user's effective role before and after the change
+-- (NULL without access), so the caller can announce the changes.
+SET search_path TO rustygpt, public;
+
+-- Conversations `p_user` can currently reach, directly or through groups.
+CREATE OR REPLACE FUNCTION rustygpt.sp_user_conversations(
+    p_user UUID
+)
+RETURNS SETOF UUID
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT DISTINCT reachable.conversation_id
+    FROM (
+        SELECT cp.conversation_id
+        FROM rustygpt.conversation_participants cp
+        WHERE cp.user_id = p_user
+          AND cp.left_at IS NULL
+        UNION
+        SELECT g.conversation_id
+        FROM rustygpt.conversation_group_grants g
+        JOIN rustygpt.user_group_members m ON m.group_id = g.group_id
+        WHERE m.user_id = p_user
+    ) reachable
+    JOIN rustygpt.conversations c ON c.id = reachable.conversation_id
+    WHERE rustygpt.sp_user_in_org(p_user, c.org_id);
+$$;
+
+-- Groups ------------------------------------------------------------------------
+
+-- Groups `p_user_id` belongs to, or every group when NULL; `p_group_id` narrows to one.
+CREATE OR REPLACE FUNCTION rustygpt.sp_group_list(
+    p_user_id UUID,
+    p_group_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    id UUID,
+    name TEXT,
+    description TEXT,
+    member_count BIGINT,
+    is_manager BOOLEAN,
+    created_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT
+        g.id,
+        g.name::TEXT,
+        g.description,
+        (SELECT count(*) FROM rustygpt.user_group_members m WHERE m.group_id = g.id),
+        mine.is_manager,
+        g.created_at,
+        g.updated_at
+    FROM rustygpt.user_groups g
+    LEFT JOIN rustygpt.user_group_members mine
+        ON mine.group_id = g.id AND mine.user_id = p_user_id
+    WHERE (p_user_id IS NULL OR mine.user_id IS NOT NULL)
+      AND (p_group_id IS NULL OR g.id = p_group_id)
+    ORDER BY g.name, g.id;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_group_create(
+    p_name TEXT,
+    p_description TEXT,
+    p_creator UUID
+)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_group_id UUID;
+BEGIN
+    IF p_name IS NULL OR btrim(p_name) = '' THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: name required';
+    END IF;
+
+    IF EXISTS (SELECT 1 FROM rustygpt.user_groups WHERE name = btrim(p_name)::CITEXT) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: a group with this name already exists';
+    END IF;
+
+    INSERT INTO rustygpt.user_groups (name, description, created_by)
+    VALUES (btrim(p_name), NULLIF(btrim(p_description), ''), p_creator)
+    RETURNING id INTO v_group_id;
+
+    INSERT INTO rustygpt.user_group_members (group_id, user_id, is_manager, added_by)
+    VALUES (v_group_id, p_creator, TRUE, p_creator);
+
+    PERFORM rustygpt.sp_audit_record(
+        'group.create',
+        'user_group',
+        v_group_id::TEXT,
+        jsonb_build_object('name', btrim(p_name))
+    );
+    RETURN v_group_id;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_group_update(
+    p_group_id UUID,
+    p_name TEXT,
+    p_description TEXT
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF p_name IS NULL OR btrim(p_name) = '' THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.VALIDATION: name required';
+    END IF;
+
+    IF EXISTS (
+        SELECT 1 FROM rustygpt.user_groups
+        WHERE name = btrim(p_name)::CITEXT AND id <> p_group_id
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: a group with this name already exists';
+    END IF;
+
+    UPDATE rustygpt.user_groups
+    SET name = btrim(p_name),
+        description = NULLIF(btrim(p_description), ''),
+        updated_at = now()
+    WHERE id = p_group_id;
+
+    IF NOT FOUND THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: group not found';
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record(
+        'group.update',
+        'user_group',
+        p_group_id::TEXT,
+        jsonb_build_object('name', btrim(p_name))
+    );
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_group_delete(
+    p_group_id UUID
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    user_id UUID,
+    previous_role TEXT,
+    role TEXT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_conversations UUID[];
+    v_users UUID[];
+    v_previous TEXT[];
+BEGIN
+    SELECT
+        array_agg(g.conversation_id),
+        array_agg(m.user_id),
+        array_agg(rustygpt.sp_user_conversation_role(m.user_id, g.conversation_id)::TEXT)
+    INTO v_conversations, v_users, v_previous
+    FROM rustygpt.conversation_group_grants g
+    JOIN rustygpt.user_group_members m ON m.group_id = g.group_id
+    WHERE g.group_id = p_group_id;
+
+    DELETE FROM rustygpt.user_groups WHERE id = p_group_id;
+    IF NOT FOUND THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: group not found';
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record('group.delete', 'user_group', p_group_id::TEXT);
+
+    RETURN QUERY
+    SELECT snapshot.c, snapshot.u, snapshot.prev,
+           rustygpt.sp_user_conversation_role(snapshot.u, snapshot.c)::TEXT
+    FROM unnest(v_conversations, v_users, v_previous) AS snapshot(c, u, prev);
+END;
+$$;
+
+-- Members -----------------------------------------------------------------------
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_group_member_list(
+    p_group_id UUID
+)
+RETURNS TABLE (
+    user_id UUID,
+    username TEXT,
+    email TEXT,
+    display_name TEXT,
+    is_manager BOOLEAN,
+    added_at TIMESTAMPTZ
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT u.id, u.username::TEXT, u.email::TEXT, u.display_name, m.is_manager, m.added_at
+    FROM rustygpt.user_group_members m
+    JOIN rustygpt.users u ON u.id = m.user_id
+    WHERE m.group_id = p_group_id
+    ORDER BY m.is_manager DESC, u.username;
+$$;
+
+-- TRUE for managers, FALSE for other members and NULL for non-members.
+CREATE OR REPLACE FUNCTION rustygpt.sp_group_is_manager(
+    p_group_id UUID,
+    p_user_id UUID
+)
+RETURNS BOOLEAN
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT m.is_manager
+    FROM rustygpt.user_group_members m
+    WHERE m.group_id = p_group_id
+      AND m.user_id = p_user_id;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_group_assert_other_manager(
+    p_group_id UUID,
+    p_user_id UUID
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1
+        FROM rustygpt.user_group_members m
+        WHERE m.group_id = p_group_id AND m.user_id = p_user_id AND m.is_manager
+    ) AND NOT EXISTS (
+        SELECT 1
+        FROM rustygpt.user_group_members m
+        WHERE m.group_id = p_group_id AND m.user_id <> p_user_id AND m.is_manager
+    ) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: a group needs at least one manager';
+    END IF;
+END;
+$$;
+
+-- Adds a member or changes whether they manage the group. Returns the conversations the
+-- member can reach through the group when they were newly added.
+CREATE OR REPLACE FUNCTION rustygpt.sp_group_member_put(
+    p_group_id UUID,
+    p_user_id UUID,
+    p_is_manager BOOLEAN
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    user_id UUID,
+    previous_role TEXT,
+    role TEXT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID := NULLIF(current_setting('app.current_user_id', true), '')::UUID;
+    v_manager BOOLEAN := COALESCE(p_is_manager, FALSE);
+    v_previous BOOLEAN;
+    v_conversations UUID[];
+    v_roles TEXT[];
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM rustygpt.user_groups WHERE id = p_group_id) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: group not found';
+    END IF;
+
+    IF NOT EXISTS (SELECT 1 FROM rustygpt.users WHERE id = p_user_id) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: user not found';
+    END IF;
+
+    SELECT m.is_manager
+    INTO v_previous
+    FROM rustygpt.user_group_members m
+    WHERE m.group_id = p_group_id AND m.user_id = p_user_id
+    FOR UPDATE;
+
+    IF v_previous IS NOT NULL THEN
+        IF v_previous <> v_manager THEN
+            IF NOT v_manager THEN
+                PERFORM rustygpt.sp_group_assert_other_manager(p_group_id, p_user_id);
+            END IF;
+
+            UPDATE rustygpt.user_group_members m
+            SET is_manager = v_manager
+            WHERE m.group_id = p_group_id AND m.user_id = p_user_id;
+
+            PERFORM rustygpt.sp_audit_record(
+                'group.member_update',
+                'user_group',
+                p_group_id::TEXT,
+                jsonb_build_object('user_id', p_user_id, 'is_manager', v_manager)
+            );
+        END IF;
+        RETURN;
+    END IF;
+
+    SELECT
+        array_agg(g.conversation_id),
+        array_agg(rustygpt.sp_user_conversation_role(p_user_id, g.conversation_id)::TEXT)
+    INTO v_conversations, v_roles
+    FROM rustygpt.conversation_group_grants g
+    WHERE g.group_id = p_group_id;
+
+    INSERT INTO rustygpt.user_group_members (group_id, user_id, is_manager, added_by)
+    VALUES (p_group_id, p_user_id, v_manager, v_actor);
+
+    PERFORM rustygpt.sp_audit_record(
+        'group.member_add',
+        'user_group',
+        p_group_id::TEXT,
+        jsonb_build_object('user_id', p_user_id, 'is_manager', v_manager)
+    );
+
+    RETURN QUERY
+    SELECT snapshot.c, p_user_id, snapshot.prev,
+           rustygpt.sp_user_conversation_role(p_user_id, snapshot.c)::TEXT
+    FROM unnest(v_conversations, v_roles) AS snapshot(c, prev);
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_group_member_remove(
+    p_group_id UUID,
+    p_user_id UUID
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    user_id UUID,
+    previous_role TEXT,
+    role TEXT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_conversations UUID[];
+    v_roles TEXT[];
+BEGIN
+    PERFORM rustygpt.sp_group_assert_other_manager(p_group_id, p_user_id);
+
+    SELECT
+        array_agg(g.conversation_id),
+        array_agg(rustygpt.sp_user_conversation_role(p_user_id, g.conversation_id)::TEXT)
+    INTO v_conversations, v_roles
+    FROM rustygpt.conversation_group_grants g
+    WHERE g.group_id = p_group_id;
+
+    DELETE FROM rustygpt.user_group_members m
+    WHERE m.group_id = p_group_id AND m.user_id = p_user_id;
+
+    IF NOT FOUND THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: member not found';
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record(
+        'group.member_remove',
+        'user_group',
+        p_group_id::TEXT,
+        jsonb_build_object('user_id', p_user_id)
+    );
+
+    RETURN QUERY
+    SELECT snapshot.c, p_user_id, snapshot.prev,
+           rustygpt.sp_user_conversation_role(p_user_id, snapshot.c)::TEXT
+    FROM unnest(v_conversations, v_roles) AS snapshot(c, prev);
+END;
+$$;
+
+-- Conversation grants -----------------------------------------------------------
+
+-- Groups granted on a conversation; any participant may look.
+CREATE OR REPLACE FUNCTION rustygpt.sp_conversation_group_list(
+    p_conversation UUID
+)
+RETURNS TABLE (
+    group_id UUID,
+    name TEXT,
+    role TEXT,
+    member_count BIGINT,
+    granted_at TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF NOT rustygpt.sp_user_can_access(rustygpt.sp_require_session_user(), p_conversation) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: not a participant';
+    END IF;
+
+    RETURN QUERY
+    SELECT
+        g.id,
+        g.name::TEXT,
+        cg.role::TEXT,
+        (SELECT count(*) FROM rustygpt.user_group_members m WHERE m.group_id = g.id),
+        cg.granted_at
+    FROM rustygpt.conversation_group_grants cg
+    JOIN rustygpt.user_groups g ON g.id = cg.group_id
+    WHERE cg.conversation_id = p_conversation
+    ORDER BY g.name;
+END;
+$$;
+
+-- Grants a group a role on the conversation, or changes the role of an existing grant.
+-- Requires owner or admin on the conversation and membership of the group.
+CREATE OR REPLACE FUNCTION rustygpt.sp_conversation_group_grant(
+    p_conversation UUID,
+    p_group_id UUID,
+    p_role TEXT DEFAULT 'member'
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    user_id UUID,
+    previous_role TEXT,
+    role TEXT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_actor_role rustygpt.conversation_role;
+    v_role rustygpt.conversation_role;
+    v_previous rustygpt.conversation_role;
+    v_users UUID[];
+    v_roles TEXT[];
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+    v_actor_role := rustygpt.sp_user_conversation_role(v_actor, p_conversation);
+
+    IF v_actor_role IS NULL OR v_actor_role NOT IN ('owner', 'admin') THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: insufficient role';
+    END IF;
+
+    IF rustygpt.sp_group_is_manager(p_group_id, v_actor) IS NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: group not found';
+    END IF;
+
+    v_role := COALESCE(p_role::rustygpt.conversation_role, 'member');
+    IF v_role = 'owner' AND v_actor_role <> 'owner' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: only owners can grant ownership';
+    END IF;
+
+    SELECT cg.role
+    INTO v_previous
+    FROM rustygpt.conversation_group_grants cg
+    WHERE cg.conversation_id = p_conversation AND cg.group_id = p_group_id
+    FOR UPDATE;
+
+    IF v_previous = v_role THEN
+        RETURN;
+    END IF;
+
+    SELECT
+        array_agg(m.user_id),
+        array_agg(rustygpt.sp_user_conversation_role(m.user_id, p_conversation)::TEXT)
+    INTO v_users, v_roles
+    FROM rustygpt.user_group_members m
+    WHERE m.group_id = p_group_id;
+
+    INSERT INTO rustygpt.conversation_group_grants (conversation_id, group_id, role, granted_by)
+    VALUES (p_conversation, p_group_id, v_role, v_actor)
+    ON CONFLICT ON CONSTRAINT conversation_group_grants_pkey DO UPDATE
+        SET role = EXCLUDED.role,
+            granted_by = EXCLUDED.granted_by,
+            granted_at = now();
+
+    PERFORM rustygpt.sp_audit_record(
+        'membership.group_grant',
+        'conversation',
+        p_conversation::TEXT,
+        jsonb_build_object('group_id', p_group_id, 'role', v_role, 'previous_role', v_previous)
+    );
+
+    RETURN QUERY
+    SELECT p_conversation, snapshot.u, snapshot.prev,
+           rustygpt.sp_user_conversation_role(snapshot.u, p_conversation)::TEXT
+    FROM unnest(v_users, v_roles) AS snapshot(u, prev);
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_conversation_group_revoke(
+    p_conversation UUID,
+    p_group_id UUID
+)
+RETURNS TABLE (
+    conversation_id UUID,
+    user_id UUID,
+    previous_role TEXT,
+    role TEXT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_actor_role rustygpt.conversation_role;
+    v_previous rustygpt.conversation_role;
+    v_users UUID[];
+    v_roles TEXT[];
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+    v_actor_role := rustygpt.sp_user_conversation_role(v_actor, p_conversation);
+
+    IF v_actor_role IS NULL OR v_actor_role NOT IN ('owner', 'admin') THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.403: insufficient role';
+    END IF;
+
+    SELECT
+        array_agg(m.user_id),
+        array_agg(rustygpt.sp_user_conversation_role(m.user_id, p_conversation)::TEXT)
+    INTO v_users, v_roles
+    FROM rustygpt.user_group_members m
+    WHERE m.group_id = p_group_id;
+
+    DELETE FROM rustygpt.conversation_group_grants cg
+    WHERE cg.conversation_id = p_conversation AND cg.group_id = p_group_id
+    RETURNING cg.role INTO v_previous;
+
+    IF v_previous IS NULL THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: grant not found';
+    END IF;
+
+    IF v_previous = 'owner' AND v_actor_role <> 'owner' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: only owners can revoke ownership';
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record(
+        'membership.group_revoke',
+        'conversation',
+        p_conversation::TEXT,
+        jsonb_build_object('group_id', p_group_id, 'previous_role', v_previous)
+    );
+
+    RETURN QUERY
+    SELECT p_conversation, snapshot.u, snapshot.prev,
+           rustygpt.sp_user_conversation_role(snapshot.u, p_conversation)::TEXT
+    FROM unnest(v_users, v_roles) AS snapshot(u, prev);
+END;
+$$;
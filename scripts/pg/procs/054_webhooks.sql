@@ -0,0 +1,439 @@
+-- Stored procedures: webhook subscriptions, the delivery outbox and replays
+SET search_path TO rustygpt, public;
+
+-- Subscriptions ---------------------------------------------------------------------
+
+-- Every subscription, or one when `p_id` is set, with delivery counts. Secrets are not
+-- returned.
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_subscription_list(
+    p_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    id UUID,
+    url TEXT,
+    description TEXT,
+    conversation_id UUID,
+    org_id UUID,
+    event_types TEXT[],
+    enabled BOOLEAN,
+    pending_count BIGINT,
+    dead_count BIGINT,
+    last_delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT
+        s.id,
+        s.url,
+        s.description,
+        s.conversation_id,
+        s.org_id,
+        s.event_types,
+        s.enabled,
+        count(d.id) FILTER (WHERE d.delivered_at IS NULL AND d.dead_at IS NULL),
+        count(d.id) FILTER (WHERE d.dead_at IS NOT NULL),
+        max(d.delivered_at),
+        s.created_at,
+        s.updated_at
+    FROM rustygpt.webhook_subscriptions s
+    LEFT JOIN rustygpt.webhook_deliveries d ON d.subscription_id = s.id
+    WHERE p_id IS NULL OR s.id = p_id
+    GROUP BY s.id
+    ORDER BY s.created_at, s.id;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_subscription_create(
+    p_url TEXT,
+    p_description TEXT,
+    p_secret TEXT,
+    p_conversation_id UUID,
+    p_org_id UUID,
+    p_event_types TEXT[]
+)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID := NULLIF(current_setting('app.current_user_id', true), '')::UUID;
+    v_id UUID;
+BEGIN
+    IF num_nonnulls(p_conversation_id, p_org_id) <> 1 THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: set exactly one of conversation_id or org_id';
+    END IF;
+
+    IF p_url IS NULL OR p_url !~* '^https?://' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: url must be an http or https URL';
+    END IF;
+
+    IF p_conversation_id IS NOT NULL
+       AND NOT EXISTS (SELECT 1 FROM rustygpt.conversations WHERE id = p_conversation_id) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: conversation not found';
+    END IF;
+
+    IF p_org_id IS NOT NULL
+       AND NOT EXISTS (SELECT 1 FROM rustygpt.organizations WHERE id = p_org_id) THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: organization not found';
+    END IF;
+
+    INSERT INTO rustygpt.webhook_subscriptions (
+        url, description, secret, conversation_id, org_id, event_types, created_by
+    )
+    VALUES (
+        btrim(p_url),
+        NULLIF(btrim(p_description), ''),
+        p_secret,
+        p_conversation_id,
+        p_org_id,
+        COALESCE(p_event_types, '{}'),
+        v_actor
+    )
+    RETURNING id INTO v_id;
+
+    PERFORM rustygpt.sp_audit_record(
+        'webhook.create',
+        'webhook_subscription',
+        v_id::TEXT,
+        jsonb_build_object(
+            'url', btrim(p_url),
+            'conversation_id', p_conversation_id,
+            'org_id', p_org_id,
+            'event_types', COALESCE(p_event_types, '{}')
+        )
+    );
+    RETURN v_id;
+END;
+$$;
+
+-- Replaces the URL, description, event filter and enabled flag. A NULL `p_secret` keeps the
+-- current secret.
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_subscription_update(
+    p_id UUID,
+    p_url TEXT,
+    p_description TEXT,
+    p_event_types TEXT[],
+    p_enabled BOOLEAN,
+    p_secret TEXT DEFAULT NULL
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    IF p_url IS NULL OR p_url !~* '^https?://' THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.VALIDATION: url must be an http or https URL';
+    END IF;
+
+    UPDATE rustygpt.webhook_subscriptions
+    SET url = btrim(p_url),
+        description = NULLIF(btrim(p_description), ''),
+        event_types = COALESCE(p_event_types, '{}'),
+        enabled = COALESCE(p_enabled, TRUE),
+        secret = COALESCE(p_secret, secret),
+        updated_at = now()
+    WHERE id = p_id;
+
+    IF NOT FOUND THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: webhook not found';
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record(
+        'webhook.update',
+        'webhook_subscription',
+        p_id::TEXT,
+        jsonb_build_object(
+            'url', btrim(p_url),
+            'event_types', COALESCE(p_event_types, '{}'),
+            'enabled', COALESCE(p_enabled, TRUE),
+            'secret_rotated', p_secret IS NOT NULL
+        )
+    );
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_subscription_delete(
+    p_id UUID
+)
+RETURNS BOOLEAN
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+BEGIN
+    DELETE FROM rustygpt.webhook_subscriptions WHERE id = p_id;
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record('webhook.delete', 'webhook_subscription', p_id::TEXT);
+    RETURN TRUE;
+END;
+$$;
+
+-- Outbox ----------------------------------------------------------------------------
+
+-- Queues one delivery per enabled subscription following the conversation, directly or
+-- through its organization, whose filter accepts `p_event_type`. Returns how many were queued.
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_enqueue(
+    p_conversation_id UUID,
+    p_event_type TEXT,
+    p_data JSONB
+)
+RETURNS INTEGER
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_event_id UUID := gen_random_uuid();
+    v_queued INTEGER;
+BEGIN
+    INSERT INTO rustygpt.webhook_deliveries (
+        subscription_id, event_id, event_type, conversation_id, payload
+    )
+    SELECT
+        s.id,
+        v_event_id,
+        p_event_type,
+        p_conversation_id,
+        jsonb_build_object(
+            'event_id', v_event_id,
+            'type', p_event_type,
+            'conversation_id', p_conversation_id,
+            'occurred_at', clock_timestamp(),
+            'data', p_data
+        )
+    FROM rustygpt.webhook_subscriptions s
+    WHERE s.enabled
+      AND (
+          s.conversation_id = p_conversation_id
+          OR s.org_id = (
+              SELECT c.org_id FROM rustygpt.conversations c WHERE c.id = p_conversation_id
+          )
+      )
+      AND (cardinality(s.event_types) = 0 OR p_event_type = ANY (s.event_types));
+
+    GET DIAGNOSTICS v_queued = ROW_COUNT;
+    RETURN v_queued;
+END;
+$$;
+
+-- Leases due deliveries of enabled subscriptions to one worker, as `sp_mail_claim` does.
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_claim(
+    p_limit INTEGER,
+    p_lease_seconds INTEGER
+)
+RETURNS TABLE (
+    id UUID,
+    subscription_id UUID,
+    url TEXT,
+    secret TEXT,
+    event_id UUID,
+    event_type TEXT,
+    payload JSONB,
+    attempts INTEGER
+)
+LANGUAGE sql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    WITH due AS (
+        SELECT d.id
+        FROM rustygpt.webhook_deliveries d
+        JOIN rustygpt.webhook_subscriptions s ON s.id = d.subscription_id
+        WHERE d.delivered_at IS NULL
+          AND d.dead_at IS NULL
+          AND d.next_attempt_at <= clock_timestamp()
+          AND s.enabled
+        ORDER BY d.next_attempt_at
+        LIMIT GREATEST(p_limit, 1)
+        FOR UPDATE OF d SKIP LOCKED
+    )
+    UPDATE rustygpt.webhook_deliveries d
+    SET next_attempt_at = clock_timestamp() + make_interval(secs => p_lease_seconds)
+    FROM due, rustygpt.webhook_subscriptions s
+    WHERE d.id = due.id
+      AND s.id = d.subscription_id
+    RETURNING d.id, d.subscription_id, s.url, s.secret, d.event_id, d.event_type, d.payload,
+              d.attempts;
+$$;
+
+-- Logs one attempt and settles the delivery: `delivered`, `retry` (backing off exponentially
+-- from 30 seconds up to an hour) or `dead` once `p_max_attempts` is reached.
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_record_attempt(
+    p_id UUID,
+    p_status_code INTEGER,
+    p_error TEXT,
+    p_duration_ms INTEGER,
+    p_succeeded BOOLEAN,
+    p_max_attempts INTEGER
+)
+RETURNS TEXT
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_attempts INTEGER;
+BEGIN
+    INSERT INTO rustygpt.webhook_delivery_attempts (delivery_id, status_code, error, duration_ms)
+    VALUES (p_id, p_status_code, left(p_error, 2000), GREATEST(p_duration_ms, 0));
+
+    UPDATE rustygpt.webhook_deliveries d
+    SET attempts = d.attempts + 1,
+        last_status_code = p_status_code,
+        last_error = left(p_error, 2000)
+    WHERE d.id = p_id
+    RETURNING d.attempts INTO v_attempts;
+
+    IF v_attempts IS NULL THEN
+        RETURN 'dead';
+    END IF;
+
+    IF p_succeeded THEN
+        UPDATE rustygpt.webhook_deliveries
+        SET delivered_at = clock_timestamp(), last_error = NULL
+        WHERE id = p_id;
+        RETURN 'delivered';
+    END IF;
+
+    IF v_attempts >= p_max_attempts THEN
+        UPDATE rustygpt.webhook_deliveries SET dead_at = clock_timestamp() WHERE id = p_id;
+        RETURN 'dead';
+    END IF;
+
+    UPDATE rustygpt.webhook_deliveries
+    SET next_attempt_at = clock_timestamp()
+        + make_interval(secs => LEAST(3600, 30 * power(2, v_attempts - 1)))
+    WHERE id = p_id;
+    RETURN 'retry';
+END;
+$$;
+
+-- Delivery log ----------------------------------------------------------------------
+
+-- Deliveries newest first. `p_status` is `pending`, `delivered` or `dead`; NULL for all.
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_delivery_list(
+    p_subscription_id UUID,
+    p_status TEXT DEFAULT NULL,
+    p_limit INTEGER DEFAULT 100,
+    p_delivery_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    id UUID,
+    subscription_id UUID,
+    event_id UUID,
+    event_type TEXT,
+    conversation_id UUID,
+    replay_of UUID,
+    status TEXT,
+    attempts INTEGER,
+    last_status_code INTEGER,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    dead_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT *
+    FROM (
+        SELECT
+            d.id,
+            d.subscription_id,
+            d.event_id,
+            d.event_type,
+            d.conversation_id,
+            d.replay_of,
+            CASE
+                WHEN d.delivered_at IS NOT NULL THEN 'delivered'
+                WHEN d.dead_at IS NOT NULL THEN 'dead'
+                ELSE 'pending'
+            END AS status,
+            d.attempts,
+            d.last_status_code,
+            d.last_error,
+            d.next_attempt_at,
+            d.delivered_at,
+            d.dead_at,
+            d.created_at
+        FROM rustygpt.webhook_deliveries d
+        WHERE (p_subscription_id IS NULL OR d.subscription_id = p_subscription_id)
+          AND (p_delivery_id IS NULL OR d.id = p_delivery_id)
+    ) deliveries
+    WHERE p_status IS NULL OR deliveries.status = p_status
+    ORDER BY deliveries.created_at DESC, deliveries.id
+    LIMIT LEAST(GREATEST(COALESCE(p_limit, 100), 1), 1000);
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_delivery_attempts(
+    p_delivery_id UUID
+)
+RETURNS TABLE (
+    attempted_at TIMESTAMPTZ,
+    status_code INTEGER,
+    error TEXT,
+    duration_ms INTEGER
+)
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT a.attempted_at, a.status_code, a.error, a.duration_ms
+    FROM rustygpt.webhook_delivery_attempts a
+    WHERE a.delivery_id = p_delivery_id
+    ORDER BY a.attempted_at, a.id;
+$$;
+
+-- Queues a fresh copy of a delivery, whatever its state, with the same event id and body.
+CREATE OR REPLACE FUNCTION rustygpt.sp_webhook_replay(
+    p_delivery_id UUID
+)
+RETURNS UUID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_id UUID;
+    v_subscription UUID;
+BEGIN
+    INSERT INTO rustygpt.webhook_deliveries (
+        subscription_id, event_id, event_type, conversation_id, payload, replay_of
+    )
+    SELECT d.subscription_id, d.event_id, d.event_type, d.conversation_id, d.payload, d.id
+    FROM rustygpt.webhook_deliveries d
+    WHERE d.id = p_delivery_id
+    RETURNING id, subscription_id INTO v_id, v_subscription;
+
+    IF v_id IS NULL THEN
+        RAISE EXCEPTION USING ERRCODE = 'P0001', MESSAGE = 'RGP.404: delivery not found';
+    END IF;
+
+    PERFORM rustygpt.sp_audit_record(
+        'webhook.replay',
+        'webhook_subscription',
+        v_subscription::TEXT,
+        jsonb_build_object('delivery_id', p_delivery_id, 'replay_id', v_id)
+    );
+    RETURN v_id;
+END;
+$$;
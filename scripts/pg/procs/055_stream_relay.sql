@@ -0,0 +1,22 @@
+-- Stored procedures: cross-instance stream relay
+SET search_path TO rustygpt, public;
+
+-- Payload of a logged stream event, used when a `NOTIFY` only carried a pointer to it.
+-- The latest row wins if the event id was ever written twice.
+CREATE OR REPLACE FUNCTION rustygpt.sp_sse_event_payload(
+    p_conversation_id UUID,
+    p_event_id TEXT
+)
+RETURNS JSONB
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT payload
+    FROM rustygpt.sse_event_log
+    WHERE conversation_id = p_conversation_id
+      AND event_id = p_event_id
+    ORDER BY id DESC
+    LIMIT 1;
+$$;
@@ -0,0 +1,20 @@
+-- Stored procedures: `@username` mentions
+SET search_path TO rustygpt, public;
+
+-- Users named in `p_usernames` who can read the conversation. Unknown names and users
+-- outside the conversation are dropped silently.
+CREATE OR REPLACE FUNCTION rustygpt.sp_conversation_mentions(
+    p_conversation_id UUID,
+    p_usernames TEXT[]
+)
+RETURNS SETOF UUID
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT u.id
+    FROM rustygpt.users u
+    WHERE u.username = ANY (p_usernames::citext[])
+      AND rustygpt.sp_user_can_access(u.id, p_conversation_id);
+$$;
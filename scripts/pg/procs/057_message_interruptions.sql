@@ -0,0 +1,41 @@
+-- Stored procedure: mark an assistant message as interrupted by shutdown
+SET search_path TO rustygpt, public;
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_mark_message_interrupted(
+    p_message UUID
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_msg RECORD;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    SELECT
+        m.id,
+        m.conversation_id
+    INTO v_msg
+    FROM rustygpt.messages m
+    WHERE m.id = p_message;
+
+    IF v_msg IS NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: message not found';
+    END IF;
+
+    IF NOT rustygpt.sp_user_can_access(v_actor, v_msg.conversation_id) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for conversation';
+    END IF;
+
+    UPDATE rustygpt.messages
+    SET interrupted_at = COALESCE(interrupted_at, NOW())
+    WHERE id = p_message;
+END;
+$$;
@@ -0,0 +1,107 @@
+-- Stored procedures: generation lifecycle of assistant replies
+SET search_path TO rustygpt, public;
+
+-- Superseded by sp_set_message_generation.
+DROP FUNCTION IF EXISTS rustygpt.sp_mark_message_interrupted(UUID);
+
+CREATE OR REPLACE FUNCTION rustygpt.sp_set_message_generation(
+    p_message UUID,
+    p_status rustygpt.generation_status,
+    p_finish_reason TEXT
+)
+RETURNS VOID
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_actor UUID;
+    v_msg RECORD;
+BEGIN
+    v_actor := rustygpt.sp_require_session_user();
+
+    SELECT
+        m.id,
+        m.conversation_id
+    INTO v_msg
+    FROM rustygpt.messages m
+    WHERE m.id = p_message;
+
+    IF v_msg IS NULL THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.404: message not found';
+    END IF;
+
+    IF NOT rustygpt.sp_user_can_access(v_actor, v_msg.conversation_id) THEN
+        RAISE EXCEPTION USING
+            ERRCODE = 'P0001',
+            MESSAGE = 'RGP.403: actor not authorized for conversation';
+    END IF;
+
+    UPDATE rustygpt.messages m
+    SET generation_status = p_status,
+        finish_reason = p_finish_reason,
+        interrupted_at = CASE
+            WHEN p_status = 'interrupted' THEN COALESCE(m.interrupted_at, NOW())
+            ELSE m.interrupted_at
+        END,
+        updated_at = NOW()
+    WHERE m.id = p_message;
+END;
+$$;
+
+-- Replies still marked `streaming` with no activity since `p_stale_before` belong to a
+-- generation whose server is gone. Their content is rebuilt from the persisted chunks when
+-- those got further than the last content update, and they are marked interrupted.
+-- Runs without a session user: it is called by the server at startup.
+CREATE OR REPLACE FUNCTION rustygpt.sp_recover_orphaned_generations(
+    p_stale_before TIMESTAMPTZ
+)
+RETURNS TABLE (
+    message_id UUID,
+    conversation_id UUID,
+    root_id UUID,
+    next_chunk_idx INT
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+DECLARE
+    v_row RECORD;
+    v_chunks TEXT;
+    v_next INT;
+BEGIN
+    FOR v_row IN
+        SELECT m.id, m.conversation_id, m.root_message_id
+        FROM rustygpt.messages m
+        WHERE m.generation_status = 'streaming'
+          AND COALESCE(m.updated_at, m.created_at) < p_stale_before
+        ORDER BY m.created_at
+        FOR UPDATE OF m SKIP LOCKED
+    LOOP
+        SELECT string_agg(ch.content, '' ORDER BY ch.idx), COALESCE(MAX(ch.idx) + 1, 0)
+        INTO v_chunks, v_next
+        FROM rustygpt.message_chunks ch
+        WHERE ch.message_id = v_row.id;
+
+        UPDATE rustygpt.messages m
+        SET content = CASE
+                WHEN length(COALESCE(v_chunks, '')) > length(m.content) THEN v_chunks
+                ELSE m.content
+            END,
+            generation_status = 'interrupted',
+            finish_reason = 'interrupted',
+            interrupted_at = COALESCE(m.interrupted_at, NOW()),
+            updated_at = NOW()
+        WHERE m.id = v_row.id;
+
+        message_id := v_row.id;
+        conversation_id := v_row.conversation_id;
+        root_id := v_row.root_message_id;
+        next_chunk_idx := v_next;
+        RETURN NEXT;
+    END LOOP;
+END;
+$$;
@@ -0,0 +1,64 @@
+-- Message revision history
+SET search_path TO rustygpt, public;
+
+CREATE TABLE IF NOT EXISTS rustygpt.message_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    message_id UUID NOT NULL REFERENCES rustygpt.messages(id) ON DELETE CASCADE,
+    conversation_id UUID NOT NULL REFERENCES rustygpt.conversations(id) ON DELETE CASCADE,
+    revision INT NOT NULL,
+    content TEXT NOT NULL,
+    edited_by UUID,
+    edit_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (message_id, revision)
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_revisions_message
+    ON rustygpt.message_revisions (message_id, revision);
+
+ALTER TABLE rustygpt.message_revisions ENABLE ROW LEVEL SECURITY;
+
+-- Revisions are visible exactly when their message is: the subquery runs under the policies on
+-- `messages`, so participant, group and organization rules all carry over.
+DROP POLICY IF EXISTS message_revisions_participant_access ON rustygpt.message_revisions;
+
+DO $policy$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'message_revisions'
+          AND policyname = 'message_revisions_message_access'
+    ) THEN
+        CREATE POLICY message_revisions_message_access ON rustygpt.message_revisions
+            USING (
+                EXISTS (
+                    SELECT 1
+                    FROM rustygpt.messages m
+                    WHERE m.id = message_revisions.message_id
+                )
+            );
+    END IF;
+END;
+$policy$;
+
+-- Allow persisted `message.edited` stream events ------------------------------
+
+ALTER TABLE rustygpt.sse_event_log
+    DROP CONSTRAINT IF EXISTS sse_event_log_event_type_check;
+
+ALTER TABLE rustygpt.sse_event_log
+    ADD CONSTRAINT sse_event_log_event_type_check CHECK (
+        event_type IN (
+            'presence.update',
+            'typing.update',
+            'unread.update',
+            'membership.changed',
+            'thread.new',
+            'thread.activity',
+            'message.delta',
+            'message.done',
+            'message.edited',
+            'error'
+        )
+    );
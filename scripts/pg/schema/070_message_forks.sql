@@ -0,0 +1,10 @@
+-- Fork provenance for copied messages
+SET search_path TO rustygpt, public;
+
+ALTER TABLE rustygpt.messages
+    ADD COLUMN IF NOT EXISTS forked_from_message_id UUID
+        REFERENCES rustygpt.messages(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_messages_forked_from
+    ON rustygpt.messages (forked_from_message_id)
+    WHERE forked_from_message_id IS NOT NULL;
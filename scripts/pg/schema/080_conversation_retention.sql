@@ -0,0 +1,70 @@
+-- Conversation retention policies
+SET search_path TO rustygpt, public;
+
+CREATE TABLE IF NOT EXISTS rustygpt.retention_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    conversation_id UUID UNIQUE REFERENCES rustygpt.conversations(id) ON DELETE CASCADE,
+    archive_after_days INT CHECK (archive_after_days IS NULL OR archive_after_days > 0),
+    purge_deleted_after_days INT CHECK (purge_deleted_after_days IS NULL OR purge_deleted_after_days > 0),
+    updated_by UUID,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- A NULL conversation_id row is the global default; allow at most one.
+CREATE UNIQUE INDEX IF NOT EXISTS ux_retention_policies_global
+    ON rustygpt.retention_policies ((conversation_id IS NULL))
+    WHERE conversation_id IS NULL;
+
+ALTER TABLE rustygpt.messages
+    ADD COLUMN IF NOT EXISTS purged_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_messages_deleted_unpurged
+    ON rustygpt.messages (conversation_id, deleted_at)
+    WHERE deleted_at IS NOT NULL AND purged_at IS NULL;
+
+ALTER TABLE rustygpt.retention_policies ENABLE ROW LEVEL SECURITY;
+
+DO $policy$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'retention_policies'
+          AND policyname = 'retention_policies_participant_access'
+    ) THEN
+        CREATE POLICY retention_policies_participant_access ON rustygpt.retention_policies
+            USING (
+                conversation_id IS NULL
+                OR EXISTS (
+                    SELECT 1
+                    FROM rustygpt.conversation_participants cp
+                    WHERE cp.conversation_id = retention_policies.conversation_id
+                      AND cp.user_id = NULLIF(current_setting('app.current_user_id', true), '')::uuid
+                      AND cp.left_at IS NULL
+                )
+            );
+    END IF;
+END;
+$policy$;
+
+-- Allow persisted `conversation.lifecycle` stream events ---------------------
+
+ALTER TABLE rustygpt.sse_event_log
+    DROP CONSTRAINT IF EXISTS sse_event_log_event_type_check;
+
+ALTER TABLE rustygpt.sse_event_log
+    ADD CONSTRAINT sse_event_log_event_type_check CHECK (
+        event_type IN (
+            'presence.update',
+            'typing.update',
+            'unread.update',
+            'membership.changed',
+            'thread.new',
+            'thread.activity',
+            'message.delta',
+            'message.done',
+            'message.edited',
+            'conversation.lifecycle',
+            'error'
+        )
+    );
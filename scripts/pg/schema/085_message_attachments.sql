@@ -0,0 +1,39 @@
+-- Attachment metadata for messages
+SET search_path TO rustygpt, public;
+
+-- Only the description of a file is kept here; the bytes live wherever `storage_key` points.
+CREATE TABLE IF NOT EXISTS rustygpt.message_attachments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    message_id UUID NOT NULL REFERENCES rustygpt.messages(id) ON DELETE CASCADE,
+    file_name TEXT NOT NULL CHECK (length(btrim(file_name)) > 0),
+    content_type TEXT NOT NULL DEFAULT 'application/octet-stream',
+    size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+    storage_key TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_attachments_message
+    ON rustygpt.message_attachments (message_id, created_at);
+
+ALTER TABLE rustygpt.message_attachments ENABLE ROW LEVEL SECURITY;
+
+-- Attachments are visible exactly when their message is, as with revisions.
+DO $policy$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'message_attachments'
+          AND policyname = 'message_attachments_message_access'
+    ) THEN
+        CREATE POLICY message_attachments_message_access ON rustygpt.message_attachments
+            USING (
+                EXISTS (
+                    SELECT 1
+                    FROM rustygpt.messages m
+                    WHERE m.id = message_attachments.message_id
+                )
+            );
+    END IF;
+END;
+$policy$;
@@ -0,0 +1,42 @@
+-- Personal access tokens for non-browser clients
+SET search_path TO rustygpt, public;
+
+CREATE TABLE IF NOT EXISTS rustygpt.api_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL CHECK (btrim(name) <> ''),
+    -- Public lookup handle embedded in the token; the secret part is only stored hashed.
+    prefix TEXT NOT NULL,
+    token_hash BYTEA NOT NULL,
+    scopes TEXT[] NOT NULL CHECK (
+        cardinality(scopes) > 0
+        AND scopes <@ ARRAY['v1:chat', 'threads:write', 'admin:*']::TEXT[]
+    ),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    last_used_ip TEXT,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_api_tokens_prefix
+    ON rustygpt.api_tokens (prefix);
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_user
+    ON rustygpt.api_tokens (user_id, created_at DESC);
+
+ALTER TABLE rustygpt.api_tokens ENABLE ROW LEVEL SECURITY;
+
+DO $policy$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'api_tokens'
+          AND policyname = 'api_tokens_owner_access'
+    ) THEN
+        CREATE POLICY api_tokens_owner_access ON rustygpt.api_tokens
+            USING (user_id = NULLIF(current_setting('app.current_user_id', true), '')::uuid);
+    END IF;
+END;
+$policy$;
@@ -0,0 +1,40 @@
+-- Token usage ledger: one row per assistant generation
+SET search_path TO rustygpt, public;
+
+CREATE TABLE IF NOT EXISTS rustygpt.usage_ledger (
+    id BIGSERIAL PRIMARY KEY,
+    -- NULL for anonymous /v1 calls; kept when users or conversations are deleted so totals stay stable.
+    user_id UUID REFERENCES rustygpt.users(id) ON DELETE SET NULL,
+    conversation_id UUID REFERENCES rustygpt.conversations(id) ON DELETE SET NULL,
+    message_id UUID REFERENCES rustygpt.messages(id) ON DELETE SET NULL,
+    source TEXT NOT NULL CHECK (source IN ('thread', 'v1')),
+    model TEXT NOT NULL CHECK (btrim(model) <> ''),
+    prompt_tokens BIGINT NOT NULL CHECK (prompt_tokens >= 0),
+    completion_tokens BIGINT NOT NULL CHECK (completion_tokens >= 0),
+    total_tokens BIGINT NOT NULL CHECK (total_tokens >= 0),
+    latency_ms BIGINT NOT NULL CHECK (latency_ms >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_ledger_created
+    ON rustygpt.usage_ledger (created_at);
+
+CREATE INDEX IF NOT EXISTS idx_usage_ledger_user_created
+    ON rustygpt.usage_ledger (user_id, created_at);
+
+ALTER TABLE rustygpt.usage_ledger ENABLE ROW LEVEL SECURITY;
+
+DO $policy$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'usage_ledger'
+          AND policyname = 'usage_ledger_owner_read'
+    ) THEN
+        CREATE POLICY usage_ledger_owner_read ON rustygpt.usage_ledger
+            FOR SELECT
+            USING (user_id = NULLIF(current_setting('app.current_user_id', true), '')::uuid);
+    END IF;
+END;
+$policy$;
@@ -0,0 +1,34 @@
+-- Token budgets enforced against the usage ledger
+SET search_path TO rustygpt, public;
+
+CREATE TABLE IF NOT EXISTS rustygpt.token_quotas (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Exactly one target: a single user, everyone holding a global role, or every
+    -- request admitted by a rate-limit profile.
+    target_kind TEXT NOT NULL CHECK (target_kind IN ('user', 'role', 'profile')),
+    user_id UUID REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    role rustygpt.user_role,
+    profile_id UUID REFERENCES rustygpt.rate_limit_profiles(id) ON DELETE CASCADE,
+    -- NULL leaves that period unlimited.
+    daily_tokens BIGINT CHECK (daily_tokens IS NULL OR daily_tokens >= 0),
+    monthly_tokens BIGINT CHECK (monthly_tokens IS NULL OR monthly_tokens >= 0),
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((target_kind = 'user') = (user_id IS NOT NULL)),
+    CHECK ((target_kind = 'role') = (role IS NOT NULL)),
+    CHECK ((target_kind = 'profile') = (profile_id IS NOT NULL)),
+    CHECK (daily_tokens IS NOT NULL OR monthly_tokens IS NOT NULL)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_token_quotas_user
+    ON rustygpt.token_quotas (user_id)
+    WHERE target_kind = 'user';
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_token_quotas_role
+    ON rustygpt.token_quotas (role)
+    WHERE target_kind = 'role';
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_token_quotas_profile
+    ON rustygpt.token_quotas (profile_id)
+    WHERE target_kind = 'profile';
@@ -0,0 +1,48 @@
+-- Append-only audit trail for security-relevant changes
+SET search_path TO rustygpt, public;
+
+CREATE TABLE IF NOT EXISTS rustygpt.audit_events (
+    id BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- No foreign key: events must outlive the accounts they mention.
+    actor_id UUID,
+    action TEXT NOT NULL CHECK (action ~ '^[a-z_]+(\.[a-z_]+)+$'),
+    target_type TEXT NOT NULL CHECK (btrim(target_type) <> ''),
+    target_id TEXT,
+    ip TEXT,
+    user_agent TEXT,
+    request_id TEXT,
+    metadata JSONB NOT NULL DEFAULT '{}'::JSONB
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_occurred
+    ON rustygpt.audit_events (occurred_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_actor
+    ON rustygpt.audit_events (actor_id, id DESC);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_target
+    ON rustygpt.audit_events (target_type, target_id, id DESC);
+
+CREATE OR REPLACE FUNCTION rustygpt.tg_audit_events_immutable()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+AS $$
+BEGIN
+    RAISE EXCEPTION USING
+        ERRCODE = 'P0001',
+        MESSAGE = 'RGP.403: audit events are append-only';
+END;
+$$;
+
+DROP TRIGGER IF EXISTS audit_events_immutable ON rustygpt.audit_events;
+CREATE TRIGGER audit_events_immutable
+    BEFORE UPDATE OR DELETE ON rustygpt.audit_events
+    FOR EACH ROW EXECUTE FUNCTION rustygpt.tg_audit_events_immutable();
+
+DROP TRIGGER IF EXISTS audit_events_no_truncate ON rustygpt.audit_events;
+CREATE TRIGGER audit_events_no_truncate
+    BEFORE TRUNCATE ON rustygpt.audit_events
+    FOR EACH STATEMENT EXECUTE FUNCTION rustygpt.tg_audit_events_immutable();
+
+ALTER TABLE rustygpt.audit_events ENABLE ROW LEVEL SECURITY;
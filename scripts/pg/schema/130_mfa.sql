@@ -0,0 +1,44 @@
+-- Two-factor authentication: TOTP secrets, recovery codes, login challenges and role policy
+SET search_path TO rustygpt, public;
+
+CREATE TABLE IF NOT EXISTS rustygpt.user_totp (
+    user_id UUID PRIMARY KEY REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    secret BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- NULL while the enrollment waits for its first code.
+    enabled_at TIMESTAMPTZ,
+    -- Highest time step accepted so far; codes at or below it are replays.
+    last_used_step BIGINT
+);
+
+CREATE TABLE IF NOT EXISTS rustygpt.user_recovery_codes (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    code_hash BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    used_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_user_recovery_codes_hash
+    ON rustygpt.user_recovery_codes (user_id, code_hash);
+
+-- Issued after a correct password when a second factor is still needed.
+CREATE TABLE IF NOT EXISTS rustygpt.mfa_login_challenges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    token_hash BYTEA NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    consumed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_mfa_login_challenges_user
+    ON rustygpt.mfa_login_challenges (user_id, expires_at);
+
+-- Holders of these global roles must sign in with a second factor.
+CREATE TABLE IF NOT EXISTS rustygpt.mfa_role_requirements (
+    role rustygpt.user_role PRIMARY KEY,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_by UUID REFERENCES rustygpt.users(id) ON DELETE SET NULL
+);
@@ -0,0 +1,52 @@
+-- Outgoing mail queue plus password reset and email verification tokens
+SET search_path TO rustygpt, public;
+
+ALTER TABLE rustygpt.users
+    ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ;
+
+-- Messages are rendered before they are queued so retries send exactly what was enqueued.
+-- `body_text` is emptied once a message is sent or given up on, since it holds one-time links.
+CREATE TABLE IF NOT EXISTS rustygpt.mail_outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    template TEXT NOT NULL CHECK (btrim(template) <> ''),
+    to_address TEXT NOT NULL CHECK (btrim(to_address) <> ''),
+    subject TEXT NOT NULL,
+    body_text TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    sent_at TIMESTAMPTZ,
+    -- Set once the retry budget is spent; the row is kept for inspection.
+    failed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_mail_outbox_pending
+    ON rustygpt.mail_outbox (next_attempt_at)
+    WHERE sent_at IS NULL AND failed_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS rustygpt.password_reset_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    token_hash BYTEA NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_user
+    ON rustygpt.password_reset_tokens (user_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS rustygpt.email_verification_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    -- The address being verified; a later email change invalidates the token.
+    email CITEXT NOT NULL,
+    token_hash BYTEA NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_verification_tokens_user
+    ON rustygpt.email_verification_tokens (user_id, created_at DESC);
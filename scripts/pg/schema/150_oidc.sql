@@ -0,0 +1,31 @@
+-- External OpenID Connect identities and in-flight authorization requests
+SET search_path TO rustygpt, public;
+
+-- One row per (provider, subject); a user may have several linked identities.
+CREATE TABLE IF NOT EXISTS rustygpt.user_identities (
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    user_id UUID NOT NULL REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    -- Email last asserted by the provider, for display only; matching uses the subject.
+    email CITEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_login_at TIMESTAMPTZ,
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user
+    ON rustygpt.user_identities (user_id);
+
+-- State, nonce and PKCE verifier for a login between the redirect and the callback.
+CREATE TABLE IF NOT EXISTS rustygpt.oidc_login_requests (
+    state_hash BYTEA PRIMARY KEY,
+    provider TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    pkce_verifier TEXT NOT NULL,
+    return_to TEXT NOT NULL DEFAULT '/',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_oidc_login_requests_expires
+    ON rustygpt.oidc_login_requests (expires_at);
@@ -0,0 +1,8 @@
+-- Per-account failed login tracking for lockout and backoff
+SET search_path TO rustygpt, public;
+
+ALTER TABLE rustygpt.users
+    ADD COLUMN IF NOT EXISTS failed_login_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS last_failed_login_at TIMESTAMPTZ,
+    -- Password logins are refused until this passes; NULL when the account is not locked.
+    ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;
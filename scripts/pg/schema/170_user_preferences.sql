@@ -0,0 +1,10 @@
+-- Per-user settings (default model, system prompt, UI language and theme, notifications)
+SET search_path TO rustygpt, public;
+
+CREATE TABLE IF NOT EXISTS rustygpt.user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    -- Validated and normalized by the server before it is written; absent keys take defaults.
+    preferences JSONB NOT NULL DEFAULT '{}'::JSONB,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT user_preferences_is_object CHECK (jsonb_typeof(preferences) = 'object')
+);
@@ -0,0 +1,198 @@
+-- Organizations: tenants that own conversations and carry their own limits, models and features
+SET search_path TO rustygpt, public;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_type typ
+        JOIN pg_namespace nsp ON nsp.oid = typ.typnamespace
+        WHERE typ.typname = 'org_role'
+          AND nsp.nspname = 'rustygpt'
+    ) THEN
+        CREATE TYPE rustygpt.org_role AS ENUM ('owner', 'admin', 'member');
+    END IF;
+END;
+$$;
+
+CREATE TABLE IF NOT EXISTS rustygpt.organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    slug CITEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    -- Replaces the `conversation.post` profile for messages in this organization's conversations.
+    rate_limit_profile_id UUID REFERENCES rustygpt.rate_limit_profiles(id) ON DELETE SET NULL,
+    -- NULL allows every model the server offers.
+    allowed_models TEXT[],
+    features JSONB NOT NULL DEFAULT '{}'::JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (btrim(name) <> ''),
+    CHECK (slug ~ '^[a-z0-9][a-z0-9-]{0,62}$'),
+    CHECK (jsonb_typeof(features) = 'object')
+);
+
+CREATE TABLE IF NOT EXISTS rustygpt.organization_members (
+    org_id UUID NOT NULL REFERENCES rustygpt.organizations(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    role rustygpt.org_role NOT NULL DEFAULT 'member',
+    added_by UUID REFERENCES rustygpt.users(id) ON DELETE SET NULL,
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (org_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_organization_members_user
+    ON rustygpt.organization_members (user_id);
+
+-- Conversations without an organization predate tenancy or were created outside one; they
+-- stay visible to their participants only.
+ALTER TABLE rustygpt.conversations
+    ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES rustygpt.organizations(id) ON DELETE RESTRICT;
+
+CREATE INDEX IF NOT EXISTS idx_conversations_org
+    ON rustygpt.conversations (org_id)
+    WHERE org_id IS NOT NULL;
+
+-- Organization-wide token budgets, counted over usage in the organization's conversations.
+ALTER TABLE rustygpt.token_quotas
+    ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES rustygpt.organizations(id) ON DELETE CASCADE;
+
+ALTER TABLE rustygpt.token_quotas
+    DROP CONSTRAINT IF EXISTS token_quotas_target_kind_check;
+
+ALTER TABLE rustygpt.token_quotas
+    ADD CONSTRAINT token_quotas_target_kind_check
+    CHECK (target_kind IN ('user', 'role', 'profile', 'org'));
+
+ALTER TABLE rustygpt.token_quotas
+    DROP CONSTRAINT IF EXISTS token_quotas_org_target_check;
+
+ALTER TABLE rustygpt.token_quotas
+    ADD CONSTRAINT token_quotas_org_target_check
+    CHECK ((target_kind = 'org') = (org_id IS NOT NULL));
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_token_quotas_org
+    ON rustygpt.token_quotas (org_id)
+    WHERE target_kind = 'org';
+
+-- Membership test used by the policies below. SECURITY DEFINER so the lookup is not itself
+-- filtered by organization_members' policy.
+CREATE OR REPLACE FUNCTION rustygpt.current_user_in_org(p_org_id UUID)
+RETURNS BOOLEAN
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT p_org_id IS NULL OR EXISTS (
+        SELECT 1
+        FROM rustygpt.organization_members m
+        WHERE m.org_id = p_org_id
+          AND m.user_id = NULLIF(current_setting('app.current_user_id', true), '')::uuid
+    );
+$$;
+
+CREATE OR REPLACE FUNCTION rustygpt.current_user_in_conversation_org(p_conversation_id UUID)
+RETURNS BOOLEAN
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT COALESCE(
+        (
+            SELECT rustygpt.current_user_in_org(c.org_id)
+            FROM rustygpt.conversations c
+            WHERE c.id = p_conversation_id
+        ),
+        TRUE
+    );
+$$;
+
+-- Row Level Security -------------------------------------------------------
+-- The participant policies stay permissive; these restrictive policies add the organization
+-- requirement on top, so a participant who leaves the organization loses access too.
+
+ALTER TABLE rustygpt.organizations ENABLE ROW LEVEL SECURITY;
+ALTER TABLE rustygpt.organization_members ENABLE ROW LEVEL SECURITY;
+
+DO $policy$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'organizations'
+          AND policyname = 'organizations_member_access'
+    ) THEN
+        CREATE POLICY organizations_member_access ON rustygpt.organizations
+            USING (rustygpt.current_user_in_org(id));
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'organization_members'
+          AND policyname = 'organization_members_member_access'
+    ) THEN
+        CREATE POLICY organization_members_member_access ON rustygpt.organization_members
+            USING (rustygpt.current_user_in_org(org_id));
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'conversations'
+          AND policyname = 'conversations_org_member'
+    ) THEN
+        CREATE POLICY conversations_org_member ON rustygpt.conversations
+            AS RESTRICTIVE
+            USING (rustygpt.current_user_in_org(org_id));
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'conversation_participants'
+          AND policyname = 'conversation_participants_org_member'
+    ) THEN
+        CREATE POLICY conversation_participants_org_member ON rustygpt.conversation_participants
+            AS RESTRICTIVE
+            USING (rustygpt.current_user_in_conversation_org(conversation_id));
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'messages'
+          AND policyname = 'messages_org_member'
+    ) THEN
+        CREATE POLICY messages_org_member ON rustygpt.messages
+            AS RESTRICTIVE
+            USING (rustygpt.current_user_in_conversation_org(conversation_id));
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'message_chunks'
+          AND policyname = 'message_chunks_org_member'
+    ) THEN
+        CREATE POLICY message_chunks_org_member ON rustygpt.message_chunks
+            AS RESTRICTIVE
+            USING (
+                rustygpt.current_user_in_conversation_org(
+                    (SELECT m.conversation_id FROM rustygpt.messages m WHERE m.id = message_id)
+                )
+            );
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'conversation_invites'
+          AND policyname = 'conversation_invites_org_member'
+    ) THEN
+        CREATE POLICY conversation_invites_org_member ON rustygpt.conversation_invites
+            AS RESTRICTIVE
+            USING (rustygpt.current_user_in_conversation_org(conversation_id));
+    END IF;
+END;
+$policy$;
@@ -0,0 +1,162 @@
+-- User groups: named sets of users that can be granted a role on a conversation as a unit
+SET search_path TO rustygpt, public;
+
+CREATE TABLE IF NOT EXISTS rustygpt.user_groups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name CITEXT NOT NULL UNIQUE,
+    description TEXT,
+    created_by UUID REFERENCES rustygpt.users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (btrim(name::TEXT) <> '')
+);
+
+CREATE TABLE IF NOT EXISTS rustygpt.user_group_members (
+    group_id UUID NOT NULL REFERENCES rustygpt.user_groups(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES rustygpt.users(id) ON DELETE CASCADE,
+    -- Managers edit the group's membership; the creator starts as one.
+    is_manager BOOLEAN NOT NULL DEFAULT FALSE,
+    added_by UUID REFERENCES rustygpt.users(id) ON DELETE SET NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (group_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_group_members_user
+    ON rustygpt.user_group_members (user_id);
+
+-- Every current member of the group holds `role` on the conversation, alongside direct
+-- participants. A user reached both ways holds the higher of the two roles.
+CREATE TABLE IF NOT EXISTS rustygpt.conversation_group_grants (
+    conversation_id UUID NOT NULL REFERENCES rustygpt.conversations(id) ON DELETE CASCADE,
+    group_id UUID NOT NULL REFERENCES rustygpt.user_groups(id) ON DELETE CASCADE,
+    role rustygpt.conversation_role NOT NULL DEFAULT 'member',
+    granted_by UUID REFERENCES rustygpt.users(id) ON DELETE SET NULL,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (conversation_id, group_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_conversation_group_grants_group
+    ON rustygpt.conversation_group_grants (group_id);
+
+-- Access through a group, used by the policies below. SECURITY DEFINER so the lookup is not
+-- itself filtered by the grant and membership tables' policies.
+CREATE OR REPLACE FUNCTION rustygpt.current_user_in_conversation_group(p_conversation_id UUID)
+RETURNS BOOLEAN
+LANGUAGE sql
+STABLE
+SECURITY DEFINER
+SET search_path = rustygpt, public
+AS $$
+    SELECT EXISTS (
+        SELECT 1
+        FROM rustygpt.conversation_group_grants g
+        JOIN rustygpt.user_group_members m ON m.group_id = g.group_id
+        WHERE g.conversation_id = p_conversation_id
+          AND m.user_id = NULLIF(current_setting('app.current_user_id', true), '')::uuid
+    );
+$$;
+
+-- Row Level Security -------------------------------------------------------
+-- Permissive policies, so a group grant opens the same rows a direct participation does. The
+-- organization policies stay restrictive and still apply.
+
+ALTER TABLE rustygpt.user_groups ENABLE ROW LEVEL SECURITY;
+ALTER TABLE rustygpt.user_group_members ENABLE ROW LEVEL SECURITY;
+ALTER TABLE rustygpt.conversation_group_grants ENABLE ROW LEVEL SECURITY;
+
+DO $policy$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'user_groups'
+          AND policyname = 'user_groups_member_access'
+    ) THEN
+        CREATE POLICY user_groups_member_access ON rustygpt.user_groups
+            USING (
+                EXISTS (
+                    SELECT 1
+                    FROM rustygpt.user_group_members m
+                    WHERE m.group_id = user_groups.id
+                      AND m.user_id = NULLIF(current_setting('app.current_user_id', true), '')::uuid
+                )
+            );
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'user_group_members'
+          AND policyname = 'user_group_members_member_access'
+    ) THEN
+        CREATE POLICY user_group_members_member_access ON rustygpt.user_group_members
+            USING (
+                user_id = NULLIF(current_setting('app.current_user_id', true), '')::uuid
+            );
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'conversation_group_grants'
+          AND policyname = 'conversation_group_grants_participant_access'
+    ) THEN
+        CREATE POLICY conversation_group_grants_participant_access
+            ON rustygpt.conversation_group_grants
+            USING (
+                EXISTS (
+                    SELECT 1
+                    FROM rustygpt.conversation_participants cp
+                    WHERE cp.conversation_id = conversation_group_grants.conversation_id
+                      AND cp.user_id = NULLIF(current_setting('app.current_user_id', true), '')::uuid
+                      AND cp.left_at IS NULL
+                )
+                OR rustygpt.current_user_in_conversation_group(conversation_id)
+            );
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'conversations'
+          AND policyname = 'conversations_group_access'
+    ) THEN
+        CREATE POLICY conversations_group_access ON rustygpt.conversations
+            USING (rustygpt.current_user_in_conversation_group(id));
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'conversation_participants'
+          AND policyname = 'conversation_participants_group_access'
+    ) THEN
+        CREATE POLICY conversation_participants_group_access ON rustygpt.conversation_participants
+            USING (rustygpt.current_user_in_conversation_group(conversation_id));
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'messages'
+          AND policyname = 'messages_group_access'
+    ) THEN
+        CREATE POLICY messages_group_access ON rustygpt.messages
+            USING (rustygpt.current_user_in_conversation_group(conversation_id));
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_policies
+        WHERE schemaname = 'rustygpt'
+          AND tablename = 'message_chunks'
+          AND policyname = 'message_chunks_group_access'
+    ) THEN
+        CREATE POLICY message_chunks_group_access ON rustygpt.message_chunks
+            USING (
+                rustygpt.current_user_in_conversation_group(
+                    (SELECT m.conversation_id FROM rustygpt.messages m WHERE m.id = message_id)
+                )
+            );
+    END IF;
+END;
+$policy$;
@@ -0,0 +1,70 @@
+-- Outgoing webhooks: subscriptions to conversation events, their delivery outbox and attempt log
+SET search_path TO rustygpt, public;
+
+-- A subscription follows exactly one conversation or every conversation of one organization.
+CREATE TABLE IF NOT EXISTS rustygpt.webhook_subscriptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    url TEXT NOT NULL CHECK (url ~* '^https?://'),
+    description TEXT,
+    -- Shared with the receiver to verify the `X-RustyGPT-Signature` header.
+    secret TEXT NOT NULL CHECK (length(secret) >= 16),
+    conversation_id UUID REFERENCES rustygpt.conversations(id) ON DELETE CASCADE,
+    org_id UUID REFERENCES rustygpt.organizations(id) ON DELETE CASCADE,
+    -- Stream event names such as `message.done`; empty means every deliverable event.
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES rustygpt.users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (num_nonnulls(conversation_id, org_id) = 1)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_conversation
+    ON rustygpt.webhook_subscriptions (conversation_id)
+    WHERE conversation_id IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_org
+    ON rustygpt.webhook_subscriptions (org_id)
+    WHERE org_id IS NOT NULL;
+
+-- One row per event and subscription. The body is fixed at enqueue time so retries and
+-- replays send the same document; `event_id` is shared by every delivery of one event.
+CREATE TABLE IF NOT EXISTS rustygpt.webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    subscription_id UUID NOT NULL
+        REFERENCES rustygpt.webhook_subscriptions(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL,
+    event_type TEXT NOT NULL,
+    conversation_id UUID NOT NULL,
+    payload JSONB NOT NULL,
+    -- Set on deliveries created by a replay.
+    replay_of UUID REFERENCES rustygpt.webhook_deliveries(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_status_code INTEGER,
+    last_error TEXT,
+    delivered_at TIMESTAMPTZ,
+    -- Dead-lettered once the retry budget is spent; the row is kept for inspection and replay.
+    dead_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_pending
+    ON rustygpt.webhook_deliveries (next_attempt_at)
+    WHERE delivered_at IS NULL AND dead_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription
+    ON rustygpt.webhook_deliveries (subscription_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS rustygpt.webhook_delivery_attempts (
+    id BIGSERIAL PRIMARY KEY,
+    delivery_id UUID NOT NULL REFERENCES rustygpt.webhook_deliveries(id) ON DELETE CASCADE,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- NULL when no response arrived (connection failure or timeout).
+    status_code INTEGER,
+    error TEXT,
+    duration_ms INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_attempts_delivery
+    ON rustygpt.webhook_delivery_attempts (delivery_id, attempted_at);
@@ -0,0 +1,6 @@
+-- Cross-instance stream relay: oversized events travel as pointers into the SSE event log
+SET search_path TO rustygpt, public;
+
+-- Listeners resolve a relayed pointer by conversation and event id.
+CREATE INDEX IF NOT EXISTS idx_sse_event_log_event_id
+    ON rustygpt.sse_event_log (conversation_id, event_id);
@@ -0,0 +1,25 @@
+-- `@username` mentions, delivered on conversation streams and the per-user stream
+SET search_path TO rustygpt, public;
+
+-- Allow persisted `message.mention` stream events ---------------------------
+
+ALTER TABLE rustygpt.sse_event_log
+    DROP CONSTRAINT IF EXISTS sse_event_log_event_type_check;
+
+ALTER TABLE rustygpt.sse_event_log
+    ADD CONSTRAINT sse_event_log_event_type_check CHECK (
+        event_type IN (
+            'presence.update',
+            'typing.update',
+            'unread.update',
+            'membership.changed',
+            'thread.new',
+            'thread.activity',
+            'message.delta',
+            'message.done',
+            'message.edited',
+            'message.mention',
+            'conversation.lifecycle',
+            'error'
+        )
+    );
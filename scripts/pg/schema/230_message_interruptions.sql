@@ -0,0 +1,5 @@
+-- Assistant messages cut off by a server shutdown before their generation finished
+SET search_path TO rustygpt, public;
+
+ALTER TABLE rustygpt.messages
+    ADD COLUMN IF NOT EXISTS interrupted_at TIMESTAMPTZ;
@@ -0,0 +1,32 @@
+-- Generation lifecycle for assistant replies, so ones cut off by a crash can be found again
+SET search_path TO rustygpt, public;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_type typ
+        JOIN pg_namespace nsp ON nsp.oid = typ.typnamespace
+        WHERE typ.typname = 'generation_status'
+          AND nsp.nspname = 'rustygpt'
+    ) THEN
+        CREATE TYPE rustygpt.generation_status AS ENUM ('streaming', 'complete', 'interrupted', 'failed');
+    END IF;
+END;
+$$;
+
+-- `updated_at` moves with every streamed chunk, so it doubles as a reply's last sign of life.
+ALTER TABLE rustygpt.messages
+    ADD COLUMN IF NOT EXISTS generation_status rustygpt.generation_status NOT NULL DEFAULT 'complete',
+    ADD COLUMN IF NOT EXISTS finish_reason TEXT,
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ;
+
+-- Replies interrupted by a graceful shutdown before generation status existed.
+UPDATE rustygpt.messages
+SET generation_status = 'interrupted',
+    finish_reason = COALESCE(finish_reason, 'interrupted')
+WHERE interrupted_at IS NOT NULL
+  AND generation_status = 'complete';
+
+CREATE INDEX IF NOT EXISTS idx_messages_streaming
+    ON rustygpt.messages (created_at)
+    WHERE generation_status = 'streaming';